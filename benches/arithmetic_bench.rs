@@ -0,0 +1,63 @@
+#[macro_use]
+extern crate criterion;
+
+use ark_bls12_381::Fr;
+use ark_gemini::misc::{evaluate_be, evaluate_le};
+use ark_gemini::ASM_ARITHMETIC;
+use ark_std::test_rng;
+use ark_std::UniformRand;
+use criterion::{BenchmarkId, Criterion};
+
+/// Compare the hot loops of the provers with and without the assembly backend of `ark-ff`, e.g. with
+/// `cargo bench --bench arithmetic_bench` and `cargo bench --bench arithmetic_bench --no-default-features --features parallel`
+/// (with the `bmi2` and `adx` target features, as set in `.cargo/config.toml`).
+fn bench_horner(c: &mut Criterion) {
+    let rng = &mut test_rng();
+    let backend = if ASM_ARITHMETIC { "asm" } else { "portable" };
+    let mut group = c.benchmark_group(format!("horner/{}", backend));
+
+    let x = Fr::rand(rng);
+    for d in [12, 16, 20] {
+        let polynomial = (0..1 << d).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+        group
+            .sample_size(10)
+            .bench_with_input(BenchmarkId::new("single-chain", d), &d, |b, _| {
+                b.iter(|| {
+                    polynomial
+                        .iter()
+                        .fold(Fr::from(0u64), |previous, c| previous * x + c)
+                })
+            });
+        group
+            .sample_size(10)
+            .bench_with_input(BenchmarkId::new("evaluate_be", d), &d, |b, _| {
+                b.iter(|| evaluate_be(&polynomial, &x))
+            });
+        group
+            .sample_size(10)
+            .bench_with_input(BenchmarkId::new("evaluate_le", d), &d, |b, _| {
+                b.iter(|| evaluate_le(&polynomial, &x))
+            });
+    }
+}
+
+fn bench_field_ops(c: &mut Criterion) {
+    let rng = &mut test_rng();
+    let backend = if ASM_ARITHMETIC { "asm" } else { "portable" };
+    let mut group = c.benchmark_group(format!("field/{}", backend));
+
+    let first = Fr::rand(rng);
+    let second = Fr::rand(rng);
+    group.bench_function("mul", |b| b.iter(|| first * second));
+    group.bench_function("square", |b| b.iter(|| ark_ff::Field::square(&first)));
+}
+
+criterion_group! {
+    name=arithmetic_benchmarks;
+    config=Criterion::default();
+    targets=
+            bench_horner,
+            bench_field_ops,
+}
+
+criterion_main! {arithmetic_benchmarks}
@@ -37,7 +37,7 @@ fn bench_commit(c: &mut Criterion) {
                     .cloned()
                     .collect::<Vec<_>>();
                 let polynomial_stream = be_polynomial.as_slice();
-                b.iter(|| ck_stream.commit(&polynomial_stream));
+                b.iter(|| ck_stream.commit(&polynomial_stream).unwrap());
             });
     }
 }
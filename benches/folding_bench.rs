@@ -0,0 +1,49 @@
+#[macro_use]
+extern crate criterion;
+
+use ark_bls12_381::Fr;
+use ark_gemini::iterable::{Iterable, MmapIterable};
+use ark_gemini::subprotocols::sumcheck::streams::FoldedPolynomialStream;
+use ark_std::test_rng;
+use ark_std::UniformRand;
+use criterion::{BenchmarkId, Criterion};
+
+fn bench_folding(c: &mut Criterion) {
+    let mut group = c.benchmark_group("folding");
+
+    let rng = &mut test_rng();
+    let d = 20;
+    let coefficients = (0..1 << d).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let challenges = (0..d).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let path = std::env::temp_dir().join("ark-gemini-folding-bench");
+    let coefficients_stream = MmapIterable::<Fr>::create(&path, &coefficients.as_slice())
+        .expect("unable to create the stream");
+
+    for depth in [1, 6, 12, 18] {
+        for block_size in [1, 1 << 6, 1 << 12, 1 << 16] {
+            let id = format!("depth={}", depth);
+            group.sample_size(10).bench_with_input(
+                BenchmarkId::new(id, block_size),
+                &block_size,
+                |b, &block_size| {
+                    let fold_stream = FoldedPolynomialStream::with_block_size(
+                        &coefficients_stream,
+                        &challenges[..depth],
+                        block_size,
+                    );
+                    b.iter(|| fold_stream.iter().fold(Fr::from(0u64), |acc, x| acc + x));
+                },
+            );
+        }
+    }
+    let _ = std::fs::remove_file(path);
+}
+
+criterion_group! {
+    name=folding_benchmarks;
+    config=Criterion::default();
+    targets=
+            bench_folding,
+}
+
+criterion_main! {folding_benchmarks}
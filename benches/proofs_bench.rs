@@ -4,13 +4,19 @@ extern crate merlin;
 
 use criterion::{BenchmarkId, Criterion};
 
-use ark_bls12_381::Fr;
+use ark_bls12_381::{Bls12_381, Fr};
 use ark_ff::One;
 use ark_poly::univariate::DensePolynomial;
 use ark_poly::DenseUVPolynomial;
 
+use ark_gemini::circuit::{generate_relation, random_circuit};
+use ark_gemini::kzg::CommitterKey;
+use ark_gemini::snark::Proof;
 use ark_gemini::subprotocols::sumcheck::proof::Sumcheck;
 
+/// The numbers of threads for the scaling benchmarks.
+const NUM_THREADS: [usize; 6] = [1, 2, 4, 8, 16, 32];
+
 fn bench_sumcheck(c: &mut Criterion) {
     let mut group = c.benchmark_group("sumcheck");
 
@@ -22,7 +28,7 @@ fn bench_sumcheck(c: &mut Criterion) {
                 let f = DensePolynomial::<Fr>::rand(1 << d, rng).coeffs;
                 let g = DensePolynomial::<Fr>::rand(1 << d, rng).coeffs;
                 let mut transcript = merlin::Transcript::new(b"LTAPS");
-                Sumcheck::new_time(&mut transcript, &f, &g, &Fr::one());
+                Sumcheck::new_time(&mut transcript, &f, &g, &Fr::one()).unwrap();
             });
         });
 
@@ -40,17 +46,49 @@ fn bench_sumcheck(c: &mut Criterion) {
 
             b.iter(|| {
                 let mut transcript = merlin::Transcript::new(b"LTAPS");
-                Sumcheck::new_space(&mut transcript, f_stream, g_stream, Fr::one());
+                Sumcheck::new_space(&mut transcript, f_stream, g_stream, Fr::one()).unwrap();
             });
         });
     }
 }
 
+// env RUSTFLAGS="-C target_cpu=native" cargo +nightly bench --bench proofs_bench -- snark-time
+fn bench_snark_time(c: &mut Criterion) {
+    let mut group = c.benchmark_group("snark-time");
+    let rng = &mut ark_std::test_rng();
+    let d = 16;
+    let r1cs = generate_relation(random_circuit(rng, 1 << d, 1 << d));
+    let ck = CommitterKey::<Bls12_381>::new(2 << d, 5, rng);
+
+    for threads in NUM_THREADS {
+        // without the parallel feature, the prover is single-threaded.
+        if cfg!(not(feature = "parallel")) && threads > 1 {
+            break;
+        }
+        #[cfg(feature = "parallel")]
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        group.sample_size(10).bench_with_input(
+            BenchmarkId::new("threads", threads),
+            &threads,
+            |b, _| {
+                #[cfg(feature = "parallel")]
+                b.iter(|| pool.install(|| Proof::new_time(&r1cs, &ck).unwrap()));
+                #[cfg(not(feature = "parallel"))]
+                b.iter(|| Proof::new_time(&r1cs, &ck).unwrap());
+            },
+        );
+    }
+}
+
 criterion_group! {
     name=proofs_benchmarks;
     config=Criterion::default();
     targets=
         bench_sumcheck,
+        bench_snark_time,
 }
 
 criterion_main! {proofs_benchmarks}
@@ -63,7 +63,7 @@ fn elastic_snark_main(rng: &mut impl Rng, instance_logsize: usize) -> Proof {
         powers_of_g: DummyStreamer::new(g1, instance_size + 1),
         powers_of_g2: vec![g2; 4],
     };
-    Proof::new_elastic(r1cs_stream, ck, max_msm_buffer)
+    Proof::new_elastic(r1cs_stream, ck, max_msm_buffer).unwrap()
 }
 
 fn time_snark_main(rng: &mut impl Rng, instance_logsize: usize) -> Proof {
@@ -75,7 +75,7 @@ fn time_snark_main(rng: &mut impl Rng, instance_logsize: usize) -> Proof {
     let r1cs = ark_gemini::circuit::dummy_r1cs(rng, num_constraints);
     let ck = ark_gemini::kzg::CommitterKey::new(num_constraints + num_variables, 5, rng);
 
-    Proof::new_time(&r1cs, &ck)
+    Proof::new_time(&r1cs, &ck).unwrap()
 }
 
 fn main() {
@@ -0,0 +1,82 @@
+/*
+ * C interface to the Gemini preprocessing SNARK over BLS12-381.
+ *
+ * Build the library with:
+ *
+ *     cargo rustc --release --features ffi --crate-type cdylib
+ *
+ * Every function returns a gemini_status_t, and writes its results through out-pointers.
+ * Handles and buffers returned by the library are owned by the caller,
+ * and must be released with the matching _free function.
+ */
+#ifndef GEMINI_H
+#define GEMINI_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef enum {
+    GEMINI_OK = 0,
+    GEMINI_NULL_POINTER = 1,
+    GEMINI_INVALID_PATH = 2,
+    GEMINI_IO = 3,
+    GEMINI_SERIALIZATION = 4,
+    GEMINI_PROVER = 5,
+    GEMINI_INVALID_PROOF = 6,
+    GEMINI_PANIC = 7,
+} gemini_status_t;
+
+typedef struct {
+    uint8_t *data;
+    size_t len;
+} gemini_buffer_t;
+
+typedef struct GeminiProvingKey gemini_proving_key_t;
+typedef struct GeminiVerifyingKey gemini_verifying_key_t;
+
+/* Index the circuit of the circom .r1cs file at r1cs_path. */
+gemini_status_t gemini_setup(const char *r1cs_path,
+                             gemini_proving_key_t **pk,
+                             gemini_verifying_key_t **vk);
+
+/* Prove the assignment of the circom .wtns file at wtns_path. */
+gemini_status_t gemini_prove(const gemini_proving_key_t *pk,
+                             const char *r1cs_path,
+                             const char *wtns_path,
+                             gemini_buffer_t *proof);
+
+/*
+ * Verify a proof for the public input, the concatenation of num_public field elements
+ * (without the constant one), each serialized as 32 little-endian bytes.
+ * Return GEMINI_OK if the proof is valid, and GEMINI_INVALID_PROOF if it is not.
+ */
+gemini_status_t gemini_verify(const gemini_verifying_key_t *vk,
+                              const uint8_t *proof,
+                              size_t proof_len,
+                              const uint8_t *public_input,
+                              size_t num_public);
+
+gemini_status_t gemini_proving_key_serialize(const gemini_proving_key_t *pk,
+                                             gemini_buffer_t *buffer);
+gemini_status_t gemini_proving_key_deserialize(const uint8_t *data,
+                                               size_t len,
+                                               gemini_proving_key_t **pk);
+gemini_status_t gemini_verifying_key_serialize(const gemini_verifying_key_t *vk,
+                                               gemini_buffer_t *buffer);
+gemini_status_t gemini_verifying_key_deserialize(const uint8_t *data,
+                                                 size_t len,
+                                                 gemini_verifying_key_t **vk);
+
+void gemini_proving_key_free(gemini_proving_key_t *pk);
+void gemini_verifying_key_free(gemini_verifying_key_t *vk);
+void gemini_buffer_free(gemini_buffer_t buffer);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* GEMINI_H */
@@ -0,0 +1,185 @@
+//! Customizable constraint systems (CCS).
+//!
+//! A CCS instance over the matrices $M_0, \dots, M_{t-1}$,
+//! with multisets $S_0, \dots, S_{q-1}$ and constants $c_0, \dots, c_{q-1}$,
+//! is satisfied by $\vec z = (\vec x, \vec w)$ if:
+//!
+//! $$
+//! \sum_{j} c_j \cdot \bigcirc_{i \in S_j} M_i \vec z = \vec 0.
+//! $$
+//!
+//! R1CS is the special case $c_0 = 1, S_0 = \\{0, 1\\}$ and $c_1 = -1, S_1 = \\{2\\}$.
+//! The arguments in this crate prove R1CS,
+//! hence CCS instances are reduced to R1CS with [`Ccs::to_r1cs`],
+//! introducing one auxiliary variable for each partial product.
+use ark_ff::Field;
+use ark_std::collections::BTreeMap;
+use ark_std::vec::Vec;
+
+use crate::circuit::{Matrix, R1cs};
+use crate::misc::product_matrix_vector;
+
+/// A CCS instance, together with its assignment.
+#[derive(Clone)]
+pub struct Ccs<F: Field> {
+    /// The matrices $M_0, \dots, M_{t-1}$, with one row per constraint.
+    pub matrices: Vec<Matrix<F>>,
+    /// The (non-empty) multisets of matrix indices $S_0, \dots, S_{q-1}$.
+    pub multisets: Vec<Vec<usize>>,
+    /// The constants $c_0, \dots, c_{q-1}$.
+    pub constants: Vec<F>,
+    pub z: Vec<F>,
+    pub w: Vec<F>,
+    pub x: Vec<F>,
+}
+
+/// Collect the linear combination of sparse rows `rows` into a sparse row, sorted by column.
+pub(crate) fn combine_rows<F: Field>(
+    rows: impl IntoIterator<Item = (F, usize)>,
+) -> Vec<(F, usize)> {
+    let mut combined = BTreeMap::new();
+    rows.into_iter()
+        .for_each(|(v, col)| *combined.entry(col).or_insert_with(F::zero) += v);
+    combined
+        .into_iter()
+        .filter(|(_, v)| !v.is_zero())
+        .map(|(col, v)| (v, col))
+        .collect()
+}
+
+impl<F: Field> Ccs<F> {
+    /// The number of constraints.
+    pub fn num_constraints(&self) -> usize {
+        self.matrices.first().map_or(0, |m| m.len())
+    }
+
+    /// Return `true` if the assignment `z` satisfies the instance.
+    pub fn is_satisfied(&self) -> bool {
+        let products = self
+            .matrices
+            .iter()
+            .map(|m| product_matrix_vector(m, &self.z))
+            .collect::<Vec<_>>();
+        (0..self.num_constraints()).all(|row| {
+            self.multisets
+                .iter()
+                .zip(&self.constants)
+                .map(|(multiset, &c)| c * multiset.iter().map(|&i| products[i][row]).product::<F>())
+                .sum::<F>()
+                .is_zero()
+        })
+    }
+
+    /// Reduce the CCS instance to an equivalent R1CS instance.
+    ///
+    /// For each constraint and each multiset $\\{i_1, \dots, i_d\\}$ with $d \geq 2$,
+    /// the auxiliary variables $y_k = y_{k-1} \cdot (M_{i_k} \vec z)$ are appended to the witness
+    /// (with $y_1 = M_{i_1} \vec z$),
+    /// so that each term becomes linear.
+    /// The resulting linear constraint $\ell(\vec z) = 0$ is enforced as $\ell(\vec z) \cdot \ell(\vec z) = 0$.
+    ///
+    /// # Panics
+    /// If any multiset is empty.
+    pub fn to_r1cs(&self) -> R1cs<F> {
+        assert!(
+            self.multisets.iter().all(|s| !s.is_empty()),
+            "constant terms are not supported"
+        );
+        let products = self
+            .matrices
+            .iter()
+            .map(|m| product_matrix_vector(m, &self.z))
+            .collect::<Vec<_>>();
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        let mut c = Vec::new();
+        let mut aux = Vec::new();
+
+        for row in 0..self.num_constraints() {
+            let mut linear = Vec::new();
+            for (multiset, &constant) in self.multisets.iter().zip(&self.constants) {
+                let first = &self.matrices[multiset[0]][row];
+                if multiset.len() == 1 {
+                    linear.extend(first.iter().map(|&(v, col)| (constant * v, col)));
+                    continue;
+                }
+                // the first factor is a linear combination, the following ones are variables.
+                let mut previous: Vec<(F, usize)> = first.clone();
+                let mut value = products[multiset[0]][row];
+                for &i in &multiset[1..] {
+                    value *= products[i][row];
+                    let y = self.z.len() + aux.len();
+                    aux.push(value);
+                    a.push(previous);
+                    b.push(self.matrices[i][row].clone());
+                    c.push(vec![(F::one(), y)]);
+                    previous = vec![(F::one(), y)];
+                }
+                linear.extend(previous.into_iter().map(|(v, col)| (constant * v, col)));
+            }
+            let linear = combine_rows(linear);
+            a.push(linear.clone());
+            b.push(linear);
+            c.push(Vec::new());
+        }
+
+        let w = self.w.iter().chain(&aux).cloned().collect::<Vec<_>>();
+        R1cs {
+            a,
+            b,
+            c,
+            z: self.x.iter().chain(&w).cloned().collect(),
+            w,
+            x: self.x.clone(),
+        }
+    }
+}
+
+impl<F: Field> From<R1cs<F>> for Ccs<F> {
+    fn from(r1cs: R1cs<F>) -> Self {
+        Ccs {
+            matrices: vec![r1cs.a, r1cs.b, r1cs.c],
+            multisets: vec![vec![0, 1], vec![2]],
+            constants: vec![F::one(), -F::one()],
+            z: r1cs.z,
+            w: r1cs.w,
+            x: r1cs.x,
+        }
+    }
+}
+
+#[cfg(feature = "prover")]
+#[test]
+fn test_ccs_to_r1cs() {
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::{test_rng, One};
+
+    use crate::kzg::CommitterKey;
+    use crate::snark::Proof;
+
+    let rng = &mut test_rng();
+    // the constraint a * b * c = out, with z = (1, out, a, b, c).
+    let (a, b, c) = (Fr::from(2u64), Fr::from(3u64), Fr::from(5u64));
+    let x = vec![Fr::one(), a * b * c];
+    let w = vec![a, b, c];
+    let selector = |col| vec![vec![(Fr::one(), col)]];
+    let mut ccs = Ccs {
+        matrices: vec![selector(2), selector(3), selector(4), selector(1)],
+        multisets: vec![vec![0, 1, 2], vec![3]],
+        constants: vec![Fr::one(), -Fr::one()],
+        z: x.iter().chain(&w).cloned().collect(),
+        w,
+        x,
+    };
+    assert!(ccs.is_satisfied());
+
+    let r1cs = ccs.to_r1cs();
+    let ck = CommitterKey::<Bls12_381>::new(16, 5, rng);
+    let vk = (&ck).into();
+    let proof = Proof::new_time(&r1cs, &ck).unwrap();
+    assert!(proof.verify(&r1cs, &vk).is_ok());
+
+    ccs.x[1] += Fr::one();
+    ccs.z[1] += Fr::one();
+    assert!(!ccs.is_satisfied());
+}
@@ -0,0 +1,622 @@
+//! Streaming import of circuits compiled with [circom](https://docs.circom.io/).
+//!
+//! A `.r1cs` file holds the constraints, and a `.wtns` file the assignment to all wires.
+//! Wire 0 is the constant one, followed by the public outputs, the public inputs, and the private signals:
+//! hence, the wires are laid out exactly as the vector $\vec z = (\vec x, \vec w)$.
+//!
+//! Both files are read lazily.
+//! Streams are big-endian, so files are read backwards, in blocks of 1024 constraints (or wires):
+//! only the offsets of the blocks of constraints are kept in memory.
+//! Matrix streams are row-major, and can be fed directly to the psnark indexer;
+//! column-major streams can be obtained with [`transpose`](crate::iterable::transpose::transpose).
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+use std::fs::File;
+use std::io::{self, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::circuit::{Matrix, R1cs};
+use crate::iterable::Iterable;
+use crate::misc::MatrixElement;
+
+/// The number of constraints (or wires) read at once.
+const BLOCK_SIZE: usize = 1 << 10;
+/// The largest supported field element, in bytes.
+const MAX_ELEMENT_SIZE: usize = 64;
+/// The type of the section holding the header, both in `.r1cs` and `.wtns` files.
+const HEADER_SECTION: u32 = 1;
+/// The type of the section holding the constraints in `.r1cs` files.
+const CONSTRAINTS_SECTION: u32 = 2;
+/// The type of the section holding the assignment in `.wtns` files.
+const WITNESS_SECTION: u32 = 2;
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+fn read_u32<R: Read>(reader: &mut R) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64<R: Read>(reader: &mut R) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Read a field element of `n8` bytes, in little-endian order.
+fn read_element<F: PrimeField, R: Read>(reader: &mut R, n8: usize) -> io::Result<F> {
+    let mut bytes = [0u8; MAX_ELEMENT_SIZE];
+    reader.read_exact(&mut bytes[..n8])?;
+    Ok(F::from_le_bytes_mod_order(&bytes[..n8]))
+}
+
+/// Check the magic number `magic` and find the offset of the section of type `section_type`.
+fn find_section(
+    reader: &mut BufReader<File>,
+    magic: &[u8; 4],
+    section_type: u32,
+) -> io::Result<u64> {
+    let mut file_magic = [0u8; 4];
+    reader.read_exact(&mut file_magic)?;
+    if &file_magic != magic {
+        return Err(invalid_data("unexpected file type"));
+    }
+    let _version = read_u32(reader)?;
+    let num_sections = read_u32(reader)?;
+    let mut offset = 12;
+    for _ in 0..num_sections {
+        let current_type = read_u32(reader)?;
+        let size = read_u64(reader)?;
+        offset += 12;
+        if current_type == section_type {
+            return Ok(offset);
+        }
+        reader.seek(SeekFrom::Current(size as i64))?;
+        offset += size;
+    }
+    Err(invalid_data("missing section"))
+}
+
+/// Read the size of field elements, and check that the prime matches the modulus of `F`.
+fn read_field_header<F: PrimeField, R: Read>(reader: &mut R) -> io::Result<usize> {
+    let n8 = read_u32(reader)? as usize;
+    if n8 > MAX_ELEMENT_SIZE {
+        return Err(invalid_data("field elements are too large"));
+    }
+    let mut prime = vec![0u8; n8];
+    reader.read_exact(&mut prime)?;
+    let mut modulus = F::MODULUS.to_bytes_le();
+    modulus.resize(n8, 0);
+    if prime != modulus {
+        return Err(invalid_data("the prime does not match the scalar field"));
+    }
+    Ok(n8)
+}
+
+/// Skip a linear combination, returning the number of bytes read.
+fn skip_linear_combination(reader: &mut BufReader<File>, n8: usize) -> io::Result<u64> {
+    let num_factors = read_u32(reader)? as usize;
+    let size = num_factors * (4 + n8);
+    reader.seek_relative(size as i64)?;
+    Ok(4 + size as u64)
+}
+
+/// Read a linear combination, as a sparse row.
+fn read_linear_combination<F: PrimeField>(
+    reader: &mut BufReader<File>,
+    n8: usize,
+) -> io::Result<Vec<(F, usize)>> {
+    let num_factors = read_u32(reader)? as usize;
+    (0..num_factors)
+        .map(|_| {
+            let wire = read_u32(reader)? as usize;
+            read_element(reader, n8).map(|value| (value, wire))
+        })
+        .collect()
+}
+
+/// The constraints of a `.r1cs` file.
+pub struct CircomR1cs<F> {
+    path: PathBuf,
+    n8: usize,
+    num_wires: usize,
+    num_statement: usize,
+    num_constraints: usize,
+    /// The offsets of the blocks of constraints.
+    checkpoints: Vec<u64>,
+    _field: PhantomData<F>,
+}
+
+impl<F: PrimeField> CircomR1cs<F> {
+    /// Open the `.r1cs` file at `path`, and index its constraints.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut reader = BufReader::new(File::open(&path)?);
+        let header_offset = find_section(&mut reader, b"r1cs", HEADER_SECTION)?;
+        reader.seek(SeekFrom::Start(header_offset))?;
+        let n8 = read_field_header::<F, _>(&mut reader)?;
+        let num_wires = read_u32(&mut reader)? as usize;
+        let num_public_outputs = read_u32(&mut reader)? as usize;
+        let num_public_inputs = read_u32(&mut reader)? as usize;
+        let _num_private_inputs = read_u32(&mut reader)?;
+        let _num_labels = read_u64(&mut reader)?;
+        let num_constraints = read_u32(&mut reader)? as usize;
+
+        reader.seek(SeekFrom::Start(0))?;
+        let mut offset = find_section(&mut reader, b"r1cs", CONSTRAINTS_SECTION)?;
+        reader.seek(SeekFrom::Start(offset))?;
+        let mut checkpoints = Vec::new();
+        for i in 0..num_constraints {
+            if i % BLOCK_SIZE == 0 {
+                checkpoints.push(offset);
+            }
+            for _ in 0..3 {
+                offset += skip_linear_combination(&mut reader, n8)?;
+            }
+        }
+        Ok(CircomR1cs {
+            path,
+            n8,
+            num_wires,
+            num_statement: 1 + num_public_outputs + num_public_inputs,
+            num_constraints,
+            checkpoints,
+            _field: PhantomData,
+        })
+    }
+
+    /// The number of wires, that is, the length of $\vec z$.
+    pub fn num_variables(&self) -> usize {
+        self.num_wires
+    }
+
+    /// The length of the public input $\vec x$, including the constant one.
+    pub fn num_statement(&self) -> usize {
+        self.num_statement
+    }
+
+    /// The number of constraints.
+    pub fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+
+    /// The row-major stream of the matrix $A$.
+    pub fn a(&self) -> CircomMatrix<'_, F> {
+        CircomMatrix {
+            r1cs: self,
+            index: 0,
+        }
+    }
+
+    /// The row-major stream of the matrix $B$.
+    pub fn b(&self) -> CircomMatrix<'_, F> {
+        CircomMatrix {
+            r1cs: self,
+            index: 1,
+        }
+    }
+
+    /// The row-major stream of the matrix $C$.
+    pub fn c(&self) -> CircomMatrix<'_, F> {
+        CircomMatrix {
+            r1cs: self,
+            index: 2,
+        }
+    }
+
+    /// Load the constraints in memory, together with the assignment `z`
+    /// (in little-endian order, of length [`num_variables`](Self::num_variables)).
+    pub fn relation(&self, z: Vec<F>) -> io::Result<R1cs<F>> {
+        if z.len() != self.num_wires {
+            return Err(invalid_data(
+                "the assignment does not match the constraints",
+            ));
+        }
+        Ok(R1cs {
+            a: collect_matrix(&self.a()),
+            b: collect_matrix(&self.b()),
+            c: collect_matrix(&self.c()),
+            x: z[..self.num_statement].to_vec(),
+            w: z[self.num_statement..].to_vec(),
+            z,
+        })
+    }
+}
+
+/// Collect the row-major stream `stream` into a matrix.
+fn collect_matrix<F: PrimeField>(stream: &CircomMatrix<'_, F>) -> Matrix<F> {
+    // the stream is big-endian: rows, and the entries within them, are in reversed order.
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    for element in stream.iter() {
+        match element {
+            MatrixElement::Element(entry) => row.push(entry),
+            MatrixElement::EOL => {
+                row.reverse();
+                rows.push(ark_std::mem::take(&mut row));
+            }
+        }
+    }
+    rows.reverse();
+    rows
+}
+
+/// The row-major stream of one of the matrices of a [`CircomR1cs`],
+/// with one line per constraint.
+#[derive(Clone, Copy)]
+pub struct CircomMatrix<'a, F> {
+    r1cs: &'a CircomR1cs<F>,
+    index: usize,
+}
+
+/// The iterator returned by [`CircomMatrix`].
+pub struct CircomMatrixIter<'a, F> {
+    r1cs: &'a CircomR1cs<F>,
+    reader: BufReader<File>,
+    index: usize,
+    /// The number of blocks left to read.
+    blocks: usize,
+    /// The elements of the current block, in reversed order.
+    buffer: Vec<MatrixElement<F>>,
+}
+
+impl<'a, F: PrimeField> CircomMatrixIter<'a, F> {
+    fn read_block(&mut self, block: usize) -> io::Result<()> {
+        let n8 = self.r1cs.n8;
+        let first = block * BLOCK_SIZE;
+        let last = usize::min(first + BLOCK_SIZE, self.r1cs.num_constraints);
+        self.reader
+            .seek(SeekFrom::Start(self.r1cs.checkpoints[block]))?;
+        for _ in first..last {
+            let mut row = Vec::new();
+            for i in 0..3 {
+                if i == self.index {
+                    row = read_linear_combination(&mut self.reader, n8)?;
+                } else {
+                    skip_linear_combination(&mut self.reader, n8)?;
+                }
+            }
+            row.sort_unstable_by_key(|&(_, col)| col);
+            self.buffer.push(MatrixElement::EOL);
+            self.buffer
+                .extend(row.into_iter().map(MatrixElement::Element));
+        }
+        Ok(())
+    }
+}
+
+impl<'a, F: PrimeField> Iterator for CircomMatrixIter<'a, F> {
+    type Item = MatrixElement<F>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(e) = self.buffer.pop() {
+                return Some(e);
+            }
+            if self.blocks == 0 {
+                return None;
+            }
+            self.blocks -= 1;
+            self.read_block(self.blocks)
+                .expect("failed to read the .r1cs file");
+        }
+    }
+}
+
+impl<'a, F: PrimeField> Iterable for CircomMatrix<'a, F> {
+    type Item = MatrixElement<F>;
+
+    type Iter = CircomMatrixIter<'a, F>;
+
+    fn iter(&self) -> Self::Iter {
+        let file = File::open(&self.r1cs.path).expect("failed to open the .r1cs file");
+        CircomMatrixIter {
+            r1cs: self.r1cs,
+            reader: BufReader::new(file),
+            index: self.index,
+            blocks: self.r1cs.checkpoints.len(),
+            buffer: Vec::new(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.r1cs.num_constraints
+    }
+}
+
+/// The assignment of a `.wtns` file.
+pub struct CircomWitness<F> {
+    path: PathBuf,
+    n8: usize,
+    num_wires: usize,
+    offset: u64,
+    _field: PhantomData<F>,
+}
+
+impl<F: PrimeField> CircomWitness<F> {
+    /// Open the `.wtns` file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut reader = BufReader::new(File::open(&path)?);
+        let header_offset = find_section(&mut reader, b"wtns", HEADER_SECTION)?;
+        reader.seek(SeekFrom::Start(header_offset))?;
+        let n8 = read_field_header::<F, _>(&mut reader)?;
+        let num_wires = read_u32(&mut reader)? as usize;
+        reader.seek(SeekFrom::Start(0))?;
+        let offset = find_section(&mut reader, b"wtns", WITNESS_SECTION)?;
+        Ok(CircomWitness {
+            path,
+            n8,
+            num_wires,
+            offset,
+            _field: PhantomData,
+        })
+    }
+
+    /// The number of wires.
+    pub fn len(&self) -> usize {
+        self.num_wires
+    }
+
+    /// Return `true` if there are no wires.
+    pub fn is_empty(&self) -> bool {
+        self.num_wires == 0
+    }
+
+    /// The (big-endian) stream of the vector $\vec z$.
+    pub fn z(&self) -> CircomAssignment<'_, F> {
+        CircomAssignment {
+            witness: self,
+            start: 0,
+            end: self.num_wires,
+        }
+    }
+
+    /// The (big-endian) stream of the witness $\vec w$,
+    /// that is, all wires but the first `num_statement`.
+    pub fn witness(&self, num_statement: usize) -> CircomAssignment<'_, F> {
+        CircomAssignment {
+            witness: self,
+            start: num_statement,
+            end: self.num_wires,
+        }
+    }
+
+    /// Read the public input $\vec x$, that is, the first `num_statement` wires.
+    pub fn public_input(&self, num_statement: usize) -> io::Result<Vec<F>> {
+        let mut reader = BufReader::new(File::open(&self.path)?);
+        reader.seek(SeekFrom::Start(self.offset))?;
+        (0..num_statement)
+            .map(|_| read_element(&mut reader, self.n8))
+            .collect()
+    }
+
+    /// Random access to the wires, holding one block of wires in memory.
+    pub fn wires(&self) -> io::Result<CircomWires<'_, F>> {
+        Ok(CircomWires {
+            witness: self,
+            reader: BufReader::new(File::open(&self.path)?),
+            first: 0,
+            block: Vec::new(),
+        })
+    }
+}
+
+/// Random access to the wires of a [`CircomWitness`], returned by [`CircomWitness::wires`].
+pub struct CircomWires<'a, F> {
+    witness: &'a CircomWitness<F>,
+    reader: BufReader<File>,
+    /// The first wire of the current block.
+    first: usize,
+    block: Vec<F>,
+}
+
+impl<'a, F: PrimeField> CircomWires<'a, F> {
+    /// Read the wire `wire`, that is, the entry `wire` of $\vec z$.
+    ///
+    /// The block holding `wire` is read if it is not the current one.
+    pub fn get(&mut self, wire: usize) -> io::Result<F> {
+        if wire >= self.witness.num_wires {
+            return Err(invalid_data("the wire is not in the assignment"));
+        }
+        if wire < self.first || wire >= self.first + self.block.len() {
+            let n8 = self.witness.n8;
+            self.first = wire - wire % BLOCK_SIZE;
+            let last = usize::min(self.first + BLOCK_SIZE, self.witness.num_wires);
+            self.block.clear();
+            let offset = self.witness.offset + (self.first * n8) as u64;
+            self.reader.seek(SeekFrom::Start(offset))?;
+            for _ in self.first..last {
+                self.block.push(read_element(&mut self.reader, n8)?);
+            }
+        }
+        Ok(self.block[wire - self.first])
+    }
+}
+
+/// The (big-endian) stream of a range of wires of a [`CircomWitness`].
+#[derive(Clone, Copy)]
+pub struct CircomAssignment<'a, F> {
+    witness: &'a CircomWitness<F>,
+    start: usize,
+    end: usize,
+}
+
+/// The iterator returned by [`CircomAssignment`].
+pub struct CircomAssignmentIter<'a, F> {
+    witness: &'a CircomWitness<F>,
+    reader: BufReader<File>,
+    start: usize,
+    /// The first wire that has been read.
+    next: usize,
+    /// The wires of the current block, in reversed order.
+    buffer: Vec<F>,
+}
+
+impl<'a, F: PrimeField> CircomAssignmentIter<'a, F> {
+    fn read_block(&mut self) -> io::Result<()> {
+        let n8 = self.witness.n8;
+        let first = usize::max(self.start, self.next.saturating_sub(BLOCK_SIZE));
+        let offset = self.witness.offset + (first * n8) as u64;
+        self.reader.seek(SeekFrom::Start(offset))?;
+        for _ in first..self.next {
+            self.buffer.push(read_element(&mut self.reader, n8)?);
+        }
+        self.next = first;
+        Ok(())
+    }
+}
+
+impl<'a, F: PrimeField> Iterator for CircomAssignmentIter<'a, F> {
+    type Item = F;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(e) = self.buffer.pop() {
+                return Some(e);
+            }
+            if self.next == self.start {
+                return None;
+            }
+            self.read_block().expect("failed to read the .wtns file");
+        }
+    }
+}
+
+impl<'a, F: PrimeField> Iterable for CircomAssignment<'a, F> {
+    type Item = F;
+
+    type Iter = CircomAssignmentIter<'a, F>;
+
+    fn iter(&self) -> Self::Iter {
+        let file = File::open(&self.witness.path).expect("failed to open the .wtns file");
+        CircomAssignmentIter {
+            witness: self.witness,
+            reader: BufReader::new(file),
+            start: self.start,
+            next: self.end,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.end - self.start
+    }
+}
+
+/// Write `r1cs` into the `.r1cs` and `.wtns` files `name` in the temporary directory,
+/// and return their paths.
+#[cfg(test)]
+pub(crate) fn write_circom_files<F: PrimeField>(r1cs: &R1cs<F>, name: &str) -> (PathBuf, PathBuf) {
+    use ark_serialize::CanonicalSerialize;
+    use std::io::Write;
+
+    let prime = F::MODULUS.to_bytes_le();
+    let n8 = prime.len();
+    let element = |bytes: &mut Vec<u8>, e: &F| e.serialize_uncompressed(bytes).unwrap();
+    let sections = |magic: &[u8], sections: &[(u32, &Vec<u8>)]| {
+        let mut bytes = magic.to_vec();
+        bytes.extend(1u32.to_le_bytes());
+        bytes.extend((sections.len() as u32).to_le_bytes());
+        for (section_type, data) in sections {
+            bytes.extend(section_type.to_le_bytes());
+            bytes.extend((data.len() as u64).to_le_bytes());
+            bytes.extend(data.iter());
+        }
+        bytes
+    };
+
+    // write the .r1cs file.
+    let mut header = Vec::new();
+    header.extend((n8 as u32).to_le_bytes());
+    header.extend(&prime);
+    header.extend((r1cs.z.len() as u32).to_le_bytes());
+    header.extend(0u32.to_le_bytes());
+    header.extend(((r1cs.x.len() - 1) as u32).to_le_bytes());
+    header.extend((r1cs.w.len() as u32).to_le_bytes());
+    header.extend(0u64.to_le_bytes());
+    header.extend((r1cs.a.len() as u32).to_le_bytes());
+    let mut constraints = Vec::new();
+    for ((a, b), c) in r1cs.a.iter().zip(&r1cs.b).zip(&r1cs.c) {
+        for row in [a, b, c] {
+            constraints.extend((row.len() as u32).to_le_bytes());
+            for (value, col) in row {
+                constraints.extend((*col as u32).to_le_bytes());
+                element(&mut constraints, value);
+            }
+        }
+    }
+    let r1cs_path = std::env::temp_dir().join(std::format!("{name}.r1cs"));
+    File::create(&r1cs_path)
+        .unwrap()
+        .write_all(&sections(b"r1cs", &[(1, &header), (2, &constraints)]))
+        .unwrap();
+
+    // write the .wtns file.
+    let mut header = Vec::new();
+    header.extend((n8 as u32).to_le_bytes());
+    header.extend(&prime);
+    header.extend((r1cs.z.len() as u32).to_le_bytes());
+    let mut values = Vec::new();
+    r1cs.z.iter().for_each(|e| element(&mut values, e));
+    let wtns_path = std::env::temp_dir().join(std::format!("{name}.wtns"));
+    File::create(&wtns_path)
+        .unwrap()
+        .write_all(&sections(b"wtns", &[(1, &header), (2, &values)]))
+        .unwrap();
+    (r1cs_path, wtns_path)
+}
+
+#[test]
+fn test_circom_import() {
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    use crate::circuit::{generate_relation, matrix_into_rowmaj, random_circuit};
+
+    let rng = &mut test_rng();
+    let r1cs = generate_relation::<Fr, _>(random_circuit(rng, 1 << 11, 1 << 11));
+    let (r1cs_path, wtns_path) = write_circom_files(&r1cs, "gemini-test-circom");
+
+    let circom_r1cs = CircomR1cs::<Fr>::open(&r1cs_path).unwrap();
+    assert_eq!(circom_r1cs.num_constraints(), r1cs.a.len());
+    assert_eq!(circom_r1cs.num_statement(), r1cs.x.len());
+    assert_eq!(
+        circom_r1cs.a().iter().collect::<Vec<_>>(),
+        matrix_into_rowmaj(&r1cs.a)
+    );
+    assert_eq!(
+        circom_r1cs.c().iter().collect::<Vec<_>>(),
+        matrix_into_rowmaj(&r1cs.c)
+    );
+
+    let circom_witness = CircomWitness::<Fr>::open(&wtns_path).unwrap();
+    let z = r1cs.z.iter().rev().cloned().collect::<Vec<_>>();
+    assert_eq!(circom_witness.z().iter().collect::<Vec<_>>(), z);
+    assert_eq!(
+        circom_witness
+            .witness(circom_r1cs.num_statement())
+            .iter()
+            .collect::<Vec<_>>(),
+        z[..r1cs.w.len()]
+    );
+    assert_eq!(
+        circom_witness
+            .public_input(circom_r1cs.num_statement())
+            .unwrap(),
+        r1cs.x
+    );
+    // wires are read in any order, across blocks.
+    let mut wires = circom_witness.wires().unwrap();
+    for wire in [0, 1500, 3, BLOCK_SIZE, r1cs.z.len() - 1, 2] {
+        assert_eq!(wires.get(wire).unwrap(), r1cs.z[wire]);
+    }
+    assert!(wires.get(r1cs.z.len()).is_err());
+
+    std::fs::remove_file(r1cs_path).unwrap();
+    std::fs::remove_file(wtns_path).unwrap();
+}
@@ -1,19 +1,25 @@
 //! This code was blatantly stolen from arkworks test suite.
-use ark_ff::{Field, PrimeField};
+use ark_ff::{Field, PrimeField, Zero};
 use ark_poly::{EvaluationDomain, GeneralEvaluationDomain};
 use ark_relations::{
     lc,
     r1cs::{
         ConstraintSynthesizer, ConstraintSystem, ConstraintSystemRef, OptimizationGoal,
-        SynthesisError,
+        SynthesisError, SynthesisMode,
     },
 };
+use ark_std::borrow::{Borrow, Cow};
 use ark_std::rand::RngCore;
 use ark_std::vec::Vec;
+use core::fmt;
+use core::sync::atomic::AtomicUsize;
 
-use crate::iterable::dummy::{RepeatMatrixStreamer, RepeatStreamer};
-use crate::iterable::Iterable;
-use crate::misc::MatrixElement;
+use crate::errors::{GeminiError, GeminiResult};
+use crate::iterable::dummy::{Mat, RepeatMatrixStreamer, RepeatStreamer};
+use crate::iterable::{Counted, Iterable, MatrixPadded, Reverse, ZeroPadded};
+#[cfg(feature = "std")]
+use crate::iterable::{Instrumented, PassLog};
+use crate::misc::{product_matrix_vector, MatrixElement};
 
 #[derive(Copy, Clone)]
 pub struct Circuit<F: Field> {
@@ -39,9 +45,367 @@ pub struct R1csStream<SM, SZ, SW> {
     pub joint_len: usize,
 }
 
+impl<SM, SZ, SW> R1csStream<SM, SZ, SW> {
+    /// Return the public input $\vec x$, in little-endian order.
+    ///
+    /// The stream `z` is the (big-endian) stream of $\vec z = (\vec x, \vec w)$,
+    /// hence the public input is found after the `witness.len()` leading elements.
+    pub fn public_input<F: Field>(&self) -> Vec<F>
+    where
+        SZ: Iterable,
+        SZ::Item: Borrow<F>,
+        SW: Iterable,
+    {
+        let mut x = self
+            .z
+            .iter()
+            .skip(self.witness.len())
+            .map(|e| *e.borrow())
+            .collect::<Vec<_>>();
+        x.reverse();
+        x
+    }
+
+    /// Check that the declared lengths of the streams are consistent:
+    /// the streams `z_a`, `z_b`, `z_c` have one element per constraint,
+    /// and the witness is no longer than $\vec z$.
+    ///
+    /// The elastic provers perform this check before starting.
+    /// It does not read the streams: see [`R1csStream::check_streams`].
+    pub fn check(&self) -> GeminiResult<()>
+    where
+        SZ: Iterable,
+        SW: Iterable,
+    {
+        GeminiError::ensure_len("r1cs.z_a", self.nonzero, self.z_a.len())?;
+        GeminiError::ensure_len("r1cs.z_b", self.nonzero, self.z_b.len())?;
+        GeminiError::ensure_len("r1cs.z_c", self.nonzero, self.z_c.len())?;
+        if self.witness.len() > self.z.len() {
+            return Err(GeminiError::MalformedInstance {
+                reason: "the witness is longer than z",
+            });
+        }
+        Ok(())
+    }
+
+    /// Same as [`R1csStream::check`], but also read each stream once, checking that:
+    /// - every vector stream has as many elements as its declared length;
+    /// - the row-major streams have one line per constraint,
+    ///   and their entries lie within the columns of $\vec z$;
+    /// - the column-major streams have one line per variable,
+    ///   and their entries lie within the constraints.
+    ///
+    /// The provers trust the declared lengths, and panic mid-proof if a stream is shorter.
+    /// For streams read from external sources, checking them beforehand is cheaper than a failed run.
+    pub fn check_streams<F: Field>(&self) -> GeminiResult<()>
+    where
+        SM: Iterable,
+        SM::Item: Borrow<MatrixElement<F>>,
+        SZ: Iterable,
+        SW: Iterable,
+    {
+        self.check()?;
+        let vectors = [
+            ("r1cs.z", &self.z),
+            ("r1cs.z_a", &self.z_a),
+            ("r1cs.z_b", &self.z_b),
+            ("r1cs.z_c", &self.z_c),
+        ];
+        for (name, stream) in vectors {
+            GeminiError::ensure_len(name, stream.len(), stream.iter().count())?;
+        }
+        let witness = &self.witness;
+        GeminiError::ensure_len("r1cs.witness", witness.len(), witness.iter().count())?;
+
+        let num_variables = self.z.len();
+        let matrices = [
+            ("r1cs.a_rowmaj", &self.a_rowmaj, self.nonzero, num_variables),
+            ("r1cs.b_rowmaj", &self.b_rowmaj, self.nonzero, num_variables),
+            ("r1cs.c_rowmaj", &self.c_rowmaj, self.nonzero, num_variables),
+            ("r1cs.a_colmaj", &self.a_colmaj, num_variables, self.nonzero),
+            ("r1cs.b_colmaj", &self.b_colmaj, num_variables, self.nonzero),
+            ("r1cs.c_colmaj", &self.c_colmaj, num_variables, self.nonzero),
+        ];
+        for (name, matrix, lines, bound) in matrices {
+            check_matrix(name, matrix, lines, bound)?;
+        }
+        Ok(())
+    }
+
+    /// Check that the streamed witness satisfies the constraints,
+    /// and return the violation of lowest index otherwise.
+    ///
+    /// The row-major matrix streams are traversed once, jointly with the streams `z_a`, `z_b`, `z_c`,
+    /// and for each constraint $i$ the products $(A \vec z)_i$, $(B \vec z)_i$, $(C \vec z)_i$
+    /// are checked against the streamed ones and against one another.
+    /// This is meant for debugging: unlike the provers, $\vec z$ is kept in memory.
+    pub fn check_satisfied<F: Field>(&self) -> Result<(), Violation<F>>
+    where
+        SM: Iterable,
+        SM::Item: Borrow<MatrixElement<F>>,
+        SZ: Iterable,
+        SZ::Item: Borrow<F>,
+    {
+        let mut z = self.z.iter().map(|e| *e.borrow()).collect::<Vec<_>>();
+        z.reverse();
+        let az = row_products(self.a_rowmaj.iter(), &z);
+        let bz = row_products(self.b_rowmaj.iter(), &z);
+        let cz = row_products(self.c_rowmaj.iter(), &z);
+        let mut z_a = self.z_a.iter();
+        let mut z_b = self.z_b.iter();
+        let mut z_c = self.z_c.iter();
+
+        // rows are streamed from the last constraint to the first one:
+        // keep the last violation found.
+        let mut violation = None;
+        let mut rows = 0;
+        for ((az, bz), cz) in az.zip(bz).zip(cz) {
+            let index = self.nonzero.saturating_sub(rows + 1);
+            rows += 1;
+            let products = match (az, bz, cz) {
+                (Ok(az), Ok(bz), Ok(cz)) => [az, bz, cz],
+                (Err(column), _, _) | (_, Err(column), _) | (_, _, Err(column)) => {
+                    violation = Some(Violation::Column { index, column });
+                    continue;
+                }
+            };
+            let streamed = [z_a.next(), z_b.next(), z_c.next()];
+            let mismatch = ["A", "B", "C"]
+                .iter()
+                .zip(products.iter().zip(streamed.iter()))
+                .find(|(_, (expected, found))| {
+                    found.as_ref().map(|f| *f.borrow()) != Some(**expected)
+                });
+            if let Some((&&matrix, (&expected, found))) = mismatch {
+                violation = Some(Violation::Product {
+                    matrix,
+                    index,
+                    expected,
+                    found: found.as_ref().map(|f| *f.borrow()),
+                });
+            } else if products[0] * products[1] != products[2] {
+                let [az, bz, cz] = products;
+                violation = Some(Violation::Constraint { index, az, bz, cz });
+            }
+        }
+        if rows != self.nonzero {
+            return Err(Violation::Length {
+                expected: self.nonzero,
+                found: rows,
+            });
+        }
+        violation.map_or(Ok(()), Err)
+    }
+
+    /// Return the streaming instance padded to square matrices
+    /// whose dimension is a power of two, consistently with [`R1cs::padded`].
+    ///
+    /// Here `num_cols` is the number of lines of the column-major streams,
+    /// while the row-major streams have `self.nonzero` lines.
+    pub fn padded<F: Field>(
+        &self,
+        num_cols: usize,
+    ) -> R1csStream<MatrixPadded<'_, SM, F>, ZeroPadded<'_, SZ, F>, ZeroPadded<'_, SW, F>>
+    where
+        SM: Iterable,
+        SM::Item: Borrow<MatrixElement<F>>,
+        SZ: Iterable,
+        SZ::Item: Borrow<F>,
+        SW: Iterable,
+        SW::Item: Borrow<F>,
+    {
+        let dim = padded_dim(self.nonzero, self.z.len());
+        let statement_len = self.z.len() - self.witness.len();
+        let colmaj = |m| MatrixPadded::new(m, dim - num_cols);
+        let rowmaj = |m| MatrixPadded::new(m, dim - self.nonzero);
+        R1csStream {
+            a_colmaj: colmaj(&self.a_colmaj),
+            b_colmaj: colmaj(&self.b_colmaj),
+            c_colmaj: colmaj(&self.c_colmaj),
+            a_rowmaj: rowmaj(&self.a_rowmaj),
+            b_rowmaj: rowmaj(&self.b_rowmaj),
+            c_rowmaj: rowmaj(&self.c_rowmaj),
+            z: ZeroPadded::new(&self.z, dim),
+            witness: ZeroPadded::new(&self.witness, dim - statement_len),
+            z_a: ZeroPadded::new(&self.z_a, dim),
+            z_b: ZeroPadded::new(&self.z_b, dim),
+            z_c: ZeroPadded::new(&self.z_c, dim),
+            nonzero: dim,
+            joint_len: self.joint_len,
+        }
+    }
+
+    /// Return the streaming instance counting in `passes` the passes over each of its streams.
+    pub fn counted<'a>(
+        &self,
+        passes: &'a AtomicUsize,
+    ) -> R1csStream<Counted<'a, SM>, Counted<'a, SZ>, Counted<'a, SW>>
+    where
+        SM: Iterable + Copy,
+        SZ: Iterable + Copy,
+        SW: Iterable + Copy,
+    {
+        R1csStream {
+            a_colmaj: Counted::new(self.a_colmaj, passes),
+            b_colmaj: Counted::new(self.b_colmaj, passes),
+            c_colmaj: Counted::new(self.c_colmaj, passes),
+            a_rowmaj: Counted::new(self.a_rowmaj, passes),
+            b_rowmaj: Counted::new(self.b_rowmaj, passes),
+            c_rowmaj: Counted::new(self.c_rowmaj, passes),
+            z: Counted::new(self.z, passes),
+            witness: Counted::new(self.witness, passes),
+            z_a: Counted::new(self.z_a, passes),
+            z_b: Counted::new(self.z_b, passes),
+            z_c: Counted::new(self.z_c, passes),
+            nonzero: self.nonzero,
+            joint_len: self.joint_len,
+        }
+    }
+
+    /// Return the streaming instance logging in `log` the passes over each of its streams,
+    /// labelled by the name of the stream (e.g. `"r1cs.a_colmaj"`).
+    #[cfg(feature = "std")]
+    pub fn instrumented<'a>(
+        &self,
+        log: &'a PassLog,
+    ) -> R1csStream<Instrumented<'a, SM>, Instrumented<'a, SZ>, Instrumented<'a, SW>>
+    where
+        SM: Iterable + Copy,
+        SZ: Iterable + Copy,
+        SW: Iterable + Copy,
+    {
+        R1csStream {
+            a_colmaj: Instrumented::new(self.a_colmaj, "r1cs.a_colmaj", log),
+            b_colmaj: Instrumented::new(self.b_colmaj, "r1cs.b_colmaj", log),
+            c_colmaj: Instrumented::new(self.c_colmaj, "r1cs.c_colmaj", log),
+            a_rowmaj: Instrumented::new(self.a_rowmaj, "r1cs.a_rowmaj", log),
+            b_rowmaj: Instrumented::new(self.b_rowmaj, "r1cs.b_rowmaj", log),
+            c_rowmaj: Instrumented::new(self.c_rowmaj, "r1cs.c_rowmaj", log),
+            z: Instrumented::new(self.z, "r1cs.z", log),
+            witness: Instrumented::new(self.witness, "r1cs.witness", log),
+            z_a: Instrumented::new(self.z_a, "r1cs.z_a", log),
+            z_b: Instrumented::new(self.z_b, "r1cs.z_b", log),
+            z_c: Instrumented::new(self.z_c, "r1cs.z_c", log),
+            nonzero: self.nonzero,
+            joint_len: self.joint_len,
+        }
+    }
+}
+
+/// A violation of the constraints found by [`R1csStream::check_satisfied`].
+///
+/// Constraints are indexed from zero, in little-endian order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation<F> {
+    /// The constraint `index` does not hold: $(A \vec z)_i \cdot (B \vec z)_i \neq (C \vec z)_i$.
+    Constraint { index: usize, az: F, bz: F, cz: F },
+    /// The stream of the product of `matrix` and $\vec z$ differs from the product at `index`
+    /// (`None` if the stream is too short).
+    Product {
+        matrix: &'static str,
+        index: usize,
+        expected: F,
+        found: Option<F>,
+    },
+    /// The constraint `index` refers to a `column` outside of $\vec z$.
+    Column { index: usize, column: usize },
+    /// The row-major streams do not have one line per constraint.
+    Length { expected: usize, found: usize },
+}
+
+impl<F: fmt::Display> fmt::Display for Violation<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::Constraint { index, az, bz, cz } => write!(
+                f,
+                "constraint {} is not satisfied: {} * {} != {}",
+                index, az, bz, cz
+            ),
+            Violation::Product {
+                matrix,
+                index,
+                expected,
+                found: Some(found),
+            } => write!(
+                f,
+                "{}z at constraint {}: expected {}, streamed {}",
+                matrix, index, expected, found
+            ),
+            Violation::Product {
+                matrix,
+                index,
+                expected,
+                found: None,
+            } => write!(
+                f,
+                "{}z at constraint {}: expected {}, stream exhausted",
+                matrix, index, expected
+            ),
+            Violation::Column { index, column } => write!(
+                f,
+                "constraint {} refers to column {} outside of z",
+                index, column
+            ),
+            Violation::Length { expected, found } => {
+                write!(f, "expected {} constraints, streamed {}", expected, found)
+            }
+        }
+    }
+}
+
+/// Fail unless the matrix stream `name` has `lines` lines, and all its indices are less than `bound`.
+fn check_matrix<F: Field, S>(
+    name: &'static str,
+    matrix: &S,
+    lines: usize,
+    bound: usize,
+) -> GeminiResult<()>
+where
+    S: Iterable,
+    S::Item: Borrow<MatrixElement<F>>,
+{
+    let mut found = 0;
+    for element in matrix.iter() {
+        match *element.borrow() {
+            MatrixElement::EOL => found += 1,
+            MatrixElement::Element((_, index)) if index >= bound => {
+                return Err(GeminiError::IndexOutOfBounds { name, index, bound })
+            }
+            MatrixElement::Element(_) => (),
+        }
+    }
+    GeminiError::ensure_len(name, lines, found)
+}
+
+/// Return, for each line of the row-major matrix stream `elements`,
+/// the inner product of the line with `z`, or the first column outside of `z`.
+fn row_products<'a, F, I>(
+    mut elements: I,
+    z: &'a [F],
+) -> impl Iterator<Item = Result<F, usize>> + 'a
+where
+    F: Field,
+    I: Iterator + 'a,
+    I::Item: Borrow<MatrixElement<F>>,
+{
+    ark_std::iter::from_fn(move || {
+        let mut product = Ok(F::zero());
+        loop {
+            let element = elements.next()?;
+            match *element.borrow() {
+                MatrixElement::EOL => return Some(product),
+                MatrixElement::Element((value, col)) => {
+                    product = product.and_then(|p| z.get(col).map(|&e| p + value * e).ok_or(col))
+                }
+            }
+        }
+    })
+}
+
 /// Represents a matrix.
 pub type Matrix<F> = Vec<Vec<(F, usize)>>;
 
+#[derive(Clone)]
 pub struct R1cs<F: Field> {
     pub a: Matrix<F>,
     pub b: Matrix<F>,
@@ -51,6 +415,59 @@ pub struct R1cs<F: Field> {
     pub x: Vec<F>,
 }
 
+/// The dimension of the square matrices, once padded to a power of two.
+pub fn padded_dim(num_constraints: usize, num_variables: usize) -> usize {
+    usize::max(num_constraints, num_variables).next_power_of_two()
+}
+
+impl<F: Field> R1cs<F> {
+    /// Return `true` if the matrices are square and their dimension is a power of two.
+    pub fn is_padded(&self) -> bool {
+        self.a.len() == self.z.len() && self.a.len().is_power_of_two()
+    }
+
+    /// Check that the instance is well formed:
+    /// the three matrices have the same number of rows,
+    /// $\vec z$ is the concatenation of $\vec x$ and $\vec w$,
+    /// and every entry of the matrices lies within the columns of $\vec z$.
+    ///
+    /// Satisfiability is not checked.
+    pub fn check(&self) -> GeminiResult<()> {
+        GeminiError::ensure_len("r1cs.b", self.a.len(), self.b.len())?;
+        GeminiError::ensure_len("r1cs.c", self.a.len(), self.c.len())?;
+        GeminiError::ensure_len("r1cs.z", self.x.len() + self.w.len(), self.z.len())?;
+        let num_variables = self.z.len();
+        let in_bounds = [&self.a, &self.b, &self.c]
+            .iter()
+            .all(|m| m.iter().flatten().all(|&(_, col)| col < num_variables));
+        if !in_bounds {
+            return Err(GeminiError::MalformedInstance {
+                reason: "matrix entry outside of the columns of z",
+            });
+        }
+        Ok(())
+    }
+
+    /// Return the instance padded to square matrices whose dimension is a power of two.
+    ///
+    /// The matrices are extended with empty constraints,
+    /// and the witness with zeros, which do not affect satisfiability.
+    /// No copy is made if the instance is already padded.
+    pub fn padded(&self) -> Cow<'_, Self> {
+        if self.is_padded() {
+            return Cow::Borrowed(self);
+        }
+        let dim = padded_dim(self.a.len(), self.z.len());
+        let mut padded = self.clone();
+        padded.a.resize(dim, Vec::new());
+        padded.b.resize(dim, Vec::new());
+        padded.c.resize(dim, Vec::new());
+        padded.z.resize(dim, F::zero());
+        padded.w.resize(dim - self.x.len(), F::zero());
+        Cow::Owned(padded)
+    }
+}
+
 impl<ConstraintF: Field> ConstraintSynthesizer<ConstraintF> for Circuit<ConstraintF> {
     fn generate_constraints(
         self,
@@ -150,27 +567,176 @@ impl<F: Field> ConstraintSynthesizer<F> for OutlineTestCircuit {
 }
 
 pub fn generate_relation<F: PrimeField, C: ConstraintSynthesizer<F>>(circuit: C) -> R1cs<F> {
+    try_generate_relation(circuit).expect("failed to synthesize the circuit")
+}
+
+/// Same as [`generate_relation`], but return the error if the synthesis fails.
+pub fn try_generate_relation<F: PrimeField, C: ConstraintSynthesizer<F>>(
+    circuit: C,
+) -> Result<R1cs<F>, SynthesisError> {
+    let pcs = synthesize(
+        circuit,
+        SynthesisMode::Prove {
+            construct_matrices: true,
+        },
+    )?;
+    R1cs::from_constraint_system(pcs).ok_or(SynthesisError::AssignmentMissing)
+}
+
+/// Synthesize the index of `circuit`, i.e. the R1CS instance without assignment:
+/// the vectors $\vec x$, $\vec w$, $\vec z$ have the right length, and are set to zero.
+pub fn generate_index<F: PrimeField, C: ConstraintSynthesizer<F>>(
+    circuit: C,
+) -> Result<R1cs<F>, SynthesisError> {
+    let pcs = synthesize(circuit, SynthesisMode::Setup)?;
+    let cs = pcs.borrow().ok_or(SynthesisError::MissingCS)?;
+    let matrices = cs.to_matrices().ok_or(SynthesisError::MissingCS)?;
+    let num_instance = cs.num_instance_variables;
+    let num_witness = cs.num_witness_variables;
+    Ok(R1cs {
+        a: matrices.a,
+        b: matrices.b,
+        c: matrices.c,
+        z: vec![F::zero(); num_instance + num_witness],
+        w: vec![F::zero(); num_witness],
+        x: vec![F::zero(); num_instance],
+    })
+}
+
+/// Generate the constraints of `circuit` in mode `mode`,
+/// padding the public input as expected by the indexer and the provers.
+fn synthesize<F: PrimeField, C: ConstraintSynthesizer<F>>(
+    circuit: C,
+    mode: SynthesisMode,
+) -> Result<ConstraintSystemRef<F>, SynthesisError> {
     let pcs = ConstraintSystem::new_ref();
     pcs.set_optimization_goal(OptimizationGoal::Weight);
     // pcs.set_optimization_goal(OptimizationGoal::Constraints);
-    pcs.set_mode(ark_relations::r1cs::SynthesisMode::Prove {
-        construct_matrices: true,
-    });
-    circuit.generate_constraints(pcs.clone()).unwrap();
+    pcs.set_mode(mode);
+    circuit.generate_constraints(pcs.clone())?;
     pad_input_for_indexer_and_prover(pcs.clone());
     pcs.finalize();
     // make_matrices_square_for_prover(pcs.clone());
-    let pcs = pcs.borrow().unwrap();
-    let statement = pcs.instance_assignment.as_slice();
-    let witness = pcs.witness_assignment.as_slice();
-    let matrices = pcs.to_matrices().expect("should not be `None`");
-    R1cs {
-        a: matrices.a,
-        b: matrices.b,
-        c: matrices.c,
-        z: statement.iter().chain(witness).cloned().collect(),
-        w: witness.to_vec(),
-        x: statement.to_vec(),
+    Ok(pcs)
+}
+
+/// Return the public input $\vec x$ of a circuit with `num_instance` instance variables,
+/// from the assignment `public_input` of the instance variables following the constant one.
+///
+/// The public input is padded with zeros, as in [`generate_relation`].
+#[cfg(feature = "snark-trait")]
+pub(crate) fn statement<F: Field>(public_input: &[F], num_instance: usize) -> GeminiResult<Vec<F>> {
+    if public_input.len() >= num_instance {
+        return Err(GeminiError::LengthMismatch {
+            name: "public_input",
+            expected: num_instance - 1,
+            found: public_input.len(),
+        });
+    }
+    let mut x = Vec::with_capacity(num_instance);
+    x.push(F::one());
+    x.extend_from_slice(public_input);
+    x.resize(num_instance, F::zero());
+    Ok(x)
+}
+
+impl<F: Field> R1cs<F> {
+    /// Extract the R1CS instance from the constraint system `cs`.
+    ///
+    /// The constraint system must be finalized and in proving mode, constructing matrices:
+    /// otherwise, `None` is returned.
+    /// The public input $\vec x$ is the instance assignment, starting with the constant one.
+    pub fn from_constraint_system(cs: ConstraintSystemRef<F>) -> Option<Self> {
+        let cs = cs.borrow()?;
+        let matrices = cs.to_matrices()?;
+        let statement = cs.instance_assignment.as_slice();
+        let witness = cs.witness_assignment.as_slice();
+        if statement.len() != cs.num_instance_variables || witness.len() != cs.num_witness_variables
+        {
+            return None;
+        }
+        Some(R1cs {
+            a: matrices.a,
+            b: matrices.b,
+            c: matrices.c,
+            z: statement.iter().chain(witness).cloned().collect(),
+            w: witness.to_vec(),
+            x: statement.to_vec(),
+        })
+    }
+}
+
+/// The streams of an R1CS instance, as expected by the elastic provers.
+///
+/// It owns the flattened matrices and vectors, and lends them out with [`R1csStreams::stream`].
+pub struct R1csStreams<F: Field> {
+    a_colmaj: Vec<MatrixElement<F>>,
+    b_colmaj: Vec<MatrixElement<F>>,
+    c_colmaj: Vec<MatrixElement<F>>,
+    a_rowmaj: Vec<MatrixElement<F>>,
+    b_rowmaj: Vec<MatrixElement<F>>,
+    c_rowmaj: Vec<MatrixElement<F>>,
+    z: Vec<F>,
+    w: Vec<F>,
+    z_a: Vec<F>,
+    z_b: Vec<F>,
+    z_c: Vec<F>,
+    num_constraints: usize,
+    joint_len: usize,
+}
+
+impl<F: Field> R1csStreams<F> {
+    /// Flatten the instance `r1cs` into streams.
+    pub fn new(r1cs: &R1cs<F>) -> Self {
+        let num_variables = r1cs.z.len();
+        let joint_len = crate::misc::sum_matrices(
+            &r1cs.a,
+            &r1cs.b,
+            &r1cs.c,
+            usize::max(r1cs.a.len(), num_variables),
+        )
+        .iter()
+        .map(|row| row.len())
+        .sum();
+        R1csStreams {
+            a_colmaj: matrix_into_colmaj(&r1cs.a, num_variables),
+            b_colmaj: matrix_into_colmaj(&r1cs.b, num_variables),
+            c_colmaj: matrix_into_colmaj(&r1cs.c, num_variables),
+            a_rowmaj: matrix_into_rowmaj(&r1cs.a),
+            b_rowmaj: matrix_into_rowmaj(&r1cs.b),
+            c_rowmaj: matrix_into_rowmaj(&r1cs.c),
+            z: r1cs.z.clone(),
+            w: r1cs.w.clone(),
+            z_a: product_matrix_vector(&r1cs.a, &r1cs.z),
+            z_b: product_matrix_vector(&r1cs.b, &r1cs.z),
+            z_c: product_matrix_vector(&r1cs.c, &r1cs.z),
+            num_constraints: r1cs.a.len(),
+            joint_len,
+        }
+    }
+
+    /// Return the streams of the instance.
+    ///
+    /// Column-major streams have one line per variable, and row-major streams one line per constraint.
+    pub fn stream(&self) -> R1csStream<Mat<&[MatrixElement<F>]>, Reverse<&[F]>, Reverse<&[F]>> {
+        let num_variables = self.z.len();
+        let colmaj = |m: &Vec<MatrixElement<F>>| Mat(m.as_slice(), num_variables);
+        let rowmaj = |m: &Vec<MatrixElement<F>>| Mat(m.as_slice(), self.num_constraints);
+        R1csStream {
+            a_colmaj: colmaj(&self.a_colmaj),
+            b_colmaj: colmaj(&self.b_colmaj),
+            c_colmaj: colmaj(&self.c_colmaj),
+            a_rowmaj: rowmaj(&self.a_rowmaj),
+            b_rowmaj: rowmaj(&self.b_rowmaj),
+            c_rowmaj: rowmaj(&self.c_rowmaj),
+            z: Reverse(self.z.as_slice()),
+            witness: Reverse(self.w.as_slice()),
+            z_a: Reverse(self.z_a.as_slice()),
+            z_b: Reverse(self.z_b.as_slice()),
+            z_c: Reverse(self.z_c.as_slice()),
+            nonzero: self.num_constraints,
+            joint_len: self.joint_len,
+        }
     }
 }
 
@@ -363,3 +929,99 @@ pub fn dummy_r1cs<F: Field>(rng: &mut impl RngCore, n: usize) -> R1cs<F> {
         x,
     }
 }
+
+#[test]
+fn test_check_satisfied() {
+    use ark_bls12_381::Fr;
+    use ark_std::One;
+
+    let rng = &mut ark_std::test_rng();
+    let circuit = random_circuit(rng, 1 << 4, 1 << 4);
+    let mut r1cs = generate_relation::<Fr, _>(circuit);
+    assert_eq!(R1csStreams::new(&r1cs).stream().check_satisfied(), Ok(()));
+
+    // tamper with the output d, only constrained by the last constraint.
+    r1cs.z[2] += Fr::one();
+    let az = product_matrix_vector(&r1cs.a, &r1cs.z);
+    let bz = product_matrix_vector(&r1cs.b, &r1cs.z);
+    let cz = product_matrix_vector(&r1cs.c, &r1cs.z);
+    let index = (0..r1cs.a.len()).find(|&i| az[i] * bz[i] != cz[i]).unwrap();
+    assert_eq!(index, r1cs.a.len() - 1);
+    let expected = Violation::Constraint {
+        index,
+        az: az[index],
+        bz: bz[index],
+        cz: cz[index],
+    };
+    assert_eq!(
+        R1csStreams::new(&r1cs).stream().check_satisfied(),
+        Err(expected)
+    );
+
+    // tamper with the streamed product.
+    let mut streams = R1csStreams::new(&r1cs);
+    streams.z_a[1] += Fr::one();
+    assert_eq!(
+        streams.stream().check_satisfied(),
+        Err(Violation::Product {
+            matrix: "A",
+            index: 1,
+            expected: az[1],
+            found: Some(az[1] + Fr::one()),
+        })
+    );
+}
+
+#[test]
+fn test_check_streams() {
+    use ark_bls12_381::Fr;
+
+    let rng = &mut ark_std::test_rng();
+    let circuit = random_circuit(rng, 1 << 4, 1 << 4);
+    let r1cs = generate_relation::<Fr, _>(circuit);
+    let streams = R1csStreams::new(&r1cs);
+    assert!(streams.stream().check_streams().is_ok());
+
+    // a row-major stream missing its last line.
+    let mut truncated = R1csStreams::new(&r1cs);
+    truncated.a_rowmaj.pop();
+    assert_eq!(
+        truncated.stream().check_streams(),
+        Err(GeminiError::LengthMismatch {
+            name: "r1cs.a_rowmaj",
+            expected: r1cs.a.len(),
+            found: r1cs.a.len() - 1,
+        })
+    );
+
+    // a column-major entry outside of the constraints.
+    let mut out_of_bounds = R1csStreams::new(&r1cs);
+    let entry = out_of_bounds
+        .b_colmaj
+        .iter_mut()
+        .find(|e| !e.is_eol())
+        .unwrap();
+    if let MatrixElement::Element((_, row)) = entry {
+        *row = r1cs.a.len();
+    }
+    assert_eq!(
+        out_of_bounds.stream().check_streams(),
+        Err(GeminiError::IndexOutOfBounds {
+            name: "r1cs.b_colmaj",
+            index: r1cs.a.len(),
+            bound: r1cs.a.len(),
+        })
+    );
+
+    // a product stream missing an element.
+    let mut short = R1csStreams::new(&r1cs);
+    short.z_c.pop();
+    assert_eq!(
+        short.stream().check_streams(),
+        Err(GeminiError::LengthMismatch {
+            name: "r1cs.z_c",
+            expected: r1cs.a.len(),
+            found: r1cs.a.len() - 1,
+        })
+    );
+}
@@ -0,0 +1,182 @@
+//! Configuration of the provers.
+//!
+//! A [`ProverConfig`] collects the buffer sizes and the resources available to the provers,
+//! and is threaded down to the commitment scheme and to the subprotocols.
+//! The proof does not depend on the configuration.
+//!
+//! With the `parallel` feature, the provers run on a thread pool of [`ProverConfig::num_threads`] threads,
+//! or else on the current [rayon](https://docs.rs/rayon/latest/rayon/) pool.
+//! Provers sharing the machine with other workloads (or pinning their threads to NUMA nodes)
+//! can thus supply their own pool, by proving within
+//! [`ThreadPool::install`](https://docs.rs/rayon/latest/rayon/struct.ThreadPool.html#method.install).
+
+use ark_ec::pairing::Pairing;
+
+use crate::psnark::memory::{commit_joint_buffer, msm_buffer, sumcheck_buffer, BATCHED_SUMCHECKS};
+use crate::SPACE_TIME_THRESHOLD;
+
+/// The default number of pairs held by a streaming multi-scalar multiplication.
+pub(crate) const DEFAULT_MSM_BUFFER: usize = 1 << 20;
+/// The default number of elements collected at once by a streaming commitment.
+pub(crate) const DEFAULT_CHUNK_SIZE: usize = 1 << 20;
+/// The default number of coefficients folded at once by the streams of folded polynomials.
+pub(crate) const DEFAULT_FOLDING_BLOCK: usize = 1 << 12;
+
+/// The resources available to the provers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProverConfig {
+    /// The maximum number of (base, scalar) pairs held by a streaming multi-scalar multiplication.
+    pub max_msm_buffer: usize,
+    /// The number of elements collected at once by a streaming commitment.
+    pub chunk_size: usize,
+    /// The number of threads of the provers,
+    /// or `None` for the threads of the current pool (by default, all available threads).
+    /// Ignored without the `parallel` feature.
+    pub num_threads: Option<usize>,
+    /// The memory budget (in field elements) of the elastic provers:
+    /// each subprotocol switches to the time-efficient prover once its state fits the budget,
+    /// and the elastic preprocessing prover holds in memory the index streams
+    /// fitting the size in bytes of `memory_budget` field elements.
+    pub memory_budget: usize,
+    /// The number of coefficients read and folded at once by the space-efficient sumcheck provers
+    /// (rounded up to a power of two).
+    pub folding_block: usize,
+    /// The hard cap (in bytes) on the buffers of the elastic preprocessing prover, or `None` for no cap.
+    /// The prover fails before proving, with
+    /// [`GeminiError::MemoryCapExceeded`](crate::errors::GeminiError::MemoryCapExceeded),
+    /// if the buffers of any phase may exceed the cap.
+    pub memory_cap: Option<usize>,
+}
+
+impl Default for ProverConfig {
+    fn default() -> Self {
+        ProverConfig {
+            max_msm_buffer: DEFAULT_MSM_BUFFER,
+            chunk_size: DEFAULT_CHUNK_SIZE,
+            num_threads: None,
+            memory_budget: 1 << SPACE_TIME_THRESHOLD,
+            folding_block: DEFAULT_FOLDING_BLOCK,
+            memory_cap: None,
+        }
+    }
+}
+
+/// Return the largest power of two `n` such that `size(n)` fits `cap`, or one if none does.
+fn largest_fitting(cap: usize, size: impl Fn(usize) -> usize) -> usize {
+    let mut n = 1usize;
+    while n <= usize::MAX / 4 && size(2 * n) <= cap {
+        n *= 2;
+    }
+    n
+}
+
+impl ProverConfig {
+    /// The configuration enforcing a hard cap of `cap` bytes
+    /// over the buffers of the elastic preprocessing prover over the pairing `E`,
+    /// with the largest buffers, chunks, and memory budget (in powers of two) fitting the cap.
+    ///
+    /// The cap bounds the buffers of the prover, and not the instance or the key held by the caller.
+    pub fn with_memory_cap<E: Pairing>(cap: usize) -> Self {
+        let max_msm_buffer = largest_fitting(cap, msm_buffer::<E>);
+        let chunk_size = largest_fitting(cap, |n| commit_joint_buffer::<E>(n, n, 3));
+        let memory_budget = largest_fitting(cap, |n| {
+            BATCHED_SUMCHECKS * sumcheck_buffer::<E>(n, n.ilog2() as usize)
+        });
+        ProverConfig {
+            max_msm_buffer,
+            chunk_size,
+            memory_budget,
+            memory_cap: Some(cap),
+            ..ProverConfig::default()
+        }
+    }
+
+    /// The logarithm of the memory budget,
+    /// i.e. the number of rounds run with the time-efficient prover.
+    pub(crate) fn threshold(&self) -> usize {
+        self.memory_budget.checked_ilog2().unwrap_or(0) as usize
+    }
+
+    /// Run `f` on a new pool of `num_threads` threads,
+    /// or on the current pool if `num_threads` is `None`.
+    ///
+    /// # Panics
+    /// If the pool cannot be built.
+    #[cfg(feature = "parallel")]
+    pub fn install<R, F>(&self, f: F) -> R
+    where
+        R: Send,
+        F: FnOnce() -> R + Send,
+    {
+        match self.num_threads {
+            Some(num_threads) => rayon::ThreadPoolBuilder::new()
+                .num_threads(num_threads)
+                .build()
+                .expect("unable to build the thread pool")
+                .install(f),
+            None => f(),
+        }
+    }
+
+    /// Run `f` on the current thread.
+    #[cfg(not(feature = "parallel"))]
+    pub fn install<R, F>(&self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        f()
+    }
+}
+
+#[test]
+fn test_with_memory_cap() {
+    use ark_bls12_381::Bls12_381;
+
+    use crate::psnark::MemoryReport;
+
+    for cap in [0, 1 << 16, 1 << 30] {
+        let config = ProverConfig::with_memory_cap::<Bls12_381>(cap);
+        assert_eq!(config.memory_cap, Some(cap));
+        let bound = MemoryReport::bound::<Bls12_381>(1 << 20, 1 << 22, &config);
+        assert_eq!(bound.ensure_cap(cap).is_ok(), cap != 0);
+    }
+    // larger caps allow larger buffers.
+    let small = ProverConfig::with_memory_cap::<Bls12_381>(1 << 16);
+    let large = ProverConfig::with_memory_cap::<Bls12_381>(1 << 30);
+    assert!(small.max_msm_buffer < large.max_msm_buffer);
+    assert!(small.chunk_size < large.chunk_size);
+    assert!(small.memory_budget < large.memory_budget);
+}
+
+#[test]
+fn test_threshold() {
+    let config = ProverConfig::default();
+    assert_eq!(config.threshold(), SPACE_TIME_THRESHOLD);
+
+    let config = ProverConfig {
+        memory_budget: 0,
+        ..config
+    };
+    assert_eq!(config.threshold(), 0);
+}
+
+#[cfg(feature = "parallel")]
+#[test]
+fn test_install() {
+    let config = ProverConfig {
+        num_threads: Some(3),
+        ..ProverConfig::default()
+    };
+    assert_eq!(config.install(rayon::current_num_threads), 3);
+
+    // without a number of threads, the current pool is used.
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+    let config = ProverConfig::default();
+    assert_eq!(
+        pool.install(|| config.install(rayon::current_num_threads)),
+        2
+    );
+}
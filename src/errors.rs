@@ -1,16 +1,263 @@
 //! Collection of errors and falurest in the proof system.
 
+use ark_relations::r1cs::SynthesisError;
 use ark_std::fmt;
+use ark_std::string::{String, ToString};
 
 /// Error identifying a failure in the proof verification.
-#[derive(Debug, Clone)]
-pub struct VerificationError;
+///
+/// Each variant pinpoints the check that failed,
+/// together with the values that were compared (if any).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationError {
+    /// The final foldings of the sumcheck `sumcheck` do not match
+    /// the claim `expected` reduced from the round messages.
+    Sumcheck {
+        /// The sumcheck that failed, e.g. `"first sumcheck"`.
+        sumcheck: &'static str,
+        /// The claim reduced from the prover's round messages.
+        expected: String,
+        /// The (batched) product of the final foldings sent by the prover.
+        found: String,
+    },
+    /// The last folding of the tensorcheck instance `instance`
+    /// does not match the (batched) asserted evaluation `expected`.
+    Tensorcheck {
+        /// The index of the tensorcheck instance that failed.
+        instance: usize,
+        /// The batched evaluation asserted by the sumcheck.
+        expected: String,
+        /// The evaluation reconstructed from the folded polynomials.
+        found: String,
+    },
+    /// The pairing equation of the evaluation proof `opening` does not hold.
+    Opening {
+        /// The opening that failed, e.g. `"tensorcheck opening"`.
+        opening: &'static str,
+    },
+    /// The consistency equation `check` between the proof and the instance does not hold.
+    Consistency {
+        /// The consistency check that failed.
+        check: &'static str,
+    },
+}
+
+impl VerificationError {
+    /// A failure of a sumcheck, reducing to `expected` and concluding with `found`.
+    pub(crate) fn sumcheck<F: fmt::Display>(expected: F, found: F) -> Self {
+        VerificationError::Sumcheck {
+            sumcheck: "sumcheck",
+            expected: expected.to_string(),
+            found: found.to_string(),
+        }
+    }
+
+    /// A failure of a pairing check.
+    pub(crate) fn opening() -> Self {
+        VerificationError::Opening {
+            opening: "evaluation proof",
+        }
+    }
+
+    /// Name the subprotocol `name` (a sumcheck, or an opening) in which the error occurred.
+    pub(crate) fn within(self, name: &'static str) -> Self {
+        match self {
+            VerificationError::Sumcheck {
+                expected, found, ..
+            } => VerificationError::Sumcheck {
+                sumcheck: name,
+                expected,
+                found,
+            },
+            VerificationError::Opening { .. } => VerificationError::Opening { opening: name },
+            e => e,
+        }
+    }
+}
 
 impl fmt::Display for VerificationError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Verification Error.")
+        match self {
+            VerificationError::Sumcheck {
+                sumcheck,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Verification Error: {sumcheck} failed (expected {expected}, found {found})."
+            ),
+            VerificationError::Tensorcheck {
+                instance,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Verification Error: tensorcheck instance {instance} failed (expected {expected}, found {found})."
+            ),
+            VerificationError::Opening { opening } => {
+                write!(f, "Verification Error: {opening} failed.")
+            }
+            VerificationError::Consistency { check } => {
+                write!(f, "Verification Error: {check} does not hold.")
+            }
+        }
     }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for VerificationError {}
+
 /// Verification result.
 pub type VerificationResult = ark_std::result::Result<(), VerificationError>;
+
+/// Error returned by the public API of the crate.
+///
+/// The provers check their inputs before starting,
+/// hence a malformed instance or a committer key that is too short
+/// is reported with an error instead of a panic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GeminiError {
+    /// The committer key holds `available` powers, while the proof requires `required` of them.
+    CommitterKeyTooShort {
+        /// The number of powers required.
+        required: usize,
+        /// The number of powers in the committer key.
+        available: usize,
+    },
+    /// The stream (or vector) `name` has `found` elements, while `expected` are required.
+    LengthMismatch {
+        /// The stream that has the wrong length, e.g. `"r1cs.z_a"`.
+        name: &'static str,
+        /// The required length.
+        expected: usize,
+        /// The actual length.
+        found: usize,
+    },
+    /// The stream `name` holds the index `index`, while indices must be less than `bound`.
+    IndexOutOfBounds {
+        /// The stream holding the index, e.g. `"r1cs.a_rowmaj"`.
+        name: &'static str,
+        /// The index out of bounds.
+        index: usize,
+        /// The bound on the indices.
+        bound: usize,
+    },
+    /// The instance is malformed, because of `reason`.
+    MalformedInstance {
+        /// The reason why the instance is malformed.
+        reason: &'static str,
+    },
+    /// The buffers of the prover phase `phase` may take `required` bytes,
+    /// exceeding the memory cap of `cap` bytes.
+    MemoryCapExceeded {
+        /// The phase exceeding the cap, e.g. `"sumchecks"`.
+        phase: &'static str,
+        /// The size (in bytes) of the buffers of the phase.
+        required: usize,
+        /// The memory cap (in bytes).
+        cap: usize,
+    },
+    /// Reading the stream `name` failed, with the error `message`
+    /// (see `iterable::Fallible`).
+    Io {
+        /// The stream that could not be read, e.g. `"r1cs.z"`.
+        name: &'static str,
+        /// The description of the read error.
+        message: String,
+    },
+    /// The verification of a proof failed.
+    Verification(VerificationError),
+    /// The synthesis of a circuit failed.
+    Synthesis(SynthesisError),
+}
+
+impl GeminiError {
+    /// Fail unless `name` has exactly `expected` elements.
+    pub(crate) fn ensure_len(
+        name: &'static str,
+        expected: usize,
+        found: usize,
+    ) -> GeminiResult<()> {
+        if expected == found {
+            Ok(())
+        } else {
+            Err(GeminiError::LengthMismatch {
+                name,
+                expected,
+                found,
+            })
+        }
+    }
+
+    /// Fail unless the committer key, holding `available` powers, has at least `required` of them.
+    pub(crate) fn ensure_key(required: usize, available: usize) -> GeminiResult<()> {
+        if required <= available {
+            Ok(())
+        } else {
+            Err(GeminiError::CommitterKeyTooShort {
+                required,
+                available,
+            })
+        }
+    }
+}
+
+impl From<VerificationError> for GeminiError {
+    fn from(e: VerificationError) -> Self {
+        GeminiError::Verification(e)
+    }
+}
+
+impl From<SynthesisError> for GeminiError {
+    fn from(e: SynthesisError) -> Self {
+        GeminiError::Synthesis(e)
+    }
+}
+
+impl fmt::Display for GeminiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeminiError::CommitterKeyTooShort {
+                required,
+                available,
+            } => write!(
+                f,
+                "Committer key too short: {required} powers required, {available} available."
+            ),
+            GeminiError::LengthMismatch {
+                name,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Length mismatch: {name} has {found} elements, expected {expected}."
+            ),
+            GeminiError::IndexOutOfBounds { name, index, bound } => write!(
+                f,
+                "Index out of bounds: {name} holds the index {index}, the bound is {bound}."
+            ),
+            GeminiError::MalformedInstance { reason } => {
+                write!(f, "Malformed instance: {reason}.")
+            }
+            GeminiError::MemoryCapExceeded {
+                phase,
+                required,
+                cap,
+            } => write!(
+                f,
+                "Memory cap exceeded: {phase} may take {required} bytes, the cap is {cap}."
+            ),
+            GeminiError::Io { name, message } => {
+                write!(f, "IO error: reading {name} failed: {message}.")
+            }
+            GeminiError::Verification(e) => e.fmt(f),
+            GeminiError::Synthesis(e) => write!(f, "Synthesis error: {e}."),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for GeminiError {}
+
+/// Result of the public API of the crate.
+pub type GeminiResult<T> = ark_std::result::Result<T, GeminiError>;
@@ -0,0 +1,452 @@
+//! A C interface to the preprocessing SNARK over BLS12-381.
+//!
+//! Circuits are read from [circom](crate::circom) files:
+//! [`gemini_setup`] indexes the `.r1cs` file and returns opaque handles to the keys,
+//! [`gemini_prove`] proves the assignment of a `.wtns` file, and returns the proof in a byte buffer,
+//! and [`gemini_verify`] checks a proof against the public input.
+//! Keys can be exported to, and imported from, byte buffers.
+//!
+//! Every function returns a [`GeminiStatus`], and writes its results through out-pointers.
+//! Handles and buffers are owned by the caller once returned,
+//! and must be released with the matching `_free` function.
+//! Panics are caught at the boundary, and reported as [`GeminiStatus::Panic`].
+//! The declarations for C are in `include/gemini.h`.
+#![allow(unsafe_code)]
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_ff::Zero;
+use ark_serialize::*;
+use ark_std::boxed::Box;
+use ark_std::vec::Vec;
+use rand::rngs::OsRng;
+use std::ffi::CStr;
+use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+
+use crate::circom::{CircomR1cs, CircomWitness};
+use crate::circuit::{R1cs, R1csStreams};
+use crate::iterable::{Iterable, Reverse};
+use crate::kzg::{Commitment, CommitterKey, VerifierKey};
+use crate::psnark::{IndexCache, Proof, R1csStats};
+
+/// The status returned by the functions of the C interface.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GeminiStatus {
+    /// The call succeeded (and, for [`gemini_verify`], the proof is valid).
+    Ok = 0,
+    /// A required pointer argument is null.
+    NullPointer = 1,
+    /// A path is not valid UTF-8.
+    InvalidPath = 2,
+    /// A file could not be read.
+    Io = 3,
+    /// A buffer does not hold a valid key, proof, or public input.
+    Serialization = 4,
+    /// The prover rejected the instance (e.g., the key is too short for it).
+    Prover = 5,
+    /// The proof is not valid.
+    InvalidProof = 6,
+    /// The call panicked.
+    Panic = 7,
+}
+
+/// A byte buffer allocated by this library, to be released with [`gemini_buffer_free`].
+#[repr(C)]
+pub struct GeminiBuffer {
+    /// The bytes of the buffer.
+    pub data: *mut u8,
+    /// The length of the buffer.
+    pub len: usize,
+}
+
+impl From<Vec<u8>> for GeminiBuffer {
+    fn from(bytes: Vec<u8>) -> Self {
+        let len = bytes.len();
+        let data = Box::into_raw(bytes.into_boxed_slice()) as *mut u8;
+        GeminiBuffer { data, len }
+    }
+}
+
+/// The opaque proving key: the committer key and the index of the circuit.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct GeminiProvingKey {
+    ck: CommitterKey<Bls12_381>,
+    cache: IndexCache<Bls12_381>,
+}
+
+/// The opaque verifying key: the verification key and the commitments to the index.
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct GeminiVerifyingKey {
+    vk: VerifierKey<Bls12_381>,
+    index_comms: Vec<Commitment<Bls12_381>>,
+    num_non_zero: usize,
+    num_constraints: usize,
+    num_variables: usize,
+    num_statement: usize,
+}
+
+type FfiResult<T> = Result<T, GeminiStatus>;
+
+/// Run `f`, catching panics.
+fn guard(f: impl FnOnce() -> FfiResult<()>) -> GeminiStatus {
+    match panic::catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(())) => GeminiStatus::Ok,
+        Ok(Err(status)) => status,
+        Err(_) => GeminiStatus::Panic,
+    }
+}
+
+/// Read the path at `path`.
+///
+/// # Safety
+///
+/// `path` must be null or point to a nul-terminated string.
+unsafe fn read_path<'a>(path: *const c_char) -> FfiResult<&'a str> {
+    if path.is_null() {
+        return Err(GeminiStatus::NullPointer);
+    }
+    CStr::from_ptr(path)
+        .to_str()
+        .map_err(|_| GeminiStatus::InvalidPath)
+}
+
+/// Read the buffer of `len` bytes at `data`.
+///
+/// # Safety
+///
+/// `data` must be null or point to `len` readable bytes.
+unsafe fn read_bytes<'a>(data: *const u8, len: usize) -> FfiResult<&'a [u8]> {
+    if data.is_null() {
+        return Err(GeminiStatus::NullPointer);
+    }
+    Ok(slice::from_raw_parts(data, len))
+}
+
+/// Read the instance at `r1cs_path` (with the assignment at `wtns_path`, if any).
+fn read_instance(r1cs_path: &str, wtns_path: Option<&str>) -> FfiResult<(R1cs<Fr>, usize)> {
+    let circom_r1cs = CircomR1cs::<Fr>::open(r1cs_path).map_err(|_| GeminiStatus::Io)?;
+    let z = match wtns_path {
+        Some(path) => {
+            let witness = CircomWitness::<Fr>::open(path).map_err(|_| GeminiStatus::Io)?;
+            let mut z = witness.z().iter().collect::<Vec<_>>();
+            z.reverse();
+            z
+        }
+        None => vec![Fr::zero(); circom_r1cs.num_variables()],
+    };
+    let r1cs = circom_r1cs
+        .relation(z)
+        .map_err(|_| GeminiStatus::Serialization)?;
+    Ok((r1cs, circom_r1cs.num_statement()))
+}
+
+fn serialize(value: &impl CanonicalSerialize) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    value
+        .serialize_compressed(&mut bytes)
+        .expect("serialization into a vector cannot fail");
+    bytes
+}
+
+/// Index the circuit of the circom file at `r1cs_path`,
+/// with a committer key sampled from the randomness of the operating system.
+///
+/// On success, `pk` and `vk` hold the new keys.
+///
+/// # Safety
+///
+/// `r1cs_path` must be a nul-terminated string,
+/// and `pk`, `vk` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_setup(
+    r1cs_path: *const c_char,
+    pk: *mut *mut GeminiProvingKey,
+    vk: *mut *mut GeminiVerifyingKey,
+) -> GeminiStatus {
+    guard(|| {
+        if pk.is_null() || vk.is_null() {
+            return Err(GeminiStatus::NullPointer);
+        }
+        let (index, num_statement) = read_instance(read_path(r1cs_path)?, None)?;
+        let stats = R1csStats::from_stream(&R1csStreams::new(&index).stream());
+        let ck = stats.committer_key(&mut OsRng);
+        let cache = IndexCache::new(&index, &ck);
+        let verifying_key = GeminiVerifyingKey {
+            vk: VerifierKey::from(&ck),
+            index_comms: cache.commitments().to_vec(),
+            num_non_zero: cache.num_non_zero(),
+            num_constraints: index.a.len(),
+            num_variables: index.z.len(),
+            num_statement,
+        };
+        *pk = Box::into_raw(Box::new(GeminiProvingKey { ck, cache }));
+        *vk = Box::into_raw(Box::new(verifying_key));
+        Ok(())
+    })
+}
+
+/// Prove the assignment of the circom file at `wtns_path` for the circuit at `r1cs_path`,
+/// indexed in `pk`.
+///
+/// On success, `proof` holds the proof, serialized with [`Proof::serialize_versioned`].
+///
+/// # Safety
+///
+/// `pk` must be a handle returned by this library,
+/// `r1cs_path` and `wtns_path` must be nul-terminated strings,
+/// and `proof` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_prove(
+    pk: *const GeminiProvingKey,
+    r1cs_path: *const c_char,
+    wtns_path: *const c_char,
+    proof: *mut GeminiBuffer,
+) -> GeminiStatus {
+    guard(|| {
+        let pk = pk.as_ref().ok_or(GeminiStatus::NullPointer)?;
+        if proof.is_null() {
+            return Err(GeminiStatus::NullPointer);
+        }
+        let (r1cs, _) = read_instance(read_path(r1cs_path)?, Some(read_path(wtns_path)?))?;
+        let snark =
+            Proof::new_time_cached(&r1cs, &pk.ck, &pk.cache).map_err(|_| GeminiStatus::Prover)?;
+        let mut bytes = Vec::new();
+        snark
+            .serialize_versioned(&mut bytes)
+            .map_err(|_| GeminiStatus::Serialization)?;
+        *proof = bytes.into();
+        Ok(())
+    })
+}
+
+/// Verify the proof of `proof_len` bytes at `proof` for the public input at `public_input`,
+/// the concatenation of `num_public` field elements (without the constant one),
+/// each serialized as 32 little-endian bytes.
+///
+/// Return [`GeminiStatus::Ok`] if the proof is valid,
+/// and [`GeminiStatus::InvalidProof`] if it is not.
+///
+/// # Safety
+///
+/// `vk` must be a handle returned by this library,
+/// `proof` must point to `proof_len` readable bytes,
+/// and `public_input` to `32 * num_public` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_verify(
+    vk: *const GeminiVerifyingKey,
+    proof: *const u8,
+    proof_len: usize,
+    public_input: *const u8,
+    num_public: usize,
+) -> GeminiStatus {
+    guard(|| {
+        let vk = vk.as_ref().ok_or(GeminiStatus::NullPointer)?;
+        let snark = Proof::<Bls12_381>::deserialize_versioned(read_bytes(proof, proof_len)?)
+            .map_err(|_| GeminiStatus::Serialization)?;
+        if num_public + 1 != vk.num_statement {
+            return Err(GeminiStatus::Serialization);
+        }
+        let mut x = vec![Fr::from(1u64)];
+        for element in read_bytes(public_input, 32 * num_public)?.chunks(32) {
+            x.push(Fr::deserialize_compressed(element).map_err(|_| GeminiStatus::Serialization)?);
+        }
+        snark
+            .verify_streaming(
+                vk.num_constraints,
+                vk.num_variables,
+                &Reverse(x.as_slice()),
+                &vk.vk,
+                &vk.index_comms,
+                vk.num_non_zero,
+            )
+            .map_err(|_| GeminiStatus::InvalidProof)
+    })
+}
+
+/// Export `pk` into `buffer`.
+///
+/// # Safety
+///
+/// `pk` must be a handle returned by this library, and `buffer` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_proving_key_serialize(
+    pk: *const GeminiProvingKey,
+    buffer: *mut GeminiBuffer,
+) -> GeminiStatus {
+    guard(|| {
+        let pk = pk.as_ref().ok_or(GeminiStatus::NullPointer)?;
+        let buffer = buffer.as_mut().ok_or(GeminiStatus::NullPointer)?;
+        *buffer = serialize(pk).into();
+        Ok(())
+    })
+}
+
+/// Import the proving key of `len` bytes at `data` into `pk`.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes, and `pk` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_proving_key_deserialize(
+    data: *const u8,
+    len: usize,
+    pk: *mut *mut GeminiProvingKey,
+) -> GeminiStatus {
+    guard(|| {
+        let pk = pk.as_mut().ok_or(GeminiStatus::NullPointer)?;
+        let key = GeminiProvingKey::deserialize_compressed(read_bytes(data, len)?)
+            .map_err(|_| GeminiStatus::Serialization)?;
+        *pk = Box::into_raw(Box::new(key));
+        Ok(())
+    })
+}
+
+/// Export `vk` into `buffer`.
+///
+/// # Safety
+///
+/// `vk` must be a handle returned by this library, and `buffer` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_verifying_key_serialize(
+    vk: *const GeminiVerifyingKey,
+    buffer: *mut GeminiBuffer,
+) -> GeminiStatus {
+    guard(|| {
+        let vk = vk.as_ref().ok_or(GeminiStatus::NullPointer)?;
+        let buffer = buffer.as_mut().ok_or(GeminiStatus::NullPointer)?;
+        *buffer = serialize(vk).into();
+        Ok(())
+    })
+}
+
+/// Import the verifying key of `len` bytes at `data` into `vk`.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes, and `vk` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_verifying_key_deserialize(
+    data: *const u8,
+    len: usize,
+    vk: *mut *mut GeminiVerifyingKey,
+) -> GeminiStatus {
+    guard(|| {
+        let vk = vk.as_mut().ok_or(GeminiStatus::NullPointer)?;
+        let key = GeminiVerifyingKey::deserialize_compressed(read_bytes(data, len)?)
+            .map_err(|_| GeminiStatus::Serialization)?;
+        *vk = Box::into_raw(Box::new(key));
+        Ok(())
+    })
+}
+
+/// Release the proving key `pk`.
+///
+/// # Safety
+///
+/// `pk` must be null or a handle returned by this library, not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_proving_key_free(pk: *mut GeminiProvingKey) {
+    if !pk.is_null() {
+        drop(Box::from_raw(pk));
+    }
+}
+
+/// Release the verifying key `vk`.
+///
+/// # Safety
+///
+/// `vk` must be null or a handle returned by this library, not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_verifying_key_free(vk: *mut GeminiVerifyingKey) {
+    if !vk.is_null() {
+        drop(Box::from_raw(vk));
+    }
+}
+
+/// Release the buffer `buffer`.
+///
+/// # Safety
+///
+/// `buffer` must have been returned by this library, and not yet released.
+#[no_mangle]
+pub unsafe extern "C" fn gemini_buffer_free(buffer: GeminiBuffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(slice::from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
+
+#[test]
+fn test_ffi() {
+    use std::ffi::CString;
+    use std::ptr;
+
+    use crate::circom::write_circom_files;
+    use crate::circuit::{generate_relation, random_circuit};
+
+    let rng = &mut ark_std::test_rng();
+    let r1cs = generate_relation::<Fr, _>(random_circuit(rng, 1 << 6, 1 << 6));
+    let element = |bytes: &mut Vec<u8>, e: &Fr| e.serialize_uncompressed(bytes).unwrap();
+    let (r1cs_path, wtns_path) = write_circom_files(&r1cs, "gemini-test-ffi");
+
+    let r1cs_path = CString::new(r1cs_path.to_str().unwrap()).unwrap();
+    let wtns_path = CString::new(wtns_path.to_str().unwrap()).unwrap();
+    let mut public_input = Vec::new();
+    r1cs.x[1..]
+        .iter()
+        .for_each(|e| element(&mut public_input, e));
+    let num_public = r1cs.x.len() - 1;
+
+    unsafe {
+        let mut pk = ptr::null_mut();
+        let mut vk = ptr::null_mut();
+        assert_eq!(
+            gemini_setup(r1cs_path.as_ptr(), &mut pk, &mut vk),
+            GeminiStatus::Ok
+        );
+        let mut proof = GeminiBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        };
+        assert_eq!(
+            gemini_prove(pk, r1cs_path.as_ptr(), wtns_path.as_ptr(), &mut proof),
+            GeminiStatus::Ok
+        );
+
+        // the verifying key survives a round trip through a buffer.
+        let mut vk_bytes = GeminiBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        };
+        assert_eq!(
+            gemini_verifying_key_serialize(vk, &mut vk_bytes),
+            GeminiStatus::Ok
+        );
+        let mut imported_vk = ptr::null_mut();
+        assert_eq!(
+            gemini_verifying_key_deserialize(vk_bytes.data, vk_bytes.len, &mut imported_vk),
+            GeminiStatus::Ok
+        );
+        let verify = |vk: *const GeminiVerifyingKey, public_input: &[u8]| {
+            gemini_verify(vk, proof.data, proof.len, public_input.as_ptr(), num_public)
+        };
+        assert_eq!(verify(imported_vk, &public_input), GeminiStatus::Ok);
+        let mut wrong_input = public_input.clone();
+        wrong_input[0] ^= 1;
+        assert_eq!(verify(vk, &wrong_input), GeminiStatus::InvalidProof);
+        assert_eq!(
+            gemini_setup(ptr::null(), &mut pk, &mut vk),
+            GeminiStatus::NullPointer
+        );
+
+        gemini_buffer_free(proof);
+        gemini_buffer_free(vk_bytes);
+        gemini_proving_key_free(pk);
+        gemini_verifying_key_free(vk);
+        gemini_verifying_key_free(imported_vk);
+    }
+}
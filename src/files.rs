@@ -0,0 +1,584 @@
+//! Proving and verifying from files, with the elastic prover.
+//!
+//! The elastic prover consumes a dozen streams: the matrices of the index in row- and column-major order,
+//! the vector $\vec z$ and the witness, and the products $A\vec z$, $B\vec z$, $C\vec z$.
+//! This module assembles them from the files of a [circom](https://docs.circom.io/) circuit:
+//! - [`index_from_files`] reads the index (a `.r1cs` file) and the SRS (an [`SrsFile`]),
+//!     and writes the verifier key (see [`write_verifier_key`]);
+//! - [`prove_from_files`] reads the index, the assignment (a `.wtns` file), and the SRS,
+//!     and writes the proof (see [`Proof::serialize_versioned`]);
+//! - [`verify_from_files`] reads the index, the verifier key, the public input
+//!     (a `public.json` file, as exported by snarkjs), and the proof.
+//!
+//! The column-major matrices are transposed in external memory, and then transposed back,
+//! so that all matrix streams are read from disk.
+//! The products are computed once, row by row, and spooled to temporary files along with $\vec z$.
+//! The entries of $\vec z$ are read from the `.wtns` file by random access (see [`CircomWires`]),
+//! holding a single block of wires in memory:
+//! neither while spooling nor during the proof are vectors held in memory, only the buffers of the streams.
+//! The spools are read through [`Fallible`] streams, hence a failure to read them
+//! fails [`prove_from_files`] with an error instead of aborting the process.
+use ark_ec::pairing::Pairing;
+use ark_ec::AffineRepr;
+use ark_ff::{One, PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::borrow::Borrow;
+use ark_std::marker::PhantomData;
+use ark_std::string::ToString;
+use ark_std::vec::Vec;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::circom::{CircomR1cs, CircomWires, CircomWitness};
+use crate::circuit::{padded_dim, R1csStream};
+use crate::config::ProverConfig;
+use crate::iterable::transpose::transpose;
+use crate::iterable::{
+    Fallible, Iterable, MatrixPadded, RandomAccessIterable, ReadFailures, ZeroPadded,
+};
+use crate::json::{scalar_from, Json};
+use crate::kzg::{CommitterKeyStream, VerifierKey};
+use crate::misc::MatrixElement;
+use crate::psnark::{index_commitments, read_verifier_key, write_verifier_key, Proof, R1csStats};
+
+/// The number of elements read at once from the files.
+const BLOCK_SIZE: usize = 1 << 12;
+
+/// A counter for naming the spooled streams uniquely within the process.
+static SPOOL_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+fn invalid_data(e: impl ToString) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+/// The configuration of [`index_from_files`] and [`prove_from_files`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FilesConfig {
+    /// The configuration of the elastic prover.
+    pub prover: ProverConfig,
+    /// The number of matrix entries held in memory when transposing the index.
+    pub transpose_buffer: usize,
+}
+
+impl Default for FilesConfig {
+    fn default() -> Self {
+        FilesConfig {
+            prover: ProverConfig::default(),
+            transpose_buffer: 1 << 20,
+        }
+    }
+}
+
+/// A committer key stored in a file, as written by
+/// [`CanonicalSerialize::serialize_uncompressed`] for [`CommitterKey`](crate::kzg::CommitterKey).
+///
+/// The powers in \\(\GG_2\\) are read when opening the file,
+/// while the powers in \\(\GG_1\\) are streamed from the file (from the highest, see [`SrsFile::committer_key`]).
+/// Elements are checked (e.g., for subgroup membership) when read.
+pub struct SrsFile<E: Pairing> {
+    path: PathBuf,
+    len: usize,
+    element_size: usize,
+    powers_of_g2: Vec<E::G2Affine>,
+}
+
+impl<E: Pairing> SrsFile<E> {
+    /// Open the committer key at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut reader = BufReader::new(File::open(&path)?);
+        let len = u64::deserialize_uncompressed(&mut reader).map_err(invalid_data)? as usize;
+        let element_size = E::G1Affine::zero().uncompressed_size();
+        let offset = 0u64.uncompressed_size() + len * element_size;
+        reader.seek(SeekFrom::Start(offset as u64))?;
+        let powers_of_g2 = Vec::deserialize_uncompressed(&mut reader).map_err(invalid_data)?;
+        Ok(SrsFile {
+            path,
+            len,
+            element_size,
+            powers_of_g2,
+        })
+    }
+
+    /// The number of powers in \\(\GG_1\\).
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if there are no powers in \\(\GG_1\\).
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The streaming committer key, reading the powers in \\(\GG_1\\) from the file.
+    pub fn committer_key(&self) -> CommitterKeyStream<E, SrsPowers<'_, E>> {
+        CommitterKeyStream {
+            powers_of_g: SrsPowers { srs: self },
+            powers_of_g2: self.powers_of_g2.clone(),
+        }
+    }
+
+    /// The verification key of the committer key.
+    pub fn verifier_key(&self) -> io::Result<VerifierKey<E>> {
+        let max_eval_points = self.powers_of_g2.len().saturating_sub(1);
+        if self.powers_of_g2.is_empty() || self.len < max_eval_points {
+            return Err(invalid_data("the committer key is too short"));
+        }
+        let mut powers_of_g = SrsPowers { srs: self }
+            .iter_from(self.len - max_eval_points)
+            .collect::<Vec<_>>();
+        powers_of_g.reverse();
+        Ok(VerifierKey {
+            powers_of_g,
+            powers_of_g2: self.powers_of_g2.clone(),
+        })
+    }
+}
+
+/// The (big-endian) stream of the powers in \\(\GG_1\\) of an [`SrsFile`].
+#[derive(Clone, Copy)]
+pub struct SrsPowers<'a, E: Pairing> {
+    srs: &'a SrsFile<E>,
+}
+
+/// The iterator returned by [`SrsPowers`].
+pub struct SrsPowersIter<'a, E: Pairing> {
+    srs: &'a SrsFile<E>,
+    reader: BufReader<File>,
+    /// The first power that has been read.
+    next: usize,
+    /// The powers of the current block, in reversed order.
+    buffer: Vec<E::G1Affine>,
+}
+
+impl<'a, E: Pairing> SrsPowersIter<'a, E> {
+    fn read_block(&mut self) -> io::Result<()> {
+        let first = self.next.saturating_sub(BLOCK_SIZE);
+        let offset = 0u64.uncompressed_size() + first * self.srs.element_size;
+        self.reader.seek(SeekFrom::Start(offset as u64))?;
+        for _ in first..self.next {
+            let power =
+                E::G1Affine::deserialize_uncompressed(&mut self.reader).map_err(invalid_data)?;
+            self.buffer.push(power);
+        }
+        self.next = first;
+        Ok(())
+    }
+}
+
+impl<'a, E: Pairing> Iterator for SrsPowersIter<'a, E> {
+    type Item = E::G1Affine;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(e) = self.buffer.pop() {
+                return Some(e);
+            }
+            if self.next == 0 {
+                return None;
+            }
+            self.read_block().expect("failed to read the SRS file");
+        }
+    }
+}
+
+impl<'a, E: Pairing> Iterable for SrsPowers<'a, E> {
+    type Item = E::G1Affine;
+
+    type Iter = SrsPowersIter<'a, E>;
+
+    fn iter(&self) -> Self::Iter {
+        self.iter_from(0)
+    }
+
+    fn len(&self) -> usize {
+        self.srs.len
+    }
+}
+
+impl<'a, E: Pairing> RandomAccessIterable for SrsPowers<'a, E> {
+    fn iter_from(&self, idx: usize) -> Self::Iter {
+        let file = File::open(&self.srs.path).expect("failed to open the SRS file");
+        SrsPowersIter {
+            srs: self.srs,
+            reader: BufReader::new(file),
+            next: self.srs.len.saturating_sub(idx),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+/// A stream spooled to a temporary file, removed when the stream is dropped.
+///
+/// Its elements are read as results, so that the prover reads it through a [`Fallible`] stream,
+/// failing instead of panicking if the file cannot be read.
+struct Spool<F> {
+    path: PathBuf,
+    len: usize,
+    _field: PhantomData<F>,
+}
+
+impl<F: CanonicalSerialize> Spool<F> {
+    /// Write the elements of `elements` to a new temporary file,
+    /// failing at the first element that could not be computed.
+    fn new(elements: impl IntoIterator<Item = io::Result<F>>) -> io::Result<Self> {
+        let path = std::env::temp_dir().join(std::format!(
+            "gemini-spool-{}-{}",
+            std::process::id(),
+            SPOOL_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        // register the file before writing, so that it's cleaned up even on failure.
+        let mut spool = Spool {
+            path,
+            len: 0,
+            _field: PhantomData,
+        };
+        let mut writer = BufWriter::new(File::create(&spool.path)?);
+        for element in elements {
+            element?
+                .serialize_uncompressed(&mut writer)
+                .map_err(invalid_data)?;
+            spool.len += 1;
+        }
+        writer.flush()?;
+        Ok(spool)
+    }
+}
+
+impl<F> Drop for Spool<F> {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// The iterator returned by [`Spool`].
+struct SpoolIter<F> {
+    reader: io::Result<BufReader<File>>,
+    remaining: usize,
+    _field: PhantomData<F>,
+}
+
+impl<F: CanonicalDeserialize> Iterator for SpoolIter<F> {
+    type Item = io::Result<F>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let reader = match &mut self.reader {
+            Ok(reader) => reader,
+            Err(e) => return Some(Err(io::Error::new(e.kind(), e.to_string()))),
+        };
+        // the spool is written by this process.
+        Some(F::deserialize_uncompressed_unchecked(reader).map_err(invalid_data))
+    }
+}
+
+impl<F: CanonicalDeserialize> Iterable for Spool<F> {
+    type Item = io::Result<F>;
+
+    type Iter = SpoolIter<F>;
+
+    fn iter(&self) -> Self::Iter {
+        SpoolIter {
+            reader: File::open(&self.path).map(BufReader::new),
+            remaining: self.len,
+            _field: PhantomData,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// Spool the (big-endian) product of the row-major matrix `matrix` with $\vec z$, row by row,
+/// reading the entries of $\vec z$ from `wires`.
+fn spool_product<F, S>(matrix: &S, wires: &mut CircomWires<'_, F>) -> io::Result<Spool<F>>
+where
+    F: PrimeField,
+    S: Iterable,
+    S::Item: Borrow<MatrixElement<F>>,
+{
+    let mut entries = matrix.iter();
+    let rows = ark_std::iter::from_fn(|| {
+        let mut row = F::zero();
+        for entry in entries.by_ref() {
+            match *entry.borrow() {
+                MatrixElement::Element((val, col)) => match wires.get(col) {
+                    Ok(z_col) => row += val * z_col,
+                    Err(e) => return Some(Err(e)),
+                },
+                MatrixElement::EOL => return Some(Ok(row)),
+            }
+        }
+        None
+    });
+    Spool::new(rows)
+}
+
+/// Preprocess the index of the `.r1cs` file at `r1cs_path`, with the SRS at `srs_path`,
+/// and write the verifier key to `verifier_key_path`.
+pub fn index_from_files<E, P>(
+    r1cs_path: P,
+    srs_path: P,
+    verifier_key_path: P,
+    config: &FilesConfig,
+) -> io::Result<()>
+where
+    E: Pairing,
+    E::ScalarField: PrimeField,
+    P: AsRef<Path>,
+{
+    let r1cs = CircomR1cs::<E::ScalarField>::open(r1cs_path)?;
+    let srs = SrsFile::<E>::open(srs_path)?;
+    let num_constraints = r1cs.num_constraints();
+    // the index is committed as padded by the prover.
+    let dim = padded_dim(num_constraints, r1cs.num_variables());
+    let (index_comms, stats) = index_commitments(
+        &srs.committer_key(),
+        &r1cs.a(),
+        &r1cs.b(),
+        &r1cs.c(),
+        num_constraints,
+        dim,
+        config.transpose_buffer,
+    )?;
+    let writer = BufWriter::new(File::create(verifier_key_path)?);
+    write_verifier_key(
+        &srs.verifier_key()?,
+        &index_comms,
+        stats.joint_non_zero,
+        writer,
+    )
+    .map_err(invalid_data)
+}
+
+/// Prove the satisfiability of the `.r1cs` file at `r1cs_path`
+/// with the assignment of the `.wtns` file at `witness_path`,
+/// using the SRS at `srs_path` and the elastic prover configured by `config`,
+/// and write the proof to `proof_path`.
+pub fn prove_from_files<E, P>(
+    r1cs_path: P,
+    witness_path: P,
+    srs_path: P,
+    proof_path: P,
+    config: &FilesConfig,
+) -> io::Result<()>
+where
+    E: Pairing,
+    E::ScalarField: PrimeField,
+    P: AsRef<Path>,
+{
+    let r1cs = CircomR1cs::<E::ScalarField>::open(r1cs_path)?;
+    let assignment = CircomWitness::<E::ScalarField>::open(witness_path)?;
+    let srs = SrsFile::<E>::open(srs_path)?;
+    let num_constraints = r1cs.num_constraints();
+    let num_variables = r1cs.num_variables();
+    let num_statement = r1cs.num_statement();
+    if assignment.len() != num_variables {
+        return Err(invalid_data("the assignment does not match the index"));
+    }
+    let dim = usize::max(num_constraints, num_variables);
+    let buffer = config.transpose_buffer;
+
+    // all matrices are streamed with `dim` lines.
+    let colmaj = |m| transpose(&m, num_constraints, dim, buffer);
+    let (a_colmaj, b_colmaj, c_colmaj) = (colmaj(r1cs.a())?, colmaj(r1cs.b())?, colmaj(r1cs.c())?);
+    let rowmaj = |m| transpose(m, dim, dim, buffer);
+    let (a_rowmaj, b_rowmaj, c_rowmaj) =
+        (rowmaj(&a_colmaj)?, rowmaj(&b_colmaj)?, rowmaj(&c_colmaj)?);
+    let stats = R1csStats::new(&r1cs.a(), &r1cs.b(), &r1cs.c(), num_constraints, dim);
+
+    let mut wires = assignment.wires()?;
+    let z_a = spool_product(&r1cs.a(), &mut wires)?;
+    let z_b = spool_product(&r1cs.b(), &mut wires)?;
+    let z_c = spool_product(&r1cs.c(), &mut wires)?;
+    let z = Spool::new((0..num_variables).rev().map(|wire| wires.get(wire)))?;
+    let witness = assignment.witness(num_statement);
+    // the first failure to read the spools fails the proof.
+    let failures = ReadFailures::new();
+    let z = Fallible::new(z, "r1cs.z", &failures);
+    let z_a = Fallible::new(z_a, "r1cs.z_a", &failures);
+    let z_b = Fallible::new(z_b, "r1cs.z_b", &failures);
+    let z_c = Fallible::new(z_c, "r1cs.z_c", &failures);
+
+    let r1cs_stream = R1csStream {
+        a_colmaj: MatrixPadded::new(&a_colmaj, 0),
+        b_colmaj: MatrixPadded::new(&b_colmaj, 0),
+        c_colmaj: MatrixPadded::new(&c_colmaj, 0),
+        a_rowmaj: MatrixPadded::new(&a_rowmaj, 0),
+        b_rowmaj: MatrixPadded::new(&b_rowmaj, 0),
+        c_rowmaj: MatrixPadded::new(&c_rowmaj, 0),
+        z: ZeroPadded::new(&z, dim),
+        witness: ZeroPadded::new(&witness, dim - num_statement),
+        z_a: ZeroPadded::new(&z_a, dim),
+        z_b: ZeroPadded::new(&z_b, dim),
+        z_c: ZeroPadded::new(&z_c, dim),
+        nonzero: dim,
+        joint_len: stats.joint_non_zero,
+    };
+    let (proof, _) = failures
+        .check(Proof::<E>::new_elastic_with_config(
+            &r1cs_stream,
+            &srs.committer_key(),
+            &config.prover,
+        ))
+        .map_err(invalid_data)?;
+    for transposed in [
+        &a_colmaj, &b_colmaj, &c_colmaj, &a_rowmaj, &b_rowmaj, &c_rowmaj,
+    ] {
+        transposed.check()?;
+    }
+
+    let mut writer = BufWriter::new(File::create(proof_path)?);
+    proof
+        .serialize_versioned(&mut writer)
+        .map_err(invalid_data)?;
+    writer.flush()
+}
+
+/// Verify the proof at `proof_path` for the `.r1cs` file at `r1cs_path`
+/// against the verifier key at `verifier_key_path`,
+/// and the public input at `public_input_path`
+/// (a JSON array of decimal strings, without the leading one).
+///
+/// Return `Ok(false)` if the proof is invalid,
+/// and fails if any of the files cannot be read.
+pub fn verify_from_files<E, P>(
+    r1cs_path: P,
+    verifier_key_path: P,
+    public_input_path: P,
+    proof_path: P,
+) -> io::Result<bool>
+where
+    E: Pairing,
+    E::ScalarField: PrimeField,
+    P: AsRef<Path>,
+{
+    let r1cs = CircomR1cs::<E::ScalarField>::open(r1cs_path)?;
+    let reader = BufReader::new(File::open(verifier_key_path)?);
+    let (vk, index_comms, num_non_zero) =
+        read_verifier_key::<E, _>(reader).map_err(invalid_data)?;
+    let public_input = Json::parse(&fs::read_to_string(public_input_path)?)
+        .and_then(|json| {
+            json.as_array()?
+                .iter()
+                .map(scalar_from::<E::ScalarField>)
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .map_err(invalid_data)?;
+    if public_input.len() + 1 != r1cs.num_statement() {
+        return Err(invalid_data("the public input does not match the index"));
+    }
+    let reader = BufReader::new(File::open(proof_path)?);
+    let proof = Proof::<E>::deserialize_versioned(reader).map_err(invalid_data)?;
+
+    // the statement starts with the constant one, and is streamed from the last element.
+    let x = ark_std::iter::once(E::ScalarField::one())
+        .chain(public_input)
+        .collect::<Vec<_>>();
+    Ok(proof
+        .verify_streaming(
+            r1cs.num_constraints(),
+            r1cs.num_variables(),
+            &crate::iterable::Reverse(x.as_slice()),
+            &vk,
+            &index_comms,
+            num_non_zero,
+        )
+        .is_ok())
+}
+
+#[test]
+fn test_files() {
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+
+    use crate::circom::write_circom_files;
+    use crate::circuit::{generate_relation, random_circuit};
+    use crate::json::scalar;
+    use crate::kzg::CommitterKey;
+
+    let rng = &mut test_rng();
+    let num_constraints = 64;
+    let num_variables = 64;
+    let r1cs = generate_relation::<Fr, _>(random_circuit(rng, num_constraints, num_variables));
+    let (r1cs_path, wtns_path) = write_circom_files(&r1cs, "gemini-test-files");
+
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints * 100 + num_variables, 3, rng);
+    let path = |name: &str| std::env::temp_dir().join(std::format!("gemini-test-files-{name}"));
+    let (srs_path, vk_path, proof_path, public_path) =
+        (path("srs"), path("vk"), path("proof"), path("public.json"));
+    ck.serialize_uncompressed(File::create(&srs_path).unwrap())
+        .unwrap();
+    let public_input = crate::json::array(&r1cs.x[1..], scalar);
+    fs::write(&public_path, public_input.to_pretty_string()).unwrap();
+
+    // small buffers, so that the matrices span several runs.
+    let config = FilesConfig {
+        transpose_buffer: 100,
+        ..FilesConfig::default()
+    };
+    index_from_files::<Bls12_381, _>(&r1cs_path, &srs_path, &vk_path, &config).unwrap();
+    prove_from_files::<Bls12_381, _>(&r1cs_path, &wtns_path, &srs_path, &proof_path, &config)
+        .unwrap();
+    assert!(
+        verify_from_files::<Bls12_381, _>(&r1cs_path, &vk_path, &public_path, &proof_path).unwrap()
+    );
+
+    // the proof is the one of the time-efficient prover.
+    let proof = Proof::deserialize_versioned(File::open(&proof_path).unwrap()).unwrap();
+    let relation = CircomR1cs::open(&r1cs_path)
+        .unwrap()
+        .relation(r1cs.z.clone())
+        .unwrap();
+    assert!(proof == Proof::new_time(&relation, &ck).unwrap());
+
+    // a wrong public input is rejected.
+    let mut wrong = r1cs.x[1..].to_vec();
+    wrong[0] += Fr::from(1u64);
+    fs::write(
+        &public_path,
+        crate::json::array(&wrong, scalar).to_pretty_string(),
+    )
+    .unwrap();
+    assert!(
+        !verify_from_files::<Bls12_381, _>(&r1cs_path, &vk_path, &public_path, &proof_path)
+            .unwrap()
+    );
+
+    for path in [
+        r1cs_path,
+        wtns_path,
+        srs_path,
+        vk_path,
+        proof_path,
+        public_path,
+    ] {
+        fs::remove_file(path).unwrap();
+    }
+}
+
+#[test]
+fn test_spool_failure() {
+    use ark_bls12_381::Fr;
+
+    let spool = Spool::new((0..4u64).map(|i| Ok(Fr::from(i)))).unwrap();
+    let path = spool.path.clone();
+    let failures = ReadFailures::new();
+    let stream = Fallible::new(spool, "spool", &failures);
+    assert!(stream.iter().eq((0..4u64).map(Fr::from)));
+    assert!(!failures.failed());
+
+    // the spool cannot be read anymore: the proof fails instead of panicking.
+    fs::remove_file(&path).unwrap();
+    assert_eq!(stream.iter().count(), 4);
+    assert!(matches!(
+        failures.check(Ok(())),
+        Err(crate::errors::GeminiError::Io { name: "spool", .. })
+    ));
+}
@@ -0,0 +1,316 @@
+//! Custom gates, applied row-wise.
+//!
+//! A [`GateSystem`] declares a small set of gate types,
+//! each over a few _wires_, and applies one of them at each row to a selection of the variables of $\vec z$:
+//! - a [`Gate::Quadratic`] gate enforces a quadratic expression of the wires to be zero;
+//! - a [`Gate::Lookup`] gate enforces a wire to hold an entry of a table.
+//!
+//! Hash functions and range checks need many R1CS constraints and auxiliary variables,
+//! while they take a few quadratic gates and lookups.
+//! The arguments in this crate prove R1CS, hence gate systems are lowered with [`GateSystem::lower`]:
+//! quadratic gates become R1CS constraints (one per product term),
+//! and lookups become reads from the tables, proven with the memory-checking argument
+//! (see [`memcheck`](crate::subprotocols::memcheck)) with the table as the memory.
+//! The values read are variables of $\vec z$:
+//! binding the evaluations of the reads to those of the witness is left to the upper protocol layer.
+use ark_ff::PrimeField;
+use ark_std::collections::BTreeMap;
+use ark_std::vec::Vec;
+
+use crate::ccs::combine_rows;
+use crate::circuit::R1cs;
+use crate::errors::{GeminiError, GeminiResult};
+
+/// A quadratic expression of the wires of a row,
+/// $\sum_k c_k \cdot w_{i_k} \cdot w_{j_k} + \sum_l c_l \cdot w_l$.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct QuadraticGate<F> {
+    /// The products $c_k \cdot w_{i_k} \cdot w_{j_k}$, as $(c_k, i_k, j_k)$.
+    pub products: Vec<(F, usize, usize)>,
+    /// The linear terms $c_l \cdot w_l$, as $(c_l, l)$.
+    pub linear: Vec<(F, usize)>,
+}
+
+/// A custom gate type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Gate<F> {
+    /// The quadratic expression of the wires is zero.
+    Quadratic(QuadraticGate<F>),
+    /// The wire `wire` holds an entry of the table `table`.
+    Lookup {
+        /// The wire looked up.
+        wire: usize,
+        /// The index of the table.
+        table: usize,
+    },
+}
+
+/// The application of the gate `gate` at a row,
+/// where the wire $i$ is the variable `wires[i]` of $\vec z$.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GateRow {
+    /// The index of the gate type.
+    pub gate: usize,
+    /// The variables of $\vec z$ of the wires.
+    pub wires: Vec<usize>,
+}
+
+/// A system of custom gates, together with its assignment.
+#[derive(Clone)]
+pub struct GateSystem<F: PrimeField> {
+    /// The gate types.
+    pub gates: Vec<Gate<F>>,
+    /// The tables of the lookups.
+    pub tables: Vec<Vec<F>>,
+    /// The rows, each applying a gate type.
+    pub rows: Vec<GateRow>,
+    pub z: Vec<F>,
+    pub w: Vec<F>,
+    pub x: Vec<F>,
+}
+
+/// The reads from a table of a lowered [`GateSystem`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TableReads {
+    /// The index of the table.
+    pub table: usize,
+    /// The addresses read in the table.
+    pub addresses: Vec<usize>,
+    /// The variables of $\vec z$ holding the values read.
+    pub variables: Vec<usize>,
+}
+
+impl<F: PrimeField> GateSystem<F> {
+    /// Check that the system is well formed:
+    /// $\vec z$ is the concatenation of $\vec x$ and $\vec w$,
+    /// every row applies a declared gate to enough variables of $\vec z$,
+    /// and every lookup refers to a declared table.
+    ///
+    /// Satisfiability is not checked.
+    pub fn check(&self) -> GeminiResult<()> {
+        GeminiError::ensure_len("gates.z", self.x.len() + self.w.len(), self.z.len())?;
+        let malformed = |reason| Err(GeminiError::MalformedInstance { reason });
+        for row in &self.rows {
+            let num_wires = match self.gates.get(row.gate) {
+                None => return malformed("row applying an undeclared gate"),
+                Some(Gate::Quadratic(gate)) => gate
+                    .products
+                    .iter()
+                    .flat_map(|&(_, i, j)| [i, j])
+                    .chain(gate.linear.iter().map(|&(_, l)| l))
+                    .max()
+                    .map_or(0, |wire| wire + 1),
+                Some(&Gate::Lookup { wire, table }) => {
+                    if table >= self.tables.len() {
+                        return malformed("lookup into an undeclared table");
+                    }
+                    wire + 1
+                }
+            };
+            if row.wires.len() < num_wires {
+                return malformed("row with fewer wires than its gate");
+            }
+            if row.wires.iter().any(|&variable| variable >= self.z.len()) {
+                return malformed("wire outside of the variables of z");
+            }
+        }
+        Ok(())
+    }
+
+    /// Return `true` if the assignment `z` satisfies all the rows.
+    ///
+    /// # Panics
+    /// If the system is malformed (see [`GateSystem::check`]).
+    pub fn is_satisfied(&self) -> bool {
+        self.rows.iter().all(|row| {
+            let wire = |i: usize| self.z[row.wires[i]];
+            match &self.gates[row.gate] {
+                Gate::Quadratic(gate) => {
+                    let products = gate.products.iter().map(|&(c, i, j)| c * wire(i) * wire(j));
+                    let linear = gate.linear.iter().map(|&(c, l)| c * wire(l));
+                    products.chain(linear).sum::<F>().is_zero()
+                }
+                &Gate::Lookup { wire: i, table } => self.tables[table].contains(&wire(i)),
+            }
+        })
+    }
+
+    /// Lower the system to an R1CS instance, and to the reads from each table.
+    ///
+    /// A quadratic gate with $k \geq 1$ products is lowered to $k$ constraints:
+    /// the auxiliary variables $y_k = w_{i_k} \cdot w_{j_k}$ are appended to the witness
+    /// for all products but the last one,
+    /// which is enforced along with the linear terms as
+    /// $(c \cdot w_i) \cdot w_j = -\sum_k c_k y_k - \sum_l c_l w_l$.
+    /// A linear gate $\ell(\vec z) = 0$ is enforced as $\ell(\vec z) \cdot \ell(\vec z) = 0$.
+    /// Lookups add no constraint: each read is the address of the first occurrence of the wire in the table.
+    ///
+    /// Fails if the system is malformed, or if a wire looked up is not in its table.
+    pub fn lower(&self) -> GeminiResult<(R1cs<F>, Vec<TableReads>)> {
+        self.check()?;
+        let positions = self
+            .tables
+            .iter()
+            .map(|table| {
+                let mut positions = BTreeMap::new();
+                table.iter().enumerate().rev().for_each(|(address, value)| {
+                    positions.insert(value.into_bigint(), address);
+                });
+                positions
+            })
+            .collect::<Vec<_>>();
+        let mut reads = (0..self.tables.len())
+            .map(|table| TableReads {
+                table,
+                addresses: Vec::new(),
+                variables: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        let mut c = Vec::new();
+        let mut aux = Vec::new();
+
+        for row in &self.rows {
+            match &self.gates[row.gate] {
+                Gate::Quadratic(gate) => {
+                    let mut linear = gate
+                        .linear
+                        .iter()
+                        .map(|&(coefficient, l)| (coefficient, row.wires[l]))
+                        .collect::<Vec<_>>();
+                    let (last, products) = match gate.products.split_last() {
+                        None => {
+                            let linear = combine_rows(linear);
+                            a.push(linear.clone());
+                            b.push(linear);
+                            c.push(Vec::new());
+                            continue;
+                        }
+                        Some(split) => split,
+                    };
+                    for &(coefficient, i, j) in products {
+                        let (i, j) = (row.wires[i], row.wires[j]);
+                        let y = self.z.len() + aux.len();
+                        aux.push(self.z[i] * self.z[j]);
+                        a.push(vec![(F::one(), i)]);
+                        b.push(vec![(F::one(), j)]);
+                        c.push(vec![(F::one(), y)]);
+                        linear.push((coefficient, y));
+                    }
+                    let &(coefficient, i, j) = last;
+                    a.push(vec![(coefficient, row.wires[i])]);
+                    b.push(vec![(F::one(), row.wires[j])]);
+                    c.push(combine_rows(linear.into_iter().map(|(v, col)| (-v, col))));
+                }
+                &Gate::Lookup { wire, table } => {
+                    let variable = row.wires[wire];
+                    let address = *positions[table]
+                        .get(&self.z[variable].into_bigint())
+                        .ok_or(GeminiError::MalformedInstance {
+                            reason: "wire looked up outside of its table",
+                        })?;
+                    reads[table].addresses.push(address);
+                    reads[table].variables.push(variable);
+                }
+            }
+        }
+
+        let w = self.w.iter().chain(&aux).cloned().collect::<Vec<_>>();
+        let r1cs = R1cs {
+            a,
+            b,
+            c,
+            z: self.x.iter().chain(&w).cloned().collect(),
+            w,
+            x: self.x.clone(),
+        };
+        Ok((r1cs, reads))
+    }
+}
+
+#[cfg(feature = "prover")]
+#[test]
+fn test_gates_lowering() {
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::{test_rng, One};
+    use merlin::Transcript;
+
+    use crate::kzg::CommitterKey;
+    use crate::snark::Proof;
+    use crate::subprotocols::memcheck::{Fingerprint, MemoryCheck, Subclaim};
+
+    let rng = &mut test_rng();
+    let one = Fr::one();
+    // a 4-bit range check, and the gates w0^2 = w1 and w0^2 + w1^2 = w2.
+    let range = (0..16u64).map(Fr::from).collect::<Vec<_>>();
+    let square = QuadraticGate {
+        products: vec![(one, 0, 0)],
+        linear: vec![(-one, 1)],
+    };
+    let sum_of_squares = QuadraticGate {
+        products: vec![(one, 0, 0), (one, 1, 1)],
+        linear: vec![(-one, 2)],
+    };
+    // z = (1, 3, 9, 5, 34).
+    let x = vec![one];
+    let w = [3u64, 9, 5, 34].map(Fr::from).to_vec();
+    let mut gates = GateSystem {
+        gates: vec![
+            Gate::Quadratic(square),
+            Gate::Quadratic(sum_of_squares),
+            Gate::Lookup { wire: 0, table: 0 },
+        ],
+        tables: vec![range],
+        rows: vec![
+            GateRow {
+                gate: 0,
+                wires: vec![1, 2],
+            },
+            GateRow {
+                gate: 1,
+                wires: vec![1, 3, 4],
+            },
+            GateRow {
+                gate: 2,
+                wires: vec![3],
+            },
+            GateRow {
+                gate: 2,
+                wires: vec![2],
+            },
+        ],
+        z: x.iter().chain(&w).cloned().collect(),
+        w,
+        x,
+    };
+    assert!(gates.is_satisfied());
+
+    let (r1cs, reads) = gates.lower().unwrap();
+    assert_eq!(r1cs.a.len(), 3);
+    assert_eq!(r1cs.w.len(), gates.w.len() + 1);
+    let ck = CommitterKey::<Bls12_381>::new(16, 5, rng);
+    let vk = (&ck).into();
+    let proof = Proof::new_time(&r1cs, &ck).unwrap();
+    assert!(proof.verify(&r1cs, &vk).is_ok());
+
+    assert_eq!(reads[0].addresses, [5, 9]);
+    assert_eq!(reads[0].variables, [3, 2]);
+    let prover_transcript = &mut Transcript::new(b"test");
+    let fingerprint = Fingerprint::new(prover_transcript);
+    let memcheck = MemoryCheck::new_time(
+        prover_transcript,
+        &ck,
+        fingerprint,
+        &gates.tables[0],
+        &reads[0].addresses,
+    );
+    let verifier_transcript = &mut Transcript::new(b"test");
+    assert!(Subclaim::new(verifier_transcript, &memcheck.msgs()).is_ok());
+
+    // a wire out of range.
+    gates.w[2] = Fr::from(17u64);
+    gates.z[3] = Fr::from(17u64);
+    assert!(!gates.is_satisfied());
+    assert!(gates.lower().is_err());
+}
@@ -0,0 +1,527 @@
+//! Generic adapters over streams.
+//!
+//! Each adapter mirrors the [`Iterator`] method of the same name,
+//! and propagates the length of the underlying streams.
+//! Streams are light handles (most of them are [`Copy`]),
+//! hence the adapters take them by value.
+//!
+//! Streams are big-endian: the first elements of the stream are the most significant ones.
+//! [`Take`] keeps the most significant elements, [`Skip`] discards them,
+//! [`Truncate`] keeps the least significant ones,
+//! and [`Zip`] aligns two streams of different length on their least significant elements.
+use ark_std::vec::Vec;
+
+use crate::iterable::Iterable;
+
+const LEN_MISMATCH_ERRMSG: &str = "Iterable::len mismatch with actual stream length.";
+
+/// Stream the image of each element of `stream` under `f`.
+#[derive(Clone, Copy)]
+pub struct Map<S, F> {
+    stream: S,
+    f: F,
+}
+
+impl<S, F, T> Map<S, F>
+where
+    S: Iterable,
+    F: Fn(S::Item) -> T + Clone + Send + Sync,
+{
+    /// Map the elements of `stream` with `f`.
+    pub fn new(stream: S, f: F) -> Self {
+        Self { stream, f }
+    }
+}
+
+impl<S, F, T> Iterable for Map<S, F>
+where
+    S: Iterable,
+    F: Fn(S::Item) -> T + Clone + Send + Sync,
+{
+    type Item = T;
+
+    type Iter = ark_std::iter::Map<S::Iter, F>;
+
+    fn iter(&self) -> Self::Iter {
+        self.stream.iter().map(self.f.clone())
+    }
+
+    fn len(&self) -> usize {
+        self.stream.len()
+    }
+}
+
+/// Stream the pairs of elements of `first` and `second`,
+/// aligned on their least significant elements.
+#[derive(Clone, Copy)]
+pub struct Zip<S0, S1> {
+    first: S0,
+    second: S1,
+}
+
+impl<S0, S1> Zip<S0, S1>
+where
+    S0: Iterable,
+    S1: Iterable,
+{
+    /// Zip `first` with `second`.
+    /// The most significant elements of the longest stream are discarded.
+    pub fn new(first: S0, second: S1) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<S0, S1> Iterable for Zip<S0, S1>
+where
+    S0: Iterable,
+    S1: Iterable,
+{
+    type Item = (S0::Item, S1::Item);
+
+    type Iter = ark_std::iter::Zip<S0::Iter, S1::Iter>;
+
+    fn iter(&self) -> Self::Iter {
+        let len = self.len();
+        let mut first = self.first.iter();
+        let mut second = self.second.iter();
+        first
+            .advance_by(self.first.len() - len)
+            .expect(LEN_MISMATCH_ERRMSG);
+        second
+            .advance_by(self.second.len() - len)
+            .expect(LEN_MISMATCH_ERRMSG);
+        first.zip(second)
+    }
+
+    fn len(&self) -> usize {
+        usize::min(self.first.len(), self.second.len())
+    }
+}
+
+/// Stream the elements of `first`, followed by the elements of `second`.
+#[derive(Clone, Copy)]
+pub struct Chain<S0, S1> {
+    first: S0,
+    second: S1,
+}
+
+impl<S0, S1> Chain<S0, S1>
+where
+    S0: Iterable,
+    S1: Iterable<Item = S0::Item>,
+{
+    /// Chain `first` with `second`.
+    pub fn new(first: S0, second: S1) -> Self {
+        Self { first, second }
+    }
+}
+
+impl<S0, S1> Iterable for Chain<S0, S1>
+where
+    S0: Iterable,
+    S1: Iterable<Item = S0::Item>,
+{
+    type Item = S0::Item;
+
+    type Iter = ark_std::iter::Chain<S0::Iter, S1::Iter>;
+
+    fn iter(&self) -> Self::Iter {
+        self.first.iter().chain(self.second.iter())
+    }
+
+    fn len(&self) -> usize {
+        self.first.len() + self.second.len()
+    }
+}
+
+/// Stream the elements of each segment, one segment after the other.
+///
+/// Segments are given in stream order: the first segment holds the most significant elements.
+/// This allows to stream, e.g., a witness that has been sharded across multiple files
+/// (say, a vector of [`MmapIterable`](crate::iterable::MmapIterable)s) without merging it first.
+#[derive(Clone)]
+pub struct Concat<S> {
+    segments: Vec<S>,
+}
+
+impl<S: Iterable> Concat<S> {
+    /// Concatenate `segments`.
+    pub fn new(segments: Vec<S>) -> Self {
+        Self { segments }
+    }
+}
+
+impl<S: Iterable> Iterable for Concat<S> {
+    type Item = S::Item;
+
+    type Iter = ark_std::iter::Flatten<ark_std::vec::IntoIter<S::Iter>>;
+
+    fn iter(&self) -> Self::Iter {
+        self.segments
+            .iter()
+            .map(|segment| segment.iter())
+            .collect::<Vec<_>>()
+            .into_iter()
+            .flatten()
+    }
+
+    fn len(&self) -> usize {
+        self.segments.iter().map(|segment| segment.len()).sum()
+    }
+}
+
+/// Stream the first `n` elements of `stream`.
+#[derive(Clone, Copy)]
+pub struct Take<S> {
+    stream: S,
+    n: usize,
+}
+
+impl<S: Iterable> Take<S> {
+    /// Take (at most) the first `n` elements of `stream`.
+    pub fn new(stream: S, n: usize) -> Self {
+        Self { stream, n }
+    }
+}
+
+impl<S: Iterable> Iterable for Take<S> {
+    type Item = S::Item;
+
+    type Iter = ark_std::iter::Take<S::Iter>;
+
+    fn iter(&self) -> Self::Iter {
+        self.stream.iter().take(self.n)
+    }
+
+    fn len(&self) -> usize {
+        usize::min(self.n, self.stream.len())
+    }
+}
+
+/// Stream the elements of `stream` after the first `n`.
+#[derive(Clone, Copy)]
+pub struct Skip<S> {
+    stream: S,
+    n: usize,
+}
+
+impl<S: Iterable> Skip<S> {
+    /// Skip the first `n` elements of `stream`.
+    pub fn new(stream: S, n: usize) -> Self {
+        Self { stream, n }
+    }
+}
+
+impl<S: Iterable> Iterable for Skip<S> {
+    type Item = S::Item;
+
+    type Iter = ark_std::iter::Skip<S::Iter>;
+
+    fn iter(&self) -> Self::Iter {
+        self.stream.iter().skip(self.n)
+    }
+
+    fn len(&self) -> usize {
+        self.stream.len().saturating_sub(self.n)
+    }
+}
+
+/// Stream the last `len` elements of `stream`.
+///
+/// Viewing the stream as the coefficients of a polynomial,
+/// this truncates the polynomial to its `len` least significant coefficients.
+#[derive(Clone, Copy)]
+pub struct Truncate<S> {
+    stream: S,
+    len: usize,
+}
+
+impl<S: Iterable> Truncate<S> {
+    /// Keep (at most) the last `len` elements of `stream`.
+    pub fn new(stream: S, len: usize) -> Self {
+        Self { stream, len }
+    }
+}
+
+impl<S: Iterable> Iterable for Truncate<S> {
+    type Item = S::Item;
+
+    type Iter = S::Iter;
+
+    fn iter(&self) -> Self::Iter {
+        let mut it = self.stream.iter();
+        it.advance_by(self.stream.len() - self.len())
+            .expect(LEN_MISMATCH_ERRMSG);
+        it
+    }
+
+    fn len(&self) -> usize {
+        usize::min(self.len, self.stream.len())
+    }
+}
+
+/// Stream the elements of `stream`, repeated `times` times.
+#[derive(Clone, Copy)]
+pub struct Repeat<S> {
+    stream: S,
+    times: usize,
+}
+
+impl<S: Iterable + Clone> Repeat<S> {
+    /// Repeat `stream` `times` times.
+    pub fn new(stream: S, times: usize) -> Self {
+        Self { stream, times }
+    }
+}
+
+/// The iterator returned by [`Repeat`].
+pub struct RepeatIter<S: Iterable> {
+    stream: S,
+    it: Option<S::Iter>,
+    remaining: usize,
+}
+
+impl<S: Iterable> Iterator for RepeatIter<S> {
+    type Item = S::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(x) = self.it.as_mut().and_then(|it| it.next()) {
+                return Some(x);
+            }
+            if self.remaining == 0 {
+                return None;
+            }
+            self.remaining -= 1;
+            self.it = Some(self.stream.iter());
+        }
+    }
+}
+
+impl<S: Iterable + Clone> Iterable for Repeat<S> {
+    type Item = S::Item;
+
+    type Iter = RepeatIter<S>;
+
+    fn iter(&self) -> Self::Iter {
+        RepeatIter {
+            stream: self.stream.clone(),
+            it: None,
+            remaining: self.times,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.stream.len() * self.times
+    }
+}
+
+/// Stream the first element of `stream`, and then every `step`-th element.
+#[derive(Clone, Copy)]
+pub struct StepBy<S> {
+    stream: S,
+    step: usize,
+}
+
+impl<S: Iterable> StepBy<S> {
+    /// Step over `stream` by `step` elements at a time.
+    ///
+    /// # Panics
+    /// If `step` is zero.
+    pub fn new(stream: S, step: usize) -> Self {
+        assert!(step != 0, "the step must be positive");
+        Self { stream, step }
+    }
+}
+
+impl<S: Iterable> Iterable for StepBy<S> {
+    type Item = S::Item;
+
+    type Iter = ark_std::iter::StepBy<S::Iter>;
+
+    fn iter(&self) -> Self::Iter {
+        self.stream.iter().step_by(self.step)
+    }
+
+    fn len(&self) -> usize {
+        (self.stream.len() + self.step - 1) / self.step
+    }
+}
+
+/// Stream the elements of `streams` element by element, in a round-robin fashion:
+/// the first element of each stream (in order), then the second element of each stream, and so on.
+#[derive(Clone)]
+pub struct Interleave<S> {
+    streams: Vec<S>,
+}
+
+impl<S: Iterable> Interleave<S> {
+    /// Interleave `streams`.
+    ///
+    /// # Panics
+    /// If the streams do not have the same length.
+    pub fn new(streams: Vec<S>) -> Self {
+        assert!(
+            streams.windows(2).all(|w| w[0].len() == w[1].len()),
+            "the interleaved streams must have the same length"
+        );
+        Self { streams }
+    }
+}
+
+/// The iterator returned by [`Interleave`].
+pub struct InterleaveIter<I> {
+    its: Vec<I>,
+    /// The stream of the next element.
+    next: usize,
+}
+
+impl<I: Iterator> Iterator for InterleaveIter<I> {
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.its.get_mut(self.next)?.next()?;
+        self.next = (self.next + 1) % self.its.len();
+        Some(item)
+    }
+}
+
+impl<S: Iterable> Iterable for Interleave<S> {
+    type Item = S::Item;
+
+    type Iter = InterleaveIter<S::Iter>;
+
+    fn iter(&self) -> Self::Iter {
+        InterleaveIter {
+            its: self.streams.iter().map(|stream| stream.iter()).collect(),
+            next: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.streams.iter().map(|stream| stream.len()).sum()
+    }
+}
+
+/// Stream the elements of the `index`-th of `k` streams interleaved into `stream`
+/// (see [`Interleave`]), i.e., every `k`-th element of `stream`, starting from the `index`-th.
+#[derive(Clone, Copy)]
+pub struct Deinterleave<S> {
+    stream: S,
+    k: usize,
+    index: usize,
+}
+
+impl<S: Iterable> Deinterleave<S> {
+    /// Extract the `index`-th of the `k` streams interleaved in `stream`.
+    ///
+    /// # Panics
+    /// If `index` is not smaller than `k`.
+    pub fn new(stream: S, k: usize, index: usize) -> Self {
+        assert!(
+            index < k,
+            "the index must be smaller than the number of streams"
+        );
+        Self { stream, k, index }
+    }
+}
+
+impl<S: Iterable> Iterable for Deinterleave<S> {
+    type Item = S::Item;
+
+    type Iter = ark_std::iter::StepBy<ark_std::iter::Skip<S::Iter>>;
+
+    fn iter(&self) -> Self::Iter {
+        self.stream.iter().skip(self.index).step_by(self.k)
+    }
+
+    fn len(&self) -> usize {
+        (self.stream.len().saturating_sub(self.index) + self.k - 1) / self.k
+    }
+}
+
+#[test]
+fn test_adapters() {
+    use crate::iterable::Reverse;
+
+    let v = [0u64, 1, 2, 3, 4, 5, 6];
+    let w = [10u64, 20, 30];
+    let s = Reverse(&v[..]);
+    let t = Reverse(&w[..]);
+
+    let map = Map::new(s, |&x| 2 * x);
+    assert_eq!(map.len(), 7);
+    assert_eq!(map.iter().collect::<Vec<_>>(), [12, 10, 8, 6, 4, 2, 0]);
+
+    let zip = Map::new(Zip::new(s, t), |(x, y)| x + y);
+    assert_eq!(zip.len(), 3);
+    assert_eq!(zip.iter().collect::<Vec<_>>(), [32, 21, 10]);
+
+    let chain = Chain::new(t, s);
+    assert_eq!(chain.len(), 10);
+    assert_eq!(
+        chain.iter().copied().collect::<Vec<_>>(),
+        [30, 20, 10, 6, 5, 4, 3, 2, 1, 0]
+    );
+
+    let concat = Concat::new(vec![t, s, t]);
+    assert_eq!(concat.len(), 13);
+    assert_eq!(
+        concat.iter().copied().collect::<Vec<_>>(),
+        [30, 20, 10, 6, 5, 4, 3, 2, 1, 0, 30, 20, 10]
+    );
+    assert_eq!(Concat::<Reverse<&[u64]>>::new(vec![]).iter().count(), 0);
+
+    let take = Take::new(s, 3);
+    assert_eq!(take.len(), 3);
+    assert_eq!(take.iter().copied().collect::<Vec<_>>(), [6, 5, 4]);
+    assert_eq!(Take::new(t, 5).len(), 3);
+
+    let skip = Skip::new(s, 5);
+    assert_eq!(skip.len(), 2);
+    assert_eq!(skip.iter().copied().collect::<Vec<_>>(), [1, 0]);
+    assert_eq!(Skip::new(t, 5).len(), 0);
+
+    let truncate = Truncate::new(s, 3);
+    assert_eq!(truncate.len(), 3);
+    assert_eq!(truncate.iter().copied().collect::<Vec<_>>(), [2, 1, 0]);
+    assert_eq!(Truncate::new(t, 5).len(), 3);
+    assert_eq!(Truncate::new(t, 5).iter().count(), 3);
+
+    let repeat = Repeat::new(t, 3);
+    assert_eq!(repeat.len(), 9);
+    assert_eq!(
+        repeat.iter().copied().collect::<Vec<_>>(),
+        [30, 20, 10, 30, 20, 10, 30, 20, 10]
+    );
+    assert_eq!(Repeat::new(t, 0).iter().count(), 0);
+    assert_eq!(Repeat::new(Take::new(t, 0), 3).iter().count(), 0);
+
+    for step in 1..9 {
+        let step_by = StepBy::new(s, step);
+        assert_eq!(step_by.len(), step_by.iter().count());
+    }
+    let step_by = StepBy::new(s, 3);
+    assert_eq!(step_by.iter().copied().collect::<Vec<_>>(), [6, 3, 0]);
+
+    let u = [100u64, 200, 300];
+    let interleave = Interleave::new(vec![t, Reverse(&u[..])]);
+    assert_eq!(interleave.len(), 6);
+    assert_eq!(
+        interleave.iter().copied().collect::<Vec<_>>(),
+        [30, 300, 20, 200, 10, 100]
+    );
+    assert_eq!(Interleave::<Reverse<&[u64]>>::new(vec![]).iter().count(), 0);
+    // de-interleaving is the inverse of interleaving.
+    for index in 0..2 {
+        let deinterleave = Deinterleave::new(interleave.clone(), 2, index);
+        assert_eq!(deinterleave.len(), 3);
+        assert!(deinterleave.iter().eq(interleave.streams[index].iter()));
+    }
+    for index in 0..3 {
+        let deinterleave = Deinterleave::new(s, 3, index);
+        assert_eq!(deinterleave.len(), deinterleave.iter().count());
+    }
+}
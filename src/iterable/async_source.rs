@@ -0,0 +1,154 @@
+//! Streams fed by asynchronous byte sources.
+//!
+//! The SRS or the witness may be hosted remotely (e.g., in an object storage),
+//! and exposed as a [`tokio`] [`AsyncRead`] source.
+//! An [`AsyncIterable`] pumps the source on a dedicated thread,
+//! running its own single-threaded runtime,
+//! and hands the bytes over to the (blocking) provers through a bounded channel.
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+use std::future::Future;
+use std::io;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::iterable::Iterable;
+
+/// The number of elements read at once from the source.
+const BLOCK_SIZE: usize = 1 << 10;
+/// The number of blocks buffered in the channel.
+const CHANNEL_CAPACITY: usize = 4;
+
+/// A stream of `len` elements read from the asynchronous source returned by `open`.
+///
+/// Each pass over the stream calls `open` to obtain a fresh source,
+/// holding the elements in stream order (that is, big-endian),
+/// each serialized with [`CanonicalSerialize::serialize_uncompressed`].
+pub struct AsyncIterable<O, T> {
+    open: O,
+    len: usize,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<O, T, Fut, R> AsyncIterable<O, T>
+where
+    O: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = io::Result<R>> + Send + 'static,
+    R: AsyncRead + Unpin,
+    T: CanonicalSerialize + CanonicalDeserialize + Default,
+{
+    /// Create a new stream of `len` elements, read from the sources opened by `open`.
+    pub fn new(len: usize, open: O) -> Self {
+        Self {
+            open,
+            len,
+            _item: PhantomData,
+        }
+    }
+}
+
+/// The iterator returned by [`AsyncIterable`].
+pub struct AsyncIter<T> {
+    receiver: Receiver<io::Result<Vec<u8>>>,
+    block: Vec<u8>,
+    position: usize,
+    element_size: usize,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T: CanonicalDeserialize> Iterator for AsyncIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position == self.block.len() {
+            // the pump hangs up once all elements have been read.
+            self.block = self
+                .receiver
+                .recv()
+                .ok()?
+                .expect("unable to read from the asynchronous source");
+            self.position = 0;
+        }
+        let bytes = &self.block[self.position..self.position + self.element_size];
+        self.position += self.element_size;
+        Some(T::deserialize_uncompressed(bytes).expect("invalid element in asynchronous source"))
+    }
+}
+
+impl<O, T, Fut, R> Iterable for AsyncIterable<O, T>
+where
+    O: Fn() -> Fut + Send + Sync,
+    Fut: Future<Output = io::Result<R>> + Send + 'static,
+    R: AsyncRead + Unpin,
+    T: CanonicalSerialize + CanonicalDeserialize + Default,
+{
+    type Item = T;
+
+    type Iter = AsyncIter<T>;
+
+    fn iter(&self) -> Self::Iter {
+        let element_size = T::default().uncompressed_size();
+        let (sender, receiver) = sync_channel(CHANNEL_CAPACITY);
+        let source = (self.open)();
+        let len = self.len;
+        thread::spawn(move || {
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("unable to start the runtime");
+            runtime.block_on(async move {
+                let mut reader = match source.await {
+                    Ok(reader) => reader,
+                    Err(e) => return sender.send(Err(e)).unwrap_or(()),
+                };
+                let mut remaining = len;
+                while remaining > 0 {
+                    let elements = usize::min(remaining, BLOCK_SIZE);
+                    let mut block = vec![0u8; elements * element_size];
+                    let block = reader.read_exact(&mut block).await.map(|_| block);
+                    let failed = block.is_err();
+                    // stop if the consumer dropped the iterator, or after forwarding an error.
+                    if sender.send(block).is_err() || failed {
+                        return;
+                    }
+                    remaining -= elements;
+                }
+            })
+        });
+        AsyncIter {
+            receiver,
+            block: Vec::new(),
+            position: 0,
+            element_size,
+            _item: PhantomData,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[test]
+fn test_async_iterable() {
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+    use ark_std::UniformRand;
+
+    let rng = &mut test_rng();
+    let scalars = (0..3000).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let mut bytes = Vec::new();
+    scalars
+        .iter()
+        .for_each(|s| s.serialize_uncompressed(&mut bytes).unwrap());
+
+    let stream = AsyncIterable::<_, Fr>::new(scalars.len(), || {
+        let bytes = bytes.clone();
+        async move { Ok::<_, io::Error>(io::Cursor::new(bytes)) }
+    });
+    assert_eq!(stream.len(), scalars.len());
+    assert!(stream.iter().eq(scalars.iter().copied()));
+    assert_eq!(stream.iter().nth(1234), Some(scalars[1234]));
+}
@@ -0,0 +1,106 @@
+//! Streams read ahead on a background thread.
+//!
+//! Reading a disk-backed stream stalls the prover whenever the next element is not in memory.
+//! A [`Buffered`] stream reads the underlying stream on a background thread,
+//! keeping up to `capacity` elements ahead of the consumer,
+//! so that the latency of the disk is hidden behind the field arithmetic of the prover.
+use ark_std::vec::Vec;
+use std::sync::mpsc::{sync_channel, Receiver};
+use std::thread;
+
+use crate::iterable::Iterable;
+
+/// The number of elements sent at once to the consumer.
+const BLOCK_SIZE: usize = 1 << 10;
+
+/// A stream prefetching (up to) `capacity` elements of `stream` on a background thread.
+///
+/// Each pass over the stream spawns a new thread,
+/// which terminates once the stream is exhausted or the iterator is dropped.
+#[derive(Clone, Copy)]
+pub struct Buffered<S> {
+    stream: S,
+    capacity: usize,
+}
+
+impl<S> Buffered<S>
+where
+    S: Iterable,
+    S::Iter: Send + 'static,
+    S::Item: Send + 'static,
+{
+    /// Read `stream` ahead by (up to) `capacity` elements.
+    pub fn new(stream: S, capacity: usize) -> Self {
+        Self { stream, capacity }
+    }
+}
+
+/// The iterator returned by [`Buffered`].
+pub struct BufferedIter<T> {
+    receiver: Receiver<Vec<T>>,
+    block: ark_std::vec::IntoIter<T>,
+}
+
+impl<T> Iterator for BufferedIter<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(item) = self.block.next() {
+                return Some(item);
+            }
+            // the sender hangs up once the stream is exhausted.
+            self.block = self.receiver.recv().ok()?.into_iter();
+        }
+    }
+}
+
+impl<S> Iterable for Buffered<S>
+where
+    S: Iterable,
+    S::Iter: Send + 'static,
+    S::Item: Send + 'static,
+{
+    type Item = S::Item;
+
+    type Iter = BufferedIter<S::Item>;
+
+    fn iter(&self) -> Self::Iter {
+        let block_size = usize::min(BLOCK_SIZE, usize::max(self.capacity, 1));
+        let (sender, receiver) = sync_channel(ark_std::cmp::max(self.capacity / block_size, 1));
+        let mut it = self.stream.iter();
+        thread::spawn(move || loop {
+            let block = (&mut it).take(block_size).collect::<Vec<_>>();
+            // stop if the stream is exhausted, or if the consumer dropped the iterator.
+            if block.is_empty() || sender.send(block).is_err() {
+                break;
+            }
+        });
+        BufferedIter {
+            receiver,
+            block: Vec::new().into_iter(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.stream.len()
+    }
+}
+
+#[test]
+fn test_buffered() {
+    use crate::iterable::Generator;
+
+    let stream = Generator::new(10_000, || (0..10_000u64).rev());
+    for capacity in [0, 1, 100, 1 << 20] {
+        let buffered = Buffered::new(stream, capacity);
+        assert_eq!(buffered.len(), stream.len());
+        assert!(buffered.iter().eq(stream.iter()));
+        // dropping the iterator early stops the background thread.
+        assert_eq!(
+            buffered.iter().take(3).collect::<Vec<_>>(),
+            [9999, 9998, 9997]
+        );
+    }
+}
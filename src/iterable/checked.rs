@@ -0,0 +1,127 @@
+//! Streams checking their declared length.
+//!
+//! The provers trust [`Iterable::len`] to align streams and to size their buffers:
+//! a stream yielding fewer (or more) elements than declared
+//! usually surfaces as an opaque failure deep inside the provers.
+//! A [`Checked`] stream fails instead as soon as the mismatch is observed,
+//! pointing to the offending stream.
+use crate::iterable::Iterable;
+
+/// A stream panicking as soon as `stream`, labelled `label`,
+/// yields more elements than `stream.len()`,
+/// or ends before yielding `stream.len()` elements.
+#[derive(Clone, Copy)]
+pub struct Checked<S> {
+    label: &'static str,
+    stream: S,
+}
+
+impl<S: Iterable> Checked<S> {
+    /// Check the length of `stream`, labelled `label` in the error messages.
+    pub fn new(label: &'static str, stream: S) -> Self {
+        Self { label, stream }
+    }
+}
+
+/// The iterator returned by [`Checked`].
+pub struct CheckedIter<I> {
+    it: I,
+    label: &'static str,
+    declared: usize,
+    observed: usize,
+}
+
+impl<I: Iterator> CheckedIter<I> {
+    fn check(&self, exhausted: bool) {
+        if self.observed > self.declared {
+            panic!(
+                "stream `{}` declares length {} but yields at least {} elements",
+                self.label, self.declared, self.observed
+            );
+        }
+        if exhausted && self.observed < self.declared {
+            panic!(
+                "stream `{}` declares length {} but yields {} elements",
+                self.label, self.declared, self.observed
+            );
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for CheckedIter<I> {
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self.it.next();
+        if next.is_some() {
+            self.observed += 1;
+        }
+        self.check(next.is_none());
+        next
+    }
+
+    fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        let advanced = self.it.advance_by(n);
+        self.observed += advanced.err().unwrap_or(n);
+        self.check(advanced.is_err());
+        advanced
+    }
+}
+
+impl<S: Iterable> Iterable for Checked<S> {
+    type Item = S::Item;
+
+    type Iter = CheckedIter<S::Iter>;
+
+    fn iter(&self) -> Self::Iter {
+        CheckedIter {
+            it: self.stream.iter(),
+            label: self.label,
+            declared: self.stream.len(),
+            observed: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.stream.len()
+    }
+}
+
+#[test]
+fn test_checked() {
+    use crate::iterable::dummy::Mat;
+
+    let v = [1u64, 2, 3];
+    let checked = Checked::new("v", &v[..]);
+    assert_eq!(checked.len(), 3);
+    assert!(checked.iter().eq(v.iter()));
+    let mut it = checked.iter();
+    assert!(it.advance_by(2).is_ok());
+    assert_eq!(it.next(), Some(&3));
+    assert_eq!(it.next(), None);
+
+    // consumers may stop early.
+    let longer = Checked::new("longer", Mat(&v[..], 2));
+    assert_eq!(longer.iter().take(2).count(), 2);
+}
+
+#[test]
+#[should_panic(expected = "stream `shorter` declares length 4 but yields 3 elements")]
+fn test_checked_shorter() {
+    use crate::iterable::dummy::Mat;
+
+    let v = [1u64, 2, 3];
+    Checked::new("shorter", Mat(&v[..], 4))
+        .iter()
+        .for_each(drop);
+}
+
+#[test]
+#[should_panic(expected = "stream `longer` declares length 2 but yields at least 3 elements")]
+fn test_checked_longer() {
+    use crate::iterable::dummy::Mat;
+
+    let v = [1u64, 2, 3];
+    Checked::new("longer", Mat(&v[..], 2)).iter().for_each(drop);
+}
@@ -0,0 +1,255 @@
+//! Streams backed by columnar files.
+//!
+//! Witnesses produced by data pipelines are often stored as a column of a table,
+//! either in [Arrow IPC](https://arrow.apache.org/docs/format/Columnar.html#ipc-file-format)
+//! or in [Parquet](https://parquet.apache.org/) files.
+//! A [`ColumnIterable`] streams a column of fixed-width binary values as field elements,
+//! reading the file one record batch at a time, without any conversion step.
+use ark_ff::PrimeField;
+use ark_std::marker::PhantomData;
+use arrow_array::{Array, FixedSizeBinaryArray, RecordBatch};
+use arrow_ipc::reader::FileReader;
+use arrow_schema::{ArrowError, DataType, Schema};
+use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+use parquet::arrow::ProjectionMask;
+use std::boxed::Box;
+use std::fs::File;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::iterable::{Endianness, Iterable};
+
+/// The format of a columnar file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Ipc,
+    Parquet,
+}
+
+/// A stream over a column of fixed-width binary values, read from an Arrow IPC or a Parquet file.
+///
+/// The rows of the file are in stream order (that is, big-endian).
+/// Each value is the encoding of an integer in the byte order given by [`Endianness`],
+/// and is reduced modulo the field characteristic.
+/// Each pass over the stream reopens the file, and reads only the selected column.
+#[derive(Clone)]
+pub struct ColumnIterable<F> {
+    path: PathBuf,
+    format: Format,
+    column: usize,
+    len: usize,
+    endianness: Endianness,
+    _field: PhantomData<fn() -> F>,
+}
+
+fn into_io_error<E>(e: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
+
+/// Return the index of the column named `name` in `schema`,
+/// failing if it does not hold fixed-width binary values.
+fn column_index(schema: &Schema, name: &str) -> io::Result<usize> {
+    let column = schema.index_of(name).map_err(into_io_error)?;
+    match schema.field(column).data_type() {
+        DataType::FixedSizeBinary(_) => Ok(column),
+        data_type => Err(into_io_error(std::format!(
+            "column `{}` has type {}, expected fixed-size binary",
+            name,
+            data_type
+        ))),
+    }
+}
+
+impl<F: PrimeField> ColumnIterable<F> {
+    /// Open the column named `name` of the Arrow IPC file at `path`.
+    ///
+    /// Arrow IPC files do not record the number of rows,
+    /// hence the file is read once to compute the length of the stream.
+    pub fn open_ipc<P: AsRef<Path>>(
+        path: P,
+        name: &str,
+        endianness: Endianness,
+    ) -> io::Result<Self> {
+        let reader = FileReader::try_new(File::open(&path)?, None).map_err(into_io_error)?;
+        let column = column_index(&reader.schema(), name)?;
+        let mut stream = Self {
+            path: path.as_ref().to_path_buf(),
+            format: Format::Ipc,
+            column,
+            len: 0,
+            endianness,
+            _field: PhantomData,
+        };
+        for batch in stream.batches()? {
+            stream.len += batch.map_err(into_io_error)?.num_rows();
+        }
+        Ok(stream)
+    }
+
+    /// Open the column named `name` of the Parquet file at `path`.
+    pub fn open_parquet<P: AsRef<Path>>(
+        path: P,
+        name: &str,
+        endianness: Endianness,
+    ) -> io::Result<Self> {
+        let builder =
+            ParquetRecordBatchReaderBuilder::try_new(File::open(&path)?).map_err(into_io_error)?;
+        let column = column_index(builder.schema(), name)?;
+        let len = builder.metadata().file_metadata().num_rows() as usize;
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            format: Format::Parquet,
+            column,
+            len,
+            endianness,
+            _field: PhantomData,
+        })
+    }
+
+    /// Open the file, and return the record batches of the selected column.
+    fn batches(&self) -> io::Result<Box<dyn Iterator<Item = Result<RecordBatch, ArrowError>>>> {
+        let file = File::open(&self.path)?;
+        match self.format {
+            Format::Ipc => {
+                let reader =
+                    FileReader::try_new(file, Some(vec![self.column])).map_err(into_io_error)?;
+                Ok(Box::new(reader))
+            }
+            Format::Parquet => {
+                let builder =
+                    ParquetRecordBatchReaderBuilder::try_new(file).map_err(into_io_error)?;
+                let mask = ProjectionMask::roots(builder.parquet_schema(), [self.column]);
+                let reader = builder
+                    .with_projection(mask)
+                    .build()
+                    .map_err(into_io_error)?;
+                Ok(Box::new(reader))
+            }
+        }
+    }
+}
+
+/// The iterator returned by [`ColumnIterable`].
+pub struct ColumnIter<F> {
+    batches: Box<dyn Iterator<Item = Result<RecordBatch, ArrowError>>>,
+    values: Option<FixedSizeBinaryArray>,
+    position: usize,
+    endianness: Endianness,
+    _field: PhantomData<fn() -> F>,
+}
+
+impl<F: PrimeField> Iterator for ColumnIter<F> {
+    type Item = F;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(values) = &self.values {
+                if self.position < values.len() {
+                    let bytes = values.value(self.position);
+                    self.position += 1;
+                    return Some(match self.endianness {
+                        Endianness::Big => F::from_be_bytes_mod_order(bytes),
+                        Endianness::Little => F::from_le_bytes_mod_order(bytes),
+                    });
+                }
+            }
+            let batch = self.batches.next()?.expect("invalid record batch");
+            let values = batch
+                .column(0)
+                .as_any()
+                .downcast_ref::<FixedSizeBinaryArray>()
+                .expect("the column does not hold fixed-size binary values");
+            self.values = Some(values.clone());
+            self.position = 0;
+        }
+    }
+}
+
+impl<F: PrimeField> Iterable for ColumnIterable<F> {
+    type Item = F;
+
+    type Iter = ColumnIter<F>;
+
+    fn iter(&self) -> Self::Iter {
+        ColumnIter {
+            batches: self.batches().expect("unable to open columnar file"),
+            values: None,
+            position: 0,
+            endianness: self.endianness,
+            _field: PhantomData,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[test]
+fn test_column_iterable() {
+    use ark_bls12_381::Fr;
+    use ark_ff::BigInteger;
+    use ark_std::test_rng;
+    use ark_std::vec::Vec;
+    use ark_std::UniformRand;
+    use arrow_schema::Field;
+    use std::sync::Arc;
+
+    let rng = &mut test_rng();
+    let scalars = (0..1000).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let le_bytes = scalars
+        .iter()
+        .map(|x| x.into_bigint().to_bytes_le())
+        .collect::<Vec<_>>();
+    let be_bytes = scalars
+        .iter()
+        .map(|x| x.into_bigint().to_bytes_be())
+        .collect::<Vec<_>>();
+
+    let schema = Arc::new(Schema::new(vec![
+        Field::new("le", DataType::FixedSizeBinary(32), false),
+        Field::new("be", DataType::FixedSizeBinary(32), false),
+    ]));
+    let batches = [0..500, 500..1000]
+        .into_iter()
+        .map(|range| {
+            let le = FixedSizeBinaryArray::try_from_iter(le_bytes[range.clone()].iter()).unwrap();
+            let be = FixedSizeBinaryArray::try_from_iter(be_bytes[range].iter()).unwrap();
+            RecordBatch::try_new(schema.clone(), vec![Arc::new(le), Arc::new(be)]).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    let ipc_path = std::env::temp_dir().join("gemini-test-column.arrow");
+    let mut writer =
+        arrow_ipc::writer::FileWriter::try_new(File::create(&ipc_path).unwrap(), &schema).unwrap();
+    for batch in &batches {
+        writer.write(batch).unwrap();
+    }
+    writer.finish().unwrap();
+
+    let parquet_path = std::env::temp_dir().join("gemini-test-column.parquet");
+    let file = File::create(&parquet_path).unwrap();
+    let mut writer = parquet::arrow::ArrowWriter::try_new(file, schema.clone(), None).unwrap();
+    for batch in &batches {
+        writer.write(batch).unwrap();
+    }
+    writer.close().unwrap();
+
+    let streams = [
+        ColumnIterable::<Fr>::open_ipc(&ipc_path, "le", Endianness::Little).unwrap(),
+        ColumnIterable::<Fr>::open_ipc(&ipc_path, "be", Endianness::Big).unwrap(),
+        ColumnIterable::<Fr>::open_parquet(&parquet_path, "le", Endianness::Little).unwrap(),
+        ColumnIterable::<Fr>::open_parquet(&parquet_path, "be", Endianness::Big).unwrap(),
+    ];
+    for stream in &streams {
+        assert_eq!(stream.len(), scalars.len());
+        assert!(stream.iter().eq(scalars.iter().copied()));
+    }
+    assert!(ColumnIterable::<Fr>::open_ipc(&ipc_path, "missing", Endianness::Big).is_err());
+
+    std::fs::remove_file(&ipc_path).unwrap();
+    std::fs::remove_file(&parquet_path).unwrap();
+}
@@ -0,0 +1,167 @@
+//! Streams backed by compressed files.
+//!
+//! The witness of a large instance takes hundreds of gigabytes on disk.
+//! The provers only ever read their inputs sequentially,
+//! hence the elements can be stored compressed (with [LZ4](https://lz4.org/) frames),
+//! and decompressed on the fly, one block at a time, at each pass over the stream.
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::borrow::Borrow;
+use ark_std::marker::PhantomData;
+use lz4_flex::frame::{FrameDecoder, FrameEncoder};
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+
+use crate::iterable::Iterable;
+
+/// A stream over the elements serialized in a compressed file.
+///
+/// The file starts with the number of elements (as a little-endian `u64`),
+/// followed by an LZ4 frame of the elements in stream order (that is, big-endian),
+/// each serialized with [`CanonicalSerialize::serialize_uncompressed`].
+/// Each pass over the stream reopens the file.
+pub struct CompressedIterable<T> {
+    path: PathBuf,
+    len: usize,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for CompressedIterable<T> {
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            len: self.len,
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<T> CompressedIterable<T>
+where
+    T: CanonicalSerialize + CanonicalDeserialize,
+{
+    /// Open the compressed file at `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let mut len = [0u8; 8];
+        File::open(&path)?.read_exact(&mut len)?;
+        // the length is stored as a u64, and may not fit the address space of 32-bit targets.
+        let len = usize::try_from(u64::from_le_bytes(len))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Self {
+            path: path.as_ref().to_path_buf(),
+            len,
+            _item: PhantomData,
+        })
+    }
+
+    /// Compress the elements of `stream` to the file at `path` (in stream order),
+    /// and open it.
+    pub fn create<P, S>(path: P, stream: &S) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+        S: Iterable,
+        S::Item: Borrow<T>,
+    {
+        let mut writer = BufWriter::new(File::create(&path)?);
+        writer.write_all(&(stream.len() as u64).to_le_bytes())?;
+        let mut encoder = FrameEncoder::new(writer);
+        let mut len = 0;
+        for element in stream.iter() {
+            element
+                .borrow()
+                .serialize_uncompressed(&mut encoder)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            len += 1;
+        }
+        if len != stream.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Iterable::len mismatch with actual stream length.",
+            ));
+        }
+        encoder
+            .finish()
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?
+            .flush()?;
+        Self::open(path)
+    }
+}
+
+/// The iterator returned by [`CompressedIterable`].
+pub struct CompressedIter<T> {
+    decoder: FrameDecoder<BufReader<File>>,
+    remaining: usize,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T: CanonicalDeserialize> Iterator for CompressedIter<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(
+            T::deserialize_uncompressed(&mut self.decoder)
+                .expect("invalid element in compressed file"),
+        )
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> Iterable for CompressedIterable<T>
+where
+    T: CanonicalDeserialize,
+{
+    type Item = T;
+
+    type Iter = CompressedIter<T>;
+
+    fn iter(&self) -> Self::Iter {
+        let mut reader =
+            BufReader::new(File::open(&self.path).expect("unable to open compressed file"));
+        reader
+            .read_exact(&mut [0u8; 8])
+            .expect("unable to read compressed file");
+        CompressedIter {
+            decoder: FrameDecoder::new(reader),
+            remaining: self.len,
+            _item: PhantomData,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[test]
+fn test_compressed_iterable() {
+    use ark_bls12_381::Fr;
+    use ark_std::vec::Vec;
+    use ark_std::{One, Zero};
+
+    use crate::iterable::Reverse;
+
+    // sparse witnesses compress well.
+    let scalars = (0..10_000u64)
+        .map(|i| if i % 10 == 0 { Fr::from(i) } else { Fr::zero() })
+        .chain([Fr::one()])
+        .collect::<Vec<_>>();
+    let path = std::env::temp_dir().join("gemini-test-compressed-scalars.lz4");
+    let stream = CompressedIterable::<Fr>::create(&path, &Reverse(scalars.as_slice())).unwrap();
+    assert_eq!(stream.len(), scalars.len());
+    assert!(stream.iter().eq(scalars.iter().rev().copied()));
+    // streams can be read multiple times.
+    assert_eq!(stream.iter().next(), Some(Fr::one()));
+    assert!(std::fs::metadata(&path).unwrap().len() < (scalars.len() * 32 / 4) as u64);
+
+    let reopened = CompressedIterable::<Fr>::open(&path).unwrap();
+    assert!(reopened.iter().eq(stream.iter()));
+    std::fs::remove_file(&path).unwrap();
+}
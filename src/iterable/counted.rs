@@ -0,0 +1,54 @@
+//! Streams counting their passes.
+//!
+//! The cost of streaming provers is dominated by the number of times they read their input,
+//! rather than by the number of elements they hold in memory.
+//! A [`Counted`] stream increments a shared counter each time a pass begins,
+//! so that the passes over several streams can be measured together.
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::iterable::Iterable;
+
+/// A stream incrementing `passes` each time [`Iterable::iter`] is called on `stream`.
+#[derive(Clone, Copy)]
+pub struct Counted<'a, S> {
+    stream: S,
+    passes: &'a AtomicUsize,
+}
+
+impl<'a, S: Iterable> Counted<'a, S> {
+    /// Count the passes over `stream` in `passes`.
+    pub fn new(stream: S, passes: &'a AtomicUsize) -> Self {
+        Self { stream, passes }
+    }
+}
+
+impl<'a, S: Iterable> Iterable for Counted<'a, S> {
+    type Item = S::Item;
+
+    type Iter = S::Iter;
+
+    fn iter(&self) -> Self::Iter {
+        self.passes.fetch_add(1, Ordering::Relaxed);
+        self.stream.iter()
+    }
+
+    fn len(&self) -> usize {
+        self.stream.len()
+    }
+}
+
+#[test]
+fn test_counted() {
+    let v = [1u64, 2, 3];
+    let passes = AtomicUsize::new(0);
+    let first = Counted::new(&v[..], &passes);
+    let second = Counted::new(&v[..2], &passes);
+    assert_eq!(first.len(), 3);
+    assert_eq!(passes.load(Ordering::Relaxed), 0);
+
+    assert!(first.iter().eq(v.iter()));
+    // abandoned passes are counted as well.
+    assert_eq!(first.iter().next(), Some(&1));
+    assert_eq!(second.iter().count(), 2);
+    assert_eq!(passes.load(Ordering::Relaxed), 3);
+}
@@ -0,0 +1,70 @@
+//! Streams that can be walked in both directions.
+//!
+//! Streams are big-endian, while vectors in memory are usually little-endian:
+//! a [`DoubleEndedIterable`] can be read in little-endian order
+//! without collecting and reversing its elements.
+use ark_std::iter::{Repeat, Rev, Take};
+use ark_std::slice::Iter;
+use ark_std::vec::Vec;
+
+use crate::iterable::dummy::DummyStreamer;
+use crate::iterable::{Iterable, Reverse};
+
+/// A stream that can also be iterated from its last element to its first.
+pub trait DoubleEndedIterable: Iterable {
+    /// The iterator over the stream in reversed order.
+    type RevIter: Iterator<Item = Self::Item>;
+
+    /// Return an iterator over the stream, from the last element to the first.
+    fn rev_iter(&self) -> Self::RevIter;
+}
+
+impl<'a, T: Sync> DoubleEndedIterable for &'a [T] {
+    type RevIter = Rev<Iter<'a, T>>;
+
+    fn rev_iter(&self) -> Self::RevIter {
+        self.iter().rev()
+    }
+}
+
+impl<'a, T: Sync> DoubleEndedIterable for &'a Vec<T> {
+    type RevIter = Rev<Iter<'a, T>>;
+
+    fn rev_iter(&self) -> Self::RevIter {
+        self.iter().rev()
+    }
+}
+
+impl<I> DoubleEndedIterable for Reverse<I>
+where
+    I: Iterable,
+    I::Iter: DoubleEndedIterator,
+{
+    type RevIter = I::Iter;
+
+    fn rev_iter(&self) -> Self::RevIter {
+        self.0.iter()
+    }
+}
+
+impl<T> DoubleEndedIterable for DummyStreamer<T>
+where
+    T: Send + Sync + Copy,
+{
+    type RevIter = Take<Repeat<T>>;
+
+    fn rev_iter(&self) -> Self::RevIter {
+        self.iter()
+    }
+}
+
+#[test]
+fn test_double_ended_iterable() {
+    let v = [1u64, 2, 3, 4];
+    let s = Reverse(&v[..]);
+    assert_eq!(s.rev_iter().collect::<Vec<_>>(), [&1, &2, &3, &4]);
+    assert!((&v[..]).rev_iter().eq(s.iter()));
+
+    let dummy = DummyStreamer::new(7u64, 3);
+    assert!(dummy.rev_iter().eq(dummy.iter()));
+}
@@ -3,7 +3,11 @@ use ark_ff::{PrimeField, Zero};
 use ark_std::vec::Vec;
 use ark_std::{iter, rand::RngCore};
 
-use crate::{circuit::R1csStream, iterable::Iterable, misc::MatrixElement};
+use crate::{
+    circuit::R1csStream,
+    iterable::{Iterable, RandomAccessIterable},
+    misc::MatrixElement,
+};
 
 /// A DummyStream is the stream that returns the same element `e`, `len` times.
 #[derive(Clone, Copy)]
@@ -214,6 +218,15 @@ where
     }
 }
 
+impl<T> RandomAccessIterable for DummyStreamer<T>
+where
+    T: Send + Sync + Copy,
+{
+    fn iter_from(&self, idx: usize) -> Self::Iter {
+        iter::repeat(self.e).take(self.len.saturating_sub(idx))
+    }
+}
+
 type DummyR1CStream<F> = R1csStream<DiagonalMatrixStreamer<F>, DummyStreamer<F>, DummyStreamer<F>>;
 
 /// Output a stream for the dummy R1CS instance.
@@ -0,0 +1,174 @@
+//! Streams whose reads may fail.
+//!
+//! The provers consume infallible streams, hence a disk-backed stream failing to read
+//! can only panic, aborting the whole proof.
+//! A [`Fallible`] stream wraps a stream of `Result`s:
+//! the first error is recorded in a shared [`ReadFailures`],
+//! and the stream (along with all other streams sharing the record)
+//! yields default elements up to its declared length, without reading further.
+//! The prover then runs to the end and its output is discarded in favour of the error,
+//! surfaced as a recoverable [`GeminiError::Io`] by [`ReadFailures::check`].
+use ark_std::fmt::Display;
+use ark_std::string::ToString;
+use core::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+use crate::errors::{GeminiError, GeminiResult};
+use crate::iterable::Iterable;
+
+/// The first read failure of a set of [`Fallible`] streams.
+#[derive(Default)]
+pub struct ReadFailures {
+    failed: AtomicBool,
+    first: Mutex<Option<GeminiError>>,
+}
+
+impl ReadFailures {
+    /// A record without failures.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return `true` if any read failed.
+    pub fn failed(&self) -> bool {
+        self.failed.load(Ordering::Relaxed)
+    }
+
+    /// Return `result`, unless a read failed, in which case return the first read failure.
+    pub fn check<T>(&self, result: GeminiResult<T>) -> GeminiResult<T> {
+        match self.first.lock().unwrap().clone() {
+            Some(e) => Err(e),
+            None => result,
+        }
+    }
+
+    fn record(&self, name: &'static str, e: impl Display) {
+        let mut first = self.first.lock().unwrap();
+        if first.is_none() {
+            *first = Some(GeminiError::Io {
+                name,
+                message: e.to_string(),
+            });
+        }
+        self.failed.store(true, Ordering::Relaxed);
+    }
+}
+
+/// A stream yielding the elements of the stream of results `stream`, named `name`,
+/// and recording its first error in `failures`.
+#[derive(Clone, Copy)]
+pub struct Fallible<'a, S> {
+    stream: S,
+    name: &'static str,
+    failures: &'a ReadFailures,
+}
+
+impl<'a, S, T, E> Fallible<'a, S>
+where
+    S: Iterable<Item = Result<T, E>>,
+    T: Default,
+    E: Display,
+{
+    /// Read `stream`, named `name` in the errors, recording its failures in `failures`.
+    pub fn new(stream: S, name: &'static str, failures: &'a ReadFailures) -> Self {
+        Self {
+            stream,
+            name,
+            failures,
+        }
+    }
+}
+
+/// The iterator returned by [`Fallible`].
+pub struct FallibleIter<'a, I> {
+    it: I,
+    name: &'static str,
+    failures: &'a ReadFailures,
+    remaining: usize,
+}
+
+impl<'a, I, T, E> Iterator for FallibleIter<'a, I>
+where
+    I: Iterator<Item = Result<T, E>>,
+    T: Default,
+    E: Display,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        // once a read failed, the elements are not read anymore.
+        if self.failures.failed() {
+            return Some(T::default());
+        }
+        match self.it.next() {
+            Some(Ok(e)) => Some(e),
+            Some(Err(e)) => {
+                self.failures.record(self.name, e);
+                Some(T::default())
+            }
+            None => {
+                self.failures.record(self.name, "the stream ended early");
+                Some(T::default())
+            }
+        }
+    }
+}
+
+impl<'a, S, T, E> Iterable for Fallible<'a, S>
+where
+    S: Iterable<Item = Result<T, E>>,
+    T: Default,
+    E: Display,
+{
+    type Item = T;
+
+    type Iter = FallibleIter<'a, S::Iter>;
+
+    fn iter(&self) -> Self::Iter {
+        FallibleIter {
+            it: self.stream.iter(),
+            name: self.name,
+            failures: self.failures,
+            remaining: self.stream.len(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.stream.len()
+    }
+}
+
+#[test]
+fn test_fallible() {
+    use ark_std::vec::Vec;
+
+    use crate::iterable::Generator;
+
+    let failures = ReadFailures::new();
+    let good = Generator::new(4, || (0..4u64).rev().map(Ok::<_, &str>));
+    let good = Fallible::new(good, "good", &failures);
+    assert!(good.iter().eq((0..4).rev()));
+    assert_eq!(failures.check(Ok(1)), Ok(1));
+
+    let bad = Generator::new(4, || {
+        (0..4u64)
+            .rev()
+            .map(|i| if i == 1 { Err("bad block") } else { Ok(i) })
+    });
+    let bad = Fallible::new(bad, "bad", &failures);
+    // the stream keeps its length, and yields defaults from the first failure.
+    assert_eq!(bad.iter().collect::<Vec<_>>(), [3, 2, 0, 0]);
+    // all streams sharing the record stop reading.
+    assert_eq!(good.iter().collect::<Vec<_>>(), [0; 4]);
+    assert_eq!(
+        failures.check(Ok(1)),
+        Err(GeminiError::Io {
+            name: "bad",
+            message: "bad block".to_string(),
+        })
+    );
+}
@@ -0,0 +1,196 @@
+//! Streams recomputed on demand.
+//!
+//! A [`Generator`] stores no element:
+//! each pass over the stream calls a closure producing all elements from scratch,
+//! for instance by re-executing the computation (e.g., a VM trace) that yields the witness.
+//! The elastic provers only ever access their inputs with a few sequential passes,
+//! hence a generator trades the storage of the witness for its recomputation.
+//!
+//! A [`FnIterable`] instead computes each element independently from its index,
+//! e.g. for powers of a challenge, sequences of indices, or constant vectors.
+use crate::iterable::{DoubleEndedIterable, Iterable, RandomAccessIterable};
+
+/// A stream of `len` elements, produced anew by the closure `generator` at each pass.
+///
+/// As for any other stream, the elements must be produced in the order expected by the provers,
+/// that is, big-endian.
+#[derive(Clone, Copy)]
+pub struct Generator<G> {
+    generator: G,
+    len: usize,
+}
+
+impl<G, I> Generator<G>
+where
+    G: Fn() -> I,
+    I: Iterator,
+{
+    /// Create a new stream of length `len` from the closure `generator`.
+    pub fn new(len: usize, generator: G) -> Self {
+        Self { generator, len }
+    }
+}
+
+impl<G, I> Iterable for Generator<G>
+where
+    G: Fn() -> I + Send + Sync,
+    I: Iterator,
+{
+    type Item = I::Item;
+
+    type Iter = I;
+
+    fn iter(&self) -> Self::Iter {
+        (self.generator)()
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+/// A stream of `len` elements, where the element of index `i`
+/// (that is, the coefficient of degree `i`) is `f(i)`.
+///
+/// Streams are big-endian: the first element produced is `f(len - 1)`, the last `f(0)`.
+#[derive(Clone, Copy)]
+pub struct FnIterable<F> {
+    f: F,
+    len: usize,
+}
+
+impl<F, T> FnIterable<F>
+where
+    F: Fn(usize) -> T + Clone + Send + Sync,
+{
+    /// Create a new stream of length `len` from the index function `f`.
+    pub fn new(len: usize, f: F) -> Self {
+        Self { f, len }
+    }
+}
+
+/// The iterator returned by [`FnIterable`].
+pub struct FnIter<F> {
+    f: F,
+    start: usize,
+    end: usize,
+}
+
+impl<F, T> Iterator for FnIter<F>
+where
+    F: Fn(usize) -> T,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            None
+        } else {
+            self.end -= 1;
+            Some((self.f)(self.end))
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.end - self.start, Some(self.end - self.start))
+    }
+
+    fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        let advanced = usize::min(n, self.end - self.start);
+        self.end -= advanced;
+        if advanced == n {
+            Ok(())
+        } else {
+            Err(advanced)
+        }
+    }
+}
+
+impl<F, T> DoubleEndedIterator for FnIter<F>
+where
+    F: Fn(usize) -> T,
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            None
+        } else {
+            self.start += 1;
+            Some((self.f)(self.start - 1))
+        }
+    }
+}
+
+impl<F, T> Iterable for FnIterable<F>
+where
+    F: Fn(usize) -> T + Clone + Send + Sync,
+{
+    type Item = T;
+
+    type Iter = FnIter<F>;
+
+    fn iter(&self) -> Self::Iter {
+        FnIter {
+            f: self.f.clone(),
+            start: 0,
+            end: self.len,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<F, T> DoubleEndedIterable for FnIterable<F>
+where
+    F: Fn(usize) -> T + Clone + Send + Sync,
+{
+    type RevIter = ark_std::iter::Rev<FnIter<F>>;
+
+    fn rev_iter(&self) -> Self::RevIter {
+        self.iter().rev()
+    }
+}
+
+impl<F, T> RandomAccessIterable for FnIterable<F>
+where
+    F: Fn(usize) -> T + Clone + Send + Sync,
+{
+    fn iter_from(&self, idx: usize) -> Self::Iter {
+        FnIter {
+            f: self.f.clone(),
+            start: 0,
+            end: self.len - usize::min(idx, self.len),
+        }
+    }
+}
+
+#[test]
+fn test_generator() {
+    use ark_std::vec::Vec;
+
+    let powers = Generator::new(8, || (0..8u32).rev().map(|i| 1u64 << i));
+    assert_eq!(powers.len(), 8);
+    assert_eq!(powers.iter().last(), Some(1));
+    assert_eq!(
+        powers.iter().collect::<Vec<_>>(),
+        powers.iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_fn_iterable() {
+    use ark_std::vec::Vec;
+
+    let squares = FnIterable::new(5, |i| i * i);
+    assert_eq!(squares.len(), 5);
+    assert_eq!(squares.iter().collect::<Vec<_>>(), [16, 9, 4, 1, 0]);
+    assert_eq!(squares.rev_iter().collect::<Vec<_>>(), [0, 1, 4, 9, 16]);
+
+    let mut it = squares.iter();
+    assert!(it.advance_by(3).is_ok());
+    assert_eq!(it.next(), Some(1));
+    assert_eq!(it.advance_by(2), Err(1));
+}
@@ -0,0 +1,385 @@
+//! Streams backed by files served over HTTP(S).
+//!
+//! A [`HttpIterable`] reads a remote file of (uncompressed) canonically-serialized elements,
+//! e.g. the points of a cloud-hosted SRS, with HTTP range requests:
+//! each pass fetches only the ranges it reaches, one block of [`HttpConfig::read_ahead`] elements at a time,
+//! and fetches the next block on a background thread while the current one is consumed.
+//! Failed requests (transport errors and server errors) are retried with exponential backoff.
+//! The server must support range requests.
+use ark_serialize::CanonicalDeserialize;
+use ark_std::marker::PhantomData;
+use std::io::{self, Read};
+use std::string::{String, ToString};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use std::vec::Vec;
+
+use crate::iterable::{Iterable, RandomAccessIterable};
+
+/// The configuration of the requests of a [`HttpIterable`].
+#[derive(Clone, Debug)]
+pub struct HttpConfig {
+    /// The number of elements fetched by each request.
+    pub read_ahead: usize,
+    /// Whether to fetch the next block in the background.
+    pub prefetch: bool,
+    /// The number of times a failed request is retried.
+    pub retries: usize,
+    /// The delay before the first retry, doubled at each subsequent retry.
+    pub backoff: Duration,
+    /// The timeout of each request.
+    pub timeout: Duration,
+}
+
+impl Default for HttpConfig {
+    fn default() -> Self {
+        Self {
+            read_ahead: 1 << 16,
+            prefetch: true,
+            retries: 5,
+            backoff: Duration::from_millis(200),
+            timeout: Duration::from_secs(60),
+        }
+    }
+}
+
+/// The remote file of a [`HttpIterable`], shared by its iterators.
+struct Remote {
+    url: String,
+    agent: ureq::Agent,
+    config: HttpConfig,
+}
+
+fn other_error(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, message)
+}
+
+impl Remote {
+    /// Send the request built by `request`, retrying on transport and server errors.
+    fn call(&self, request: impl Fn() -> ureq::Request) -> io::Result<ureq::Response> {
+        let mut backoff = self.config.backoff;
+        let mut attempt = 0;
+        loop {
+            let error = match request().call() {
+                Ok(response) => return Ok(response),
+                Err(ureq::Error::Status(status, _)) if status < 500 => {
+                    return Err(other_error(std::format!(
+                        "request to {} failed with status {status}",
+                        self.url
+                    )))
+                }
+                Err(error) => error,
+            };
+            if attempt == self.config.retries {
+                return Err(other_error(error.to_string()));
+            }
+            attempt += 1;
+            thread::sleep(backoff);
+            backoff *= 2;
+        }
+    }
+
+    /// Return the size of the remote file, in bytes.
+    fn size(&self) -> io::Result<usize> {
+        let response = self.call(|| self.agent.head(&self.url))?;
+        response
+            .header("Content-Length")
+            .and_then(|len| len.parse().ok())
+            .ok_or_else(|| other_error(std::format!("{} has no content length", self.url)))
+    }
+
+    /// Fetch the bytes in `start..end`.
+    fn fetch(&self, start: usize, end: usize) -> io::Result<Vec<u8>> {
+        let range = std::format!("bytes={}-{}", start, end - 1);
+        let response = self.call(|| self.agent.get(&self.url).set("Range", &range))?;
+        if response.status() != 206 {
+            return Err(other_error(std::format!(
+                "{} does not support range requests",
+                self.url
+            )));
+        }
+        let mut bytes = Vec::with_capacity(end - start);
+        response
+            .into_reader()
+            .take((end - start) as u64)
+            .read_to_end(&mut bytes)?;
+        if bytes.len() != end - start {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the range is shorter than requested",
+            ));
+        }
+        Ok(bytes)
+    }
+}
+
+/// A stream over the elements serialized in a remote file.
+///
+/// The file holds the elements in stream order (that is, big-endian),
+/// each serialized with [`CanonicalSerialize::serialize_uncompressed`](ark_serialize::CanonicalSerialize::serialize_uncompressed).
+/// Elements are checked (e.g., for subgroup membership) when deserialized.
+/// Iterators panic if a range cannot be fetched after all the retries.
+pub struct HttpIterable<T> {
+    remote: Arc<Remote>,
+    len: usize,
+    element_size: usize,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T> Clone for HttpIterable<T> {
+    fn clone(&self) -> Self {
+        Self {
+            remote: self.remote.clone(),
+            len: self.len,
+            element_size: self.element_size,
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<T> HttpIterable<T>
+where
+    T: CanonicalDeserialize + ark_serialize::CanonicalSerialize + Default,
+{
+    /// Open the remote file at `url`, with configuration `config`.
+    ///
+    /// Fails if the size of the file cannot be retrieved,
+    /// or if it is not a multiple of the size of a serialized element.
+    pub fn open(url: &str, config: HttpConfig) -> io::Result<Self> {
+        assert!(config.read_ahead > 0, "blocks must not be empty");
+        let agent = ureq::AgentBuilder::new().timeout(config.timeout).build();
+        let remote = Remote {
+            url: url.to_string(),
+            agent,
+            config,
+        };
+        let size = remote.size()?;
+        let element_size = T::default().uncompressed_size();
+        if size % element_size != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the file size is not a multiple of the element size",
+            ));
+        }
+        Ok(Self {
+            remote: Arc::new(remote),
+            len: size / element_size,
+            element_size,
+            _item: PhantomData,
+        })
+    }
+}
+
+/// The iterator returned by [`HttpIterable`].
+pub struct HttpIter<T> {
+    remote: Arc<Remote>,
+    /// The index of the next element.
+    position: usize,
+    len: usize,
+    element_size: usize,
+    /// The current block, and the index of its first element.
+    block: Vec<u8>,
+    block_start: usize,
+    /// The block being fetched in the background, and the index of its first element.
+    prefetched: Option<(usize, JoinHandle<io::Result<Vec<u8>>>)>,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T> HttpIter<T> {
+    /// Fetch the block starting at the element `start` on a background thread.
+    fn spawn_fetch(&self, start: usize) -> JoinHandle<io::Result<Vec<u8>>> {
+        let remote = self.remote.clone();
+        let end = usize::min(start + remote.config.read_ahead, self.len);
+        let element_size = self.element_size;
+        thread::spawn(move || remote.fetch(start * element_size, end * element_size))
+    }
+
+    /// Make the block holding the element `position` current.
+    fn load_block(&mut self) -> io::Result<()> {
+        let start = self.position;
+        let block = match self.prefetched.take() {
+            Some((prefetched_start, handle)) if prefetched_start == start => handle
+                .join()
+                .map_err(|_| other_error("the prefetching thread panicked".to_string()))??,
+            // after a skip, the prefetched block is discarded.
+            _ => {
+                let end = usize::min(start + self.remote.config.read_ahead, self.len);
+                self.remote
+                    .fetch(start * self.element_size, end * self.element_size)?
+            }
+        };
+        self.block_start = start;
+        self.block = block;
+        let next = start + self.block.len() / self.element_size;
+        if self.remote.config.prefetch && next < self.len {
+            self.prefetched = Some((next, self.spawn_fetch(next)));
+        }
+        Ok(())
+    }
+}
+
+impl<T: CanonicalDeserialize> Iterator for HttpIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position == self.len {
+            return None;
+        }
+        let block_len = self.block.len() / self.element_size;
+        if self.position < self.block_start || self.position >= self.block_start + block_len {
+            self.load_block()
+                .unwrap_or_else(|e| panic!("could not fetch the remote file: {e}"));
+        }
+        let offset = (self.position - self.block_start) * self.element_size;
+        let bytes = &self.block[offset..offset + self.element_size];
+        self.position += 1;
+        Some(T::deserialize_uncompressed(bytes).expect("invalid element in remote file"))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.position;
+        (remaining, Some(remaining))
+    }
+
+    fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        // skipped elements are not fetched.
+        let advanced = usize::min(n, self.len - self.position);
+        self.position += advanced;
+        if advanced == n {
+            Ok(())
+        } else {
+            Err(advanced)
+        }
+    }
+}
+
+impl<T> Iterable for HttpIterable<T>
+where
+    T: CanonicalDeserialize,
+{
+    type Item = T;
+
+    type Iter = HttpIter<T>;
+
+    fn iter(&self) -> Self::Iter {
+        self.iter_from(0)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<T: CanonicalDeserialize> RandomAccessIterable for HttpIterable<T> {
+    fn iter_from(&self, idx: usize) -> Self::Iter {
+        HttpIter {
+            remote: self.remote.clone(),
+            position: usize::min(idx, self.len),
+            len: self.len,
+            element_size: self.element_size,
+            block: Vec::new(),
+            block_start: 0,
+            prefetched: None,
+            _item: PhantomData,
+        }
+    }
+}
+
+#[test]
+fn test_http_iterable() {
+    use ark_bls12_381::{Fr, G1Affine};
+    use ark_serialize::CanonicalSerialize;
+    use ark_std::test_rng;
+    use ark_std::UniformRand;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    /// Serve `file` with range requests, failing the first range request with a server error.
+    fn serve(file: Vec<u8>) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let failed = AtomicBool::new(false);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut request_line = String::new();
+                reader.read_line(&mut request_line).unwrap();
+                let mut range = None;
+                loop {
+                    let mut line = String::new();
+                    reader.read_line(&mut line).unwrap();
+                    if line.trim().is_empty() {
+                        break;
+                    }
+                    if let Some(value) = line.to_lowercase().strip_prefix("range: bytes=") {
+                        let (start, end) = value.trim().split_once('-').unwrap();
+                        range = Some((
+                            start.parse::<usize>().unwrap(),
+                            end.parse::<usize>().unwrap(),
+                        ));
+                    }
+                }
+                let response = match range {
+                    None => std::format!(
+                        "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        file.len()
+                    )
+                    .into_bytes(),
+                    Some(_) if !failed.swap(true, Ordering::SeqCst) => {
+                        b"HTTP/1.1 503 Service Unavailable\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_vec()
+                    }
+                    Some((start, end)) => {
+                        let mut response = std::format!(
+                            "HTTP/1.1 206 Partial Content\r\nContent-Length: {}\r\nContent-Range: bytes {start}-{end}/{}\r\nConnection: close\r\n\r\n",
+                            end + 1 - start,
+                            file.len()
+                        )
+                        .into_bytes();
+                        response.extend_from_slice(&file[start..=end]);
+                        response
+                    }
+                };
+                stream.write_all(&response).unwrap();
+            }
+        });
+        std::format!("http://{address}/srs.bin")
+    }
+
+    let rng = &mut test_rng();
+    let config = HttpConfig {
+        read_ahead: 7,
+        backoff: Duration::from_millis(1),
+        ..HttpConfig::default()
+    };
+
+    let scalars = (0..100).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let mut file = Vec::new();
+    for s in &scalars {
+        s.serialize_uncompressed(&mut file).unwrap();
+    }
+    let stream = HttpIterable::<Fr>::open(&serve(file), config.clone()).unwrap();
+    assert_eq!(stream.len(), scalars.len());
+    assert!(stream.iter().eq(scalars.iter().copied()));
+    let mut it = stream.iter();
+    assert!(it.advance_by(50).is_ok());
+    assert_eq!(it.next(), Some(scalars[50]));
+    assert_eq!(it.advance_by(50), Err(49));
+    assert_eq!(stream.iter_from(98).next(), Some(scalars[98]));
+    assert_eq!(stream.iter_from(100).next(), None);
+
+    let points = (0..10).map(|_| G1Affine::rand(rng)).collect::<Vec<_>>();
+    let mut file = Vec::new();
+    for p in &points {
+        p.serialize_uncompressed(&mut file).unwrap();
+    }
+    let config = HttpConfig {
+        prefetch: false,
+        ..config
+    };
+    let stream = HttpIterable::<G1Affine>::open(&serve(file), config).unwrap();
+    assert!(stream.iter().eq(points.iter().copied()));
+}
@@ -0,0 +1,212 @@
+//! Streams logging their passes.
+//!
+//! A [`Counted`](crate::iterable::Counted) stream measures how many passes a prover makes,
+//! but not over which stream, nor how far each pass goes.
+//! An [`Instrumented`] stream logs in a shared [`PassLog`] each pass over it, labelled,
+//! together with the number of elements the pass consumed,
+//! so that the pass structure of the space-efficient provers can be inspected,
+//! and regressions in the number of passes detected in tests.
+//! A [`PassReport`] aggregates the log by label.
+use ark_std::collections::BTreeMap;
+use ark_std::vec::Vec;
+use std::sync::Mutex;
+
+use crate::iterable::Iterable;
+
+/// A pass over the stream labelled `label`, which consumed `elements` elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pass {
+    /// The label of the stream.
+    pub label: &'static str,
+    /// The number of elements consumed by the pass (so far, if the pass is not over).
+    pub elements: usize,
+}
+
+/// The passes over a set of [`Instrumented`] streams, in the order they began.
+#[derive(Debug, Default)]
+pub struct PassLog(Mutex<Vec<Pass>>);
+
+impl PassLog {
+    /// An empty log.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The passes logged so far, in the order they began.
+    pub fn passes(&self) -> Vec<Pass> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// The passes logged so far, aggregated by label.
+    pub fn report(&self) -> PassReport {
+        let mut streams = BTreeMap::<_, StreamPasses>::new();
+        for pass in self.0.lock().unwrap().iter() {
+            let stream = streams.entry(pass.label).or_default();
+            stream.passes += 1;
+            stream.elements += pass.elements;
+        }
+        PassReport { streams }
+    }
+
+    /// Clear the log.
+    pub fn clear(&self) {
+        self.0.lock().unwrap().clear()
+    }
+
+    fn begin(&self, label: &'static str) -> usize {
+        let mut passes = self.0.lock().unwrap();
+        passes.push(Pass { label, elements: 0 });
+        passes.len() - 1
+    }
+
+    fn end(&self, pass: usize, elements: usize) {
+        // the log may have been cleared during the pass,
+        // and it is not updated while panicking with the lock held.
+        if let Ok(mut passes) = self.0.lock() {
+            if let Some(pass) = passes.get_mut(pass) {
+                pass.elements = elements;
+            }
+        }
+    }
+}
+
+/// The passes over the streams sharing a label.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StreamPasses {
+    /// The number of passes.
+    pub passes: usize,
+    /// The number of elements consumed, across all passes.
+    pub elements: usize,
+}
+
+/// The passes of a [`PassLog`], aggregated by label.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PassReport {
+    /// The passes over the streams of each label.
+    pub streams: BTreeMap<&'static str, StreamPasses>,
+}
+
+impl PassReport {
+    /// The number of passes over the streams labelled `label`.
+    pub fn passes(&self, label: &str) -> usize {
+        self.streams.get(label).map_or(0, |stream| stream.passes)
+    }
+
+    /// The number of passes over all streams.
+    pub fn total_passes(&self) -> usize {
+        self.streams.values().map(|stream| stream.passes).sum()
+    }
+
+    /// The number of elements consumed over all streams.
+    pub fn total_elements(&self) -> usize {
+        self.streams.values().map(|stream| stream.elements).sum()
+    }
+}
+
+/// A stream logging in `log` each pass over `stream`, labelled `label`.
+#[derive(Clone, Copy)]
+pub struct Instrumented<'a, S> {
+    stream: S,
+    label: &'static str,
+    log: &'a PassLog,
+}
+
+impl<'a, S: Iterable> Instrumented<'a, S> {
+    /// Log in `log` the passes over `stream`, labelled `label`.
+    pub fn new(stream: S, label: &'static str, log: &'a PassLog) -> Self {
+        Self { stream, label, log }
+    }
+}
+
+/// The iterator returned by [`Instrumented`],
+/// logging the elements consumed once dropped.
+pub struct InstrumentedIter<'a, I> {
+    it: I,
+    log: &'a PassLog,
+    pass: usize,
+    elements: usize,
+}
+
+impl<'a, I: Iterator> Iterator for InstrumentedIter<'a, I> {
+    type Item = I::Item;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.it.next();
+        self.elements += item.is_some() as usize;
+        item
+    }
+
+    #[inline]
+    fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        let advanced = self.it.advance_by(n);
+        self.elements += advanced.err().unwrap_or(n);
+        advanced
+    }
+}
+
+impl<'a, I> Drop for InstrumentedIter<'a, I> {
+    fn drop(&mut self) {
+        self.log.end(self.pass, self.elements);
+    }
+}
+
+impl<'a, S: Iterable> Iterable for Instrumented<'a, S> {
+    type Item = S::Item;
+
+    type Iter = InstrumentedIter<'a, S::Iter>;
+
+    fn iter(&self) -> Self::Iter {
+        InstrumentedIter {
+            it: self.stream.iter(),
+            log: self.log,
+            pass: self.log.begin(self.label),
+            elements: 0,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.stream.len()
+    }
+}
+
+#[test]
+fn test_instrumented() {
+    let v = [1u64, 2, 3, 4];
+    let log = PassLog::new();
+    let first = Instrumented::new(&v[..], "first", &log);
+    let second = Instrumented::new(&v[..2], "second", &log);
+    assert_eq!(first.len(), 4);
+
+    assert!(first.iter().eq(v.iter()));
+    // abandoned passes are logged with the elements they consumed.
+    assert_eq!(first.iter().next(), Some(&1));
+    let mut it = second.iter();
+    assert!(it.advance_by(1).is_ok());
+    drop(it);
+    assert_eq!(
+        log.passes(),
+        [
+            Pass {
+                label: "first",
+                elements: 4
+            },
+            Pass {
+                label: "first",
+                elements: 1
+            },
+            Pass {
+                label: "second",
+                elements: 1
+            },
+        ]
+    );
+
+    let report = log.report();
+    assert_eq!(report.passes("first"), 2);
+    assert_eq!(report.passes("third"), 0);
+    assert_eq!(report.total_passes(), 3);
+    assert_eq!(report.total_elements(), 6);
+    log.clear();
+    assert_eq!(log.report(), PassReport::default());
+}
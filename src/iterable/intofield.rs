@@ -0,0 +1,485 @@
+//! Stream machine integers and raw bytes as field elements.
+use ark_ff::{Field, PrimeField};
+use ark_std::borrow::Borrow;
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::iterable::Iterable;
+use crate::misc::ceil_div;
+
+/// A machine integer that can be lifted into a field element.
+pub trait Limb: Copy {
+    /// The bit-width of the integer.
+    const BITS: usize;
+
+    /// Return the integer as a `u64`.
+    fn to_u64(self) -> u64;
+}
+
+macro_rules! impl_limb {
+    ($($t:ty),*) => {
+        $(
+            impl Limb for $t {
+                const BITS: usize = <$t>::BITS as usize;
+
+                #[inline(always)]
+                fn to_u64(self) -> u64 {
+                    self as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_limb!(u8, u16, u32, usize);
+
+impl Limb for u64 {
+    const BITS: usize = u64::BITS as usize;
+
+    #[inline(always)]
+    fn to_u64(self) -> u64 {
+        self
+    }
+}
+
+/// The order in which limbs are packed inside a field element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Endianness {
+    /// The first limb of the stream is the most significant one.
+    Big,
+    /// The first limb of the stream is the least significant one.
+    Little,
+}
+
+/// Stream the field representation of a stream of integers.
+///
+/// By default, every integer is mapped into a single field element.
+/// Using [`IntoField::with_packing`], `packing` consecutive integers of the stream
+/// are instead packed into a single field element,
+/// in the order specified by [`Endianness`].
+/// If the length of the stream is not a multiple of `packing`,
+/// the last field element packs the remaining integers.
+///
+/// No intermediate vector is ever allocated.
+pub struct IntoField<'a, S, F, L = usize> {
+    iterable: &'a S,
+    packing: usize,
+    endianness: Endianness,
+    _field: PhantomData<(F, L)>,
+}
+
+/// The iterator returned by [`IntoField`].
+pub struct IntoFieldIter<I, F, L> {
+    it: I,
+    packing: usize,
+    endianness: Endianness,
+    shift: F,
+    _limb: PhantomData<L>,
+}
+
+impl<I, F, L> Iterator for IntoFieldIter<I, F, L>
+where
+    I: Iterator,
+    I::Item: Borrow<L>,
+    L: Limb,
+    F: Field,
+{
+    type Item = F;
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut acc = F::from(self.it.next()?.borrow().to_u64());
+        let mut shift = self.shift;
+        for _ in 1..self.packing {
+            let limb = match self.it.next() {
+                Some(x) => F::from(x.borrow().to_u64()),
+                None => break,
+            };
+            match self.endianness {
+                Endianness::Big => acc = acc * self.shift + limb,
+                Endianness::Little => {
+                    acc += limb * shift;
+                    shift *= self.shift;
+                }
+            }
+        }
+        Some(acc)
+    }
+
+    fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        // the last field element may pack fewer than `packing` integers.
+        self.it
+            .advance_by(n.saturating_mul(self.packing))
+            .or_else(|advanced| match ceil_div(advanced, self.packing) {
+                elements if elements == n => Ok(()),
+                elements => Err(elements),
+            })
+    }
+}
+
+impl<'a, S, F> IntoField<'a, S, F, usize>
+where
+    S: Iterable,
+    F: Field,
+    S::Item: Borrow<usize>,
+{
+    /// Map each index of `iterable` into a field element.
+    pub fn new(iterable: &'a S) -> Self {
+        Self {
+            iterable,
+            packing: 1,
+            endianness: Endianness::Big,
+            _field: PhantomData,
+        }
+    }
+}
+
+impl<'a, S, F, L> IntoField<'a, S, F, L>
+where
+    S: Iterable,
+    F: PrimeField,
+    S::Item: Borrow<L>,
+    L: Limb,
+{
+    /// Pack `packing` consecutive integers of `iterable` into a single field element.
+    ///
+    /// # Panics
+    /// If `packing` is zero or if `packing` integers do not fit in a field element.
+    pub fn with_packing(iterable: &'a S, packing: usize, endianness: Endianness) -> Self {
+        assert!(packing > 0, "packing must be positive");
+        assert!(
+            packing * L::BITS < F::MODULUS_BIT_SIZE as usize,
+            "packed integers exceed the field capacity"
+        );
+        Self {
+            iterable,
+            packing,
+            endianness,
+            _field: PhantomData,
+        }
+    }
+}
+
+impl<'a, S, F, L> Iterable for IntoField<'a, S, F, L>
+where
+    S: Iterable,
+    F: Field,
+    S::Item: Borrow<L>,
+    L: Limb,
+{
+    type Item = F;
+
+    type Iter = IntoFieldIter<S::Iter, F, L>;
+
+    #[inline]
+    fn iter(&self) -> Self::Iter {
+        Self::Iter {
+            it: self.iterable.iter(),
+            packing: self.packing,
+            endianness: self.endianness,
+            shift: F::from(2u64).pow(&[L::BITS as u64]),
+            _limb: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        ceil_div(self.iterable.len(), self.packing)
+    }
+}
+
+/// A fixed-size chunk of raw data encoding an integer.
+pub trait ByteChunk: Clone + Send + Sync {
+    /// Return the integer encoded by the chunk (in the order given by `endianness`),
+    /// reduced modulo the field characteristic.
+    fn reduce<F: PrimeField>(&self, endianness: Endianness) -> F;
+}
+
+impl<const N: usize> ByteChunk for [u8; N] {
+    #[inline]
+    fn reduce<F: PrimeField>(&self, endianness: Endianness) -> F {
+        match endianness {
+            Endianness::Big => F::from_be_bytes_mod_order(self),
+            Endianness::Little => F::from_le_bytes_mod_order(self),
+        }
+    }
+}
+
+// borrowed chunks are reduced in place, without copying the bytes.
+impl<'b> ByteChunk for &'b [u8] {
+    #[inline]
+    fn reduce<F: PrimeField>(&self, endianness: Endianness) -> F {
+        match endianness {
+            Endianness::Big => F::from_be_bytes_mod_order(self),
+            Endianness::Little => F::from_le_bytes_mod_order(self),
+        }
+    }
+}
+
+impl ByteChunk for Vec<u8> {
+    #[inline]
+    fn reduce<F: PrimeField>(&self, endianness: Endianness) -> F {
+        match endianness {
+            Endianness::Big => F::from_be_bytes_mod_order(self),
+            Endianness::Little => F::from_le_bytes_mod_order(self),
+        }
+    }
+}
+
+impl<const N: usize> ByteChunk for [u64; N] {
+    #[inline]
+    fn reduce<F: PrimeField>(&self, endianness: Endianness) -> F {
+        // reduced straight from the limbs, with Horner's rule in base 2^64.
+        let shift = F::from(u64::MAX) + F::one();
+        let horner = |acc: F, &limb: &u64| acc * shift + F::from(limb);
+        match endianness {
+            Endianness::Big => self.iter().fold(F::zero(), horner),
+            Endianness::Little => self.iter().rev().fold(F::zero(), horner),
+        }
+    }
+}
+
+/// The default number of chunks converted at once by [`BytesIntoField`].
+const BATCH_SIZE: usize = 1 << 12;
+
+/// Stream the field representation of a stream of byte chunks (or of `u64` limbs).
+///
+/// Chunks are arrays, vectors, or slices borrowed from a larger buffer (`&[u8]`, whose bytes are not copied).
+/// Each chunk is mapped into a single field element,
+/// and is reduced modulo the field characteristic:
+/// chunks need not be canonical (e.g., they can be wider than the modulus).
+/// Chunks are converted in batches of fixed size (in parallel, with the `parallel` feature),
+/// hence at most one batch is held in memory.
+pub struct BytesIntoField<'a, S, F, C> {
+    iterable: &'a S,
+    endianness: Endianness,
+    batch_size: usize,
+    _field: PhantomData<(F, C)>,
+}
+
+impl<'a, S, F, C> BytesIntoField<'a, S, F, C>
+where
+    S: Iterable,
+    S::Item: Borrow<C>,
+    F: PrimeField,
+    C: ByteChunk,
+{
+    /// Map each chunk of `iterable` into a field element,
+    /// reading the chunks in the order given by `endianness`.
+    pub fn new(iterable: &'a S, endianness: Endianness) -> Self {
+        Self::with_batch_size(iterable, endianness, BATCH_SIZE)
+    }
+
+    /// Map each chunk of `iterable` into a field element,
+    /// converting `batch_size` chunks at once.
+    ///
+    /// # Panics
+    /// If `batch_size` is zero.
+    pub fn with_batch_size(iterable: &'a S, endianness: Endianness, batch_size: usize) -> Self {
+        assert!(batch_size > 0, "batch size must be positive");
+        Self {
+            iterable,
+            endianness,
+            batch_size,
+            _field: PhantomData,
+        }
+    }
+}
+
+/// The iterator returned by [`BytesIntoField`].
+pub struct BytesIntoFieldIter<I, F, C> {
+    it: I,
+    endianness: Endianness,
+    batch_size: usize,
+    batch: ark_std::vec::IntoIter<F>,
+    _chunk: PhantomData<C>,
+}
+
+impl<I, F, C> Iterator for BytesIntoFieldIter<I, F, C>
+where
+    I: Iterator,
+    I::Item: Borrow<C>,
+    F: PrimeField,
+    C: ByteChunk,
+{
+    type Item = F;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(x) = self.batch.next() {
+            return Some(x);
+        }
+        let chunks = self
+            .it
+            .by_ref()
+            .take(self.batch_size)
+            .map(|chunk| chunk.borrow().clone())
+            .collect::<Vec<C>>();
+        let endianness = self.endianness;
+        self.batch = cfg_iter!(chunks)
+            .map(|chunk| chunk.reduce(endianness))
+            .collect::<Vec<F>>()
+            .into_iter();
+        self.batch.next()
+    }
+
+    fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        let buffered = self.batch.len();
+        if n <= buffered {
+            return self.batch.advance_by(n);
+        }
+        self.batch = Vec::new().into_iter();
+        self.it
+            .advance_by(n - buffered)
+            .map_err(|advanced| buffered + advanced)
+    }
+}
+
+impl<'a, S, F, C> Iterable for BytesIntoField<'a, S, F, C>
+where
+    S: Iterable,
+    S::Item: Borrow<C>,
+    F: PrimeField,
+    C: ByteChunk,
+{
+    type Item = F;
+
+    type Iter = BytesIntoFieldIter<S::Iter, F, C>;
+
+    #[inline]
+    fn iter(&self) -> Self::Iter {
+        Self::Iter {
+            it: self.iterable.iter(),
+            endianness: self.endianness,
+            batch_size: self.batch_size,
+            batch: Vec::new().into_iter(),
+            _chunk: PhantomData,
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.iterable.len()
+    }
+}
+
+#[test]
+fn test_into_field_packing() {
+    use ark_bls12_381::Fr as F;
+
+    let limbs = [1u32, 2, 3, 4, 5];
+    let shift = F::from(1u64 << 32);
+
+    let unpacked = IntoField::<_, F, u32>::with_packing(&&limbs[..], 1, Endianness::Big);
+    let got = unpacked.iter().collect::<Vec<_>>();
+    let expected = limbs.iter().map(|&x| F::from(x)).collect::<Vec<_>>();
+    assert_eq!(got, expected);
+
+    let packed_be = IntoField::<_, F, u32>::with_packing(&&limbs[..], 2, Endianness::Big);
+    let got = packed_be.iter().collect::<Vec<_>>();
+    let expected = [
+        F::from(1u64) * shift + F::from(2u64),
+        F::from(3u64) * shift + F::from(4u64),
+        F::from(5u64),
+    ];
+    assert_eq!(packed_be.len(), 3);
+    assert_eq!(got, expected);
+
+    let packed_le = IntoField::<_, F, u32>::with_packing(&&limbs[..], 2, Endianness::Little);
+    let got = packed_le.iter().collect::<Vec<_>>();
+    let expected = [
+        F::from(1u64) + F::from(2u64) * shift,
+        F::from(3u64) + F::from(4u64) * shift,
+        F::from(5u64),
+    ];
+    assert_eq!(got, expected);
+
+    // the last element packs a single integer, and is skipped as a whole.
+    let mut it = packed_le.iter();
+    assert!(it.advance_by(1).is_ok());
+    assert_eq!(it.next(), Some(expected[1]));
+    assert!(it.advance_by(1).is_ok());
+    assert_eq!(it.next(), None);
+    let mut it = packed_le.iter();
+    assert_eq!(it.advance_by(4), Err(3));
+}
+
+#[test]
+fn test_bytes_into_field() {
+    use ark_bls12_381::Fr as F;
+    use ark_ff::{BigInteger, One};
+
+    let scalars = (0..10u64)
+        .map(|i| F::from(i) - F::from(1u64 << 40))
+        .collect::<Vec<_>>();
+    let le_bytes = scalars
+        .iter()
+        .map(|x| x.into_bigint().to_bytes_le())
+        .collect::<Vec<_>>();
+    let be_bytes = scalars
+        .iter()
+        .map(|x| x.into_bigint().to_bytes_be())
+        .collect::<Vec<_>>();
+    let limbs = scalars
+        .iter()
+        .map(|x| x.into_bigint().0)
+        .collect::<Vec<_>>();
+
+    let stream =
+        BytesIntoField::<_, F, Vec<u8>>::with_batch_size(&&le_bytes[..], Endianness::Little, 3);
+    assert_eq!(stream.len(), scalars.len());
+    assert_eq!(stream.iter().collect::<Vec<_>>(), scalars);
+    let stream = BytesIntoField::<_, F, Vec<u8>>::new(&&be_bytes[..], Endianness::Big);
+    assert_eq!(stream.iter().collect::<Vec<_>>(), scalars);
+    // chunks can also be borrowed from a larger buffer.
+    let buffer = le_bytes.concat();
+    let chunks = buffer.chunks(le_bytes[0].len()).collect::<Vec<_>>();
+    let stream = BytesIntoField::<_, F, &[u8]>::new(&&chunks[..], Endianness::Little);
+    assert_eq!(stream.iter().collect::<Vec<_>>(), scalars);
+    let stream =
+        BytesIntoField::<_, F, [u64; 4]>::with_batch_size(&&limbs[..], Endianness::Little, 4);
+    assert_eq!(stream.iter().collect::<Vec<_>>(), scalars);
+    let mut it = stream.iter();
+    assert_eq!(it.next(), Some(scalars[0]));
+    assert!(it.advance_by(2).is_ok());
+    assert_eq!(it.next(), Some(scalars[3]));
+    assert!(it.advance_by(5).is_ok());
+    assert_eq!(it.next(), Some(scalars[9]));
+    assert_eq!(it.advance_by(1), Err(0));
+
+    // non-canonical chunks are reduced.
+    let mut modulus_plus_one = F::MODULUS.0;
+    modulus_plus_one[0] += 1;
+    let big_endian = [
+        modulus_plus_one[3],
+        modulus_plus_one[2],
+        modulus_plus_one[1],
+        modulus_plus_one[0],
+    ];
+    let chunks = [modulus_plus_one];
+    let stream = BytesIntoField::<_, F, [u64; 4]>::new(&&chunks[..], Endianness::Little);
+    assert_eq!(stream.iter().next(), Some(F::one()));
+    let chunks = [big_endian];
+    let stream = BytesIntoField::<_, F, [u64; 4]>::new(&&chunks[..], Endianness::Big);
+    assert_eq!(stream.iter().next(), Some(F::one()));
+    let chunks = [[0xffu8; 64]];
+    let stream = BytesIntoField::<_, F, [u8; 64]>::new(&&chunks[..], Endianness::Big);
+    assert_eq!(
+        stream.iter().next(),
+        Some(F::from_le_bytes_mod_order(&[0xff; 64]))
+    );
+    let chunks = [[u64::MAX; 8]];
+    let stream = BytesIntoField::<_, F, [u64; 8]>::new(&&chunks[..], Endianness::Little);
+    assert_eq!(
+        stream.iter().next(),
+        Some(F::from_le_bytes_mod_order(&[0xff; 64]))
+    );
+    let chunks = [[0, 0, 0, 0, 0, 0, 1, 2]];
+    let stream = BytesIntoField::<_, F, [u64; 8]>::new(&&chunks[..], Endianness::Big);
+    assert_eq!(
+        stream.iter().next(),
+        Some(F::from(2u64) + F::from(2u64).pow([64]))
+    );
+}
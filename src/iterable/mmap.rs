@@ -1,110 +1,213 @@
-use std::marker::PhantomData;
+//! Streams backed by memory-mapped files.
+//!
+//! A [`MmapIterable`] maps a file of (uncompressed) canonically-serialized elements,
+//! e.g. the witness, a polynomial, or the points of the SRS,
+//! and deserializes each element only when the stream reaches it.
+//! The operating system pages the file in and out on demand,
+//! hence the memory used is independent of the length of the stream.
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::borrow::Borrow;
+use ark_std::iter::Rev;
+use ark_std::marker::PhantomData;
+use memmap2::Mmap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::iterable::{DoubleEndedIterable, Iterable, RandomAccessIterable};
+
+/// A stream over the elements serialized in a memory-mapped file.
+///
+/// The file holds the elements in stream order (that is, big-endian),
+/// each serialized with [`CanonicalSerialize::serialize_uncompressed`].
+/// Elements are checked (e.g., for subgroup membership) when deserialized.
+pub struct MmapIterable<T> {
+    mmap: Arc<Mmap>,
+    element_size: usize,
+    _item: PhantomData<fn() -> T>,
+}
 
-use ark_ff::Field;
-use memmap::Mmap;
+impl<T> Clone for MmapIterable<T> {
+    fn clone(&self) -> Self {
+        Self {
+            mmap: self.mmap.clone(),
+            element_size: self.element_size,
+            _item: PhantomData,
+        }
+    }
+}
 
-/// A memory-mapped buffer for field elements.
-#[derive(Clone, Copy)]
-pub struct FieldMmap<'a, F>
+impl<T> MmapIterable<T>
 where
-    F: Field,
+    T: CanonicalSerialize + CanonicalDeserialize + Default,
 {
-    mmap: &'a Mmap,
-    _field: PhantomData<F>,
+    /// Map the file at `path`.
+    ///
+    /// Fails if the file cannot be mapped,
+    /// or if its size is not a multiple of the size of a serialized element.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // SAFETY: the file must not be modified while mapped.
+        #[allow(unsafe_code)]
+        let mmap = unsafe { Mmap::map(&file)? };
+        let element_size = T::default().uncompressed_size();
+        if mmap.len() % element_size != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the file size is not a multiple of the element size",
+            ));
+        }
+        Ok(Self {
+            mmap: Arc::new(mmap),
+            element_size,
+            _item: PhantomData,
+        })
+    }
+
+    /// Serialize the elements of `stream` to the file at `path` (in stream order),
+    /// and map it.
+    pub fn create<P, S>(path: P, stream: &S) -> io::Result<Self>
+    where
+        P: AsRef<Path>,
+        S: Iterable,
+        S::Item: Borrow<T>,
+    {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        let mut writer = BufWriter::new(file);
+        for element in stream.iter() {
+            element
+                .borrow()
+                .serialize_uncompressed(&mut writer)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        }
+        writer.flush()?;
+        drop(writer);
+        Self::open(path)
+    }
 }
 
-impl<'a, F> FieldMmap<'a, F>
-where
-    F: Field,
-{
-    /// Initialize a new memory map buffer.
-    pub fn new(mmap: &'a Mmap) -> Self {
-        Self {
-            mmap,
-            _field: PhantomData,
+/// The iterator returned by [`MmapIterable`].
+pub struct MmapIter<T> {
+    mmap: Arc<Mmap>,
+    position: usize,
+    end: usize,
+    element_size: usize,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T: CanonicalDeserialize> Iterator for MmapIter<T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position == self.end {
+            return None;
+        }
+        let bytes = &self.mmap[self.position..self.position + self.element_size];
+        self.position += self.element_size;
+        Some(T::deserialize_uncompressed(bytes).expect("invalid element in memory-mapped file"))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = (self.end - self.position) / self.element_size;
+        (remaining, Some(remaining))
+    }
+
+    fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        let remaining = (self.end - self.position) / self.element_size;
+        let advanced = usize::min(n, remaining);
+        self.position += advanced * self.element_size;
+        if advanced == n {
+            Ok(())
+        } else {
+            Err(advanced)
         }
     }
 }
 
-impl<'a, F> Iterable for FieldMmap<'a, F>
+impl<T: CanonicalDeserialize> DoubleEndedIterator for MmapIter<T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.position == self.end {
+            return None;
+        }
+        self.end -= self.element_size;
+        let bytes = &self.mmap[self.end..self.end + self.element_size];
+        Some(T::deserialize_uncompressed(bytes).expect("invalid element in memory-mapped file"))
+    }
+}
+
+impl<T> Iterable for MmapIterable<T>
 where
-    F: Field,
+    T: CanonicalDeserialize,
 {
-    type Item = &'a F;
+    type Item = T;
 
-    type Iter = std::slice::Iter<'a, F>;
+    type Iter = MmapIter<T>;
 
     fn iter(&self) -> Self::Iter {
-        let source =
-            unsafe { std::slice::from_raw_parts_mut(self.mmap.as_ptr() as *mut F, self.len()) }
-                as &[F];
-        source.iter()
+        MmapIter {
+            mmap: self.mmap.clone(),
+            position: 0,
+            end: self.mmap.len(),
+            element_size: self.element_size,
+            _item: PhantomData,
+        }
     }
 
     fn len(&self) -> usize {
-        self.mmap.len() / std::mem::size_of::<F>()
+        self.mmap.len() / self.element_size
     }
 }
 
-// #[test]
-// fn write_ck<G: AffineCurve>() {
-//     let length = std::mem::size_of::G
-//         let file = std::fs::OpenOptions::new()
-//             .read(true)
-//             .write(true)
-//             .create(true)
-//             .open(path)
-//             .unwrap();
-//         file.set_len(length as u64).unwrap();
-//         let mut mmap = unsafe { MmapOptions::new().map_mut(&file).unwrap() };
-//         let dst =
-//             unsafe { std::slice::from_raw_parts_mut(mmap.as_mut_ptr() as *mut F, self.len()) };
-//         let src = self.stream().cloned().collect::<Vec<_>>();
-
-// }
-// impl<'a, F: Fields> FieldStreamer<'a, F> {
-//     pub fn from_file(path: &str) -> Result<(Mmap, Self)> {
-//         let file = std::fs::File::open(path).map_err(|_e| StreamError).unwrap();
-
-//         let mmap = unsafe { MmapOptions::new().map(&file).unwrap() };
-//         let source = unsafe {
-//             std::slice::from_raw_parts_mut(
-//                 mmap.as_ptr() as *mut F,
-//                 mmap.len() / std::mem::size_of::<F>(),
-//             )
-//         } as &[F];
-
-//         Ok((mmap, source))
-//     }
-
-//     pub fn to_file(&self, path: &str) -> Result<()> {
-//         let length = std::mem::size_of::<F>() * self.len();
-
-//         let file = std::fs::OpenOptions::new()
-//             .read(true)
-//             .write(true)
-//             .create(true)
-//             .open(path)
-//             .unwrap();
-//         file.set_len(length as u64).unwrap();
-//         let mut mmap = unsafe { MmapOptions::new().map_mut(&file).unwrap() };
-//         let dst =
-//             unsafe { std::slice::from_raw_parts_mut(mmap.as_mut_ptr() as *mut F, self.len()) };
-//         let src = self.stream().cloned().collect::<Vec<_>>();
-
-//         dst.copy_from_slice(&src);
-//         Ok(())
-//     }
-// }
-
-// #[test]
-// fn test_stream_from_file() {
-//     use ark_ff::{One, Zero};
-//     type F = ark_bls12_381::Fr;
-//     let a = [F::one(), F::one(), F::zero()];
-//     let stream_a = &a[..];
-//     assert!(stream_a.to_file("/tmp/test.mmap").is_ok());
-
-//     let (mmap, read_stream_a) = FieldStreamer::<F>::from_file("/tmp/test.mmap").unwrap();
-//     assert_eq!(stream_a.stream().next(), read_stream_a.stream().next());
-// }
+impl<T: CanonicalDeserialize> DoubleEndedIterable for MmapIterable<T> {
+    type RevIter = Rev<MmapIter<T>>;
+
+    fn rev_iter(&self) -> Self::RevIter {
+        self.iter().rev()
+    }
+}
+
+impl<T: CanonicalDeserialize> RandomAccessIterable for MmapIterable<T> {
+    fn iter_from(&self, idx: usize) -> Self::Iter {
+        let mut it = self.iter();
+        it.position = usize::min(idx, self.len()) * self.element_size;
+        it
+    }
+}
+
+#[test]
+fn test_mmap_iterable() {
+    use ark_bls12_381::{Fr, G1Affine};
+    use ark_std::test_rng;
+    use ark_std::vec::Vec;
+    use ark_std::UniformRand;
+
+    use crate::iterable::Reverse;
+
+    let rng = &mut test_rng();
+    let scalars = (0..100).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let path = std::env::temp_dir().join("gemini-test-mmap-scalars.bin");
+    let stream = MmapIterable::<Fr>::create(&path, &Reverse(scalars.as_slice())).unwrap();
+    assert_eq!(stream.len(), scalars.len());
+    assert!(stream.iter().eq(scalars.iter().rev().copied()));
+    assert!(stream.rev_iter().eq(scalars.iter().copied()));
+    let mut it = stream.iter();
+    assert!(it.advance_by(98).is_ok());
+    assert_eq!(it.next(), Some(scalars[1]));
+    assert_eq!(it.advance_by(2), Err(1));
+    assert_eq!(stream.iter_from(98).next(), Some(scalars[1]));
+    assert_eq!(stream.iter_from(100).next(), None);
+    std::fs::remove_file(&path).unwrap();
+
+    let points = (0..10).map(|_| G1Affine::rand(rng)).collect::<Vec<_>>();
+    let path = std::env::temp_dir().join("gemini-test-mmap-points.bin");
+    let stream = MmapIterable::<G1Affine>::create(&path, &points.as_slice()).unwrap();
+    assert!(stream.iter().eq(points.iter().copied()));
+    std::fs::remove_file(&path).unwrap();
+}
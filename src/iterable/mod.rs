@@ -1,10 +1,99 @@
 //! A base library for iterfacing with streams of vectors and matrices.
 //!
 //! This library extends the abstraction layer provided by [`ark_std::iterable::Iterable`]
-//! with streams that repeat the same element over and over, and that iterate in reversed order.
+//! with generic adapters (mapping, zipping, chaining, concatenating, interleaving, repeating,
+//! and slicing streams),
+//! with streams that repeat the same element over and over, that iterate in reversed order
+//! (or in both directions), that can be read from any position,
+//! that are recomputed at each pass by a closure (or expanded from a seed),
+//! that are held in memory when they fit a budget,
+//! that are read ahead on a background thread,
+//! that are cached on disk after their first pass, that are split across shards,
+//! that are deserialized on the fly from any seekable reader,
+//! that map machine integers or raw bytes into field elements,
+//! that pad vectors and matrices with zeros, that check their declared length,
+//! that record their read failures, that count (or log) their passes,
+//! that transpose (or canonicalize) sparse matrices in external memory,
+//! that (with the `parallel` feature) are processed in parallel, one chunk at a time,
+//! and (with the `mmap`, `compressed`, `columnar`, `async`, and `http` features)
+//! that read elements from memory-mapped files, compressed files, Arrow/Parquet columns,
+//! asynchronous sources, or remote files over HTTP(S).
 
+mod adapters;
+#[cfg(feature = "async")]
+mod async_source;
+#[cfg(feature = "std")]
+mod buffered;
+mod checked;
+#[cfg(feature = "columnar")]
+mod columnar;
+#[cfg(feature = "compressed")]
+mod compressed;
+mod counted;
+mod double_ended;
 pub mod dummy;
+#[cfg(feature = "std")]
+mod fallible;
+mod generator;
+#[cfg(feature = "http")]
+mod http;
+#[cfg(feature = "std")]
+mod instrumented;
+mod intofield;
+#[cfg(feature = "mmap")]
+mod mmap;
+mod padding;
+#[cfg(feature = "parallel")]
+mod parallel;
+mod random_access;
+#[cfg(feature = "std")]
+mod reader;
+mod resident;
+mod seeded;
+mod sharded;
 pub(crate) mod slice;
+#[cfg(feature = "std")]
+mod tee;
+#[cfg(feature = "std")]
+pub mod transpose;
 
+pub use adapters::{
+    Chain, Concat, Deinterleave, Interleave, InterleaveIter, Map, Repeat, RepeatIter, Skip, StepBy,
+    Take, Truncate, Zip,
+};
 pub use ark_std::iterable::Iterable;
+#[cfg(feature = "async")]
+pub use async_source::{AsyncIter, AsyncIterable};
+#[cfg(feature = "std")]
+pub use buffered::{Buffered, BufferedIter};
+pub use checked::{Checked, CheckedIter};
+#[cfg(feature = "columnar")]
+pub use columnar::{ColumnIter, ColumnIterable};
+#[cfg(feature = "compressed")]
+pub use compressed::{CompressedIter, CompressedIterable};
+pub use counted::Counted;
+pub use double_ended::DoubleEndedIterable;
+#[cfg(feature = "std")]
+pub use fallible::{Fallible, FallibleIter, ReadFailures};
+pub use generator::{FnIter, FnIterable, Generator};
+#[cfg(feature = "http")]
+pub use http::{HttpConfig, HttpIter, HttpIterable};
+#[cfg(feature = "std")]
+pub use instrumented::{Instrumented, InstrumentedIter, Pass, PassLog, PassReport, StreamPasses};
+pub use intofield::{ByteChunk, BytesIntoField, BytesIntoFieldIter, Endianness, IntoField, Limb};
+#[cfg(feature = "mmap")]
+pub use mmap::{MmapIter, MmapIterable};
+pub use padding::{MatrixPadded, PadToPow2, ZeroPadded};
+#[cfg(feature = "parallel")]
+pub use parallel::{Chunks, ParallelIterable};
+pub use random_access::{RandomAccessIterable, Slice};
+#[cfg(feature = "std")]
+pub use reader::{ReadIter, ReadIterable};
+pub use resident::{Resident, ResidentIter};
+pub use seeded::{Seeded, SeededIter};
+#[cfg(feature = "std")]
+pub use sharded::open_shards;
+pub use sharded::{Sharded, ShardedIter};
 pub use slice::Reverse;
+#[cfg(feature = "std")]
+pub use tee::{Tee, TeeIter};
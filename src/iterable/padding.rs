@@ -0,0 +1,270 @@
+//! Streams padded at their most significant positions.
+//!
+//! Streams are big-endian, hence padding a vector (or a matrix) with zeros
+//! at its most significant positions corresponds to prepending elements to the stream.
+use ark_ff::Field;
+use ark_std::borrow::Borrow;
+use ark_std::marker::PhantomData;
+
+use crate::iterable::Iterable;
+use crate::misc::MatrixElement;
+
+/// Stream `padding` zeros, followed by the elements of `stream`.
+pub struct ZeroPadded<'a, S, F> {
+    stream: &'a S,
+    padding: usize,
+    _field: PhantomData<F>,
+}
+
+impl<'a, S, F> ZeroPadded<'a, S, F>
+where
+    S: Iterable,
+    S::Item: Borrow<F>,
+    F: Field,
+{
+    /// Pad `stream` with zeros up to length `len`.
+    ///
+    /// # Panics
+    /// If `stream` is longer than `len`.
+    pub fn new(stream: &'a S, len: usize) -> Self {
+        assert!(
+            stream.len() <= len,
+            "the stream is longer than its padded length"
+        );
+        Self {
+            stream,
+            padding: len - stream.len(),
+            _field: PhantomData,
+        }
+    }
+}
+
+// `derive` would require `S: Copy` and `F: Copy`.
+impl<'a, S, F> Clone for ZeroPadded<'a, S, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, S, F> Copy for ZeroPadded<'a, S, F> {}
+
+/// The iterator returned by [`ZeroPadded`].
+pub struct ZeroPaddedIter<I, F> {
+    it: I,
+    padding: usize,
+    _field: PhantomData<F>,
+}
+
+impl<I, F> Iterator for ZeroPaddedIter<I, F>
+where
+    I: Iterator,
+    I::Item: Borrow<F>,
+    F: Field,
+{
+    type Item = F;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.padding > 0 {
+            self.padding -= 1;
+            Some(F::zero())
+        } else {
+            self.it.next().map(|x| *x.borrow())
+        }
+    }
+
+    fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        let skipped = usize::min(n, self.padding);
+        self.padding -= skipped;
+        self.it
+            .advance_by(n - skipped)
+            .map_err(|advanced| advanced + skipped)
+    }
+}
+
+impl<'a, S, F> Iterable for ZeroPadded<'a, S, F>
+where
+    S: Iterable,
+    S::Item: Borrow<F>,
+    F: Field,
+{
+    type Item = F;
+
+    type Iter = ZeroPaddedIter<S::Iter, F>;
+
+    fn iter(&self) -> Self::Iter {
+        ZeroPaddedIter {
+            it: self.stream.iter(),
+            padding: self.padding,
+            _field: PhantomData,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.padding + self.stream.len()
+    }
+}
+
+/// Stream `stream`, padded with zeros up to the next power of two.
+///
+/// This is the length expected by the provers for a vector of the (padded) instance.
+pub struct PadToPow2<'a, S, F>(ZeroPadded<'a, S, F>);
+
+impl<'a, S, F> PadToPow2<'a, S, F>
+where
+    S: Iterable,
+    S::Item: Borrow<F>,
+    F: Field,
+{
+    /// Pad `stream` with zeros up to length `stream.len().next_power_of_two()`.
+    pub fn new(stream: &'a S) -> Self {
+        Self(ZeroPadded::new(stream, stream.len().next_power_of_two()))
+    }
+}
+
+impl<'a, S, F> Clone for PadToPow2<'a, S, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, S, F> Copy for PadToPow2<'a, S, F> {}
+
+impl<'a, S, F> Iterable for PadToPow2<'a, S, F>
+where
+    S: Iterable,
+    S::Item: Borrow<F>,
+    F: Field,
+{
+    type Item = F;
+
+    type Iter = ZeroPaddedIter<S::Iter, F>;
+
+    fn iter(&self) -> Self::Iter {
+        self.0.iter()
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+}
+
+/// Stream `padding` empty lines, followed by the lines of the matrix stream `matrix`.
+pub struct MatrixPadded<'a, S, F> {
+    matrix: &'a S,
+    padding: usize,
+    _field: PhantomData<F>,
+}
+
+impl<'a, S, F> MatrixPadded<'a, S, F>
+where
+    S: Iterable,
+    S::Item: Borrow<MatrixElement<F>>,
+    F: Field,
+{
+    /// Prepend `padding` empty lines to `matrix`.
+    pub fn new(matrix: &'a S, padding: usize) -> Self {
+        Self {
+            matrix,
+            padding,
+            _field: PhantomData,
+        }
+    }
+}
+
+impl<'a, S, F> Clone for MatrixPadded<'a, S, F> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, S, F> Copy for MatrixPadded<'a, S, F> {}
+
+/// The iterator returned by [`MatrixPadded`].
+pub struct MatrixPaddedIter<I, F> {
+    it: I,
+    padding: usize,
+    _field: PhantomData<F>,
+}
+
+impl<I, F> Iterator for MatrixPaddedIter<I, F>
+where
+    I: Iterator,
+    I::Item: Borrow<MatrixElement<F>>,
+    F: Field,
+{
+    type Item = MatrixElement<F>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.padding > 0 {
+            self.padding -= 1;
+            Some(MatrixElement::EOL)
+        } else {
+            self.it.next().map(|x| *x.borrow())
+        }
+    }
+}
+
+impl<'a, S, F> Iterable for MatrixPadded<'a, S, F>
+where
+    S: Iterable,
+    S::Item: Borrow<MatrixElement<F>>,
+    F: Field,
+{
+    type Item = MatrixElement<F>;
+
+    type Iter = MatrixPaddedIter<S::Iter, F>;
+
+    fn iter(&self) -> Self::Iter {
+        MatrixPaddedIter {
+            it: self.matrix.iter(),
+            padding: self.padding,
+            _field: PhantomData,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.padding + self.matrix.len()
+    }
+}
+
+#[test]
+fn test_padded_streams() {
+    use ark_bls12_381::Fr;
+    use ark_std::vec::Vec;
+    use ark_std::{One, Zero};
+
+    let v = [Fr::one(), Fr::from(2u64)];
+    let padded = ZeroPadded::new(&&v[..], 4);
+    assert_eq!(padded.len(), 4);
+    assert_eq!(
+        padded.iter().collect::<Vec<_>>(),
+        [Fr::zero(), Fr::zero(), Fr::one(), Fr::from(2u64)]
+    );
+    let mut it = padded.iter();
+    assert!(it.advance_by(3).is_ok());
+    assert_eq!(it.next(), Some(Fr::from(2u64)));
+
+    let w = [Fr::one(), Fr::from(2u64), Fr::from(3u64)];
+    let padded = PadToPow2::new(&&w[..]);
+    assert_eq!(padded.len(), 4);
+    assert_eq!(
+        padded.iter().collect::<Vec<_>>(),
+        [Fr::zero(), Fr::one(), Fr::from(2u64), Fr::from(3u64)]
+    );
+    assert_eq!(PadToPow2::<_, Fr>::new(&&v[..]).len(), 2);
+
+    let m = [MatrixElement::Element((Fr::one(), 0)), MatrixElement::EOL];
+    let padded = MatrixPadded::new(&&m[..], 2);
+    assert_eq!(padded.len(), 4);
+    assert_eq!(
+        padded.iter().collect::<Vec<_>>(),
+        [
+            MatrixElement::EOL,
+            MatrixElement::EOL,
+            MatrixElement::Element((Fr::one(), 0)),
+            MatrixElement::EOL
+        ]
+    );
+}
@@ -0,0 +1,95 @@
+//! Parallel processing of streams.
+//!
+//! Reading a stream (e.g., from disk) is inherently sequential,
+//! but the arithmetic on its elements is not.
+//! A [`ParallelIterable`] reads the stream sequentially, one chunk at a time,
+//! and hands each chunk over to the [`rayon`] thread pool,
+//! so that chunks are processed in parallel while the underlying IO stays sequential.
+use ark_std::vec::Vec;
+use rayon::iter::{IterBridge, ParallelBridge};
+
+use crate::iterable::Iterable;
+
+/// Iterator over consecutive chunks of `chunk_size` elements of `it`,
+/// each together with the position of its first element in the stream.
+pub struct Chunks<I> {
+    it: I,
+    chunk_size: usize,
+    position: usize,
+}
+
+impl<I: Iterator> Chunks<I> {
+    /// Split `it` in chunks of `chunk_size` elements (the last one possibly shorter).
+    ///
+    /// # Panics
+    /// If `chunk_size` is zero.
+    pub fn new(it: I, chunk_size: usize) -> Self {
+        assert!(chunk_size != 0, "the chunk size must be positive");
+        Self {
+            it,
+            chunk_size,
+            position: 0,
+        }
+    }
+}
+
+impl<I: Iterator> Iterator for Chunks<I> {
+    type Item = (usize, Vec<I::Item>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = (&mut self.it).take(self.chunk_size).collect::<Vec<_>>();
+        if chunk.is_empty() {
+            return None;
+        }
+        let position = self.position;
+        self.position += chunk.len();
+        Some((position, chunk))
+    }
+}
+
+/// Extension of [`Iterable`] to rayon's parallel iterators.
+pub trait ParallelIterable: Iterable {
+    /// Return a parallel iterator over the chunks of `chunk_size` elements of the stream,
+    /// each together with the position of its first element.
+    ///
+    /// Chunks are read in order, but may be processed in any order:
+    /// at most one chunk per thread is held in memory at any given time.
+    fn par_chunks(&self, chunk_size: usize) -> IterBridge<Chunks<Self::Iter>>;
+}
+
+impl<S> ParallelIterable for S
+where
+    S: Iterable,
+    S::Iter: Send,
+    S::Item: Send,
+{
+    fn par_chunks(&self, chunk_size: usize) -> IterBridge<Chunks<Self::Iter>> {
+        Chunks::new(self.iter(), chunk_size).par_bridge()
+    }
+}
+
+#[test]
+fn test_par_chunks() {
+    use rayon::iter::ParallelIterator;
+
+    use crate::iterable::Reverse;
+
+    let v = (0..1000u64).collect::<Vec<_>>();
+    let stream = Reverse(v.as_slice());
+
+    let sum = stream
+        .par_chunks(64)
+        .map(|(_, chunk)| chunk.into_iter().sum::<u64>())
+        .sum::<u64>();
+    assert_eq!(sum, v.iter().sum::<u64>());
+
+    let mut chunks = stream.par_chunks(64).collect::<Vec<_>>();
+    chunks.sort_by_key(|&(position, _)| position);
+    assert_eq!(chunks.len(), 16);
+    assert_eq!(chunks[15].1.len(), 1000 - 15 * 64);
+    let flattened = chunks
+        .into_iter()
+        .flat_map(|(_, chunk)| chunk)
+        .collect::<Vec<_>>();
+    assert!(flattened.into_iter().eq(stream.iter()));
+}
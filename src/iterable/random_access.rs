@@ -0,0 +1,127 @@
+//! Streams that can be read from any position.
+//!
+//! The provers frequently align streams of different length
+//! by skipping the most significant elements of the longest one.
+//! Skipping with [`Iterator::advance_by`] may read (and discard) all the skipped elements;
+//! a [`RandomAccessIterable`] instead starts reading directly from the given position.
+use ark_std::vec::Vec;
+
+use crate::iterable::{Iterable, Reverse};
+
+/// A stream whose passes can start from any of its elements.
+pub trait RandomAccessIterable: Iterable {
+    /// Return an iterator over the stream, starting from the element at position `idx`
+    /// (in stream order).
+    ///
+    /// The iterator is empty if `idx` is not smaller than the length of the stream.
+    fn iter_from(&self, idx: usize) -> Self::Iter;
+
+    /// Return a view over the (at most) `len` elements of the stream from position `offset`
+    /// (in stream order), positioned directly with [`RandomAccessIterable::iter_from`].
+    fn slice(&self, offset: usize, len: usize) -> Slice<'_, Self>
+    where
+        Self: Sized,
+    {
+        Slice {
+            stream: self,
+            offset,
+            len,
+        }
+    }
+}
+
+/// A view over a contiguous range of a stream (see [`RandomAccessIterable::slice`]).
+pub struct Slice<'a, S> {
+    stream: &'a S,
+    offset: usize,
+    len: usize,
+}
+
+// manual implementations, not requiring `S: Clone`.
+impl<'a, S> Clone for Slice<'a, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, S> Copy for Slice<'a, S> {}
+
+impl<'a, S: RandomAccessIterable> Iterable for Slice<'a, S> {
+    type Item = S::Item;
+
+    type Iter = ark_std::iter::Take<S::Iter>;
+
+    fn iter(&self) -> Self::Iter {
+        self.stream.iter_from(self.offset).take(self.len)
+    }
+
+    fn len(&self) -> usize {
+        usize::min(self.len, self.stream.len().saturating_sub(self.offset))
+    }
+}
+
+impl<'a, S: RandomAccessIterable> RandomAccessIterable for Slice<'a, S> {
+    fn iter_from(&self, idx: usize) -> Self::Iter {
+        let idx = usize::min(idx, self.len);
+        self.stream
+            .iter_from(self.offset + idx)
+            .take(self.len - idx)
+    }
+}
+
+impl<'a, T: Sync> RandomAccessIterable for &'a [T] {
+    fn iter_from(&self, idx: usize) -> Self::Iter {
+        let slice: &'a [T] = *self;
+        slice[usize::min(idx, slice.len())..].iter()
+    }
+}
+
+impl<'a, T: Sync> RandomAccessIterable for &'a Vec<T> {
+    fn iter_from(&self, idx: usize) -> Self::Iter {
+        let vec: &'a Vec<T> = *self;
+        vec.as_slice().iter_from(idx)
+    }
+}
+
+impl<'a, T: Sync> RandomAccessIterable for Reverse<&'a [T]> {
+    fn iter_from(&self, idx: usize) -> Self::Iter {
+        let slice: &'a [T] = self.0;
+        let end = slice.len() - usize::min(idx, slice.len());
+        slice[..end].iter().rev()
+    }
+}
+
+#[test]
+fn test_random_access_iterable() {
+    use crate::iterable::dummy::DummyStreamer;
+
+    let v = (0..10u64).collect::<Vec<_>>();
+    let s = Reverse(v.as_slice());
+    for idx in 0..12 {
+        let mut it = s.iter();
+        let _ = it.advance_by(idx);
+        assert!(s.iter_from(idx).eq(it));
+        let mut it = (&v).iter();
+        let _ = it.advance_by(idx);
+        assert!((&v).iter_from(idx).eq(it));
+    }
+
+    let dummy = DummyStreamer::new(1u64, 5);
+    assert_eq!(dummy.iter_from(2).count(), 3);
+    assert_eq!(dummy.iter_from(7).count(), 0);
+}
+
+#[test]
+fn test_slice() {
+    let v = (0..10u64).collect::<Vec<_>>();
+    let s = Reverse(v.as_slice());
+    for (offset, len) in [(0, 10), (2, 3), (7, 5), (10, 1), (12, 0)] {
+        let slice = s.slice(offset, len);
+        let expected = s.iter().skip(offset).take(len);
+        assert_eq!(slice.len(), expected.clone().count());
+        assert!(slice.iter().eq(expected));
+        assert!(slice
+            .iter_from(1)
+            .eq(s.iter().skip(offset).take(len).skip(1)));
+    }
+}
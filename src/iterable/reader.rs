@@ -0,0 +1,245 @@
+//! Streams backed by generic readers.
+//!
+//! Files are not the only storage of large vectors: elements may sit in an archive member,
+//! in a custom storage layer, or in memory (e.g., in a [`Cursor`](std::io::Cursor)).
+//! A [`ReadIterable`] wraps any seekable reader of (uncompressed) canonically-serialized elements,
+//! and deserializes its elements on the fly, one block of [`ReadIterable::buffer_size`] elements at a time.
+//! The reader is shared by all the passes over the stream:
+//! each block is read after seeking to its position, hence passes may start from any position
+//! and be interleaved.
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::{Arc, Mutex};
+
+use crate::iterable::{Iterable, RandomAccessIterable};
+
+/// A stream over the elements serialized in a reader.
+///
+/// The elements follow the current position of the reader when the stream is created,
+/// up to its end, in stream order (that is, big-endian),
+/// each serialized with [`CanonicalSerialize::serialize_uncompressed`].
+/// Elements are checked (e.g., for subgroup membership) when deserialized.
+/// Iterators panic if a block cannot be read.
+pub struct ReadIterable<R, T> {
+    reader: Arc<Mutex<R>>,
+    /// The position of the first element in the reader, in bytes.
+    start: u64,
+    len: usize,
+    element_size: usize,
+    buffer_size: usize,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<R, T> Clone for ReadIterable<R, T> {
+    fn clone(&self) -> Self {
+        Self {
+            reader: self.reader.clone(),
+            start: self.start,
+            len: self.len,
+            element_size: self.element_size,
+            buffer_size: self.buffer_size,
+            _item: PhantomData,
+        }
+    }
+}
+
+impl<R, T> ReadIterable<R, T>
+where
+    R: Read + Seek + Send,
+    T: CanonicalDeserialize + CanonicalSerialize + Default,
+{
+    /// Stream the elements of `reader`, from its current position,
+    /// reading `buffer_size` elements at a time.
+    ///
+    /// Fails if the remaining size of the reader is not a multiple of the size of a serialized element.
+    pub fn new(mut reader: R, buffer_size: usize) -> io::Result<Self> {
+        assert!(buffer_size > 0, "blocks must not be empty");
+        let start = reader.stream_position()?;
+        let size = reader.seek(SeekFrom::End(0))? - start;
+        let element_size = T::default().uncompressed_size();
+        if size % element_size as u64 != 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "the size of the reader is not a multiple of the element size",
+            ));
+        }
+        // the number of elements may not fit the address space of 32-bit targets.
+        let len = usize::try_from(size / element_size as u64)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(Self {
+            reader: Arc::new(Mutex::new(reader)),
+            start,
+            len,
+            element_size,
+            buffer_size,
+            _item: PhantomData,
+        })
+    }
+}
+
+impl<R, T> ReadIterable<R, T> {
+    /// The number of elements read at once.
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+
+    /// Read `buffer_size` elements at once.
+    pub fn with_buffer_size(self, buffer_size: usize) -> Self {
+        assert!(buffer_size > 0, "blocks must not be empty");
+        Self {
+            buffer_size,
+            ..self
+        }
+    }
+}
+
+/// The iterator returned by [`ReadIterable`].
+pub struct ReadIter<R, T> {
+    reader: Arc<Mutex<R>>,
+    start: u64,
+    /// The index of the next element.
+    position: usize,
+    len: usize,
+    element_size: usize,
+    buffer_size: usize,
+    /// The current block, and the index of its first element.
+    block: Vec<u8>,
+    block_start: usize,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<R: Read + Seek, T> ReadIter<R, T> {
+    /// Make the block starting at the element `position` current.
+    fn read_block(&mut self) -> io::Result<()> {
+        let end = usize::min(self.position + self.buffer_size, self.len);
+        self.block
+            .resize((end - self.position) * self.element_size, 0);
+        let offset = self.start + (self.position * self.element_size) as u64;
+        // a panicking pass does not prevent the others from reading.
+        let mut reader = self.reader.lock().unwrap_or_else(|e| e.into_inner());
+        reader.seek(SeekFrom::Start(offset))?;
+        reader.read_exact(&mut self.block)?;
+        self.block_start = self.position;
+        Ok(())
+    }
+}
+
+impl<R: Read + Seek, T: CanonicalDeserialize> Iterator for ReadIter<R, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.position == self.len {
+            return None;
+        }
+        let block_len = self.block.len() / self.element_size;
+        if self.position < self.block_start || self.position >= self.block_start + block_len {
+            self.read_block()
+                .unwrap_or_else(|e| panic!("could not read the stream: {e}"));
+        }
+        let offset = (self.position - self.block_start) * self.element_size;
+        let bytes = &self.block[offset..offset + self.element_size];
+        self.position += 1;
+        Some(T::deserialize_uncompressed(bytes).expect("invalid element in the stream"))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.len - self.position;
+        (remaining, Some(remaining))
+    }
+
+    fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        // skipped elements are not read.
+        let advanced = usize::min(n, self.len - self.position);
+        self.position += advanced;
+        if advanced == n {
+            Ok(())
+        } else {
+            Err(advanced)
+        }
+    }
+}
+
+impl<R, T> Iterable for ReadIterable<R, T>
+where
+    R: Read + Seek + Send,
+    T: CanonicalDeserialize,
+{
+    type Item = T;
+
+    type Iter = ReadIter<R, T>;
+
+    fn iter(&self) -> Self::Iter {
+        self.iter_from(0)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<R, T> RandomAccessIterable for ReadIterable<R, T>
+where
+    R: Read + Seek + Send,
+    T: CanonicalDeserialize,
+{
+    fn iter_from(&self, idx: usize) -> Self::Iter {
+        ReadIter {
+            reader: self.reader.clone(),
+            start: self.start,
+            position: usize::min(idx, self.len),
+            len: self.len,
+            element_size: self.element_size,
+            buffer_size: self.buffer_size,
+            block: Vec::new(),
+            block_start: 0,
+            _item: PhantomData,
+        }
+    }
+}
+
+#[test]
+fn test_read_iterable() {
+    use ark_bls12_381::{Fr, G1Affine};
+    use ark_std::test_rng;
+    use ark_std::UniformRand;
+    use std::io::Cursor;
+
+    let rng = &mut test_rng();
+    let scalars = (0..100).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    // the elements follow a header, skipped before creating the stream.
+    let mut bytes = b"header".to_vec();
+    for s in &scalars {
+        s.serialize_uncompressed(&mut bytes).unwrap();
+    }
+    let mut reader = Cursor::new(bytes);
+    reader.seek(SeekFrom::Start(6)).unwrap();
+    let stream = ReadIterable::<_, Fr>::new(reader, 7).unwrap();
+    assert_eq!(stream.len(), scalars.len());
+    assert_eq!(stream.buffer_size(), 7);
+    assert!(stream.iter().eq(scalars.iter().copied()));
+
+    // passes share the reader, and can be interleaved.
+    let mut first = stream.iter();
+    let mut second = stream.iter_from(98);
+    assert_eq!(first.next(), Some(scalars[0]));
+    assert_eq!(second.next(), Some(scalars[98]));
+    assert!(first.advance_by(49).is_ok());
+    assert_eq!(first.next(), Some(scalars[50]));
+    assert_eq!(first.advance_by(50), Err(49));
+    assert_eq!(stream.iter_from(100).next(), None);
+    let stream = stream.with_buffer_size(1 << 10);
+    assert!(stream.iter().eq(scalars.iter().copied()));
+
+    let points = (0..10).map(|_| G1Affine::rand(rng)).collect::<Vec<_>>();
+    let mut bytes = Vec::new();
+    for p in &points {
+        p.serialize_uncompressed(&mut bytes).unwrap();
+    }
+    let stream = ReadIterable::<_, G1Affine>::new(Cursor::new(bytes.clone()), 3).unwrap();
+    assert!(stream.iter().eq(points.iter().copied()));
+    // truncated readers are rejected.
+    bytes.pop();
+    assert!(ReadIterable::<_, G1Affine>::new(Cursor::new(bytes), 3).is_err());
+}
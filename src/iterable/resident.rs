@@ -0,0 +1,130 @@
+//! Streams held in memory when they fit.
+//!
+//! Elastic provers read some of their streams many times, and each pass may recompute them
+//! from the instance (or read them from disk).
+//! A [`Resident`] stream is either the elements of a stream, read once into memory,
+//! or the stream itself,
+//! so that a prover can choose, for each stream, between holding it and passing over it again.
+use ark_std::iter::Cloned;
+use ark_std::mem::size_of;
+use ark_std::slice;
+use ark_std::vec::Vec;
+
+use crate::iterable::Iterable;
+
+/// A stream whose elements are either held in memory, or streamed from `S`.
+#[derive(Clone, Copy)]
+pub enum Resident<'a, S: Iterable> {
+    /// The elements of the stream, in the order of the stream.
+    Memory(&'a [S::Item]),
+    /// The stream itself.
+    Stream(S),
+}
+
+impl<'a, S> Resident<'a, S>
+where
+    S: Iterable,
+    S::Item: Clone + Sync,
+{
+    /// Read `stream` into memory if its elements take at most `budget` bytes,
+    /// and deduct their size from the budget.
+    pub fn load(stream: &S, budget: &mut usize) -> Option<Vec<S::Item>> {
+        let size = stream.len().saturating_mul(size_of::<S::Item>());
+        if size > *budget {
+            return None;
+        }
+        *budget -= size;
+        Some(stream.iter().collect())
+    }
+
+    /// Stream from `memory`, if the stream has been loaded (see [`Resident::load`]),
+    /// and from `stream` otherwise.
+    pub fn new(stream: S, memory: Option<&'a [S::Item]>) -> Self {
+        match memory {
+            Some(elements) => Resident::Memory(elements),
+            None => Resident::Stream(stream),
+        }
+    }
+
+    /// Return `true` if the elements are held in memory.
+    pub fn is_resident(&self) -> bool {
+        matches!(self, Resident::Memory(_))
+    }
+}
+
+/// The iterator returned by [`Resident`].
+pub enum ResidentIter<'a, T, I> {
+    /// Iterating over the elements in memory.
+    Memory(Cloned<slice::Iter<'a, T>>),
+    /// Iterating over the stream.
+    Stream(I),
+}
+
+impl<'a, T: Clone, I: Iterator<Item = T>> Iterator for ResidentIter<'a, T, I> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match self {
+            ResidentIter::Memory(it) => it.next(),
+            ResidentIter::Stream(it) => it.next(),
+        }
+    }
+
+    #[inline]
+    fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        match self {
+            ResidentIter::Memory(it) => it.advance_by(n),
+            ResidentIter::Stream(it) => it.advance_by(n),
+        }
+    }
+}
+
+impl<'a, S> Iterable for Resident<'a, S>
+where
+    S: Iterable,
+    S::Item: Clone + Sync,
+{
+    type Item = S::Item;
+
+    type Iter = ResidentIter<'a, S::Item, S::Iter>;
+
+    fn iter(&self) -> Self::Iter {
+        match self {
+            Resident::Memory(elements) => ResidentIter::Memory(elements.iter().cloned()),
+            Resident::Stream(stream) => ResidentIter::Stream(stream.iter()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            Resident::Memory(elements) => elements.len(),
+            Resident::Stream(stream) => stream.len(),
+        }
+    }
+}
+
+#[test]
+fn test_resident() {
+    use crate::iterable::Reverse;
+
+    let v = [1u64, 2, 3, 4];
+    let stream = Reverse(&v[..]);
+    // the budget is in bytes, and the stream yields references.
+    let mut budget = 6 * size_of::<&u64>();
+    let memory = Resident::load(&stream, &mut budget);
+    assert_eq!(budget, 2 * size_of::<&u64>());
+    // the budget left does not fit the stream a second time.
+    assert!(Resident::load(&stream, &mut budget).is_none());
+    assert_eq!(budget, 2 * size_of::<&u64>());
+
+    let resident = Resident::new(stream, memory.as_deref());
+    let streamed = Resident::new(stream, None);
+    assert!(resident.is_resident() && !streamed.is_resident());
+    assert_eq!(resident.len(), streamed.len());
+    assert!(resident.iter().eq(stream.iter()));
+    assert!(streamed.iter().eq(stream.iter()));
+    let mut it = resident.iter();
+    assert!(it.advance_by(2).is_ok());
+    assert_eq!(it.next(), Some(&2));
+}
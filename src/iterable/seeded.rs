@@ -0,0 +1,157 @@
+//! Pseudorandom streams expanded from a seed.
+//!
+//! Masking vectors, the coefficients of random linear combinations, and test fixtures
+//! are random vectors that only need to be reproducible.
+//! A [`Seeded`] stream stores none of its elements:
+//! each block of `BLOCK_SIZE` coefficients is sampled anew at each pass,
+//! from a ChaCha-based generator ([`StdRng`]) seeded with the hash of the seed and of the block position.
+//! Hence, the coefficient of degree $i$ depends only on the seed and on $i$
+//! (and not on the length of the stream), and passes may start from any position.
+use ark_std::marker::PhantomData;
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::SeedableRng;
+use ark_std::vec::Vec;
+use ark_std::UniformRand;
+use sha2::{Digest, Sha256};
+
+use crate::iterable::{Iterable, RandomAccessIterable};
+
+/// The number of coefficients sampled from the same generator.
+const BLOCK_SIZE: usize = 1 << 8;
+
+/// The domain separator of the seeds of the blocks.
+const SEEDED_LABEL: &[u8] = b"gemini-seeded-stream";
+
+/// A (big-endian) stream of `len` pseudorandom elements, expanded from `seed`.
+pub struct Seeded<T> {
+    seed: [u8; 32],
+    len: usize,
+    _element: PhantomData<T>,
+}
+
+// manual implementations, not requiring `T: Clone`.
+impl<T> Clone for Seeded<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Seeded<T> {}
+
+impl<T: UniformRand + Send + Sync> Seeded<T> {
+    /// A stream of `len` elements expanded from `seed`.
+    pub fn new(seed: [u8; 32], len: usize) -> Self {
+        Self {
+            seed,
+            len,
+            _element: PhantomData,
+        }
+    }
+}
+
+/// The generator of the block `block` of the stream expanded from `seed`.
+fn block_rng(seed: &[u8; 32], block: usize) -> StdRng {
+    let mut hasher = Sha256::new();
+    hasher.update(SEEDED_LABEL);
+    hasher.update(seed);
+    hasher.update((block as u64).to_le_bytes());
+    StdRng::from_seed(hasher.finalize().into())
+}
+
+/// The iterator returned by [`Seeded`].
+pub struct SeededIter<T> {
+    seed: [u8; 32],
+    /// The number of coefficients, from degree zero, not yet sampled.
+    next: usize,
+    /// The coefficients sampled and not yet streamed, from the lowest degree.
+    buffer: Vec<T>,
+}
+
+impl<T: UniformRand> Iterator for SeededIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.buffer.is_empty() {
+            if self.next == 0 {
+                return None;
+            }
+            // sample the block of the next coefficient, up to the coefficient itself.
+            let start = (self.next - 1) / BLOCK_SIZE * BLOCK_SIZE;
+            let rng = &mut block_rng(&self.seed, start / BLOCK_SIZE);
+            self.buffer.extend((start..self.next).map(|_| T::rand(rng)));
+            self.next = start;
+        }
+        self.buffer.pop()
+    }
+
+    fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        let buffered = usize::min(n, self.buffer.len());
+        self.buffer.truncate(self.buffer.len() - buffered);
+        // skipped coefficients out of the buffer are not sampled.
+        let skipped = usize::min(n - buffered, self.next);
+        self.next -= skipped;
+        if buffered + skipped == n {
+            Ok(())
+        } else {
+            Err(buffered + skipped)
+        }
+    }
+}
+
+impl<T: UniformRand + Send + Sync> Iterable for Seeded<T> {
+    type Item = T;
+
+    type Iter = SeededIter<T>;
+
+    fn iter(&self) -> Self::Iter {
+        self.iter_from(0)
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<T: UniformRand + Send + Sync> RandomAccessIterable for Seeded<T> {
+    fn iter_from(&self, idx: usize) -> Self::Iter {
+        SeededIter {
+            seed: self.seed,
+            next: self.len - usize::min(idx, self.len),
+            buffer: Vec::new(),
+        }
+    }
+}
+
+#[test]
+fn test_seeded() {
+    use ark_bls12_381::Fr;
+
+    let len = 3 * BLOCK_SIZE + 5;
+    let stream = Seeded::<Fr>::new([7; 32], len);
+    let elements = stream.iter().collect::<Vec<_>>();
+    assert_eq!(elements.len(), len);
+    assert!(stream.iter().eq(elements.iter().cloned()));
+    assert!(Seeded::<Fr>::new([8; 32], len)
+        .iter()
+        .ne(elements.iter().cloned()));
+
+    // the coefficients do not depend on the length of the stream.
+    let short = Seeded::<Fr>::new([7; 32], BLOCK_SIZE + 1);
+    assert!(short
+        .iter()
+        .eq(elements[len - short.len()..].iter().cloned()));
+
+    // passes can start from any position, and skip without sampling.
+    for idx in [0, 1, BLOCK_SIZE, 2 * BLOCK_SIZE + 3, len, len + 1] {
+        let skipped = elements.get(idx..).unwrap_or(&[]);
+        assert!(stream.iter_from(idx).eq(skipped.iter().cloned()));
+        let mut it = stream.iter();
+        let _ = it.advance_by(idx);
+        assert!(it.eq(skipped.iter().cloned()));
+    }
+    let mut it = stream.iter();
+    assert_eq!(it.next(), Some(elements[0]));
+    assert!(it.advance_by(BLOCK_SIZE).is_ok());
+    assert_eq!(it.next(), Some(elements[BLOCK_SIZE + 1]));
+    assert_eq!(it.advance_by(len), Err(len - BLOCK_SIZE - 2));
+}
@@ -0,0 +1,245 @@
+//! Streams split across multiple shards.
+//!
+//! Distributed witness generation (or indexing) produces a vector split into shards,
+//! usually numbered files `<path>.0`, `<path>.1`, ...,
+//! where the shard $i$ holds the elements following the ones of the shard $i-1$.
+//! A [`Sharded`] stream concatenates the shards in stream order (from the last shard to the first),
+//! so that the indexer and the provers can read them without merging them first.
+//!
+//! Each shard is itself a stream of its elements, in stream order,
+//! and declares its length: the number of elements for vectors,
+//! and the number of lines for matrices (whose lines must not cross the shard boundaries).
+//! The boundaries are validated with [`Sharded::check_lengths`] and [`Sharded::check_lines`].
+use ark_std::borrow::Borrow;
+use ark_std::vec::Vec;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::path::{Path, PathBuf};
+
+use crate::errors::{GeminiError, GeminiResult};
+use crate::iterable::{Iterable, RandomAccessIterable};
+use crate::misc::MatrixElement;
+
+/// The concatenation of the shards `shards`, given in vector order.
+pub struct Sharded<'a, S> {
+    label: &'static str,
+    shards: &'a [S],
+}
+
+// the stream only borrows the shards, hence it is `Copy` even if the shards are not.
+impl<'a, S> Clone for Sharded<'a, S> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, S> Copy for Sharded<'a, S> {}
+
+impl<'a, S: Iterable> Sharded<'a, S> {
+    /// Concatenate `shards`, the first one holding the least significant elements,
+    /// and label the stream `label` in the error messages.
+    pub fn new(label: &'static str, shards: &'a [S]) -> Self {
+        Self { label, shards }
+    }
+
+    /// Return the position, within the vector, of the first element of each shard.
+    pub fn offsets(&self) -> Vec<usize> {
+        self.shards
+            .iter()
+            .scan(0, |offset, shard| {
+                let current = *offset;
+                *offset += shard.len();
+                Some(current)
+            })
+            .collect()
+    }
+
+    /// Fail unless each (vector) shard yields as many elements as declared.
+    ///
+    /// This requires a pass over the shards.
+    pub fn check_lengths(&self) -> GeminiResult<()> {
+        for shard in self.shards {
+            GeminiError::ensure_len(self.label, shard.len(), shard.iter().count())?;
+        }
+        Ok(())
+    }
+
+    /// Fail unless each (matrix) shard holds as many lines as declared,
+    /// the last one being complete.
+    ///
+    /// This requires a pass over the shards.
+    pub fn check_lines<F>(&self) -> GeminiResult<()>
+    where
+        S::Item: Borrow<MatrixElement<F>>,
+    {
+        for shard in self.shards {
+            let mut lines = 0;
+            let mut complete = true;
+            for element in shard.iter() {
+                complete = element.borrow().is_eol();
+                lines += complete as usize;
+            }
+            if !complete {
+                return Err(GeminiError::MalformedInstance {
+                    reason: "a matrix shard ends within a line",
+                });
+            }
+            GeminiError::ensure_len(self.label, shard.len(), lines)?;
+        }
+        Ok(())
+    }
+}
+
+/// The iterator returned by [`Sharded`].
+pub struct ShardedIter<'a, S: Iterable> {
+    /// The shards not yet started, in vector order.
+    shards: &'a [S],
+    current: Option<S::Iter>,
+}
+
+impl<'a, S: Iterable> Iterator for ShardedIter<'a, S> {
+    type Item = S::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(element) = self.current.as_mut().and_then(|it| it.next()) {
+                return Some(element);
+            }
+            let (last, rest) = self.shards.split_last()?;
+            self.shards = rest;
+            self.current = Some(last.iter());
+        }
+    }
+}
+
+impl<'a, S: Iterable> Iterable for Sharded<'a, S> {
+    type Item = S::Item;
+
+    type Iter = ShardedIter<'a, S>;
+
+    fn iter(&self) -> Self::Iter {
+        ShardedIter {
+            shards: self.shards,
+            current: None,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.shards.iter().map(|shard| shard.len()).sum()
+    }
+}
+
+impl<'a, S: RandomAccessIterable> RandomAccessIterable for Sharded<'a, S> {
+    /// Start from the shard holding the element at position `idx`, skipping the previous ones.
+    fn iter_from(&self, idx: usize) -> Self::Iter {
+        let mut shards = self.shards;
+        let mut idx = idx;
+        while let Some((last, rest)) = shards.split_last() {
+            if idx < last.len() {
+                return ShardedIter {
+                    shards: rest,
+                    current: Some(last.iter_from(idx)),
+                };
+            }
+            idx -= last.len();
+            shards = rest;
+        }
+        ShardedIter {
+            shards,
+            current: None,
+        }
+    }
+}
+
+/// Open with `open` the numbered shards `<path>.0`, `<path>.1`, ...,
+/// up to the first missing one.
+///
+/// Fails if `<path>.0` does not exist, or if `open` fails.
+#[cfg(feature = "std")]
+pub fn open_shards<S, P, O>(path: P, mut open: O) -> io::Result<Vec<S>>
+where
+    P: AsRef<Path>,
+    O: FnMut(&Path) -> io::Result<S>,
+{
+    let mut shards = Vec::new();
+    loop {
+        let mut shard_path = path.as_ref().as_os_str().to_owned();
+        shard_path.push(std::format!(".{}", shards.len()));
+        let shard_path = PathBuf::from(shard_path);
+        if !shard_path.exists() {
+            break;
+        }
+        shards.push(open(&shard_path)?);
+    }
+    if shards.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            "no shard found for the given path",
+        ));
+    }
+    Ok(shards)
+}
+
+#[test]
+fn test_sharded() {
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+    use ark_std::UniformRand;
+
+    use crate::iterable::Reverse;
+
+    let rng = &mut test_rng();
+    let vector = (0..10).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let shards = [
+        Reverse(&vector[..3]),
+        Reverse(&vector[3..3]),
+        Reverse(&vector[3..]),
+    ];
+    let stream = Sharded::new("vector", &shards);
+    assert_eq!(stream.len(), vector.len());
+    assert_eq!(stream.offsets(), vec![0, 3, 3]);
+    assert!(stream.iter().eq(vector.iter().rev()));
+    assert!(stream.check_lengths().is_ok());
+    for idx in [0, 6, 7, 9, 10] {
+        assert!(stream.iter_from(idx).eq(vector.iter().rev().skip(idx)));
+    }
+}
+
+#[test]
+fn test_sharded_matrix() {
+    use ark_bls12_381::Fr;
+
+    use crate::iterable::dummy::Mat;
+
+    let element = MatrixElement::Element((Fr::from(1u64), 0));
+    let line = [element, MatrixElement::EOL];
+    // an empty line, and a line of two elements.
+    let lines = [MatrixElement::EOL, element, element, MatrixElement::EOL];
+    let shards = [Mat(&line[..], 1), Mat(&lines[..], 2)];
+    assert!(Sharded::new("matrix", &shards).check_lines::<Fr>().is_ok());
+    let shards = [Mat(&line[..], 2), Mat(&lines[..], 2)];
+    assert!(Sharded::new("matrix", &shards).check_lines::<Fr>().is_err());
+    // the first shard ends within a line.
+    let shards = [Mat(&lines[..3], 1), Mat(&line[..], 1)];
+    assert!(Sharded::new("matrix", &shards).check_lines::<Fr>().is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_open_shards() {
+    let path = std::env::temp_dir().join(std::format!("gemini-shards-{}", std::process::id()));
+    assert!(open_shards(&path, |p| Ok(p.to_path_buf())).is_err());
+    for i in 0..3 {
+        let mut shard_path = path.as_os_str().to_owned();
+        shard_path.push(std::format!(".{}", i));
+        std::fs::write(&shard_path, [i as u8]).unwrap();
+    }
+    let shards = open_shards(&path, std::fs::read).unwrap();
+    assert_eq!(shards, vec![vec![0], vec![1], vec![2]]);
+    for i in 0..3 {
+        let mut shard_path = path.as_os_str().to_owned();
+        shard_path.push(std::format!(".{}", i));
+        std::fs::remove_file(shard_path).unwrap();
+    }
+}
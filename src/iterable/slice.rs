@@ -27,19 +27,3 @@ where
         self.0.len()
     }
 }
-
-// So my understa
-pub struct IterableRange(pub usize);
-
-impl Iterable for IterableRange {
-    type Item = usize;
-    type Iter = ark_std::iter::Rev<ark_std::ops::Range<usize>>;
-
-    fn iter(&self) -> Self::Iter {
-        (0..self.0).into_iter().rev()
-    }
-
-    fn len(&self) -> usize {
-        self.0
-    }
-}
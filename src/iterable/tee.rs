@@ -0,0 +1,235 @@
+//! Streams cached on disk after their first pass.
+//!
+//! Some derived streams (e.g., folded polynomials) are expensive to recompute,
+//! and are iterated multiple times by the protocols.
+//! A [`Tee`] stream writes the elements to a temporary file during the first complete pass,
+//! and serves all later passes from the file,
+//! trading the recomputation of the stream for its storage on disk.
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::borrow::Borrow;
+use ark_std::marker::PhantomData;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::iterable::Iterable;
+
+/// Counter distinguishing the caches of different streams.
+static TEE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// The state of the cache on disk.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Cache {
+    /// No complete pass over the stream has been recorded.
+    Empty,
+    /// A pass over the stream is being recorded.
+    Recording,
+    /// The file holds all the elements of the stream.
+    Ready,
+}
+
+/// A stream recording the elements of `stream` to disk during its first pass,
+/// and reading them back from disk afterwards.
+///
+/// Passes started while the first one is still being recorded read `stream` directly.
+/// The elements are serialized uncompressed, and not checked when read back.
+/// The cache is deleted once the stream is dropped.
+pub struct Tee<S, T> {
+    stream: S,
+    path: PathBuf,
+    cache: Arc<Mutex<Cache>>,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<S, T> Tee<S, T>
+where
+    S: Iterable,
+    S::Item: Borrow<T>,
+    T: CanonicalSerialize + CanonicalDeserialize + Clone,
+{
+    /// Cache `stream` in a new file in the temporary directory.
+    pub fn new(stream: S) -> Self {
+        let path = std::env::temp_dir().join(std::format!(
+            "gemini-tee-{}-{}",
+            std::process::id(),
+            TEE_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        Self::with_path(stream, path)
+    }
+
+    /// Cache `stream` in the file at `path`.
+    ///
+    /// The file is overwritten, and deleted once the stream is dropped.
+    pub fn with_path(stream: S, path: PathBuf) -> Self {
+        Self {
+            stream,
+            path,
+            cache: Arc::new(Mutex::new(Cache::Empty)),
+            _item: PhantomData,
+        }
+    }
+
+    /// Return `true` if the later passes are served from disk.
+    pub fn is_cached(&self) -> bool {
+        *self.cache.lock().unwrap() == Cache::Ready
+    }
+}
+
+impl<S, T> Drop for Tee<S, T> {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// The recording of a pass over the stream.
+///
+/// If the pass is abandoned (or fails) before the end of the stream,
+/// the cache is reset, and the next pass is recorded anew.
+struct Recorder {
+    writer: Option<BufWriter<File>>,
+    cache: Arc<Mutex<Cache>>,
+}
+
+impl Recorder {
+    fn record<T: CanonicalSerialize>(&mut self, element: &T) {
+        if let Some(writer) = self.writer.as_mut() {
+            if element.serialize_uncompressed(writer).is_err() {
+                self.writer = None;
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        if let Some(mut writer) = self.writer.take() {
+            if writer.flush().is_ok() {
+                *self.cache.lock().unwrap() = Cache::Ready;
+            }
+        }
+    }
+}
+
+impl Drop for Recorder {
+    fn drop(&mut self) {
+        let mut cache = self.cache.lock().unwrap();
+        if *cache == Cache::Recording {
+            *cache = Cache::Empty;
+        }
+    }
+}
+
+/// The iterator returned by [`Tee`].
+pub struct TeeIter<I, T> {
+    pass: Pass<I>,
+    _item: PhantomData<fn() -> T>,
+}
+
+enum Pass<I> {
+    Source(I),
+    Recording(I, Recorder),
+    Cached(BufReader<File>, usize),
+}
+
+impl<I, T> Iterator for TeeIter<I, T>
+where
+    I: Iterator,
+    I::Item: Borrow<T>,
+    T: CanonicalSerialize + CanonicalDeserialize + Clone,
+{
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.pass {
+            Pass::Source(it) => it.next().map(|x| x.borrow().clone()),
+            Pass::Recording(it, recorder) => match it.next() {
+                Some(x) => {
+                    recorder.record(x.borrow());
+                    Some(x.borrow().clone())
+                }
+                None => {
+                    recorder.finish();
+                    None
+                }
+            },
+            Pass::Cached(reader, remaining) => {
+                if *remaining == 0 {
+                    return None;
+                }
+                *remaining -= 1;
+                Some(T::deserialize_uncompressed_unchecked(reader).expect("corrupted stream cache"))
+            }
+        }
+    }
+}
+
+impl<S, T> Iterable for Tee<S, T>
+where
+    S: Iterable,
+    S::Item: Borrow<T>,
+    T: CanonicalSerialize + CanonicalDeserialize + Clone,
+{
+    type Item = T;
+
+    type Iter = TeeIter<S::Iter, T>;
+
+    fn iter(&self) -> Self::Iter {
+        let mut cache = self.cache.lock().unwrap();
+        let pass = match *cache {
+            Cache::Ready => match File::open(&self.path) {
+                Ok(file) => Pass::Cached(BufReader::new(file), self.len()),
+                Err(_) => Pass::Source(self.stream.iter()),
+            },
+            Cache::Recording => Pass::Source(self.stream.iter()),
+            Cache::Empty => match File::create(&self.path) {
+                Ok(file) => {
+                    *cache = Cache::Recording;
+                    let recorder = Recorder {
+                        writer: Some(BufWriter::new(file)),
+                        cache: self.cache.clone(),
+                    };
+                    Pass::Recording(self.stream.iter(), recorder)
+                }
+                Err(_) => Pass::Source(self.stream.iter()),
+            },
+        };
+        TeeIter {
+            pass,
+            _item: PhantomData,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.stream.len()
+    }
+}
+
+#[test]
+fn test_tee() {
+    use ark_bls12_381::Fr;
+    use ark_std::vec::Vec;
+
+    use crate::iterable::Generator;
+
+    let passes = AtomicUsize::new(0);
+    let stream = Generator::new(100, || {
+        passes.fetch_add(1, Ordering::Relaxed);
+        (0..100u64).rev().map(Fr::from)
+    });
+    let expected = stream.iter().collect::<Vec<_>>();
+    passes.store(0, Ordering::Relaxed);
+
+    let tee = Tee::<_, Fr>::new(stream);
+    // an abandoned pass is not cached.
+    assert_eq!(tee.iter().take(10).count(), 10);
+    assert!(!tee.is_cached());
+    assert_eq!(tee.iter().collect::<Vec<_>>(), expected);
+    assert!(tee.is_cached());
+    assert_eq!(passes.load(Ordering::Relaxed), 2);
+
+    // later passes do not recompute the stream.
+    assert_eq!(tee.iter().collect::<Vec<_>>(), expected);
+    assert_eq!(tee.iter().collect::<Vec<_>>(), expected);
+    assert_eq!(passes.load(Ordering::Relaxed), 2);
+}
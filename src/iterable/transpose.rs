@@ -0,0 +1,497 @@
+//! External-memory transposition of sparse matrix streams.
+//!
+//! A row-major matrix stream (in the same format used by the R1CS streams)
+//! is consumed in chunks of bounded size:
+//! each chunk is sorted in column-major order and written to a temporary file.
+//! The resulting [`TransposedMatrix`] streams the column-major representation
+//! by merging the sorted runs on the fly, with a heap over the heads of the runs.
+//! At most `MAX_FAN_IN` runs are merged at once:
+//! with more runs, they are first merged into longer runs on disk,
+//! so that each pass holds a bounded number of files open.
+//!
+//! The same sorted runs [`canonicalize`] a row-major matrix stream emitted by a frontend:
+//! each line is sorted, duplicate entries are summed, and explicit zeros are dropped,
+//! as the indexer expects.
+use ark_ff::{PrimeField, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::borrow::Borrow;
+use ark_std::collections::BinaryHeap;
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::iterable::Iterable;
+use crate::misc::MatrixElement;
+
+/// A counter for naming the temporary files uniquely within the process.
+static TRANSPOSE_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+/// An entry of the matrix, stored as (line, index within the line, value).
+type Entry<F> = (usize, usize, F);
+
+fn into_io_error(e: ark_serialize::SerializationError) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e)
+}
+
+/// The maximum number of runs merged at once, hence of run files open at once by each pass.
+const MAX_FAN_IN: usize = 64;
+
+/// The stream of a sparse matrix, backed by sorted runs on disk:
+/// either the column-major stream returned by [`transpose`],
+/// or the row-major stream returned by [`canonicalize`].
+///
+/// The temporary files are removed when the stream is dropped.
+/// A pass failing to read the runs ends early,
+/// and the failure is reported by [`TransposedMatrix::check`].
+pub struct TransposedMatrix<F> {
+    runs: Vec<(PathBuf, usize)>,
+    /// The number of run files created so far, for naming them uniquely.
+    created: usize,
+    num_lines: usize,
+    num_entries: usize,
+    canonical: bool,
+    failure: Arc<Mutex<Option<io::Error>>>,
+    _field: PhantomData<F>,
+}
+
+/// Transpose the row-major stream `matrix` of a sparse matrix with `num_rows` rows and `num_cols` columns,
+/// holding at most `buffer_size` entries in memory at any given time.
+///
+/// Fails if an entry of the matrix is out of its `num_cols` columns.
+///
+/// # Panics
+/// If `buffer_size` is zero, or if the matrix stream has more than `num_rows` lines.
+pub fn transpose<S, F>(
+    matrix: &S,
+    num_rows: usize,
+    num_cols: usize,
+    buffer_size: usize,
+) -> io::Result<TransposedMatrix<F>>
+where
+    S: Iterable,
+    S::Item: Borrow<MatrixElement<F>>,
+    F: PrimeField,
+{
+    let transpose_time = start_timer!(|| "Transposing the matrix stream");
+    trace_span!("transpose", num_rows = num_rows, num_cols = num_cols);
+    let transposed = sorted_runs(matrix, num_rows, num_cols, buffer_size, |row, col| {
+        (col, row)
+    })?;
+    end_timer!(transpose_time);
+    Ok(transposed)
+}
+
+/// Canonicalize the row-major stream `matrix` of a sparse matrix with `num_rows` rows,
+/// holding at most `buffer_size` entries in memory at any given time.
+///
+/// The entries of each row are sorted by column, entries in the same position are summed,
+/// and entries equal to zero are dropped.
+/// The resulting row-major stream has exactly `num_rows` lines,
+/// and its length is computed with one more pass over the sorted runs.
+///
+/// # Panics
+/// If `buffer_size` is zero, or if the matrix stream has more than `num_rows` lines.
+pub fn canonicalize<S, F>(
+    matrix: &S,
+    num_rows: usize,
+    buffer_size: usize,
+) -> io::Result<TransposedMatrix<F>>
+where
+    S: Iterable,
+    S::Item: Borrow<MatrixElement<F>>,
+    F: PrimeField,
+{
+    let canonicalize_time = start_timer!(|| "Canonicalizing the matrix stream");
+    trace_span!("canonicalize", num_rows = num_rows);
+    let mut canonical = sorted_runs(matrix, num_rows, num_rows, buffer_size, |row, col| {
+        (row, col)
+    })?;
+    canonical.canonical = true;
+    canonical.num_entries = canonical.iter().filter(|e| !e.is_eol()).count();
+    canonical.check()?;
+    end_timer!(canonicalize_time);
+    Ok(canonical)
+}
+
+/// Sort the entries of the row-major stream `matrix` with `num_rows` rows into runs on disk,
+/// keyed by `key(row, col)` in `num_lines` lines,
+/// and merge the runs until at most [`MAX_FAN_IN`] are left.
+fn sorted_runs<S, F>(
+    matrix: &S,
+    num_rows: usize,
+    num_lines: usize,
+    buffer_size: usize,
+    key: impl Fn(usize, usize) -> (usize, usize),
+) -> io::Result<TransposedMatrix<F>>
+where
+    S: Iterable,
+    S::Item: Borrow<MatrixElement<F>>,
+    F: PrimeField,
+{
+    assert!(buffer_size > 0, "the buffer must hold at least one entry");
+    let id = TRANSPOSE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let mut sorted = TransposedMatrix {
+        runs: Vec::new(),
+        created: 0,
+        num_lines,
+        num_entries: 0,
+        canonical: false,
+        failure: Arc::new(Mutex::new(None)),
+        _field: PhantomData,
+    };
+
+    let mut buffer = Vec::with_capacity(buffer_size);
+    let mut lines = 0;
+    for e in matrix.iter() {
+        match *e.borrow() {
+            MatrixElement::EOL => lines += 1,
+            MatrixElement::Element((val, col)) => {
+                assert!(lines < num_rows, "too many lines in the matrix stream");
+                let (line, index) = key(num_rows - 1 - lines, col);
+                if line >= num_lines {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "the matrix stream has an entry out of its columns",
+                    ));
+                }
+                buffer.push((line, index, val));
+                if buffer.len() == buffer_size {
+                    sorted.write_run(id, &mut buffer)?;
+                }
+            }
+        }
+    }
+    if !buffer.is_empty() {
+        sorted.write_run(id, &mut buffer)?;
+    }
+    while sorted.runs.len() > MAX_FAN_IN {
+        sorted.merge_runs(id)?;
+    }
+    Ok(sorted)
+}
+
+impl<F: PrimeField> TransposedMatrix<F> {
+    /// Return the first failure to read the runs, if any pass failed.
+    pub fn check(&self) -> io::Result<()> {
+        match &*self.failure.lock().unwrap() {
+            Some(e) => Err(io::Error::new(e.kind(), e.to_string())),
+            None => Ok(()),
+        }
+    }
+
+    /// Create a new run file, registered so that it's cleaned up even on failure.
+    fn create_run(&mut self, id: usize, len: usize) -> io::Result<BufWriter<File>> {
+        let path = std::env::temp_dir().join(std::format!(
+            "gemini-transpose-{}-{}-{}",
+            std::process::id(),
+            id,
+            self.created
+        ));
+        self.created += 1;
+        self.runs.push((path.clone(), len));
+        Ok(BufWriter::new(File::create(&path)?))
+    }
+
+    /// Sort `buffer` in decreasing order, flush it to a new temporary file, and empty it.
+    fn write_run(&mut self, id: usize, buffer: &mut Vec<Entry<F>>) -> io::Result<()> {
+        buffer.sort_unstable_by(|a, b| (b.0, b.1).cmp(&(a.0, a.1)));
+        let mut writer = self.create_run(id, buffer.len())?;
+        for &entry in buffer.iter() {
+            write_entry(&mut writer, entry)?;
+        }
+        writer.flush()?;
+        self.num_entries += buffer.len();
+        buffer.clear();
+        Ok(())
+    }
+
+    /// Merge the runs, [`MAX_FAN_IN`] at a time, into new runs.
+    fn merge_runs(&mut self, id: usize) -> io::Result<()> {
+        let merged = self.runs.len();
+        for start in (0..merged).step_by(MAX_FAN_IN) {
+            let group = &self.runs[start..usize::min(start + MAX_FAN_IN, merged)];
+            let len = group.iter().map(|&(_, len)| len).sum();
+            let mut merge = Merge::open(group)?;
+            let mut writer = self.create_run(id, len)?;
+            while let Some(entry) = merge.pop()? {
+                write_entry(&mut writer, entry)?;
+            }
+            writer.flush()?;
+        }
+        self.runs.drain(..merged).for_each(|(path, _)| {
+            let _ = fs::remove_file(path);
+        });
+        Ok(())
+    }
+}
+
+impl<F> Drop for TransposedMatrix<F> {
+    fn drop(&mut self) {
+        self.runs.iter().for_each(|(path, _)| {
+            let _ = fs::remove_file(path);
+        });
+    }
+}
+
+fn write_entry<F: PrimeField>(writer: &mut impl Write, entry: Entry<F>) -> io::Result<()> {
+    let (line, index, val) = entry;
+    (line as u64)
+        .serialize_uncompressed(&mut *writer)
+        .map_err(into_io_error)?;
+    (index as u64)
+        .serialize_uncompressed(&mut *writer)
+        .map_err(into_io_error)?;
+    val.serialize_uncompressed(writer).map_err(into_io_error)
+}
+
+/// A sorted run, read back from disk.
+struct Run<F> {
+    reader: BufReader<File>,
+    remaining: usize,
+    _field: PhantomData<F>,
+}
+
+impl<F: PrimeField> Run<F> {
+    fn open(path: &Path, remaining: usize) -> io::Result<Self> {
+        Ok(Run {
+            reader: BufReader::new(File::open(path)?),
+            remaining,
+            _field: PhantomData,
+        })
+    }
+
+    /// Read the next entry of the run.
+    fn read(&mut self) -> io::Result<Option<Entry<F>>> {
+        if self.remaining == 0 {
+            return Ok(None);
+        }
+        self.remaining -= 1;
+        let line = u64::deserialize_uncompressed(&mut self.reader).map_err(into_io_error)?;
+        let index = u64::deserialize_uncompressed(&mut self.reader).map_err(into_io_error)?;
+        let val = F::deserialize_uncompressed(&mut self.reader).map_err(into_io_error)?;
+        Ok(Some((line as usize, index as usize, val)))
+    }
+}
+
+/// The merge of sorted runs, in decreasing order of position.
+struct Merge<F> {
+    runs: Vec<Run<F>>,
+    /// The positions of the heads of the runs, along with their run.
+    heap: BinaryHeap<((usize, usize), usize)>,
+    /// The value of the head of each run.
+    heads: Vec<F>,
+}
+
+impl<F: PrimeField> Merge<F> {
+    /// Open the runs `runs`, and read their heads.
+    fn open(runs: &[(PathBuf, usize)]) -> io::Result<Self> {
+        let mut merge = Merge {
+            runs: runs
+                .iter()
+                .map(|(path, remaining)| Run::open(path, *remaining))
+                .collect::<io::Result<_>>()?,
+            heap: BinaryHeap::with_capacity(runs.len()),
+            heads: vec![F::zero(); runs.len()],
+        };
+        for i in 0..runs.len() {
+            merge.advance(i)?;
+        }
+        Ok(merge)
+    }
+
+    /// Read the next head of the run `i`.
+    fn advance(&mut self, i: usize) -> io::Result<()> {
+        if let Some((line, index, val)) = self.runs[i].read()? {
+            self.heads[i] = val;
+            self.heap.push(((line, index), i));
+        }
+        Ok(())
+    }
+
+    /// Return the position of the greatest head.
+    fn peek(&self) -> Option<(usize, usize)> {
+        self.heap.peek().map(|&(position, _)| position)
+    }
+
+    /// Pop the greatest head.
+    fn pop(&mut self) -> io::Result<Option<Entry<F>>> {
+        match self.heap.pop() {
+            Some(((line, index), i)) => {
+                let val = self.heads[i];
+                self.advance(i)?;
+                Ok(Some((line, index, val)))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+/// The iterator returned by [`TransposedMatrix`], merging all runs.
+pub struct TransposedMatrixIter<F> {
+    /// The merge of the runs, dropped after a read failure.
+    merge: Option<Merge<F>>,
+    remaining_lines: usize,
+    canonical: bool,
+    failure: Arc<Mutex<Option<io::Error>>>,
+}
+
+impl<F: PrimeField> TransposedMatrixIter<F> {
+    /// Record the read failure `e`, and stop reading the runs.
+    fn fail(&mut self, e: io::Error) {
+        self.merge = None;
+        self.failure.lock().unwrap().get_or_insert(e);
+    }
+
+    fn peek(&self) -> Option<(usize, usize)> {
+        self.merge.as_ref()?.peek()
+    }
+
+    fn pop(&mut self) -> Option<Entry<F>> {
+        match self.merge.as_mut()?.pop() {
+            Ok(entry) => entry,
+            Err(e) => {
+                self.fail(e);
+                None
+            }
+        }
+    }
+}
+
+impl<F: PrimeField> Iterator for TransposedMatrixIter<F> {
+    type Item = MatrixElement<F>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.remaining_lines == 0 {
+                return None;
+            }
+            let current_line = self.remaining_lines - 1;
+            match self.peek() {
+                Some((line, index)) if line == current_line => {
+                    let mut val = match self.pop() {
+                        Some((_, _, val)) => val,
+                        None => continue,
+                    };
+                    if !self.canonical {
+                        return Some(MatrixElement::Element((val, index)));
+                    }
+                    // sum all the entries in the same position, across (and within) the runs.
+                    while self.peek() == Some((line, index)) {
+                        if let Some((_, _, other)) = self.pop() {
+                            val += other;
+                        }
+                    }
+                    if !val.is_zero() {
+                        return Some(MatrixElement::Element((val, index)));
+                    }
+                }
+                _ => {
+                    self.remaining_lines -= 1;
+                    return Some(MatrixElement::EOL);
+                }
+            }
+        }
+    }
+}
+
+impl<F: PrimeField> Iterable for TransposedMatrix<F> {
+    type Item = MatrixElement<F>;
+
+    type Iter = TransposedMatrixIter<F>;
+
+    /// Stream the lines of the matrix (columns if transposed, rows if canonicalized).
+    ///
+    /// If the runs cannot be read, the lines are streamed empty
+    /// and the failure is reported by [`TransposedMatrix::check`].
+    fn iter(&self) -> Self::Iter {
+        let mut it = TransposedMatrixIter {
+            merge: None,
+            remaining_lines: self.num_lines,
+            canonical: self.canonical,
+            failure: self.failure.clone(),
+        };
+        match Merge::open(&self.runs) {
+            Ok(merge) => it.merge = Some(merge),
+            Err(e) => it.fail(e),
+        }
+        it
+    }
+
+    fn len(&self) -> usize {
+        self.num_entries + self.num_lines
+    }
+}
+
+#[test]
+fn test_transpose() {
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+
+    use crate::circuit::{
+        generate_relation, matrix_into_colmaj, matrix_into_rowmaj, random_circuit, Circuit,
+    };
+
+    let rng = &mut test_rng();
+    let n = 256;
+    let circuit: Circuit<Fr> = random_circuit(rng, n, n);
+    let r1cs = generate_relation(circuit);
+    let rowmaj = matrix_into_rowmaj(&r1cs.a);
+    let colmaj = matrix_into_colmaj(&r1cs.a, r1cs.z.len());
+
+    for &buffer_size in &[1, 7, 1 << 20] {
+        let transposed = transpose(&rowmaj.as_slice(), r1cs.a.len(), r1cs.z.len(), buffer_size)
+            .expect("transposition failed");
+        assert_eq!(transposed.len(), colmaj.len());
+        assert_eq!(transposed.iter().collect::<Vec<_>>(), colmaj);
+        // the runs are merged until passes open a bounded number of files.
+        assert!(transposed.runs.len() <= MAX_FAN_IN);
+        assert!(buffer_size > 1 || transposed.created > MAX_FAN_IN);
+        assert!(transposed.check().is_ok());
+    }
+
+    // entries out of the columns are rejected.
+    assert!(transpose(&rowmaj.as_slice(), r1cs.a.len(), 1, 7).is_err());
+
+    // failures to read the runs are reported.
+    let transposed = transpose(&rowmaj.as_slice(), r1cs.a.len(), r1cs.z.len(), 7).unwrap();
+    std::fs::remove_file(&transposed.runs[0].0).unwrap();
+    assert_eq!(transposed.iter().count(), r1cs.z.len());
+    assert!(transposed.check().is_err());
+}
+
+#[test]
+fn test_canonicalize() {
+    use ark_bls12_381::Fr;
+    use ark_std::{One, Zero};
+
+    use crate::circuit::matrix_into_rowmaj;
+
+    let one = Fr::one();
+    let two = one + one;
+    // rows out of order, with duplicates (also cancelling out) and explicit zeros.
+    let messy = vec![
+        vec![(one, 3), (one, 0), (two, 3)],
+        vec![(Fr::zero(), 1)],
+        vec![(one, 2), (-one, 2), (one, 1), (two, 0)],
+        vec![],
+    ];
+    let canonical = vec![
+        vec![(one, 0), (one + two, 3)],
+        vec![],
+        vec![(two, 0), (one, 1)],
+        vec![],
+    ];
+    let rowmaj = matrix_into_rowmaj(&messy);
+    let expected = matrix_into_rowmaj(&canonical);
+
+    for &buffer_size in &[1, 2, 1 << 20] {
+        let canonicalized = canonicalize(&rowmaj.as_slice(), messy.len(), buffer_size)
+            .expect("canonicalization failed");
+        assert_eq!(canonicalized.len(), expected.len());
+        assert_eq!(canonicalized.iter().collect::<Vec<_>>(), expected);
+    }
+}
@@ -0,0 +1,501 @@
+//! A minimal JSON encoder and decoder, for exporting keys and proofs.
+//!
+//! The exports follow the conventions of snarkjs:
+//! objects with named fields, tagged with the field `"protocol": "gemini"`,
+//! where group elements are strings holding the `0x`-prefixed hexadecimal encoding
+//! of their compressed canonical serialization,
+//! and field elements are strings holding their canonical decimal representation.
+use ark_ff::PrimeField;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::str::FromStr;
+use ark_std::string::{String, ToString};
+use ark_std::vec::Vec;
+
+use crate::PROOF_FORMAT_VERSION;
+
+/// The value of the field `"protocol"` of the exports.
+pub(crate) const PROTOCOL: &str = "gemini";
+
+/// The maximum nesting of arrays and objects accepted by the parser.
+const MAX_DEPTH: usize = 32;
+
+/// A JSON value.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Json {
+    Null,
+    Bool(bool),
+    /// A number, in its textual representation.
+    Number(String),
+    String(String),
+    Array(Vec<Json>),
+    /// An object, its fields in order.
+    Object(Vec<(String, Json)>),
+}
+
+impl Json {
+    /// An object with the fields `fields`.
+    pub(crate) fn object(fields: Vec<(&str, Json)>) -> Self {
+        Json::Object(
+            fields
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// The header of the exports: the protocol, the format version, and the kind of export.
+    pub(crate) fn header(kind: &str) -> Vec<(&'static str, Json)> {
+        vec![
+            ("protocol", Json::String(PROTOCOL.to_string())),
+            ("version", Json::Number(PROOF_FORMAT_VERSION.to_string())),
+            ("type", Json::String(kind.to_string())),
+        ]
+    }
+
+    /// Fail unless the value is an object with the header [`Json::header`] for `kind`.
+    pub(crate) fn check_header(&self, kind: &str) -> Result<(), SerializationError> {
+        let expected = Json::header(kind);
+        for (key, value) in &expected {
+            if self.get(key)? != value {
+                return Err(SerializationError::InvalidData);
+            }
+        }
+        Ok(())
+    }
+
+    /// The field `key` of an object.
+    pub(crate) fn get(&self, key: &str) -> Result<&Json, SerializationError> {
+        match self {
+            Json::Object(fields) => fields
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value)
+                .ok_or(SerializationError::InvalidData),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+
+    /// The elements of an array.
+    pub(crate) fn as_array(&self) -> Result<&[Json], SerializationError> {
+        match self {
+            Json::Array(elements) => Ok(elements),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+
+    /// The content of a string.
+    pub(crate) fn as_str(&self) -> Result<&str, SerializationError> {
+        match self {
+            Json::String(string) => Ok(string),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+
+    /// Parse the JSON document `input`.
+    pub(crate) fn parse(input: &str) -> Result<Self, SerializationError> {
+        let mut parser = Parser {
+            input: input.as_bytes(),
+            position: 0,
+        };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        if parser.position != parser.input.len() {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(value)
+    }
+
+    /// Return the value as a JSON document, indented by two spaces.
+    pub(crate) fn to_pretty_string(&self) -> String {
+        let mut out = String::new();
+        self.write(&mut out, 0);
+        out.push('\n');
+        out
+    }
+
+    fn write(&self, out: &mut String, indent: usize) {
+        match self {
+            Json::Null => out.push_str("null"),
+            Json::Bool(true) => out.push_str("true"),
+            Json::Bool(false) => out.push_str("false"),
+            Json::Number(number) => out.push_str(number),
+            Json::String(string) => push_string(out, string),
+            Json::Array(elements) if elements.is_empty() => out.push_str("[]"),
+            Json::Array(elements) => {
+                out.push('[');
+                for (i, element) in elements.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    push_newline(out, indent + 1);
+                    element.write(out, indent + 1);
+                }
+                push_newline(out, indent);
+                out.push(']');
+            }
+            Json::Object(fields) if fields.is_empty() => out.push_str("{}"),
+            Json::Object(fields) => {
+                out.push('{');
+                for (i, (key, value)) in fields.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    push_newline(out, indent + 1);
+                    push_string(out, key);
+                    out.push_str(": ");
+                    value.write(out, indent + 1);
+                }
+                push_newline(out, indent);
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn push_newline(out: &mut String, indent: usize) {
+    out.push('\n');
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+/// Append to `out` the (quoted and escaped) string `string`.
+fn push_string(out: &mut String, string: &str) {
+    out.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                out.push_str("\\u00");
+                push_hex(out, &[c as u8]);
+            }
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+/// Append to `out` the lowercase hexadecimal encoding of `bytes`.
+pub(crate) fn push_hex(out: &mut String, bytes: &[u8]) {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    for byte in bytes {
+        out.push(DIGITS[(byte >> 4) as usize] as char);
+        out.push(DIGITS[(byte & 0x0f) as usize] as char);
+    }
+}
+
+/// The value of the hexadecimal digit `digit`.
+fn hex_digit(digit: u8) -> Result<u8, SerializationError> {
+    match digit {
+        b'0'..=b'9' => Ok(digit - b'0'),
+        b'a'..=b'f' => Ok(digit - b'a' + 10),
+        b'A'..=b'F' => Ok(digit - b'A' + 10),
+        _ => Err(SerializationError::InvalidData),
+    }
+}
+
+/// Decode the hexadecimal string `hex` (without prefix) into bytes.
+pub(crate) fn decode_hex(hex: &[u8]) -> Result<Vec<u8>, SerializationError> {
+    if hex.len() % 2 != 0 {
+        return Err(SerializationError::InvalidData);
+    }
+    hex.chunks(2)
+        .map(|pair| Ok((hex_digit(pair[0])? << 4) | hex_digit(pair[1])?))
+        .collect()
+}
+
+/// A recursive-descent parser over the bytes of a JSON document.
+struct Parser<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.position).copied()
+    }
+
+    fn bump(&mut self) -> Result<u8, SerializationError> {
+        let byte = self.peek().ok_or(SerializationError::InvalidData)?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.position += 1;
+        }
+    }
+
+    /// Consume `literal`, or fail.
+    fn expect(&mut self, literal: &[u8]) -> Result<(), SerializationError> {
+        if self.input[self.position..].starts_with(literal) {
+            self.position += literal.len();
+            Ok(())
+        } else {
+            Err(SerializationError::InvalidData)
+        }
+    }
+
+    /// Parse a value nested within `depth` arrays or objects.
+    fn value(&mut self, depth: usize) -> Result<Json, SerializationError> {
+        if depth > MAX_DEPTH {
+            return Err(SerializationError::InvalidData);
+        }
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'n') => self.expect(b"null").map(|_| Json::Null),
+            Some(b't') => self.expect(b"true").map(|_| Json::Bool(true)),
+            Some(b'f') => self.expect(b"false").map(|_| Json::Bool(false)),
+            Some(b'"') => self.string().map(Json::String),
+            Some(b'[') => {
+                self.position += 1;
+                let mut elements = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(b']') {
+                    self.position += 1;
+                    return Ok(Json::Array(elements));
+                }
+                loop {
+                    elements.push(self.value(depth + 1)?);
+                    self.skip_whitespace();
+                    match self.bump()? {
+                        b',' => continue,
+                        b']' => return Ok(Json::Array(elements)),
+                        _ => return Err(SerializationError::InvalidData),
+                    }
+                }
+            }
+            Some(b'{') => {
+                self.position += 1;
+                let mut fields = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(b'}') {
+                    self.position += 1;
+                    return Ok(Json::Object(fields));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.string()?;
+                    self.skip_whitespace();
+                    self.expect(b":")?;
+                    fields.push((key, self.value(depth + 1)?));
+                    self.skip_whitespace();
+                    match self.bump()? {
+                        b',' => continue,
+                        b'}' => return Ok(Json::Object(fields)),
+                        _ => return Err(SerializationError::InvalidData),
+                    }
+                }
+            }
+            Some(b'-' | b'0'..=b'9') => self.number().map(Json::Number),
+            _ => Err(SerializationError::InvalidData),
+        }
+    }
+
+    fn number(&mut self) -> Result<String, SerializationError> {
+        let start = self.position;
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        let digits = self.digits();
+        // leading zeros are not allowed.
+        if digits == 0 || (digits > 1 && self.input[self.position - digits] == b'0') {
+            return Err(SerializationError::InvalidData);
+        }
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            if self.digits() == 0 {
+                return Err(SerializationError::InvalidData);
+            }
+        }
+        if let Some(b'e' | b'E') = self.peek() {
+            self.position += 1;
+            if let Some(b'+' | b'-') = self.peek() {
+                self.position += 1;
+            }
+            if self.digits() == 0 {
+                return Err(SerializationError::InvalidData);
+            }
+        }
+        // the input is a string, and the number is ASCII.
+        let number = ark_std::str::from_utf8(&self.input[start..self.position])
+            .map_err(|_| SerializationError::InvalidData)?;
+        Ok(number.to_string())
+    }
+
+    /// Consume a sequence of decimal digits, and return its length.
+    fn digits(&mut self) -> usize {
+        let start = self.position;
+        while let Some(b'0'..=b'9') = self.peek() {
+            self.position += 1;
+        }
+        self.position - start
+    }
+
+    fn string(&mut self) -> Result<String, SerializationError> {
+        self.expect(b"\"")?;
+        let mut bytes = Vec::new();
+        loop {
+            match self.bump()? {
+                b'"' => break,
+                b'\\' => {
+                    let escaped = match self.bump()? {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(SerializationError::InvalidData),
+                    };
+                    let mut buffer = [0u8; 4];
+                    bytes.extend_from_slice(escaped.encode_utf8(&mut buffer).as_bytes());
+                }
+                byte if byte < 0x20 => return Err(SerializationError::InvalidData),
+                byte => bytes.push(byte),
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| SerializationError::InvalidData)
+    }
+
+    /// Parse the code point of an escape `\uXXXX` (the prefix `\u` already consumed),
+    /// combining surrogate pairs.
+    fn unicode_escape(&mut self) -> Result<char, SerializationError> {
+        let high = self.code_unit()?;
+        let code_point = if (0xd800..0xdc00).contains(&high) {
+            self.expect(b"\\u")?;
+            let low = self.code_unit()?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(SerializationError::InvalidData);
+            }
+            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+        } else {
+            high
+        };
+        char::from_u32(code_point).ok_or(SerializationError::InvalidData)
+    }
+
+    fn code_unit(&mut self) -> Result<u32, SerializationError> {
+        let mut code_unit = 0;
+        for _ in 0..4 {
+            code_unit = (code_unit << 4) | hex_digit(self.bump()?)? as u32;
+        }
+        Ok(code_unit)
+    }
+}
+
+/// Encode the group element `element` as the hexadecimal string of its compressed serialization.
+pub(crate) fn point<G: CanonicalSerialize>(element: &G) -> Json {
+    let mut bytes = Vec::new();
+    element
+        .serialize_compressed(&mut bytes)
+        .expect("serialization into a vector cannot fail");
+    let mut hex = String::from("0x");
+    push_hex(&mut hex, &bytes);
+    Json::String(hex)
+}
+
+/// Decode a group element encoded with [`point`].
+///
+/// Fails unless the point is on the curve and in the prime-order subgroup.
+pub(crate) fn point_from<G: CanonicalDeserialize>(value: &Json) -> Result<G, SerializationError> {
+    let hex = value.as_str()?;
+    let hex = hex
+        .strip_prefix("0x")
+        .ok_or(SerializationError::InvalidData)?
+        .as_bytes();
+    let bytes = decode_hex(hex)?;
+    let mut reader = &bytes[..];
+    let point = G::deserialize_compressed(&mut reader)?;
+    // trailing bytes are not part of any canonical encoding.
+    if !reader.is_empty() {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(point)
+}
+
+/// Encode the field element `element` as the string of its decimal representation.
+pub(crate) fn scalar<F: PrimeField>(element: &F) -> Json {
+    Json::String(element.into_bigint().to_string())
+}
+
+/// Decode a field element encoded with [`scalar`].
+///
+/// Fails unless the representation is canonical,
+/// that is, without leading zeros and smaller than the modulus.
+pub(crate) fn scalar_from<F: PrimeField>(value: &Json) -> Result<F, SerializationError> {
+    let decimal = value.as_str()?;
+    let scalar = <F as FromStr>::from_str(decimal).map_err(|_| SerializationError::InvalidData)?;
+    if scalar.into_bigint().to_string() != decimal {
+        return Err(SerializationError::InvalidData);
+    }
+    Ok(scalar)
+}
+
+/// Encode each element of `elements` with `encode`, into an array.
+pub(crate) fn array<T>(elements: &[T], encode: impl Fn(&T) -> Json) -> Json {
+    Json::Array(elements.iter().map(encode).collect())
+}
+
+/// Decode each element of an array with `decode`.
+pub(crate) fn array_from<T>(
+    value: &Json,
+    decode: impl Fn(&Json) -> Result<T, SerializationError>,
+) -> Result<Vec<T>, SerializationError> {
+    value.as_array()?.iter().map(decode).collect()
+}
+
+/// Decode an array of exactly `N` field elements.
+pub(crate) fn scalars_from<F: PrimeField, const N: usize>(
+    value: &Json,
+) -> Result<[F; N], SerializationError> {
+    let elements = array_from(value, scalar_from)?;
+    elements
+        .try_into()
+        .map_err(|_| SerializationError::InvalidData)
+}
+
+#[test]
+fn test_json_parse() {
+    let input = r#" {"a": [1, -2.5e3, true, null], "b\n": "\u00e9\ud83d\ude00\"", "c": {}} "#;
+    let value = Json::parse(input).unwrap();
+    assert_eq!(
+        value.get("a").unwrap().as_array().unwrap()[1],
+        Json::Number("-2.5e3".to_string())
+    );
+    assert_eq!(value.get("b\n").unwrap().as_str().unwrap(), "é😀\"");
+    assert_eq!(Json::parse(&value.to_pretty_string()).unwrap(), value);
+
+    for malformed in ["", "[1,]", "{\"a\" 1}", "01", "\"\\ud83d\"", "[1] 2", "tru"] {
+        assert!(Json::parse(malformed).is_err());
+    }
+    let deep = "[".repeat(MAX_DEPTH + 2) + &"]".repeat(MAX_DEPTH + 2);
+    assert!(Json::parse(&deep).is_err());
+}
+
+#[test]
+fn test_json_elements() {
+    use ark_bls12_381::{Fr, G1Affine};
+    use ark_ec::AffineRepr;
+
+    let x = -Fr::from(1u64);
+    assert_eq!(scalar_from::<Fr>(&scalar(&x)).unwrap(), x);
+    assert_eq!(scalar(&Fr::from(42u64)), Json::String("42".to_string()));
+    for malformed in ["042", "-1", "", "0x2a"] {
+        assert!(scalar_from::<Fr>(&Json::String(malformed.to_string())).is_err());
+    }
+    // the modulus is not canonical.
+    let modulus = Json::String(Fr::MODULUS.to_string());
+    assert!(scalar_from::<Fr>(&modulus).is_err());
+
+    let g = G1Affine::generator();
+    assert_eq!(point_from::<G1Affine>(&point(&g)).unwrap(), g);
+    let hex = point(&g).as_str().unwrap().to_string();
+    assert!(point_from::<G1Affine>(&Json::String(hex[2..].to_string())).is_err());
+    assert!(point_from::<G1Affine>(&Json::String(hex + "00")).is_err());
+}
@@ -0,0 +1,34 @@
+//! Pluggable backends for the multi-scalar multiplications of the commitments.
+//!
+//! Committing is dominated by the multi-scalar multiplication of the coefficients with the key.
+//! A [`MsmBackend`] computes it: [`CpuBackend`] relies on the Pippenger implementation of arkworks,
+//! and other backends (e.g., on accelerators) can be plugged in by implementing the trait.
+//! No accelerated backend is included: [`CpuBackend`] is the only implementation in this crate.
+//! Streaming commitments hand the backend one chunk at a time,
+//! so that the inputs to the backend never exceed the streaming buffers.
+use ark_ec::{CurveGroup, VariableBaseMSM};
+
+/// A backend computing multi-scalar multiplications over the group `G`.
+pub trait MsmBackend<G: CurveGroup> {
+    /// Return the linear combination of the first `scalars.len()` elements of `bases` with `scalars`.
+    ///
+    /// Fails if there are fewer bases than scalars.
+    fn msm(&self, bases: &[G::Affine], scalars: &[G::ScalarField]) -> G;
+}
+
+impl<'a, G: CurveGroup, B: MsmBackend<G>> MsmBackend<G> for &'a B {
+    fn msm(&self, bases: &[G::Affine], scalars: &[G::ScalarField]) -> G {
+        (**self).msm(bases, scalars)
+    }
+}
+
+/// The default backend, computing multi-scalar multiplications on the CPU.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CpuBackend;
+
+impl<G: CurveGroup> MsmBackend<G> for CpuBackend {
+    fn msm(&self, bases: &[G::Affine], scalars: &[G::ScalarField]) -> G {
+        assert!(bases.len() >= scalars.len(), "not enough bases");
+        G::msm(&bases[..scalars.len()], scalars)
+    }
+}
@@ -0,0 +1,287 @@
+//! Coordination of a powers-of-tau ceremony for the universal SRS.
+//!
+//! The committer key is updatable: a participant holding a key with trapdoor $\tau$
+//! samples a secret $s$ and multiplies the $i$-th powers in both groups by $s^i$,
+//! obtaining the key with trapdoor $s\tau$.
+//! As long as one of the participants discards their secret, nobody knows the final trapdoor.
+//! Each update comes with an [`UpdateProof`], i.e. $s$ in both groups,
+//! which the coordinator checks against the previous key with a handful of pairings.
+//!
+//! The ceremony starts from [`CommitterKey::ceremony_start`], and is usually sealed with
+//! a contribution derived from a public random beacon, with [`CommitterKey::apply_beacon`].
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup, Group, VariableBaseMSM};
+use ark_ff::{PrimeField, Zero};
+use ark_serialize::*;
+use ark_std::rand::RngCore;
+use ark_std::vec::Vec;
+use ark_std::UniformRand;
+use sha2::{Digest, Sha256};
+
+use crate::errors::{VerificationError, VerificationResult};
+use crate::kzg::CommitterKey;
+use crate::misc::powers;
+
+/// The domain separator of the secrets derived by [`CommitterKey::apply_beacon`].
+const BEACON_DOMAIN: &[u8] = b"gemini-ceremony-beacon";
+
+/// The proof of an update of the committer key,
+/// consisting of the secret of the participant in both groups.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UpdateProof<E: Pairing> {
+    s_g1: E::G1Affine,
+    s_g2: E::G2Affine,
+}
+
+/// Derive the secret of a beacon contribution, hashing `beacon` `iterations` times more.
+fn beacon_secret<F: PrimeField>(beacon: &[u8], iterations: usize) -> F {
+    let mut digest: [u8; 32] = Sha256::new()
+        .chain_update(BEACON_DOMAIN)
+        .chain_update(beacon)
+        .finalize()
+        .into();
+    for _ in 0..iterations {
+        digest = Sha256::digest(digest).into();
+    }
+    F::from_le_bytes_mod_order(&digest)
+}
+
+fn consistency(holds: bool, check: &'static str) -> VerificationResult {
+    if holds {
+        Ok(())
+    } else {
+        Err(VerificationError::Consistency { check })
+    }
+}
+
+impl<E: Pairing> CommitterKey<E> {
+    /// The committer key at the start of a ceremony, with trapdoor one:
+    /// all powers are the generators of \\(\GG_1\\) and \\(\GG_2\\).
+    ///
+    /// This key is public, and must be updated by the participants before any use.
+    pub fn ceremony_start(max_degree: usize, max_eval_points: usize) -> Self {
+        CommitterKey {
+            powers_of_g: vec![E::G1::generator().into_affine(); max_degree + 1],
+            powers_of_g2: vec![E::G2::generator().into_affine(); max_eval_points + 1],
+        }
+    }
+
+    /// Contribute to the ceremony with a secret drawn from `rng`,
+    /// returning the updated key along with the proof of the update.
+    ///
+    /// The secret is not returned, and is dropped with the stack frame.
+    pub fn update(&self, rng: &mut impl RngCore) -> (Self, UpdateProof<E>) {
+        let mut s = E::ScalarField::rand(rng);
+        while s.is_zero() {
+            s = E::ScalarField::rand(rng);
+        }
+        self.update_with(s)
+    }
+
+    /// Contribute to the ceremony with the secret derived from the public random beacon `beacon`,
+    /// hashed `iterations` more times with SHA-256 to delay its computation.
+    ///
+    /// Since the secret is public, this must be the last contribution:
+    /// it only prevents the last participant from biasing the final key.
+    pub fn apply_beacon(&self, beacon: &[u8], iterations: usize) -> (Self, UpdateProof<E>) {
+        self.update_with(beacon_secret(beacon, iterations))
+    }
+
+    fn update_with(&self, s: E::ScalarField) -> (Self, UpdateProof<E>) {
+        let degree = usize::max(self.powers_of_g.len(), self.powers_of_g2.len());
+        let powers_of_s = powers(s, degree);
+        let powers_of_g = self
+            .powers_of_g
+            .iter()
+            .zip(&powers_of_s)
+            .map(|(g, s)| *g * s)
+            .collect::<Vec<_>>();
+        let powers_of_g2 = self
+            .powers_of_g2
+            .iter()
+            .zip(&powers_of_s)
+            .map(|(g2, s)| *g2 * s)
+            .collect::<Vec<_>>();
+        let proof = UpdateProof {
+            s_g1: (self.powers_of_g[0] * s).into_affine(),
+            s_g2: (self.powers_of_g2[0] * s).into_affine(),
+        };
+        let ck = CommitterKey {
+            powers_of_g: E::G1::normalize_batch(&powers_of_g),
+            powers_of_g2: E::G2::normalize_batch(&powers_of_g2),
+        };
+        (ck, proof)
+    }
+}
+
+/// Check that `next` is the update of `previous` proven by `proof`,
+/// and that its points are consecutive powers of the same trapdoor.
+///
+/// The powers are checked all at once, combined with the powers of a challenge drawn from `rng`.
+pub fn verify_update<E: Pairing>(
+    previous: &CommitterKey<E>,
+    next: &CommitterKey<E>,
+    proof: &UpdateProof<E>,
+    rng: &mut impl RngCore,
+) -> VerificationResult {
+    let g1 = previous.powers_of_g[0];
+    let g2 = previous.powers_of_g2[0];
+    consistency(
+        next.powers_of_g.len() == previous.powers_of_g.len()
+            && next.powers_of_g2.len() == previous.powers_of_g2.len()
+            && next.powers_of_g.len() > 1
+            && next.powers_of_g2.len() > 1,
+        "committer key length",
+    )?;
+    consistency(
+        next.powers_of_g[0] == g1 && next.powers_of_g2[0] == g2,
+        "committer key generators",
+    )?;
+    consistency(
+        !proof.s_g1.is_zero() && E::pairing(proof.s_g1, g2) == E::pairing(g1, proof.s_g2),
+        "update proof",
+    )?;
+    consistency(
+        E::pairing(next.powers_of_g[1], g2) == E::pairing(previous.powers_of_g[1], proof.s_g2),
+        "trapdoor update",
+    )?;
+
+    // Σ_i ρ^i [τ^{i+1}] = τ Σ_i ρ^i [τ^i], in both groups.
+    let n = next.powers_of_g.len();
+    let m = next.powers_of_g2.len();
+    let challenges = powers(E::ScalarField::rand(rng), usize::max(n, m) - 1);
+    let lhs = E::G1::msm(&next.powers_of_g[..n - 1], &challenges[..n - 1]);
+    let rhs = E::G1::msm(&next.powers_of_g[1..], &challenges[..n - 1]);
+    consistency(
+        E::pairing(rhs, g2) == E::pairing(lhs, next.powers_of_g2[1]),
+        "powers of tau in G1",
+    )?;
+    let lhs = E::G2::msm(&next.powers_of_g2[..m - 1], &challenges[..m - 1]);
+    let rhs = E::G2::msm(&next.powers_of_g2[1..], &challenges[..m - 1]);
+    consistency(
+        E::pairing(g1, rhs) == E::pairing(next.powers_of_g[1], lhs),
+        "powers of tau in G2",
+    )?;
+    Ok(())
+}
+
+/// Check a contribution produced by [`CommitterKey::apply_beacon`]
+/// with the beacon `beacon` hashed `iterations` times.
+pub fn verify_beacon<E: Pairing>(
+    previous: &CommitterKey<E>,
+    next: &CommitterKey<E>,
+    proof: &UpdateProof<E>,
+    beacon: &[u8],
+    iterations: usize,
+    rng: &mut impl RngCore,
+) -> VerificationResult {
+    let s = beacon_secret::<E::ScalarField>(beacon, iterations);
+    consistency(
+        proof.s_g2 == (previous.powers_of_g2[0] * s).into_affine(),
+        "beacon secret",
+    )?;
+    verify_update(previous, next, proof, rng)
+}
+
+/// Check the whole chain of contributions `contributions` from the key `start`,
+/// as returned by [`CommitterKey::ceremony_start`], and return the final key.
+///
+/// Each contribution is the updated key together with its proof.
+/// On failure, the index of the first invalid contribution is returned along with the error.
+pub fn verify_chain<'a, E: Pairing>(
+    start: &'a CommitterKey<E>,
+    contributions: &'a [(CommitterKey<E>, UpdateProof<E>)],
+    rng: &mut impl RngCore,
+) -> Result<&'a CommitterKey<E>, (usize, VerificationError)> {
+    let mut current = start;
+    for (i, (next, proof)) in contributions.iter().enumerate() {
+        verify_update(current, next, proof, rng).map_err(|e| (i, e))?;
+        current = next;
+    }
+    Ok(current)
+}
+
+/// Export the final key `ck` in the streaming format:
+/// the file at `powers_of_g_path` holds the powers in \\(\GG_1\\) in stream order (i.e., highest first),
+/// each serialized uncompressed, as read by [`MmapIterable`](crate::iterable::mmap::MmapIterable),
+/// and the file at `powers_of_g2_path` holds the (uncompressed) vector of powers in \\(\GG_2\\),
+/// together forming a [`CommitterKeyStream`](crate::kzg::CommitterKeyStream).
+#[cfg(feature = "std")]
+pub fn export_streaming<E, P>(
+    ck: &CommitterKey<E>,
+    powers_of_g_path: P,
+    powers_of_g2_path: P,
+) -> std::io::Result<()>
+where
+    E: Pairing,
+    P: AsRef<std::path::Path>,
+{
+    use std::fs::File;
+    use std::io::{self, BufWriter, Write};
+
+    let invalid_data = |e| io::Error::new(io::ErrorKind::InvalidData, e);
+    let mut writer = BufWriter::new(File::create(powers_of_g_path)?);
+    for g in ck.powers_of_g.iter().rev() {
+        g.serialize_uncompressed(&mut writer)
+            .map_err(invalid_data)?;
+    }
+    writer.flush()?;
+    let mut writer = BufWriter::new(File::create(powers_of_g2_path)?);
+    ck.powers_of_g2
+        .serialize_uncompressed(&mut writer)
+        .map_err(invalid_data)?;
+    writer.flush()
+}
+
+#[test]
+fn test_ceremony() {
+    use ark_bls12_381::{Bls12_381, Fr, G1Affine, G2Affine};
+    use ark_std::test_rng;
+
+    use crate::kzg::VerifierKey;
+
+    let rng = &mut test_rng();
+    let start = CommitterKey::<Bls12_381>::ceremony_start(16, 2);
+    let mut contributions = Vec::new();
+    let mut current = start.clone();
+    for _ in 0..2 {
+        let (next, proof) = current.update(rng);
+        contributions.push((next.clone(), proof));
+        current = next;
+    }
+    let (last, beacon_proof) = current.apply_beacon(b"block 42", 8);
+    assert!(verify_beacon(&current, &last, &beacon_proof, b"block 42", 8, rng).is_ok());
+    assert!(verify_beacon(&current, &last, &beacon_proof, b"block 43", 8, rng).is_err());
+    contributions.push((last, beacon_proof));
+    let ck = verify_chain(&start, &contributions, rng).unwrap();
+
+    // the final key is a working committer key.
+    let polynomial = (0..16).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let alpha = Fr::rand(rng);
+    let commitment = ck.commit(&polynomial);
+    let (evaluation, proof) = ck.open(&polynomial, &alpha);
+    let vk = VerifierKey::from(ck);
+    assert!(vk.verify(&commitment, &alpha, &evaluation, &proof).is_ok());
+
+    // tampering with any power, or replaying a proof, is detected.
+    let mut tampered = contributions.clone();
+    tampered[1].0.powers_of_g[5] = tampered[1].0.powers_of_g[6];
+    assert_eq!(verify_chain(&start, &tampered, rng).unwrap_err().0, 1);
+    let mut tampered = contributions.clone();
+    tampered[2].1 = tampered[1].1;
+    assert_eq!(verify_chain(&start, &tampered, rng).unwrap_err().0, 2);
+
+    // the exported key is read back in stream order.
+    let g1_path = std::env::temp_dir().join("gemini-test-ceremony-g1.srs");
+    let g2_path = std::env::temp_dir().join("gemini-test-ceremony-g2.srs");
+    export_streaming(ck, &g1_path, &g2_path).unwrap();
+    let g1_bytes = std::fs::read(&g1_path).unwrap();
+    let stream = g1_bytes
+        .chunks(G1Affine::default().uncompressed_size())
+        .map(|bytes| G1Affine::deserialize_uncompressed(bytes).unwrap())
+        .collect::<Vec<_>>();
+    assert!(stream.iter().rev().eq(ck.powers_of_g.iter()));
+    let g2_bytes = std::fs::read(&g2_path).unwrap();
+    let powers_of_g2 = Vec::<G2Affine>::deserialize_uncompressed(&g2_bytes[..]).unwrap();
+    assert_eq!(powers_of_g2, ck.powers_of_g2);
+}
@@ -0,0 +1,307 @@
+//! Commitments compatible with [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844) blobs.
+//!
+//! The Ethereum KZG trusted setup holds the powers of its trapdoor in the Lagrange basis
+//! of the roots of unity of order 4096 (in bit-reversed order),
+//! and a blob is the list of the evaluations of a polynomial of degree less than 4096
+//! over the same points.
+//! Converting the setup to the monomial basis yields a [`CommitterKey`] whose commitments
+//! to the interpolated polynomials are exactly the blob commitments:
+//! proofs can then refer to the data of a blob through its commitment,
+//! or through its versioned hash.
+//!
+//! The key can also be loaded from the JSON output of the ceremony itself,
+//! which holds the powers of the trapdoor in the monomial basis
+//! (see [`CommitterKey::from_ethereum_ceremony`]).
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, SerializationError};
+use ark_std::string::ToString;
+use ark_std::vec::Vec;
+use sha2::{Digest, Sha256};
+
+use crate::json::{array_from, decode_hex, point_from, Json};
+use crate::kzg::{Commitment, CommitterKey};
+
+/// The number of field elements in a blob.
+pub const FIELD_ELEMENTS_PER_BLOB: usize = 4096;
+/// The size of a field element in a blob, in bytes.
+pub const BYTES_PER_FIELD_ELEMENT: usize = 32;
+/// The version byte of the versioned hashes of KZG commitments.
+pub const VERSIONED_HASH_VERSION_KZG: u8 = 0x01;
+
+/// Reverse the `log_n` least significant bits of `i`.
+fn bit_reverse(i: usize, log_n: u32) -> usize {
+    if log_n == 0 {
+        i
+    } else {
+        i.reverse_bits() >> (usize::BITS - log_n)
+    }
+}
+
+/// Permute `elements` (of length a power of two) from bit-reversed to natural order, or back.
+fn bit_reverse_permutation<T: Copy>(elements: &[T]) -> Vec<T> {
+    let log_n = elements.len().trailing_zeros();
+    (0..elements.len())
+        .map(|i| elements[bit_reverse(i, log_n)])
+        .collect()
+}
+
+/// Parse the group element compressed in the hex string `hex`.
+fn parse_point<G: CanonicalDeserialize>(hex: Option<&str>) -> Result<G, SerializationError> {
+    let hex = hex.ok_or(SerializationError::InvalidData)?;
+    let hex = hex.strip_prefix("0x").unwrap_or(hex);
+    let bytes = decode_hex(hex.as_bytes())?;
+    G::deserialize_compressed(&bytes[..])
+}
+
+impl CommitterKey<Bls12_381> {
+    /// Load the Ethereum KZG trusted setup, in the text format of
+    /// [c-kzg-4844](https://github.com/ethereum/c-kzg-4844):
+    /// the number of \\(\GG_1\\) points and of \\(\GG_2\\) points,
+    /// followed by the \\(\GG_1\\) points in the Lagrange basis, in bit-reversed order,
+    /// and by the \\(\GG_2\\) points in the monomial basis, all compressed and hex-encoded.
+    /// Any trailing data (e.g., the \\(\GG_1\\) points in the monomial basis) is ignored.
+    ///
+    /// The \\(\GG_1\\) points are converted to the monomial basis with an FFT:
+    /// the key commits to polynomials of degree less than the number of \\(\GG_1\\) points,
+    /// which must be a power of two.
+    pub fn from_ethereum_setup(setup: &str) -> Result<Self, SerializationError> {
+        let mut tokens = setup.split_whitespace();
+        let mut length = || -> Result<usize, SerializationError> {
+            tokens
+                .next()
+                .and_then(|token| token.parse().ok())
+                .ok_or(SerializationError::InvalidData)
+        };
+        let num_g1 = length()?;
+        let num_g2 = length()?;
+        if !num_g1.is_power_of_two() || num_g2 == 0 {
+            return Err(SerializationError::InvalidData);
+        }
+        let lagrange = (0..num_g1)
+            .map(|_| parse_point::<G1Affine>(tokens.next()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let powers_of_g2 = (0..num_g2)
+            .map(|_| parse_point::<G2Affine>(tokens.next()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // since τ^i = Σ_j ω^{ij} L_j(τ), the monomial basis is the FFT of the Lagrange basis.
+        let domain =
+            Radix2EvaluationDomain::<Fr>::new(num_g1).ok_or(SerializationError::InvalidData)?;
+        let mut powers_of_g = bit_reverse_permutation(&lagrange)
+            .into_iter()
+            .map(G1Projective::from)
+            .collect::<Vec<_>>();
+        domain.fft_in_place(&mut powers_of_g);
+        Ok(CommitterKey {
+            powers_of_g: G1Projective::normalize_batch(&powers_of_g),
+            powers_of_g2,
+        })
+    }
+
+    /// Load the powers of the trapdoor with `num_g1_powers` \\(\GG_1\\) points
+    /// from the output of the [Ethereum KZG ceremony](https://ceremony.ethereum.org/),
+    /// in the JSON format of its sequencer (`transcript.json`):
+    /// a list of `transcripts`, each holding the powers in the monomial basis
+    /// (`powersOfTau.G1Powers` and `powersOfTau.G2Powers`), compressed and hex-encoded.
+    /// The ceremony has transcripts with 4096, 8192, 16384, and 32768 \\(\GG_1\\) points;
+    /// the streaming key is obtained with [`CommitterKeyStream::from`](crate::kzg::CommitterKeyStream).
+    ///
+    /// Fails if no transcript has `num_g1_powers` points,
+    /// if any point is not in the prime-order subgroup,
+    /// or if the powers do not start from the generators.
+    /// The consistency of the powers among each other is not checked.
+    pub fn from_ethereum_ceremony(
+        transcript: &str,
+        num_g1_powers: usize,
+    ) -> Result<Self, SerializationError> {
+        let transcript = Json::parse(transcript)?;
+        let num_powers = num_g1_powers.to_string();
+        let powers = transcript
+            .get("transcripts")?
+            .as_array()?
+            .iter()
+            .find(|t| t.get("numG1Powers").ok() == Some(&Json::Number(num_powers.clone())))
+            .ok_or(SerializationError::InvalidData)?
+            .get("powersOfTau")?;
+        let powers_of_g = array_from(powers.get("G1Powers")?, point_from::<G1Affine>)?;
+        let powers_of_g2 = array_from(powers.get("G2Powers")?, point_from::<G2Affine>)?;
+        if powers_of_g.len() != num_g1_powers
+            || powers_of_g.first() != Some(&G1Affine::generator())
+            || powers_of_g2.first() != Some(&G2Affine::generator())
+        {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(CommitterKey {
+            powers_of_g,
+            powers_of_g2,
+        })
+    }
+
+    /// Return the commitment to the blob `blob`, as defined in EIP-4844.
+    ///
+    /// Fails on the same inputs as [`blob_polynomial`].
+    pub fn commit_blob(&self, blob: &[u8]) -> Result<Commitment<Bls12_381>, SerializationError> {
+        Ok(self.commit(&blob_polynomial(blob)?))
+    }
+}
+
+/// Return the coefficients of the polynomial whose evaluations are stored in the blob `blob`,
+/// that is, the polynomial committed by the blob commitment.
+///
+/// The blob is the concatenation of big-endian field elements of [`BYTES_PER_FIELD_ELEMENT`] bytes,
+/// the evaluations over the roots of unity in bit-reversed order:
+/// Ethereum blobs hold [`FIELD_ELEMENTS_PER_BLOB`] elements, but any power of two is accepted.
+/// Fails if the number of elements is not a power of two,
+/// or if any element is not in its canonical representation.
+pub fn blob_polynomial(blob: &[u8]) -> Result<Vec<Fr>, SerializationError> {
+    let num_elements = blob.len() / BYTES_PER_FIELD_ELEMENT;
+    if blob.len() % BYTES_PER_FIELD_ELEMENT != 0 || !num_elements.is_power_of_two() {
+        return Err(SerializationError::InvalidData);
+    }
+    let evaluations = blob
+        .chunks(BYTES_PER_FIELD_ELEMENT)
+        .map(|chunk| {
+            // field elements are serialized in little-endian order.
+            let mut bytes = chunk.to_vec();
+            bytes.reverse();
+            Fr::deserialize_compressed(&bytes[..])
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let domain =
+        Radix2EvaluationDomain::<Fr>::new(num_elements).ok_or(SerializationError::InvalidData)?;
+    Ok(domain.ifft(&bit_reverse_permutation(&evaluations)))
+}
+
+/// Return the versioned hash of `commitment`, as referenced by blob transactions:
+/// the SHA-256 digest of the compressed commitment, with the first byte replaced by
+/// [`VERSIONED_HASH_VERSION_KZG`].
+pub fn versioned_hash(commitment: &Commitment<Bls12_381>) -> [u8; 32] {
+    let mut bytes = Vec::new();
+    commitment
+        .0
+        .serialize_compressed(&mut bytes)
+        .expect("serialization into a vector cannot fail");
+    let mut hash: [u8; 32] = Sha256::digest(&bytes).into();
+    hash[0] = VERSIONED_HASH_VERSION_KZG;
+    hash
+}
+
+#[test]
+fn test_ethereum_setup() {
+    use ark_ec::{Group, VariableBaseMSM};
+    use ark_ff::{BigInteger, PrimeField};
+    use ark_std::string::String;
+    use ark_std::test_rng;
+    use ark_std::UniformRand;
+
+    use crate::json::push_hex;
+    use crate::misc::powers;
+
+    let rng = &mut test_rng();
+    let num_elements = 16;
+    let tau = Fr::rand(rng);
+    let domain = Radix2EvaluationDomain::<Fr>::new(num_elements).unwrap();
+    let lagrange = bit_reverse_permutation(&domain.evaluate_all_lagrange_coefficients(tau))
+        .into_iter()
+        .map(|l| (G1Projective::generator() * l).into_affine())
+        .collect::<Vec<_>>();
+    let powers_of_tau = powers(tau, num_elements);
+
+    fn push_point(setup: &mut String, point: &impl CanonicalSerialize) {
+        let mut bytes = Vec::new();
+        point.serialize_compressed(&mut bytes).unwrap();
+        push_hex(setup, &bytes);
+        setup.push('\n');
+    }
+    let mut setup = String::from("16\n2\n");
+    for point in &lagrange {
+        push_point(&mut setup, point);
+    }
+    for t in &powers_of_tau[..2] {
+        push_point(
+            &mut setup,
+            &(ark_bls12_381::G2Projective::generator() * t).into_affine(),
+        );
+    }
+
+    let ck = CommitterKey::<Bls12_381>::from_ethereum_setup(&setup).unwrap();
+    let expected = powers_of_tau
+        .iter()
+        .map(|t| (G1Projective::generator() * t).into_affine())
+        .collect::<Vec<_>>();
+    assert_eq!(ck.powers_of_g, expected);
+
+    // the commitment is the linear combination of the Lagrange basis with the blob.
+    let evaluations = (0..num_elements).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let blob = evaluations
+        .iter()
+        .flat_map(|e| e.into_bigint().to_bytes_be())
+        .collect::<Vec<_>>();
+    let commitment = ck.commit_blob(&blob).unwrap();
+    assert_eq!(commitment.0, G1Projective::msm(&lagrange, &evaluations));
+    assert_eq!(versioned_hash(&commitment)[0], VERSIONED_HASH_VERSION_KZG);
+
+    // non-canonical field elements are rejected.
+    let mut malformed = blob.clone();
+    malformed[..BYTES_PER_FIELD_ELEMENT].copy_from_slice(&[0xff; BYTES_PER_FIELD_ELEMENT]);
+    assert!(blob_polynomial(&malformed).is_err());
+    assert!(blob_polynomial(&blob[1..]).is_err());
+}
+
+#[test]
+fn test_ethereum_ceremony() {
+    use ark_ec::Group;
+    use ark_std::test_rng;
+    use ark_std::UniformRand;
+
+    use crate::json::{array, point};
+    use crate::misc::powers;
+
+    let rng = &mut test_rng();
+    let tau = Fr::rand(rng);
+    let powers_of_tau = powers(tau, 8);
+    let g1_powers = powers_of_tau
+        .iter()
+        .map(|t| (G1Projective::generator() * t).into_affine())
+        .collect::<Vec<_>>();
+    let g2_powers = powers_of_tau[..3]
+        .iter()
+        .map(|t| (ark_bls12_381::G2Projective::generator() * t).into_affine())
+        .collect::<Vec<_>>();
+    let transcript = |num_g1_powers: usize| {
+        Json::object(vec![
+            ("numG1Powers", Json::Number(num_g1_powers.to_string())),
+            ("numG2Powers", Json::Number(g2_powers.len().to_string())),
+            (
+                "powersOfTau",
+                Json::object(vec![
+                    ("G1Powers", array(&g1_powers[..num_g1_powers], point)),
+                    ("G2Powers", array(&g2_powers, point)),
+                ]),
+            ),
+        ])
+    };
+    let output = Json::object(vec![(
+        "transcripts",
+        Json::Array(vec![transcript(4), transcript(8)]),
+    )])
+    .to_pretty_string();
+
+    let ck = CommitterKey::<Bls12_381>::from_ethereum_ceremony(&output, 8).unwrap();
+    assert_eq!(ck.powers_of_g, g1_powers);
+    assert_eq!(ck.powers_of_g2, g2_powers);
+    let ck = CommitterKey::<Bls12_381>::from_ethereum_ceremony(&output, 4).unwrap();
+    assert_eq!(ck.powers_of_g, &g1_powers[..4]);
+    assert!(CommitterKey::<Bls12_381>::from_ethereum_ceremony(&output, 16).is_err());
+
+    // the powers must start from the generator.
+    let output = Json::object(vec![("transcripts", Json::Array(vec![transcript(8)]))])
+        .to_pretty_string()
+        .replace(
+            point(&g1_powers[0]).as_str().unwrap(),
+            point(&g1_powers[1]).as_str().unwrap(),
+        );
+    assert!(CommitterKey::<Bls12_381>::from_ethereum_ceremony(&output, 8).is_err());
+}
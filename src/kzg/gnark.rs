@@ -0,0 +1,253 @@
+//! Serialization compatible with [gnark](https://github.com/consensys/gnark-crypto).
+//!
+//! Over BLS12-381, gnark encodes points as arkworks does (in the format of zcash):
+//! the big-endian coordinates, with the three most significant bits flagging compression,
+//! the point at infinity, and the sign of the \\(y\\) coordinate.
+//! Field elements instead are encoded in big-endian order,
+//! and vectors are prefixed by their length, as a big-endian `u32`.
+//!
+//! This module reads and writes the layouts of the KZG artifacts of `gnark-crypto/ecc/bls12-381/kzg`:
+//! - a [`Commitment`] is a `kzg.Digest`, a compressed \\(\GG_1\\) point;
+//! - an [`EvaluationProof`], together with the evaluation, is a `kzg.OpeningProof`:
+//!     the compressed quotient followed by the claimed value;
+//! - a [`VerifierKey`] is a `kzg.VerifyingKey`: the generator of \\(\GG_2\\), its multiplication by the trapdoor,
+//!     and the generator of \\(\GG_1\\);
+//! - a [`CommitterKey`] is a `kzg.SRS`: the vector of the powers of the trapdoor in \\(\GG_1\\)
+//!     (the proving key), followed by the verifying key.
+//!
+//! Verifying keys are written without the precomputed lines of the Miller loop
+//! that recent versions of gnark append to them.
+//! Points are always written compressed, and read back with the subgroup checks.
+use ark_bls12_381::{Bls12_381, Fr, G1Affine, G2Affine};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::vec::Vec;
+
+use crate::kzg::{Commitment, CommitterKey, EvaluationProof, VerifierKey};
+
+/// The size of a field element, in bytes.
+pub const GNARK_FR_SIZE: usize = 32;
+/// The size of a compressed \\(\GG_1\\) point, in bytes.
+pub const GNARK_G1_SIZE: usize = 48;
+/// The size of a compressed \\(\GG_2\\) point, in bytes.
+pub const GNARK_G2_SIZE: usize = 96;
+
+/// Write the field element `element` in big-endian order.
+pub fn write_gnark_fr<W: Write>(element: &Fr, mut writer: W) -> Result<(), SerializationError> {
+    let mut bytes = Vec::with_capacity(GNARK_FR_SIZE);
+    element.serialize_compressed(&mut bytes)?;
+    bytes.reverse();
+    Ok(writer.write_all(&bytes)?)
+}
+
+/// Read a field element in big-endian order.
+///
+/// Fails if the element is not in its canonical representation.
+pub fn read_gnark_fr<R: Read>(mut reader: R) -> Result<Fr, SerializationError> {
+    let mut bytes = [0u8; GNARK_FR_SIZE];
+    reader.read_exact(&mut bytes)?;
+    bytes.reverse();
+    Fr::deserialize_compressed(&bytes[..])
+}
+
+/// Write the length of a vector, as a big-endian `u32`.
+fn write_len<W: Write>(len: usize, mut writer: W) -> Result<(), SerializationError> {
+    let len = u32::try_from(len).map_err(|_| SerializationError::InvalidData)?;
+    Ok(writer.write_all(&len.to_be_bytes())?)
+}
+
+/// Read the length of a vector, as a big-endian `u32`.
+fn read_len<R: Read>(mut reader: R) -> Result<usize, SerializationError> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_be_bytes(bytes) as usize)
+}
+
+impl Commitment<Bls12_381> {
+    /// Write the commitment as a gnark `kzg.Digest`.
+    pub fn serialize_gnark<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+        G1Affine::from(self.0).serialize_compressed(writer)
+    }
+
+    /// Read a commitment from a gnark `kzg.Digest`.
+    pub fn deserialize_gnark<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        Ok(Commitment(G1Affine::deserialize_compressed(reader)?.into()))
+    }
+}
+
+impl EvaluationProof<Bls12_381> {
+    /// Write the proof, along with the evaluation `evaluation` it proves,
+    /// as a gnark `kzg.OpeningProof`.
+    pub fn serialize_gnark<W: Write>(
+        &self,
+        evaluation: &Fr,
+        mut writer: W,
+    ) -> Result<(), SerializationError> {
+        G1Affine::from(self.0).serialize_compressed(&mut writer)?;
+        write_gnark_fr(evaluation, writer)
+    }
+
+    /// Read a proof and the evaluation it proves from a gnark `kzg.OpeningProof`.
+    pub fn deserialize_gnark<R: Read>(mut reader: R) -> Result<(Self, Fr), SerializationError> {
+        let proof = G1Affine::deserialize_compressed(&mut reader)?;
+        let evaluation = read_gnark_fr(reader)?;
+        Ok((EvaluationProof(proof.into()), evaluation))
+    }
+}
+
+/// Write the verifying key with generators `g` and `g2`, and trapdoor `tau_g2` in \\(\GG_2\\).
+fn write_verifying_key<W: Write>(
+    g: &G1Affine,
+    g2: &G2Affine,
+    tau_g2: &G2Affine,
+    mut writer: W,
+) -> Result<(), SerializationError> {
+    g2.serialize_compressed(&mut writer)?;
+    tau_g2.serialize_compressed(&mut writer)?;
+    g.serialize_compressed(writer)
+}
+
+/// Read the generators and the trapdoor in \\(\GG_2\\) of a verifying key.
+fn read_verifying_key<R: Read>(
+    mut reader: R,
+) -> Result<(G1Affine, Vec<G2Affine>), SerializationError> {
+    let g2 = G2Affine::deserialize_compressed(&mut reader)?;
+    let tau_g2 = G2Affine::deserialize_compressed(&mut reader)?;
+    let g = G1Affine::deserialize_compressed(reader)?;
+    Ok((g, vec![g2, tau_g2]))
+}
+
+impl VerifierKey<Bls12_381> {
+    /// Write the key as a gnark `kzg.VerifyingKey`.
+    ///
+    /// Only the elements verifying single-point openings are written.
+    pub fn serialize_gnark<W: Write>(&self, writer: W) -> Result<(), SerializationError> {
+        if self.powers_of_g.is_empty() || self.powers_of_g2.len() < 2 {
+            return Err(SerializationError::InvalidData);
+        }
+        write_verifying_key(
+            &self.powers_of_g[0],
+            &self.powers_of_g2[0],
+            &self.powers_of_g2[1],
+            writer,
+        )
+    }
+
+    /// Read a key from a gnark `kzg.VerifyingKey`.
+    ///
+    /// The key verifies openings over a single evaluation point.
+    pub fn deserialize_gnark<R: Read>(reader: R) -> Result<Self, SerializationError> {
+        let (g, powers_of_g2) = read_verifying_key(reader)?;
+        Ok(VerifierKey {
+            powers_of_g: vec![g],
+            powers_of_g2,
+        })
+    }
+}
+
+impl CommitterKey<Bls12_381> {
+    /// Write the key as a gnark `kzg.SRS`.
+    ///
+    /// Only the elements verifying single-point openings are written in the verifying key.
+    pub fn serialize_gnark<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        if self.powers_of_g.is_empty() || self.powers_of_g2.len() < 2 {
+            return Err(SerializationError::InvalidData);
+        }
+        write_len(self.powers_of_g.len(), &mut writer)?;
+        for g in &self.powers_of_g {
+            g.serialize_compressed(&mut writer)?;
+        }
+        write_verifying_key(
+            &self.powers_of_g[0],
+            &self.powers_of_g2[0],
+            &self.powers_of_g2[1],
+            writer,
+        )
+    }
+
+    /// Read a key from a gnark `kzg.SRS`.
+    ///
+    /// The key opens polynomials over a single evaluation point.
+    /// Fails if the proving and the verifying key have different generators of \\(\GG_1\\).
+    pub fn deserialize_gnark<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let len = read_len(&mut reader)?;
+        // do not trust the length for the allocation.
+        let mut powers_of_g = Vec::with_capacity(ark_std::cmp::min(len, 1 << 16));
+        for _ in 0..len {
+            powers_of_g.push(G1Affine::deserialize_compressed(&mut reader)?);
+        }
+        let (g, powers_of_g2) = read_verifying_key(reader)?;
+        if powers_of_g.first() != Some(&g) {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(CommitterKey {
+            powers_of_g,
+            powers_of_g2,
+        })
+    }
+}
+
+#[test]
+fn test_gnark_serialization() {
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_std::UniformRand;
+
+    use crate::json::decode_hex;
+
+    let rng = &mut ark_std::test_rng();
+    let d = 15;
+    let ck = CommitterKey::<Bls12_381>::new(d, 1, rng);
+    let vk = VerifierKey::from(&ck);
+    let polynomial = (0..d + 1).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let alpha = Fr::rand(rng);
+    let commitment = ck.commit(&polynomial);
+    let (evaluation, proof) = ck.open(&polynomial, &alpha);
+
+    // field elements are big-endian.
+    let mut bytes = Vec::new();
+    write_gnark_fr(&Fr::from(1u64), &mut bytes).unwrap();
+    assert_eq!(bytes[GNARK_FR_SIZE - 1], 1);
+    assert!(bytes[..GNARK_FR_SIZE - 1].iter().all(|&b| b == 0));
+    // non-canonical elements are rejected.
+    assert!(read_gnark_fr(&[0xffu8; GNARK_FR_SIZE][..]).is_err());
+
+    // the generator of G1, as encoded by gnark.
+    let generator = decode_hex(
+        b"97f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb",
+    )
+    .unwrap();
+    let mut bytes = Vec::new();
+    Commitment::<Bls12_381>(G1Affine::generator().into_group())
+        .serialize_gnark(&mut bytes)
+        .unwrap();
+    assert_eq!(bytes, generator);
+    let mut infinity = [0u8; GNARK_G1_SIZE];
+    infinity[0] = 0xc0;
+    let identity = Commitment::<Bls12_381>::deserialize_gnark(&infinity[..]).unwrap();
+    assert_eq!(identity.0.into_affine(), G1Affine::zero());
+
+    let mut bytes = Vec::new();
+    commitment.serialize_gnark(&mut bytes).unwrap();
+    assert_eq!(bytes.len(), GNARK_G1_SIZE);
+    let commitment = Commitment::deserialize_gnark(&bytes[..]).unwrap();
+
+    let mut bytes = Vec::new();
+    proof.serialize_gnark(&evaluation, &mut bytes).unwrap();
+    assert_eq!(bytes.len(), GNARK_G1_SIZE + GNARK_FR_SIZE);
+    let (proof, evaluation) = EvaluationProof::deserialize_gnark(&bytes[..]).unwrap();
+
+    let mut bytes = Vec::new();
+    vk.serialize_gnark(&mut bytes).unwrap();
+    assert_eq!(bytes.len(), 2 * GNARK_G2_SIZE + GNARK_G1_SIZE);
+    let vk = VerifierKey::deserialize_gnark(&bytes[..]).unwrap();
+    assert!(vk.verify(&commitment, &alpha, &evaluation, &proof).is_ok());
+
+    let mut bytes = Vec::new();
+    ck.serialize_gnark(&mut bytes).unwrap();
+    assert_eq!(
+        bytes.len(),
+        4 + (d + 1) * GNARK_G1_SIZE + 2 * GNARK_G2_SIZE + GNARK_G1_SIZE
+    );
+    let gnark_ck = CommitterKey::<Bls12_381>::deserialize_gnark(&bytes[..]).unwrap();
+    assert_eq!(gnark_ck.commit(&polynomial), commitment);
+    assert!(CommitterKey::<Bls12_381>::deserialize_gnark(&bytes[..bytes.len() - 1]).is_err());
+}
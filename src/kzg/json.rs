@@ -0,0 +1,50 @@
+//! JSON export of the verification key.
+use ark_ec::pairing::Pairing;
+use ark_serialize::SerializationError;
+use ark_std::string::String;
+
+use crate::json::{array, array_from, point, point_from, Json};
+use crate::kzg::VerifierKey;
+
+impl<E: Pairing> VerifierKey<E> {
+    /// Export the verification key as a JSON object with the fields:
+    ///
+    /// ```json
+    /// {
+    ///   "protocol": "gemini",
+    ///   "version": 1,
+    ///   "type": "verifier_key",
+    ///   "powers_of_g": ["0x…", …],
+    ///   "powers_of_g2": ["0x…", …]
+    /// }
+    /// ```
+    ///
+    /// where `version` is [`PROOF_FORMAT_VERSION`](crate::PROOF_FORMAT_VERSION),
+    /// and points are hex strings of their compressed serialization.
+    pub fn to_json(&self) -> String {
+        let mut fields = Json::header("verifier_key");
+        fields.push(("powers_of_g", array(&self.powers_of_g, point)));
+        fields.push(("powers_of_g2", array(&self.powers_of_g2, point)));
+        Json::object(fields).to_pretty_string()
+    }
+
+    /// Import a verification key exported with [`VerifierKey::to_json`].
+    ///
+    /// Fails if the header does not match,
+    /// if any point is not in the prime-order subgroup,
+    /// or if the key is malformed.
+    pub fn from_json(json: &str) -> Result<Self, SerializationError> {
+        let value = Json::parse(json)?;
+        value.check_header("verifier_key")?;
+        let powers_of_g = array_from(value.get("powers_of_g")?, point_from)?;
+        let powers_of_g2 = array_from(value.get("powers_of_g2")?, point_from)?;
+        // the key for `n` evaluation points has `n` powers in G1 and `n+1` powers in G2.
+        if powers_of_g.is_empty() || powers_of_g2.len() != powers_of_g.len() + 1 {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(VerifierKey {
+            powers_of_g,
+            powers_of_g2,
+        })
+    }
+}
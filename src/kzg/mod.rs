@@ -82,12 +82,32 @@
 //! assert!(vk.verify(&commitment, &alpha, &evaluation, &proof).is_ok())
 //! ```
 //!
+//! Over BLS12-381, the committer key can also be loaded from the Ethereum KZG trusted setup,
+//! so that commitments to [EIP-4844](https://eips.ethereum.org/EIPS/eip-4844) blobs
+//! are commitments of this scheme (see [`eip4844`]).
+//! Projects can also run their own ceremony for the SRS, see [`ceremony`].
+//! Commitments, proofs, and keys can be exchanged with Go components in the layouts of gnark, see [`gnark`].
+//!
+//! The multi-scalar multiplications of the commitments can be delegated to a [`backend`].
+//! Provers committing many times with the same key can instead precompute
+//! fixed-base tables of the key, see [`precomputed`].
+//! Streaming openings choose the window of their multi-scalar multiplications
+//! from the length of the stream, see [`pippenger`].
 
+pub mod backend;
+pub mod ceremony;
+pub mod eip4844;
+pub mod gnark;
+mod json;
+pub mod pippenger;
+pub mod precomputed;
+mod prepared;
 mod space;
 mod time;
 
 use ark_ec::CurveGroup;
 use ark_std::vec::Vec;
+pub use prepared::PreparedVerifierKey;
 pub use space::CommitterKeyStream;
 pub use time::CommitterKey;
 
@@ -98,17 +118,34 @@ use ark_ec::{pairing::Pairing, AffineRepr, VariableBaseMSM};
 use ark_ff::{Field, One, Zero};
 use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
 use ark_serialize::*;
-use ark_std::fmt;
 use ark_std::ops::{Add, Mul};
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 
-use crate::misc::{linear_combination, powers};
+use crate::misc::{batch_inverse, linear_combination, powers};
 
 /// A Kate polynomial commitment over a bilinear group, represented as a single \\(\GG_1\\) element.
-#[derive(CanonicalSerialize, Copy, Clone, Debug, PartialEq, Eq)]
+#[derive(CanonicalSerialize, CanonicalDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Commitment<E: Pairing>(pub(crate) E::G1);
 
+// commitments to disjoint chunks of a polynomial (each with the chunk of the key at the same positions)
+// add up to the commitment to the polynomial.
+impl<E: Pairing> Add for Commitment<E> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Commitment(self.0 + rhs.0)
+    }
+}
+
+impl<E: Pairing> core::iter::Sum for Commitment<E> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Commitment(E::G1::zero()), |x, y| x + y)
+    }
+}
+
 /// Polynomial evaluation proof, represented as a single $\GG_1$ element.
-#[derive(CanonicalSerialize, Clone, Debug, PartialEq, Eq)]
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
 pub struct EvaluationProof<E: Pairing>(pub E::G1);
 
 impl<E: Pairing> Add for EvaluationProof<E> {
@@ -126,26 +163,90 @@ impl<E: Pairing> core::iter::Sum for EvaluationProof<E> {
 }
 
 /// Error type denoting an incorrect evaluation proof.
-#[derive(Debug, Clone)]
-pub struct VerificationError;
+pub use crate::errors::VerificationError;
+pub(crate) use crate::errors::VerificationResult;
+
+/// A deferred pairing check $e(L, H) = e(\pi, Z)$,
+/// where $H$ is the generator of $\GG_2$.
+///
+/// Many checks can be verified at once with [`VerifierKey::batch_check`].
+/// Checks can be serialized, e.g. for delegating them to an aggregator:
+/// when deserialized, all points are checked to lie in the prime-order subgroups.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PairingCheck<E: Pairing> {
+    lhs: E::G1,
+    proof: E::G1,
+    zeros: E::G2,
+}
+
+impl<E: Pairing> PairingCheck<E> {
+    /// The element $L$ of the check.
+    pub fn lhs(&self) -> E::G1 {
+        self.lhs
+    }
 
-impl fmt::Display for VerificationError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Error in stream.")
+    /// The evaluation proof $\pi$ of the check.
+    pub fn proof(&self) -> E::G1 {
+        self.proof
     }
+
+    /// The commitment $Z$ to the vanishing polynomial of the evaluation points.
+    pub fn zeros(&self) -> E::G2 {
+        self.zeros
+    }
+}
+
+/// A multi-scalar multiplication $\sum_i s_i \cdot G_i$, left to compute.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeferredMsm<G: AffineRepr> {
+    /// The bases $G_i$.
+    pub bases: Vec<G>,
+    /// The scalars $s_i$, as many as the bases.
+    pub scalars: Vec<G::ScalarField>,
 }
 
-pub(crate) type VerificationResult = Result<(), VerificationError>;
+impl<G: AffineRepr> DeferredMsm<G> {
+    /// Compute the multi-scalar multiplication.
+    pub fn compute(&self) -> G::Group {
+        <G::Group as VariableBaseMSM>::msm(&self.bases, &self.scalars)
+    }
+}
+
+/// A [`PairingCheck`] where the elements $L$ and $Z$ are left as multi-scalar multiplications:
+/// $L$ over the commitments and the verifier key in $\GG_1$, and $Z$ over the verifier key in $\GG_2$.
+///
+/// This is the group-side part of the verification,
+/// e.g. for computing it within a circuit over the base field of the curve.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MsmPairingCheck<E: Pairing> {
+    /// The multi-scalar multiplication for the element $L$.
+    pub lhs: DeferredMsm<E::G1Affine>,
+    /// The evaluation proof $\pi$.
+    pub proof: E::G1Affine,
+    /// The multi-scalar multiplication for the element $Z$.
+    pub zeros: DeferredMsm<E::G2Affine>,
+}
+
+impl<E: Pairing> MsmPairingCheck<E> {
+    /// Compute the multi-scalar multiplications, and return the resulting check.
+    pub fn evaluate(&self) -> PairingCheck<E> {
+        PairingCheck {
+            lhs: self.lhs.compute(),
+            proof: self.proof.into_group(),
+            zeros: self.zeros.compute(),
+        }
+    }
+}
 
 // XXX.  add const generic argument for the size.
 /// The verification key for the polynomial commitment scheme.
 /// It also implements verification functions for the evaluation proof.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
 pub struct VerifierKey<E: Pairing> {
     /// The generator of $\GG_1$
-    powers_of_g: Vec<E::G1Affine>,
+    pub(crate) powers_of_g: Vec<E::G1Affine>,
     /// The generator of $\GG_2$, together with its multiplication by the trapdoor.
-    powers_of_g2: Vec<E::G2Affine>,
+    pub(crate) powers_of_g2: Vec<E::G2Affine>,
 }
 
 impl<E: Pairing> VerifierKey<E> {
@@ -167,7 +268,7 @@ impl<E: Pairing> VerifierKey<E> {
         if E::pairing(lhs, g2) == E::pairing(proof.0, ep) {
             Ok(())
         } else {
-            Err(VerificationError)
+            Err(VerificationError::opening())
         }
     }
 
@@ -185,13 +286,49 @@ impl<E: Pairing> VerifierKey<E> {
         proof: &EvaluationProof<E>,
         open_chal: &E::ScalarField,
     ) -> VerificationResult {
+        let check =
+            self.multi_points_check(commitments, eval_points, evaluations, proof, open_chal);
+        self.check(&check)
+    }
+
+    /// Same as [`VerifierKey::verify_multi_points`],
+    /// but return the pairing check instead of computing it.
+    pub(crate) fn multi_points_check(
+        &self,
+        commitments: &[Commitment<E>],
+        eval_points: &[E::ScalarField],
+        evaluations: &[Vec<E::ScalarField>],
+        proof: &EvaluationProof<E>,
+        open_chal: &E::ScalarField,
+    ) -> PairingCheck<E> {
+        self.multi_points_msm_check(commitments, eval_points, evaluations, proof, open_chal)
+            .evaluate()
+    }
+
+    /// Same as [`VerifierKey::multi_points_check`],
+    /// but leave the multi-scalar multiplications of the check to compute.
+    pub(crate) fn multi_points_msm_check(
+        &self,
+        commitments: &[Commitment<E>],
+        eval_points: &[E::ScalarField],
+        evaluations: &[Vec<E::ScalarField>],
+        proof: &EvaluationProof<E>,
+        open_chal: &E::ScalarField,
+    ) -> MsmPairingCheck<E> {
         // Computing the vanishing polynomial over eval_points
         let zeros = vanishing_polynomial(eval_points);
-        let zeros = E::G2::msm(&self.powers_of_g2, zeros.coeffs());
+        let zeros = DeferredMsm {
+            bases: self
+                .powers_of_g2
+                .iter()
+                .take(zeros.coeffs.len())
+                .copied()
+                .collect(),
+            scalars: zeros.coeffs,
+        };
 
         // Computing the inverse for the interpolation
-        let mut sca_inverse = Vec::new();
-        for (j, x_j) in eval_points.iter().enumerate() {
+        let denominators = eval_points.iter().enumerate().map(|(j, x_j)| {
             let mut sca = E::ScalarField::one();
             for (k, x_k) in eval_points.iter().enumerate() {
                 if j == k {
@@ -199,9 +336,10 @@ impl<E: Pairing> VerifierKey<E> {
                 }
                 sca *= *x_j - x_k;
             }
-            sca = sca.inverse().unwrap();
-            sca_inverse.push(sca);
-        }
+            sca
+        });
+        let sca_inverse =
+            batch_inverse(denominators, usize::max(eval_points.len(), 1)).collect::<Vec<_>>();
 
         // Computing the lagrange polynomial for the interpolation
         let mut lang = Vec::new();
@@ -220,26 +358,67 @@ impl<E: Pairing> VerifierKey<E> {
 
         // Computing the commitment for the interpolated polynomials
         let etas = powers(*open_chal, evaluations.len());
-        let interpolated_polynomials = evaluations
-            .iter()
+        let interpolated_polynomials = cfg_iter!(evaluations)
             .map(|e| interpolate_poly::<E>(eval_points, e, &sca_inverse, &lang).coeffs)
             .collect::<Vec<_>>();
         let i_poly = linear_combination(&interpolated_polynomials[..], &etas).unwrap();
 
-        let i_comm = E::G1::msm(&self.powers_of_g, &i_poly);
-
-        // Gathering commitments
-        let comm_vec = commitments
+        // Gathering commitments: L = Σ_j η^j C_j - Σ_i i_i G_i
+        let mut bases = commitments
             .iter()
             .map(|x| x.0.into_affine())
             .collect::<Vec<_>>();
-        let f_comm = E::G1::msm(&comm_vec, &etas);
+        let mut scalars = etas;
+        let len = usize::min(bases.len(), scalars.len());
+        bases.truncate(len);
+        scalars.truncate(len);
+        bases.extend(self.powers_of_g.iter().take(i_poly.len()));
+        scalars.extend(i_poly.iter().map(|c| -*c));
+        MsmPairingCheck {
+            lhs: DeferredMsm { bases, scalars },
+            proof: proof.0.into_affine(),
+            zeros,
+        }
+    }
+
+    /// Compute the pairing check `check`.
+    pub fn check(&self, check: &PairingCheck<E>) -> VerificationResult {
         let g2 = self.powers_of_g2[0];
+        if E::pairing(check.lhs, g2) == E::pairing(check.proof, check.zeros) {
+            Ok(())
+        } else {
+            Err(VerificationError::opening())
+        }
+    }
+
+    /// Compute all pairing checks `checks` with a single multi-pairing.
+    ///
+    /// The checks are combined using the powers of `randomness`,
+    /// which must be sampled after all checks are fixed.
+    pub fn batch_check(
+        &self,
+        checks: &[PairingCheck<E>],
+        randomness: E::ScalarField,
+    ) -> VerificationResult {
+        let coefficients = powers(randomness, checks.len());
+        let lhs = checks
+            .iter()
+            .zip(&coefficients)
+            .map(|(check, coefficient)| check.lhs * coefficient)
+            .sum::<E::G1>();
+        let proofs = checks
+            .iter()
+            .zip(&coefficients)
+            .map(|(check, coefficient)| check.proof * coefficient)
+            .collect::<Vec<_>>();
+        let zeros = checks.iter().map(|check| check.zeros).collect::<Vec<_>>();
 
-        if E::pairing(f_comm - i_comm, g2) == E::pairing(proof.0, zeros) {
+        if E::pairing(lhs, self.powers_of_g2[0]) == E::multi_pairing(proofs, zeros) {
             Ok(())
         } else {
-            Err(VerificationError)
+            Err(VerificationError::Opening {
+                opening: "batched pairing check",
+            })
         }
     }
 }
@@ -0,0 +1,145 @@
+//! A streaming Pippenger algorithm, choosing its window from the length of the stream.
+//!
+//! Buffering the (base, scalar) pairs in chunks and computing one multi-scalar multiplication per chunk
+//! chooses the window of each multi-scalar multiplication from the size of the chunk,
+//! and sums the buckets once per chunk, independently of how many scalars the stream actually has.
+//! A [`StreamPippenger`] instead keeps the buckets across the whole stream:
+//! the window is chosen once, by [`window_size`], from the (known) length of the stream
+//! and the bit size of the scalars, and the buckets are summed only once at the end.
+//! The pairs are still buffered in chunks, so that the windows of each chunk are processed in parallel.
+//! Pairs with zero scalars are skipped, and bases with unit scalars are summed apart, as they come.
+use ark_ec::CurveGroup;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use ark_std::borrow::Borrow;
+use ark_std::vec::Vec;
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::kzg::precomputed::digit;
+use crate::misc::ceil_div;
+
+/// The largest window considered, in bits.
+const MAX_WINDOW: usize = 24;
+
+/// Return the window (in bits) minimizing the group additions of Pippenger's algorithm
+/// over `len` scalars of `num_bits` bits, with at most `max_buckets` buckets across all windows
+/// (or windows of a single bit, if none fits).
+///
+/// Each window costs one addition per scalar, plus two additions per bucket to sum the buckets up:
+/// small streams prefer small windows, while large streams amortize the buckets of larger windows.
+pub fn window_size(len: usize, num_bits: usize, max_buckets: usize) -> usize {
+    let cost = |window: usize| ceil_div(num_bits, window) * (len + (2 << window));
+    (2..=MAX_WINDOW)
+        .filter(|&window| ceil_div(num_bits, window) << window <= max_buckets)
+        .fold(1, |best, window| {
+            if cost(window) < cost(best) {
+                window
+            } else {
+                best
+            }
+        })
+}
+
+/// Return `true` if the limbs `limbs` (in little-endian order) represent one.
+pub(crate) fn is_unit(limbs: &[u64]) -> bool {
+    limbs.split_first().map_or(false, |(&low, high)| {
+        low == 1 && high.iter().all(|&limb| limb == 0)
+    })
+}
+
+/// A multi-scalar multiplication over a stream of (base, scalar) pairs of known length.
+pub struct StreamPippenger<G: CurveGroup> {
+    window: usize,
+    /// The buckets of each window, from the least significant.
+    buckets: Vec<Vec<G>>,
+    /// The sum of the bases with unit scalars.
+    units: G,
+    bases: Vec<G::Affine>,
+    scalars: Vec<<G::ScalarField as PrimeField>::BigInt>,
+    buf_size: usize,
+}
+
+impl<G: CurveGroup> StreamPippenger<G> {
+    /// Prepare a multi-scalar multiplication of `len` pairs,
+    /// holding at most `max_msm_buffer` pairs and (see [`window_size`]) `max_msm_buffer` buckets.
+    pub fn new(len: usize, max_msm_buffer: usize) -> Self {
+        let num_bits = G::ScalarField::MODULUS_BIT_SIZE as usize;
+        let window = window_size(len, num_bits, max_msm_buffer);
+        let buf_size = usize::max(usize::min(len, max_msm_buffer), 1);
+        Self {
+            window,
+            buckets: vec![vec![G::zero(); (1 << window) - 1]; ceil_div(num_bits, window)],
+            units: G::zero(),
+            bases: Vec::with_capacity(buf_size),
+            scalars: Vec::with_capacity(buf_size),
+            buf_size,
+        }
+    }
+
+    /// The window chosen, in bits.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// Add a new (base, scalar) pair into the multi-scalar multiplication.
+    #[inline]
+    pub fn add<B, S>(&mut self, base: B, scalar: S)
+    where
+        B: Borrow<G::Affine>,
+        S: Borrow<<G::ScalarField as PrimeField>::BigInt>,
+    {
+        let scalar = scalar.borrow();
+        if BigInteger::is_zero(scalar) {
+            return;
+        } else if is_unit(scalar.as_ref()) {
+            self.units += *base.borrow();
+            return;
+        }
+        self.bases.push(*base.borrow());
+        self.scalars.push(*scalar);
+        if self.bases.len() == self.buf_size {
+            self.flush();
+        }
+    }
+
+    /// Add the buffered pairs into the buckets.
+    fn flush(&mut self) {
+        let window = self.window;
+        let (bases, scalars) = (&self.bases, &self.scalars);
+        cfg_iter_mut!(self.buckets)
+            .enumerate()
+            .for_each(|(j, buckets)| {
+                for (base, scalar) in bases.iter().zip(scalars) {
+                    let digit = digit(scalar.as_ref(), j * window, window);
+                    if digit != 0 {
+                        buckets[digit - 1] += *base;
+                    }
+                }
+            });
+        self.bases.clear();
+        self.scalars.clear();
+    }
+
+    /// Output the result of the multi-scalar multiplication.
+    pub fn finalize(mut self) -> G {
+        self.flush();
+        // sum the buckets of each window, each multiplied by its digit,
+        // and combine the windows from the most significant.
+        let total = self
+            .buckets
+            .iter()
+            .rev()
+            .fold(G::zero(), |mut total, buckets| {
+                (0..self.window).for_each(|_| {
+                    total.double_in_place();
+                });
+                let mut running = G::zero();
+                for bucket in buckets.iter().rev() {
+                    running += bucket;
+                    total += running;
+                }
+                total
+            });
+        total + self.units
+    }
+}
@@ -0,0 +1,306 @@
+//! Fixed-base precomputation, for committing many times with the same key.
+//!
+//! Provers serving many proofs with the same key compute many multi-scalar multiplications
+//! over the same bases $G, \tau G, \dots, \tau^D G$.
+//! For windows of $c$ bits, a fixed-base table stores, for each base $P$,
+//! the points $2^{cj} P$ for each of the $\lceil b / c \rceil$ windows of a $b$-bit scalar.
+//! A multi-scalar multiplication is then a single round of the bucket method over the table:
+//! one addition for each window of each scalar, and no doublings.
+//!
+//! The table is $\lceil b / c \rceil$ times larger than the key, and the bucket method holds $2^c$ buckets:
+//! larger windows shrink the table but enlarge the buckets.
+//! A [`FixedBaseTable`] holds the table in memory, for the [`CommitterKey`].
+//! For the [`CommitterKeyStream`], the table is written to disk with
+//! [`CommitterKeyStream::write_fixed_base_table`], and read back by a [`FixedBaseStream`]
+//! from any stream of points (e.g. a memory-mapped file).
+//! The table is not checked against the key when read back.
+use ark_ec::pairing::Pairing;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{PrimeField, Zero};
+use ark_serialize::{CanonicalSerialize, SerializationError, Write};
+use ark_std::borrow::Borrow;
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+
+use crate::errors::{GeminiError, GeminiResult};
+use crate::iterable::Iterable;
+use crate::kzg::{Commitment, CommitterKey, CommitterKeyStream};
+use crate::misc::ceil_div;
+
+/// The largest window supported, in bits.
+const MAX_WINDOW: usize = 32;
+
+/// Panic if `window` is not supported.
+fn check_window(window: usize) {
+    assert!(
+        (1..=MAX_WINDOW).contains(&window),
+        "windows must have between 1 and {} bits",
+        MAX_WINDOW
+    );
+}
+
+/// The number of windows of `window` bits in the scalars of `G`.
+fn num_windows<G: CurveGroup>(window: usize) -> usize {
+    ceil_div(G::ScalarField::MODULUS_BIT_SIZE as usize, window)
+}
+
+/// Return the points $2^{cj} P$ for $j < $ `num_windows`, where $c$ is `window` and $P$ is `base`.
+fn table_row<G: CurveGroup>(base: &G::Affine, window: usize, num_windows: usize) -> Vec<G::Affine> {
+    let mut point = base.into_group();
+    let row = (0..num_windows)
+        .map(|_| {
+            let current = point;
+            (0..window).for_each(|_| {
+                point.double_in_place();
+            });
+            current
+        })
+        .collect::<Vec<_>>();
+    G::normalize_batch(&row)
+}
+
+/// Return the digit of `window` bits of `limbs` (in little-endian order) starting at bit `offset`.
+pub(super) fn digit(limbs: &[u64], offset: usize, window: usize) -> usize {
+    let (limb, shift) = (offset / 64, offset % 64);
+    let mut digit = limbs.get(limb).map_or(0, |l| l >> shift);
+    if shift + window > 64 {
+        digit |= limbs.get(limb + 1).map_or(0, |l| l << (64 - shift));
+    }
+    (digit & ((1 << window) - 1)) as usize
+}
+
+/// The buckets of a multi-scalar multiplication over a fixed-base table.
+struct Buckets<G: CurveGroup> {
+    window: usize,
+    buckets: Vec<G>,
+}
+
+impl<G: CurveGroup> Buckets<G> {
+    fn new(window: usize) -> Self {
+        Self {
+            window,
+            buckets: vec![G::zero(); (1 << window) - 1],
+        }
+    }
+
+    /// Add the multiplication of the base with table row `row` by `scalar`.
+    fn add<I>(&mut self, row: I, scalar: &G::ScalarField)
+    where
+        I: Iterator,
+        I::Item: Borrow<G::Affine>,
+    {
+        let scalar = scalar.into_bigint();
+        for (j, point) in row.enumerate() {
+            let digit = digit(scalar.as_ref(), j * self.window, self.window);
+            if digit != 0 {
+                self.buckets[digit - 1] += *point.borrow();
+            }
+        }
+    }
+
+    /// Return the sum of the buckets, each multiplied by its digit.
+    fn finalize(self) -> G {
+        let mut running = G::zero();
+        let mut sum = G::zero();
+        for bucket in self.buckets.into_iter().rev() {
+            running += bucket;
+            sum += running;
+        }
+        sum
+    }
+}
+
+/// The fixed-base table of a sequence of bases, held in memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FixedBaseTable<G: CurveGroup> {
+    window: usize,
+    table: Vec<G::Affine>,
+}
+
+impl<G: CurveGroup> FixedBaseTable<G> {
+    /// Precompute the table of `bases`, for windows of `window` bits.
+    ///
+    /// # Panics
+    /// If `window` is not between 1 and 32.
+    pub fn new(bases: &[G::Affine], window: usize) -> Self {
+        check_window(window);
+        let num_windows = num_windows::<G>(window);
+        let table = bases
+            .iter()
+            .flat_map(|base| table_row::<G>(base, window, num_windows))
+            .collect();
+        Self { window, table }
+    }
+
+    /// The size of the windows, in bits.
+    pub fn window(&self) -> usize {
+        self.window
+    }
+
+    /// The number of bases in the table.
+    pub fn len(&self) -> usize {
+        self.table.len() / num_windows::<G>(self.window)
+    }
+
+    /// Return `true` if the table has no bases.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// Return the linear combination of the first `scalars.len()` bases with `scalars`.
+    ///
+    /// # Panics
+    /// If there are fewer bases than scalars.
+    pub fn msm(&self, scalars: &[G::ScalarField]) -> G {
+        assert!(self.len() >= scalars.len(), "not enough bases");
+        let mut buckets = Buckets::<G>::new(self.window);
+        let rows = self.table.chunks(num_windows::<G>(self.window));
+        for (row, scalar) in rows.zip(scalars) {
+            buckets.add(row.iter(), scalar);
+        }
+        buckets.finalize()
+    }
+}
+
+/// The fixed-base table of a stream of bases, streamed from `table`.
+///
+/// The table lists, in the order of the bases, the points of the windows of each base,
+/// as written by [`CommitterKeyStream::write_fixed_base_table`].
+#[derive(Clone, Copy)]
+pub struct FixedBaseStream<G, ST> {
+    table: ST,
+    window: usize,
+    _group: PhantomData<G>,
+}
+
+impl<G, ST> FixedBaseStream<G, ST>
+where
+    G: CurveGroup,
+    ST: Iterable,
+    ST::Item: Borrow<G::Affine>,
+{
+    /// Read the table for windows of `window` bits from the stream `table`.
+    ///
+    /// # Panics
+    /// If `window` is not between 1 and 32, or if the length of `table` is not a multiple of the number of windows.
+    pub fn new(table: ST, window: usize) -> Self {
+        check_window(window);
+        assert_eq!(
+            table.len() % num_windows::<G>(window),
+            0,
+            "the table is not a sequence of windows"
+        );
+        Self {
+            table,
+            window,
+            _group: PhantomData,
+        }
+    }
+
+    /// The number of bases in the table.
+    pub fn len(&self) -> usize {
+        self.table.len() / num_windows::<G>(self.window)
+    }
+
+    /// Return `true` if the table has no bases.
+    pub fn is_empty(&self) -> bool {
+        self.table.len() == 0
+    }
+
+    /// Return the linear combination of the last `scalars.len()` bases with the stream `scalars`,
+    /// with a single pass over the table.
+    ///
+    /// # Panics
+    /// If there are fewer bases than scalars.
+    pub fn msm<SF: ?Sized>(&self, scalars: &SF) -> G
+    where
+        SF: Iterable,
+        SF::Item: Borrow<G::ScalarField>,
+    {
+        assert!(self.len() >= scalars.len(), "not enough bases");
+        let num_windows = num_windows::<G>(self.window);
+        let mut points = self.table.iter();
+        // align the streams
+        points
+            .advance_by((self.len() - scalars.len()) * num_windows)
+            .expect("bases not long enough");
+        let mut buckets = Buckets::<G>::new(self.window);
+        for scalar in scalars.iter() {
+            buckets.add((&mut points).take(num_windows), scalar.borrow());
+        }
+        buckets.finalize()
+    }
+}
+
+impl<E: Pairing> CommitterKey<E> {
+    /// Precompute the fixed-base table of the key, for windows of `window` bits.
+    ///
+    /// # Panics
+    /// If `window` is not between 1 and 32.
+    pub fn fixed_base_table(&self, window: usize) -> FixedBaseTable<E::G1> {
+        FixedBaseTable::new(&self.powers_of_g, window)
+    }
+
+    /// Same as [`CommitterKey::commit`],
+    /// but computing the multi-scalar multiplication over the fixed-base table `table` of the key.
+    ///
+    /// # Panics
+    /// If `table` is not as long as the key.
+    pub fn commit_precomputed(
+        &self,
+        polynomial: &[E::ScalarField],
+        table: &FixedBaseTable<E::G1>,
+    ) -> Commitment<E> {
+        assert_eq!(table.len(), self.powers_of_g.len(), "table of another key");
+        trace_span!("commit", len = polynomial.len());
+        Commitment(table.msm(polynomial))
+    }
+}
+
+impl<E, SG> CommitterKeyStream<E, SG>
+where
+    E: Pairing,
+    SG: Iterable,
+    SG::Item: Borrow<E::G1Affine>,
+{
+    /// Write the fixed-base table of the key for windows of `window` bits into `writer`,
+    /// one base at a time, in stream order, with the points serialized uncompressed.
+    ///
+    /// # Panics
+    /// If `window` is not between 1 and 32.
+    pub fn write_fixed_base_table<W: Write>(
+        &self,
+        window: usize,
+        mut writer: W,
+    ) -> Result<(), SerializationError> {
+        check_window(window);
+        let num_windows = num_windows::<E::G1>(window);
+        for base in self.powers_of_g.iter() {
+            for point in table_row::<E::G1>(base.borrow(), window, num_windows) {
+                point.serialize_uncompressed(&mut writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Same as [`CommitterKeyStream::commit`],
+    /// but computing the multi-scalar multiplication over the fixed-base table `table` of the key.
+    ///
+    /// Fails if `table` is not as long as the key, or if the key is shorter than the polynomial.
+    pub fn commit_precomputed<SF: ?Sized, ST>(
+        &self,
+        polynomial: &SF,
+        table: &FixedBaseStream<E::G1, ST>,
+    ) -> GeminiResult<Commitment<E>>
+    where
+        SF: Iterable,
+        SF::Item: Borrow<E::ScalarField>,
+        ST: Iterable,
+        ST::Item: Borrow<E::G1Affine>,
+    {
+        GeminiError::ensure_len("table", self.powers_of_g.len(), table.len())?;
+        GeminiError::ensure_key(polynomial.len(), self.powers_of_g.len())?;
+        trace_span!("commit", len = polynomial.len());
+        Ok(Commitment(table.msm(polynomial)))
+    }
+}
@@ -0,0 +1,115 @@
+//! Verifier keys prepared once for many verifications.
+//!
+//! Each pairing check $e(L, G_2) = e(\pi, Z)$ has the generator $G_2$ as fixed argument.
+//! A [`PreparedVerifierKey`] precomputes the lines of the Miller loop for $G_2$ once,
+//! and checks $e(L, G_2) \cdot e(-\pi, Z) = 1$ with a single final exponentiation.
+//! Verifiers serving many proofs against the same key should prepare it once, and reuse it.
+use ark_ec::pairing::Pairing;
+use ark_ec::VariableBaseMSM;
+use ark_ff::{One, Zero};
+use ark_std::vec::Vec;
+
+use crate::errors::{VerificationError, VerificationResult};
+use crate::kzg::{Commitment, EvaluationProof, PairingCheck, VerifierKey};
+use crate::misc::powers;
+
+/// A [`VerifierKey`], together with the Miller loop lines of the generator of $\GG_2$.
+#[derive(Clone, Debug)]
+pub struct PreparedVerifierKey<E: Pairing> {
+    vk: VerifierKey<E>,
+    prepared_g2: E::G2Prepared,
+}
+
+impl<E: Pairing> From<&VerifierKey<E>> for PreparedVerifierKey<E> {
+    fn from(vk: &VerifierKey<E>) -> Self {
+        PreparedVerifierKey {
+            vk: vk.clone(),
+            prepared_g2: vk.powers_of_g2[0].into(),
+        }
+    }
+}
+
+impl<E: Pairing> PreparedVerifierKey<E> {
+    /// The verifier key prepared.
+    pub fn verifier_key(&self) -> &VerifierKey<E> {
+        &self.vk
+    }
+
+    /// Return `true` if $e(L, G_2) = \prod_i e(\pi_i, Z_i)$.
+    fn holds(&self, lhs: E::G1, proofs: &[E::G1], zeros: &[E::G2]) -> bool {
+        let g1 = ark_std::iter::once(lhs).chain(proofs.iter().map(|proof| -*proof));
+        let g2 = ark_std::iter::once(self.prepared_g2.clone())
+            .chain(zeros.iter().map(|zeros| E::G2Prepared::from(*zeros)));
+        E::multi_pairing(g1, g2).is_zero()
+    }
+
+    /// Same as [`VerifierKey::verify`].
+    pub fn verify(
+        &self,
+        commitment: &Commitment<E>,
+        &alpha: &E::ScalarField,
+        evaluation: &E::ScalarField,
+        proof: &EvaluationProof<E>,
+    ) -> VerificationResult {
+        let scalars = [-alpha, E::ScalarField::one()];
+        let ep = E::G2::msm(&self.vk.powers_of_g2, &scalars);
+        let lhs = commitment.0 - self.vk.powers_of_g[0] * evaluation;
+        if self.holds(lhs, &[proof.0], &[ep]) {
+            Ok(())
+        } else {
+            Err(VerificationError::opening())
+        }
+    }
+
+    /// Same as [`VerifierKey::verify_multi_points`].
+    pub fn verify_multi_points(
+        &self,
+        commitments: &[Commitment<E>],
+        eval_points: &[E::ScalarField],
+        evaluations: &[Vec<E::ScalarField>],
+        proof: &EvaluationProof<E>,
+        open_chal: &E::ScalarField,
+    ) -> VerificationResult {
+        let check =
+            self.vk
+                .multi_points_check(commitments, eval_points, evaluations, proof, open_chal);
+        self.check(&check)
+    }
+
+    /// Same as [`VerifierKey::check`].
+    pub fn check(&self, check: &PairingCheck<E>) -> VerificationResult {
+        if self.holds(check.lhs, &[check.proof], &[check.zeros]) {
+            Ok(())
+        } else {
+            Err(VerificationError::opening())
+        }
+    }
+
+    /// Same as [`VerifierKey::batch_check`].
+    pub fn batch_check(
+        &self,
+        checks: &[PairingCheck<E>],
+        randomness: E::ScalarField,
+    ) -> VerificationResult {
+        let coefficients = powers(randomness, checks.len());
+        let lhs = checks
+            .iter()
+            .zip(&coefficients)
+            .map(|(check, coefficient)| check.lhs * coefficient)
+            .sum::<E::G1>();
+        let proofs = checks
+            .iter()
+            .zip(&coefficients)
+            .map(|(check, coefficient)| check.proof * coefficient)
+            .collect::<Vec<_>>();
+        let zeros = checks.iter().map(|check| check.zeros).collect::<Vec<_>>();
+
+        if self.holds(lhs, &proofs, &zeros) {
+            Ok(())
+        } else {
+            Err(VerificationError::Opening {
+                opening: "batched pairing check",
+            })
+        }
+    }
+}
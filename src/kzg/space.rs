@@ -1,26 +1,47 @@
 //! Space-efficient implementation of the polynomial commitment of Kate et al.
 use ark_ec::pairing::Pairing;
-use ark_ec::scalar_mul::variable_base::{ChunkedPippenger, HashMapPippenger};
+use ark_ec::scalar_mul::variable_base::HashMapPippenger;
 use ark_ec::CurveGroup;
 use ark_ec::VariableBaseMSM;
-use ark_ff::{PrimeField, Zero};
+use ark_ff::{One, PrimeField, Zero};
 use ark_poly::Polynomial;
 use ark_std::borrow::Borrow;
 use ark_std::collections::VecDeque;
 use ark_std::vec::Vec;
-
-use crate::iterable::{Iterable, Reverse};
+#[cfg(feature = "parallel")]
+use rayon::iter::{ParallelBridge, ParallelIterator};
+
+use crate::config::DEFAULT_CHUNK_SIZE;
+use crate::errors::{GeminiError, GeminiResult};
+#[cfg(feature = "parallel")]
+use crate::iterable::Chunks;
+use crate::iterable::{DoubleEndedIterable, Iterable, RandomAccessIterable, Reverse, Slice};
 use crate::kzg::vanishing_polynomial;
-use crate::misc::ceil_div;
+use crate::misc::{ceil_div, pipeline, Producer};
 use crate::subprotocols::sumcheck::streams::FoldedPolynomialTree;
 
+use super::backend::{CpuBackend, MsmBackend};
+use super::pippenger::StreamPippenger;
 use super::{time::CommitterKey, VerifierKey};
 use super::{Commitment, EvaluationProof};
 
-const LENGTH_MISMATCH_MSG: &str = "Expecting at least one element in the committer key.";
+/// The failure of a stream yielding fewer elements than its [`Iterable::len`].
+fn short_stream(name: &'static str, expected: usize, found: usize) -> GeminiError {
+    GeminiError::LengthMismatch {
+        name,
+        expected,
+        found,
+    }
+}
 
-/// Steaming multi-scalar multiplication algorithm with hard-coded chunk size.
-pub fn msm_chunks<G, F, I: ?Sized, J>(bases_stream: &J, scalars_stream: &I) -> G
+/// Steaming multi-scalar multiplication algorithm, collecting `step` elements at once.
+///
+/// Fails if there are fewer bases than scalars.
+pub fn msm_chunks<G, F, I: ?Sized, J>(
+    bases_stream: &J,
+    scalars_stream: &I,
+    step: usize,
+) -> GeminiResult<G>
 where
     G: CurveGroup<ScalarField = F>,
     I: Iterable,
@@ -29,30 +50,81 @@ where
     J: Iterable,
     J::Item: Borrow<G::Affine>,
 {
-    assert!(scalars_stream.len() <= bases_stream.len());
+    msm_chunks_with(bases_stream, scalars_stream, step, &CpuBackend)
+}
 
-    // remove offset
-    let mut bases = bases_stream.iter();
-    let mut scalars = scalars_stream.iter();
+/// Same as [`msm_chunks`], computing the multi-scalar multiplication of each chunk with `backend`.
+pub fn msm_chunks_with<G, F, I: ?Sized, J, B>(
+    bases_stream: &J,
+    scalars_stream: &I,
+    step: usize,
+    backend: &B,
+) -> GeminiResult<G>
+where
+    G: CurveGroup<ScalarField = F>,
+    I: Iterable,
+    F: PrimeField,
+    I::Item: Borrow<F>,
+    J: Iterable,
+    J::Item: Borrow<G::Affine>,
+    B: MsmBackend<G>,
+{
+    GeminiError::ensure_key(scalars_stream.len(), bases_stream.len())?;
 
-    // align the streams
-    bases
-        .advance_by(bases_stream.len() - scalars_stream.len())
-        .expect("bases not long enough");
-    let step: usize = 1 << 20;
+    // read the next chunk while computing the multi-scalar multiplication of the current one
     let mut result = G::zero();
-    for _ in 0..(scalars_stream.len() + step - 1) / step {
-        let bases_step = (&mut bases)
-            .take(step)
-            .map(|b| *b.borrow())
-            .collect::<Vec<_>>();
-        let scalars_step = (&mut scalars)
-            .take(step)
-            .map(|s| *s.borrow())
-            .collect::<Vec<_>>();
-        result += G::msm(bases_step.as_slice(), scalars_step.as_slice());
+    let mut units = G::zero();
+    // the number of bases actually read, if the stream of bases ends early.
+    let mut short = None;
+    pipeline(
+        |producer: &mut Producer<'_, (Vec<G::Affine>, Vec<F>)>| {
+            // remove offset
+            let mut bases = bases_stream.iter();
+            let mut scalars = scalars_stream.iter();
+
+            // align the streams
+            let offset = bases_stream.len() - scalars_stream.len();
+            if let Err(advanced) = bases.advance_by(offset) {
+                short = Some(advanced);
+                return;
+            }
+            for chunk in 0..(scalars_stream.len() + step - 1) / step {
+                // refill the chunks already consumed, if any
+                let (mut bases_step, mut scalars_step) = producer.chunk();
+                bases_step.clear();
+                scalars_step.clear();
+                // skip zero scalars, and sum the bases of unit scalars apart
+                for (i, scalar) in (&mut scalars).take(step).enumerate() {
+                    let base = match bases.next() {
+                        Some(base) => base,
+                        None => {
+                            short = Some(offset + chunk * step + i);
+                            return;
+                        }
+                    };
+                    let scalar = *scalar.borrow();
+                    if scalar.is_zero() {
+                        continue;
+                    } else if scalar.is_one() {
+                        units += *base.borrow();
+                    } else {
+                        bases_step.push(*base.borrow());
+                        scalars_step.push(scalar);
+                    }
+                }
+                if !producer.send((bases_step, scalars_step)) {
+                    return;
+                }
+            }
+        },
+        |(bases_step, scalars_step)| {
+            result += backend.msm(bases_step.as_slice(), scalars_step.as_slice());
+        },
+    );
+    match short {
+        Some(found) => Err(short_stream("bases", bases_stream.len(), found)),
+        None => Ok(result + units),
     }
-    result
 }
 
 /// The streaming SRS for the polynomial commitment scheme consists of the stream of consecutive powers of $G$.
@@ -72,83 +144,134 @@ where
 impl<E, SG> CommitterKeyStream<E, SG>
 where
     E: Pairing,
-    SG: Iterable,
+    SG: DoubleEndedIterable,
     SG::Item: Borrow<E::G1Affine>,
 {
-    /// Turn a streaming SRS into a normal SRS.
+    /// Turn a streaming SRS into a normal SRS,
+    /// holding the first `max_degree` powers of \\(\tau\\).
+    ///
+    /// The stream is walked backwards, hence the powers are read in order.
     pub fn as_committer_key(&self, max_degree: usize) -> CommitterKey<E> {
-        let offset = self.powers_of_g.len() - max_degree;
-        let mut powers_of_g = self
+        let powers_of_g = self
             .powers_of_g
-            .iter()
-            .skip(offset)
+            .rev_iter()
+            .take(max_degree)
             .map(|x| *x.borrow())
             .collect::<Vec<_>>();
-        powers_of_g.reverse();
         let powers_of_g2 = self.powers_of_g2.clone().to_vec();
         CommitterKey {
             powers_of_g,
             powers_of_g2,
         }
     }
+}
+
+impl<E, SG> CommitterKeyStream<E, SG>
+where
+    E: Pairing,
+    SG: Iterable,
+    SG::Item: Borrow<E::G1Affine>,
+{
+    /// Return a view over the (at most) `len` powers of the stream from position `offset`
+    /// (in stream order, i.e., from the highest power),
+    /// positioned without reading the powers before it.
+    pub fn slice(&self, offset: usize, len: usize) -> Slice<'_, SG>
+    where
+        SG: RandomAccessIterable,
+    {
+        self.powers_of_g.slice(offset, len)
+    }
 
     /// Evaluate a single polynomial at the point `alpha`, and provide an evaluation proof along with the evaluation.
+    ///
+    /// Fails if the key is shorter than the polynomial.
     pub fn open<SF>(
         &self,
         polynomial: &SF,
         alpha: &E::ScalarField,
         max_msm_buffer: usize,
-    ) -> (E::ScalarField, EvaluationProof<E>)
+    ) -> GeminiResult<(E::ScalarField, EvaluationProof<E>)>
     where
+        SG: RandomAccessIterable,
         SF: Iterable,
         SF::Item: Borrow<E::ScalarField>,
     {
-        let mut quotient = ChunkedPippenger::<E::G1>::new(max_msm_buffer);
-
-        let mut bases = self.powers_of_g.iter();
-        let scalars = polynomial.iter();
+        trace_span!("open", len = polynomial.len());
+        let mut quotient = StreamPippenger::<E::G1>::new(polynomial.len(), max_msm_buffer);
+        let chunk_size = usize::max(usize::min(polynomial.len(), max_msm_buffer), 1);
 
         // align the streams and remove one degree
-        bases
-            .advance_by(self.powers_of_g.len() - polynomial.len())
-            .expect(LENGTH_MISMATCH_MSG);
-
+        GeminiError::ensure_key(polynomial.len(), self.powers_of_g.len())?;
         let mut previous = E::ScalarField::zero();
-        for (scalar, base) in scalars.zip(bases) {
-            quotient.add(base, previous.into_bigint());
-            let coefficient = previous * alpha + scalar.borrow();
-            previous = coefficient;
-        }
+        // read the next chunk of the streams while adding the current one into the quotient
+        pipeline(
+            |producer: &mut Producer<'_, Vec<(E::ScalarField, E::G1Affine)>>| {
+                let bases = self
+                    .slice(self.powers_of_g.len() - polynomial.len(), polynomial.len())
+                    .iter();
+                let mut pairs = polynomial
+                    .iter()
+                    .zip(bases)
+                    .map(|(scalar, base)| (*scalar.borrow(), *base.borrow()));
+                loop {
+                    let mut chunk = producer.chunk();
+                    chunk.clear();
+                    chunk.extend((&mut pairs).take(chunk_size));
+                    if chunk.is_empty() || !producer.send(chunk) {
+                        return;
+                    }
+                }
+            },
+            |chunk| {
+                for (scalar, base) in chunk.iter() {
+                    quotient.add(base, previous.into_bigint());
+                    let coefficient = previous * alpha + scalar;
+                    previous = coefficient;
+                }
+            },
+        );
 
         let evaluation = previous;
         let evaluation_proof = quotient.finalize();
-        (evaluation, EvaluationProof(evaluation_proof))
+        Ok((evaluation, EvaluationProof(evaluation_proof)))
     }
 
     /// Evaluate a single polynomial at a set of points `points`, and provide an evaluation proof along with evaluations.
+    ///
+    /// Fails if the key is shorter than the quotient.
     pub fn open_multi_points<SF>(
         &self,
         polynomial: &SF,
         points: &[E::ScalarField],
         max_msm_buffer: usize,
-    ) -> (Vec<E::ScalarField>, EvaluationProof<E>)
+    ) -> GeminiResult<(Vec<E::ScalarField>, EvaluationProof<E>)>
     where
+        SG: RandomAccessIterable,
         SF: Iterable,
         SF::Item: Borrow<E::ScalarField>,
     {
+        trace_span!("open", len = polynomial.len(), points = points.len());
         let zeros = vanishing_polynomial(points);
-        let mut quotient = ChunkedPippenger::<E::G1>::new(max_msm_buffer);
-        let mut bases = self.powers_of_g.iter();
-        bases
-            .advance_by(self.powers_of_g.len() - polynomial.len() + zeros.degree())
-            .unwrap();
+        let quotient_len = polynomial.len().saturating_sub(points.len());
+        GeminiError::ensure_key(quotient_len, self.powers_of_g.len())?;
+        let mut quotient = StreamPippenger::<E::G1>::new(quotient_len, max_msm_buffer);
+        let mut bases = self
+            .powers_of_g
+            .iter_from(self.powers_of_g.len() - quotient_len);
 
         let mut state = VecDeque::<E::ScalarField>::with_capacity(points.len());
 
         let mut polynomial_iterator = polynomial.iter();
 
-        (0..points.len()).for_each(|_| {
-            state.push_back(*polynomial_iterator.next().unwrap().borrow());
+        // a polynomial shorter than the points is its own remainder:
+        // pad it with leading zeros.
+        let padding = points.len().saturating_sub(polynomial.len());
+        state.extend((0..padding).map(|_| E::ScalarField::zero()));
+        (padding..points.len()).for_each(|_| {
+            let coefficient = polynomial_iterator
+                .next()
+                .expect("Iterable::len does not match the length of the polynomial.");
+            state.push_back(*coefficient.borrow());
         });
 
         for coefficient in polynomial_iterator {
@@ -163,24 +286,153 @@ where
         }
         let remainder = state.make_contiguous().to_vec();
         let commitment = EvaluationProof(quotient.finalize());
-        (remainder, commitment)
+        Ok((remainder, commitment))
     }
 
     /// The commitment procedures, that takes as input a committer key and the streaming coefficients of polynomial, and produces the desired commitment.
-    pub fn commit<SF: ?Sized>(&self, polynomial: &SF) -> Commitment<E>
+    ///
+    /// Fails if the key is shorter than the polynomial.
+    pub fn commit<SF: ?Sized>(&self, polynomial: &SF) -> GeminiResult<Commitment<E>>
+    where
+        SF: Iterable,
+        SF::Item: Borrow<E::ScalarField>,
+    {
+        self.commit_chunked(polynomial, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Same as [`CommitterKeyStream::commit`],
+    /// but collecting `chunk_size` coefficients at once.
+    pub fn commit_chunked<SF: ?Sized>(
+        &self,
+        polynomial: &SF,
+        chunk_size: usize,
+    ) -> GeminiResult<Commitment<E>>
+    where
+        SF: Iterable,
+        SF::Item: Borrow<E::ScalarField>,
+    {
+        trace_span!("commit", len = polynomial.len());
+
+        msm_chunks(&self.powers_of_g, polynomial, chunk_size).map(Commitment)
+    }
+
+    /// Same as [`CommitterKeyStream::commit_chunked`],
+    /// but computing the multi-scalar multiplication of each chunk with `backend`.
+    pub fn commit_with<SF: ?Sized, B>(
+        &self,
+        polynomial: &SF,
+        chunk_size: usize,
+        backend: &B,
+    ) -> GeminiResult<Commitment<E>>
+    where
+        SF: Iterable,
+        SF::Item: Borrow<E::ScalarField>,
+        B: MsmBackend<E::G1>,
+    {
+        trace_span!("commit", len = polynomial.len());
+
+        msm_chunks_with(&self.powers_of_g, polynomial, chunk_size, backend).map(Commitment)
+    }
+
+    /// Commit to the `N` polynomials streamed jointly by `polynomials`,
+    /// whose elements are the coefficients of each polynomial at the same position,
+    /// collecting `chunk_size` coefficients at once.
+    ///
+    /// The polynomials and the key are read with a single pass,
+    /// instead of one pass per polynomial as with [`CommitterKeyStream::commit_chunked`].
+    pub fn commit_joint<SF: ?Sized, const N: usize>(
+        &self,
+        polynomials: &SF,
+        chunk_size: usize,
+    ) -> GeminiResult<[Commitment<E>; N]>
+    where
+        SF: Iterable,
+        SF::Item: Borrow<[E::ScalarField; N]>,
+    {
+        GeminiError::ensure_key(polynomials.len(), self.powers_of_g.len())?;
+        trace_span!("commit_joint", len = polynomials.len());
+
+        let mut bases = self.powers_of_g.iter();
+        let offset = self.powers_of_g.len() - polynomials.len();
+        bases
+            .advance_by(offset)
+            .map_err(|found| short_stream("ck.powers_of_g", self.powers_of_g.len(), found))?;
+        let mut coefficients = polynomials.iter();
+        let mut commitments = [E::G1::zero(); N];
+        // the chunks are refilled at each step.
+        let capacity = usize::min(chunk_size, polynomials.len());
+        let mut bases_step = Vec::with_capacity(capacity);
+        let mut scalars_step = vec![Vec::with_capacity(capacity); N];
+        for chunk in 0..ceil_div(polynomials.len(), chunk_size) {
+            bases_step.clear();
+            bases_step.extend((&mut bases).take(chunk_size).map(|b| *b.borrow()));
+            let expected = usize::min(chunk_size, polynomials.len() - chunk * chunk_size);
+            if bases_step.len() < expected {
+                let found = offset + chunk * chunk_size + bases_step.len();
+                return Err(short_stream(
+                    "ck.powers_of_g",
+                    self.powers_of_g.len(),
+                    found,
+                ));
+            }
+            scalars_step.iter_mut().for_each(Vec::clear);
+            (&mut coefficients).take(chunk_size).for_each(|c| {
+                scalars_step
+                    .iter_mut()
+                    .zip(c.borrow())
+                    .for_each(|(scalars, c)| scalars.push(*c))
+            });
+            commitments
+                .iter_mut()
+                .zip(&scalars_step)
+                .for_each(|(commitment, scalars)| {
+                    *commitment += MsmBackend::<E::G1>::msm(&CpuBackend, &bases_step, scalars)
+                });
+        }
+        Ok(commitments.map(Commitment))
+    }
+
+    /// Same as [`CommitterKeyStream::commit_chunked`],
+    /// but computing the multi-scalar multiplication of each chunk in parallel.
+    ///
+    /// The key and the polynomial are still read sequentially,
+    /// and at most one chunk per thread is held in memory.
+    #[cfg(feature = "parallel")]
+    pub fn par_commit<SF: ?Sized>(
+        &self,
+        polynomial: &SF,
+        chunk_size: usize,
+    ) -> GeminiResult<Commitment<E>>
     where
         SF: Iterable,
         SF::Item: Borrow<E::ScalarField>,
+        SF::Iter: Send,
+        SG::Iter: Send,
     {
-        assert!(self.powers_of_g.len() >= polynomial.len());
+        GeminiError::ensure_key(polynomial.len(), self.powers_of_g.len())?;
+        trace_span!("commit", len = polynomial.len());
 
-        Commitment(msm_chunks(&self.powers_of_g, polynomial))
+        let mut bases = self.powers_of_g.iter();
+        bases
+            .advance_by(self.powers_of_g.len() - polynomial.len())
+            .map_err(|found| short_stream("ck.powers_of_g", self.powers_of_g.len(), found))?;
+        let pairs = bases
+            .zip(polynomial.iter())
+            .map(|(base, scalar)| (*base.borrow(), *scalar.borrow()));
+        let commitment = Chunks::new(pairs, chunk_size)
+            .par_bridge()
+            .map(|(_, chunk)| {
+                let (bases, scalars): (Vec<_>, Vec<_>) = chunk.into_iter().unzip();
+                E::G1::msm(&bases, &scalars)
+            })
+            .reduce(E::G1::zero, |x, y| x + y);
+        Ok(Commitment(commitment))
     }
 
     pub fn batch_commit<'a, F>(
         &self,
         polynomials: &[&'a dyn Iterable<Item = F, Iter = &mut dyn Iterator<Item = F>>],
-    ) -> Vec<Commitment<E>>
+    ) -> GeminiResult<Vec<Commitment<E>>>
     where
         F: Borrow<E::ScalarField>,
     {
@@ -194,20 +446,23 @@ where
         &self,
         polynomials: &FoldedPolynomialTree<'_, E::ScalarField, SF>,
         max_msm_buffer: usize,
-    ) -> Vec<Commitment<E>>
+    ) -> GeminiResult<Vec<Commitment<E>>>
     where
+        SG: RandomAccessIterable,
         SF: Iterable,
         SF::Item: Borrow<E::ScalarField>,
     {
         let n = polynomials.depth();
-        let mut pippengers: Vec<ChunkedPippenger<E::G1>> = Vec::new();
+        trace_span!("commit_folding", len = polynomials.len(), depth = n);
+        let mut pippengers: Vec<StreamPippenger<E::G1>> = Vec::new();
         let mut folded_bases = Vec::new();
         for i in 1..n + 1 {
-            let pippenger = ChunkedPippenger::with_size(max_msm_buffer / n);
-            let mut bases = self.powers_of_g.iter();
-
-            let delta = self.powers_of_g.len() - ceil_div(polynomials.len(), 1 << i);
-            bases.advance_by(delta).expect(LENGTH_MISMATCH_MSG);
+            let folded_len = ceil_div(polynomials.len(), 1 << i);
+            let pippenger = StreamPippenger::new(folded_len, max_msm_buffer / n);
+            GeminiError::ensure_key(folded_len, self.powers_of_g.len())?;
+            let bases = self
+                .slice(self.powers_of_g.len() - folded_len, folded_len)
+                .iter();
             folded_bases.push(bases);
             pippengers.push(pippenger);
         }
@@ -217,10 +472,10 @@ where
             pippengers[i - 1].add(base, coefficient.into_bigint());
         }
 
-        pippengers
+        Ok(pippengers
             .into_iter()
             .map(|p| Commitment(p.finalize()))
-            .collect::<Vec<_>>()
+            .collect::<Vec<_>>())
     }
 
     /// The commitment procedures for our tensor check protocol.
@@ -233,24 +488,27 @@ where
         points: &[E::ScalarField],
         etas: &[E::ScalarField],
         max_msm_buffer: usize,
-    ) -> (Vec<Vec<E::ScalarField>>, EvaluationProof<E>)
+    ) -> GeminiResult<(Vec<Vec<E::ScalarField>>, EvaluationProof<E>)>
     where
-        SG: Iterable,
+        SG: RandomAccessIterable,
         SF: Iterable,
         E: Pairing,
         SG::Item: Borrow<E::G1Affine>,
         SF::Item: Borrow<E::ScalarField> + Copy,
     {
         let n = polynomials.depth();
+        trace_span!("open_folding", len = polynomials.len(), depth = n);
         let mut pippenger = HashMapPippenger::<E::G1>::new(max_msm_buffer);
         let mut folded_bases = Vec::new();
         let zeros = vanishing_polynomial(points);
         let mut remainders = vec![VecDeque::new(); n];
 
         for i in 1..n + 1 {
-            let mut bases = self.powers_of_g.iter();
-            let delta = self.powers_of_g.len() - ceil_div(polynomials.len(), 1 << i);
-            bases.advance_by(delta).expect(LENGTH_MISMATCH_MSG);
+            let folded_len = ceil_div(polynomials.len(), 1 << i);
+            GeminiError::ensure_key(folded_len, self.powers_of_g.len())?;
+            let bases = self
+                .slice(self.powers_of_g.len() - folded_len, folded_len)
+                .iter();
 
             (0..points.len()).for_each(|_| {
                 remainders[i - 1].push_back(E::ScalarField::zero());
@@ -282,7 +540,7 @@ where
             .map(|x| x.make_contiguous().to_vec())
             .collect::<Vec<_>>();
 
-        (remainders, EvaluationProof(evaluation_proof))
+        Ok((remainders, EvaluationProof(evaluation_proof)))
     }
 }
 
@@ -297,25 +555,31 @@ impl<'a, E: Pairing> From<&'a CommitterKey<E>>
     }
 }
 
-impl<E, SG> From<&CommitterKeyStream<E, SG>> for VerifierKey<E>
+/// Fails if the committer key is empty.
+impl<E, SG> TryFrom<&CommitterKeyStream<E, SG>> for VerifierKey<E>
 where
     E: Pairing,
     SG: Iterable,
     SG::Item: Borrow<E::G1Affine>,
 {
-    fn from(ck: &CommitterKeyStream<E, SG>) -> Self {
+    type Error = GeminiError;
+
+    fn try_from(ck: &CommitterKeyStream<E, SG>) -> GeminiResult<Self> {
         let powers_of_g2 = ck.powers_of_g2.to_vec();
         // take the first element from the stream
         let g = *ck
             .powers_of_g
             .iter()
             .last()
-            .expect(LENGTH_MISMATCH_MSG)
+            .ok_or(GeminiError::CommitterKeyTooShort {
+                required: 1,
+                available: 0,
+            })?
             .borrow();
-        Self {
+        Ok(Self {
             powers_of_g2,
             powers_of_g: vec![g],
-        }
+        })
     }
 }
 
@@ -347,32 +611,40 @@ fn test_open_multi_points() {
     let time_ck = CommitterKey::<Bls12_381>::new(200, 3, rng);
     let space_ck = CommitterKeyStream::from(&time_ck);
 
-    let (remainder, _commitment) = space_ck.open_multi_points(
-        &polynomial_stream,
-        &[beta.square(), beta, -beta],
-        max_msm_buffer,
-    );
+    let (remainder, _commitment) = space_ck
+        .open_multi_points(
+            &polynomial_stream,
+            &[beta.square(), beta, -beta],
+            max_msm_buffer,
+        )
+        .unwrap();
     let evaluation_remainder = evaluate_be(&remainder, &beta);
     assert_eq!(evaluation_remainder, Fr::from(1807299544171u64));
 
-    let (remainder, _commitment) =
-        space_ck.open_multi_points(&polynomial_stream, &[beta], max_msm_buffer);
+    let (remainder, _commitment) = space_ck
+        .open_multi_points(&polynomial_stream, &[beta], max_msm_buffer)
+        .unwrap();
     assert_eq!(remainder.len(), 1);
 
     // get a random polynomial with random coefficient,
     let polynomial = DensePolynomial::rand(100, rng).coeffs().to_vec();
     let polynomial_stream = &polynomial[..];
     let beta = Fr::rand(rng);
-    let (_, evaluation_proof_batch) =
-        space_ck.open_multi_points(&polynomial_stream, &[beta], max_msm_buffer);
-    let (_, evaluation_proof_single) = space_ck.open(&polynomial_stream, &beta, max_msm_buffer);
+    let (_, evaluation_proof_batch) = space_ck
+        .open_multi_points(&polynomial_stream, &[beta], max_msm_buffer)
+        .unwrap();
+    let (_, evaluation_proof_single) = space_ck
+        .open(&polynomial_stream, &beta, max_msm_buffer)
+        .unwrap();
     assert_eq!(evaluation_proof_batch, evaluation_proof_single);
 
-    let (remainder, _evaluation_poof) = space_ck.open_multi_points(
-        &polynomial_stream,
-        &[beta, -beta, beta.square()],
-        max_msm_buffer,
-    );
+    let (remainder, _evaluation_poof) = space_ck
+        .open_multi_points(
+            &polynomial_stream,
+            &[beta, -beta, beta.square()],
+            max_msm_buffer,
+        )
+        .unwrap();
     let expected_evaluation = evaluate_be(&remainder, &beta);
     let obtained_evaluation = evaluate_be(&polynomial, &beta);
     assert_eq!(expected_evaluation, obtained_evaluation);
@@ -386,3 +658,35 @@ fn test_open_multi_points() {
     // let obtained_evaluation = evaluate_be(&polynomial, &beta.square());
     // assert_eq!(expected_evaluation, obtained_evaluation);
 }
+
+#[test]
+fn test_short_key() {
+    use crate::ark_std::UniformRand;
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+
+    let rng = &mut test_rng();
+    let time_ck = CommitterKey::<Bls12_381>::new(9, 3, rng);
+    let space_ck = CommitterKeyStream::from(&time_ck);
+    let polynomial = (0..11).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let polynomial_stream = &polynomial[..];
+    let too_short = GeminiError::CommitterKeyTooShort {
+        required: 11,
+        available: 10,
+    };
+
+    assert_eq!(space_ck.commit(&polynomial_stream).unwrap_err(), too_short);
+    let opening = space_ck.open(&polynomial_stream, &Fr::rand(rng), 1 << 10);
+    assert_eq!(opening.unwrap_err(), too_short);
+    // a polynomial shorter than the points is its own remainder.
+    let (remainder, _) = space_ck
+        .open_multi_points(&&polynomial[..2], &[Fr::rand(rng); 3], 1 << 10)
+        .unwrap();
+    assert_eq!(remainder, [&[Fr::zero()][..], &polynomial[..2]].concat());
+
+    let empty_ck = CommitterKeyStream::<Bls12_381, _> {
+        powers_of_g: Reverse(&time_ck.powers_of_g[..0]),
+        powers_of_g2: time_ck.powers_of_g2.clone(),
+    };
+    assert!(VerifierKey::try_from(&empty_ck).is_err());
+}
@@ -8,7 +8,7 @@ use ark_std::UniformRand;
 use crate::iterable::{Iterable, Reverse};
 use crate::kzg::space::CommitterKeyStream;
 use crate::kzg::time::CommitterKey;
-use crate::kzg::VerifierKey;
+use crate::kzg::{PreparedVerifierKey, VerifierKey};
 use crate::misc::evaluate_le;
 
 #[test]
@@ -22,11 +22,86 @@ fn test_commitment_consistency() {
 
     // compute the time commitment
     let time_commitment = time_ck.commit(&polynomial);
-    let space_commitment = space_ck.commit(&polynomial_stream);
+    let space_commitment = space_ck.commit(&polynomial_stream).unwrap();
 
     assert_eq!(space_commitment, time_commitment);
 }
 
+#[cfg(feature = "parallel")]
+#[test]
+fn test_par_commit() {
+    let rng = &mut ark_std::test_rng();
+    let d = 100;
+    let polynomial = DensePolynomial::<Fr>::rand(d, rng);
+    let polynomial_stream = Reverse(polynomial.coeffs());
+    let time_ck = CommitterKey::<Bls12_381>::new(d + 10, 3, rng);
+    let space_ck = CommitterKeyStream::from(&time_ck);
+
+    let commitment = time_ck.commit(&polynomial);
+    for chunk_size in [1, 7, 64, 1 << 10] {
+        assert_eq!(
+            space_ck.par_commit(&polynomial_stream, chunk_size).unwrap(),
+            commitment
+        );
+    }
+}
+
+#[test]
+fn test_sparse_commitment() {
+    use ark_ff::{One, Zero};
+
+    let rng = &mut ark_std::test_rng();
+    let d = 100;
+    // runs of zeros and ones, with few other coefficients.
+    let coefficients = (0..d)
+        .map(|i| match i % 10 {
+            0 => Fr::rand(rng),
+            1..=4 => Fr::one(),
+            _ => Fr::zero(),
+        })
+        .collect::<Vec<_>>();
+    let polynomial = DensePolynomial::from_coefficients_vec(coefficients);
+    let polynomial_stream = Reverse(polynomial.coeffs());
+    let time_ck = CommitterKey::<Bls12_381>::new(d + 10, 3, rng);
+    let space_ck = CommitterKeyStream::from(&time_ck);
+
+    let commitment = time_ck.commit(&polynomial);
+    for chunk_size in [1, 7, 1 << 10] {
+        assert_eq!(
+            space_ck
+                .commit_chunked(&polynomial_stream, chunk_size)
+                .unwrap(),
+            commitment
+        );
+    }
+}
+
+#[test]
+fn test_commit_joint() {
+    let rng = &mut ark_std::test_rng();
+    let d = 100;
+    let first = DensePolynomial::<Fr>::rand(d, rng);
+    let second = DensePolynomial::<Fr>::rand(d, rng);
+    let joint = first
+        .coeffs()
+        .iter()
+        .zip(second.coeffs())
+        .map(|(&x, &y)| [x, y])
+        .collect::<Vec<_>>();
+    let time_ck = CommitterKey::<Bls12_381>::new(d + 10, 3, rng);
+    let space_ck = CommitterKeyStream::from(&time_ck);
+
+    let commitments = [time_ck.commit(&first), time_ck.commit(&second)];
+    for chunk_size in [1, 7, 64, 1 << 10] {
+        assert_eq!(
+            space_ck
+                .commit_joint(&Reverse(joint.as_slice()), chunk_size)
+                .unwrap(),
+            commitments
+        );
+    }
+}
+
 #[test]
 fn test_srs() {
     use ark_bls12_381::Bls12_381;
@@ -36,6 +111,67 @@ fn test_srs() {
     let space_ck = CommitterKeyStream::from(&time_ck);
     // Make sure that there are enough elements for the entire array.
     assert_eq!(time_ck.powers_of_g.len(), space_ck.powers_of_g.len());
+    // views over the stream hold the powers aligned to polynomials of smaller degree.
+    let slice = space_ck.slice(space_ck.powers_of_g.len() - 4, 4);
+    assert_eq!(slice.len(), 4);
+    assert!(slice.iter().eq(time_ck.powers_of_g[..4].iter().rev()));
+}
+
+#[test]
+fn test_srs_from_seed() {
+    use ark_ec::AffineRepr;
+
+    let ck = CommitterKey::<Bls12_381>::from_seed(10, 3, [7; 32]);
+    let same_ck = CommitterKey::<Bls12_381>::from_seed(10, 3, [7; 32]);
+    let other_ck = CommitterKey::<Bls12_381>::from_seed(10, 3, [8; 32]);
+    assert_eq!(ck.powers_of_g, same_ck.powers_of_g);
+    assert_eq!(ck.powers_of_g2, same_ck.powers_of_g2);
+    assert_ne!(ck.powers_of_g, other_ck.powers_of_g);
+
+    // the key is built on the fixed generators.
+    assert_eq!(ck.powers_of_g[0], ark_bls12_381::G1Affine::generator());
+    assert_eq!(ck.powers_of_g2[0], ark_bls12_381::G2Affine::generator());
+
+    let rng = &mut test_rng();
+    let vk = VerifierKey::from(&ck);
+    let polynomial = DensePolynomial::<Fr>::rand(9, rng);
+    let alpha = Fr::rand(rng);
+    let commitment = ck.commit(&polynomial);
+    let (evaluation, proof) = ck.open(&polynomial, &alpha);
+    assert!(vk.verify(&commitment, &alpha, &evaluation, &proof).is_ok());
+}
+
+#[test]
+fn test_vk_json() {
+    let rng = &mut test_rng();
+    let ck = CommitterKey::<Bls12_381>::new(10, 3, rng);
+    let vk = VerifierKey::from(&ck);
+    let json = vk.to_json();
+    assert!(json.contains("\"type\": \"verifier_key\""));
+    assert_eq!(VerifierKey::<Bls12_381>::from_json(&json).unwrap(), vk);
+
+    // a key without powers in G2 is malformed.
+    let vk = VerifierKey::<Bls12_381> {
+        powers_of_g: vk.powers_of_g,
+        powers_of_g2: Vec::new(),
+    };
+    assert!(VerifierKey::<Bls12_381>::from_json(&vk.to_json()).is_err());
+}
+
+#[test]
+fn test_fake_setup() {
+    let rng = &mut test_rng();
+    let d = 15;
+    let ck = CommitterKey::<Bls12_381>::fake_setup(42, d + 1, 3);
+    let vk = VerifierKey::from(&ck);
+    assert_eq!(ck.powers_of_g.len(), d + 2);
+    assert_eq!(ck.max_eval_points(), 3);
+
+    let polynomial = DensePolynomial::<Fr>::rand(d, rng);
+    let alpha = Fr::rand(rng);
+    let commitment = ck.commit(&polynomial);
+    let (evaluation, proof) = ck.open(&polynomial, &alpha);
+    assert!(vk.verify(&commitment, &alpha, &evaluation, &proof).is_ok());
 }
 
 #[test]
@@ -51,12 +187,48 @@ fn test_open_consistency() {
 
     // compute the time commitment
     let (time_evaluation, time_open) = time_ck.open(&polynomials, &alpha);
-    let (space_evaluation, space_open) = space_ck.open(&polynomial_stream, &alpha, max_msm_buffer);
+    let (space_evaluation, space_open) = space_ck
+        .open(&polynomial_stream, &alpha, max_msm_buffer)
+        .unwrap();
     // compute the space commitment
     assert_eq!(time_evaluation, space_evaluation);
     assert_eq!(time_open, space_open);
 }
 
+#[test]
+fn test_update() {
+    let rng = &mut ark_std::test_rng();
+    let d = 15;
+    let ck = CommitterKey::<Bls12_381>::new(d + 5, 3, rng);
+    let polynomial = (0..d).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let alpha = Fr::rand(rng);
+    let commitment = ck.commit(&polynomial);
+    let (evaluation, proof) = ck.open(&polynomial, &alpha);
+
+    // repeated indices add up, and the polynomial may grow.
+    let changes = [
+        (3, Fr::rand(rng)),
+        (0, Fr::rand(rng)),
+        (3, Fr::rand(rng)),
+        (d + 2, Fr::rand(rng)),
+    ];
+    let mut updated = polynomial.clone();
+    updated.resize(d + 3, Fr::from(0u64));
+    for &(i, delta) in &changes {
+        updated[i] += delta;
+    }
+    assert_eq!(commitment.update(&changes, &ck), ck.commit(&updated));
+    assert_eq!(
+        proof.update(&evaluation, &changes, &alpha, &ck),
+        ck.open(&updated, &alpha)
+    );
+    assert_eq!(commitment.update(&[], &ck), commitment);
+    assert_eq!(
+        proof.update(&evaluation, &[], &alpha, &ck),
+        (evaluation, proof)
+    );
+}
+
 #[test]
 fn test_open_multipoints_correctness() {
     let mut rng = &mut test_rng();
@@ -99,3 +271,186 @@ fn test_open_multipoints_correctness() {
 
     assert!(verification_result.is_ok());
 }
+
+#[test]
+fn test_prepared_verifier_key() {
+    use ark_ff::One;
+
+    let rng = &mut test_rng();
+    let d = 15;
+    let ck = CommitterKey::<Bls12_381>::new(d + 1, 3, rng);
+    let vk = VerifierKey::from(&ck);
+    let pvk = PreparedVerifierKey::from(&vk);
+    assert_eq!(pvk.verifier_key(), &vk);
+
+    let polynomial = DensePolynomial::<Fr>::rand(d, rng);
+    let alpha = Fr::rand(rng);
+    let commitment = ck.commit(&polynomial);
+    let (evaluation, proof) = ck.open(&polynomial, &alpha);
+    assert!(pvk.verify(&commitment, &alpha, &evaluation, &proof).is_ok());
+    let wrong_evaluation = evaluation + Fr::one();
+    assert!(vk
+        .verify(&commitment, &alpha, &wrong_evaluation, &proof)
+        .is_err());
+    assert!(pvk
+        .verify(&commitment, &alpha, &wrong_evaluation, &proof)
+        .is_err());
+
+    let eval_points = (0..3).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let polynomials = (0..4)
+        .map(|_| DensePolynomial::<Fr>::rand(d, rng).coeffs)
+        .collect::<Vec<_>>();
+    let mut evals = polynomials
+        .iter()
+        .map(|p| eval_points.iter().map(|e| evaluate_le(p, e)).collect())
+        .collect::<Vec<Vec<_>>>();
+    let commitments = ck.batch_commit(&polynomials);
+    let eta = Fr::rand(rng);
+    let proof = ck.batch_open_multi_points(
+        &polynomials.iter().collect::<Vec<_>>()[..],
+        &eval_points,
+        &eta,
+    );
+    let check = vk.multi_points_check(&commitments, &eval_points, &evals, &proof, &eta);
+    assert!(pvk
+        .verify_multi_points(&commitments, &eval_points, &evals, &proof, &eta)
+        .is_ok());
+    assert!(pvk.batch_check(&[check, check], Fr::rand(rng)).is_ok());
+    evals[0][0] += Fr::one();
+    let wrong_check = vk.multi_points_check(&commitments, &eval_points, &evals, &proof, &eta);
+    assert!(pvk.check(&wrong_check).is_err());
+    assert!(pvk
+        .batch_check(&[check, wrong_check], Fr::rand(rng))
+        .is_err());
+}
+
+#[test]
+fn test_partial_commitments() {
+    let rng = &mut ark_std::test_rng();
+    let d = 100;
+    let chunk_size = 16;
+    let polynomial = DensePolynomial::<Fr>::rand(d, rng);
+    let ck = CommitterKey::<Bls12_381>::new(d + 1, 3, rng);
+
+    // each chunk is committed with the powers at the same positions.
+    let partial_commitments = polynomial
+        .chunks(chunk_size)
+        .enumerate()
+        .map(|(i, chunk)| ck.commit_at(i * chunk_size, chunk));
+    assert_eq!(
+        partial_commitments.sum::<crate::kzg::Commitment<_>>(),
+        ck.commit(&polynomial)
+    );
+}
+
+#[test]
+fn test_msm_backend() {
+    use crate::kzg::backend::CpuBackend;
+
+    let rng = &mut ark_std::test_rng();
+    let d = 100;
+    let polynomial = DensePolynomial::<Fr>::rand(d, rng);
+    let time_ck = CommitterKey::<Bls12_381>::new(d + 10, 3, rng);
+    let space_ck = CommitterKeyStream::from(&time_ck);
+
+    let commitment = time_ck.commit(&polynomial);
+    assert_eq!(time_ck.commit_with(&polynomial, &CpuBackend), commitment);
+    assert_eq!(
+        space_ck
+            .commit_with(&Reverse(polynomial.coeffs()), 16, &CpuBackend)
+            .unwrap(),
+        commitment
+    );
+}
+
+#[test]
+fn test_fixed_base_table() {
+    use ark_bls12_381::G1Affine;
+    use ark_serialize::CanonicalDeserialize;
+
+    use crate::kzg::precomputed::FixedBaseStream;
+
+    let rng = &mut ark_std::test_rng();
+    let d = 100;
+    let polynomial = DensePolynomial::<Fr>::rand(d, rng);
+    let short_polynomial = DensePolynomial::<Fr>::rand(d / 3, rng);
+    let time_ck = CommitterKey::<Bls12_381>::new(d + 10, 3, rng);
+    let space_ck = CommitterKeyStream::from(&time_ck);
+
+    for window in [1, 4, 13] {
+        let table = time_ck.fixed_base_table(window);
+        assert_eq!(table.len(), time_ck.powers_of_g.len());
+        for polynomial in [&polynomial, &short_polynomial] {
+            assert_eq!(
+                time_ck.commit_precomputed(polynomial, &table),
+                time_ck.commit(polynomial)
+            );
+        }
+
+        // the table written to disk is read back as a stream.
+        let mut bytes = Vec::new();
+        space_ck.write_fixed_base_table(window, &mut bytes).unwrap();
+        let mut reader = bytes.as_slice();
+        let mut points = Vec::new();
+        while !reader.is_empty() {
+            points.push(G1Affine::deserialize_uncompressed(&mut reader).unwrap());
+        }
+        let table_stream = FixedBaseStream::new(points.as_slice(), window);
+        assert_eq!(table_stream.len(), time_ck.powers_of_g.len());
+        for polynomial in [&polynomial, &short_polynomial] {
+            assert_eq!(
+                space_ck
+                    .commit_precomputed(&Reverse(polynomial.coeffs()), &table_stream)
+                    .unwrap(),
+                time_ck.commit(polynomial)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_window_size() {
+    use crate::kzg::pippenger::window_size;
+
+    // larger streams use larger windows.
+    let windows = (0..30)
+        .map(|d| window_size(1 << d, 255, usize::MAX))
+        .collect::<Vec<_>>();
+    assert!(windows.windows(2).all(|w| w[0] <= w[1]));
+    assert!(windows[0] <= 2);
+    assert!(windows[20] >= 12);
+    // the buckets do not exceed the bound.
+    assert!(window_size(1 << 20, 255, 1 << 10) <= 6);
+    assert_eq!(window_size(1 << 20, 255, 0), 1);
+}
+
+#[test]
+fn test_stream_pippenger() {
+    use ark_bls12_381::G1Projective;
+    use ark_ec::{CurveGroup, VariableBaseMSM};
+    use ark_ff::{One, PrimeField, Zero};
+
+    use crate::kzg::pippenger::StreamPippenger;
+
+    let rng = &mut ark_std::test_rng();
+    let bases = (0..200)
+        .map(|_| G1Projective::rand(rng).into_affine())
+        .collect::<Vec<_>>();
+    for len in [0, 1, 7, 200] {
+        let scalars = (0..len).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+        // zero and unit scalars are added apart.
+        let sparse = (0..len)
+            .map(|i| [Fr::zero(), Fr::one(), scalars[i]][i % 3])
+            .collect::<Vec<_>>();
+        for scalars in [scalars, sparse] {
+            let expected = G1Projective::msm(&bases[..len], &scalars);
+            for max_msm_buffer in [1, 16, 1 << 20] {
+                let mut pippenger = StreamPippenger::<G1Projective>::new(len, max_msm_buffer);
+                for (base, scalar) in bases.iter().zip(&scalars) {
+                    pippenger.add(base, scalar.into_bigint());
+                }
+                assert_eq!(pippenger.finalize(), expected);
+            }
+        }
+    }
+}
@@ -4,23 +4,29 @@ use ark_ec::scalar_mul::fixed_base::FixedBase;
 use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup, VariableBaseMSM};
 use ark_ff::{PrimeField, Zero};
 use ark_poly::{univariate::DensePolynomial, DenseUVPolynomial};
+use ark_serialize::*;
 use ark_std::borrow::Borrow;
 use ark_std::ops::Div;
 use ark_std::rand::RngCore;
 use ark_std::vec::Vec;
 use ark_std::UniformRand;
 
+use crate::kzg::backend::MsmBackend;
 use crate::kzg::{Commitment, EvaluationProof, VerifierKey};
 use crate::misc::{linear_combination, powers};
 
 use super::vanishing_polynomial;
 
+/// The domain separator of the trapdoors derived by [`CommitterKey::from_seed`].
+const SEED_DOMAIN: &[u8] = b"gemini-committer-key-from-seed";
+
 /// The SRS for the polynomial commitment scheme for a max
 ///
 /// The SRS consists of the `max_degree` powers of \\(\tau\\) in \\(\GG_1\\)
 /// plus the `max_eval_degree` powers over \\(\GG_2\\),
 /// where `max_degree` is the max polynomial degree to commit to,
 /// and `max_eval_degree` is the max number of different points to open simultaneously.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone)]
 pub struct CommitterKey<E: Pairing> {
     pub(crate) powers_of_g: Vec<E::G1Affine>,
     pub(crate) powers_of_g2: Vec<E::G2Affine>,
@@ -71,6 +77,79 @@ impl<E: Pairing> CommitterKey<E> {
         }
     }
 
+    /// **Insecure** setup, for tests and development only.
+    ///
+    /// Construct the committer key from the trapdoor derived by hashing `seed` with SHA-512,
+    /// using the fixed generators of \\(\GG_1\\) and \\(\GG_2\\).
+    /// The key depends on nothing but the seed and the curve,
+    /// hence the same seed produces the same key (and, since the provers use no randomness, the same proofs)
+    /// on any machine, and with any version of the random number generators:
+    /// test fixtures can be committed as seeds rather than as keys.
+    /// Anyone knowing `seed` knows the trapdoor and can forge proofs:
+    /// never use this key in production.
+    pub fn from_seed(max_degree: usize, max_eval_points: usize, seed: [u8; 32]) -> Self {
+        use sha2::{Digest, Sha512};
+
+        let digest = Sha512::new()
+            .chain_update(SEED_DOMAIN)
+            .chain_update(seed)
+            .finalize();
+        let tau = E::ScalarField::from_le_bytes_mod_order(&digest);
+        Self::from_trapdoor(tau, max_degree, max_eval_points)
+    }
+
+    /// **Insecure** setup, for tests and development only.
+    ///
+    /// Construct the committer key from the trapdoor derived from `seed`,
+    /// using the fixed generators of \\(\GG_1\\) and \\(\GG_2\\)
+    /// and fixed-base windowed exponentiation in both groups.
+    /// Anyone knowing `seed` knows the trapdoor and can forge proofs:
+    /// never use this key in production.
+    #[cfg(any(test, feature = "fake-setup"))]
+    pub fn fake_setup(seed: u64, max_degree: usize, max_eval_points: usize) -> Self {
+        use ark_std::rand::rngs::StdRng;
+        use ark_std::rand::SeedableRng;
+
+        let tau = E::ScalarField::rand(&mut StdRng::seed_from_u64(seed));
+        Self::from_trapdoor(tau, max_degree, max_eval_points)
+    }
+
+    /// Construct the committer key from the trapdoor `tau`,
+    /// using the fixed generators of \\(\GG_1\\) and \\(\GG_2\\)
+    /// and fixed-base windowed exponentiation in both groups.
+    fn from_trapdoor(tau: E::ScalarField, max_degree: usize, max_eval_points: usize) -> Self {
+        let powers_of_tau = powers(tau, max_degree + 1);
+        let scalar_bits = E::ScalarField::MODULUS_BIT_SIZE as usize;
+
+        let window_size = FixedBase::get_mul_window_size(max_degree + 1);
+        let g_table = FixedBase::get_window_table(
+            scalar_bits,
+            window_size,
+            <E::G1 as ark_ec::Group>::generator(),
+        );
+        let powers_of_g_proj = FixedBase::msm(scalar_bits, window_size, &g_table, &powers_of_tau);
+        let powers_of_g = E::G1::normalize_batch(&powers_of_g_proj);
+
+        let window_size = FixedBase::get_mul_window_size(max_eval_points + 1);
+        let g2_table = FixedBase::get_window_table(
+            scalar_bits,
+            window_size,
+            <E::G2 as ark_ec::Group>::generator(),
+        );
+        let powers_of_g2_proj = FixedBase::msm(
+            scalar_bits,
+            window_size,
+            &g2_table,
+            &powers(tau, max_eval_points + 1),
+        );
+        let powers_of_g2 = E::G2::normalize_batch(&powers_of_g2_proj);
+
+        CommitterKey {
+            powers_of_g,
+            powers_of_g2,
+        }
+    }
+
     /// Return the bound on evaluation points.
     #[inline]
     pub fn max_eval_points(&self) -> usize {
@@ -79,9 +158,29 @@ impl<E: Pairing> CommitterKey<E> {
 
     /// Given a polynomial `polynomial` of degree less than `max_degree`, return a commitment to `polynomial`.
     pub fn commit(&self, polynomial: &[E::ScalarField]) -> Commitment<E> {
+        trace_span!("commit", len = polynomial.len());
         Commitment(E::G1::msm(&self.powers_of_g, polynomial))
     }
 
+    /// Same as [`CommitterKey::commit`],
+    /// but computing the multi-scalar multiplication with `backend`.
+    pub fn commit_with<B: MsmBackend<E::G1>>(
+        &self,
+        polynomial: &[E::ScalarField],
+        backend: &B,
+    ) -> Commitment<E> {
+        trace_span!("commit", len = polynomial.len());
+        Commitment(backend.msm(&self.powers_of_g, polynomial))
+    }
+
+    /// Given a polynomial `polynomial`, return a commitment to `polynomial` shifted by `offset`,
+    /// that is, to the polynomial $X^{\text{offset}} f(X)$.
+    pub fn commit_at(&self, offset: usize, polynomial: &[E::ScalarField]) -> Commitment<E> {
+        assert!(self.powers_of_g.len() >= offset + polynomial.len());
+        trace_span!("commit", len = polynomial.len());
+        Commitment(E::G1::msm(&self.powers_of_g[offset..], polynomial))
+    }
+
     /// Obtain a new preprocessed committer key defined by the indices `indices`.
     pub fn index_by(&self, indices: &[usize]) -> Self {
         let mut indexed_powers_of_g = vec![E::G1Affine::zero(); self.powers_of_g.len()];
@@ -114,6 +213,7 @@ impl<E: Pairing> CommitterKey<E> {
         polynomial: &[E::ScalarField],
         evalualtion_point: &E::ScalarField,
     ) -> (E::ScalarField, EvaluationProof<E>) {
+        trace_span!("open", len = polynomial.len());
         let mut quotient = Vec::new();
 
         let mut previous = E::ScalarField::zero();
@@ -136,6 +236,7 @@ impl<E: Pairing> CommitterKey<E> {
         polynomial: &[E::ScalarField],
         eval_points: &[E::ScalarField],
     ) -> EvaluationProof<E> {
+        trace_span!("open", len = polynomial.len(), points = eval_points.len());
         // Computing the vanishing polynomial over eval_points
         let z_poly = vanishing_polynomial(eval_points);
 
@@ -160,6 +261,64 @@ impl<E: Pairing> CommitterKey<E> {
     }
 }
 
+impl<E: Pairing> Commitment<E> {
+    /// Return the commitment to the polynomial committed in `self`,
+    /// once the coefficient of $X^i$ is increased by $\delta$ for each pair `(i, delta)` in `changes`,
+    /// in time proportional to the number of changes.
+    ///
+    /// # Panics
+    /// If any index is beyond the committer key `ck`.
+    pub fn update(&self, changes: &[(usize, E::ScalarField)], ck: &CommitterKey<E>) -> Self {
+        assert!(
+            changes.iter().all(|&(i, _)| i < ck.powers_of_g.len()),
+            "index beyond the committer key"
+        );
+        let (bases, deltas): (Vec<_>, Vec<_>) = changes
+            .iter()
+            .map(|&(i, delta)| (ck.powers_of_g[i], delta))
+            .unzip();
+        Commitment(self.0 + E::G1::msm(&bases, &deltas))
+    }
+}
+
+impl<E: Pairing> EvaluationProof<E> {
+    /// Given the evaluation `evaluation` at `evaluation_point` of the polynomial opened in `self`,
+    /// return the evaluation and the evaluation proof of the polynomial
+    /// once the coefficient of $X^i$ is increased by $\delta$ for each pair `(i, delta)` in `changes`.
+    ///
+    /// Increasing the coefficient of $X^i$ by $\delta$ increases the quotient by
+    /// $\delta \cdot (X^i - x^i) / (X - x) = \delta \sum_{j < i} x^{i - 1 - j} X^j$:
+    /// the proof is updated in time proportional to the largest index changed, instead of the degree.
+    ///
+    /// # Panics
+    /// If any index is beyond the committer key `ck`.
+    pub fn update(
+        &self,
+        evaluation: &E::ScalarField,
+        changes: &[(usize, E::ScalarField)],
+        evaluation_point: &E::ScalarField,
+        ck: &CommitterKey<E>,
+    ) -> (E::ScalarField, Self) {
+        let len = changes.iter().map(|&(i, _)| i).max().unwrap_or(0);
+        assert!(len < ck.powers_of_g.len(), "index beyond the committer key");
+        let mut deltas = vec![E::ScalarField::zero(); len + 1];
+        for &(i, delta) in changes {
+            deltas[i] += delta;
+        }
+        // the changes of the quotient, from the highest coefficient,
+        // as in the division of [`CommitterKey::open`].
+        let mut quotient = vec![E::ScalarField::zero(); len];
+        let mut previous = E::ScalarField::zero();
+        for j in (0..len).rev() {
+            previous = previous * evaluation_point + deltas[j + 1];
+            quotient[j] = previous;
+        }
+        let evaluation = *evaluation + previous * evaluation_point + deltas[0];
+        let proof = self.0 + E::G1::msm(&ck.powers_of_g[..len], &quotient);
+        (evaluation, EvaluationProof(proof))
+    }
+}
+
 #[test]
 fn test_srs() {
     use ark_bls12_381::Bls12_381;
@@ -13,6 +13,10 @@
 //! All arguments are internally using the [`kzg`](crate::kzg) commitment scheme.
 //! Support for generic univariate or multivariate commitments will is scheduled and will
 //! happen at some point in the future.
+//! For small prime fields without pairing-friendly curves, [`transparent`] provides
+//! a transparent, streaming polynomial commitment over the Goldilocks field;
+//! it is a standalone commitment scheme, not (yet) a backend of the arguments.
+//! To prove and verify circom circuits directly from their files, see [`files`].
 //!
 //! Both arguments rely on some sub-protocols, implemented as separate modules in [`subprotocols`]
 //! and free of use for other protocols.
@@ -24,9 +28,74 @@
 //! on:
 //!
 //! - `asm`, to turn on the assembly backend within [`ark-ff`](https://docs.rs/ark-ff/);
+//! - `prover` (on by default), to compile the provers of [`snark`] and [`psnark`],
+//!     together with the prover keys and the index cache.
+//!     Without it, only the verifier side is compiled: the verifier keys,
+//!     the (de)serialization of proofs, and the verification equations (see [below](#verifier-only-builds)).
+//!     **Builds with `--no-default-features` that use the provers must now enable `prover` explicitly.**
 //! - `parallel`, to turn on multi-threading. This requires the additional dependency [`rayon`](https://docs.rs/rayon/latest/rayon/);
-//! - `std`, to rely on the Rust Standard library;
+//! - `std`, to rely on the Rust Standard library.
+//!     Without it, the crate builds under `no_std` (with `alloc`):
+//!     the provers and the verifiers are available,
+//!     while the streams backed by files or threads, the circom and zkInterface readers, the file-based prover, and the on-disk index cache are not;
 //! - `print-trace`, to print additional information concerning the execution time of the sub-protocols. **This feature must be enabled if you want to print the execution time of the examples.**
+//! - `tracing`, to emit [`tracing`](https://docs.rs/tracing/) spans for each phase of the provers
+//!     (commitments, sumcheck rounds, streaming passes, tensorchecks, and openings),
+//!     along with the number of elements processed.
+//!     Spans are recorded by the subscriber installed by the caller.
+//! - `snark-trait`, to implement the [`ark-snark`](https://docs.rs/ark-snark/) `SNARK` trait
+//!     for `snark::GeminiSnark` and `psnark::GeminiPsnark`,
+//!     so that they can replace other arkworks SNARKs.
+//! - `noir`, to lower circuits compiled with [Noir](https://noir-lang.org/) into R1CS streams,
+//!     via the [`acir`](https://docs.rs/acir/) crate.
+//! - `http`, to stream elements (e.g., the SRS) from remote files with HTTP range requests
+//!     (see `iterable::HttpIterable`), with read-ahead and retries.
+//! - `ffi`, to expose a C interface to the preprocessing SNARK (see [`ffi`] and `include/gemini.h`).
+//!     Build the shared library with `cargo rustc --release --features ffi --crate-type cdylib`.
+//! - `python`, to expose Python bindings to the preprocessing SNARK via [`pyo3`](https://pyo3.rs),
+//!     taking witnesses as `numpy` arrays or `.wtns` files.
+//! - `remote`, to prove on a remote machine (see `remote::ProvingService`),
+//!     uploading the witness in chunks over a length-prefixed protocol.
+//! - `solidity-experimental`, to generate a Solidity verifier for SNARK proofs (see `snark::solidity`).
+//!     **The contract has not been compiled nor run on an EVM: it must not be deployed as is.**
+//!
+//! ## Verifier-only builds
+//!
+//! Light clients (e.g., wallets and on-node verifiers) only verify proofs:
+//! disable the default features to compile the verifiers without the provers and without `rayon`,
+//! shrinking the binary and the code to audit.
+//!
+//! ```bash
+//! cargo build --no-default-features --features std
+//! ```
+//!
+//! The features depending on a prover (`ffi`, `python`, `remote`, `snark-trait`, `test-utils`) enable `prover`.
+//! The streams of [`iterable`], the subprotocols, and the commitment scheme are always compiled,
+//! as the verifiers and the indexer build on them:
+//! verifier-only builds drop the provers, not the streaming subsystem.
+//! Only the helpers used exclusively by the provers are gated behind `prover`.
+//! This configuration is not exercised by the test suite:
+//! run `cargo check --no-default-features --features std` when changing the gating.
+//!
+//! ## WebAssembly
+//!
+//! The provers and the verifiers build for `wasm32-unknown-unknown`,
+//! where threads and the file system are not available: disable the default features,
+//! and enable `std` and `prover` only.
+//! On this target, the streaming commitments and sumchecks read their streams
+//! on the current thread (see [`misc::pipeline`]).
+//!
+//! ```bash
+//! cargo build --target wasm32-unknown-unknown --no-default-features --features std,prover
+//! ```
+//!
+//! The `print-trace` feature measures time with `std::time::Instant`,
+//! which panics on this target.
+//! The tests for this target run with [`wasm-pack`](https://rustwasm.github.io/wasm-pack/):
+//!
+//! ```bash
+//! wasm-pack test --node -- --no-default-features --features std,prover
+//! ```
 //!
 //! # Benchmarking
 //!
@@ -51,7 +120,7 @@
 #![feature(iter_advance_by)]
 #![no_std]
 #![deny(unused_comparisons, bare_trait_objects, unused_must_use, const_err)]
-#![forbid(unsafe_code)]
+#![deny(unsafe_code)]
 #![deny(trivial_numeric_casts)]
 #![deny(private_in_public)]
 #![deny(unused_allocation)]
@@ -65,23 +134,70 @@
 // #![deny(renamed_and_removed_lints, stable_features, unused_allocation)]
 #[macro_use]
 extern crate ark_std;
+#[cfg(feature = "std")]
+extern crate std;
 
 /// The domain separator, used when proving statements on gemini.
 pub(crate) const PROTOCOL_NAME: &[u8] = b"GEMINI-v0";
+/// The version of the serialization format of proofs,
+/// prepended to each proof serialized with `serialize_versioned`.
+pub const PROOF_FORMAT_VERSION: u8 = 1;
+/// Whether the field arithmetic runs on the assembly backend of `ark-ff`.
+///
+/// The backend requires the `asm` feature, and a build for `x86_64` with the `bmi2` and `adx` target features
+/// (e.g. with `RUSTFLAGS="-C target-cpu=native"` on a recent CPU);
+/// otherwise, `ark-ff` silently falls back to portable arithmetic.
+pub const ASM_ARITHMETIC: bool = cfg!(all(
+    feature = "asm",
+    target_arch = "x86_64",
+    target_feature = "bmi2",
+    target_feature = "adx"
+));
 /// The threshold for switching from space to time prover within the sumcheck.
 const SPACE_TIME_THRESHOLD: usize = 22;
 // const SUMCHECK_BUF_SIZE: usize = 1 << 20;
 
+/// Enter the span `$name`, with the given fields, until the end of the current scope.
+/// Spans are emitted only with the `tracing` feature.
+macro_rules! trace_span {
+    ($name:expr $(, $field:ident = $value:expr)* $(,)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::info_span!($name $(, $field = $value)*).entered();
+    };
+}
+
+pub mod ccs;
+#[cfg(feature = "std")]
+pub mod circom;
+pub mod config;
 pub mod errors;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+#[cfg(all(feature = "std", feature = "prover"))]
+pub mod files;
+pub mod gates;
 pub mod iterable;
 pub mod kzg;
+#[cfg(feature = "noir")]
+pub mod noir;
+pub mod poly_utils;
 pub mod psnark;
+#[cfg(feature = "python")]
+pub mod python;
+#[cfg(feature = "remote")]
+pub mod remote;
 pub mod snark;
 pub mod subprotocols;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+pub mod transparent;
+#[cfg(feature = "std")]
+pub mod zkinterface;
 
 // private modules
 
 #[doc(hidden)]
 pub mod circuit;
+mod json;
 pub mod misc;
-mod transcript;
+pub mod transcript;
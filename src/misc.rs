@@ -6,8 +6,26 @@ use ark_std::vec::Vec;
 use crate::circuit::Matrix;
 use ark_std::collections::{BTreeMap, BTreeSet};
 
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::slice::ParallelSlice;
+
+#[cfg(feature = "std")]
+mod fft;
+mod kronecker;
+mod pipeline;
+#[cfg(feature = "std")]
+pub use fft::{
+    fft, fft_with, ifft, ifft_with, NttBackend, TransformedStream, TransformedStreamIter,
+};
+pub use kronecker::{contract, KroneckerIter, KroneckerStream};
+pub use pipeline::{pipeline, sequential, Producer};
+
 pub(crate) const TENSOR_EXPANSION_LOG: usize = 16;
 pub(crate) const TENSOR_EXPANSION: usize = (1 << TENSOR_EXPANSION_LOG) - 1;
+/// The number of coefficients processed sequentially by each thread, in the time-efficient provers.
+pub(crate) const PARALLEL_CHUNK_SIZE: usize = 1 << 12;
 
 /// Return a string will all the feature tags enabled so far.
 pub(crate) fn _features_enabled() -> ark_std::string::String {
@@ -50,13 +68,26 @@ where
 /// Helper function for folding single polynomial.
 #[inline]
 pub(crate) fn fold_polynomial<F: Field>(f: &[F], r: F) -> Vec<F> {
-    f.chunks(2)
-        .map(|pair| pair[0] + r * pair.get(1).unwrap_or(&F::zero()))
-        .collect()
+    let mut folded = Vec::new();
+    fold_polynomial_into(f, r, &mut folded);
+    folded
+}
+
+/// Same as [`fold_polynomial`], but writing the folding into `folded`, reusing its allocation.
+#[inline]
+pub(crate) fn fold_polynomial_into<F: Field>(f: &[F], r: F, folded: &mut Vec<F>) {
+    let fold = |pair: &[F]| pair[0] + r * pair.get(1).unwrap_or(&F::zero());
+    #[cfg(feature = "parallel")]
+    f.par_chunks(2).map(fold).collect_into_vec(folded);
+    #[cfg(not(feature = "parallel"))]
+    {
+        folded.clear();
+        folded.extend(f.chunks(2).map(fold));
+    }
 }
 
 /// Return a vector of length `len` containing the consecutive powers of element.
-pub(crate) fn powers<F: Field>(element: F, len: usize) -> Vec<F> {
+pub fn powers<F: Field>(element: F, len: usize) -> Vec<F> {
     let mut powers = vec![F::one(); len];
     for i in 1..len {
         powers[i] = element * powers[i - 1];
@@ -106,7 +137,42 @@ pub fn product_matrix_vector<F: Field>(matrix: &[Vec<(F, usize)>], z: &[F]) -> V
         acc
     };
 
-    matrix.iter().map(|row| inner_prod_fn(row)).collect()
+    cfg_iter!(matrix).map(|row| inner_prod_fn(row)).collect()
+}
+
+/// Given the sparse matrices $M_i$ and the vectors $\vec v_i$ in `terms`,
+/// compute $\sum_i \vec v_i M_i$, for matrices with `len` columns.
+pub(crate) fn product_vectors_matrices<F: Field>(
+    terms: &[(&[Vec<(F, usize)>], &[F])],
+    len: usize,
+) -> Vec<F> {
+    let scatter = |mut acc: Vec<F>, (row, v): (&Vec<(F, usize)>, &F)| {
+        row.iter().for_each(|&(val, col)| acc[col] += *v * val);
+        acc
+    };
+    let mut res = vec![F::zero(); len];
+    for &(matrix, v) in terms {
+        #[cfg(not(feature = "parallel"))]
+        {
+            res = matrix.iter().zip(v).fold(res, scatter);
+        }
+        #[cfg(feature = "parallel")]
+        {
+            let partial = matrix
+                .par_iter()
+                .zip(v)
+                .fold(|| vec![F::zero(); len], scatter)
+                .reduce(
+                    || vec![F::zero(); len],
+                    |mut lhs, rhs| {
+                        lhs.iter_mut().zip(rhs).for_each(|(l, r)| *l += r);
+                        lhs
+                    },
+                );
+            res.iter_mut().zip(partial).for_each(|(l, r)| *l += r);
+        }
+    }
+    res
 }
 
 /// Given a vector `z` and a sparse matrix `matrix`, compute `z * matrix`.
@@ -176,6 +242,10 @@ pub fn expand_tensor<F: Field>(elements: &[F]) -> PartialTensor<F> {
 
 /// Polynomial evaluation, assuming that the
 /// coeffients are in big-endian.
+///
+/// The even and the odd coefficients are accumulated by two independent Horner chains in $x^2$,
+/// so that consecutive multiplications do not wait on each other
+/// (which is most of the time of the assembly multiplication of `ark-ff`).
 #[inline]
 pub fn evaluate_be<I, F>(polynomial: I, x: &F) -> F
 where
@@ -183,19 +253,85 @@ where
     I: IntoIterator,
     I::Item: Borrow<F>,
 {
-    polynomial
-        .into_iter()
-        .fold(F::zero(), |previous, c| previous * x + c.borrow())
+    let square = x.square();
+    let mut coefficients = polynomial.into_iter();
+    let (mut even, mut odd) = (F::zero(), F::zero());
+    while let Some(c) = coefficients.next() {
+        match coefficients.next() {
+            Some(d) => {
+                even = even * square + c.borrow();
+                odd = odd * square + d.borrow();
+            }
+            // an odd number of coefficients: `c` is the constant term.
+            None => return (even * x + odd) * x + c.borrow(),
+        }
+    }
+    even * x + odd
 }
 
 /// Polynomial evaluation, assuming that the
 /// coefficients are in little-endian.
+///
+/// With the `parallel` feature, chunks of coefficients are evaluated in parallel.
 #[inline]
 pub fn evaluate_le<F>(polynomial: &[F], x: &F) -> F
 where
     F: Field,
 {
-    evaluate_be(polynomial.iter().rev(), x)
+    let shift = x.pow([PARALLEL_CHUNK_SIZE as u64]);
+    let evaluations = cfg_chunks!(polynomial, PARALLEL_CHUNK_SIZE)
+        .map(|chunk| evaluate_be(chunk.iter().rev(), x))
+        .collect::<Vec<_>>();
+    evaluate_be(evaluations.iter().rev(), &shift)
+}
+
+/// Polynomial evaluation at each of the `points`, assuming that the
+/// coefficients are in big-endian.
+///
+/// The coefficients are read once, running one Horner accumulator per point.
+#[inline]
+pub fn evaluate_be_at<I, F>(polynomial: I, points: &[F]) -> Vec<F>
+where
+    F: Field,
+    I: IntoIterator,
+    I::Item: Borrow<F>,
+{
+    let mut evaluations = vec![F::zero(); points.len()];
+    for coefficient in polynomial {
+        let coefficient = coefficient.borrow();
+        evaluations
+            .iter_mut()
+            .zip(points)
+            .for_each(|(evaluation, x)| *evaluation = *evaluation * x + coefficient);
+    }
+    evaluations
+}
+
+/// Polynomial evaluation at each of the `points`, assuming that the
+/// coefficients are in little-endian.
+///
+/// Same as [`evaluate_le`] for each point, but with a single pass over the coefficients.
+#[inline]
+pub fn evaluate_le_at<F>(polynomial: &[F], points: &[F]) -> Vec<F>
+where
+    F: Field,
+{
+    let shifts = points
+        .iter()
+        .map(|x| x.pow([PARALLEL_CHUNK_SIZE as u64]))
+        .collect::<Vec<_>>();
+    let evaluations = cfg_chunks!(polynomial, PARALLEL_CHUNK_SIZE)
+        .map(|chunk| evaluate_be_at(chunk.iter().rev(), points))
+        .collect::<Vec<_>>();
+    let mut combined = vec![F::zero(); points.len()];
+    for chunk_evaluations in evaluations.iter().rev() {
+        combined
+            .iter_mut()
+            .zip(&shifts)
+            .zip(chunk_evaluations)
+            .for_each(|((evaluation, shift), e)| *evaluation = *evaluation * shift + e);
+    }
+    combined
 }
 
 /// Return the hadamard product of `lhs` and `rhs`.
@@ -204,7 +340,7 @@ where
 #[inline]
 pub fn hadamard<F: Field>(lhs: &[F], rhs: &[F]) -> Vec<F> {
     assert_eq!(lhs.len(), rhs.len());
-    hadamard_unsafe(lhs, rhs)
+    cfg_iter!(lhs).zip(rhs).map(|(&x, y)| x * y).collect()
 }
 
 /// Return the inner product of `lhs` with `rhs`.
@@ -245,6 +381,56 @@ where
         .sum()
 }
 
+/// Stream the inverses of `elements`, inverting `chunk_size` elements at once
+/// with Montgomery's trick: a single inversion and three multiplications per element.
+///
+/// Zeros are mapped to zero.
+///
+/// # Panics
+/// If `chunk_size` is zero.
+pub fn batch_inverse<F, I>(elements: I, chunk_size: usize) -> BatchInverse<I::IntoIter, F>
+where
+    F: Field,
+    I: IntoIterator,
+    I::Item: Borrow<F>,
+{
+    assert!(chunk_size > 0, "chunks must not be empty");
+    BatchInverse {
+        elements: elements.into_iter(),
+        chunk_size,
+        inverses: Vec::new().into_iter(),
+    }
+}
+
+/// The iterator returned by [`batch_inverse`].
+pub struct BatchInverse<I, F> {
+    elements: I,
+    chunk_size: usize,
+    inverses: ark_std::vec::IntoIter<F>,
+}
+
+impl<I, F> Iterator for BatchInverse<I, F>
+where
+    F: Field,
+    I: Iterator,
+    I::Item: Borrow<F>,
+{
+    type Item = F;
+
+    fn next(&mut self) -> Option<F> {
+        if let Some(inverse) = self.inverses.next() {
+            return Some(inverse);
+        }
+        let mut chunk = (&mut self.elements)
+            .take(self.chunk_size)
+            .map(|e| *e.borrow())
+            .collect::<Vec<_>>();
+        ark_ff::batch_inversion(&mut chunk);
+        self.inverses = chunk.into_iter();
+        self.inverses.next()
+    }
+}
+
 #[inline]
 pub fn sum_matrices<F: Field>(
     a: &Matrix<F>,
@@ -398,6 +584,24 @@ fn test_linear_combination() {
     assert_eq!(got.unwrap(), expected);
 }
 
+#[test]
+fn test_fold_polynomial_into() {
+    use ark_bls12_381::Fr as F;
+
+    let f = (0..9u64).map(F::from).collect::<Vec<_>>();
+    let r = F::from(3u64);
+    let expected = [3u64, 11, 19, 27, 8].map(F::from);
+    assert_eq!(fold_polynomial(&f, r), expected);
+
+    // the buffer is refilled in place.
+    let mut folded = Vec::with_capacity(f.len());
+    folded.extend(&f);
+    let buffer = folded.as_ptr();
+    fold_polynomial_into(&f, r, &mut folded);
+    assert_eq!(folded, expected);
+    assert_eq!(folded.as_ptr(), buffer);
+}
+
 #[test]
 fn test_evaluate_index_poly() {
     use ark_bls12_381::Fr as F;
@@ -412,3 +616,92 @@ fn test_evaluate_index_poly() {
     let expected = evaluate_le(&index_polynomial, &x);
     assert_eq!(got, expected);
 }
+
+#[test]
+fn test_evaluate_be() {
+    use ark_bls12_381::Fr as F;
+    use ark_ff::{UniformRand, Zero};
+
+    let rng = &mut ark_std::test_rng();
+    let x = F::rand(rng);
+    for len in [0, 1, 2, 7, 8] {
+        let polynomial = (0..len).map(|_| F::rand(rng)).collect::<Vec<_>>();
+        let expected = polynomial
+            .iter()
+            .fold(F::zero(), |previous, c| previous * x + c);
+        assert_eq!(evaluate_be(&polynomial, &x), expected);
+    }
+}
+
+#[test]
+fn test_evaluate_le_chunks() {
+    use ark_bls12_381::Fr as F;
+    use ark_ff::UniformRand;
+
+    let rng = &mut ark_std::test_rng();
+    let x = F::rand(rng);
+    let polynomial = (0..3 * PARALLEL_CHUNK_SIZE + 5)
+        .map(|_| F::rand(rng))
+        .collect::<Vec<_>>();
+
+    let expected = evaluate_be(polynomial.iter().rev(), &x);
+    assert_eq!(evaluate_le(&polynomial, &x), expected);
+}
+
+#[test]
+fn test_evaluate_at() {
+    use ark_bls12_381::Fr as F;
+    use ark_ff::UniformRand;
+
+    let rng = &mut ark_std::test_rng();
+    let points = [F::rand(rng), F::rand(rng), F::rand(rng)];
+    let polynomial = (0..2 * PARALLEL_CHUNK_SIZE + 3)
+        .map(|_| F::rand(rng))
+        .collect::<Vec<_>>();
+    let expected = points
+        .iter()
+        .map(|x| evaluate_le(&polynomial, x))
+        .collect::<Vec<_>>();
+    assert_eq!(evaluate_le_at(&polynomial, &points), expected);
+    assert_eq!(evaluate_be_at(polynomial.iter().rev(), &points), expected);
+    assert!(evaluate_le_at(&polynomial, &[]).is_empty());
+}
+
+#[test]
+fn test_product_vectors_matrices() {
+    use ark_bls12_381::Fr as F;
+    use ark_ff::UniformRand;
+
+    let rng = &mut ark_std::test_rng();
+    let matrix = vec![
+        vec![(F::from(2u64), 0), (F::from(3u64), 2)],
+        vec![],
+        vec![(F::from(5u64), 1)],
+    ];
+    let v = (0..3).map(|_| F::rand(rng)).collect::<Vec<_>>();
+    let term = (matrix.as_slice(), v.as_slice());
+    let got = product_vectors_matrices(&[term, term], 3);
+    let expected = product_vector_matrix(&v, &matrix)
+        .iter()
+        .map(|&e| e.double())
+        .collect::<Vec<_>>();
+    assert_eq!(got, expected);
+}
+
+#[test]
+fn test_batch_inverse() {
+    use ark_bls12_381::Fr as F;
+    use ark_ff::{UniformRand, Zero};
+
+    let rng = &mut ark_std::test_rng();
+    let mut elements = (0..100).map(|_| F::rand(rng)).collect::<Vec<_>>();
+    elements[42] = F::zero();
+    let expected = elements
+        .iter()
+        .map(|e| e.inverse().unwrap_or_else(F::zero))
+        .collect::<Vec<_>>();
+    for chunk_size in [1, 7, 100, 1 << 10] {
+        let got = batch_inverse(&elements, chunk_size).collect::<Vec<_>>();
+        assert_eq!(got, expected);
+    }
+}
@@ -0,0 +1,402 @@
+//! External-memory FFTs over streams.
+//!
+//! The $n = r \cdot c$ coefficients (or evaluations) of a polynomial
+//! are arranged in a matrix with $r$ rows and $c$ columns,
+//! and transformed with the four-step algorithm:
+//! $c$ transforms of size $r$ over the columns, a multiplication by the twiddle factors,
+//! and $r$ transforms of size $c$ over the rows.
+//! The columns are collected in multiple passes over the input stream,
+//! and the intermediate matrix is transposed through temporary files,
+//! so that at most `buffer_size` elements are held in memory at any given time
+//! (or a single row or column, if larger).
+//!
+//! The transforms of the columns and of the rows are computed by a [`NttBackend`],
+//! a group of columns (or a block of rows) at once:
+//! [`fft`] and [`ifft`] compute them on the CPU,
+//! while [`fft_with`] and [`ifft_with`] hand them to any backend.
+//! No GPU backend is included: only the trait is provided,
+//! and [`CpuBackend`] is its only implementation in this crate.
+use ark_ff::{FftField, Field};
+use ark_std::borrow::Borrow;
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::iterable::Iterable;
+use crate::kzg::backend::CpuBackend;
+
+/// A counter for naming the temporary files uniquely within the process.
+static FFT_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+fn into_io_error(e: ark_serialize::SerializationError) -> io::Error {
+    io::Error::new(io::ErrorKind::Other, e)
+}
+
+/// A temporary file holding a fixed number of field elements, addressed by position.
+///
+/// The file is removed when dropped.
+struct Scratch<F> {
+    path: PathBuf,
+    file: File,
+    len: usize,
+    element_size: usize,
+    _field: PhantomData<F>,
+}
+
+impl<F: Field> Scratch<F> {
+    /// Create a temporary file holding `len` zeros.
+    fn new(len: usize) -> io::Result<Self> {
+        let path = std::env::temp_dir().join(std::format!(
+            "gemini-fft-{}-{}",
+            std::process::id(),
+            FFT_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)?;
+        let element_size = F::zero().uncompressed_size();
+        let scratch = Scratch {
+            path,
+            file,
+            len,
+            element_size,
+            _field: PhantomData,
+        };
+        scratch.file.set_len((len * element_size) as u64)?;
+        Ok(scratch)
+    }
+
+    /// Write `values` starting from position `position`.
+    fn write_at(&mut self, position: usize, values: &[F]) -> io::Result<()> {
+        let mut bytes = Vec::with_capacity(values.len() * self.element_size);
+        for value in values {
+            value
+                .serialize_uncompressed(&mut bytes)
+                .map_err(into_io_error)?;
+        }
+        self.file
+            .seek(SeekFrom::Start((position * self.element_size) as u64))?;
+        self.file.write_all(&bytes)
+    }
+
+    /// Read `count` values starting from position `position`.
+    fn read_at(&mut self, position: usize, count: usize) -> io::Result<Vec<F>> {
+        let mut bytes = vec![0u8; count * self.element_size];
+        self.file
+            .seek(SeekFrom::Start((position * self.element_size) as u64))?;
+        self.file.read_exact(&mut bytes)?;
+        bytes
+            .chunks(self.element_size)
+            .map(|chunk| F::deserialize_uncompressed(chunk).map_err(into_io_error))
+            .collect()
+    }
+}
+
+impl<F> Drop for Scratch<F> {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// The output of [`fft`] and [`ifft`], stored in a temporary file.
+///
+/// The elements are streamed in stream order, the last one first.
+/// The temporary file is removed when the stream is dropped.
+pub struct TransformedStream<F> {
+    scratch: Scratch<F>,
+}
+
+/// The iterator returned by [`TransformedStream`].
+pub struct TransformedStreamIter<F> {
+    reader: BufReader<File>,
+    remaining: usize,
+    _field: PhantomData<F>,
+}
+
+impl<F: Field> Iterator for TransformedStreamIter<F> {
+    type Item = F;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(F::deserialize_uncompressed(&mut self.reader).expect("corrupted transform"))
+    }
+}
+
+impl<F: Field> Iterable for TransformedStream<F> {
+    type Item = F;
+
+    type Iter = TransformedStreamIter<F>;
+
+    /// # Panics
+    /// If the temporary file cannot be opened.
+    fn iter(&self) -> Self::Iter {
+        let file = File::open(&self.scratch.path).expect("missing transform");
+        TransformedStreamIter {
+            reader: BufReader::new(file),
+            remaining: self.scratch.len,
+            _field: PhantomData,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.scratch.len
+    }
+}
+
+/// Transform in place the (natural-order) vector `values`,
+/// whose length is a power of two,
+/// with the primitive root of unity `root` of order `values.len()`.
+fn fft_in_place<F: Field>(values: &mut [F], root: F) {
+    let n = values.len();
+    if n <= 1 {
+        return;
+    }
+    let log_n = n.trailing_zeros();
+    for i in 0..n {
+        let j = i.reverse_bits() >> (usize::BITS - log_n);
+        if i < j {
+            values.swap(i, j);
+        }
+    }
+    let mut half = 1;
+    while half < n {
+        let step = root.pow([(n / (2 * half)) as u64]);
+        for chunk in values.chunks_mut(2 * half) {
+            let (lo, hi) = chunk.split_at_mut(half);
+            let mut twiddle = F::one();
+            for (x, y) in lo.iter_mut().zip(hi) {
+                let t = *y * twiddle;
+                *y = *x - t;
+                *x += t;
+                twiddle *= step;
+            }
+        }
+        half *= 2;
+    }
+}
+
+/// A backend computing the number-theoretic transforms of the external-memory FFTs.
+pub trait NttBackend<F: FftField> {
+    /// Transform in place each of the consecutive vectors of `size` elements of `values`,
+    /// where `size` is a power of two dividing `values.len()`.
+    ///
+    /// Each vector $v$ (in natural order) becomes the vector of $\sum_i v_i \omega^{ik}$ for $k <$ `size`,
+    /// where $\omega$ is the root of unity of order `size` of [`FftField::get_root_of_unity`],
+    /// or its inverse if `inverse`.
+    /// Inverse transforms are not scaled.
+    fn ntt(&self, values: &mut [F], size: usize, inverse: bool);
+}
+
+impl<'a, F: FftField, B: NttBackend<F>> NttBackend<F> for &'a B {
+    fn ntt(&self, values: &mut [F], size: usize, inverse: bool) {
+        (**self).ntt(values, size, inverse)
+    }
+}
+
+impl<F: FftField> NttBackend<F> for CpuBackend {
+    fn ntt(&self, values: &mut [F], size: usize, inverse: bool) {
+        let root = F::get_root_of_unity(size as u64).expect("no root of unity of this order");
+        let root = if inverse {
+            root.inverse().unwrap()
+        } else {
+            root
+        };
+        values
+            .chunks_mut(size)
+            .for_each(|vector| fft_in_place(vector, root));
+    }
+}
+
+/// Return the stream of $\text{scale} \cdot \sum_i v_i \omega^{ik}$ for $k < n$,
+/// where $v$ is padded with zeros to length $n$,
+/// and $\omega$ is the root of unity of order $n$ (or its inverse, if `inverse`).
+fn transform<F, S, B>(
+    stream: &S,
+    n: usize,
+    inverse: bool,
+    scale: F,
+    buffer_size: usize,
+    backend: &B,
+) -> io::Result<TransformedStream<F>>
+where
+    F: FftField,
+    S: Iterable,
+    S::Item: Borrow<F>,
+    B: NttBackend<F>,
+{
+    let transform_time = start_timer!(|| "External-memory FFT");
+    trace_span!("fft", len = n);
+    let len = stream.len();
+    let root = root_of_unity::<F>(n)?;
+    let root = if inverse {
+        root.inverse().unwrap()
+    } else {
+        root
+    };
+    let log_n = n.trailing_zeros();
+    let cols = 1 << ((log_n + 1) / 2);
+    let rows = n / cols;
+
+    // transform the columns, a group of columns for each pass over the stream.
+    let mut columns_file = Scratch::<F>::new(n)?;
+    let group = usize::max(1, buffer_size / rows);
+    for first in (0..cols).step_by(group) {
+        let last = usize::min(first + group, cols);
+        // the columns of the group, one after the other.
+        let mut columns = vec![F::zero(); rows * (last - first)];
+        for (position, x) in stream.iter().take(len).enumerate() {
+            let i = len - 1 - position;
+            let col = i % cols;
+            if (first..last).contains(&col) {
+                columns[(col - first) * rows + i / cols] = *x.borrow();
+            }
+        }
+        backend.ntt(&mut columns, rows, inverse);
+        for (j, column) in (first..last).zip(columns.chunks_mut(rows)) {
+            let step = root.pow([j as u64]);
+            let mut twiddle = F::one();
+            column.iter_mut().for_each(|x| {
+                *x *= twiddle;
+                twiddle *= step;
+            });
+        }
+        columns_file.write_at(first * rows, &columns)?;
+    }
+
+    // transform the rows, a block of rows for each pass over the columns.
+    let mut output = Scratch::<F>::new(n)?;
+    let block = usize::max(1, buffer_size / cols);
+    for first in (0..rows).step_by(block) {
+        let last = usize::min(first + block, rows);
+        // the rows of the block, one after the other.
+        let mut block_rows = vec![F::zero(); cols * (last - first)];
+        for j in 0..cols {
+            let segment = columns_file.read_at(j * rows + first, last - first)?;
+            segment
+                .into_iter()
+                .enumerate()
+                .for_each(|(row, x)| block_rows[row * cols + j] = x);
+        }
+        backend.ntt(&mut block_rows, cols, inverse);
+        // the k-th element of the row is the output of index row + rows * k,
+        // stored in stream order.
+        for k in 0..cols {
+            let segment = block_rows
+                .chunks(cols)
+                .rev()
+                .map(|row| row[k] * scale)
+                .collect::<Vec<_>>();
+            output.write_at(n - last - rows * k, &segment)?;
+        }
+    }
+    end_timer!(transform_time);
+    Ok(TransformedStream { scratch: output })
+}
+
+/// Return the primitive root of unity of order `n`, if any.
+fn root_of_unity<F: FftField>(n: usize) -> io::Result<F> {
+    F::get_root_of_unity(n as u64).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the field has no root of unity of the domain size",
+        )
+    })
+}
+
+/// Evaluate the polynomial with coefficients streamed by `coefficients` (in stream order)
+/// over the multiplicative subgroup of size `coefficients.len().next_power_of_two()`,
+/// holding at most `buffer_size` elements in memory.
+///
+/// Return the stream of the evaluations at $1, \omega, \omega^2, \dots$ in stream order,
+/// that is, starting from the last one,
+/// where $\omega$ is the root of unity of [`FftField::get_root_of_unity`]
+/// (the same of [`ark_poly::Radix2EvaluationDomain`]).
+pub fn fft<F, S>(coefficients: &S, buffer_size: usize) -> io::Result<TransformedStream<F>>
+where
+    F: FftField,
+    S: Iterable,
+    S::Item: Borrow<F>,
+{
+    fft_with(coefficients, buffer_size, &CpuBackend)
+}
+
+/// Same as [`fft`], computing the transforms of the columns and of the rows with `backend`.
+pub fn fft_with<F, S, B>(
+    coefficients: &S,
+    buffer_size: usize,
+    backend: &B,
+) -> io::Result<TransformedStream<F>>
+where
+    F: FftField,
+    S: Iterable,
+    S::Item: Borrow<F>,
+    B: NttBackend<F>,
+{
+    let n = coefficients.len().next_power_of_two();
+    transform(coefficients, n, false, F::one(), buffer_size, backend)
+}
+
+/// The inverse of [`fft`]: interpolate the evaluations streamed by `evaluations` (in stream order)
+/// into the coefficients of the polynomial (in stream order),
+/// holding at most `buffer_size` elements in memory.
+pub fn ifft<F, S>(evaluations: &S, buffer_size: usize) -> io::Result<TransformedStream<F>>
+where
+    F: FftField,
+    S: Iterable,
+    S::Item: Borrow<F>,
+{
+    ifft_with(evaluations, buffer_size, &CpuBackend)
+}
+
+/// Same as [`ifft`], computing the transforms of the columns and of the rows with `backend`.
+pub fn ifft_with<F, S, B>(
+    evaluations: &S,
+    buffer_size: usize,
+    backend: &B,
+) -> io::Result<TransformedStream<F>>
+where
+    F: FftField,
+    S: Iterable,
+    S::Item: Borrow<F>,
+    B: NttBackend<F>,
+{
+    let n = evaluations.len().next_power_of_two();
+    let n_inverse = F::from(n as u64).inverse().unwrap();
+    transform(evaluations, n, true, n_inverse, buffer_size, backend)
+}
+
+#[test]
+fn test_fft() {
+    use ark_bls12_381::Fr;
+    use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+    use ark_std::test_rng;
+    use ark_std::UniformRand;
+
+    use crate::iterable::Reverse;
+
+    let rng = &mut test_rng();
+    for len in [1, 2, 5, 64, 200] {
+        let coefficients = (0..len).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+        let domain = Radix2EvaluationDomain::<Fr>::new(len).unwrap();
+        let evaluations = domain.fft(&coefficients);
+        for buffer_size in [1, 7, 1 << 10] {
+            let stream = fft(&Reverse(coefficients.as_slice()), buffer_size).unwrap();
+            assert_eq!(stream.len(), evaluations.len());
+            assert!(stream.iter().eq(evaluations.iter().rev().copied()));
+
+            let interpolated = ifft(&stream, buffer_size).unwrap();
+            let mut expected = coefficients.clone();
+            expected.resize(evaluations.len(), Fr::from(0u64));
+            assert!(interpolated.iter().eq(expected.iter().rev().copied()));
+        }
+    }
+}
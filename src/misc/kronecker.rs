@@ -0,0 +1,218 @@
+//! Streams of Kronecker products.
+//!
+//! The Kronecker product $v_0 \otimes \dots \otimes v_{k-1}$ of $k$ (small) vectors
+//! has $\prod_j |v_j|$ entries: the entry of index $i$
+//! is $\prod_j v_j[i_j]$, where $i_j$ is the $j$-th digit of $i$
+//! in the mixed radix $(|v_0|, \dots, |v_{k-1}|)$, the first factor being the least significant.
+//! In particular, the Kronecker product of the vectors $(1, \rho_j)$
+//! is the tensor computed by [`tensor`](crate::misc::tensor).
+//!
+//! A [`KroneckerStream`] produces the entries lazily, in stream order (the last entry first),
+//! with an amortized constant number of multiplications per entry,
+//! and holding only (a copy of) the factors in memory.
+use ark_ff::Field;
+use ark_std::borrow::Borrow;
+use ark_std::vec::Vec;
+
+use crate::iterable::{Iterable, RandomAccessIterable};
+
+/// The stream of the Kronecker product of some vectors.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KroneckerStream<F> {
+    factors: Vec<Vec<F>>,
+}
+
+impl<F: Field> KroneckerStream<F> {
+    /// Stream the Kronecker product of `factors`, the first one being the least significant.
+    pub fn new(factors: Vec<Vec<F>>) -> Self {
+        Self { factors }
+    }
+
+    /// Stream the tensor product $\otimes_j (1, \rho_j)$ of the `elements` $\rho_j$.
+    ///
+    /// Differently from [`tensor`](crate::misc::tensor), the vector is never materialized.
+    pub fn tensor(elements: &[F]) -> Self {
+        let factors = elements.iter().map(|&e| vec![F::one(), e]).collect();
+        Self { factors }
+    }
+}
+
+/// The iterator returned by [`KroneckerStream`].
+pub struct KroneckerIter<F> {
+    factors: Vec<Vec<F>>,
+    /// The digits of the next entry.
+    digits: Vec<usize>,
+    /// The `j`-th partial product is the product of the factors from the `j`-th onwards.
+    partial_products: Vec<F>,
+    remaining: usize,
+}
+
+impl<F: Field> KroneckerIter<F> {
+    /// Create an iterator over the first `remaining` entries from the one of index `index`,
+    /// in decreasing order.
+    fn new(factors: Vec<Vec<F>>, index: usize, remaining: usize) -> Self {
+        let mut digits = Vec::with_capacity(factors.len());
+        let mut quotient = index;
+        for factor in &factors {
+            // if the product is empty, no digit will be ever read.
+            let radix = usize::max(factor.len(), 1);
+            digits.push(quotient % radix);
+            quotient /= radix;
+        }
+        let levels = factors.len();
+        let mut iter = Self {
+            factors,
+            digits,
+            partial_products: vec![F::one(); levels + 1],
+            remaining,
+        };
+        if remaining > 0 {
+            iter.update_partial_products(levels);
+        }
+        iter
+    }
+
+    /// Recompute the partial products of the factors below `level`.
+    fn update_partial_products(&mut self, level: usize) {
+        let mut product = self.partial_products[level];
+        let partial_products = self.partial_products[..level]
+            .iter_mut()
+            .zip(&self.factors)
+            .zip(&self.digits);
+        for ((partial_product, factor), &digit) in partial_products.rev() {
+            product *= factor[digit];
+            *partial_product = product;
+        }
+    }
+}
+
+impl<F: Field> Iterator for KroneckerIter<F> {
+    type Item = F;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        let entry = self.partial_products[0];
+        if self.remaining > 0 {
+            // decrement the index: the least significant nonzero digit is decreased,
+            // and the digits below it are set to their maximum.
+            let level = self.digits.iter().position(|&d| d > 0).unwrap();
+            self.digits[level] -= 1;
+            self.digits[..level]
+                .iter_mut()
+                .zip(&self.factors)
+                .for_each(|(digit, factor)| *digit = factor.len() - 1);
+            self.update_partial_products(level + 1);
+        }
+        Some(entry)
+    }
+}
+
+impl<F: Field> Iterable for KroneckerStream<F> {
+    type Item = F;
+
+    type Iter = KroneckerIter<F>;
+
+    fn iter(&self) -> Self::Iter {
+        self.iter_from(0)
+    }
+
+    fn len(&self) -> usize {
+        self.factors.iter().map(|factor| factor.len()).product()
+    }
+}
+
+impl<F: Field> RandomAccessIterable for KroneckerStream<F> {
+    fn iter_from(&self, idx: usize) -> Self::Iter {
+        let len = self.len();
+        let remaining = len.saturating_sub(idx);
+        // the entry at position `idx` (in stream order) has index `len - 1 - idx`.
+        let index = if remaining > 0 { len - 1 - idx } else { 0 };
+        KroneckerIter::new(self.factors.clone(), index, remaining)
+    }
+}
+
+/// Return the scalar product of the stream `stream` with the Kronecker product `kronecker`,
+/// without materializing either.
+///
+/// If the streams have different length,
+/// the most significant elements of the longest one are ignored.
+pub fn contract<F, S>(stream: &S, kronecker: &KroneckerStream<F>) -> F
+where
+    F: Field,
+    S: Iterable,
+    S::Item: Borrow<F>,
+{
+    let len = usize::min(stream.len(), kronecker.len());
+    stream
+        .iter()
+        .skip(stream.len() - len)
+        .zip(kronecker.iter_from(kronecker.len() - len))
+        .map(|(x, y)| *x.borrow() * y)
+        .sum()
+}
+
+#[test]
+fn test_kronecker_stream() {
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+    use ark_std::UniformRand;
+
+    use crate::misc::tensor;
+
+    let rng = &mut test_rng();
+    let elements = (0..5).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let stream = KroneckerStream::tensor(&elements);
+    let expected = tensor(&elements);
+    assert_eq!(stream.len(), expected.len());
+    assert!(stream.iter().eq(expected.iter().rev().copied()));
+    for idx in [0, 1, 7, 31, 32, 40] {
+        assert!(stream
+            .iter_from(idx)
+            .eq(expected.iter().rev().skip(idx).copied()));
+    }
+
+    // factors of different length, including zeros.
+    let factors = vec![
+        vec![Fr::from(2u64), Fr::from(0u64), Fr::from(3u64)],
+        vec![Fr::from(5u64)],
+        vec![Fr::from(7u64), Fr::from(11u64)],
+    ];
+    let mut expected = Vec::new();
+    for c in &factors[2] {
+        for b in &factors[1] {
+            for a in &factors[0] {
+                expected.push(*a * b * c);
+            }
+        }
+    }
+    let stream = KroneckerStream::new(factors);
+    assert!(stream.iter().eq(expected.iter().rev().copied()));
+
+    assert_eq!(KroneckerStream::<Fr>::new(vec![]).iter().count(), 1);
+    assert_eq!(
+        KroneckerStream::new(vec![vec![Fr::from(1u64)], vec![]])
+            .iter()
+            .count(),
+        0
+    );
+}
+
+#[test]
+fn test_contract() {
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+    use ark_std::UniformRand;
+
+    use crate::iterable::Reverse;
+    use crate::misc::{ip, tensor};
+
+    let rng = &mut test_rng();
+    let elements = (0..4).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let vector = (0..10).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let kronecker = KroneckerStream::tensor(&elements);
+    let expected = ip(&vector, &tensor(&elements)[..10]);
+    assert_eq!(contract(&Reverse(vector.as_slice()), &kronecker), expected);
+}
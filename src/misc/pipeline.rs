@@ -0,0 +1,162 @@
+//! Double-buffered pipelines, overlapping reading a stream with computing on it.
+//!
+//! Proving over a disk-backed stream alternates between reading (and deserializing) the stream,
+//! and the field and group arithmetic over what has been read.
+//! A [`pipeline`] splits the two: a background thread produces the chunks of the stream,
+//! while the current thread consumes them.
+//! The producer reads the next chunk while the consumer computes on the current one,
+//! and hands it over only once the consumer is done: at most two chunks are held at once,
+//! besides those already consumed.
+//! Consumed chunks are handed back to the producer (see [`Producer::chunk`]),
+//! so that, once the pipeline is running, chunks are refilled instead of allocated.
+//! Without the `std` feature, and on `wasm32` targets (where threads cannot be spawned),
+//! the chunks are produced and consumed in turn, on the current thread, as in [`sequential`].
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+use std::sync::mpsc::{channel, sync_channel, Receiver, SyncSender};
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+use std::thread;
+
+/// The producing end of a [`pipeline`].
+pub struct Producer<'a, T>(Inner<'a, T>);
+
+enum Inner<'a, T> {
+    /// Send the chunks to another thread, and receive them back once consumed.
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    Thread {
+        sender: SyncSender<T>,
+        consumed: Receiver<T>,
+    },
+    /// Consume the chunks as soon as they are sent.
+    Inline {
+        consume: &'a mut dyn FnMut(&mut T),
+        consumed: Option<T>,
+    },
+}
+
+impl<'a, T: Default> Producer<'a, T> {
+    /// Return a chunk to fill: a consumed chunk, if any, or else a new one.
+    ///
+    /// Consumed chunks are returned as they were consumed, and must be cleared before being refilled.
+    pub fn chunk(&mut self) -> T {
+        match &mut self.0 {
+            #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+            Inner::Thread { consumed, .. } => consumed.try_recv().unwrap_or_default(),
+            Inner::Inline { consumed, .. } => consumed.take().unwrap_or_default(),
+        }
+    }
+
+    /// Send `chunk` to the consumer.
+    ///
+    /// Return `false` if the consumer is gone, in which case the producer should return.
+    pub fn send(&mut self, mut chunk: T) -> bool {
+        match &mut self.0 {
+            #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+            Inner::Thread { sender, .. } => sender.send(chunk).is_ok(),
+            Inner::Inline { consume, consumed } => {
+                consume(&mut chunk);
+                *consumed = Some(chunk);
+                true
+            }
+        }
+    }
+}
+
+/// Run `produce` on a background thread, and `consume` each chunk it sends on the current thread.
+#[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+pub fn pipeline<T, P, C>(produce: P, mut consume: C)
+where
+    T: Default + Send,
+    P: FnOnce(&mut Producer<'_, T>) + Send,
+    C: FnMut(&mut T),
+{
+    thread::scope(|scope| {
+        // a rendezvous channel: each chunk is handed over once the previous one is consumed.
+        let (sender, receiver) = sync_channel(0);
+        let (recycle, consumed) = channel();
+        scope.spawn(move || produce(&mut Producer(Inner::Thread { sender, consumed })));
+        for mut chunk in receiver {
+            consume(&mut chunk);
+            // the producer may be done already.
+            let _ = recycle.send(chunk);
+        }
+    })
+}
+
+/// Run `produce` and `consume` each chunk it sends as soon as it is sent.
+#[cfg(any(not(feature = "std"), target_arch = "wasm32"))]
+pub fn pipeline<T, P, C>(produce: P, consume: C)
+where
+    T: Default + Send,
+    P: FnOnce(&mut Producer<'_, T>) + Send,
+    C: FnMut(&mut T),
+{
+    sequential(produce, consume)
+}
+
+/// Run `produce` and `consume` each chunk it sends as soon as it is sent, on the current thread.
+///
+/// This is a [`pipeline`] for short streams, not worth a thread.
+pub fn sequential<T, P, C>(produce: P, mut consume: C)
+where
+    T: Default,
+    P: FnOnce(&mut Producer<'_, T>),
+    C: FnMut(&mut T),
+{
+    produce(&mut Producer(Inner::Inline {
+        consume: &mut consume,
+        consumed: None,
+    }))
+}
+
+#[test]
+fn test_pipeline() {
+    use ark_std::vec::Vec;
+
+    let produce = |producer: &mut Producer<'_, Vec<usize>>| {
+        for i in 0..10 {
+            let mut chunk = producer.chunk();
+            chunk.clear();
+            chunk.extend(0..i);
+            if !producer.send(chunk) {
+                return;
+            }
+        }
+    };
+    let expected = (0..10).map(|i| (0..i).collect()).collect::<Vec<Vec<_>>>();
+
+    let mut chunks = Vec::new();
+    pipeline(produce, |chunk| chunks.push(chunk.clone()));
+    assert_eq!(chunks, expected);
+
+    let mut chunks = Vec::new();
+    sequential(produce, |chunk| chunks.push(chunk.clone()));
+    assert_eq!(chunks, expected);
+
+    // consumed chunks are handed back to the producer.
+    sequential(
+        |producer: &mut Producer<'_, Vec<usize>>| {
+            assert!(producer.send(vec![1, 2]));
+            assert_eq!(producer.chunk(), vec![1, 2, 3]);
+            assert!(producer.chunk().is_empty());
+        },
+        |chunk| chunk.push(3),
+    );
+
+    // the producer stops once the consumer is gone,
+    // e.g. if the consumer panics.
+    #[cfg(all(feature = "std", not(target_arch = "wasm32")))]
+    {
+        let result = std::panic::catch_unwind(|| {
+            pipeline(
+                |producer: &mut Producer<'_, usize>| {
+                    let mut i = 0;
+                    while producer.send(i) {
+                        i += 1;
+                    }
+                },
+                |&mut i| assert!(i < 5),
+            )
+        });
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,469 @@
+//! Streaming import of circuits compiled with [Noir](https://noir-lang.org/),
+//! from their intermediate representation, ACIR.
+//!
+//! The ACIR opcodes are lowered to R1CS constraints on the fly:
+//! - `AssertZero` opcodes with at most one multiplication term become one constraint,
+//!     while opcodes with $m > 1$ multiplication terms become $m + 1$ constraints,
+//!     with one auxiliary variable for each product;
+//! - `RANGE` black-box calls over $n$ bits become $n + 1$ constraints,
+//!     with one auxiliary (boolean) variable for each bit;
+//! - `BrilligCall` and `Directive` opcodes only compute the witness, and are skipped.
+//!
+//! Other opcodes (memory, calls, and the remaining black-box functions) are not supported,
+//! and programs must be compiled to a single function.
+//!
+//! Variable 0 is the constant one, followed by the public parameters and the return values,
+//! by the other ACIR witnesses, in increasing order, and by the auxiliary variables:
+//! hence, the variables are laid out exactly as the vector $\vec z = (\vec x, \vec w)$.
+//! Only the offsets of the lowered opcodes are kept in memory.
+//! Matrix streams are row-major, and can be fed directly to the psnark indexer;
+//! column-major streams can be obtained with [`transpose`](crate::iterable::transpose::transpose).
+use acir::circuit::opcodes::BlackBoxFuncCall;
+use acir::circuit::{Circuit, Opcode};
+use acir::native_types::{Expression, Witness, WitnessMap};
+use acir::AcirField;
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+
+use crate::circuit::R1cs;
+use crate::errors::{GeminiError, GeminiResult};
+use crate::iterable::Iterable;
+use crate::misc::MatrixElement;
+
+/// A constraint, as its rows in $A$, $B$, $C$.
+type Constraint<F> = [Vec<(F, usize)>; 3];
+
+/// An opcode that contributes constraints.
+#[derive(Clone, Copy)]
+struct Gadget {
+    /// The index of the opcode.
+    opcode: usize,
+    /// The first auxiliary variable.
+    aux: usize,
+}
+
+/// Convert an ACIR field element into a field element of `F`.
+fn convert<F: PrimeField, A: AcirField>(value: A) -> F {
+    F::from_be_bytes_mod_order(&value.to_be_bytes())
+}
+
+/// Merge the terms on the same variable, and sort them by variable.
+fn normalize<F: PrimeField>(mut row: Vec<(F, usize)>) -> Vec<(F, usize)> {
+    row.sort_unstable_by_key(|&(_, col)| col);
+    let mut merged: Vec<(F, usize)> = Vec::with_capacity(row.len());
+    for (coeff, col) in row {
+        match merged.last_mut() {
+            Some(last) if last.1 == col => last.0 += coeff,
+            _ => merged.push((coeff, col)),
+        }
+    }
+    merged.retain(|(coeff, _)| !coeff.is_zero());
+    merged
+}
+
+/// The number of constraints and of auxiliary variables of `opcode`,
+/// or `None` if it contributes no constraint.
+fn gadget_size<A: AcirField>(opcode: &Opcode<A>) -> GeminiResult<Option<(usize, usize)>> {
+    match opcode {
+        Opcode::AssertZero(expr) => match expr.mul_terms.len() {
+            0 | 1 => Ok(Some((1, 0))),
+            m => Ok(Some((m + 1, m))),
+        },
+        Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }) => {
+            let num_bits = input.num_bits as usize;
+            Ok(Some((num_bits + 1, num_bits)))
+        }
+        Opcode::BrilligCall { .. } | Opcode::Directive(_) => Ok(None),
+        _ => Err(GeminiError::MalformedInstance {
+            reason: "unsupported ACIR opcode",
+        }),
+    }
+}
+
+/// The constraints of an ACIR circuit.
+pub struct AcirR1cs<'a, F, A: AcirField> {
+    circuit: &'a Circuit<A>,
+    /// The position in $\vec z$ of each ACIR witness.
+    positions: Vec<usize>,
+    /// The opcodes contributing constraints.
+    gadgets: Vec<Gadget>,
+    num_statement: usize,
+    num_variables: usize,
+    num_constraints: usize,
+    _field: PhantomData<F>,
+}
+
+impl<'a, F: PrimeField, A: AcirField> AcirR1cs<'a, F, A> {
+    /// Index the opcodes of `circuit`,
+    /// failing if any of them is not supported.
+    pub fn new(circuit: &'a Circuit<A>) -> GeminiResult<Self> {
+        let num_witnesses = circuit.current_witness_index as usize + 1;
+        let mut public = circuit
+            .public_parameters
+            .0
+            .iter()
+            .chain(circuit.return_values.0.iter())
+            .map(|w| w.witness_index() as usize)
+            .collect::<Vec<_>>();
+        public.sort_unstable();
+        public.dedup();
+
+        let mut positions = vec![0; num_witnesses];
+        let mut is_public = vec![false; num_witnesses];
+        for (i, &witness) in public.iter().enumerate() {
+            positions[witness] = 1 + i;
+            is_public[witness] = true;
+        }
+        let mut next = 1 + public.len();
+        for (position, _) in positions
+            .iter_mut()
+            .zip(is_public)
+            .filter(|(_, is_public)| !is_public)
+        {
+            *position = next;
+            next += 1;
+        }
+
+        let mut gadgets = Vec::new();
+        let mut num_rows = 0;
+        for (i, opcode) in circuit.opcodes.iter().enumerate() {
+            if let Some((num_constraints, num_aux)) = gadget_size(opcode)? {
+                gadgets.push(Gadget {
+                    opcode: i,
+                    aux: next,
+                });
+                num_rows += num_constraints;
+                next += num_aux;
+            }
+        }
+        Ok(AcirR1cs {
+            circuit,
+            positions,
+            gadgets,
+            num_statement: 1 + public.len(),
+            num_variables: next,
+            num_constraints: num_rows,
+            _field: PhantomData,
+        })
+    }
+
+    /// The number of variables, that is, the length of $\vec z$.
+    pub fn num_variables(&self) -> usize {
+        self.num_variables
+    }
+
+    /// The length of the public input $\vec x$, including the constant one.
+    pub fn num_statement(&self) -> usize {
+        self.num_statement
+    }
+
+    /// The number of constraints.
+    pub fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+
+    /// The row-major stream of the matrix $A$.
+    pub fn a(&self) -> AcirMatrix<'_, 'a, F, A> {
+        AcirMatrix {
+            r1cs: self,
+            index: 0,
+        }
+    }
+
+    /// The row-major stream of the matrix $B$.
+    pub fn b(&self) -> AcirMatrix<'_, 'a, F, A> {
+        AcirMatrix {
+            r1cs: self,
+            index: 1,
+        }
+    }
+
+    /// The row-major stream of the matrix $C$.
+    pub fn c(&self) -> AcirMatrix<'_, 'a, F, A> {
+        AcirMatrix {
+            r1cs: self,
+            index: 2,
+        }
+    }
+
+    fn position(&self, witness: &Witness) -> usize {
+        self.positions[witness.witness_index() as usize]
+    }
+
+    fn linear_combination(&self, expr: &Expression<A>) -> Vec<(F, usize)> {
+        let mut row = expr
+            .linear_combinations
+            .iter()
+            .map(|(coeff, w)| (convert(*coeff), self.position(w)))
+            .collect::<Vec<_>>();
+        row.push((convert(expr.q_c), 0));
+        row
+    }
+
+    /// Lower the opcode of `gadget` into its constraints.
+    fn lower(&self, gadget: &Gadget) -> Vec<Constraint<F>> {
+        let one = || vec![(F::one(), 0)];
+        match &self.circuit.opcodes[gadget.opcode] {
+            Opcode::AssertZero(expr) if expr.mul_terms.is_empty() => {
+                vec![[self.linear_combination(expr), one(), Vec::new()]]
+            }
+            Opcode::AssertZero(expr) if expr.mul_terms.len() == 1 => {
+                let (q, wi, wj) = &expr.mul_terms[0];
+                let c = self
+                    .linear_combination(expr)
+                    .into_iter()
+                    .map(|(coeff, col)| (-coeff, col))
+                    .collect();
+                vec![[
+                    vec![(convert(*q), self.position(wi))],
+                    vec![(F::one(), self.position(wj))],
+                    c,
+                ]]
+            }
+            Opcode::AssertZero(expr) => {
+                let mut constraints = Vec::new();
+                let mut sum = self.linear_combination(expr);
+                for (k, (q, wi, wj)) in expr.mul_terms.iter().enumerate() {
+                    constraints.push([
+                        vec![(convert(*q), self.position(wi))],
+                        vec![(F::one(), self.position(wj))],
+                        vec![(F::one(), gadget.aux + k)],
+                    ]);
+                    sum.push((F::one(), gadget.aux + k));
+                }
+                constraints.push([sum, one(), Vec::new()]);
+                constraints
+            }
+            Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }) => {
+                let num_bits = input.num_bits as usize;
+                let mut constraints = Vec::new();
+                let mut sum = vec![(-F::one(), self.position(&input.witness))];
+                let mut power = F::one();
+                for k in 0..num_bits {
+                    let bit = vec![(F::one(), gadget.aux + k)];
+                    constraints.push([bit.clone(), bit.clone(), bit]);
+                    sum.push((power, gadget.aux + k));
+                    power.double_in_place();
+                }
+                constraints.push([sum, one(), Vec::new()]);
+                constraints
+            }
+            _ => unreachable!("the opcode contributes no constraint"),
+        }
+        .into_iter()
+        .map(|rows| rows.map(normalize))
+        .collect()
+    }
+
+    /// The vector $\vec z$, given the ACIR witnesses `witness`,
+    /// as returned by the Noir execution.
+    ///
+    /// Fails if a witness is missing, or is out of its range.
+    pub fn z(&self, witness: &WitnessMap<A>) -> GeminiResult<Vec<F>> {
+        let value = |w: &Witness| -> GeminiResult<A> {
+            witness
+                .get(w)
+                .copied()
+                .ok_or(GeminiError::MalformedInstance {
+                    reason: "missing ACIR witness",
+                })
+        };
+        let mut z = vec![F::zero(); self.num_variables];
+        z[0] = F::one();
+        for (i, &position) in self.positions.iter().enumerate() {
+            z[position] = witness
+                .get(&Witness(i as u32))
+                .map_or(F::zero(), |&v| convert(v));
+        }
+        for gadget in &self.gadgets {
+            match &self.circuit.opcodes[gadget.opcode] {
+                Opcode::AssertZero(expr) if expr.mul_terms.len() > 1 => {
+                    for (k, (q, wi, wj)) in expr.mul_terms.iter().enumerate() {
+                        z[gadget.aux + k] = convert(*q * value(wi)? * value(wj)?);
+                    }
+                }
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::RANGE { input }) => {
+                    let num_bits = input.num_bits as usize;
+                    let bits = convert::<F, A>(value(&input.witness)?)
+                        .into_bigint()
+                        .to_bits_le();
+                    if bits.iter().skip(num_bits).any(|&bit| bit) {
+                        return Err(GeminiError::MalformedInstance {
+                            reason: "ACIR witness out of range",
+                        });
+                    }
+                    for (k, bit) in bits.into_iter().take(num_bits).enumerate() {
+                        z[gadget.aux + k] = F::from(bit);
+                    }
+                }
+                _ => (),
+            }
+        }
+        Ok(z)
+    }
+
+    /// The (in-memory) R1CS instance, given the ACIR witnesses `witness`.
+    pub fn relation(&self, witness: &WitnessMap<A>) -> GeminiResult<R1cs<F>> {
+        let z = self.z(witness)?;
+        let mut a = Vec::with_capacity(self.num_constraints);
+        let mut b = Vec::with_capacity(self.num_constraints);
+        let mut c = Vec::with_capacity(self.num_constraints);
+        for gadget in &self.gadgets {
+            for [row_a, row_b, row_c] in self.lower(gadget) {
+                a.push(row_a);
+                b.push(row_b);
+                c.push(row_c);
+            }
+        }
+        Ok(R1cs {
+            a,
+            b,
+            c,
+            x: z[..self.num_statement].to_vec(),
+            w: z[self.num_statement..].to_vec(),
+            z,
+        })
+    }
+}
+
+/// The row-major stream of one of the matrices of an [`AcirR1cs`],
+/// with one line per constraint.
+pub struct AcirMatrix<'b, 'a, F, A: AcirField> {
+    r1cs: &'b AcirR1cs<'a, F, A>,
+    index: usize,
+}
+
+impl<'b, 'a, F, A: AcirField> Clone for AcirMatrix<'b, 'a, F, A> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'b, 'a, F, A: AcirField> Copy for AcirMatrix<'b, 'a, F, A> {}
+
+/// The iterator returned by [`AcirMatrix`].
+pub struct AcirMatrixIter<'b, 'a, F, A: AcirField> {
+    r1cs: &'b AcirR1cs<'a, F, A>,
+    index: usize,
+    /// The number of opcodes left to lower.
+    gadgets: usize,
+    /// The elements of the current opcode, in reversed order.
+    buffer: Vec<MatrixElement<F>>,
+}
+
+impl<'b, 'a, F: PrimeField, A: AcirField> Iterator for AcirMatrixIter<'b, 'a, F, A> {
+    type Item = MatrixElement<F>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(e) = self.buffer.pop() {
+                return Some(e);
+            }
+            if self.gadgets == 0 {
+                return None;
+            }
+            self.gadgets -= 1;
+            let gadget = self.r1cs.gadgets[self.gadgets];
+            for mut rows in self.r1cs.lower(&gadget) {
+                let row = core::mem::take(&mut rows[self.index]);
+                self.buffer.push(MatrixElement::EOL);
+                self.buffer
+                    .extend(row.into_iter().map(MatrixElement::Element));
+            }
+        }
+    }
+}
+
+impl<'b, 'a, F: PrimeField, A: AcirField> Iterable for AcirMatrix<'b, 'a, F, A> {
+    type Item = MatrixElement<F>;
+
+    type Iter = AcirMatrixIter<'b, 'a, F, A>;
+
+    fn iter(&self) -> Self::Iter {
+        AcirMatrixIter {
+            r1cs: self.r1cs,
+            index: self.index,
+            gadgets: self.r1cs.gadgets.len(),
+            buffer: Vec::new(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.r1cs.num_constraints
+    }
+}
+
+#[test]
+fn test_acir_import() {
+    use acir::circuit::opcodes::FunctionInput;
+    use acir::circuit::PublicInputs;
+    use acir::FieldElement;
+    use ark_bls12_381::Fr;
+    use std::collections::BTreeSet;
+
+    use crate::circuit::matrix_into_rowmaj;
+    use crate::misc::product_matrix_vector;
+
+    // w0 * w1 - w2 = 0, w0 * w0 + 2 * w1 * w2 - w3 + 1 = 0, and w1 < 2^4.
+    let fe = |n: i128| FieldElement::from(n);
+    let first = Expression {
+        mul_terms: vec![(fe(1), Witness(0), Witness(1))],
+        linear_combinations: vec![(fe(-1), Witness(2))],
+        q_c: fe(0),
+    };
+    let second = Expression {
+        mul_terms: vec![
+            (fe(1), Witness(0), Witness(0)),
+            (fe(2), Witness(1), Witness(2)),
+        ],
+        linear_combinations: vec![(fe(-1), Witness(3))],
+        q_c: fe(1),
+    };
+    let range = BlackBoxFuncCall::RANGE {
+        input: FunctionInput {
+            witness: Witness(1),
+            num_bits: 4,
+        },
+    };
+    let circuit = Circuit {
+        current_witness_index: 3,
+        opcodes: vec![
+            Opcode::AssertZero(first),
+            Opcode::AssertZero(second),
+            Opcode::BlackBoxFuncCall(range),
+        ],
+        private_parameters: BTreeSet::from([Witness(0), Witness(1)]),
+        return_values: PublicInputs(BTreeSet::from([Witness(3)])),
+        ..Circuit::default()
+    };
+    let mut witness = WitnessMap::new();
+    for (i, value) in [3, 5, 15, 160].into_iter().enumerate() {
+        witness.insert(Witness(i as u32), fe(value));
+    }
+
+    let acir_r1cs = AcirR1cs::<Fr, _>::new(&circuit).unwrap();
+    assert_eq!(acir_r1cs.num_constraints(), 1 + 3 + 5);
+    assert_eq!(acir_r1cs.num_statement(), 2);
+    assert_eq!(acir_r1cs.num_variables(), 1 + 4 + 2 + 4);
+
+    let r1cs = acir_r1cs.relation(&witness).unwrap();
+    assert_eq!(r1cs.x, vec![Fr::from(1u64), Fr::from(160u64)]);
+    let z_a = product_matrix_vector(&r1cs.a, &r1cs.z);
+    let z_b = product_matrix_vector(&r1cs.b, &r1cs.z);
+    let z_c = product_matrix_vector(&r1cs.c, &r1cs.z);
+    for ((a, b), c) in z_a.iter().zip(&z_b).zip(&z_c) {
+        assert_eq!(*a * b, *c);
+    }
+    assert_eq!(
+        acir_r1cs.a().iter().collect::<Vec<_>>(),
+        matrix_into_rowmaj(&r1cs.a)
+    );
+    assert_eq!(
+        acir_r1cs.c().iter().collect::<Vec<_>>(),
+        matrix_into_rowmaj(&r1cs.c)
+    );
+
+    witness.insert(Witness(1), fe(16));
+    assert!(acir_r1cs.z(&witness).is_err());
+}
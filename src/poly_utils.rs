@@ -0,0 +1,175 @@
+//! Utilities for polynomials given as vectors or as streams.
+//!
+//! Across the crate, vectors hold the coefficients of a polynomial in little-endian order
+//! (the constant term first), while streams yield them in big-endian order
+//! (the constant term last), as most streams are produced
+//! by the provers from the most significant coefficient down.
+//! For instance, the polynomial $f(x) = f_0 + f_1 x + f_2 x^2$
+//! is the vector `vec![f0, f1, f2]`, and the stream `Reverse(&[f0, f1, f2][..])`.
+//!
+//! Streams of different length are aligned on their last (least significant) element:
+//! the first element of a stream of length $n$ has index $n-1$.
+use ark_ff::Field;
+use ark_std::borrow::Borrow;
+use ark_std::vec::Vec;
+
+use crate::iterable::Iterable;
+
+pub use crate::misc::{evaluate_be, evaluate_be_at, evaluate_le, evaluate_le_at, ip, powers};
+
+/// Evaluate at `x` the polynomial whose coefficients are streamed by `polynomial`,
+/// with Horner's rule.
+pub fn evaluate_stream<F, S>(polynomial: &S, x: &F) -> F
+where
+    F: Field,
+    S: Iterable,
+    S::Item: Borrow<F>,
+{
+    evaluate_be(polynomial.iter(), x)
+}
+
+/// Evaluate at each of the `points` the polynomial whose coefficients are streamed by `polynomial`,
+/// within a single pass over the stream.
+pub fn evaluate_stream_at<F, S>(polynomial: &S, points: &[F]) -> Vec<F>
+where
+    F: Field,
+    S: Iterable,
+    S::Item: Borrow<F>,
+{
+    evaluate_be_at(polynomial.iter(), points)
+}
+
+/// Return the scalar product of the streams `lhs` and `rhs`.
+///
+/// If the streams have different length,
+/// the most significant elements of the longest one are ignored.
+pub fn scalar_product_stream<F, S1, S2>(lhs: &S1, rhs: &S2) -> F
+where
+    F: Field,
+    S1: Iterable,
+    S2: Iterable,
+    S1::Item: Borrow<F>,
+    S2::Item: Borrow<F>,
+{
+    let len = usize::min(lhs.len(), rhs.len());
+    lhs.iter()
+        .skip(lhs.len() - len)
+        .zip(rhs.iter().skip(rhs.len() - len))
+        .map(|(x, y)| *x.borrow() * y.borrow())
+        .sum()
+}
+
+/// The stream of the first `len` powers of an element, in stream order:
+/// $x^{\text{len}-1}, \dots, x, 1$.
+///
+/// It is the stream counterpart of [`powers`].
+#[derive(Clone, Copy, Debug)]
+pub struct PowersStream<F> {
+    element: F,
+    len: usize,
+}
+
+impl<F: Field> PowersStream<F> {
+    /// Stream the first `len` powers of `element`.
+    pub fn new(element: F, len: usize) -> Self {
+        Self { element, len }
+    }
+}
+
+/// The iterator returned by [`PowersStream`].
+pub struct PowersIter<F> {
+    current: F,
+    step: F,
+    remaining: usize,
+}
+
+impl<F: Field> Iterator for PowersIter<F> {
+    type Item = F;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        // the last power is 1, even when the element is zero.
+        let power = if self.remaining == 0 {
+            F::one()
+        } else {
+            self.current
+        };
+        self.current *= self.step;
+        Some(power)
+    }
+}
+
+impl<F: Field> Iterable for PowersStream<F> {
+    type Item = F;
+
+    type Iter = PowersIter<F>;
+
+    fn iter(&self) -> Self::Iter {
+        let exponent = self.len.saturating_sub(1) as u64;
+        PowersIter {
+            current: self.element.pow([exponent]),
+            // the powers of zero are zero, but for the last one.
+            step: self.element.inverse().unwrap_or_else(F::zero),
+            remaining: self.len,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[test]
+fn test_stream_evaluations() {
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+    use ark_std::UniformRand;
+
+    use crate::iterable::Reverse;
+
+    let rng = &mut test_rng();
+    let polynomial = (0..100).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let points = (0..3).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let stream = Reverse(polynomial.as_slice());
+
+    let evaluations = evaluate_stream_at(&stream, &points);
+    for (x, evaluation) in points.iter().zip(evaluations) {
+        assert_eq!(evaluate_stream(&stream, x), evaluate_le(&polynomial, x));
+        assert_eq!(evaluation, evaluate_le(&polynomial, x));
+    }
+}
+
+#[test]
+fn test_stream_scalar_product() {
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+    use ark_std::UniformRand;
+
+    use crate::iterable::Reverse;
+
+    let rng = &mut test_rng();
+    let lhs = (0..100).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let rhs = (0..60).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let expected = ip(&lhs[..60], &rhs);
+    let lhs_stream = Reverse(lhs.as_slice());
+    let rhs_stream = Reverse(rhs.as_slice());
+    assert_eq!(scalar_product_stream(&lhs_stream, &rhs_stream), expected);
+    assert_eq!(scalar_product_stream(&rhs_stream, &lhs_stream), expected);
+}
+
+#[test]
+fn test_powers_stream() {
+    use ark_bls12_381::Fr;
+    use ark_ff::Zero;
+
+    for element in [Fr::from(3u64), Fr::zero()] {
+        for len in [0, 1, 10] {
+            let stream = PowersStream::new(element, len);
+            assert_eq!(stream.len(), len);
+            assert!(stream.iter().eq(powers(element, len).into_iter().rev()));
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! The arkworks [`SNARK`] interface of the preprocessing SNARK.
+//!
+//! The setup indexes the circuit: the prover keeps the index cache,
+//! and the verifier only the commitments to the index
+//! (see [`IndexProverKey`] and [`IndexVerifierKey`]).
+use ark_ec::pairing::Pairing;
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_std::marker::PhantomData;
+use ark_std::rand::{CryptoRng, RngCore};
+
+use crate::circuit::{generate_index, statement, try_generate_relation, R1csStreams};
+use crate::errors::{GeminiError, GeminiResult};
+use crate::psnark::{generate_index_keys, IndexProverKey, IndexVerifierKey, Proof, R1csStats};
+
+/// The preprocessing SNARK over the pairing `E`, as an arkworks [`SNARK`].
+pub struct GeminiPsnark<E: Pairing>(PhantomData<E>);
+
+/// The proving key of [`GeminiPsnark`].
+pub type ProvingKey<E> = IndexProverKey<E>;
+
+/// The verifying key of [`GeminiPsnark`].
+pub type VerifyingKey<E> = IndexVerifierKey<E>;
+
+impl<E: Pairing> SNARK<E::ScalarField> for GeminiPsnark<E> {
+    type ProvingKey = ProvingKey<E>;
+    type VerifyingKey = VerifyingKey<E>;
+    type Proof = Proof<E>;
+    type ProcessedVerifyingKey = VerifyingKey<E>;
+    type Error = GeminiError;
+
+    fn circuit_specific_setup<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore + CryptoRng>(
+        circuit: C,
+        rng: &mut R,
+    ) -> GeminiResult<(ProvingKey<E>, VerifyingKey<E>)> {
+        let index = generate_index(circuit)?;
+        let stats = R1csStats::from_stream(&R1csStreams::new(&index).stream());
+        let ck = stats.committer_key(rng);
+        Ok(generate_index_keys(&index, ck))
+    }
+
+    /// Prove with the time-efficient prover: the proof is deterministic, and `rng` is not used.
+    fn prove<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore + CryptoRng>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        _rng: &mut R,
+    ) -> GeminiResult<Proof<E>> {
+        let r1cs = try_generate_relation(circuit)?;
+        Proof::new_time_indexed(&r1cs, pk)
+    }
+
+    fn process_vk(vk: &VerifyingKey<E>) -> GeminiResult<VerifyingKey<E>> {
+        Ok(vk.clone())
+    }
+
+    /// Verify `proof` for the public input `public_input`,
+    /// which excludes the constant one and may omit the padding.
+    fn verify_with_processed_vk(
+        pvk: &VerifyingKey<E>,
+        public_input: &[E::ScalarField],
+        proof: &Proof<E>,
+    ) -> GeminiResult<bool> {
+        let x = statement(public_input, pvk.num_instance)?;
+        Ok(proof.verify_indexed(&x, pvk).is_ok())
+    }
+}
+
+impl<E: Pairing> CircuitSpecificSetupSNARK<E::ScalarField> for GeminiPsnark<E> {}
+
+#[test]
+fn test_psnark_trait() {
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+    use ark_std::One;
+
+    use crate::circuit::random_circuit;
+
+    let rng = &mut test_rng();
+    let circuit = random_circuit::<Fr>(rng, 16, 16);
+    let public_input = try_generate_relation(circuit).unwrap().x[1..].to_vec();
+
+    let (pk, vk) = GeminiPsnark::<Bls12_381>::setup(circuit, rng).unwrap();
+    let proof = GeminiPsnark::prove(&pk, circuit, rng).unwrap();
+    assert!(GeminiPsnark::verify(&vk, &public_input, &proof).unwrap());
+
+    let mut wrong_input = public_input.clone();
+    wrong_input[0] += Fr::one();
+    assert!(!GeminiPsnark::verify(&vk, &wrong_input, &proof).unwrap());
+}
@@ -0,0 +1,124 @@
+//! Preprocessing of the R1CS index, shared across proofs.
+//!
+//! The time-efficient prover derives from the index (i.e., the matrices $A$, $B$, $C$)
+//! the joint matrix, the frequencies of the row and column indices,
+//! and the committer keys indexed by them.
+//! None of these depend on the witness,
+//! hence they can be computed once and reused by every proof for the same index.
+use ark_ec::pairing::Pairing;
+use ark_serialize::*;
+use ark_std::vec::Vec;
+#[cfg(feature = "std")]
+use std::{fs::File, io, path::Path};
+
+use crate::circuit::R1cs;
+use crate::kzg::{Commitment, CommitterKey};
+use crate::misc::{joint_matrices, sum_matrices};
+use crate::subprotocols::plookup::time_prover::{compute_frequency, extend_frequency};
+
+/// The preprocessed index of the preprocessing SNARK,
+/// built once with [`IndexCache::new`] and passed to [`Proof::new_time_cached`](super::Proof::new_time_cached).
+///
+/// The cache refers to the padded instance (see [`R1cs::padded`]),
+/// and holds the committer keys indexed by the joint matrix:
+/// it can be persisted to disk alongside the index commitments with [`IndexCache::save`].
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone)]
+pub struct IndexCache<E: Pairing> {
+    pub(crate) num_constraints: usize,
+    pub(crate) num_variables: usize,
+    pub(crate) row: Vec<E::ScalarField>,
+    pub(crate) col: Vec<E::ScalarField>,
+    pub(crate) row_index: Vec<usize>,
+    pub(crate) col_index: Vec<usize>,
+    pub(crate) val_a: Vec<E::ScalarField>,
+    pub(crate) val_b: Vec<E::ScalarField>,
+    pub(crate) val_c: Vec<E::ScalarField>,
+    pub(crate) row_frequency: Vec<usize>,
+    pub(crate) col_frequency: Vec<usize>,
+    pub(crate) ck_row: CommitterKey<E>,
+    pub(crate) ck_col: CommitterKey<E>,
+    pub(crate) ck_row_frequency: CommitterKey<E>,
+    pub(crate) ck_col_frequency: CommitterKey<E>,
+    pub(crate) commitments: Vec<Commitment<E>>,
+}
+
+impl<E: Pairing> IndexCache<E> {
+    /// Preprocess the index of the R1CS instance `r1cs` with the committer key `ck`.
+    ///
+    /// The witness of `r1cs` is ignored.
+    pub fn new(r1cs: &R1cs<E::ScalarField>, ck: &CommitterKey<E>) -> Self {
+        let cache_time = start_timer!(|| "Index cache");
+        trace_span!("index_cache", num_constraints = r1cs.a.len());
+        let padded = r1cs.padded();
+        let num_constraints = padded.a.len();
+        let num_variables = padded.z.len();
+
+        let joint_matrix = sum_matrices(&padded.a, &padded.b, &padded.c, num_variables);
+        let (row, col, row_index, col_index, val_a, val_b, val_c) = joint_matrices(
+            &joint_matrix,
+            num_constraints,
+            num_variables,
+            &padded.a,
+            &padded.b,
+            &padded.c,
+        );
+
+        let ck_row = ck.index_by(&row_index);
+        let ck_col = ck.index_by(&col_index);
+
+        let row_frequency = compute_frequency(num_constraints, &row_index);
+        let col_frequency = compute_frequency(num_variables, &col_index);
+        let ck_row_frequency = ck.index_by(&extend_frequency(&row_frequency));
+        let ck_col_frequency = ck.index_by(&extend_frequency(&col_frequency));
+
+        let commitments = ck.batch_commit([&row, &col, &val_a, &val_b, &val_c]);
+        end_timer!(cache_time);
+
+        IndexCache {
+            num_constraints,
+            num_variables,
+            row,
+            col,
+            row_index,
+            col_index,
+            val_a,
+            val_b,
+            val_c,
+            row_frequency,
+            col_frequency,
+            ck_row,
+            ck_col,
+            ck_row_frequency,
+            ck_col_frequency,
+            commitments,
+        }
+    }
+
+    /// The commitments to the index polynomials `row`, `col`, `val_a`, `val_b`, `val_c`,
+    /// as expected by [`Proof::verify`](super::Proof::verify).
+    pub fn commitments(&self) -> &[Commitment<E>] {
+        &self.commitments
+    }
+
+    /// The number of non-zero entries of the joint matrix,
+    /// as expected by [`Proof::verify`](super::Proof::verify).
+    pub fn num_non_zero(&self) -> usize {
+        self.row.len()
+    }
+
+    /// Write the cache (uncompressed) to the file at `path`.
+    #[cfg(feature = "std")]
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let file = io::BufWriter::new(File::create(path)?);
+        self.serialize_uncompressed(file)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Read a cache written with [`IndexCache::save`] from the file at `path`.
+    #[cfg(feature = "std")]
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = io::BufReader::new(File::open(path)?);
+        Self::deserialize_uncompressed(file)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
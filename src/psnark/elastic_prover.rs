@@ -3,37 +3,44 @@ use ark_ec::{pairing::Pairing};
 use ark_ff::Field;
 use ark_std::borrow::Borrow;
 use ark_std::boxed::Box;
+use ark_std::mem::size_of;
 use ark_std::vec::Vec;
 use ark_std::One;
+use core::sync::atomic::{AtomicUsize, Ordering};
 use merlin::Transcript;
 
-use crate::circuit::R1csStream;
-use crate::iterable::slice::IterableRange;
-use crate::iterable::Iterable;
+use crate::circuit::{padded_dim, R1csStream};
+use crate::config::ProverConfig;
+use crate::errors::{GeminiError, GeminiResult};
+use crate::iterable::{
+    FnIterable, IntoField, Iterable, Map, RandomAccessIterable, Resident, Reverse,
+};
 use crate::kzg::{CommitterKeyStream, EvaluationProof};
-use crate::misc::{evaluate_be, hadamard, ip_unsafe, powers, powers2, strip_last, MatrixElement};
+use crate::misc::{
+    evaluate_be, expand_tensor, hadamard, powers, powers2, strip_last, MatrixElement,
+};
+use crate::psnark::memory::{
+    commit_buffer, commit_joint_buffer, msm_buffer, sumcheck_buffer, MemoryReport,
+};
 use crate::psnark::streams::{
-    AlgebraicHash, HadamardStreamer, IntoField, JointColStream, JointRowStream, JointValStream,
-    LookupStreamer, LookupTensorStreamer, Tensor,
+    check_len, lookup_tensor, AlgebraicHash, HadamardStreamer, JointColStream, JointMatrixStream,
+    JointRowStream, JointValStream, LookupStreamer, LookupTensorStreamer, Tensor,
 };
-use crate::psnark::Proof;
+use crate::psnark::{Proof, PSNARK_LABEL};
 use crate::subprotocols::entryproduct::streams::entry_product_streams;
 use crate::subprotocols::entryproduct::EntryProduct;
 use crate::subprotocols::plookup::streams::{plookup_streams, SortedStreamer};
 use crate::subprotocols::sumcheck::proof::Sumcheck;
 use crate::subprotocols::sumcheck::streams::FoldedPolynomialTree;
 use crate::subprotocols::sumcheck::ElasticProver;
-use crate::subprotocols::tensorcheck::{evaluate_folding, TensorcheckProof};
-use crate::transcript::GeminiTranscript;
+use crate::subprotocols::tensorcheck::{evaluate_folding_points, TensorcheckProof};
+use crate::transcript::{bind_context, bind_public_input, GeminiTranscript};
 use crate::{lincomb, PROTOCOL_NAME};
 
-#[cfg(feature = "parallel")]
-use rayon::iter::{
-    IndexedParallelIterator, IntoParallelRefIterator, IntoParallelRefMutIterator, ParallelIterator,
-};
-
+/// Evaluate `base_polynomial` at the three points `eval_points`, with a single pass over the stream,
+/// and add the evaluations to the transcript.
 fn evaluate_base_polynomial<I, F>(
-    transcript: &mut Transcript,
+    transcript: &mut impl GeminiTranscript,
     base_polynomial: &I,
     eval_points: &[F; 3],
 ) -> [F; 3]
@@ -43,9 +50,12 @@ where
     I::Item: Borrow<F>,
 {
     let mut evaluations_w = [F::zero(); 3];
-    cfg_iter!(eval_points)
-        .zip(cfg_iter_mut!(evaluations_w))
-        .for_each(|(eval_point, dst)| *dst = evaluate_be(base_polynomial.iter(), eval_point));
+    base_polynomial.iter().for_each(|c| {
+        evaluations_w
+            .iter_mut()
+            .zip(eval_points.iter())
+            .for_each(|(dst, eval_point)| *dst = *dst * eval_point + c.borrow())
+    });
 
     evaluations_w
         .iter()
@@ -53,31 +63,244 @@ where
     evaluations_w
 }
 
+/// Evaluate the three polynomials `val_a`, `val_b`, `val_c`, streamed jointly by `joint_matrix`,
+/// with a single pass over the matrices.
+///
+/// The evaluations are added to the transcript in the same order as
+/// [`evaluate_base_polynomial`] would, one polynomial at a time.
+fn evaluate_joint_vals<I, F>(
+    transcript: &mut impl GeminiTranscript,
+    joint_matrix: &I,
+    eval_points: &[F; 3],
+) -> [[F; 3]; 3]
+where
+    F: Field,
+    I: Iterable<Item = (usize, usize, [F; 3])>,
+{
+    let mut evaluations = [[F::zero(); 3]; 3];
+    joint_matrix.iter().for_each(|(_, _, vals)| {
+        evaluations
+            .iter_mut()
+            .zip(vals.iter())
+            .for_each(|(evaluations_val, val)| {
+                evaluations_val
+                    .iter_mut()
+                    .zip(eval_points.iter())
+                    .for_each(|(dst, eval_point)| *dst = *dst * eval_point + val)
+            })
+    });
+
+    evaluations
+        .iter()
+        .flatten()
+        .for_each(|e| transcript.append_serializable(b"eval", e));
+    evaluations
+}
+
+/// Read into memory the values of `val_a`, `val_b`, `val_c` fitting `budget` (in bytes),
+/// in this order, with a single pass over `joint_matrix`, and deduct their size from the budget.
+///
+/// The same as calling [`Resident::load`] on each of the three streams,
+/// without traversing the three matrices once per stream.
+fn load_joint_vals<I, F>(joint_matrix: &I, budget: &mut usize) -> [Option<Vec<F>>; 3]
+where
+    F: Field,
+    I: Iterable<Item = (usize, usize, [F; 3])>,
+{
+    let len = joint_matrix.len();
+    let size = len.saturating_mul(size_of::<F>());
+    let loaded = match size {
+        0 => 3,
+        size => usize::min(3, *budget / size),
+    };
+    *budget -= loaded * size;
+    let mut memory = [(); 3].map(|_| Vec::new());
+    if loaded > 0 {
+        memory[..loaded]
+            .iter_mut()
+            .for_each(|elements| elements.reserve_exact(len));
+        joint_matrix.iter().for_each(|(_, _, vals)| {
+            memory[..loaded]
+                .iter_mut()
+                .zip(vals)
+                .for_each(|(elements, val)| elements.push(val))
+        });
+    }
+    let mut index = 0;
+    memory.map(|elements| {
+        index += 1;
+        (index <= loaded).then_some(elements)
+    })
+}
+
+/// Compute the inner products of `lhs_a` with `val_a`, and of `lhs_b` with `val_b`,
+/// with a single pass over the streams.
+///
+/// The iterator `vals` yields the pairs of values of `val_a` and `val_b`.
+fn joint_inner_products<F, IA, IB, IV>(lhs_a: IA, lhs_b: IB, vals: IV) -> [F; 2]
+where
+    F: Field,
+    IA: Iterator,
+    IB: Iterator,
+    IV: Iterator<Item = (F, F)>,
+    IA::Item: Borrow<F>,
+    IB::Item: Borrow<F>,
+{
+    lhs_a
+        .zip(lhs_b)
+        .zip(vals)
+        .fold([F::zero(); 2], |[ip_a, ip_b], ((a, b), (val_a, val_b))| {
+            [ip_a + val_a * a.borrow(), ip_b + val_b * b.borrow()]
+        })
+}
+
 impl<E: Pairing> Proof<E> {
     /// Given as input the _streaming_ R1CS instance `r1cs`
     /// and the _streaming_ committer key `ck`,
     /// return a new _preprocessing_ SNARK using the elastic prover.
+    ///
+    /// The matrices are assumed square, with `r1cs.nonzero` lines each.
+    /// The instance is padded on the fly (see [`R1csStream::padded`]).
+    ///
+    /// Fails if the streams $\vec z, A \vec z, B \vec z, C \vec z$ do not have `r1cs.nonzero` elements,
+    /// or if the witness is longer than $\vec z$.
     pub fn new_elastic<SM, SG, SZ, SW>(
         r1cs: &R1csStream<SM, SZ, SW>,
         ck: &CommitterKeyStream<E, SG>,
         max_msm_buffer: usize,
-    ) -> Proof<E>
+    ) -> GeminiResult<Proof<E>>
     where
         SM: Iterable + Copy,
         SZ: Iterable + Copy,
         SW: Iterable + Copy,
-        SG: Iterable,
+        SG: RandomAccessIterable,
         SM::Item: Borrow<MatrixElement<E::ScalarField>>,
         SZ::Item: Borrow<E::ScalarField> + Copy,
         SW::Item: Borrow<E::ScalarField>,
         SZ::Item: Borrow<E::ScalarField>,
         SG::Item: Borrow<E::G1Affine>,
     {
+        Self::new_elastic_with_report(r1cs, ck, max_msm_buffer).map(|(proof, _)| proof)
+    }
+
+    /// Same as [`Proof::new_elastic`],
+    /// but also return a [`MemoryReport`] with the peak buffer sizes of each proving phase,
+    /// and the number of passes over the instance.
+    pub fn new_elastic_with_report<SM, SG, SZ, SW>(
+        r1cs: &R1csStream<SM, SZ, SW>,
+        ck: &CommitterKeyStream<E, SG>,
+        max_msm_buffer: usize,
+    ) -> GeminiResult<(Proof<E>, MemoryReport)>
+    where
+        SM: Iterable + Copy,
+        SZ: Iterable + Copy,
+        SW: Iterable + Copy,
+        SG: RandomAccessIterable,
+        SM::Item: Borrow<MatrixElement<E::ScalarField>>,
+        SZ::Item: Borrow<E::ScalarField> + Copy,
+        SW::Item: Borrow<E::ScalarField>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SG::Item: Borrow<E::G1Affine>,
+    {
+        let config = ProverConfig {
+            max_msm_buffer,
+            ..ProverConfig::default()
+        };
+        Self::new_elastic_with_config(r1cs, ck, &config)
+    }
+
+    /// Same as [`Proof::new_elastic_with_report`],
+    /// with the buffer sizes, the memory budget, and the threads of `config`.
+    ///
+    /// The time-space trade-off is chosen for each stream, rather than for the whole proof:
+    /// the index streams (the rows, the columns, and the values of the joint matrix)
+    /// are held in memory while their sizes fit the memory budget (and the memory cap, if any),
+    /// and the streams derived from the witness are always streamed.
+    /// The proof does not depend on the choice, only the passes over the instance and
+    /// the [`MemoryReport::index`] of the report do.
+    pub fn new_elastic_with_config<SM, SG, SZ, SW>(
+        r1cs: &R1csStream<SM, SZ, SW>,
+        ck: &CommitterKeyStream<E, SG>,
+        config: &ProverConfig,
+    ) -> GeminiResult<(Proof<E>, MemoryReport)>
+    where
+        SM: Iterable + Copy,
+        SZ: Iterable + Copy,
+        SW: Iterable + Copy,
+        SG: RandomAccessIterable,
+        SM::Item: Borrow<MatrixElement<E::ScalarField>>,
+        SZ::Item: Borrow<E::ScalarField> + Copy,
+        SW::Item: Borrow<E::ScalarField>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SG::Item: Borrow<E::G1Affine>,
+    {
+        config.install(move || Self::prove_elastic(r1cs, ck, config))
+    }
+
+    /// The elastic prover of [`Proof::new_elastic_with_config`], on the current threads.
+    fn prove_elastic<SM, SG, SZ, SW>(
+        r1cs: &R1csStream<SM, SZ, SW>,
+        ck: &CommitterKeyStream<E, SG>,
+        config: &ProverConfig,
+    ) -> GeminiResult<(Proof<E>, MemoryReport)>
+    where
+        SM: Iterable + Copy,
+        SZ: Iterable + Copy,
+        SW: Iterable + Copy,
+        SG: RandomAccessIterable,
+        SM::Item: Borrow<MatrixElement<E::ScalarField>>,
+        SZ::Item: Borrow<E::ScalarField> + Copy,
+        SW::Item: Borrow<E::ScalarField>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SG::Item: Borrow<E::G1Affine>,
+    {
+        // count the passes over the instance.
+        let passes = AtomicUsize::new(0);
+        let counted = r1cs.counted(&passes);
+        let r1cs = &counted;
+        let max_msm_buffer = config.max_msm_buffer;
+        let chunk_size = config.chunk_size;
+        let threshold = config.threshold();
+        GeminiError::ensure_len("r1cs.z", r1cs.nonzero, r1cs.z.len())?;
+        r1cs.check()?;
+        // the size (in bytes) of the index streams that can be held in memory.
+        let mut index_budget = config
+            .memory_budget
+            .saturating_mul(size_of::<E::ScalarField>());
+        if let Some(cap) = config.memory_cap {
+            let num_variables = padded_dim(r1cs.nonzero, r1cs.z.len());
+            let bound = MemoryReport::bound::<E>(num_variables, r1cs.joint_len, config);
+            bound.ensure_cap(cap)?;
+            // the index streams take what the buffers of the phases leave of the cap.
+            index_budget = usize::min(index_budget, cap - bound.peak());
+        }
         let psnark_time = start_timer!(|| module_path!());
+        trace_span!(
+            "psnark_elastic_prover",
+            num_variables = r1cs.z.len(),
+            num_non_zero = r1cs.joint_len,
+        );
+        // pad the instance to square matrices of power-of-two dimension.
+        let padded = r1cs.padded::<E::ScalarField>(r1cs.nonzero);
+        let r1cs = &padded;
+        let mut memory_report = MemoryReport::default();
+        check_len("r1cs.z", &r1cs.z);
+        check_len("r1cs.witness", &r1cs.witness);
+        check_len("r1cs.z_a", &r1cs.z_a);
+        check_len("r1cs.z_b", &r1cs.z_b);
+        check_len("r1cs.z_c", &r1cs.z_c);
         let mut transcript = Transcript::new(PROTOCOL_NAME);
+        // bind the context and the public input to the transcript.
+        let index = [r1cs.z.len(), r1cs.joint_len];
+        bind_context::<E, _>(&mut transcript, PSNARK_LABEL, &index);
+        let public_input = r1cs.public_input::<E::ScalarField>();
+        bind_public_input::<E::ScalarField, _, _>(
+            &mut transcript,
+            &Reverse(public_input.as_slice()),
+        );
         // send the vector w
         let witness_commitment_time = start_timer!(|| "Commitment to w");
-        let witness_commitment = ck.commit(&r1cs.witness);
+        let witness_commitment = ck.commit_chunked(&r1cs.witness, chunk_size)?;
         end_timer!(witness_commitment_time);
 
         // send witness, receive challenge.
@@ -90,7 +313,7 @@ impl<E: Pairing> Proof<E> {
 
         // run the sumcheck for z_a and z_b with twist alpha
         let sumcheck_time = start_timer!(|| "sumcheck1");
-        let sumcheck1 = Sumcheck::new_space(&mut transcript, r1cs.z_a, r1cs.z_b, alpha);
+        let sumcheck1 = Sumcheck::new_space(&mut transcript, r1cs.z_a, r1cs.z_b, alpha)?;
         end_timer!(sumcheck_time);
 
         let row_sorted = JointRowStream::new(
@@ -140,8 +363,39 @@ impl<E: Pairing> Proof<E> {
             r1cs.nonzero,
             r1cs.joint_len,
         );
+        let joint_matrix = JointMatrixStream::new(
+            &r1cs.a_colmaj,
+            &r1cs.b_colmaj,
+            &r1cs.c_colmaj,
+            r1cs.nonzero,
+            r1cs.joint_len,
+        );
+        // hold in memory the index streams fitting the budget, starting from the ones read most often
+        // (the rows and the columns are vectors of `usize`, the values vectors of field elements).
+        // The other index streams, and all the streams derived from the witness, are streamed.
+        let mut budget = index_budget;
+        let row_memory = Resident::load(&row, &mut budget);
+        let col_memory = Resident::load(&col, &mut budget);
+        // the three val streams are loaded with a single pass over the matrices.
+        let [val_a_memory, val_b_memory, val_c_memory] =
+            load_joint_vals(&joint_matrix, &mut budget);
+        let row_sorted_memory = Resident::load(&row_sorted, &mut budget);
+        let row = Resident::new(row, row_memory.as_deref());
+        let col = Resident::new(col, col_memory.as_deref());
+        let val_a = Resident::new(val_a, val_a_memory.as_deref());
+        let val_b = Resident::new(val_b, val_b_memory.as_deref());
+        let val_c = Resident::new(val_c, val_c_memory.as_deref());
+        let row_sorted = Resident::new(row_sorted, row_sorted_memory.as_deref());
+        memory_report.index = index_budget - budget;
+        check_len("row_sorted", &row_sorted);
+        check_len("row", &row);
+        check_len("col", &col);
+        check_len("val_a", &val_a);
+        check_len("val_b", &val_b);
+        check_len("val_c", &val_c);
         // lookup in z for the nonzero positions
         let z_star = LookupStreamer::new(&r1cs.z, &col);
+        check_len("z_star", &z_star);
         // compose the randomness for the A-, B-, C-matrices
         let len = sumcheck1.challenges.len();
         let r_short = &sumcheck1.challenges;
@@ -154,17 +408,26 @@ impl<E: Pairing> Proof<E> {
         let ralpha_star = LookupTensorStreamer::new(ralpha_short, &row);
         let r_star = LookupTensorStreamer::new(r_short, &row);
         let alpha_star = LookupTensorStreamer::new(alpha_short, &row);
-
-        // commit to the looked up vectors
-        let ralpha_star_commitment = ck.commit(&ralpha_star);
-        let r_star_commitment = ck.commit(&r_star);
-        let alpha_star_commitment = ck.commit(&alpha_star);
-        let r_star_commitments = [
-            ralpha_star_commitment,
-            r_star_commitment,
-            alpha_star_commitment,
+        check_len("ralpha_star", &ralpha_star);
+        check_len("r_star", &r_star);
+        check_len("alpha_star", &alpha_star);
+
+        // commit to the looked up vectors, with a single pass over the non-zero positions.
+        let tensors = &[
+            expand_tensor(ralpha_short),
+            expand_tensor(r_short),
+            expand_tensor(alpha_short),
         ];
-        let z_star_commitment = ck.commit(&z_star);
+        let lookups = Map::new(row, |index: usize| {
+            [
+                lookup_tensor(&tensors[0], index),
+                lookup_tensor(&tensors[1], index),
+                lookup_tensor(&tensors[2], index),
+            ]
+        });
+        let r_star_commitments = ck.commit_joint(&lookups, chunk_size)?;
+        let [ralpha_star_commitment, r_star_commitment, alpha_star_commitment] = r_star_commitments;
+        let z_star_commitment = ck.commit_chunked(&z_star, chunk_size)?;
 
         transcript.append_serializable(b"ra*", &ralpha_star_commitment);
         transcript.append_serializable(b"rb*", &r_star_commitment);
@@ -177,6 +440,8 @@ impl<E: Pairing> Proof<E> {
         let challenges = powers(challenge, 3);
         // assert_eq!(val_a.len(), val_b.len());
         assert_eq!(val_a.len(), val_c.len());
+        // each pass of the sumcheck reads the three val streams separately:
+        // those not held in memory traverse the matrices once each.
         let ralpha_star_val_a = HadamardStreamer::new(&ralpha_star, &val_a);
         let r_star_val_b = HadamardStreamer::new(&r_star, &val_b);
         let alpha_star_val_c = HadamardStreamer::new(&alpha_star, &val_c);
@@ -186,15 +451,22 @@ impl<E: Pairing> Proof<E> {
         );
 
         let sumcheck_time = start_timer!(|| "sumcheck2");
-        let sumcheck2 = Sumcheck::new_elastic(&mut transcript, z_star, rhs, E::ScalarField::one());
+        let sumcheck2 = Sumcheck::new_elastic_with_config(
+            &mut transcript,
+            z_star,
+            rhs,
+            E::ScalarField::one(),
+            config,
+        )?;
         end_timer!(sumcheck_time);
 
         // Lookup protocol (plookup) for r_a \subset r, z* \subset r
         let zeta = transcript.get_challenge(b"zeta");
 
-        let idx_r = IterableRange(rs.len());
-        let idx_alpha = IterableRange(alphas.len());
-        let idx_z = IterableRange(r1cs.z.len());
+        let index = |i: usize| i;
+        let idx_r = FnIterable::new(rs.len(), index);
+        let idx_alpha = FnIterable::new(alphas.len(), index);
+        let idx_z = FnIterable::new(r1cs.z.len(), index);
 
         let hashed_alpha = AlgebraicHash::new(&alphas, &idx_alpha, zeta);
         let hashed_alphastar = AlgebraicHash::new(&alpha_star, &row, zeta);
@@ -202,14 +474,35 @@ impl<E: Pairing> Proof<E> {
         let hashed_rstar = AlgebraicHash::new(&r_star, &row, zeta);
         let hashed_z = AlgebraicHash::new(&r1cs.z, &idx_z, zeta);
         let hashed_zstar = AlgebraicHash::new(&z_star, &col, zeta);
+        check_len("hashed_alpha", &hashed_alpha);
+        check_len("hashed_alphastar", &hashed_alphastar);
+        check_len("hashed_r", &hashed_r);
+        check_len("hashed_rstar", &hashed_rstar);
+        check_len("hashed_z", &hashed_z);
+        check_len("hashed_zstar", &hashed_zstar);
 
         let sorted_r = SortedStreamer::new(&hashed_r, &row_sorted);
         let sorted_alpha = SortedStreamer::new(&hashed_alpha, &row_sorted);
         let sorted_z = SortedStreamer::new(&hashed_z, &col);
+        check_len("sorted_r", &sorted_r);
+        check_len("sorted_alpha", &sorted_alpha);
+        check_len("sorted_z", &sorted_z);
         // compute the commitments to the sorted polynomials
-        let sorted_r_commitment = ck.commit(&sorted_r);
-        let sorted_alpha_commitment = ck.commit(&sorted_alpha);
-        let sorted_z_commitment = ck.commit(&sorted_z);
+        let sorted_r_commitment = ck.commit_chunked(&sorted_r, chunk_size)?;
+        let sorted_alpha_commitment = ck.commit_chunked(&sorted_alpha, chunk_size)?;
+        let sorted_z_commitment = ck.commit_chunked(&sorted_z, chunk_size)?;
+        let committed_lens = [
+            r1cs.witness.len(),
+            ralpha_star.len(),
+            z_star.len(),
+            sorted_r.len(),
+            sorted_alpha.len(),
+            sorted_z.len(),
+        ];
+        memory_report.commitments = usize::max(
+            commit_buffer::<E>(committed_lens.into_iter().max().unwrap(), chunk_size),
+            commit_joint_buffer::<E>(lookups.len(), chunk_size, 3),
+        );
 
         transcript.append_serializable(b"sorted_alpha_commitment", &sorted_alpha_commitment);
         transcript.append_serializable(b"sorted_r_commitment", &sorted_r_commitment);
@@ -224,6 +517,26 @@ impl<E: Pairing> Proof<E> {
             plookup_streams(&hashed_rstar, &hashed_r, &row_sorted, gamma, chi);
         let (pl_set_z, pl_subset_z, pl_sorted_z) =
             plookup_streams(&hashed_zstar, &hashed_z, &col, gamma, chi);
+        check_len("pl_set_alpha", &pl_set_alpha);
+        check_len("pl_subset_alpha", &pl_subset_alpha);
+        check_len("pl_sorted_alpha", &pl_sorted_alpha);
+        check_len("pl_set_r", &pl_set_r);
+        check_len("pl_subset_r", &pl_subset_r);
+        check_len("pl_sorted_r", &pl_sorted_r);
+        check_len("pl_set_z", &pl_set_z);
+        check_len("pl_subset_z", &pl_subset_z);
+        check_len("pl_sorted_z", &pl_sorted_z);
+        let pl_lens = [
+            pl_set_r.len(),
+            pl_subset_r.len(),
+            pl_sorted_r.len(),
+            pl_set_alpha.len(),
+            pl_subset_alpha.len(),
+            pl_sorted_alpha.len(),
+            pl_set_z.len(),
+            pl_subset_z.len(),
+            pl_sorted_z.len(),
+        ];
         // compute the products to send to the verifier.
         // XXXX. There is no need to compute the sorted ones as they can be derived.
         let set_alpha_ep = pl_set_alpha.iter().product();
@@ -258,6 +571,7 @@ impl<E: Pairing> Proof<E> {
         } = EntryProduct::new_elastic_batch(
             &mut transcript,
             ck,
+            config,
             (
                 &pl_set_r,
                 &pl_subset_r,
@@ -280,7 +594,7 @@ impl<E: Pairing> Proof<E> {
                 subset_z_ep,
                 sorted_z_ep,
             ],
-        );
+        )?;
 
         // At the end of the entry product protocol, we have some inneer-product claims.
         // We don't use them yet. Instead:
@@ -313,7 +627,11 @@ impl<E: Pairing> Proof<E> {
             ),
             &open_chals
         );
-        let ralpha_star_acc_mu_proof = ck.open(&polynomial, &psi, max_msm_buffer).1;
+        let ralpha_star_acc_mu_proof = ck.open(&polynomial, &psi, max_msm_buffer)?.1;
+        memory_report.entry_products = usize::max(
+            commit_buffer::<E>(pl_lens.into_iter().max().unwrap(), chunk_size),
+            msm_buffer::<E>(max_msm_buffer),
+        );
 
         let ralpha_star_acc_mu_evals = vec![
             evaluate_be(ralpha_star.iter(), &psi),
@@ -331,8 +649,21 @@ impl<E: Pairing> Proof<E> {
         // <r_a* \otimes (sumcheck chals), val_a>
         // <r_b* \otimes (sumcheck chals), val_b>
         // <r_c* \otimes (sumcheck chals), val_c> (not needed as it can be derived)
-        let r_val_chal_a = ip_unsafe(lhs_ralpha_star.iter(), val_a.iter());
-        let r_val_chal_b = ip_unsafe(lhs_r_star.iter(), val_b.iter());
+        // with a single pass over val_a and val_b if they are held in memory,
+        // and over the three matrices otherwise.
+        let [r_val_chal_a, r_val_chal_b] = if val_a.is_resident() && val_b.is_resident() {
+            joint_inner_products(
+                lhs_ralpha_star.iter(),
+                lhs_r_star.iter(),
+                val_a.iter().zip(val_b.iter()),
+            )
+        } else {
+            joint_inner_products(
+                lhs_ralpha_star.iter(),
+                lhs_r_star.iter(),
+                joint_matrix.iter().map(|(_, _, vals)| (vals[0], vals[1])),
+            )
+        };
 
         ralpha_star_acc_mu_evals
             .iter()
@@ -340,31 +671,46 @@ impl<E: Pairing> Proof<E> {
         transcript.append_serializable(b"ralpha_star_mu_proof", &ralpha_star_acc_mu_proof);
 
         // Add to the list of inner-products claims (obtained from the entry product)
-        // additional inner products:
-        provers.push(Box::new(ElasticProver::new(
+        // additional inner products.
+        // Each prover folds its own val stream, hence the val streams are traversed separately.
+        provers.push(Box::new(ElasticProver::with_config(
             lhs_ralpha_star,
             val_a,
             E::ScalarField::one(),
-        )));
-        provers.push(Box::new(ElasticProver::new(
+            config,
+        )?));
+        provers.push(Box::new(ElasticProver::with_config(
             lhs_r_star,
             val_b,
             E::ScalarField::one(),
-        )));
-        provers.push(Box::new(ElasticProver::new(
+            config,
+        )?));
+        provers.push(Box::new(ElasticProver::with_config(
             lhs_alpha_star,
             val_c,
             E::ScalarField::one(),
-        )));
-        provers.push(Box::new(ElasticProver::new(
+            config,
+        )?));
+        provers.push(Box::new(ElasticProver::with_config(
             r_star.clone(),
             alpha_star.clone(),
             psi,
-        )));
+            config,
+        )?));
 
         let sumcheck_time = start_timer!(|| "sumcheck3");
-        let sumcheck3 = Sumcheck::prove_batch(&mut transcript, provers);
+        let sumcheck3 = Sumcheck::prove_batch(&mut transcript, provers)?;
         end_timer!(sumcheck_time);
+        // the entry products' provers, plus the four inner products above.
+        let sumcheck3_memory = pl_lens
+            .into_iter()
+            .chain([val_a.len(); 4])
+            .map(|len| sumcheck_buffer::<E>(len, threshold))
+            .sum::<usize>();
+        memory_report.sumchecks = usize::max(
+            sumcheck_buffer::<E>(z_star.len(), threshold),
+            sumcheck3_memory,
+        );
 
         // tensorcheck protocol
         let tc_time = start_timer!(|| "tensorcheck");
@@ -387,6 +733,7 @@ impl<E: Pairing> Proof<E> {
             ),
             &tc_challenges
         );
+        // as for the sumchecks, the linear combination reads val_a, val_b, val_c separately.
         let body_polynomials_1 = &lincomb!(
             (
                 pl_set_sh_r,
@@ -434,13 +781,13 @@ impl<E: Pairing> Proof<E> {
 
         let mut folded_polynomials_commitments = Vec::new();
         folded_polynomials_commitments
-            .extend(ck.commit_folding(&tensorcheck_foldings_0, max_msm_buffer));
+            .extend(ck.commit_folding(&tensorcheck_foldings_0, max_msm_buffer)?);
         folded_polynomials_commitments
-            .extend(ck.commit_folding(&tensorcheck_foldings_1, max_msm_buffer));
+            .extend(ck.commit_folding(&tensorcheck_foldings_1, max_msm_buffer)?);
         folded_polynomials_commitments
-            .extend(ck.commit_folding(&tensorcheck_foldings_2, max_msm_buffer));
+            .extend(ck.commit_folding(&tensorcheck_foldings_2, max_msm_buffer)?);
         folded_polynomials_commitments
-            .extend(ck.commit_folding(&tensorcheck_foldings_3, max_msm_buffer));
+            .extend(ck.commit_folding(&tensorcheck_foldings_3, max_msm_buffer)?);
 
         // add commitments to transcript
         folded_polynomials_commitments
@@ -454,35 +801,30 @@ impl<E: Pairing> Proof<E> {
         let evaluations_time = start_timer!(|| "evaluations");
         let mut folded_polynomials_evaluations = vec![];
 
-        folded_polynomials_evaluations.extend(
-            evaluate_folding(&tensorcheck_foldings_0, eval_points[1])
-                .into_iter()
-                .zip(evaluate_folding(&tensorcheck_foldings_0, eval_points[2]))
-                .map(|(x, y)| [x, y]),
-        );
-
-        folded_polynomials_evaluations.extend(
-            evaluate_folding(&tensorcheck_foldings_1, eval_points[1])
-                .into_iter()
-                .zip(evaluate_folding(&tensorcheck_foldings_1, eval_points[2]))
-                .map(|(x, y)| [x, y]),
-        );
-        folded_polynomials_evaluations.extend(
-            evaluate_folding(&tensorcheck_foldings_2, eval_points[1])
-                .into_iter()
-                .zip(evaluate_folding(&tensorcheck_foldings_2, eval_points[2]))
-                .map(|(x, y)| [x, y]),
-        );
-        folded_polynomials_evaluations.extend(
-            evaluate_folding(&tensorcheck_foldings_3, eval_points[1])
-                .into_iter()
-                .zip(evaluate_folding(&tensorcheck_foldings_3, eval_points[2]))
-                .map(|(x, y)| [x, y]),
-        );
+        // each tree is evaluated at both points with a single pass.
+        let folding_points = [eval_points[1], eval_points[2]];
+        folded_polynomials_evaluations.extend(evaluate_folding_points(
+            &tensorcheck_foldings_0,
+            &folding_points,
+        ));
+        folded_polynomials_evaluations.extend(evaluate_folding_points(
+            &tensorcheck_foldings_1,
+            &folding_points,
+        ));
+        folded_polynomials_evaluations.extend(evaluate_folding_points(
+            &tensorcheck_foldings_2,
+            &folding_points,
+        ));
+        folded_polynomials_evaluations.extend(evaluate_folding_points(
+            &tensorcheck_foldings_3,
+            &folding_points,
+        ));
 
         let field_row = IntoField::<_, E::ScalarField>::new(&row);
         let field_col = IntoField::<_, E::ScalarField>::new(&col);
-        let base_polynomials_evaluations = vec![
+        check_len("field_row", &field_row);
+        check_len("field_col", &field_col);
+        let mut base_polynomials_evaluations = vec![
             evaluate_base_polynomial(&mut transcript, &r1cs.witness, &eval_points),
             evaluate_base_polynomial(&mut transcript, &ralpha_star, &eval_points),
             evaluate_base_polynomial(&mut transcript, &r_star, &eval_points),
@@ -490,9 +832,14 @@ impl<E: Pairing> Proof<E> {
             evaluate_base_polynomial(&mut transcript, &z_star, &eval_points),
             evaluate_base_polynomial(&mut transcript, &field_row, &eval_points),
             evaluate_base_polynomial(&mut transcript, &field_col, &eval_points),
-            evaluate_base_polynomial(&mut transcript, &val_a, &eval_points),
-            evaluate_base_polynomial(&mut transcript, &val_b, &eval_points),
-            evaluate_base_polynomial(&mut transcript, &val_c, &eval_points),
+        ];
+        // val_a, val_b, val_c are evaluated in one pass over the three matrices.
+        base_polynomials_evaluations.extend(evaluate_joint_vals(
+            &mut transcript,
+            &joint_matrix,
+            &eval_points,
+        ));
+        base_polynomials_evaluations.extend(vec![
             // sorted polynomials r*, alpha*, z*
             evaluate_base_polynomial(&mut transcript, &sorted_r, &eval_points),
             evaluate_base_polynomial(&mut transcript, &sorted_alpha, &eval_points),
@@ -509,7 +856,7 @@ impl<E: Pairing> Proof<E> {
             evaluate_base_polynomial(&mut transcript, &pl_set_acc_z, &eval_points),
             evaluate_base_polynomial(&mut transcript, &pl_subset_acc_z, &eval_points),
             evaluate_base_polynomial(&mut transcript, &pl_sorted_acc_z, &eval_points),
-        ];
+        ]);
         end_timer!(evaluations_time);
 
         folded_polynomials_evaluations
@@ -528,6 +875,7 @@ impl<E: Pairing> Proof<E> {
         let open_chals_2 = &open_chals_1[tensorcheck_foldings_1.depth()..];
         let open_chals_3 = &open_chals_2[tensorcheck_foldings_2.depth()..];
 
+        // val_a, val_b, val_c are read as separate streams (see the tensorcheck above).
         let partial_eval_stream = lincomb!(
             (
                 r1cs.witness,
@@ -558,7 +906,7 @@ impl<E: Pairing> Proof<E> {
         // do this for each element.
         let open_time = start_timer!(|| "opening time");
         let evaluation_proof = EvaluationProof(
-            ck.open_multi_points(&partial_eval_stream, &eval_points, max_msm_buffer)
+            ck.open_multi_points(&partial_eval_stream, &eval_points, max_msm_buffer)?
                 .1
                  .0
                 + ck.open_folding(
@@ -566,7 +914,7 @@ impl<E: Pairing> Proof<E> {
                     &eval_points,
                     open_chals_0,
                     max_msm_buffer,
-                )
+                )?
                 .1
                  .0
                 + ck.open_folding(
@@ -574,7 +922,7 @@ impl<E: Pairing> Proof<E> {
                     &eval_points,
                     open_chals_1,
                     max_msm_buffer,
-                )
+                )?
                 .1
                  .0
                 + ck.open_folding(
@@ -582,7 +930,7 @@ impl<E: Pairing> Proof<E> {
                     &eval_points,
                     open_chals_2,
                     max_msm_buffer,
-                )
+                )?
                 .1
                  .0
                 + ck.open_folding(
@@ -590,7 +938,7 @@ impl<E: Pairing> Proof<E> {
                     &eval_points,
                     open_chals_3,
                     max_msm_buffer,
-                )
+                )?
                 .1
                  .0,
         );
@@ -603,9 +951,12 @@ impl<E: Pairing> Proof<E> {
             base_polynomials_evaluations,
         };
         end_timer!(tc_time);
+        // the tensorcheck commitments and openings share a buffer of `max_msm_buffer` pairs.
+        memory_report.tensorcheck = msm_buffer::<E>(max_msm_buffer);
+        memory_report.passes = passes.load(Ordering::Relaxed);
 
         end_timer!(psnark_time);
-        Proof {
+        let proof = Proof {
             witness_commitment,
             zc_alpha,
             first_sumcheck_msgs: sumcheck1.prover_messages(),
@@ -627,6 +978,7 @@ impl<E: Pairing> Proof<E> {
             rstars_vals: [r_val_chal_a, r_val_chal_b],
             third_sumcheck_msgs: sumcheck3.prover_messages(),
             tensorcheck_proof,
-        }
+        };
+        Ok((proof, memory_report))
     }
 }
@@ -0,0 +1,150 @@
+//! The index keys of the preprocessing SNARK.
+//!
+//! Indexing an R1CS instance yields two keys.
+//! The [`IndexProverKey`] holds the committer key and the [`IndexCache`],
+//! whose size is linear in the number of non-zero entries of the matrices.
+//! The [`IndexVerifierKey`] holds the verification key, the commitments to the index,
+//! and the dimensions of the instance: its size is constant,
+//! and it is serialized on its own, hence a verifier never reads the prover-side material.
+//!
+//! # Index digest
+//!
+//! The proofs of [`Proof::new_time_indexed`] are bound to the whole index:
+//! the SHA-256 [`IndexVerifierKey::digest`] of the verifier key
+//! (the verification key, the index commitments, and the dimensions)
+//! is absorbed into the transcript before any other message, hence before any challenge is derived.
+//! A proof thus verifies with [`Proof::verify_indexed`] only against the very index it was produced for,
+//! and not against another index of the same dimensions.
+//! The other provers and verifiers of the preprocessing SNARK bind only the dimensions of the index.
+use ark_ec::pairing::Pairing;
+use ark_serialize::*;
+use ark_std::vec::Vec;
+use sha2::{Digest, Sha256};
+
+use crate::circuit::padded_dim;
+use crate::errors::{VerificationError, VerificationResult};
+use crate::iterable::Reverse;
+use crate::kzg::{Commitment, VerifierKey};
+use crate::psnark::Proof;
+use crate::transcript::bind_index_digest;
+use crate::PROTOCOL_NAME;
+#[cfg(feature = "prover")]
+use crate::{circuit::R1cs, errors::GeminiResult, kzg::CommitterKey, psnark::IndexCache};
+
+/// The domain separator of the index digest.
+const INDEX_DIGEST_LABEL: &[u8] = b"gemini-psnark-index";
+
+/// The prover side of the index: the committer key and the preprocessed index.
+#[cfg(feature = "prover")]
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone)]
+pub struct IndexProverKey<E: Pairing> {
+    /// The committer key.
+    pub ck: CommitterKey<E>,
+    /// The preprocessed index.
+    pub cache: IndexCache<E>,
+    /// The digest of the verifier key (see [`IndexVerifierKey::digest`]).
+    pub digest: [u8; 32],
+}
+
+/// The verifier side of the index, of constant size.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone)]
+pub struct IndexVerifierKey<E: Pairing> {
+    /// The verification key of the commitment scheme.
+    pub vk: VerifierKey<E>,
+    /// The commitments to the index.
+    pub index_comms: Vec<Commitment<E>>,
+    /// The number of non-zero entries of the joint matrix.
+    pub num_non_zero: usize,
+    /// The number of constraints.
+    pub num_constraints: usize,
+    /// The number of variables.
+    pub num_variables: usize,
+    /// The number of instance variables, including the constant one and the padding.
+    pub num_instance: usize,
+}
+
+impl<E: Pairing> IndexVerifierKey<E> {
+    /// The SHA-256 digest of the compressed serialization of the verifier key,
+    /// bound to the transcript of the indexed proofs.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut bytes = Vec::new();
+        self.serialize_compressed(&mut bytes)
+            .expect("serialization into a vector cannot fail");
+        let mut hasher = Sha256::new();
+        hasher.update(INDEX_DIGEST_LABEL);
+        hasher.update(bytes);
+        hasher.finalize().into()
+    }
+}
+
+/// Index the matrices of `r1cs` with the committer key `ck`,
+/// returning the prover and the verifier keys.
+///
+/// The witness of `r1cs` is ignored, but the length of its public input is bound in the verifier key.
+#[cfg(feature = "prover")]
+pub fn generate_index_keys<E: Pairing>(
+    r1cs: &R1cs<E::ScalarField>,
+    ck: CommitterKey<E>,
+) -> (IndexProverKey<E>, IndexVerifierKey<E>) {
+    let cache = IndexCache::new(r1cs, &ck);
+    let ivk = IndexVerifierKey {
+        vk: VerifierKey::from(&ck),
+        index_comms: cache.commitments().to_vec(),
+        num_non_zero: cache.num_non_zero(),
+        num_constraints: r1cs.a.len(),
+        num_variables: r1cs.z.len(),
+        num_instance: r1cs.x.len(),
+    };
+    let digest = ivk.digest();
+    (IndexProverKey { ck, cache, digest }, ivk)
+}
+
+#[cfg(feature = "prover")]
+impl<E: Pairing> Proof<E> {
+    /// Same as [`Proof::new_time_cached`], with the prover key `ipk` of the index of `r1cs`.
+    ///
+    /// The digest of the index (see [`IndexVerifierKey::digest`]) is bound to the transcript
+    /// before any other message.
+    pub fn new_time_indexed(
+        r1cs: &R1cs<E::ScalarField>,
+        ipk: &IndexProverKey<E>,
+    ) -> GeminiResult<Proof<E>> {
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        bind_index_digest(&mut transcript, &ipk.digest);
+        Self::new_time_cached_with_transcript(r1cs, &ipk.ck, &ipk.cache, &mut transcript)
+    }
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Verify the proof for the public input `x` (starting with the constant one, and padded),
+    /// given only the verifier key `ivk` of the index.
+    ///
+    /// The digest of `ivk` is bound to the transcript, as done by [`Proof::new_time_indexed`].
+    pub fn verify_indexed(
+        &self,
+        x: &[E::ScalarField],
+        ivk: &IndexVerifierKey<E>,
+    ) -> VerificationResult {
+        if x.len() != ivk.num_instance {
+            return Err(VerificationError::Consistency {
+                check: "public input length",
+            });
+        }
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        bind_index_digest(&mut transcript, &ivk.digest());
+        let [entry_product_check, tensorcheck_check] = self.deferred_verify(
+            &mut transcript,
+            &Reverse(x),
+            padded_dim(ivk.num_constraints, ivk.num_variables),
+            &ivk.vk,
+            &ivk.index_comms,
+            ivk.num_non_zero,
+        )?;
+        ivk.vk
+            .check(&entry_product_check)
+            .map_err(|e| e.within("entry product opening"))?;
+        ivk.vk
+            .check(&tensorcheck_check)
+            .map_err(|e| e.within("tensorcheck opening"))
+    }
+}
@@ -0,0 +1,280 @@
+//! Preprocessing utilities for the R1CS index.
+use ark_ec::pairing::Pairing;
+use ark_ff::Field;
+use ark_std::borrow::Borrow;
+use ark_std::rand::RngCore;
+#[cfg(feature = "std")]
+use ark_std::vec::Vec;
+#[cfg(feature = "std")]
+use std::io;
+
+use crate::circuit::{padded_dim, R1csStream};
+use crate::iterable::Iterable;
+use crate::kzg::CommitterKey;
+use crate::misc::MatrixElement;
+use crate::psnark::streams::JointMatrixStream;
+#[cfg(feature = "std")]
+use crate::{
+    iterable::{transpose::transpose, Map},
+    kzg::{Commitment, CommitterKeyStream},
+};
+
+/// The number of distinct evaluation points opened simultaneously by the preprocessing SNARK.
+const PSNARK_MAX_EVAL_POINTS: usize = 3;
+
+/// Statistics on the matrices of an R1CS instance,
+/// collected with a single pass over the (row-major) matrix streams.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct R1csStats {
+    /// The number of constraints, i.e. the number of rows of the matrices.
+    pub num_constraints: usize,
+    /// The number of variables, i.e. the number of columns of the matrices.
+    pub num_variables: usize,
+    /// The number of non-zero entries in each of the matrices $A$, $B$, $C$.
+    pub num_non_zero: [usize; 3],
+    /// The number of non-zero entries of $A + B + C$.
+    pub joint_non_zero: usize,
+    /// The maximum number of non-zero entries in a row, across all matrices.
+    pub max_row_degree: usize,
+}
+
+impl R1csStats {
+    /// Analyze the row-major streams of the matrices `a`, `b`, `c`
+    /// with `num_constraints` rows and `num_variables` columns.
+    pub fn new<SM, F>(a: &SM, b: &SM, c: &SM, num_constraints: usize, num_variables: usize) -> Self
+    where
+        SM: Iterable,
+        SM::Item: Borrow<MatrixElement<F>>,
+        F: Field,
+    {
+        let stats_time = start_timer!(|| "R1CS statistics");
+        trace_span!("r1cs_statistics", num_constraints = num_constraints);
+        // the joint length is not needed to stream the matrices.
+        let joint_matrix = JointMatrixStream::new(a, b, c, num_constraints, 0);
+
+        let mut num_non_zero = [0; 3];
+        let mut joint_non_zero = 0;
+        let mut max_row_degree = 0;
+        let mut current_row = None;
+        let mut row_degree = [0; 3];
+        for (row, _col, vals) in joint_matrix.iter() {
+            if current_row != Some(row) {
+                current_row = Some(row);
+                row_degree = [0; 3];
+            }
+            joint_non_zero += 1;
+            vals.iter()
+                .zip(num_non_zero.iter_mut().zip(row_degree.iter_mut()))
+                .filter(|(val, _)| !val.is_zero())
+                .for_each(|(_, (non_zero, degree))| {
+                    *non_zero += 1;
+                    *degree += 1;
+                });
+            max_row_degree = row_degree.iter().fold(max_row_degree, |x, &y| x.max(y));
+        }
+        end_timer!(stats_time);
+
+        Self {
+            num_constraints,
+            num_variables,
+            num_non_zero,
+            joint_non_zero,
+            max_row_degree,
+        }
+    }
+
+    /// Analyze the matrices of the streaming R1CS instance `r1cs`.
+    pub fn from_stream<SM, SZ, SW, F>(r1cs: &R1csStream<SM, SZ, SW>) -> Self
+    where
+        SM: Iterable,
+        SM::Item: Borrow<MatrixElement<F>>,
+        SZ: Iterable,
+        F: Field,
+    {
+        Self::new(
+            &r1cs.a_rowmaj,
+            &r1cs.b_rowmaj,
+            &r1cs.c_rowmaj,
+            r1cs.nonzero,
+            r1cs.z.len(),
+        )
+    }
+
+    /// The maximum degree of the polynomials committed during the preprocessing SNARK.
+    ///
+    /// The largest polynomials are the accumulated products for the plookup arguments,
+    /// whose length is the length of the table, plus the number of non-zero entries, plus two.
+    /// The table is as large as the padded matrices.
+    pub fn srs_degree(&self) -> usize {
+        let table_len = padded_dim(self.num_constraints, self.num_variables);
+        table_len + self.joint_non_zero + 1
+    }
+
+    /// Run the setup for the preprocessing SNARK,
+    /// generating a committer key large enough for the instance.
+    pub fn committer_key<E: Pairing>(&self, rng: &mut impl RngCore) -> CommitterKey<E> {
+        CommitterKey::new(self.srs_degree(), PSNARK_MAX_EVAL_POINTS, rng)
+    }
+}
+
+/// Commit to the index of the preprocessing SNARK,
+/// i.e. to the polynomials `row`, `col`, `val_a`, `val_b`, `val_c`,
+/// given the row-major streams of the matrices `a`, `b`, `c`.
+/// The streams must be canonical, with sorted lines and neither duplicate entries nor explicit zeros:
+/// streams emitted by a frontend can be canonicalized beforehand with
+/// [`canonicalize`](crate::iterable::transpose::canonicalize).
+///
+/// The matrices are transposed in external memory,
+/// holding at most `buffer_size` entries of each matrix in memory at any given time.
+/// The five polynomials are then committed with a single pass over the key and the transposed matrices,
+/// collecting `buffer_size` coefficients of each polynomial at once.
+/// Along with the commitments, return the statistics of the instance:
+/// `joint_non_zero` is the number of non-zero entries expected by the verifier.
+#[cfg(feature = "std")]
+pub fn index_commitments<E, SM, SG>(
+    ck: &CommitterKeyStream<E, SG>,
+    a: &SM,
+    b: &SM,
+    c: &SM,
+    num_constraints: usize,
+    num_variables: usize,
+    buffer_size: usize,
+) -> io::Result<(Vec<Commitment<E>>, R1csStats)>
+where
+    E: Pairing,
+    SM: Iterable,
+    SM::Item: Borrow<MatrixElement<E::ScalarField>>,
+    SG: Iterable,
+    SG::Item: Borrow<E::G1Affine>,
+{
+    let index_time = start_timer!(|| "Index commitments");
+    trace_span!(
+        "index_commitments",
+        num_constraints = num_constraints,
+        num_variables = num_variables,
+    );
+    let stats = R1csStats::new(a, b, c, num_constraints, num_variables);
+    let a_colmaj = transpose(a, num_constraints, num_variables, buffer_size)?;
+    let b_colmaj = transpose(b, num_constraints, num_variables, buffer_size)?;
+    let c_colmaj = transpose(c, num_constraints, num_variables, buffer_size)?;
+
+    let joint_len = stats.joint_non_zero;
+    let joint_matrix =
+        JointMatrixStream::new(&a_colmaj, &b_colmaj, &c_colmaj, num_variables, joint_len);
+    // same naming as in the elastic prover: the column-major streams are cut to obtain the rows,
+    // so that `row` is the index within the column, and `col` is the column.
+    let index = Map::new(joint_matrix, |(col, row, [val_a, val_b, val_c])| {
+        [
+            E::ScalarField::from(row as u64),
+            E::ScalarField::from(col as u64),
+            val_a,
+            val_b,
+            val_c,
+        ]
+    });
+    let commitments = ck.commit_joint(&index, buffer_size)?.to_vec();
+    for transposed in [&a_colmaj, &b_colmaj, &c_colmaj] {
+        transposed.check()?;
+    }
+    end_timer!(index_time);
+    Ok((commitments, stats))
+}
+
+#[test]
+fn test_r1cs_stats() {
+    use ark_bls12_381::Fr;
+    use ark_std::test_rng;
+    use ark_std::Zero;
+
+    use crate::circuit::{generate_relation, matrix_into_rowmaj, random_circuit, Circuit};
+    use crate::iterable::dummy::Mat;
+    use crate::misc::{joint_matrices, sum_matrices};
+
+    let rng = &mut test_rng();
+    let num_constraints = 64;
+    let num_variables = 64;
+    let circuit: Circuit<Fr> = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+
+    let a_rowmaj = matrix_into_rowmaj(&r1cs.a);
+    let b_rowmaj = matrix_into_rowmaj(&r1cs.b);
+    let c_rowmaj = matrix_into_rowmaj(&r1cs.c);
+    let stats = R1csStats::new(
+        &Mat(a_rowmaj.as_slice(), a_rowmaj.len()),
+        &Mat(b_rowmaj.as_slice(), b_rowmaj.len()),
+        &Mat(c_rowmaj.as_slice(), c_rowmaj.len()),
+        r1cs.a.len(),
+        r1cs.z.len(),
+    );
+
+    let joint_matrix = sum_matrices(&r1cs.a, &r1cs.b, &r1cs.c, r1cs.z.len());
+    let (row, ..) = joint_matrices(
+        &joint_matrix,
+        num_constraints,
+        num_variables,
+        &r1cs.a,
+        &r1cs.b,
+        &r1cs.c,
+    );
+    let expected_non_zero = [&r1cs.a, &r1cs.b, &r1cs.c]
+        .map(|m| m.iter().flatten().filter(|(val, _)| !val.is_zero()).count());
+    let expected_degree = [&r1cs.a, &r1cs.b, &r1cs.c]
+        .iter()
+        .flat_map(|m| {
+            m.iter()
+                .map(|row| row.iter().filter(|(val, _)| !val.is_zero()).count())
+        })
+        .max()
+        .unwrap();
+
+    assert_eq!(stats.joint_non_zero, row.len());
+    assert_eq!(stats.num_non_zero, expected_non_zero);
+    assert_eq!(stats.max_row_degree, expected_degree);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_index_commitments() {
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+
+    use crate::circuit::{generate_relation, matrix_into_rowmaj, random_circuit, Circuit};
+    use crate::iterable::dummy::Mat;
+    use crate::misc::{joint_matrices, sum_matrices};
+
+    let rng = &mut test_rng();
+    let num_constraints = 64;
+    let num_variables = 64;
+    let circuit: Circuit<Fr> = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+
+    let joint_matrix = sum_matrices(&r1cs.a, &r1cs.b, &r1cs.c, r1cs.z.len());
+    let (row, col, _row_index, _col_index, val_a, val_b, val_c) = joint_matrices(
+        &joint_matrix,
+        num_constraints,
+        num_variables,
+        &r1cs.a,
+        &r1cs.b,
+        &r1cs.c,
+    );
+    let joint_non_zero = row.len();
+    let ck = CommitterKey::<Bls12_381>::new(joint_non_zero + 1, 3, rng);
+    let ck_stream = CommitterKeyStream::from(&ck);
+    let expected = ck.batch_commit(&vec![row, col, val_a, val_b, val_c]);
+
+    let a_rowmaj = matrix_into_rowmaj(&r1cs.a);
+    let b_rowmaj = matrix_into_rowmaj(&r1cs.b);
+    let c_rowmaj = matrix_into_rowmaj(&r1cs.c);
+    let (commitments, stats) = index_commitments(
+        &ck_stream,
+        &Mat(a_rowmaj.as_slice(), r1cs.a.len()),
+        &Mat(b_rowmaj.as_slice(), r1cs.b.len()),
+        &Mat(c_rowmaj.as_slice(), r1cs.c.len()),
+        r1cs.a.len(),
+        r1cs.z.len(),
+        16,
+    )
+    .expect("index generation failed");
+    assert_eq!(commitments, expected);
+    assert_eq!(stats.joint_non_zero, joint_non_zero);
+}
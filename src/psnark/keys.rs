@@ -0,0 +1,415 @@
+//! A stable binary container for the index keys.
+//!
+//! Keys are written as a header followed by a sequence of sections.
+//! The header holds the magic bytes [`KEY_MAGIC`], the format version [`KEY_FORMAT_VERSION`],
+//! the kind of the key, the identifier of the curve (see [`curve_id`]),
+//! the degree of the index (the number of non-zero entries of the joint matrix),
+//! and the number of sections.
+//! Each section holds a tag, the length of its payload, the payload (serialized uncompressed),
+//! and the SHA-256 digest of the payload.
+//! All integers are little-endian.
+//!
+//! The prover key holds the powers of the trapdoor in \\(\GG_1\\), those in \\(\GG_2\\),
+//! and the [`IndexCache`];
+//! the verifier key holds the [`VerifierKey`] and the commitments to the index.
+//! Sections are written and read incrementally, without being buffered in memory:
+//! with [`write_prover_key_stream`], the powers in \\(\GG_1\\) are read from a stream.
+use ark_ec::pairing::Pairing;
+use ark_ec::AffineRepr;
+use ark_ff::Field;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
+use ark_std::borrow::Borrow;
+use ark_std::vec::Vec;
+use sha2::{Digest, Sha256};
+
+use crate::kzg::{Commitment, VerifierKey};
+#[cfg(feature = "prover")]
+use crate::{
+    iterable::DoubleEndedIterable,
+    kzg::{CommitterKey, CommitterKeyStream},
+    psnark::IndexCache,
+};
+
+/// The magic bytes opening every key file.
+pub const KEY_MAGIC: [u8; 8] = *b"GEMINIKY";
+/// The version of the key format.
+pub const KEY_FORMAT_VERSION: u16 = 1;
+
+#[cfg(feature = "prover")]
+const TAG_POWERS_OF_G: u8 = 0;
+#[cfg(feature = "prover")]
+const TAG_POWERS_OF_G2: u8 = 1;
+#[cfg(feature = "prover")]
+const TAG_INDEX: u8 = 2;
+const TAG_VERIFIER_KEY: u8 = 3;
+const TAG_INDEX_COMMITMENTS: u8 = 4;
+
+/// The kind of a key file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyKind {
+    /// The prover key: the committer key and the index cache.
+    Prover = 0,
+    /// The verifier key: the verification key and the commitments to the index.
+    Verifier = 1,
+}
+
+/// The header of a key file.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyHeader {
+    /// The kind of the key.
+    pub kind: KeyKind,
+    /// The identifier of the curve.
+    pub curve_id: [u8; 8],
+    /// The number of non-zero entries of the joint matrix of the index.
+    pub degree: u64,
+    /// The number of sections following the header.
+    pub num_sections: u8,
+}
+
+/// Return the identifier of the bilinear group `E`:
+/// the first 8 bytes of the SHA-256 digest of the characteristic of the scalar field
+/// and of the generators of \\(\GG_1\\) and \\(\GG_2\\).
+pub fn curve_id<E: Pairing>() -> [u8; 8] {
+    let mut hasher = Sha256::new();
+    for limb in E::ScalarField::characteristic() {
+        hasher.update(limb.to_le_bytes());
+    }
+    let mut bytes = Vec::new();
+    E::G1Affine::generator()
+        .serialize_uncompressed(&mut bytes)
+        .expect("serialization into a vector cannot fail");
+    E::G2Affine::generator()
+        .serialize_uncompressed(&mut bytes)
+        .expect("serialization into a vector cannot fail");
+    hasher.update(&bytes);
+    let digest = hasher.finalize();
+    let mut id = [0u8; 8];
+    id.copy_from_slice(&digest[..8]);
+    id
+}
+
+/// A writer hashing and counting the bytes of a section.
+struct SectionWriter<'a, W> {
+    inner: &'a mut W,
+    hasher: Sha256,
+    written: u64,
+}
+
+impl<'a, W: Write> Write for SectionWriter<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> ark_std::io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> ark_std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// A reader hashing the bytes of a section, and not reading past its end.
+struct SectionReader<'a, R> {
+    inner: &'a mut R,
+    hasher: Sha256,
+    remaining: u64,
+}
+
+impl<'a, R: Read> Read for SectionReader<'a, R> {
+    fn read(&mut self, buf: &mut [u8]) -> ark_std::io::Result<usize> {
+        let len = ark_std::cmp::min(buf.len() as u64, self.remaining) as usize;
+        let n = self.inner.read(&mut buf[..len])?;
+        self.hasher.update(&buf[..n]);
+        self.remaining -= n as u64;
+        Ok(n)
+    }
+}
+
+/// Write a key file, one section at a time.
+pub struct KeyWriter<W> {
+    writer: W,
+    remaining_sections: u8,
+}
+
+impl<W: Write> KeyWriter<W> {
+    /// Write the header of a key of kind `kind` over `E`, of degree `degree`, with `num_sections` sections.
+    pub fn new<E: Pairing>(
+        mut writer: W,
+        kind: KeyKind,
+        degree: u64,
+        num_sections: u8,
+    ) -> Result<Self, SerializationError> {
+        writer.write_all(&KEY_MAGIC)?;
+        writer.write_all(&KEY_FORMAT_VERSION.to_le_bytes())?;
+        writer.write_all(&[kind as u8])?;
+        writer.write_all(&curve_id::<E>())?;
+        writer.write_all(&degree.to_le_bytes())?;
+        writer.write_all(&[num_sections])?;
+        Ok(Self {
+            writer,
+            remaining_sections: num_sections,
+        })
+    }
+
+    /// Write the section `tag`, whose payload of `len` bytes is written by `payload`.
+    fn write_payload(
+        &mut self,
+        tag: u8,
+        len: u64,
+        payload: impl FnOnce(&mut SectionWriter<'_, W>) -> Result<(), SerializationError>,
+    ) -> Result<(), SerializationError> {
+        if self.remaining_sections == 0 {
+            return Err(SerializationError::InvalidData);
+        }
+        self.remaining_sections -= 1;
+        self.writer.write_all(&[tag])?;
+        self.writer.write_all(&len.to_le_bytes())?;
+        let mut section = SectionWriter {
+            inner: &mut self.writer,
+            hasher: Sha256::new(),
+            written: 0,
+        };
+        payload(&mut section)?;
+        if section.written != len {
+            return Err(SerializationError::InvalidData);
+        }
+        let checksum = section.hasher.finalize();
+        Ok(self.writer.write_all(&checksum)?)
+    }
+
+    /// Write `value` in the section `tag`.
+    pub fn write_section<S: CanonicalSerialize>(
+        &mut self,
+        tag: u8,
+        value: &S,
+    ) -> Result<(), SerializationError> {
+        let len = value.uncompressed_size() as u64;
+        self.write_payload(tag, len, |section| value.serialize_uncompressed(section))
+    }
+
+    /// Write the `len` elements of `elements` in the section `tag`,
+    /// as a vector (to be read back with [`KeyReader::read_section`] as a `Vec`).
+    ///
+    /// All elements must have the same serialized size, e.g., field elements or curve points.
+    pub fn write_section_iter<T, I>(
+        &mut self,
+        tag: u8,
+        len: usize,
+        elements: I,
+    ) -> Result<(), SerializationError>
+    where
+        T: CanonicalSerialize,
+        I: IntoIterator,
+        I::Item: Borrow<T>,
+    {
+        let mut elements = elements.into_iter().peekable();
+        let element_size = elements
+            .peek()
+            .map_or(0, |e| e.borrow().uncompressed_size());
+        let payload_len = (len as u64).uncompressed_size() + len * element_size;
+        self.write_payload(tag, payload_len as u64, |section| {
+            (len as u64).serialize_uncompressed(&mut *section)?;
+            let mut count = 0;
+            for element in elements {
+                element.borrow().serialize_uncompressed(&mut *section)?;
+                count += 1;
+            }
+            if count != len {
+                return Err(SerializationError::InvalidData);
+            }
+            Ok(())
+        })
+    }
+
+    /// Check that all sections have been written, and return the underlying writer.
+    pub fn finish(mut self) -> Result<W, SerializationError> {
+        if self.remaining_sections != 0 {
+            return Err(SerializationError::InvalidData);
+        }
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Read a key file, one section at a time.
+pub struct KeyReader<R> {
+    reader: R,
+    header: KeyHeader,
+    remaining_sections: u8,
+}
+
+impl<R: Read> KeyReader<R> {
+    /// Read the header of a key of kind `kind` over `E`.
+    ///
+    /// Fails if the magic bytes, the version, the kind, or the curve do not match.
+    pub fn new<E: Pairing>(mut reader: R, kind: KeyKind) -> Result<Self, SerializationError> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        let mut version = [0u8; 2];
+        reader.read_exact(&mut version)?;
+        let mut found_kind = [0u8; 1];
+        reader.read_exact(&mut found_kind)?;
+        let mut found_curve_id = [0u8; 8];
+        reader.read_exact(&mut found_curve_id)?;
+        let mut degree = [0u8; 8];
+        reader.read_exact(&mut degree)?;
+        let mut num_sections = [0u8; 1];
+        reader.read_exact(&mut num_sections)?;
+        if magic != KEY_MAGIC
+            || u16::from_le_bytes(version) != KEY_FORMAT_VERSION
+            || found_kind[0] != kind as u8
+            || found_curve_id != curve_id::<E>()
+        {
+            return Err(SerializationError::InvalidData);
+        }
+        let header = KeyHeader {
+            kind,
+            curve_id: found_curve_id,
+            degree: u64::from_le_bytes(degree),
+            num_sections: num_sections[0],
+        };
+        Ok(Self {
+            reader,
+            header,
+            remaining_sections: header.num_sections,
+        })
+    }
+
+    /// The header of the key.
+    pub fn header(&self) -> &KeyHeader {
+        &self.header
+    }
+
+    /// Read the section `tag`.
+    ///
+    /// Fails if the next section has a different tag,
+    /// if its payload is malformed or has trailing bytes, or if its checksum does not match.
+    pub fn read_section<S: CanonicalDeserialize>(
+        &mut self,
+        tag: u8,
+    ) -> Result<S, SerializationError> {
+        if self.remaining_sections == 0 {
+            return Err(SerializationError::InvalidData);
+        }
+        self.remaining_sections -= 1;
+        let mut found_tag = [0u8; 1];
+        self.reader.read_exact(&mut found_tag)?;
+        let mut len = [0u8; 8];
+        self.reader.read_exact(&mut len)?;
+        if found_tag[0] != tag {
+            return Err(SerializationError::InvalidData);
+        }
+        let mut section = SectionReader {
+            inner: &mut self.reader,
+            hasher: Sha256::new(),
+            remaining: u64::from_le_bytes(len),
+        };
+        let value = S::deserialize_uncompressed(&mut section)?;
+        if section.remaining != 0 {
+            return Err(SerializationError::InvalidData);
+        }
+        let expected = section.hasher.finalize();
+        let mut checksum = [0u8; 32];
+        self.reader.read_exact(&mut checksum)?;
+        if checksum[..] != expected[..] {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(value)
+    }
+
+    /// Check that all sections have been read, and return the underlying reader.
+    pub fn finish(self) -> Result<R, SerializationError> {
+        if self.remaining_sections != 0 {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(self.reader)
+    }
+}
+
+/// Write the prover key, made of the committer key `ck` and the index `cache`, to `writer`.
+#[cfg(feature = "prover")]
+pub fn write_prover_key<E: Pairing, W: Write>(
+    ck: &CommitterKey<E>,
+    cache: &IndexCache<E>,
+    writer: W,
+) -> Result<(), SerializationError> {
+    let mut keys = KeyWriter::new::<E>(writer, KeyKind::Prover, cache.num_non_zero() as u64, 3)?;
+    keys.write_section(TAG_POWERS_OF_G, &ck.powers_of_g)?;
+    keys.write_section(TAG_POWERS_OF_G2, &ck.powers_of_g2)?;
+    keys.write_section(TAG_INDEX, cache)?;
+    keys.finish().map(|_| ())
+}
+
+/// Same as [`write_prover_key`], but reading the powers in \\(\GG_1\\) from the stream of `ck`,
+/// holding only one element in memory at a time.
+#[cfg(feature = "prover")]
+pub fn write_prover_key_stream<E, SG, W>(
+    ck: &CommitterKeyStream<E, SG>,
+    cache: &IndexCache<E>,
+    writer: W,
+) -> Result<(), SerializationError>
+where
+    E: Pairing,
+    SG: DoubleEndedIterable,
+    SG::Item: Borrow<E::G1Affine>,
+    W: Write,
+{
+    let mut keys = KeyWriter::new::<E>(writer, KeyKind::Prover, cache.num_non_zero() as u64, 3)?;
+    // the stream holds the powers from the highest.
+    keys.write_section_iter::<E::G1Affine, _>(
+        TAG_POWERS_OF_G,
+        ck.powers_of_g.len(),
+        ck.powers_of_g.rev_iter(),
+    )?;
+    keys.write_section(TAG_POWERS_OF_G2, &ck.powers_of_g2)?;
+    keys.write_section(TAG_INDEX, cache)?;
+    keys.finish().map(|_| ())
+}
+
+/// Read a prover key written with [`write_prover_key`] or [`write_prover_key_stream`].
+#[cfg(feature = "prover")]
+pub fn read_prover_key<E: Pairing, R: Read>(
+    reader: R,
+) -> Result<(CommitterKey<E>, IndexCache<E>), SerializationError> {
+    let mut keys = KeyReader::new::<E>(reader, KeyKind::Prover)?;
+    let powers_of_g = keys.read_section(TAG_POWERS_OF_G)?;
+    let powers_of_g2 = keys.read_section(TAG_POWERS_OF_G2)?;
+    let cache: IndexCache<E> = keys.read_section(TAG_INDEX)?;
+    if cache.num_non_zero() as u64 != keys.header().degree {
+        return Err(SerializationError::InvalidData);
+    }
+    keys.finish()?;
+    let ck = CommitterKey {
+        powers_of_g,
+        powers_of_g2,
+    };
+    Ok((ck, cache))
+}
+
+/// Write the verifier key, made of the verification key `vk` and the commitments `index_comms`
+/// to an index with `num_non_zero` non-zero entries, to `writer`.
+pub fn write_verifier_key<E: Pairing, W: Write>(
+    vk: &VerifierKey<E>,
+    index_comms: &[Commitment<E>],
+    num_non_zero: usize,
+    writer: W,
+) -> Result<(), SerializationError> {
+    let mut keys = KeyWriter::new::<E>(writer, KeyKind::Verifier, num_non_zero as u64, 2)?;
+    keys.write_section(TAG_VERIFIER_KEY, vk)?;
+    keys.write_section(TAG_INDEX_COMMITMENTS, &index_comms.to_vec())?;
+    keys.finish().map(|_| ())
+}
+
+/// Read a verifier key written with [`write_verifier_key`]:
+/// return the verification key, the commitments to the index, and its number of non-zero entries.
+pub fn read_verifier_key<E: Pairing, R: Read>(
+    reader: R,
+) -> Result<(VerifierKey<E>, Vec<Commitment<E>>, usize), SerializationError> {
+    let mut keys = KeyReader::new::<E>(reader, KeyKind::Verifier)?;
+    let vk = keys.read_section(TAG_VERIFIER_KEY)?;
+    let index_comms = keys.read_section(TAG_INDEX_COMMITMENTS)?;
+    let num_non_zero =
+        usize::try_from(keys.header().degree).map_err(|_| SerializationError::InvalidData)?;
+    keys.finish()?;
+    Ok((vk, index_comms, num_non_zero))
+}
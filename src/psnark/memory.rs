@@ -0,0 +1,130 @@
+//! Memory usage of the elastic prover.
+//!
+//! The elastic prover never holds the full instance in memory:
+//! its footprint is dominated by few buffers whose size depends on
+//! the length of the streams and on the [`ProverConfig`](crate::config::ProverConfig).
+//! A [`MemoryReport`] records, for each phase of the prover,
+//! an estimate of the peak size of these buffers,
+//! together with the number of passes over the instance.
+//! The sizes are computed from the lengths of the buffers the prover allocates,
+//! not measured: allocator overhead, temporaries, and the state of the streams are not accounted for.
+use ark_ec::pairing::Pairing;
+use ark_std::mem::size_of;
+
+use crate::config::ProverConfig;
+use crate::errors::{GeminiError, GeminiResult};
+
+/// The number of sumchecks batched in the last sumcheck of the prover:
+/// the nine entry products of the plookup arguments, and four inner products.
+pub(crate) const BATCHED_SUMCHECKS: usize = 13;
+
+/// Estimated peak size (in bytes) of the buffers allocated in each phase of the elastic prover.
+///
+/// Sizes are estimates, derived from the number of scalars and group elements in the buffers
+/// (see the [module documentation](self)), and not measurements of the heap:
+/// they do not account for the (logarithmic) state of the streams.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryReport {
+    /// Commitments to the witness, to the lookups, and to the sorted vectors.
+    pub commitments: usize,
+    /// The sumchecks, once the elastic provers switch to the time-efficient strategy.
+    pub sumchecks: usize,
+    /// The entry products for the plookup arguments, including the commitments to the accumulated products.
+    pub entry_products: usize,
+    /// Commitments and openings of the tensorcheck.
+    pub tensorcheck: usize,
+    /// The index streams held in memory throughout the proof, on top of the buffers of each phase:
+    /// the rows and the columns are counted at the size of a `usize`,
+    /// and the values at the size of a field element.
+    pub index: usize,
+    /// The number of passes over the streams of the instance (not a size),
+    /// counting each stream of matrices, vectors, and witness separately.
+    pub passes: usize,
+}
+
+impl MemoryReport {
+    /// The estimated peak memory across all phases,
+    /// excluding the index streams held in memory.
+    pub fn peak(&self) -> usize {
+        usize::max(
+            usize::max(self.commitments, self.sumchecks),
+            usize::max(self.entry_products, self.tensorcheck),
+        )
+    }
+
+    /// An upper bound on the report of the elastic prover configured by `config`
+    /// over a (padded) instance with `num_variables` variables and `joint_len` non-zero entries,
+    /// before proving.
+    ///
+    /// The streams of the prover are at most as long as the sorted vectors of the plookup arguments,
+    /// i.e. `num_variables + joint_len + 1` elements. Passes are not bounded,
+    /// and the index streams are held in memory only within what the cap leaves to them.
+    pub(crate) fn bound<E: Pairing>(
+        num_variables: usize,
+        joint_len: usize,
+        config: &ProverConfig,
+    ) -> Self {
+        let len = num_variables + joint_len + 1;
+        let (chunk_size, threshold) = (config.chunk_size, config.threshold());
+        MemoryReport {
+            commitments: usize::max(
+                commit_buffer::<E>(len, chunk_size),
+                commit_joint_buffer::<E>(joint_len, chunk_size, 3),
+            ),
+            sumchecks: usize::max(
+                sumcheck_buffer::<E>(joint_len, threshold),
+                BATCHED_SUMCHECKS * sumcheck_buffer::<E>(len, threshold),
+            ),
+            entry_products: usize::max(
+                commit_buffer::<E>(len, chunk_size),
+                msm_buffer::<E>(config.max_msm_buffer),
+            ),
+            tensorcheck: msm_buffer::<E>(config.max_msm_buffer),
+            index: 0,
+            passes: 0,
+        }
+    }
+
+    /// Fail if the buffers of any phase exceed `cap` bytes.
+    pub(crate) fn ensure_cap(&self, cap: usize) -> GeminiResult<()> {
+        let phases = [
+            ("commitments", self.commitments),
+            ("sumchecks", self.sumchecks),
+            ("entry_products", self.entry_products),
+            ("tensorcheck", self.tensorcheck),
+        ];
+        match phases.into_iter().find(|&(_, required)| required > cap) {
+            Some((phase, required)) => Err(GeminiError::MemoryCapExceeded {
+                phase,
+                required,
+                cap,
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The buffer size of a streaming commitment to a polynomial of length `len`,
+/// collecting `chunk_size` elements at once.
+pub(crate) fn commit_buffer<E: Pairing>(len: usize, chunk_size: usize) -> usize {
+    usize::min(len, chunk_size) * (size_of::<E::G1Affine>() + size_of::<E::ScalarField>())
+}
+
+/// The buffer size of a streaming commitment to `n` polynomials of length `len` streamed jointly,
+/// collecting `chunk_size` elements at once.
+pub(crate) fn commit_joint_buffer<E: Pairing>(len: usize, chunk_size: usize, n: usize) -> usize {
+    usize::min(len, chunk_size) * (size_of::<E::G1Affine>() + n * size_of::<E::ScalarField>())
+}
+
+/// The buffer size of a multi-scalar multiplication holding at most `max_msm_buffer` pairs
+/// and as many buckets.
+pub(crate) fn msm_buffer<E: Pairing>(max_msm_buffer: usize) -> usize {
+    max_msm_buffer * (size_of::<E::G1Affine>() + size_of::<E::ScalarField>() + size_of::<E::G1>())
+}
+
+/// The buffer size of an elastic sumcheck over two streams of length `len`,
+/// once it switches to the time-efficient prover for the last `threshold` rounds.
+pub(crate) fn sumcheck_buffer<E: Pairing>(len: usize, threshold: usize) -> usize {
+    let time_len = usize::min(len.next_power_of_two(), 1 << threshold);
+    2 * time_len * size_of::<E::ScalarField>()
+}
@@ -1,29 +1,73 @@
 //! Elastic preprocessing SNARK for R1CS.
 //!
-//!
-//!
-//!
-//!
+//! Proofs are _not_ zero-knowledge:
+//! the commitments are not hiding, and the sumcheck messages are not masked,
+//! hence they leak information about the witness.
+//! Do not use this SNARK when the witness must be kept private.
+#[cfg(feature = "snark-trait")]
+mod arkworks;
+#[cfg(feature = "prover")]
+mod cache;
+#[cfg(feature = "prover")]
 mod elastic_prover;
+mod index;
+mod indexer;
+mod keys;
+pub(crate) mod memory;
+#[cfg(feature = "prover")]
 mod time_prover;
 mod verifier;
 
 mod streams;
+/// The algebraic hash is exposed so that arguments built on top of this crate
+/// can bind their own streams to indices (e.g. for memory-checking),
+/// and the multi-column lookups so that they can look up tuples with a single argument
+/// (e.g. for range or decomposition checks).
+pub use streams::{AlgebraicHash, CombinedColumns, MultiLookupStreamer};
 
-#[cfg(test)]
+#[cfg(feature = "snark-trait")]
+pub use arkworks::{GeminiPsnark, ProvingKey, VerifyingKey};
+#[cfg(feature = "prover")]
+pub use cache::IndexCache;
+#[cfg(feature = "prover")]
+pub use index::{generate_index_keys, IndexProverKey};
+pub use index::IndexVerifierKey;
+pub use indexer::R1csStats;
+#[cfg(feature = "std")]
+pub use indexer::index_commitments;
+pub use keys::{
+    curve_id, read_verifier_key, write_verifier_key, KeyHeader, KeyKind, KeyReader, KeyWriter,
+    KEY_FORMAT_VERSION, KEY_MAGIC,
+};
+#[cfg(feature = "prover")]
+pub use keys::{read_prover_key, write_prover_key, write_prover_key_stream};
+pub use memory::MemoryReport;
+
+#[cfg(all(test, feature = "prover"))]
 mod tests;
 
 use ark_ec::pairing::Pairing;
+use ark_ff::Zero;
 use ark_serialize::*;
+use ark_std::log2;
 use ark_std::vec::Vec;
 
+use crate::circuit::padded_dim;
 use crate::kzg::{Commitment, EvaluationProof};
+use crate::snark::SizeBreakdown;
 use crate::subprotocols::entryproduct;
 use crate::subprotocols::sumcheck::prover::ProverMsgs;
 use crate::subprotocols::tensorcheck::TensorcheckProof;
+use crate::PROOF_FORMAT_VERSION;
+
+/// The identifier of the preprocessing SNARK, bound to the transcript.
+pub(crate) const PSNARK_LABEL: &[u8] = b"psnark";
 
 /// The preprocessing SNARK proof, containing all prover messages.
-#[derive(CanonicalSerialize, PartialEq, Eq)]
+///
+/// Proving is deterministic: the same instance, committer key, and transcript
+/// always yield the same proof.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, PartialEq, Eq)]
 pub struct Proof<E: Pairing> {
     witness_commitment: Commitment<E>,
     zc_alpha: E::ScalarField,
@@ -47,3 +91,122 @@ pub struct Proof<E: Pairing> {
     third_sumcheck_msgs: ProverMsgs<E::ScalarField>,
     tensorcheck_proof: TensorcheckProof<E>,
 }
+
+impl<E: Pairing> ark_std::fmt::Debug for Proof<E> {
+    fn fmt(&self, f: &mut ark_std::fmt::Formatter<'_>) -> ark_std::fmt::Result {
+        f.debug_struct("Proof").finish()
+    }
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Serialize the proof, with compressed points, prefixed by [`PROOF_FORMAT_VERSION`].
+    pub fn serialize_versioned<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        PROOF_FORMAT_VERSION.serialize_compressed(&mut writer)?;
+        self.serialize_compressed(writer)
+    }
+
+    /// Deserialize a proof serialized with [`Proof::serialize_versioned`].
+    ///
+    /// Fails if the format version does not match [`PROOF_FORMAT_VERSION`],
+    /// if any point is not in the prime-order subgroup,
+    /// or if the proof is malformed.
+    pub fn deserialize_versioned<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let version = u8::deserialize_compressed(&mut reader)?;
+        if version != PROOF_FORMAT_VERSION {
+            return Err(SerializationError::InvalidData);
+        }
+        let proof = Self::deserialize_compressed(reader)?;
+        if !(proof.ep_msgs.is_well_formed()
+            && proof.ralpha_star_acc_mu_evals.len() == proof.ep_msgs.acc_v_commitments.len() + 1
+            && proof.tensorcheck_proof.is_well_formed())
+        {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(proof)
+    }
+
+    /// The size (in bytes) of the proof serialized with [`Proof::serialize_versioned`].
+    pub fn serialized_size(&self) -> usize {
+        PROOF_FORMAT_VERSION.compressed_size() + self.compressed_size()
+    }
+
+    /// Return the size of each component of the proof serialized with [`Proof::serialize_versioned`].
+    pub fn size_breakdown(&self) -> SizeBreakdown {
+        let mut breakdown = SizeBreakdown::versioned();
+        breakdown.commitments += self.witness_commitment.compressed_size()
+            + self.r_star_commitments.compressed_size()
+            + self.z_star_commitment.compressed_size()
+            + self.sorted_r_commitment.compressed_size()
+            + self.sorted_alpha_commitment.compressed_size()
+            + self.sorted_z_commitment.compressed_size()
+            + self.ep_msgs.acc_v_commitments.compressed_size();
+        breakdown.evaluations += self.zc_alpha.compressed_size()
+            + [self.set_r_ep, self.subset_r_ep].compressed_size()
+            + [self.set_alpha_ep, self.subset_alpha_ep].compressed_size()
+            + [self.set_z_ep, self.subset_z_ep].compressed_size()
+            + self.ep_msgs.claimed_sumchecks.compressed_size()
+            + self.ralpha_star_acc_mu_evals.compressed_size()
+            + self.rstars_vals.compressed_size();
+        breakdown.openings += self.ralpha_star_acc_mu_proof.compressed_size();
+        breakdown.add_sumcheck(&self.first_sumcheck_msgs);
+        breakdown.add_sumcheck(&self.second_sumcheck_msgs);
+        breakdown.add_sumcheck(&self.third_sumcheck_msgs);
+        breakdown.add_tensorcheck(&self.tensorcheck_proof);
+        breakdown
+    }
+
+    /// Return the size (in bytes) of the proof for any instance
+    /// with `num_constraints` constraints, `num_variables` variables (the length of $\vec z$),
+    /// and `num_non_zero` non-zero entries in the joint matrix,
+    /// serialized with the version byte and in compression mode `compress`,
+    /// without running the prover.
+    ///
+    /// With [`Compress::Yes`], this is the size of [`Proof::serialize_versioned`].
+    pub fn estimate_size(
+        num_constraints: usize,
+        num_variables: usize,
+        num_non_zero: usize,
+        compress: Compress,
+    ) -> usize {
+        let dim = padded_dim(num_constraints, num_variables);
+        let zero = E::ScalarField::zero();
+        let commitment = Commitment(E::G1::zero());
+        // the entry products of the three lookups, for the set, the subset, and the sorted vector.
+        let num_entry_products = 9;
+        // the sumcheck over z, then the sumcheck batching the entry products,
+        // whose longest vector is the monic sorted vector of length dim + num_non_zero + 2,
+        // with the products with val_a, val_b, val_c, and the twisted one.
+        let first_rounds = log2(dim) as usize;
+        let second_rounds = log2(num_non_zero) as usize;
+        let third_rounds = log2(dim + num_non_zero + 2) as usize;
+        let foldings = 2 * third_rounds.saturating_sub(1) + 2 * second_rounds.saturating_sub(1);
+        let proof = Proof::<E> {
+            witness_commitment: commitment,
+            zc_alpha: zero,
+            first_sumcheck_msgs: ProverMsgs::zeroed(first_rounds, 1),
+            r_star_commitments: [commitment; 3],
+            z_star_commitment: commitment,
+            second_sumcheck_msgs: ProverMsgs::zeroed(second_rounds, 1),
+            set_r_ep: zero,
+            subset_r_ep: zero,
+            sorted_r_commitment: commitment,
+            set_alpha_ep: zero,
+            subset_alpha_ep: zero,
+            sorted_alpha_commitment: commitment,
+            set_z_ep: zero,
+            subset_z_ep: zero,
+            sorted_z_commitment: commitment,
+            ep_msgs: entryproduct::ProverMsgs {
+                acc_v_commitments: vec![commitment; num_entry_products],
+                claimed_sumchecks: vec![zero; num_entry_products],
+            },
+            ralpha_star_acc_mu_evals: vec![zero; num_entry_products + 1],
+            ralpha_star_acc_mu_proof: EvaluationProof(E::G1::zero()),
+            rstars_vals: [zero; 2],
+            third_sumcheck_msgs: ProverMsgs::zeroed(third_rounds, num_entry_products + 4),
+            tensorcheck_proof: TensorcheckProof::zeroed(foldings, 22),
+        };
+        PROOF_FORMAT_VERSION.compressed_size()
+            + CanonicalSerialize::serialized_size(&proof, compress)
+    }
+}
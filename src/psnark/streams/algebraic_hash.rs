@@ -3,12 +3,30 @@ use ark_std::borrow::Borrow;
 
 use crate::iterable::Iterable;
 
+/// A streaming algebraic hash of a vector together with its positions.
+///
+/// Given a stream of elements $\vec f \in \FF^n$,
+/// a stream of indices $\vec I \in [N]^n$,
+/// and a challenge $\zeta \in \FF$,
+/// stream the vector:
+/// $$
+/// (f_0 + \zeta I_0, f_1 + \zeta I_1, \dots, f_{n-1} + \zeta I_{n-1}).
+/// $$
+/// The resulting stream binds each element to its index,
+/// so that multiset (or memory-checking style) arguments over pairs $(I_i, f_i)$
+/// reduce to arguments over a single vector.
+/// The [psnark](crate::psnark) uses it, for instance, to hash $\vec z^*$ with the column indices
+/// before running plookup.
+///
+/// The element stream and the index stream are expected to have the same length;
+/// the length of the hashed stream is the length of the element stream.
 pub struct AlgebraicHash<'a, F, EltS, IdxS> {
     chal: F,
     elt_stream: &'a EltS,
     idx_stream: &'a IdxS,
 }
 
+/// The iterator returned by [`AlgebraicHash`].
 pub struct AlgebraicHashIterator<EltI, IdxI, F> {
     elt_it: EltI,
     idx_it: IdxI,
@@ -23,8 +41,9 @@ where
     EltS::Item: Borrow<F>,
     F: Field,
 {
+    /// Hash the stream `elt_stream` with the positions `idx_stream` using the challenge `chal`.
     pub fn new(elt_stream: &'a EltS, idx_stream: &'a IdxS, chal: F) -> Self {
-        // assert_eq!(elt_stream.len(), idx_stream.len());
+        debug_assert_eq!(elt_stream.len(), idx_stream.len());
         Self {
             elt_stream,
             idx_stream,
@@ -79,3 +98,28 @@ where
         Some(self.chal * F::from(index) + element.borrow())
     }
 }
+
+#[test]
+fn test_algebraic_hash_consistency() {
+    use ark_bls12_381::Fr as F;
+    use ark_ff::UniformRand;
+    use ark_std::test_rng;
+    use ark_std::vec::Vec;
+
+    use crate::iterable::Reverse;
+    use crate::subprotocols::plookup::time_prover::alg_hash;
+
+    let rng = &mut test_rng();
+    let elements = (0..100).map(|_| F::rand(rng)).collect::<Vec<_>>();
+    let indices = (0..100).map(|i| (i * 7) % 13).collect::<Vec<usize>>();
+    let chal = F::rand(rng);
+
+    let expected = alg_hash(&elements, &indices, &chal);
+    let elements_stream = Reverse(elements.as_slice());
+    let indices_stream = Reverse(indices.as_slice());
+    let mut got = AlgebraicHash::new(&elements_stream, &indices_stream, chal)
+        .iter()
+        .collect::<Vec<_>>();
+    got.reverse();
+    assert_eq!(got, expected);
+}
@@ -1,7 +1,10 @@
+use ark_ff::Field;
 use ark_std::borrow::Borrow;
+use ark_std::vec::Vec;
 
 use crate::iterable::Iterable;
 
+#[cfg(feature = "prover")]
 #[derive(Clone, Copy)]
 pub struct LookupStreamer<'a, S, I>
 where
@@ -24,6 +27,7 @@ where
     current_item: Option<I::Item>,
 }
 
+#[cfg(feature = "prover")]
 impl<'a, S, I> LookupStreamer<'a, S, I>
 where
     S: Iterable,
@@ -36,6 +40,7 @@ where
     }
 }
 
+#[cfg(feature = "prover")]
 impl<'a, S, I> Iterable for LookupStreamer<'a, S, I>
 where
     S: Iterable,
@@ -92,6 +97,149 @@ where
     }
 }
 
+/// The random linear combination $\sum_j \chi^j \vec t_j$ of the (big-endian) columns $\vec t_j$ of a table,
+/// streamed jointly.
+///
+/// Combining the columns with a random combiner $\chi$ reduces a lookup of tuples
+/// into a multi-column table to a lookup into a single column,
+/// hence several columns are looked up with a single argument.
+/// All columns are expected to have the same length.
+#[derive(Clone, Copy)]
+pub struct CombinedColumns<'a, F, S> {
+    columns: &'a [S],
+    combiner: F,
+}
+
+/// The iterator returned by [`CombinedColumns`].
+pub struct CombinedColumnsIter<F, I> {
+    columns: Vec<I>,
+    combiner: F,
+}
+
+impl<'a, F, S> CombinedColumns<'a, F, S>
+where
+    F: Field,
+    S: Iterable,
+    S::Item: Borrow<F>,
+{
+    /// Combine the (non-empty) `columns` with the powers of `combiner`.
+    pub fn new(columns: &'a [S], combiner: F) -> Self {
+        assert!(!columns.is_empty(), "no columns to combine");
+        debug_assert!(columns.iter().all(|c| c.len() == columns[0].len()));
+        Self { columns, combiner }
+    }
+}
+
+impl<F, I> Iterator for CombinedColumnsIter<F, I>
+where
+    F: Field,
+    I: Iterator,
+    I::Item: Borrow<F>,
+{
+    type Item = F;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut combined = F::zero();
+        for column in self.columns.iter_mut().rev() {
+            combined = combined * self.combiner + column.next()?.borrow();
+        }
+        Some(combined)
+    }
+
+    fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        self.columns
+            .iter_mut()
+            .map(|column| column.advance_by(n))
+            .fold(Ok(()), Result::and)
+    }
+}
+
+impl<'a, F, S> Iterable for CombinedColumns<'a, F, S>
+where
+    F: Field,
+    S: Iterable,
+    S::Item: Borrow<F>,
+{
+    type Item = F;
+
+    type Iter = CombinedColumnsIter<F, S::Iter>;
+
+    fn iter(&self) -> Self::Iter {
+        CombinedColumnsIter {
+            columns: self.columns.iter().map(|column| column.iter()).collect(),
+            combiner: self.combiner,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.columns[0].len()
+    }
+}
+
+/// A batched lookup of tuples from several columns.
+///
+/// Given the columns $\vec t_0, \dots, \vec t_{k-1}$ of a table,
+/// a (sorted, big-endian) stream of positions $\vec I$, and a combiner $\chi$,
+/// stream the combined tuples $\sum_j \chi^j t_{j, I_i}$ at the positions of $\vec I$,
+/// i.e. the lookup of $\vec I$ in the [`CombinedColumns`] of the table.
+/// Hashing the looked-up stream and the combined table with the positions (see [`AlgebraicHash`](super::AlgebraicHash))
+/// proves all $k$ lookups with a single plookup argument, instead of one per column.
+#[derive(Clone, Copy)]
+pub struct MultiLookupStreamer<'a, F, S, I> {
+    table: CombinedColumns<'a, F, S>,
+    indices: &'a I,
+}
+
+impl<'a, F, S, I> MultiLookupStreamer<'a, F, S, I>
+where
+    F: Field,
+    S: Iterable,
+    S::Item: Borrow<F>,
+    I: Iterable,
+    I::Item: Borrow<usize>,
+{
+    /// Look up the positions `indices` in the (non-empty) `columns`, combined with `combiner`.
+    pub fn new(columns: &'a [S], indices: &'a I, combiner: F) -> Self {
+        Self {
+            table: CombinedColumns::new(columns, combiner),
+            indices,
+        }
+    }
+
+    /// The table, combined as the looked-up tuples.
+    pub fn table(&self) -> CombinedColumns<'a, F, S> {
+        self.table
+    }
+}
+
+impl<'a, F, S, I> Iterable for MultiLookupStreamer<'a, F, S, I>
+where
+    F: Field,
+    S: Iterable,
+    S::Item: Borrow<F>,
+    I: Iterable,
+    I::Item: Borrow<usize>,
+{
+    type Item = F;
+
+    type Iter = LookupIter<CombinedColumnsIter<F, S::Iter>, I::Iter>;
+
+    fn iter(&self) -> Self::Iter {
+        LookupIter {
+            item_stream: self.table.iter(),
+            index_stream: self.indices.iter(),
+            current_height: self.table.len(),
+            current_item: None,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.indices.len()
+    }
+}
+
+#[cfg(feature = "prover")]
 #[test]
 fn test_index() {
     use ark_std::vec::Vec;
@@ -103,3 +251,30 @@ fn test_index() {
     let stream = stream.iter().cloned().collect::<Vec<_>>();
     assert_eq!(stream, indices);
 }
+
+#[test]
+fn test_multi_lookup() {
+    use ark_bls12_381::Fr;
+    use ark_ff::UniformRand;
+    use ark_std::test_rng;
+
+    use crate::iterable::Reverse;
+
+    let rng = &mut test_rng();
+    let columns = (0..3)
+        .map(|_| (0..8).map(|_| Fr::rand(rng)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    let streams = columns
+        .iter()
+        .map(|column| Reverse(column.as_slice()))
+        .collect::<Vec<_>>();
+    let indices: &[usize] = &[7, 5, 5, 2, 0];
+    let combiner = Fr::rand(rng);
+
+    let lookup = MultiLookupStreamer::new(&streams, &indices, combiner);
+    assert_eq!(lookup.len(), indices.len());
+    let combine =
+        |i: usize| combiner.square() * columns[2][i] + combiner * columns[1][i] + columns[0][i];
+    assert!(lookup.iter().eq(indices.iter().map(|&i| combine(i))));
+    assert!(lookup.table().iter().eq((0..8).rev().map(combine)));
+}
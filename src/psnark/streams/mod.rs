@@ -1,16 +1,51 @@
 mod algebraic_hash;
+#[cfg(feature = "prover")]
 mod hadamard_stream;
 mod lookup_stream;
+#[cfg(feature = "prover")]
 mod tensor_stream;
 mod val_stream;
 
-mod intofield_stream;
-
+#[cfg(feature = "prover")]
 pub use hadamard_stream::HadamardStreamer;
+#[cfg(feature = "prover")]
 pub use tensor_stream::Tensor;
 // XXX. this struct should probably replace TensorStreamer.
 pub use algebraic_hash::AlgebraicHash;
-pub use intofield_stream::IntoField;
+#[cfg(feature = "prover")]
 pub use lookup_stream::LookupStreamer;
+pub use lookup_stream::{CombinedColumns, MultiLookupStreamer};
+#[cfg(feature = "prover")]
+pub(crate) use tensor_stream::lookup_tensor;
+#[cfg(feature = "prover")]
 pub use tensor_stream::LookupTensorStreamer;
+pub use val_stream::JointMatrixStream;
+#[cfg(feature = "prover")]
 pub use val_stream::{JointColStream, JointRowStream, JointValStream};
+
+use crate::iterable::Iterable;
+
+/// Check that the stream `stream`, labelled `name`, yields exactly `stream.len()` elements.
+///
+/// This requires an additional pass over the stream,
+/// and is performed only when the `check-streams` feature is enabled.
+/// Otherwise, it's a no-op.
+///
+/// # Panics
+/// If the declared length differs from the number of elements that are actually streamed.
+#[cfg(feature = "prover")]
+#[inline]
+pub(crate) fn check_len<S: Iterable>(name: &str, stream: &S) {
+    #[cfg(feature = "check-streams")]
+    {
+        let declared = stream.len();
+        let observed = stream.iter().count();
+        assert_eq!(
+            declared, observed,
+            "stream `{}` declares length {} but yields {} elements",
+            name, declared, observed
+        );
+    }
+    #[cfg(not(feature = "check-streams"))]
+    let _ = (name, stream);
+}
@@ -108,6 +108,19 @@ where
     }
 }
 
+/// Return the element at position `index` of the tensor product expanded in `tensor`.
+#[inline(always)]
+pub(crate) fn lookup_tensor<F: Field>(tensor: &PartialTensor<F>, index: usize) -> F {
+    let mut value = F::one();
+    for (i, r) in tensor.iter().enumerate() {
+        let selection_index = (index >> (i * TENSOR_EXPANSION_LOG)) & T;
+        if selection_index != 0 {
+            value *= r[selection_index - 1];
+        }
+    }
+    value
+}
+
 #[derive(Clone)]
 pub struct LookupTensorStreamer<'a, F, S>
 where
@@ -172,14 +185,7 @@ where
     #[inline(always)]
     fn next(&mut self) -> Option<Self::Item> {
         let index = *self.index.next()?.borrow();
-        let mut value = F::one();
-        for (i, r) in self.tensor.iter().enumerate() {
-            let selection_index = (index >> (i * TENSOR_EXPANSION_LOG)) & T;
-            if selection_index != 0 {
-                value *= r[selection_index - 1];
-            }
-        }
-        Some(value)
+        Some(lookup_tensor(&self.tensor, index))
     }
 
     fn advance_by(&mut self, n: usize) -> Result<(), usize> {
@@ -1,4 +1,6 @@
 use crate::iterable::Iterable;
+#[cfg(feature = "prover")]
+use crate::iterable::Map;
 use crate::misc::MatrixElement;
 use ark_ff::Field;
 use ark_std::borrow::Borrow;
@@ -85,222 +87,95 @@ where
     }
 }
 
-pub struct JointIter<IA, IB, F>
+/// Merge three sparse matrix iterators into a single iterator over the union of their
+/// non-zero positions, yielding for each position the values of the three matrices
+/// (padded with zeros where the matrix is zero).
+pub struct JointIter<IA, IB, IC, F>
 where
     IA: Iterator<Item = (usize, usize, F)>,
     IB: Iterator<Item = (usize, usize, F)>,
+    IC: Iterator<Item = (usize, usize, F)>,
     F: Field,
 {
     matrix_a: IA,
     matrix_b: IB,
+    matrix_c: IC,
     current_a: Option<(usize, usize, F)>,
     current_b: Option<(usize, usize, F)>,
+    current_c: Option<(usize, usize, F)>,
 }
 
-impl<IA, IB, F> Iterator for JointIter<IA, IB, F>
+impl<IA, IB, IC, F> JointIter<IA, IB, IC, F>
 where
     IA: Iterator<Item = (usize, usize, F)>,
     IB: Iterator<Item = (usize, usize, F)>,
+    IC: Iterator<Item = (usize, usize, F)>,
     F: Field,
 {
-    type Item = (usize, usize, F);
-
-    #[inline(always)]
-    fn next(&mut self) -> Option<Self::Item> {
-        match (self.current_a, self.current_b) {
-            // If all streams are empty, return None
-            (None, None) => None,
-
-            // If only the lhs is available, stream it
-            (Some(e), None) => {
-                self.current_a = self.matrix_a.next();
-                Some(e)
-            }
-            // If only the rhs is available, pad it with zeros
-            (None, Some((i, j, _))) => {
-                self.current_b = self.matrix_b.next();
-                Some((i, j, F::zero()))
-            }
-            // If only the two are available simultaneously, we have to make a choice:
-            (Some((ia, ja, ea)), Some((ib, jb, _))) => {
-                // two values on the same coordinate => advance both
-                if ia == ib && ja == jb {
-                    self.current_a = self.matrix_a.next();
-                    self.current_b = self.matrix_b.next();
-                    Some((ia, ja, ea))
-                // lhs is ahead => advance rhs
-                // } else if ja < jb || (ja == jb && ib > ia) {
-                } else if ia < ib || (ia == ib && jb > ja) {
-                    self.current_b = self.matrix_b.next();
-                    Some((ib, jb, F::zero()))
-                // rhs is ahed => advance lhs
-                // } else if ja > jb || (ja==jb && ia > ib) {
-                } else if ia > ib || (ia == ib && ja > jb) {
-                    self.current_a = self.matrix_a.next();
-                    Some((ia, ja, ea))
-                // it should never happen e.g. that ib > ia and jb < ja
-                } else {
-                    panic!("Format invalid!")
-                }
-            }
-        }
-    }
-}
-
-#[derive(Clone, Copy)]
-pub struct JointValStream<'a, SA, SB, SC, F>
-where
-    SA: Iterable,
-    SB: Iterable,
-    SC: Iterable,
-    F: Field,
-    SA::Item: Borrow<MatrixElement<F>>,
-    SB::Item: Borrow<MatrixElement<F>>,
-    SC::Item: Borrow<MatrixElement<F>>,
-{
-    matrix_a: SparseMatrixStream<'a, F, SA>,
-    matrix_b: SparseMatrixStream<'a, F, SB>,
-    matrix_c: SparseMatrixStream<'a, F, SC>,
-    joint_len: usize,
-    _field: PhantomData<F>,
-}
-
-impl<'a, SA, SB, SC, F> JointValStream<'a, SA, SB, SC, F>
-where
-    SA: Iterable,
-    SB: Iterable,
-    SC: Iterable,
-    F: Field,
-    SA::Item: Borrow<MatrixElement<F>>,
-    SB::Item: Borrow<MatrixElement<F>>,
-    SC::Item: Borrow<MatrixElement<F>>,
-{
-    pub fn new(
-        matrix_a: &'a SA,
-        matrix_b: &'a SB,
-        matrix_c: &'a SC,
-        len: usize,
-        joint_len: usize,
-    ) -> Self {
-        Self {
-            matrix_a: SparseMatrixStream::new(matrix_a, len),
-            matrix_b: SparseMatrixStream::new(matrix_b, len),
-            matrix_c: SparseMatrixStream::new(matrix_c, len),
-            joint_len,
-
-            _field: PhantomData,
-        }
-    }
-}
-
-impl<IA, IB, F> JointIter<IA, IB, F>
-where
-    IA: Iterator<Item = (usize, usize, F)>,
-    IB: Iterator<Item = (usize, usize, F)>,
-    F: Field,
-{
-    fn new(mut matrix_a: IA, mut matrix_b: IB) -> Self {
+    fn new(mut matrix_a: IA, mut matrix_b: IB, mut matrix_c: IC) -> Self {
         let current_a = matrix_a.next();
         let current_b = matrix_b.next();
+        let current_c = matrix_c.next();
         Self {
             matrix_a,
             matrix_b,
+            matrix_c,
             current_a,
             current_b,
+            current_c,
         }
     }
 }
 
-impl<'a, SA, SB, SC, F> Iterable for JointValStream<'a, SA, SB, SC, F>
-where
-    SA: Iterable,
-    SB: Iterable,
-    SC: Iterable,
-    F: Field,
-    SA::Item: Borrow<MatrixElement<F>>,
-    SB::Item: Borrow<MatrixElement<F>>,
-    SC::Item: Borrow<MatrixElement<F>>,
-{
-    type Item = F;
-
-    type Iter = TrimValIter<
-        JointIter<
-            JointIter<SparseMatrixIter<SA::Iter, F>, SparseMatrixIter<SB::Iter, F>, F>,
-            SparseMatrixIter<SC::Iter, F>,
-            F,
-        >,
-        F,
-    >;
-
-    fn iter(&self) -> Self::Iter {
-        let matrix_a = self.matrix_a.iter();
-        let matrix_b = self.matrix_b.iter();
-        let matrix_c = self.matrix_c.iter();
-
-        TrimValIter(JointIter::new(JointIter::new(matrix_a, matrix_b), matrix_c))
-    }
-
-    fn len(&self) -> usize {
-        self.joint_len
-    }
-}
-
-pub struct TrimValIter<I, F>(I)
-where
-    I: Iterator<Item = (usize, usize, F)>,
-    F: Field;
-
-impl<I, F> Iterator for TrimValIter<I, F>
-where
-    I: Iterator<Item = (usize, usize, F)>,
-    F: Field,
-{
-    type Item = F;
-
-    #[inline(always)]
-    fn next(&mut self) -> Option<Self::Item> {
-        Some(self.0.next()?.2)
-    }
-}
-
-pub struct TrimRowIter<I, F>(I)
-where
-    I: Iterator<Item = (usize, usize, F)>,
-    F: Field;
-
-impl<I, F> Iterator for TrimRowIter<I, F>
+impl<IA, IB, IC, F> Iterator for JointIter<IA, IB, IC, F>
 where
-    I: Iterator<Item = (usize, usize, F)>,
+    IA: Iterator<Item = (usize, usize, F)>,
+    IB: Iterator<Item = (usize, usize, F)>,
+    IC: Iterator<Item = (usize, usize, F)>,
     F: Field,
 {
-    type Item = usize;
-
-    #[inline(always)]
-    fn next(&mut self) -> Option<Self::Item> {
-        let item = self.0.next()?;
-        Some(item.0)
-    }
-}
-
-pub struct TrimColIter<I, T>(I)
-where
-    I: Iterator<Item = (usize, usize, T)>;
-
-impl<I, T> Iterator for TrimColIter<I, T>
-where
-    I: Iterator<Item = (usize, usize, T)>,
-{
-    type Item = usize;
+    type Item = (usize, usize, [F; 3]);
 
     #[inline(always)]
     fn next(&mut self) -> Option<Self::Item> {
-        let item = self.0.next()?;
-        Some(item.1)
+        // The entries are streamed in decreasing order:
+        // the next position is the largest (line, index) among the three heads.
+        let position = [self.current_a, self.current_b, self.current_c]
+            .iter()
+            .flatten()
+            .map(|&(i, j, _)| (i, j))
+            .max()?;
+
+        let mut vals = [F::zero(); 3];
+        if let Some((i, j, e)) = self.current_a {
+            if (i, j) == position {
+                vals[0] = e;
+                self.current_a = self.matrix_a.next();
+            }
+        }
+        if let Some((i, j, e)) = self.current_b {
+            if (i, j) == position {
+                vals[1] = e;
+                self.current_b = self.matrix_b.next();
+            }
+        }
+        if let Some((i, j, e)) = self.current_c {
+            if (i, j) == position {
+                vals[2] = e;
+                self.current_c = self.matrix_c.next();
+            }
+        }
+        Some((position.0, position.1, vals))
     }
 }
 
+/// The joint stream of three sparse matrices.
+///
+/// The three matrices are traversed once and simultaneously:
+/// for each non-zero position of $A + B + C$, the stream yields
+/// the position together with the values of $A$, $B$, and $C$ in that position.
 #[derive(Clone, Copy)]
-pub struct JointRowStream<'a, SA, SB, SC, F>
+pub struct JointMatrixStream<'a, SA, SB, SC, F>
 where
     SA: Iterable,
     SB: Iterable,
@@ -314,10 +189,9 @@ where
     matrix_b: SparseMatrixStream<'a, F, SB>,
     matrix_c: SparseMatrixStream<'a, F, SC>,
     joint_len: usize,
-    _field: PhantomData<F>,
 }
 
-impl<'a, SA, SB, SC, F> JointRowStream<'a, SA, SB, SC, F>
+impl<'a, SA, SB, SC, F> JointMatrixStream<'a, SA, SB, SC, F>
 where
     SA: Iterable,
     SB: Iterable,
@@ -339,13 +213,18 @@ where
             matrix_b: SparseMatrixStream::new(matrix_b, len),
             matrix_c: SparseMatrixStream::new(matrix_c, len),
             joint_len,
-
-            _field: PhantomData,
         }
     }
 }
 
-impl<'a, SA, SB, SC, F> Iterable for JointRowStream<'a, SA, SB, SC, F>
+type JointMatrixIter<SA, SB, SC, F> = JointIter<
+    SparseMatrixIter<<SA as Iterable>::Iter, F>,
+    SparseMatrixIter<<SB as Iterable>::Iter, F>,
+    SparseMatrixIter<<SC as Iterable>::Iter, F>,
+    F,
+>;
+
+impl<'a, SA, SB, SC, F> Iterable for JointMatrixStream<'a, SA, SB, SC, F>
 where
     SA: Iterable,
     SB: Iterable,
@@ -355,23 +234,16 @@ where
     SB::Item: Borrow<MatrixElement<F>>,
     SC::Item: Borrow<MatrixElement<F>>,
 {
-    type Item = usize;
+    type Item = (usize, usize, [F; 3]);
 
-    type Iter = TrimRowIter<
-        JointIter<
-            JointIter<SparseMatrixIter<SA::Iter, F>, SparseMatrixIter<SB::Iter, F>, F>,
-            SparseMatrixIter<SC::Iter, F>,
-            F,
-        >,
-        F,
-    >;
+    type Iter = JointMatrixIter<SA, SB, SC, F>;
 
     fn iter(&self) -> Self::Iter {
-        let matrix_a = self.matrix_a.iter();
-        let matrix_b = self.matrix_b.iter();
-        let matrix_c = self.matrix_c.iter();
-
-        TrimRowIter(JointIter::new(JointIter::new(matrix_a, matrix_b), matrix_c))
+        JointIter::new(
+            self.matrix_a.iter(),
+            self.matrix_b.iter(),
+            self.matrix_c.iter(),
+        )
     }
 
     fn len(&self) -> usize {
@@ -379,86 +251,98 @@ where
     }
 }
 
-#[derive(Clone, Copy)]
-pub struct JointColStream<'a, SA, SB, SC, F>
-where
-    SA: Iterable,
-    SB: Iterable,
-    SC: Iterable,
-    F: Field,
-    SA::Item: Borrow<MatrixElement<F>>,
-    SB::Item: Borrow<MatrixElement<F>>,
-    SC::Item: Borrow<MatrixElement<F>>,
-{
-    matrix_a: SparseMatrixStream<'a, F, SA>,
-    matrix_b: SparseMatrixStream<'a, F, SB>,
-    matrix_c: SparseMatrixStream<'a, F, SC>,
-    joint_len: usize,
-    _field: PhantomData<F>,
-}
-
-impl<'a, SA, SB, SC, F> JointColStream<'a, SA, SB, SC, F>
-where
-    SA: Iterable,
-    SB: Iterable,
-    SC: Iterable,
-    F: Field,
-    SA::Item: Borrow<MatrixElement<F>>,
-    SB::Item: Borrow<MatrixElement<F>>,
-    SC::Item: Borrow<MatrixElement<F>>,
-{
-    pub fn new(
-        matrix_a: &'a SA,
-        matrix_b: &'a SB,
-        matrix_c: &'a SC,
-        len: usize,
-        joint_len: usize,
-    ) -> Self {
-        Self {
-            matrix_a: SparseMatrixStream::new(matrix_a, len),
-            matrix_b: SparseMatrixStream::new(matrix_b, len),
-            matrix_c: SparseMatrixStream::new(matrix_c, len),
-            joint_len,
-
-            _field: PhantomData,
+/// Map each joint non-zero position to one of its components.
+#[cfg(feature = "prover")]
+type Trim<'a, SA, SB, SC, F, T> =
+    Map<JointMatrixStream<'a, SA, SB, SC, F>, fn((usize, usize, [F; 3])) -> T>;
+
+#[cfg(feature = "prover")]
+macro_rules! impl_joint_stream {
+    ($name:ident, $item:ty, $trim:expr, $doc:literal) => {
+        #[doc = $doc]
+        #[derive(Clone, Copy)]
+        pub struct $name<'a, SA, SB, SC, F>(Trim<'a, SA, SB, SC, F, $item>)
+        where
+            SA: Iterable,
+            SB: Iterable,
+            SC: Iterable,
+            F: Field,
+            SA::Item: Borrow<MatrixElement<F>>,
+            SB::Item: Borrow<MatrixElement<F>>,
+            SC::Item: Borrow<MatrixElement<F>>;
+
+        impl<'a, SA, SB, SC, F> $name<'a, SA, SB, SC, F>
+        where
+            SA: Iterable,
+            SB: Iterable,
+            SC: Iterable,
+            F: Field,
+            SA::Item: Borrow<MatrixElement<F>>,
+            SB::Item: Borrow<MatrixElement<F>>,
+            SC::Item: Borrow<MatrixElement<F>>,
+        {
+            pub fn new(
+                matrix_a: &'a SA,
+                matrix_b: &'a SB,
+                matrix_c: &'a SC,
+                len: usize,
+                joint_len: usize,
+            ) -> Self {
+                let joint_matrix =
+                    JointMatrixStream::new(matrix_a, matrix_b, matrix_c, len, joint_len);
+                let trim: fn((usize, usize, [F; 3])) -> $item = $trim;
+                Self(Map::new(joint_matrix, trim))
+            }
         }
-    }
-}
-
-impl<'a, SA, SB, SC, F> Iterable for JointColStream<'a, SA, SB, SC, F>
-where
-    SA: Iterable,
-    SB: Iterable,
-    SC: Iterable,
-    F: Field,
-    SA::Item: Borrow<MatrixElement<F>>,
-    SB::Item: Borrow<MatrixElement<F>>,
-    SC::Item: Borrow<MatrixElement<F>>,
-{
-    type Item = usize;
-
-    type Iter = TrimColIter<
-        JointIter<
-            JointIter<SparseMatrixIter<SA::Iter, F>, SparseMatrixIter<SB::Iter, F>, F>,
-            SparseMatrixIter<SC::Iter, F>,
-            F,
-        >,
-        F,
-    >;
 
-    fn iter(&self) -> Self::Iter {
-        let matrix_a = self.matrix_a.iter();
-        let matrix_b = self.matrix_b.iter();
-        let matrix_c = self.matrix_c.iter();
-
-        TrimColIter(JointIter::new(JointIter::new(matrix_a, matrix_b), matrix_c))
-    }
+        impl<'a, SA, SB, SC, F> Iterable for $name<'a, SA, SB, SC, F>
+        where
+            SA: Iterable,
+            SB: Iterable,
+            SC: Iterable,
+            F: Field,
+            SA::Item: Borrow<MatrixElement<F>>,
+            SB::Item: Borrow<MatrixElement<F>>,
+            SC::Item: Borrow<MatrixElement<F>>,
+        {
+            type Item = $item;
+
+            type Iter = <Trim<'a, SA, SB, SC, F, $item> as Iterable>::Iter;
+
+            fn iter(&self) -> Self::Iter {
+                self.0.iter()
+            }
 
-    fn len(&self) -> usize {
-        self.joint_len
-    }
+            fn len(&self) -> usize {
+                self.0.len()
+            }
+        }
+    };
 }
 
+#[cfg(feature = "prover")]
+impl_joint_stream!(
+    JointValStream,
+    F,
+    |(_, _, vals)| vals[0],
+    "Stream the values of the first matrix over the joint non-zero positions."
+);
+#[cfg(feature = "prover")]
+impl_joint_stream!(
+    JointRowStream,
+    usize,
+    |(row, _, _)| row,
+    "Stream the line of each joint non-zero position."
+);
+#[cfg(feature = "prover")]
+impl_joint_stream!(
+    JointColStream,
+    usize,
+    |(_, col, _)| col,
+    "Stream the index within the line of each joint non-zero position."
+);
+
+#[cfg(feature = "prover")]
 #[test]
 fn test_joint_val() {
     use crate::iterable::dummy::Mat;
@@ -495,6 +379,7 @@ fn test_joint_val() {
     assert_eq!(joint_a_it.next(), None);
 }
 
+#[cfg(feature = "prover")]
 #[test]
 fn test_matrix() {
     use ark_bls12_381::Fr;
@@ -549,3 +434,50 @@ fn test_matrix() {
         state = y;
     }
 }
+
+#[test]
+fn test_joint_matrix() {
+    use crate::iterable::dummy::Mat;
+    use ark_bls12_381::Fr;
+    use ark_std::vec::Vec;
+    use ark_std::Zero;
+
+    let a = [
+        MatrixElement::Element((Fr::from(1u64), 1)),
+        MatrixElement::EOL,
+        MatrixElement::Element((Fr::from(2u64), 0)),
+        MatrixElement::EOL,
+    ];
+    let b = [
+        MatrixElement::Element((Fr::from(3u64), 1)),
+        MatrixElement::Element((Fr::from(4u64), 0)),
+        MatrixElement::EOL,
+        MatrixElement::EOL,
+    ];
+    let c = [
+        MatrixElement::EOL,
+        MatrixElement::Element((Fr::from(5u64), 0)),
+        MatrixElement::EOL,
+    ];
+    let a_stream = Mat(&a, 2);
+    let b_stream = Mat(&b, 2);
+    let c_stream = Mat(&c, 2);
+    let joint = JointMatrixStream::new(&a_stream, &b_stream, &c_stream, 2, 3);
+    let got = joint.iter().collect::<Vec<_>>();
+    let zero = Fr::zero();
+    let expected = vec![
+        (1, 1, [Fr::from(1u64), Fr::from(3u64), zero]),
+        (1, 0, [zero, Fr::from(4u64), zero]),
+        (0, 0, [Fr::from(2u64), zero, Fr::from(5u64)]),
+    ];
+    assert_eq!(got, expected);
+
+    #[cfg(feature = "prover")]
+    {
+        let val_b = JointValStream::new(&b_stream, &c_stream, &a_stream, 2, 3);
+        assert_eq!(
+            val_b.iter().collect::<Vec<_>>(),
+            got.iter().map(|e| e.2[1]).collect::<Vec<_>>()
+        );
+    }
+}
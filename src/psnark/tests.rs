@@ -9,6 +9,7 @@ use crate::kzg::{CommitterKey, CommitterKeyStream};
 use crate::misc::{joint_matrices, product_matrix_vector, sum_matrices};
 use ark_bls12_381::{Bls12_381, Fr};
 use ark_std::test_rng;
+use core::sync::atomic::{AtomicUsize, Ordering};
 
 #[test]
 fn test_consistency() {
@@ -50,8 +51,8 @@ fn test_consistency() {
     let ck = CommitterKey::<Bls12_381>::new(num_constraints * 100 + num_variables, 3, rng);
     let ck_stream = CommitterKeyStream::from(&ck);
 
-    let time_proof = Proof::new_time(&r1cs, &ck);
-    let elastic_proof = Proof::new_elastic(&r1cs_stream, &ck_stream, max_msm_buffer);
+    let time_proof = Proof::new_time(&r1cs, &ck).unwrap();
+    let elastic_proof = Proof::new_elastic(&r1cs_stream, &ck_stream, max_msm_buffer).unwrap();
 
     assert_eq!(
         elastic_proof.witness_commitment,
@@ -149,9 +150,678 @@ fn test_psnark_correctness() {
 
     let index_comms = ck.batch_commit(&vec![row, col, val_a, val_b, val_c]);
 
-    let time_proof = Proof::new_time(&r1cs, &ck);
+    let time_proof = Proof::new_time(&r1cs, &ck).unwrap();
 
     assert!(time_proof
         .verify(&r1cs, &vk, &index_comms, num_non_zero)
         .is_ok())
 }
+
+#[test]
+fn test_psnark_key_sizing() {
+    use super::R1csStats;
+
+    let rng = &mut test_rng();
+    let num_constraints = 128;
+    let num_variables = 128;
+
+    let circuit: Circuit<Fr> = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let a_rowmaj = matrix_into_rowmaj(&r1cs.a);
+    let b_rowmaj = matrix_into_rowmaj(&r1cs.b);
+    let c_rowmaj = matrix_into_rowmaj(&r1cs.c);
+    let stats = R1csStats::new(
+        &Mat(a_rowmaj.as_slice(), a_rowmaj.len()),
+        &Mat(b_rowmaj.as_slice(), b_rowmaj.len()),
+        &Mat(c_rowmaj.as_slice(), c_rowmaj.len()),
+        r1cs.a.len(),
+        r1cs.z.len(),
+    );
+
+    let joint_matrix = sum_matrices(&r1cs.a, &r1cs.b, &r1cs.c, num_variables);
+    let (row, col, _row_index, _col_index, val_a, val_b, val_c) = joint_matrices(
+        &joint_matrix,
+        num_constraints,
+        num_variables,
+        &r1cs.a,
+        &r1cs.b,
+        &r1cs.c,
+    );
+    assert_eq!(stats.joint_non_zero, row.len());
+
+    let ck = stats.committer_key::<Bls12_381>(rng);
+    let vk = (&ck).into();
+    let index_comms = ck.batch_commit(&vec![row, col, val_a, val_b, val_c]);
+    let time_proof = Proof::new_time(&r1cs, &ck).unwrap();
+    assert!(time_proof
+        .verify(&r1cs, &vk, &index_comms, stats.joint_non_zero)
+        .is_ok())
+}
+
+#[test]
+fn test_memory_report() {
+    let rng = &mut test_rng();
+    let num_constraints = 64;
+    let num_variables = 64;
+    let circuit: Circuit<Fr> = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+
+    let z_a = product_matrix_vector(&r1cs.a, &r1cs.z);
+    let z_b = product_matrix_vector(&r1cs.b, &r1cs.z);
+    let z_c = product_matrix_vector(&r1cs.c, &r1cs.z);
+    let rows = num_constraints;
+    let a_colmaj = matrix_into_colmaj(&r1cs.a, rows);
+    let b_colmaj = matrix_into_colmaj(&r1cs.b, rows);
+    let c_colmaj = matrix_into_colmaj(&r1cs.c, rows);
+    let a_rowmaj = matrix_into_rowmaj(&r1cs.a);
+    let b_rowmaj = matrix_into_rowmaj(&r1cs.b);
+    let c_rowmaj = matrix_into_rowmaj(&r1cs.c);
+    let joint_matrix = sum_matrices(&r1cs.a, &r1cs.b, &r1cs.c, r1cs.z.len());
+    let (row, ..) = joint_matrices(
+        &joint_matrix,
+        num_constraints,
+        num_variables,
+        &r1cs.a,
+        &r1cs.b,
+        &r1cs.c,
+    );
+
+    let r1cs_stream = R1csStream {
+        z: Reverse(r1cs.z.as_slice()),
+        a_colmaj: Mat(a_colmaj.as_slice(), rows),
+        b_colmaj: Mat(b_colmaj.as_slice(), rows),
+        c_colmaj: Mat(c_colmaj.as_slice(), rows),
+        a_rowmaj: Mat(a_rowmaj.as_slice(), rows),
+        b_rowmaj: Mat(b_rowmaj.as_slice(), rows),
+        c_rowmaj: Mat(c_rowmaj.as_slice(), rows),
+        witness: Reverse(r1cs.w.as_slice()),
+        z_a: Reverse(z_a.as_slice()),
+        z_b: Reverse(z_b.as_slice()),
+        z_c: Reverse(z_c.as_slice()),
+        nonzero: num_constraints,
+        joint_len: row.len(),
+    };
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints * 100 + num_variables, 3, rng);
+    let ck_stream = CommitterKeyStream::from(&ck);
+
+    let small_buffer = 1 << 8;
+    let large_buffer = 1 << 12;
+    let (small_proof, small_report) =
+        Proof::new_elastic_with_report(&r1cs_stream, &ck_stream, small_buffer).unwrap();
+    let (large_proof, large_report) =
+        Proof::new_elastic_with_report(&r1cs_stream, &ck_stream, large_buffer).unwrap();
+
+    // the buffer size does not affect the proof, only the memory of the openings.
+    assert!(small_proof == large_proof);
+    assert_eq!(small_report.commitments, large_report.commitments);
+    assert_eq!(small_report.sumchecks, large_report.sumchecks);
+    assert!(small_report.tensorcheck < large_report.tensorcheck);
+    assert!(small_report.peak() <= large_report.peak());
+    assert!(small_report.commitments > 0 && small_report.sumchecks > 0);
+
+    // the passes over the instance are those observed from outside the prover.
+    let passes = AtomicUsize::new(0);
+    let counted = r1cs_stream.counted(&passes);
+    let (_, counted_report) =
+        Proof::new_elastic_with_report(&counted, &ck_stream, small_buffer).unwrap();
+    assert_eq!(counted_report.passes, passes.load(Ordering::Relaxed));
+    assert_eq!(counted_report.passes, small_report.passes);
+    assert_eq!(small_report.passes, large_report.passes);
+    // the passes are logged stream by stream, as many as counted.
+    #[cfg(feature = "std")]
+    {
+        use crate::iterable::PassLog;
+
+        let log = PassLog::new();
+        let instrumented = r1cs_stream.instrumented(&log);
+        Proof::new_elastic_with_report(&instrumented, &ck_stream, small_buffer).unwrap();
+        let pass_report = log.report();
+        assert_eq!(pass_report.total_passes(), small_report.passes);
+        assert!(pass_report.passes("r1cs.witness") >= 1);
+        assert!(pass_report.passes("r1cs.a_rowmaj") >= 1);
+        // the commitment to the witness reads it entirely.
+        let witness_len = r1cs_stream.witness.len();
+        assert!(log
+            .passes()
+            .iter()
+            .any(|pass| pass.label == "r1cs.witness" && pass.elements == witness_len));
+    }
+
+    // a memory cap bounds the buffers of each phase, or fails before proving.
+    use crate::circuit::padded_dim;
+    use crate::config::ProverConfig;
+    use crate::errors::GeminiError;
+    use crate::psnark::MemoryReport;
+    use ark_std::mem::size_of;
+
+    let cap = 1 << 16;
+    let config = ProverConfig::with_memory_cap::<Bls12_381>(cap);
+    let (capped_proof, capped_report) =
+        Proof::new_elastic_with_config(&r1cs_stream, &ck_stream, &config).unwrap();
+    assert!(capped_proof == small_proof);
+    assert!(capped_report.peak() + capped_report.index <= cap);
+    let dim = padded_dim(num_constraints, r1cs.z.len());
+    let bound = MemoryReport::bound::<Bls12_381>(dim, row.len(), &config);
+    assert!(capped_report.commitments <= bound.commitments);
+    assert!(capped_report.sumchecks <= bound.sumchecks);
+    assert!(capped_report.entry_products <= bound.entry_products);
+    assert!(capped_report.tensorcheck <= bound.tensorcheck);
+
+    let config = ProverConfig {
+        memory_cap: Some(cap),
+        ..ProverConfig::default()
+    };
+    assert!(matches!(
+        Proof::new_elastic_with_config(&r1cs_stream, &ck_stream, &config),
+        Err(GeminiError::MemoryCapExceeded { cap: 65536, .. })
+    ));
+
+    // the index streams are held in memory only within the budget,
+    // trading passes over the instance for memory without changing the proof.
+    let config = ProverConfig {
+        memory_budget: 1,
+        ..ProverConfig::default()
+    };
+    let (streamed_proof, streamed_report) =
+        Proof::new_elastic_with_config(&r1cs_stream, &ck_stream, &config).unwrap();
+    assert!(streamed_proof == small_proof);
+    assert_eq!(streamed_report.index, 0);
+    assert!(small_report.index > 0);
+    assert!(small_report.passes < streamed_report.passes);
+
+    // a budget of two field elements per non-zero entry holds the rows, the columns,
+    // and the sorted rows (vectors of `usize`), and only one of the val streams.
+    let config = ProverConfig {
+        memory_budget: 2 * row.len(),
+        ..ProverConfig::default()
+    };
+    let (partial_proof, partial_report) =
+        Proof::new_elastic_with_config(&r1cs_stream, &ck_stream, &config).unwrap();
+    assert!(partial_proof == small_proof);
+    assert_eq!(
+        partial_report.index,
+        3 * row.len() * size_of::<usize>() + row.len() * size_of::<Fr>()
+    );
+    assert!(partial_report.passes < streamed_report.passes);
+}
+
+#[test]
+fn test_psnark_batch_verification() {
+    use super::R1csStats;
+
+    let rng = &mut test_rng();
+    let num_constraints = 64;
+    let num_variables = 64;
+
+    // circuits of the same size share the same matrices.
+    let circuit_1: Circuit<Fr> = random_circuit(rng, num_constraints, num_variables);
+    let circuit_2: Circuit<Fr> = random_circuit(rng, num_constraints, num_variables);
+    let r1cs_1 = generate_relation(circuit_1);
+    let r1cs_2 = generate_relation(circuit_2);
+    assert_eq!(r1cs_1.a, r1cs_2.a);
+
+    let a_rowmaj = matrix_into_rowmaj(&r1cs_1.a);
+    let b_rowmaj = matrix_into_rowmaj(&r1cs_1.b);
+    let c_rowmaj = matrix_into_rowmaj(&r1cs_1.c);
+    let stats = R1csStats::new(
+        &Mat(a_rowmaj.as_slice(), a_rowmaj.len()),
+        &Mat(b_rowmaj.as_slice(), b_rowmaj.len()),
+        &Mat(c_rowmaj.as_slice(), c_rowmaj.len()),
+        r1cs_1.a.len(),
+        r1cs_1.z.len(),
+    );
+    let joint_matrix = sum_matrices(&r1cs_1.a, &r1cs_1.b, &r1cs_1.c, num_variables);
+    let (row, col, _row_index, _col_index, val_a, val_b, val_c) = joint_matrices(
+        &joint_matrix,
+        num_constraints,
+        num_variables,
+        &r1cs_1.a,
+        &r1cs_1.b,
+        &r1cs_1.c,
+    );
+    let ck = stats.committer_key::<Bls12_381>(rng);
+    let vk = (&ck).into();
+    let index_comms = ck.batch_commit(&vec![row, col, val_a, val_b, val_c]);
+    let num_non_zero = stats.joint_non_zero;
+
+    let proofs = [
+        Proof::new_time(&r1cs_1, &ck).unwrap(),
+        Proof::new_time(&r1cs_2, &ck).unwrap(),
+    ];
+    let public_inputs = [r1cs_1.x.clone(), r1cs_2.x.clone()];
+    assert!(Proof::verify_batch(
+        &r1cs_1,
+        &vk,
+        &index_comms,
+        num_non_zero,
+        &proofs,
+        &public_inputs
+    )
+    .is_ok());
+
+    let swapped_inputs = [r1cs_2.x.clone(), r1cs_1.x.clone()];
+    assert!(Proof::verify_batch(
+        &r1cs_1,
+        &vk,
+        &index_comms,
+        num_non_zero,
+        &proofs,
+        &swapped_inputs
+    )
+    .is_err());
+}
+
+#[test]
+fn test_psnark_serialization() {
+    use super::R1csStats;
+    use ark_std::vec::Vec;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit: Circuit<Fr> = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let a_rowmaj = matrix_into_rowmaj(&r1cs.a);
+    let b_rowmaj = matrix_into_rowmaj(&r1cs.b);
+    let c_rowmaj = matrix_into_rowmaj(&r1cs.c);
+    let stats = R1csStats::new(
+        &Mat(a_rowmaj.as_slice(), a_rowmaj.len()),
+        &Mat(b_rowmaj.as_slice(), b_rowmaj.len()),
+        &Mat(c_rowmaj.as_slice(), c_rowmaj.len()),
+        r1cs.a.len(),
+        r1cs.z.len(),
+    );
+    let ck = stats.committer_key::<Bls12_381>(rng);
+    let proof = Proof::new_time(&r1cs, &ck).unwrap();
+
+    let mut bytes = Vec::new();
+    proof.serialize_versioned(&mut bytes).unwrap();
+    assert_eq!(bytes.len(), proof.serialized_size());
+    assert_eq!(Proof::deserialize_versioned(&bytes[..]).unwrap(), proof);
+
+    // truncated proofs are rejected.
+    assert!(Proof::<Bls12_381>::deserialize_versioned(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn test_psnark_untrusted_deserialization() {
+    use super::R1csStats;
+    use crate::kzg::Commitment;
+    use ark_bls12_381::{Fq, G1Affine};
+    use ark_ec::AffineRepr;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Compress, Validate};
+    use ark_std::vec::Vec;
+    use ark_std::UniformRand;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit: Circuit<Fr> = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let a_rowmaj = matrix_into_rowmaj(&r1cs.a);
+    let b_rowmaj = matrix_into_rowmaj(&r1cs.b);
+    let c_rowmaj = matrix_into_rowmaj(&r1cs.c);
+    let stats = R1csStats::new(
+        &Mat(a_rowmaj.as_slice(), a_rowmaj.len()),
+        &Mat(b_rowmaj.as_slice(), b_rowmaj.len()),
+        &Mat(c_rowmaj.as_slice(), c_rowmaj.len()),
+        r1cs.a.len(),
+        r1cs.z.len(),
+    );
+    let ck = stats.committer_key::<Bls12_381>(rng);
+    let mut proof = Proof::new_time(&r1cs, &ck).unwrap();
+
+    // a point on the curve, but outside of the prime-order subgroup.
+    let point = loop {
+        let x = Fq::rand(rng);
+        if let Some(point) = G1Affine::get_point_from_x_unchecked(x, false) {
+            if !point.is_in_correct_subgroup_assuming_on_curve() {
+                break point;
+            }
+        }
+    };
+    proof.witness_commitment = Commitment(point.into_group());
+    for compress in [Compress::Yes, Compress::No] {
+        let mut bytes = Vec::new();
+        proof.serialize_with_mode(&mut bytes, compress).unwrap();
+        assert!(
+            Proof::<Bls12_381>::deserialize_with_mode(&bytes[..], compress, Validate::Yes).is_err()
+        );
+        assert!(
+            Proof::<Bls12_381>::deserialize_with_mode(&bytes[..], compress, Validate::No).is_ok()
+        );
+    }
+
+    // a point outside of the curve.
+    let mut off_curve = G1Affine::generator();
+    off_curve.y += Fq::from(1u64);
+    let mut bytes = Vec::new();
+    off_curve.serialize_uncompressed(&mut bytes).unwrap();
+    assert!(Commitment::<Bls12_381>::deserialize_uncompressed(&bytes[..]).is_err());
+}
+
+#[test]
+fn test_psnark_deferred_verification() {
+    use super::R1csStats;
+    use crate::transcript::PoseidonTranscript;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit: Circuit<Fr> = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let a_rowmaj = matrix_into_rowmaj(&r1cs.a);
+    let b_rowmaj = matrix_into_rowmaj(&r1cs.b);
+    let c_rowmaj = matrix_into_rowmaj(&r1cs.c);
+    let stats = R1csStats::new(
+        &Mat(a_rowmaj.as_slice(), a_rowmaj.len()),
+        &Mat(b_rowmaj.as_slice(), b_rowmaj.len()),
+        &Mat(c_rowmaj.as_slice(), c_rowmaj.len()),
+        r1cs.a.len(),
+        r1cs.z.len(),
+    );
+    let joint_matrix = sum_matrices(&r1cs.a, &r1cs.b, &r1cs.c, num_variables);
+    let (row, col, _row_index, _col_index, val_a, val_b, val_c) = joint_matrices(
+        &joint_matrix,
+        num_constraints,
+        num_variables,
+        &r1cs.a,
+        &r1cs.b,
+        &r1cs.c,
+    );
+    let ck = stats.committer_key::<Bls12_381>(rng);
+    let vk = (&ck).into();
+    let index_comms = ck.batch_commit(&vec![row, col, val_a, val_b, val_c]);
+
+    let transcript = PoseidonTranscript::<Fr>::new(b"test");
+    let proof = Proof::new_time_with_transcript(&r1cs, &ck, &mut transcript.clone()).unwrap();
+    let checks = proof
+        .verify_deferred(
+            &r1cs,
+            &vk,
+            &index_comms,
+            stats.joint_non_zero,
+            &mut transcript.clone(),
+        )
+        .unwrap();
+    assert!(checks.iter().all(|check| vk.check(check).is_ok()));
+}
+
+#[test]
+fn test_psnark_generator_witness() {
+    use crate::iterable::Generator;
+    use ark_std::vec::Vec;
+
+    let rng = &mut test_rng();
+    let num_constraints = 64;
+    let num_variables = 64;
+    let circuit: Circuit<Fr> = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+
+    let a_colmaj = matrix_into_colmaj(&r1cs.a, num_constraints);
+    let b_colmaj = matrix_into_colmaj(&r1cs.b, num_constraints);
+    let c_colmaj = matrix_into_colmaj(&r1cs.c, num_constraints);
+    let a_rowmaj = matrix_into_rowmaj(&r1cs.a);
+    let b_rowmaj = matrix_into_rowmaj(&r1cs.b);
+    let c_rowmaj = matrix_into_rowmaj(&r1cs.c);
+
+    // the assignment and its products are recomputed at each pass.
+    let (a, b, c) = (&r1cs.a, &r1cs.b, &r1cs.c);
+    let row_product =
+        |row: &Vec<(Fr, usize)>| row.iter().map(|&(v, col)| v * r1cs.z[col]).sum::<Fr>();
+    let r1cs_stream = R1csStream {
+        z: Generator::new(r1cs.z.len(), || r1cs.z.iter().rev().copied()),
+        a_colmaj: Mat(a_colmaj.as_slice(), num_constraints),
+        b_colmaj: Mat(b_colmaj.as_slice(), num_constraints),
+        c_colmaj: Mat(c_colmaj.as_slice(), num_constraints),
+        a_rowmaj: Mat(a_rowmaj.as_slice(), num_constraints),
+        b_rowmaj: Mat(b_rowmaj.as_slice(), num_constraints),
+        c_rowmaj: Mat(c_rowmaj.as_slice(), num_constraints),
+        witness: Generator::new(r1cs.w.len(), || r1cs.w.iter().rev().copied()),
+        z_a: Generator::new(num_constraints, move || a.iter().rev().map(row_product)),
+        z_b: Generator::new(num_constraints, move || b.iter().rev().map(row_product)),
+        z_c: Generator::new(num_constraints, move || c.iter().rev().map(row_product)),
+        nonzero: num_constraints,
+        joint_len: sum_matrices(&r1cs.a, &r1cs.b, &r1cs.c, num_variables)
+            .iter()
+            .map(|row| row.len())
+            .sum(),
+    };
+
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints * 100 + num_variables, 3, rng);
+    let ck_stream = CommitterKeyStream::from(&ck);
+
+    let time_proof = Proof::new_time(&r1cs, &ck).unwrap();
+    let elastic_proof = Proof::new_elastic(&r1cs_stream, &ck_stream, 1 << 20).unwrap();
+    assert!(elastic_proof == time_proof);
+}
+
+#[test]
+fn test_psnark_estimate_size() {
+    use super::R1csStats;
+    use ark_serialize::Compress;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit: Circuit<Fr> = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let a_rowmaj = matrix_into_rowmaj(&r1cs.a);
+    let b_rowmaj = matrix_into_rowmaj(&r1cs.b);
+    let c_rowmaj = matrix_into_rowmaj(&r1cs.c);
+    let stats = R1csStats::new(
+        &Mat(a_rowmaj.as_slice(), a_rowmaj.len()),
+        &Mat(b_rowmaj.as_slice(), b_rowmaj.len()),
+        &Mat(c_rowmaj.as_slice(), c_rowmaj.len()),
+        r1cs.a.len(),
+        r1cs.z.len(),
+    );
+    let ck = stats.committer_key::<Bls12_381>(rng);
+    let proof = Proof::new_time(&r1cs, &ck).unwrap();
+
+    let estimate = Proof::<Bls12_381>::estimate_size(
+        stats.num_constraints,
+        stats.num_variables,
+        stats.joint_non_zero,
+        Compress::Yes,
+    );
+    assert_eq!(estimate, proof.serialized_size());
+    assert_eq!(proof.size_breakdown().total(), proof.serialized_size());
+}
+
+#[test]
+fn test_psnark_streaming_verifier() {
+    use ark_std::One;
+
+    let rng = &mut test_rng();
+    let num_constraints = 64;
+    let num_variables = 64;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+
+    let joint_matrix = sum_matrices(&r1cs.a, &r1cs.b, &r1cs.c, num_variables);
+    let (row, col, _row_index, _col_index, val_a, val_b, val_c) = joint_matrices(
+        &joint_matrix,
+        num_constraints,
+        num_variables,
+        &r1cs.a,
+        &r1cs.b,
+        &r1cs.c,
+    );
+    let num_non_zero = row.len();
+
+    let ck = CommitterKey::<Bls12_381>::new(num_non_zero + num_variables + num_constraints, 5, rng);
+    let vk = (&ck).into();
+    let index_comms = ck.batch_commit(&vec![row, col, val_a, val_b, val_c]);
+    let proof = Proof::new_time(&r1cs, &ck).unwrap();
+
+    let x = Reverse(r1cs.x.as_slice());
+    assert!(proof
+        .verify_streaming(
+            r1cs.a.len(),
+            r1cs.z.len(),
+            &x,
+            &vk,
+            &index_comms,
+            num_non_zero
+        )
+        .is_ok());
+
+    let mut tampered = r1cs.x.clone();
+    tampered[0] += Fr::one();
+    assert!(proof
+        .verify_streaming(
+            r1cs.a.len(),
+            r1cs.z.len(),
+            &Reverse(tampered.as_slice()),
+            &vk,
+            &index_comms,
+            num_non_zero
+        )
+        .is_err());
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_psnark_index_cache() {
+    use super::{IndexCache, R1csStats};
+
+    let rng = &mut test_rng();
+    let num_constraints = 64;
+    let num_variables = 64;
+
+    let circuit: Circuit<Fr> = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let a_rowmaj = matrix_into_rowmaj(&r1cs.a);
+    let b_rowmaj = matrix_into_rowmaj(&r1cs.b);
+    let c_rowmaj = matrix_into_rowmaj(&r1cs.c);
+    let stats = R1csStats::new(
+        &Mat(a_rowmaj.as_slice(), a_rowmaj.len()),
+        &Mat(b_rowmaj.as_slice(), b_rowmaj.len()),
+        &Mat(c_rowmaj.as_slice(), c_rowmaj.len()),
+        r1cs.a.len(),
+        r1cs.z.len(),
+    );
+    let ck = stats.committer_key::<Bls12_381>(rng);
+    let vk = (&ck).into();
+
+    let cache = IndexCache::new(&r1cs, &ck);
+    assert_eq!(cache.num_non_zero(), stats.joint_non_zero);
+
+    let path = std::env::temp_dir().join("gemini-test-index-cache.bin");
+    cache.save(&path).unwrap();
+    let cache = IndexCache::<Bls12_381>::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let proof = Proof::new_time_cached(&r1cs, &ck, &cache).unwrap();
+    assert_eq!(proof, Proof::new_time(&r1cs, &ck).unwrap());
+    assert!(proof
+        .verify(
+            &r1cs,
+            &vk,
+            &cache.commitments().to_vec(),
+            cache.num_non_zero()
+        )
+        .is_ok());
+}
+
+#[test]
+fn test_psnark_key_files() {
+    use super::{
+        read_prover_key, read_verifier_key, write_prover_key, write_prover_key_stream,
+        write_verifier_key, IndexCache,
+    };
+    use crate::kzg::VerifierKey;
+    use ark_std::vec::Vec;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit: Circuit<Fr> = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints * 100 + num_variables, 3, rng);
+    let vk = VerifierKey::from(&ck);
+    let cache = IndexCache::new(&r1cs, &ck);
+
+    let mut prover_key = Vec::new();
+    write_prover_key(&ck, &cache, &mut prover_key).unwrap();
+    // the streaming writer produces the same file.
+    let mut streamed_prover_key = Vec::new();
+    write_prover_key_stream(
+        &CommitterKeyStream::from(&ck),
+        &cache,
+        &mut streamed_prover_key,
+    )
+    .unwrap();
+    assert_eq!(prover_key, streamed_prover_key);
+    let (ck, cache) = read_prover_key::<Bls12_381, _>(&prover_key[..]).unwrap();
+
+    let mut verifier_key = Vec::new();
+    write_verifier_key(
+        &vk,
+        cache.commitments(),
+        cache.num_non_zero(),
+        &mut verifier_key,
+    )
+    .unwrap();
+    let (vk, index_comms, num_non_zero) =
+        read_verifier_key::<Bls12_381, _>(&verifier_key[..]).unwrap();
+
+    let proof = Proof::new_time_cached(&r1cs, &ck, &cache).unwrap();
+    assert!(proof.verify(&r1cs, &vk, &index_comms, num_non_zero).is_ok());
+
+    // keys of the wrong kind, truncated, or corrupted are rejected.
+    assert!(read_verifier_key::<Bls12_381, _>(&prover_key[..]).is_err());
+    assert!(read_prover_key::<Bls12_381, _>(&prover_key[..prover_key.len() - 1]).is_err());
+    let last = verifier_key.len() - 40;
+    verifier_key[last] ^= 1;
+    assert!(read_verifier_key::<Bls12_381, _>(&verifier_key[..]).is_err());
+}
+
+#[test]
+fn test_psnark_index_keys() {
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::vec::Vec;
+
+    use super::{generate_index_keys, IndexVerifierKey};
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit: Circuit<Fr> = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints * 100 + num_variables, 3, rng);
+    let (ipk, ivk) = generate_index_keys(&r1cs, ck);
+
+    let proof = Proof::new_time_indexed(&r1cs, &ipk).unwrap();
+    assert!(proof.verify_indexed(&r1cs.x, &ivk).is_ok());
+    assert!(proof.verify_indexed(&r1cs.x[1..], &ivk).is_err());
+    assert_eq!(ipk.digest, ivk.digest());
+
+    // the proof is bound to the index, not only to its dimensions.
+    let unbound_proof = Proof::new_time_cached(&r1cs, &ipk.ck, &ipk.cache).unwrap();
+    assert!(unbound_proof.verify_indexed(&r1cs.x, &ivk).is_err());
+    let mut other_ivk = ivk.clone();
+    other_ivk.index_comms.swap(0, 1);
+    assert_ne!(ivk.digest(), other_ivk.digest());
+    assert!(proof.verify_indexed(&r1cs.x, &other_ivk).is_err());
+
+    // the verifier key does not grow with the instance.
+    let large_circuit: Circuit<Fr> = random_circuit(rng, 4 * num_constraints, 4 * num_variables);
+    let large_r1cs = generate_relation(large_circuit);
+    let (large_ipk, large_ivk) = generate_index_keys(&large_r1cs, ipk.ck.clone());
+    assert_eq!(ivk.compressed_size(), large_ivk.compressed_size());
+    assert!(large_ipk.compressed_size() > ipk.compressed_size());
+
+    let mut bytes = Vec::new();
+    ivk.serialize_compressed(&mut bytes).unwrap();
+    let ivk = IndexVerifierKey::<Bls12_381>::deserialize_compressed(&bytes[..]).unwrap();
+    assert!(proof.verify_indexed(&r1cs.x, &ivk).is_ok());
+}
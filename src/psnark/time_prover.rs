@@ -7,26 +7,26 @@ use ark_std::vec::Vec;
 use ark_std::One;
 
 use crate::circuit::R1cs;
+use crate::config::ProverConfig;
+use crate::errors::{GeminiError, GeminiResult};
+use crate::iterable::Reverse;
 use crate::kzg::CommitterKey;
 use crate::misc::{
-    evaluate_le, hadamard, ip, joint_matrices, linear_combination, powers, powers2,
-    product_matrix_vector, sum_matrices, tensor,
+    evaluate_le, hadamard, ip, linear_combination, powers, powers2, product_matrix_vector, tensor,
 };
 
 use crate::subprotocols::entryproduct::time_prover::{accumulated_product, monic, right_rotation};
 use crate::subprotocols::entryproduct::EntryProduct;
-use crate::subprotocols::plookup::time_prover::{
-    alg_hash, compute_frequency, extend_frequency, lookup, plookup, sorted,
-};
+use crate::subprotocols::plookup::time_prover::{alg_hash, lookup, plookup, sorted};
 use crate::subprotocols::sumcheck::{
     proof::Sumcheck, time_prover::TimeProver, time_prover::Witness,
 };
 use crate::subprotocols::tensorcheck::TensorcheckProof;
-use crate::transcript::GeminiTranscript;
+use crate::transcript::{bind_context, bind_public_input, GeminiTranscript};
 
 use crate::PROTOCOL_NAME;
 
-use super::Proof;
+use super::{IndexCache, Proof, PSNARK_LABEL};
 
 fn product3<F: Field>(v: &[Vec<F>; 3]) -> Vec<F> {
     vec![
@@ -49,12 +49,83 @@ impl<E: Pairing> Proof<E> {
     /// Given as input the R1CS instance `r1cs`
     /// and the committer key `ck`,
     /// return a new _preprocessing_ SNARK using the elastic prover.
-    pub fn new_time(r1cs: &R1cs<E::ScalarField>, ck: &CommitterKey<E>) -> Proof<E> {
+    ///
+    /// The instance is padded internally (see [`R1cs::padded`]).
+    ///
+    /// Fails if the instance is malformed (see [`R1cs::check`]).
+    pub fn new_time(r1cs: &R1cs<E::ScalarField>, ck: &CommitterKey<E>) -> GeminiResult<Proof<E>> {
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        Self::new_time_with_transcript(r1cs, ck, &mut transcript)
+    }
+
+    /// Same as [`Proof::new_time`],
+    /// running on the threads of `config`.
+    pub fn new_time_with_config(
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+        config: &ProverConfig,
+    ) -> GeminiResult<Proof<E>> {
+        config.install(|| Self::new_time(r1cs, ck))
+    }
+
+    /// Produce a new _preprocessing_ SNARK using the time-efficient prover,
+    /// deriving the challenges from `transcript` (e.g., a [`PoseidonTranscript`]).
+    ///
+    /// The proof must be verified with [`Proof::verify_with_transcript`],
+    /// starting from the same transcript.
+    ///
+    /// [`PoseidonTranscript`]: crate::transcript::PoseidonTranscript
+    pub fn new_time_with_transcript(
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+        transcript: &mut impl GeminiTranscript,
+    ) -> GeminiResult<Proof<E>> {
+        r1cs.check()?;
+        let cache = IndexCache::new(r1cs, ck);
+        Self::new_time_cached_with_transcript(r1cs, ck, &cache, transcript)
+    }
+
+    /// Same as [`Proof::new_time`],
+    /// reusing the preprocessed index `cache` of `r1cs`.
+    pub fn new_time_cached(
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+        cache: &IndexCache<E>,
+    ) -> GeminiResult<Proof<E>> {
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        Self::new_time_cached_with_transcript(r1cs, ck, cache, &mut transcript)
+    }
+
+    /// Same as [`Proof::new_time_with_transcript`],
+    /// reusing the preprocessed index `cache` of `r1cs`.
+    ///
+    /// Fails if the instance is malformed,
+    /// or if `cache` was built for an instance of different dimensions.
+    pub fn new_time_cached_with_transcript(
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+        cache: &IndexCache<E>,
+        transcript: &mut impl GeminiTranscript,
+    ) -> GeminiResult<Proof<E>> {
+        r1cs.check()?;
+        // pad the instance to square matrices of power-of-two dimension.
+        let padded = r1cs.padded();
+        let r1cs: &R1cs<E::ScalarField> = &padded;
+        GeminiError::ensure_len("cache.num_constraints", r1cs.a.len(), cache.num_constraints)?;
+        GeminiError::ensure_len("cache.num_variables", r1cs.z.len(), cache.num_variables)?;
+        trace_span!(
+            "psnark_time_prover",
+            num_variables = r1cs.z.len(),
+            num_non_zero = cache.num_non_zero(),
+        );
         let z_a = product_matrix_vector(&r1cs.a, &r1cs.z);
         let z_b = product_matrix_vector(&r1cs.b, &r1cs.z);
         let z_c = product_matrix_vector(&r1cs.c, &r1cs.z);
 
-        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        // bind the context and the public input to the transcript.
+        let index = [r1cs.z.len(), cache.num_non_zero()];
+        bind_context::<E, _>(transcript, PSNARK_LABEL, &index);
+        bind_public_input::<E::ScalarField, _, _>(transcript, &Reverse(r1cs.x.as_slice()));
         let witness_commitment_time = start_timer!(|| "Commitment to w");
         let witness_commitment = ck.commit(&r1cs.w);
         end_timer!(witness_commitment_time);
@@ -66,35 +137,25 @@ impl<E: Pairing> Proof<E> {
         transcript.append_serializable(b"zc(alpha)", &zc_alpha);
 
         let first_sumcheck_time = start_timer!(|| "First sumcheck");
-        let first_proof = Sumcheck::new_time(&mut transcript, &z_a, &z_b, &alpha);
+        let first_proof = Sumcheck::new_time(transcript, &z_a, &z_b, &alpha)?;
         end_timer!(first_sumcheck_time);
 
         let b_challenges = tensor(&first_proof.challenges);
         let c_challenges = powers(alpha, b_challenges.len());
         let a_challenges = hadamard(&b_challenges, &c_challenges);
 
-        let num_constraints = r1cs.a.len();
-        let num_variables = r1cs.z.len();
-
-        let joint_matrix = sum_matrices(&r1cs.a, &r1cs.b, &r1cs.c, num_variables);
-        let (row, col, row_index, col_index, val_a, val_b, val_c) = joint_matrices(
-            &joint_matrix,
-            num_constraints,
-            num_variables,
-            &r1cs.a,
-            &r1cs.b,
-            &r1cs.c,
-        );
+        let (row, col, row_index, col_index) =
+            (&cache.row, &cache.col, &cache.row_index, &cache.col_index);
+        let (val_a, val_b, val_c) = (&cache.val_a, &cache.val_b, &cache.val_c);
 
-        let num_non_zero = row.len();
+        let num_non_zero = cache.num_non_zero();
 
-        let ralpha_star = lookup(&a_challenges, &row_index);
-        let r_star = lookup(&b_challenges, &row_index);
-        let alpha_star = lookup(&c_challenges, &row_index);
-        let z_star = lookup(&r1cs.z, &col_index);
+        let ralpha_star = lookup(&a_challenges, row_index);
+        let r_star = lookup(&b_challenges, row_index);
+        let alpha_star = lookup(&c_challenges, row_index);
+        let z_star = lookup(&r1cs.z, col_index);
 
-        let ck_row = ck.index_by(&row_index[..]);
-        let ck_col = ck.index_by(&col_index[..]);
+        let (ck_row, ck_col) = (&cache.ck_row, &cache.ck_col);
 
         let z_r_commitments_time = start_timer!(|| "Commitments to z* and r*");
         let mut z_r_commitments =
@@ -113,9 +174,9 @@ impl<E: Pairing> Proof<E> {
 
         let r_star_val = linear_combination(
             &[
-                hadamard(&ralpha_star, &val_a),
-                hadamard(&r_star, &val_b),
-                hadamard(&alpha_star, &val_c),
+                hadamard(&ralpha_star, val_a),
+                hadamard(&r_star, val_b),
+                hadamard(&alpha_star, val_c),
             ],
             &challenges,
         )
@@ -123,11 +184,11 @@ impl<E: Pairing> Proof<E> {
 
         let second_sumcheck_time = start_timer!(|| "Second sumcheck");
         let second_proof = Sumcheck::new_time(
-            &mut transcript,
+            transcript,
             &z_star,
             &r_star_val,
             &E::ScalarField::one(),
-        );
+        )?;
         let second_challenges = tensor(&second_proof.challenges);
         let second_challenges_head = &second_challenges[..num_non_zero];
         end_timer!(second_sumcheck_time);
@@ -140,21 +201,14 @@ impl<E: Pairing> Proof<E> {
             alg_hash(&c_challenges, 0..c_challenges.len(), &zeta),
             alg_hash(&r1cs.z, 0..r1cs.z.len(), &zeta),
         ];
-        let frequency = [
-            compute_frequency(alg_hash_poly[0].len(), &row_index),
-            compute_frequency(alg_hash_poly[2].len(), &col_index),
-        ];
+        let frequency = [&cache.row_frequency, &cache.col_frequency];
         let sorted_polynomials = [
             &sorted(&alg_hash_poly[0], &frequency[0]),
             &sorted(&alg_hash_poly[1], &frequency[0]),
             &sorted(&alg_hash_poly[2], &frequency[1]),
         ];
 
-        let ext_fre = [
-            extend_frequency(&frequency[0]),
-            extend_frequency(&frequency[1]),
-        ];
-        let ck_fre = [ck.index_by(&ext_fre[0]), ck.index_by(&ext_fre[1])];
+        let ck_fre = [&cache.ck_row_frequency, &cache.ck_col_frequency];
         let mut sorted_commitments =
             ck_fre[0].batch_commit(vec![&alg_hash_poly[0], &alg_hash_poly[1]]);
         sorted_commitments.push(ck_fre[1].commit(&alg_hash_poly[2]));
@@ -169,15 +223,15 @@ impl<E: Pairing> Proof<E> {
         let chi = transcript.get_challenge(b"chi");
 
         // TODO: Make sorted vectors as input to the plookup function.
-        let r_lookup_vec = plookup(&r_star, &b_challenges, &row_index, &gamma, &chi, &zeta);
+        let r_lookup_vec = plookup(&r_star, &b_challenges, row_index, &gamma, &chi, &zeta);
         let r_prod_vec = product3(&r_lookup_vec);
         let r_accumulated_vec = accproduct3(&r_lookup_vec);
 
-        let alpha_lookup_vec = plookup(&alpha_star, &c_challenges, &row_index, &gamma, &chi, &zeta);
+        let alpha_lookup_vec = plookup(&alpha_star, &c_challenges, row_index, &gamma, &chi, &zeta);
         let alpha_prod_vec = product3(&alpha_lookup_vec);
         let alpha_accumulated_vec = accproduct3(&alpha_lookup_vec);
 
-        let z_lookup_vec = plookup(&z_star, &r1cs.z, &col_index, &gamma, &chi, &zeta);
+        let z_lookup_vec = plookup(&z_star, &r1cs.z, col_index, &gamma, &chi, &zeta);
         let z_prod_vec = product3(&z_lookup_vec);
         let z_accumulated_vec = accproduct3(&z_lookup_vec);
 
@@ -199,7 +253,7 @@ impl<E: Pairing> Proof<E> {
         transcript.append_serializable(b"subset_z_ep", &z_prod_vec[1]);
 
         let entry_products = EntryProduct::new_time_batch(
-            &mut transcript,
+            transcript,
             ck,
             &lookup_vec,
             &[
@@ -227,8 +281,8 @@ impl<E: Pairing> Proof<E> {
             ralpha_star_acc_mu_evals.push(evaluate_le(v, &psi));
         });
 
-        let s_0_prime = ip(&hadamard(&ralpha_star, &val_a), second_challenges_head);
-        let s_1_prime = ip(&hadamard(&r_star, &val_b), second_challenges_head);
+        let s_0_prime = ip(&hadamard(&ralpha_star, val_a), second_challenges_head);
+        let s_1_prime = ip(&hadamard(&r_star, val_b), second_challenges_head);
         // let s_2_prime = ip(&hadamard(&alpha_star, &val_c), &second_challenges_head);
         // transcript.append_serializable(b"r_val_chal_a", &s_0_prime);
         // transcript.append_serializable(b"r_val_chal_b", &s_1_prime);
@@ -242,17 +296,17 @@ impl<E: Pairing> Proof<E> {
 
         provers.push(Box::new(TimeProver::new(Witness::new(
             &hadamard(&ralpha_star, second_challenges_head),
-            &val_a,
+            val_a,
             &E::ScalarField::one(),
         ))));
         provers.push(Box::new(TimeProver::new(Witness::new(
             &hadamard(&r_star, second_challenges_head),
-            &val_b,
+            val_b,
             &E::ScalarField::one(),
         ))));
         provers.push(Box::new(TimeProver::new(Witness::new(
             &hadamard(&alpha_star, second_challenges_head),
-            &val_c,
+            val_c,
             &E::ScalarField::one(),
         ))));
         provers.push(Box::new(TimeProver::new(Witness::new(
@@ -262,7 +316,7 @@ impl<E: Pairing> Proof<E> {
         ))));
 
         let third_sumcheck_time = start_timer!(|| "Third sumcheck");
-        let third_proof = Sumcheck::prove_batch(&mut transcript, provers);
+        let third_proof = Sumcheck::prove_batch(transcript, provers)?;
         end_timer!(third_sumcheck_time);
 
         let tc_base_polynomials = [
@@ -271,11 +325,11 @@ impl<E: Pairing> Proof<E> {
             &r_star,
             &alpha_star,
             &z_star,
-            &row,
-            &col,
-            &val_a,
-            &val_b,
-            &val_c,
+            row,
+            col,
+            val_a,
+            val_b,
+            val_c,
             sorted_polynomials[0],
             sorted_polynomials[1],
             sorted_polynomials[2],
@@ -299,7 +353,7 @@ impl<E: Pairing> Proof<E> {
         let mut third_proof_vec = Vec::new();
 
         third_proof_vec.extend(&shift_monic_lookup_vec);
-        third_proof_vec.extend(&[&val_a, &val_b, &val_c, &alpha_star]);
+        third_proof_vec.extend(&[val_a, val_b, val_c, &alpha_star]);
 
         // third_proof.challenges might be longer than second_proof.challenges because of
         // the batched sumcheck involves entry products polynomials.
@@ -331,13 +385,13 @@ impl<E: Pairing> Proof<E> {
 
         let tensorcheck_time = start_timer!(|| "Tensorcheck");
         let tensorcheck_proof = TensorcheckProof::new_time(
-            &mut transcript,
+            transcript,
             ck,
             tc_base_polynomials,
             tc_body_polynomials,
         );
         end_timer!(tensorcheck_time);
-        Proof {
+        Ok(Proof {
             witness_commitment,
             zc_alpha,
             first_sumcheck_msgs: first_proof.prover_messages(),
@@ -359,6 +413,6 @@ impl<E: Pairing> Proof<E> {
             rstars_vals: [s_0_prime, s_1_prime],
             third_sumcheck_msgs: third_proof.prover_messages(),
             tensorcheck_proof,
-        }
+        })
     }
 }
@@ -1,19 +1,24 @@
 //! The verifier for the algebraic holographicc proofs.
 use ark_ec::pairing::Pairing;
 use ark_ff::Field;
+use ark_std::borrow::Borrow;
 use ark_std::vec::Vec;
 use ark_std::{One, Zero};
 
-use crate::circuit::R1cs;
+use crate::circuit::{padded_dim, R1cs};
 use crate::errors::{VerificationError, VerificationResult};
-use crate::kzg::{Commitment, VerifierKey};
-use crate::misc::{evaluate_geometric_poly, evaluate_le, evaluate_tensor_poly};
+use crate::iterable::{Iterable, Reverse};
+use crate::kzg::{Commitment, PairingCheck, PreparedVerifierKey, VerifierKey};
+use crate::misc::{evaluate_geometric_poly, evaluate_tensor_poly};
 use crate::misc::{evaluate_index_poly, hadamard, powers, powers2};
-use crate::psnark::Proof;
+use crate::psnark::{Proof, PSNARK_LABEL};
 use crate::subprotocols::sumcheck::Subclaim;
-use crate::transcript::GeminiTranscript;
+use crate::transcript::{bind_context, bind_public_input, GeminiTranscript};
 use crate::PROTOCOL_NAME;
 
+/// The protocol name for the transcript sampling the randomness of batch verification.
+const BATCH_PROTOCOL_NAME: &[u8] = b"GEMINI-v0-batch";
+
 /// Given oracle access to a polynomial $f \in \FF[x]$ and a field element $\psi \in \FF$, returns $(f - \psi)(x)$.
 #[inline]
 fn eval_entryprod<F: Field>(oracle: impl FnOnce(F) -> F, psi: F, n: usize) -> impl FnOnce(F) -> F {
@@ -81,6 +86,8 @@ impl<E: Pairing> Proof<E> {
     /// Verification function for Preprocsessing SNARK proof.
     /// The input contains the R1CS instance and the verification key
     /// of polynomial commitment.
+    /// The public input `r1cs.x` is bound to the transcript,
+    /// and its contribution to $\vec z$ is evaluated by the verifier itself.
     pub fn verify(
         &self,
         r1cs: &R1cs<E::ScalarField>,
@@ -89,6 +96,167 @@ impl<E: Pairing> Proof<E> {
         num_non_zero: usize,
     ) -> VerificationResult {
         let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        self.verify_with_transcript(r1cs, vk, index_comms, num_non_zero, &mut transcript)
+    }
+
+    /// Verify a proof produced by [`Proof::new_time_with_transcript`],
+    /// deriving the challenges from `transcript`.
+    pub fn verify_with_transcript(
+        &self,
+        r1cs: &R1cs<E::ScalarField>,
+        vk: &VerifierKey<E>,
+        index_comms: &[Commitment<E>],
+        num_non_zero: usize,
+        transcript: &mut impl GeminiTranscript,
+    ) -> VerificationResult {
+        let checks = self.verify_deferred(r1cs, vk, index_comms, num_non_zero, transcript)?;
+        let [entry_product_check, tensorcheck_check] = checks;
+        vk.check(&entry_product_check)
+            .map_err(|e| e.within("entry product opening"))?;
+        vk.check(&tensorcheck_check)
+            .map_err(|e| e.within("tensorcheck opening"))
+    }
+
+    /// Same as [`Proof::verify`], with a verifier key prepared once for many proofs.
+    pub fn verify_prepared(
+        &self,
+        r1cs: &R1cs<E::ScalarField>,
+        pvk: &PreparedVerifierKey<E>,
+        index_comms: &[Commitment<E>],
+        num_non_zero: usize,
+    ) -> VerificationResult {
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        let vk = pvk.verifier_key();
+        let checks = self.verify_deferred(r1cs, vk, index_comms, num_non_zero, &mut transcript)?;
+        let [entry_product_check, tensorcheck_check] = checks;
+        pvk.check(&entry_product_check)
+            .map_err(|e| e.within("entry product opening"))?;
+        pvk.check(&tensorcheck_check)
+            .map_err(|e| e.within("tensorcheck opening"))
+    }
+
+    /// Run the verifier up to the final pairings,
+    /// and return the pairing checks left to verify.
+    ///
+    /// Only the pairings are deferred: the checks over the scalar field,
+    /// and the multi-scalar multiplications in $\GG_1$ building each [`PairingCheck`],
+    /// are computed natively by this function.
+    /// The proof is valid if and only if all returned checks pass [`VerifierKey::check`].
+    /// No constraint-system gadget for this verifier is provided:
+    /// the returned checks can be accumulated, e.g. with [`VerifierKey::batch_check`],
+    /// or delegated to an aggregator.
+    pub fn verify_deferred(
+        &self,
+        r1cs: &R1cs<E::ScalarField>,
+        vk: &VerifierKey<E>,
+        index_comms: &[Commitment<E>],
+        num_non_zero: usize,
+        transcript: &mut impl GeminiTranscript,
+    ) -> Result<[PairingCheck<E>; 2], VerificationError> {
+        // the instance is padded as the prover does.
+        let num_variables = padded_dim(r1cs.a.len(), r1cs.z.len());
+        self.deferred_verify(
+            transcript,
+            &Reverse(r1cs.x.as_slice()),
+            num_variables,
+            vk,
+            index_comms,
+            num_non_zero,
+        )
+    }
+
+    /// Same as [`Proof::verify`],
+    /// but reading the public input from the (big-endian) stream `x`,
+    /// for an instance with `num_constraints` constraints and `num_variables` variables.
+    ///
+    /// The public input is never held entirely in memory:
+    /// it is read once for binding it to the transcript,
+    /// and once for evaluating its contribution to $\vec z$.
+    pub fn verify_streaming<S>(
+        &self,
+        num_constraints: usize,
+        num_variables: usize,
+        x: &S,
+        vk: &VerifierKey<E>,
+        index_comms: &[Commitment<E>],
+        num_non_zero: usize,
+    ) -> VerificationResult
+    where
+        S: Iterable,
+        S::Item: Borrow<E::ScalarField>,
+    {
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        let num_variables = padded_dim(num_constraints, num_variables);
+        let [entry_product_check, tensorcheck_check] = self.deferred_verify(
+            &mut transcript,
+            x,
+            num_variables,
+            vk,
+            index_comms,
+            num_non_zero,
+        )?;
+        vk.check(&entry_product_check)
+            .map_err(|e| e.within("entry product opening"))?;
+        vk.check(&tensorcheck_check)
+            .map_err(|e| e.within("tensorcheck opening"))
+    }
+
+    /// Verify many proofs `proofs` for the same index, with public inputs `public_inputs`.
+    ///
+    /// Only the dimensions of `r1cs` are read: the public inputs are taken from `public_inputs`.
+    /// The pairing checks of all proofs are folded into a single multi-pairing.
+    pub fn verify_batch(
+        r1cs: &R1cs<E::ScalarField>,
+        vk: &VerifierKey<E>,
+        index_comms: &Vec<Commitment<E>>,
+        num_non_zero: usize,
+        proofs: &[Self],
+        public_inputs: &[Vec<E::ScalarField>],
+    ) -> VerificationResult {
+        if proofs.len() != public_inputs.len() {
+            return Err(VerificationError::Consistency {
+                check: "one public input per proof",
+            });
+        }
+        let num_variables = padded_dim(r1cs.a.len(), r1cs.z.len());
+        let mut transcript = merlin::Transcript::new(BATCH_PROTOCOL_NAME);
+        let mut checks = Vec::new();
+        for (proof, x) in proofs.iter().zip(public_inputs) {
+            transcript.append_serializable(b"public-input", x);
+            transcript.append_serializable(b"proof", proof);
+            let proof_checks = proof.deferred_verify(
+                &mut merlin::Transcript::new(PROTOCOL_NAME),
+                &Reverse(x.as_slice()),
+                num_variables,
+                vk,
+                index_comms,
+                num_non_zero,
+            )?;
+            checks.extend(proof_checks);
+        }
+        // the randomness is sampled after all proofs are fixed.
+        let randomness = transcript.get_challenge(b"batch-randomness");
+        vk.batch_check(&checks, randomness)
+    }
+
+    /// Run the verifier on the padded instance with (big-endian) public input `x` and `num_variables` variables,
+    /// deferring the final pairing checks.
+    pub(super) fn deferred_verify<S>(
+        &self,
+        transcript: &mut impl GeminiTranscript,
+        x: &S,
+        num_variables: usize,
+        vk: &VerifierKey<E>,
+        index_comms: &[Commitment<E>],
+        num_non_zero: usize,
+    ) -> Result<[PairingCheck<E>; 2], VerificationError>
+    where
+        S: Iterable,
+        S::Item: Borrow<E::ScalarField>,
+    {
+        // bind the context and the public input to the transcript.
+        bind_context::<E, _>(transcript, PSNARK_LABEL, &[num_variables, num_non_zero]);
+        bind_public_input::<E::ScalarField, _, _>(transcript, x);
         let witness_commitment = self.witness_commitment;
 
         transcript.append_serializable(b"witness", &witness_commitment);
@@ -97,13 +265,13 @@ impl<E: Pairing> Proof<E> {
 
         // Verify the first sumcheck
         let first_sumcheck_msgs = &self.first_sumcheck_msgs;
-        let subclaim_1 = Subclaim::new(&mut transcript, first_sumcheck_msgs, self.zc_alpha)?;
+        let subclaim_1 = Subclaim::new(transcript, first_sumcheck_msgs, self.zc_alpha)
+            .map_err(|e| e.within("first sumcheck"))?;
 
         /*
         TODO: num_constraints should be the input.
         */
         // let num_constraints = r1cs.a.len();
-        let num_variables = r1cs.z.len();
         self.r_star_commitments
             .iter()
             .zip(vec![b"ra*", b"rb*", b"rc*"].iter())
@@ -118,8 +286,8 @@ impl<E: Pairing> Proof<E> {
             + subclaim_1.final_foldings[0][1] * challenges[1]
             + self.zc_alpha * challenges[2];
 
-        let subclaim_2 =
-            Subclaim::new(&mut transcript, &self.second_sumcheck_msgs, asserted_sum_2)?;
+        let subclaim_2 = Subclaim::new(transcript, &self.second_sumcheck_msgs, asserted_sum_2)
+            .map_err(|e| e.within("second sumcheck"))?;
 
         let zeta = transcript.get_challenge::<E::ScalarField>(b"zeta");
 
@@ -182,14 +350,13 @@ impl<E: Pairing> Proof<E> {
             .iter()
             .map(|e| vec![*e])
             .collect::<Vec<_>>();
-        vk.verify_multi_points(
+        let entry_product_check = vk.multi_points_check(
             &commitments,
             &[mu],
             &evaluations[..],
             &self.ralpha_star_acc_mu_proof,
             &open_chal,
-        )
-        .map_err(|_e| VerificationError)?;
+        );
 
         // transcript.append_serializable(b"r_val_chal_a", &self.rstars_vals[0]);
         // transcript.append_serializable(b"r_val_chal_b", &self.rstars_vals[1]);
@@ -209,7 +376,8 @@ impl<E: Pairing> Proof<E> {
         asserted_sum_3.push(self.ralpha_star_acc_mu_evals[0]);
 
         let subclaim_3 =
-            Subclaim::new_batch(&mut transcript, &self.third_sumcheck_msgs, &asserted_sum_3)?;
+            Subclaim::new_batch(transcript, &self.third_sumcheck_msgs, &asserted_sum_3)
+                .map_err(|e| e.within("third sumcheck"))?;
 
         // Consistency check
         let batch_consistency = transcript.get_challenge::<E::ScalarField>(b"batch_challenge");
@@ -400,15 +568,18 @@ impl<E: Pairing> Proof<E> {
         tmp *= batch_consistency;
         //
         // lookup z*
-        let beta_power = E::ScalarField::pow(&beta, &[r1cs.x.len() as u64]);
-        let z_pos = evaluate_le(&r1cs.x, &beta)
-            + beta_power * self.tensorcheck_proof.base_polynomials_evaluations[0][1];
-        let z_neg = if (r1cs.x.len() & 1) == 0 {
-            evaluate_le(&r1cs.x, &-beta)
-                + beta_power * self.tensorcheck_proof.base_polynomials_evaluations[0][2]
+        let beta_power = E::ScalarField::pow(&beta, &[x.len() as u64]);
+        // evaluate the public input in beta and -beta with a single pass.
+        let minus_beta = -beta;
+        let (x_beta, x_minus_beta) = x.iter().fold(
+            (E::ScalarField::zero(), E::ScalarField::zero()),
+            |(pos, neg), e| (pos * beta + e.borrow(), neg * minus_beta + e.borrow()),
+        );
+        let z_pos = x_beta + beta_power * self.tensorcheck_proof.base_polynomials_evaluations[0][1];
+        let z_neg = if (x.len() & 1) == 0 {
+            x_minus_beta + beta_power * self.tensorcheck_proof.base_polynomials_evaluations[0][2]
         } else {
-            evaluate_le(&r1cs.x, &-beta)
-                - beta_power * self.tensorcheck_proof.base_polynomials_evaluations[0][2]
+            x_minus_beta - beta_power * self.tensorcheck_proof.base_polynomials_evaluations[0][2]
         };
         direct_base_polynomials_evaluations_2[0] += tmp
             * compute_plookup_set_eval(
@@ -529,32 +700,31 @@ impl<E: Pairing> Proof<E> {
 
         let mu_powers2 = powers2(mu, subclaim_3.challenges.len());
         let subclaim_3_chal_leading = &subclaim_3.challenges[0..subclaim_2.challenges.len()];
-        self.tensorcheck_proof
-            .verify(
-                &mut transcript,
-                vk,
-                &[
-                    asserted_res_vec_1,
-                    asserted_res_vec_2,
-                    asserted_res_vec_3,
-                    asserted_res_vec_4,
-                ],
-                &base_polynomials_commitments,
-                &[
-                    direct_base_polynomials_evaluations_1,
-                    direct_base_polynomials_evaluations_2,
-                    direct_base_polynomials_evaluations_3,
-                    direct_base_polynomials_evaluations_4,
-                ],
-                &[
-                    hadamard(&subclaim_3.challenges, &mu_powers2),
-                    subclaim_3.challenges.clone(),
-                    subclaim_2.challenges.clone(),
-                    hadamard(&subclaim_2.challenges, subclaim_3_chal_leading),
-                ],
-                beta,
-                batch_consistency,
-            )
-            .map_err(|_| VerificationError)
+        let tensorcheck_check = self.tensorcheck_proof.deferred_verify(
+            transcript,
+            vk,
+            &[
+                asserted_res_vec_1,
+                asserted_res_vec_2,
+                asserted_res_vec_3,
+                asserted_res_vec_4,
+            ],
+            &base_polynomials_commitments,
+            &[
+                direct_base_polynomials_evaluations_1,
+                direct_base_polynomials_evaluations_2,
+                direct_base_polynomials_evaluations_3,
+                direct_base_polynomials_evaluations_4,
+            ],
+            &[
+                hadamard(&subclaim_3.challenges, &mu_powers2),
+                subclaim_3.challenges.clone(),
+                subclaim_2.challenges.clone(),
+                hadamard(&subclaim_2.challenges, subclaim_3_chal_leading),
+            ],
+            beta,
+            batch_consistency,
+        )?;
+        Ok([entry_product_check, tensorcheck_check])
     }
 }
@@ -0,0 +1,301 @@
+//! Python bindings to the preprocessing SNARK over BLS12-381, built with [pyo3](https://pyo3.rs).
+//!
+//! Circuits are read from [circom](crate::circom) files, as in the C interface of the `ffi` feature:
+//! `setup(r1cs_path)` indexes the `.r1cs` file and returns a `ProvingKey` and a `VerifyingKey`,
+//! `prove(pk, r1cs_path, witness)` returns the proof as `bytes`,
+//! and `verify(vk, proof, public_input)` returns whether the proof is valid.
+//! Keys can be exported with `to_bytes`, and imported with `from_bytes`.
+//!
+//! The witness is either the path to a `.wtns` file, or the full assignment $\vec z$
+//! (starting with the constant one) as any of:
+//! - a `numpy.uint64` array of shape `(n,)`, for assignments with small entries;
+//! - a `numpy.uint8` array of shape `(n, 32)`, each row a field element in little-endian order;
+//! - a `bytes` object, the concatenation of the rows above.
+//!
+//! The public input (without the constant one) is passed in the same formats.
+//! Proving and verification release the GIL.
+//!
+//! Build the extension module with
+//! `cargo rustc --release --features python,pyo3/extension-module --crate-type cdylib`,
+//! and rename the library to `ark_gemini.so`.
+// the code generated by pyo3 is unsafe.
+#![allow(unsafe_code)]
+
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_serialize::*;
+use ark_std::string::{String, ToString};
+use ark_std::vec::Vec;
+use numpy::{PyReadonlyArray1, PyReadonlyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::PyBytes;
+use rand::rngs::OsRng;
+
+use crate::circom::{CircomR1cs, CircomWitness};
+use crate::circuit::{R1cs, R1csStreams};
+use crate::iterable::{Iterable, Reverse};
+use crate::kzg::{Commitment, CommitterKey, VerifierKey};
+use crate::psnark::{IndexCache, Proof, R1csStats};
+
+/// The size of a serialized field element, in bytes.
+const ELEMENT_SIZE: usize = 32;
+
+/// A vector of field elements, as passed from Python.
+#[derive(FromPyObject)]
+pub enum Elements<'py> {
+    /// The concatenation of the elements, in little-endian order.
+    Bytes(&'py PyBytes),
+    /// The elements, one per row, in little-endian order.
+    Array(PyReadonlyArray2<'py, u8>),
+    /// Elements smaller than \\(2^{64}\\).
+    Integers(PyReadonlyArray1<'py, u64>),
+}
+
+impl Elements<'_> {
+    /// Read the field elements; fail if any is malformed, or not in its canonical representation.
+    fn to_vec(&self) -> PyResult<Vec<Fr>> {
+        let rows: Vec<Vec<u8>> = match self {
+            Elements::Bytes(bytes) => {
+                let bytes = bytes.as_bytes();
+                if bytes.len() % ELEMENT_SIZE != 0 {
+                    return Err(PyValueError::new_err(
+                        "the length is not a multiple of 32 bytes",
+                    ));
+                }
+                bytes.chunks(ELEMENT_SIZE).map(<[u8]>::to_vec).collect()
+            }
+            Elements::Array(array) => {
+                let array = array.as_array();
+                if array.ncols() != ELEMENT_SIZE {
+                    return Err(PyValueError::new_err("the array does not have 32 columns"));
+                }
+                array.rows().into_iter().map(|row| row.to_vec()).collect()
+            }
+            Elements::Integers(array) => {
+                return Ok(array.as_array().iter().map(|&e| Fr::from(e)).collect());
+            }
+        };
+        rows.into_iter()
+            .map(|row| {
+                Fr::deserialize_compressed(&row[..])
+                    .map_err(|_| PyValueError::new_err("malformed field element"))
+            })
+            .collect()
+    }
+}
+
+/// The witness of a circuit, as passed from Python.
+#[derive(FromPyObject)]
+pub enum Witness<'py> {
+    /// The path to a `.wtns` file.
+    Path(String),
+    /// The full assignment $\vec z$.
+    Elements(Elements<'py>),
+}
+
+/// The proving key: the committer key and the index of the circuit.
+#[pyclass]
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct ProvingKey {
+    ck: CommitterKey<Bls12_381>,
+    cache: IndexCache<Bls12_381>,
+}
+
+/// The verifying key: the verification key and the commitments to the index.
+#[pyclass]
+#[derive(CanonicalSerialize, CanonicalDeserialize)]
+pub struct VerifyingKey {
+    vk: VerifierKey<Bls12_381>,
+    index_comms: Vec<Commitment<Bls12_381>>,
+    num_non_zero: usize,
+    num_constraints: usize,
+    num_variables: usize,
+    num_statement: usize,
+}
+
+fn to_bytes<'py>(py: Python<'py>, value: &impl CanonicalSerialize) -> &'py PyBytes {
+    let mut bytes = Vec::new();
+    value
+        .serialize_compressed(&mut bytes)
+        .expect("serialization into a vector cannot fail");
+    PyBytes::new(py, &bytes)
+}
+
+fn from_bytes<T: CanonicalDeserialize>(bytes: &[u8]) -> PyResult<T> {
+    T::deserialize_compressed(bytes).map_err(|_| PyValueError::new_err("malformed key"))
+}
+
+#[pymethods]
+impl ProvingKey {
+    /// Export the key.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        to_bytes(py, self)
+    }
+
+    /// Import a key exported with `to_bytes`.
+    #[staticmethod]
+    fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        from_bytes(bytes)
+    }
+}
+
+#[pymethods]
+impl VerifyingKey {
+    /// Export the key.
+    fn to_bytes<'py>(&self, py: Python<'py>) -> &'py PyBytes {
+        to_bytes(py, self)
+    }
+
+    /// Import a key exported with `to_bytes`.
+    #[staticmethod]
+    fn from_bytes(bytes: &[u8]) -> PyResult<Self> {
+        from_bytes(bytes)
+    }
+
+    /// The number of constraints of the circuit.
+    #[getter]
+    fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+
+    /// The length of the public input, without the constant one.
+    #[getter]
+    fn num_public(&self) -> usize {
+        self.num_statement - 1
+    }
+}
+
+/// Index the circuit of the circom file at `r1cs_path`,
+/// with a committer key sampled from the randomness of the operating system.
+#[pyfunction]
+pub fn setup(py: Python<'_>, r1cs_path: &str) -> PyResult<(ProvingKey, VerifyingKey)> {
+    py.allow_threads(|| {
+        let circom_r1cs = CircomR1cs::<Fr>::open(r1cs_path)?;
+        let index = circom_r1cs.relation(vec![Fr::from(0u64); circom_r1cs.num_variables()])?;
+        let stats = R1csStats::from_stream(&R1csStreams::new(&index).stream());
+        let ck = stats.committer_key(&mut OsRng);
+        let cache = IndexCache::new(&index, &ck);
+        let vk = VerifyingKey {
+            vk: VerifierKey::from(&ck),
+            index_comms: cache.commitments().to_vec(),
+            num_non_zero: cache.num_non_zero(),
+            num_constraints: index.a.len(),
+            num_variables: index.z.len(),
+            num_statement: circom_r1cs.num_statement(),
+        };
+        PyResult::Ok((ProvingKey { ck, cache }, vk))
+    })
+}
+
+/// Prove `witness` for the circuit of the circom file at `r1cs_path`, indexed in `pk`.
+///
+/// Return the proof, serialized with [`Proof::serialize_versioned`].
+#[pyfunction]
+pub fn prove<'py>(
+    py: Python<'py>,
+    pk: &ProvingKey,
+    r1cs_path: &str,
+    witness: Witness<'_>,
+) -> PyResult<&'py PyBytes> {
+    let z = match witness {
+        Witness::Path(path) => {
+            let mut z = CircomWitness::<Fr>::open(path)?
+                .z()
+                .iter()
+                .collect::<Vec<_>>();
+            z.reverse();
+            z
+        }
+        Witness::Elements(elements) => elements.to_vec()?,
+    };
+    let bytes = py.allow_threads(|| {
+        let r1cs: R1cs<Fr> = CircomR1cs::open(r1cs_path)?.relation(z)?;
+        let proof = Proof::new_time_cached(&r1cs, &pk.ck, &pk.cache)
+            .map_err(|e| PyValueError::new_err(e.to_string()))?;
+        let mut bytes = Vec::new();
+        proof
+            .serialize_versioned(&mut bytes)
+            .expect("serialization into a vector cannot fail");
+        PyResult::Ok(bytes)
+    })?;
+    Ok(PyBytes::new(py, &bytes))
+}
+
+/// Verify `proof` for `public_input` (without the constant one).
+///
+/// Fail if the proof or the public input are malformed, and return whether the proof is valid otherwise.
+#[pyfunction]
+pub fn verify(
+    py: Python<'_>,
+    vk: &VerifyingKey,
+    proof: &[u8],
+    public_input: Elements<'_>,
+) -> PyResult<bool> {
+    let proof = Proof::<Bls12_381>::deserialize_versioned(proof)
+        .map_err(|_| PyValueError::new_err("malformed proof"))?;
+    let mut x = vec![Fr::from(1u64)];
+    x.extend(public_input.to_vec()?);
+    if x.len() != vk.num_statement {
+        return Err(PyValueError::new_err(
+            "the public input does not match the circuit",
+        ));
+    }
+    Ok(py.allow_threads(|| {
+        proof
+            .verify_streaming(
+                vk.num_constraints,
+                vk.num_variables,
+                &Reverse(x.as_slice()),
+                &vk.vk,
+                &vk.index_comms,
+                vk.num_non_zero,
+            )
+            .is_ok()
+    }))
+}
+
+/// The `ark_gemini` Python module.
+#[pymodule]
+fn ark_gemini(_py: Python<'_>, m: &PyModule) -> PyResult<()> {
+    m.add_class::<ProvingKey>()?;
+    m.add_class::<VerifyingKey>()?;
+    m.add_function(wrap_pyfunction!(setup, m)?)?;
+    m.add_function(wrap_pyfunction!(prove, m)?)?;
+    m.add_function(wrap_pyfunction!(verify, m)?)?;
+    Ok(())
+}
+
+#[test]
+fn test_python() {
+    use crate::circom::write_circom_files;
+    use crate::circuit::{generate_relation, random_circuit};
+
+    let rng = &mut ark_std::test_rng();
+    let r1cs = generate_relation::<Fr, _>(random_circuit(rng, 1 << 6, 1 << 6));
+    let (r1cs_path, wtns_path) = write_circom_files(&r1cs, "gemini-test-python");
+    let r1cs_path = r1cs_path.to_str().unwrap();
+    let serialize = |elements: &[Fr]| {
+        let mut bytes = Vec::new();
+        for e in elements {
+            e.serialize_compressed(&mut bytes).unwrap();
+        }
+        bytes
+    };
+    let mut wrong_input = r1cs.x[1..].to_vec();
+    wrong_input[0] += Fr::from(1u64);
+
+    pyo3::prepare_freethreaded_python();
+    Python::with_gil(|py| {
+        let (pk, vk) = setup(py, r1cs_path).unwrap();
+        let vk = VerifyingKey::from_bytes(vk.to_bytes(py).as_bytes()).unwrap();
+        assert_eq!(vk.num_public(), r1cs.x.len() - 1);
+        let witness = Witness::Path(wtns_path.to_str().unwrap().into());
+        let proof = prove(py, &pk, r1cs_path, witness).unwrap().as_bytes();
+
+        let public_input = Elements::Bytes(PyBytes::new(py, &serialize(&r1cs.x[1..])));
+        assert!(verify(py, &vk, proof, public_input).unwrap());
+        let wrong_input = Elements::Bytes(PyBytes::new(py, &serialize(&wrong_input)));
+        assert!(!verify(py, &vk, proof, wrong_input).unwrap());
+        assert!(verify(py, &vk, &proof[1..], Elements::Bytes(PyBytes::new(py, &[]))).is_err());
+    });
+}
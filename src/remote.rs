@@ -0,0 +1,534 @@
+//! Proving on a remote machine, over a length-prefixed protocol.
+//!
+//! A [`ProvingService`] holds the index of a circuit and the committer key,
+//! and serves proofs to [`RemoteProver`] clients, usually over TCP.
+//! Each message is a frame: a one-byte tag, the length of the payload (four bytes, little-endian),
+//! and the payload.
+//! The client uploads the assignment $\vec z$ in stream order, in chunks of [`CHUNK_SIZE`] elements;
+//! the service spools the chunks to disk, and reads them back as an [`Upload`] stream,
+//! so that the witness is never held in a single message.
+//! The service proves with the elastic prover: the products $A\vec z$, $B\vec z$, $C\vec z$
+//! are spooled next to the upload, hence the witness is never held in memory either.
+//! The client can also ask the service to verify a proof against a public input.
+//!
+//! Connections are served one at a time, and the service answers each request with a proof,
+//! with the outcome of a verification, or with an error message.
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field, Zero};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::borrow::Borrow;
+use ark_std::marker::PhantomData;
+use ark_std::string::{String, ToString};
+use ark_std::vec::Vec;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::circuit::{matrix_into_colmaj, matrix_into_rowmaj, Matrix, R1cs, R1csStream};
+use crate::config::DEFAULT_MSM_BUFFER;
+use crate::errors::{GeminiError, GeminiResult};
+use crate::iterable::dummy::Mat;
+use crate::iterable::{Iterable, MatrixPadded, RandomAccessIterable, Reverse, ZeroPadded};
+use crate::kzg::{Commitment, CommitterKey, CommitterKeyStream, VerifierKey};
+use crate::misc::MatrixElement;
+use crate::psnark::{IndexCache, Proof};
+
+/// The number of elements uploaded in each frame.
+pub const CHUNK_SIZE: usize = 1 << 12;
+/// The largest accepted payload, in bytes.
+const MAX_FRAME_SIZE: usize = 1 << 26;
+
+/// A chunk of the assignment (client to service).
+const WITNESS: u8 = 0;
+/// The end of the assignment (client to service): prove it.
+const PROVE: u8 = 1;
+/// A proof and a public input (client to service): verify them.
+const VERIFY: u8 = 2;
+/// A proof (service to client).
+const PROOF: u8 = 3;
+/// The proof is valid (service to client).
+const VALID: u8 = 4;
+/// The proof is not valid (service to client).
+const INVALID: u8 = 5;
+/// An error message (service to client).
+const ERROR: u8 = 6;
+
+/// Counter distinguishing the uploads to the same process.
+static UPLOAD_COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+fn invalid_data<E: ToString>(e: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e.to_string())
+}
+
+fn write_frame<W: Write>(writer: &mut W, tag: u8, payload: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(payload.len()).map_err(invalid_data)?;
+    writer.write_all(&[tag])?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(payload)
+}
+
+fn read_frame<R: Read>(reader: &mut R) -> io::Result<(u8, Vec<u8>)> {
+    let mut header = [0u8; 5];
+    reader.read_exact(&mut header)?;
+    let len = u32::from_le_bytes([header[1], header[2], header[3], header[4]]) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(invalid_data("frame too large"));
+    }
+    let mut payload = vec![0u8; len];
+    reader.read_exact(&mut payload)?;
+    Ok((header[0], payload))
+}
+
+/// An assignment uploaded in chunks, and spooled to a file in the temporary directory.
+///
+/// It streams the elements in the order they were uploaded.
+/// The elements are checked once, when uploaded, and the file is deleted once the stream is dropped.
+pub struct Upload<F> {
+    path: PathBuf,
+    len: usize,
+    _field: PhantomData<F>,
+}
+
+impl<F: CanonicalSerialize + CanonicalDeserialize> Upload<F> {
+    /// Create an empty upload.
+    pub fn new() -> io::Result<Self> {
+        let path = std::env::temp_dir().join(std::format!(
+            "gemini-upload-{}-{}",
+            std::process::id(),
+            UPLOAD_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        File::create(&path)?;
+        Ok(Self {
+            path,
+            len: 0,
+            _field: PhantomData,
+        })
+    }
+
+    /// Append the chunk `chunk`, the concatenation of uncompressed elements.
+    ///
+    /// Fails, leaving the upload unchanged, if any element is malformed.
+    pub fn push(&mut self, chunk: &[u8]) -> io::Result<()> {
+        let mut reader = chunk;
+        let mut num_elements = 0;
+        while !reader.is_empty() {
+            F::deserialize_uncompressed(&mut reader).map_err(invalid_data)?;
+            num_elements += 1;
+        }
+        OpenOptions::new()
+            .append(true)
+            .open(&self.path)?
+            .write_all(chunk)?;
+        self.len += num_elements;
+        Ok(())
+    }
+}
+
+impl<F> Drop for Upload<F> {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// The iterator returned by [`Upload`].
+pub struct UploadIter<F> {
+    reader: BufReader<File>,
+    remaining: usize,
+    _field: PhantomData<F>,
+}
+
+impl<F: CanonicalDeserialize> Iterator for UploadIter<F> {
+    type Item = F;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        Some(F::deserialize_uncompressed_unchecked(&mut self.reader).expect("corrupted upload"))
+    }
+}
+
+impl<F: CanonicalSerialize + CanonicalDeserialize> Iterable for Upload<F> {
+    type Item = F;
+
+    type Iter = UploadIter<F>;
+
+    fn iter(&self) -> Self::Iter {
+        UploadIter {
+            reader: BufReader::new(File::open(&self.path).expect("the upload is not readable")),
+            remaining: self.len,
+            _field: PhantomData,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<F: CanonicalSerialize + CanonicalDeserialize + Zero> RandomAccessIterable for Upload<F> {
+    fn iter_from(&self, idx: usize) -> Self::Iter {
+        let idx = usize::min(idx, self.len);
+        let offset = (idx * F::zero().uncompressed_size()) as u64;
+        let mut reader =
+            BufReader::new(File::open(&self.path).expect("the upload is not readable"));
+        reader
+            .seek(SeekFrom::Start(offset))
+            .expect("the upload is not readable");
+        UploadIter {
+            reader,
+            remaining: self.len - idx,
+            _field: PhantomData,
+        }
+    }
+}
+
+/// Random access to the elements of an [`Upload`], holding one chunk of elements in memory.
+struct UploadReader<'a, F> {
+    upload: &'a Upload<F>,
+    /// The position of the first element of the current chunk.
+    first: usize,
+    chunk: Vec<F>,
+}
+
+impl<'a, F: CanonicalSerialize + CanonicalDeserialize + Zero> UploadReader<'a, F> {
+    fn new(upload: &'a Upload<F>) -> Self {
+        Self {
+            upload,
+            first: 0,
+            chunk: Vec::new(),
+        }
+    }
+
+    /// Return the element at position `idx` (in stream order), if any.
+    ///
+    /// The chunk holding it is read if it is not the current one.
+    fn get(&mut self, idx: usize) -> Option<F> {
+        if idx >= self.upload.len {
+            return None;
+        }
+        if idx < self.first || idx >= self.first + self.chunk.len() {
+            self.first = idx - idx % CHUNK_SIZE;
+            self.chunk = self.upload.iter_from(self.first).take(CHUNK_SIZE).collect();
+        }
+        self.chunk.get(idx - self.first).copied()
+    }
+}
+
+/// Spool the (big-endian) product of `matrix` with the assignment `z` (in stream order),
+/// row by row.
+fn spool_product<F>(matrix: &Matrix<F>, z: &Upload<F>) -> io::Result<Upload<F>>
+where
+    F: Field,
+{
+    let mut reader = UploadReader::new(z);
+    let mut product = Upload::new()?;
+    let element_size = F::zero().uncompressed_size();
+    let mut chunk = Vec::with_capacity(CHUNK_SIZE * element_size);
+    for row in matrix.iter().rev() {
+        let mut entry = F::zero();
+        for &(val, col) in row {
+            // the entry `col` of z is at position `z.len() - 1 - col` of the stream.
+            let z_col = z
+                .len
+                .checked_sub(col + 1)
+                .and_then(|idx| reader.get(idx))
+                .ok_or_else(|| invalid_data("the index has more columns than variables"))?;
+            entry += val * z_col;
+        }
+        entry
+            .serialize_uncompressed(&mut chunk)
+            .map_err(invalid_data)?;
+        if chunk.len() == CHUNK_SIZE * element_size {
+            product.push(&chunk)?;
+            chunk.clear();
+        }
+    }
+    product.push(&chunk)?;
+    Ok(product)
+}
+
+/// A service proving assignments for a fixed circuit.
+pub struct ProvingService<E: Pairing> {
+    r1cs: R1cs<E::ScalarField>,
+    ck: CommitterKey<E>,
+    /// The column- and row-major streams of $A$, $B$, $C$, flattened once.
+    colmaj: [Vec<MatrixElement<E::ScalarField>>; 3],
+    rowmaj: [Vec<MatrixElement<E::ScalarField>>; 3],
+    index_comms: Vec<Commitment<E>>,
+    num_non_zero: usize,
+}
+
+impl<E: Pairing> ProvingService<E> {
+    /// Serve proofs for the circuit `index` (whose assignment is ignored), with the committer key `ck`.
+    pub fn new(index: R1cs<E::ScalarField>, ck: CommitterKey<E>) -> Self {
+        let cache = IndexCache::new(&index, &ck);
+        // the matrices are streamed as square, with `dim` lines.
+        let dim = usize::max(index.a.len(), index.z.len());
+        let colmaj = [&index.a, &index.b, &index.c].map(|m| matrix_into_colmaj(m, dim));
+        let rowmaj = [&index.a, &index.b, &index.c].map(|m| matrix_into_rowmaj(m));
+        Self {
+            r1cs: index,
+            ck,
+            colmaj,
+            rowmaj,
+            index_comms: cache.commitments().to_vec(),
+            num_non_zero: cache.num_non_zero(),
+        }
+    }
+
+    /// Prove the assignment `z`, uploaded in stream order.
+    ///
+    /// The products of the matrices with `z` are spooled to temporary files,
+    /// and the elastic prover streams the upload and the spools from disk.
+    pub fn prove(&self, z: &Upload<E::ScalarField>) -> GeminiResult<Proof<E>> {
+        let num_constraints = self.r1cs.a.len();
+        let num_variables = self.r1cs.z.len();
+        let num_statement = self.r1cs.x.len();
+        GeminiError::ensure_len("z", num_variables, z.len())?;
+        let dim = usize::max(num_constraints, num_variables);
+        let spool = |name, m: &Matrix<E::ScalarField>| {
+            spool_product(m, z).map_err(|e| GeminiError::Io {
+                name,
+                message: e.to_string(),
+            })
+        };
+        let z_a = spool("r1cs.z_a", &self.r1cs.a)?;
+        let z_b = spool("r1cs.z_b", &self.r1cs.b)?;
+        let z_c = spool("r1cs.z_c", &self.r1cs.c)?;
+        // the witness is made of the leading elements of the stream.
+        let witness = z.slice(0, num_variables - num_statement);
+
+        let colmaj = self.colmaj.each_ref().map(|m| Mat(m.as_slice(), dim));
+        let rowmaj = self
+            .rowmaj
+            .each_ref()
+            .map(|m| Mat(m.as_slice(), num_constraints));
+        let r1cs_stream = R1csStream {
+            a_colmaj: MatrixPadded::new(&colmaj[0], 0),
+            b_colmaj: MatrixPadded::new(&colmaj[1], 0),
+            c_colmaj: MatrixPadded::new(&colmaj[2], 0),
+            a_rowmaj: MatrixPadded::new(&rowmaj[0], dim - num_constraints),
+            b_rowmaj: MatrixPadded::new(&rowmaj[1], dim - num_constraints),
+            c_rowmaj: MatrixPadded::new(&rowmaj[2], dim - num_constraints),
+            z: ZeroPadded::new(z, dim),
+            witness: ZeroPadded::new(&witness, dim - num_statement),
+            z_a: ZeroPadded::new(&z_a, dim),
+            z_b: ZeroPadded::new(&z_b, dim),
+            z_c: ZeroPadded::new(&z_c, dim),
+            nonzero: dim,
+            joint_len: self.num_non_zero,
+        };
+        Proof::new_elastic(
+            &r1cs_stream,
+            &CommitterKeyStream::from(&self.ck),
+            DEFAULT_MSM_BUFFER,
+        )
+    }
+
+    /// Verify `proof` for the public input `x` (without the constant one).
+    pub fn verify(&self, proof: &Proof<E>, x: &[E::ScalarField]) -> bool {
+        if x.len() + 1 != self.r1cs.x.len() {
+            return false;
+        }
+        let mut statement = vec![E::ScalarField::from(1u64)];
+        statement.extend_from_slice(x);
+        proof
+            .verify_streaming(
+                self.r1cs.a.len(),
+                self.r1cs.z.len(),
+                &Reverse(statement.as_slice()),
+                &VerifierKey::from(&self.ck),
+                &self.index_comms,
+                self.num_non_zero,
+            )
+            .is_ok()
+    }
+
+    /// Serve the connections to `listener`, one at a time.
+    ///
+    /// A failed connection is logged and dropped; the service stops only if `listener` fails.
+    pub fn serve(&mut self, listener: &TcpListener) -> io::Result<()> {
+        for stream in listener.incoming() {
+            if let Err(e) = self.handle(stream?) {
+                log::warn!("remote prover: connection dropped: {}", e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Answer the requests in `stream` until the client closes it.
+    pub fn handle<S: Read + Write>(&mut self, mut stream: S) -> io::Result<()> {
+        let mut upload = None;
+        loop {
+            let (tag, payload) = match read_frame(&mut stream) {
+                Ok(frame) => frame,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+                Err(e) => return Err(e),
+            };
+            match tag {
+                WITNESS => {
+                    if upload.is_none() {
+                        upload = Some(Upload::<E::ScalarField>::new()?);
+                    }
+                    if let Err(e) = upload.as_mut().unwrap().push(&payload) {
+                        write_frame(&mut stream, ERROR, e.to_string().as_bytes())?;
+                        return Err(e);
+                    }
+                }
+                PROVE => {
+                    let z = match upload.take() {
+                        Some(upload) => upload,
+                        None => Upload::new()?,
+                    };
+                    match self.prove(&z) {
+                        Ok(proof) => {
+                            let mut bytes = Vec::new();
+                            proof
+                                .serialize_versioned(&mut bytes)
+                                .expect("serialization into a vector cannot fail");
+                            write_frame(&mut stream, PROOF, &bytes)?;
+                        }
+                        Err(e) => write_frame(&mut stream, ERROR, e.to_string().as_bytes())?,
+                    }
+                }
+                VERIFY => {
+                    let mut reader = &payload[..];
+                    let request =
+                        Proof::<E>::deserialize_versioned(&mut reader).and_then(|proof| {
+                            let x = Vec::<E::ScalarField>::deserialize_compressed(&mut reader)?;
+                            Ok((proof, x))
+                        });
+                    match request {
+                        Ok((proof, x)) if self.verify(&proof, &x) => {
+                            write_frame(&mut stream, VALID, &[])?
+                        }
+                        Ok(_) => write_frame(&mut stream, INVALID, &[])?,
+                        Err(e) => write_frame(&mut stream, ERROR, e.to_string().as_bytes())?,
+                    }
+                }
+                _ => {
+                    write_frame(&mut stream, ERROR, b"unknown request")?;
+                    return Err(invalid_data("unknown request"));
+                }
+            }
+            stream.flush()?;
+        }
+    }
+}
+
+/// A client of a [`ProvingService`].
+pub struct RemoteProver<E, S> {
+    stream: S,
+    _pairing: PhantomData<E>,
+}
+
+impl<E: Pairing> RemoteProver<E, TcpStream> {
+    /// Connect to the service listening at `addr`.
+    pub fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<Self> {
+        Ok(Self::new(TcpStream::connect(addr)?))
+    }
+}
+
+impl<E: Pairing, S: Read + Write> RemoteProver<E, S> {
+    /// Talk to the service over `stream`.
+    pub fn new(stream: S) -> Self {
+        Self {
+            stream,
+            _pairing: PhantomData,
+        }
+    }
+
+    /// Read the answer of the service, failing if it is an error.
+    fn answer(&mut self) -> io::Result<(u8, Vec<u8>)> {
+        let (tag, payload) = read_frame(&mut self.stream)?;
+        if tag == ERROR {
+            let message = String::from_utf8_lossy(&payload).into_owned();
+            return Err(io::Error::new(io::ErrorKind::Other, message));
+        }
+        Ok((tag, payload))
+    }
+
+    /// Upload the assignment `z` (in stream order), and return its proof.
+    pub fn prove<I>(&mut self, z: &I) -> io::Result<Proof<E>>
+    where
+        I: Iterable,
+        I::Item: Borrow<E::ScalarField>,
+    {
+        let element_size = E::ScalarField::zero().uncompressed_size();
+        let mut chunk = Vec::with_capacity(CHUNK_SIZE * element_size);
+        for element in z.iter() {
+            element
+                .borrow()
+                .serialize_uncompressed(&mut chunk)
+                .map_err(invalid_data)?;
+            if chunk.len() == CHUNK_SIZE * element_size {
+                write_frame(&mut self.stream, WITNESS, &chunk)?;
+                chunk.clear();
+            }
+        }
+        if !chunk.is_empty() {
+            write_frame(&mut self.stream, WITNESS, &chunk)?;
+        }
+        write_frame(&mut self.stream, PROVE, &[])?;
+        self.stream.flush()?;
+        match self.answer()? {
+            (PROOF, payload) => Proof::deserialize_versioned(&payload[..]).map_err(invalid_data),
+            _ => Err(invalid_data("unexpected answer")),
+        }
+    }
+
+    /// Ask the service whether `proof` is valid for the public input `x` (without the constant one).
+    pub fn verify(&mut self, proof: &Proof<E>, x: &[E::ScalarField]) -> io::Result<bool> {
+        let mut payload = Vec::new();
+        proof
+            .serialize_versioned(&mut payload)
+            .and_then(|_| x.serialize_compressed(&mut payload))
+            .map_err(invalid_data)?;
+        write_frame(&mut self.stream, VERIFY, &payload)?;
+        self.stream.flush()?;
+        match self.answer()? {
+            (VALID, _) => Ok(true),
+            (INVALID, _) => Ok(false),
+            _ => Err(invalid_data("unexpected answer")),
+        }
+    }
+}
+
+#[test]
+fn test_remote_prover() {
+    use ark_bls12_381::{Bls12_381, Fr};
+
+    use crate::circuit::{generate_relation, random_circuit, R1csStreams};
+    use crate::psnark::R1csStats;
+
+    let rng = &mut ark_std::test_rng();
+    // spread the assignment across several chunks.
+    let r1cs = generate_relation::<Fr, _>(random_circuit(rng, CHUNK_SIZE, CHUNK_SIZE + 1));
+    let ck = R1csStats::from_stream(&R1csStreams::new(&r1cs).stream()).committer_key(rng);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut service = ProvingService::<Bls12_381>::new(r1cs.clone(), ck.clone());
+    let server = std::thread::spawn(move || {
+        let (stream, _) = listener.accept().unwrap();
+        service.handle(stream).unwrap();
+    });
+
+    let mut client = RemoteProver::<Bls12_381, _>::connect(addr).unwrap();
+    let proof = client.prove(&Reverse(r1cs.z.as_slice())).unwrap();
+    // the elastic prover of the service agrees with the time prover.
+    assert!(proof == Proof::new_time(&r1cs, &ck).unwrap());
+    assert!(client.verify(&proof, &r1cs.x[1..]).unwrap());
+    let mut wrong_input = r1cs.x[1..].to_vec();
+    wrong_input[0] += Fr::from(1u64);
+    assert!(!client.verify(&proof, &wrong_input).unwrap());
+    // a truncated assignment is rejected.
+    assert!(client.prove(&Reverse(&r1cs.z[1..])).is_err());
+    drop(client);
+    server.join().unwrap();
+}
@@ -0,0 +1,132 @@
+//! The arkworks [`SNARK`] interface of the non-preprocessing SNARK.
+//!
+//! The setup only sizes the committer key for the circuit:
+//! since the verifier reads the matrices, they are part of the verifying key.
+use ark_ec::pairing::Pairing;
+use ark_relations::r1cs::ConstraintSynthesizer;
+use ark_serialize::*;
+use ark_snark::{CircuitSpecificSetupSNARK, SNARK};
+use ark_std::marker::PhantomData;
+use ark_std::rand::{CryptoRng, RngCore};
+use ark_std::Zero;
+
+use crate::circuit::{generate_index, padded_dim, statement, try_generate_relation, Matrix, R1cs};
+use crate::errors::{GeminiError, GeminiResult};
+use crate::kzg::{CommitterKey, VerifierKey};
+use crate::snark::Proof;
+
+/// The non-preprocessing SNARK over the pairing `E`, as an arkworks [`SNARK`].
+pub struct GeminiSnark<E: Pairing>(PhantomData<E>);
+
+/// The proving key of [`GeminiSnark`].
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone)]
+pub struct ProvingKey<E: Pairing> {
+    /// The committer key.
+    pub ck: CommitterKey<E>,
+}
+
+/// The verifying key of [`GeminiSnark`].
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone)]
+pub struct VerifyingKey<E: Pairing> {
+    /// The verification key of the commitment scheme.
+    pub vk: VerifierKey<E>,
+    /// The matrix $A$ of the circuit.
+    pub a: Matrix<E::ScalarField>,
+    /// The matrix $B$ of the circuit.
+    pub b: Matrix<E::ScalarField>,
+    /// The matrix $C$ of the circuit.
+    pub c: Matrix<E::ScalarField>,
+    /// The number of instance variables, including the constant one and the padding.
+    pub num_instance: usize,
+    /// The number of variables.
+    pub num_variables: usize,
+}
+
+impl<E: Pairing> SNARK<E::ScalarField> for GeminiSnark<E> {
+    type ProvingKey = ProvingKey<E>;
+    type VerifyingKey = VerifyingKey<E>;
+    type Proof = Proof<E>;
+    type ProcessedVerifyingKey = VerifyingKey<E>;
+    type Error = GeminiError;
+
+    fn circuit_specific_setup<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore + CryptoRng>(
+        circuit: C,
+        rng: &mut R,
+    ) -> GeminiResult<(ProvingKey<E>, VerifyingKey<E>)> {
+        let index = generate_index(circuit)?;
+        let dim = padded_dim(index.a.len(), index.z.len());
+        // the largest polynomials committed, as in `check_key_len`.
+        let max_degree = usize::max(dim - index.x.len(), dim / 2);
+        let ck = CommitterKey::new(max_degree, 3, rng);
+        let vk = VerifyingKey {
+            vk: VerifierKey::from(&ck),
+            num_instance: index.x.len(),
+            num_variables: index.z.len(),
+            a: index.a,
+            b: index.b,
+            c: index.c,
+        };
+        Ok((ProvingKey { ck }, vk))
+    }
+
+    /// Prove with the time-efficient prover: the proof is deterministic, and `rng` is not used.
+    fn prove<C: ConstraintSynthesizer<E::ScalarField>, R: RngCore + CryptoRng>(
+        pk: &ProvingKey<E>,
+        circuit: C,
+        _rng: &mut R,
+    ) -> GeminiResult<Proof<E>> {
+        let r1cs = try_generate_relation(circuit)?;
+        Proof::new_time(&r1cs, &pk.ck)
+    }
+
+    fn process_vk(vk: &VerifyingKey<E>) -> GeminiResult<VerifyingKey<E>> {
+        Ok(vk.clone())
+    }
+
+    /// Verify `proof` for the public input `public_input`,
+    /// which excludes the constant one and may omit the padding.
+    fn verify_with_processed_vk(
+        pvk: &VerifyingKey<E>,
+        public_input: &[E::ScalarField],
+        proof: &Proof<E>,
+    ) -> GeminiResult<bool> {
+        let x = statement(public_input, pvk.num_instance)?;
+        let num_witness = pvk.num_variables - pvk.num_instance;
+        let mut z = x.clone();
+        z.resize(pvk.num_variables, E::ScalarField::zero());
+        let r1cs = R1cs {
+            a: pvk.a.clone(),
+            b: pvk.b.clone(),
+            c: pvk.c.clone(),
+            z,
+            w: vec![E::ScalarField::zero(); num_witness],
+            x,
+        };
+        Ok(proof.verify(&r1cs, &pvk.vk).is_ok())
+    }
+}
+
+impl<E: Pairing> CircuitSpecificSetupSNARK<E::ScalarField> for GeminiSnark<E> {}
+
+#[test]
+fn test_snark_trait() {
+    use ark_bls12_381::{Bls12_381, Fr};
+    use ark_std::test_rng;
+    use ark_std::One;
+
+    use crate::circuit::random_circuit;
+
+    let rng = &mut test_rng();
+    let circuit = random_circuit::<Fr>(rng, 16, 16);
+    let public_input = try_generate_relation(circuit).unwrap().x[1..].to_vec();
+
+    let (pk, vk) = GeminiSnark::<Bls12_381>::setup(circuit, rng).unwrap();
+    let proof = GeminiSnark::prove(&pk, circuit, rng).unwrap();
+    assert!(GeminiSnark::verify(&vk, &public_input, &proof).unwrap());
+
+    let mut wrong_input = public_input.clone();
+    wrong_input[0] += Fr::one();
+    assert!(!GeminiSnark::verify(&vk, &wrong_input, &proof).unwrap());
+    let too_long = vec![Fr::one(); vk.num_instance];
+    assert!(GeminiSnark::verify(&vk, &too_long, &proof).is_err());
+}
@@ -0,0 +1,324 @@
+//! Batched proofs for many instances sharing the same index.
+//!
+//! Data-parallel applications prove the same circuit (the same matrices $A, B, C$)
+//! over many statements $\vec x_0, \dots, \vec x_{k-1}$ and witnesses $\vec w_0, \dots, \vec w_{k-1}$.
+//! A [`BatchProof`] proves all of them at once:
+//! the prover commits to each witness and sends each $u_{2, i} \defeq C \vec z_i \cdot \vec r_C$,
+//! and the $k$ first sumchecks are batched (see [`BatchSumcheck`](crate::subprotocols::sumcheck::BatchSumcheck)),
+//! hence share the challenges $\rho_0, \dots, \rho_{n-1}$.
+//! The claims of the second sumcheck are then linear in $\vec z_i$ over the same matrices:
+//! for a challenge $\mu \in \FF^\times$, they collapse into a single claim over
+//! $\vec z \defeq \sum_i \mu^i \vec z_i$,
+//! proven with one second sumcheck and one tensorcheck,
+//! whose commitment to $\sum_i \mu^i \vec w_i$ the verifier derives from the commitments to the witnesses.
+//!
+//! Each additional instance adds to the proof one commitment, one field element, and one final folding,
+//! and the verifier processes the matrices only once.
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field, Zero};
+use ark_serialize::*;
+use ark_std::vec::Vec;
+#[cfg(feature = "prover")]
+use ark_std::{boxed::Box, One};
+
+use crate::circuit::R1cs;
+use crate::errors::{VerificationError, VerificationResult};
+use crate::iterable::Reverse;
+use crate::kzg::{Commitment, VerifierKey};
+use crate::misc::{evaluate_le_at, ip, powers};
+use crate::snark::verifier::evaluate_matrices;
+use crate::subprotocols::sumcheck::{ProverMsgs, Subclaim};
+use crate::subprotocols::tensorcheck::TensorcheckProof;
+use crate::transcript::{bind_context, bind_public_input, GeminiTranscript};
+use crate::PROTOCOL_NAME;
+#[cfg(feature = "prover")]
+use crate::{
+    errors::{GeminiError, GeminiResult},
+    kzg::CommitterKey,
+    misc::{evaluate_le, hadamard, product_matrix_vector, product_vectors_matrices, tensor},
+    snark::check_key_len,
+    subprotocols::sumcheck::proof::Sumcheck,
+    subprotocols::sumcheck::time_prover::Witness,
+    subprotocols::sumcheck::{Prover, TimeProver},
+};
+
+/// The identifier of the batched SNARK, bound to the transcript.
+const BATCH_SNARK_LABEL: &[u8] = b"batch-snark";
+
+/// A SNARK proof for many instances sharing the same matrices.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, PartialEq, Eq)]
+pub struct BatchProof<E: Pairing> {
+    witness_commitments: Vec<Commitment<E>>,
+    zc_alphas: Vec<E::ScalarField>,
+    first_sumcheck_msgs: ProverMsgs<E::ScalarField>,
+    second_sumcheck_msgs: ProverMsgs<E::ScalarField>,
+    tensorcheck_proof: TensorcheckProof<E>,
+}
+
+impl<E: Pairing> ark_std::fmt::Debug for BatchProof<E> {
+    fn fmt(&self, f: &mut ark_std::fmt::Formatter<'_>) -> ark_std::fmt::Result {
+        f.debug_struct("BatchProof")
+            .field("instances", &self.witness_commitments.len())
+            .finish()
+    }
+}
+
+/// Return the instances padded, failing unless there is at least one instance,
+/// and all of them share the matrices and the lengths of the statement and of the witness of the first.
+fn padded_instances<F: Field>(instances: &[R1cs<F>]) -> Option<Vec<R1cs<F>>> {
+    let first = instances.first()?;
+    let same_index = instances.iter().all(|r1cs| {
+        r1cs.a == first.a
+            && r1cs.b == first.b
+            && r1cs.c == first.c
+            && r1cs.x.len() == first.x.len()
+            && r1cs.z.len() == first.z.len()
+    });
+    same_index.then(|| {
+        instances
+            .iter()
+            .map(|r1cs| r1cs.padded().into_owned())
+            .collect()
+    })
+}
+
+/// Return the linear combination of the `vectors` (of the same length) with the `coefficients`.
+fn combine<F: Field>(vectors: &[&[F]], coefficients: &[F]) -> Vec<F> {
+    let mut combination = vec![F::zero(); vectors[0].len()];
+    for (vector, coefficient) in vectors.iter().zip(coefficients) {
+        combination
+            .iter_mut()
+            .zip(vector.iter())
+            .for_each(|(acc, element)| *acc += *coefficient * element);
+    }
+    combination
+}
+
+impl<E: Pairing> BatchProof<E> {
+    /// Produce a proof for all the `instances`, using the time-efficient prover.
+    ///
+    /// Fails if there are no instances, if they do not share the same matrices,
+    /// if any of them is malformed (see [`R1cs::check`]),
+    /// or if the committer key is too short for the instances.
+    #[cfg(feature = "prover")]
+    pub fn new_time(
+        instances: &[R1cs<E::ScalarField>],
+        ck: &CommitterKey<E>,
+    ) -> GeminiResult<Self> {
+        let not_shared = GeminiError::MalformedInstance {
+            reason: "the instances do not share the same index",
+        };
+        instances.iter().try_for_each(|r1cs| r1cs.check())?;
+        let instances = padded_instances(instances).ok_or(not_shared)?;
+        let index = &instances[0];
+        check_key_len(
+            index.a.len(),
+            index.z.len(),
+            index.x.len(),
+            ck.powers_of_g.len(),
+        )?;
+        trace_span!(
+            "snark_batch_prover",
+            instances = instances.len(),
+            num_constraints = index.a.len(),
+        );
+
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        let transcript = &mut transcript;
+        bind_context::<E, _>(
+            transcript,
+            BATCH_SNARK_LABEL,
+            &[index.a.len(), index.z.len(), instances.len()],
+        );
+        instances.iter().for_each(|r1cs| {
+            bind_public_input::<E::ScalarField, _, _>(transcript, &Reverse(r1cs.x.as_slice()))
+        });
+        let witness_commitments = ck.batch_commit(instances.iter().map(|r1cs| &r1cs.w));
+        witness_commitments
+            .iter()
+            .for_each(|c| transcript.append_serializable(b"witness", c));
+        let alpha = transcript.get_challenge(b"alpha");
+
+        let products = instances
+            .iter()
+            .map(|r1cs| {
+                [&r1cs.a, &r1cs.b, &r1cs.c].map(|matrix| product_matrix_vector(matrix, &r1cs.z))
+            })
+            .collect::<Vec<_>>();
+        let zc_alphas = products
+            .iter()
+            .map(|[_, _, z_c]| evaluate_le(z_c, &alpha))
+            .collect::<Vec<_>>();
+        zc_alphas
+            .iter()
+            .for_each(|zc_alpha| transcript.append_serializable(b"zc(alpha)", zc_alpha));
+
+        let provers = products
+            .iter()
+            .map(|[z_a, z_b, _]| {
+                Box::new(TimeProver::new(Witness::new(z_a, z_b, &alpha)))
+                    as Box<dyn Prover<E::ScalarField>>
+            })
+            .collect::<Vec<_>>();
+        let first_proof = Sumcheck::prove_batch(transcript, provers)?;
+        let first_sumcheck_msgs = first_proof.prover_messages();
+
+        let eta = transcript.get_challenge::<E::ScalarField>(b"eta");
+        let mu = transcript.get_challenge::<E::ScalarField>(b"mu");
+        let mus = powers(mu, instances.len());
+        let eta2 = eta.square();
+
+        let b_challenges = tensor(&first_proof.challenges);
+        let c_challenges = powers(alpha, b_challenges.len());
+        let a_challenges = hadamard(&b_challenges, &c_challenges);
+        let eta_b_challenges = b_challenges.iter().map(|&b| eta * b).collect::<Vec<_>>();
+        let eta2_c_challenges = c_challenges.iter().map(|&c| eta2 * c).collect::<Vec<_>>();
+        let abc_tensored = product_vectors_matrices(
+            &[
+                (index.a.as_slice(), a_challenges.as_slice()),
+                (index.b.as_slice(), eta_b_challenges.as_slice()),
+                (index.c.as_slice(), eta2_c_challenges.as_slice()),
+            ],
+            index.z.len(),
+        );
+
+        let zs = instances.iter().map(|r1cs| &r1cs.z[..]).collect::<Vec<_>>();
+        let ws = instances.iter().map(|r1cs| &r1cs.w[..]).collect::<Vec<_>>();
+        let z = combine(&zs, &mus);
+        let w = combine(&ws, &mus);
+        let second_proof =
+            Sumcheck::new_time(transcript, &abc_tensored, &z, &E::ScalarField::one())?;
+        let second_sumcheck_msgs = second_proof.prover_messages();
+
+        let second_sumcheck_polynomials = [&abc_tensored, &z];
+        let tc_body_polynomials = [(
+            &second_sumcheck_polynomials[..],
+            &second_proof.challenges[..],
+        )];
+        let tensorcheck_proof =
+            TensorcheckProof::new_time(transcript, ck, [&w], tc_body_polynomials);
+
+        Ok(BatchProof {
+            witness_commitments,
+            zc_alphas,
+            first_sumcheck_msgs,
+            second_sumcheck_msgs,
+            tensorcheck_proof,
+        })
+    }
+
+    /// Verify the proof for all the `instances`, with the verifier key `vk`.
+    ///
+    /// The instances must share the same matrices, and be in the order of the prover.
+    /// Their witnesses are ignored.
+    pub fn verify(
+        &self,
+        instances: &[R1cs<E::ScalarField>],
+        vk: &VerifierKey<E>,
+    ) -> VerificationResult {
+        let instances = padded_instances(instances).ok_or(VerificationError::Consistency {
+            check: "instances share the same index",
+        })?;
+        let index = &instances[0];
+        let k = instances.len();
+        if self.witness_commitments.len() != k
+            || self.zc_alphas.len() != k
+            || self.first_sumcheck_msgs.1.len() != k
+            || self.second_sumcheck_msgs.1.len() != 1
+            || self.tensorcheck_proof.base_polynomials_evaluations.len() != 1
+        {
+            return Err(VerificationError::Consistency {
+                check: "one message per instance",
+            });
+        }
+
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        let transcript = &mut transcript;
+        bind_context::<E, _>(
+            transcript,
+            BATCH_SNARK_LABEL,
+            &[index.a.len(), index.z.len(), k],
+        );
+        instances.iter().for_each(|r1cs| {
+            bind_public_input::<E::ScalarField, _, _>(transcript, &Reverse(r1cs.x.as_slice()))
+        });
+        self.witness_commitments
+            .iter()
+            .for_each(|c| transcript.append_serializable(b"witness", c));
+        let alpha = transcript.get_challenge(b"alpha");
+        self.zc_alphas
+            .iter()
+            .for_each(|zc_alpha| transcript.append_serializable(b"zc(alpha)", zc_alpha));
+
+        let subclaim_1 =
+            Subclaim::new_batch(transcript, &self.first_sumcheck_msgs, &self.zc_alphas)
+                .map_err(|e| e.within("first sumcheck"))?;
+
+        let eta = transcript.get_challenge::<E::ScalarField>(b"eta");
+        let mu = transcript.get_challenge::<E::ScalarField>(b"mu");
+        let mus = powers(mu, k);
+        let etas = powers(eta, 3);
+
+        let instance_sums = subclaim_1
+            .final_foldings
+            .iter()
+            .zip(&self.zc_alphas)
+            .map(|(final_folding, &zc_alpha)| {
+                ip(&[final_folding[0], final_folding[1], zc_alpha], &etas)
+            })
+            .collect::<Vec<_>>();
+        let asserted_sum_2 = ip(&instance_sums, &mus);
+        let subclaim_2 = Subclaim::new(transcript, &self.second_sumcheck_msgs, asserted_sum_2)
+            .map_err(|e| e.within("second sumcheck"))?;
+
+        let gamma = transcript.get_challenge::<E::ScalarField>(b"batch_challenge");
+        self.tensorcheck_proof
+            .folded_polynomials_commitments
+            .iter()
+            .for_each(|c| transcript.append_serializable(b"commitment", c));
+        let beta = transcript.get_challenge::<E::ScalarField>(b"evaluation-chal");
+        let [m_pos, m_neg] = evaluate_matrices(index, alpha, &subclaim_1.challenges, eta, beta);
+
+        // the statements and the witness commitments are combined as the witnesses.
+        let xs = instances.iter().map(|r1cs| &r1cs.x[..]).collect::<Vec<_>>();
+        let x = combine(&xs, &mus);
+        let witness_commitment = self
+            .witness_commitments
+            .iter()
+            .zip(&mus)
+            .fold(E::G1::zero(), |acc, (c, &mu_i)| acc + c.0 * mu_i);
+        let x_evaluations = evaluate_le_at(&x, &[beta, -beta]);
+        let w_evaluations = self.tensorcheck_proof.base_polynomials_evaluations[0];
+        let beta_power = beta.pow([x.len() as u64]);
+        let z_pos = x_evaluations[0] + beta_power * w_evaluations[1];
+        let beta_power = if (x.len() & 1) == 0 {
+            beta_power
+        } else {
+            -beta_power
+        };
+        let z_neg = x_evaluations[1] + beta_power * w_evaluations[2];
+
+        let check = self.tensorcheck_proof.deferred_msm_verify(
+            transcript,
+            vk,
+            &[subclaim_2.final_foldings[0].to_vec()],
+            &[Commitment(witness_commitment)],
+            &[[m_pos + gamma * z_pos, m_neg + gamma * z_neg]],
+            &[subclaim_2.challenges],
+            beta,
+            gamma,
+        )?;
+        vk.check(&check.evaluate())
+            .map_err(|e| e.within("tensorcheck opening"))
+    }
+
+    /// The number of instances proven.
+    pub fn len(&self) -> usize {
+        self.witness_commitments.len()
+    }
+
+    /// Return `true` if the proof is for no instance.
+    pub fn is_empty(&self) -> bool {
+        self.witness_commitments.is_empty()
+    }
+}
@@ -0,0 +1,95 @@
+//! Committed public inputs: proving statements about instances the verifier never sees.
+//!
+//! By default, the verifier receives the public input $\vec x$ in the clear,
+//! binds it to the transcript, and evaluates its contribution to $\vec z$ itself.
+//! Instead, the parties can agree on a [`CommittedInput`], a commitment to $\vec x$
+//! (e.g. to an instance shared by several parties, and kept private from the verifier).
+//! The commitment is bound to the transcript in place of $\vec x$,
+//! and the prover opens $\vec x$ at the evaluation points of the tensorcheck along with the witness,
+//! proving that the instance it satisfies is the committed one.
+//!
+//! The commitment is binding, but not hiding, and the proof reveals the evaluations of $\vec x$ at three points:
+//! inputs with low entropy can be recovered by the verifier.
+use ark_ec::pairing::Pairing;
+use ark_serialize::*;
+
+use crate::circuit::R1cs;
+#[cfg(feature = "prover")]
+use crate::errors::{GeminiError, GeminiResult};
+use crate::errors::{VerificationError, VerificationResult};
+use crate::kzg::{Commitment, CommitterKey, VerifierKey};
+use crate::snark::Proof;
+#[cfg(feature = "prover")]
+use crate::snark::ProofPart;
+use crate::PROTOCOL_NAME;
+
+/// A commitment to the public input $\vec x$ of an instance, standing in for $\vec x$ at verification.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CommittedInput<E: Pairing> {
+    /// The length of the public input.
+    pub len: usize,
+    /// The commitment to the public input.
+    pub commitment: Commitment<E>,
+}
+
+impl<E: Pairing> CommittedInput<E> {
+    /// Commit to the public input `x`.
+    pub fn new(ck: &CommitterKey<E>, x: &[E::ScalarField]) -> Self {
+        CommittedInput {
+            len: x.len(),
+            commitment: ck.commit(x),
+        }
+    }
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Produce a SNARK proof for `r1cs` using the time-efficient prover,
+    /// for the public input committed in `input`.
+    ///
+    /// The proof is verified with [`Proof::verify_committed_input`], without the public input.
+    /// Fails if `input` does not open to the public input `r1cs.x`,
+    /// or if [`Proof::new_time`] fails.
+    #[cfg(feature = "prover")]
+    pub fn new_time_with_committed_input(
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+        input: &CommittedInput<E>,
+    ) -> GeminiResult<Proof<E>> {
+        GeminiError::ensure_key(r1cs.x.len(), ck.powers_of_g.len())?;
+        if input.len != r1cs.x.len() || ck.commit(&r1cs.x) != input.commitment {
+            return Err(GeminiError::MalformedInstance {
+                reason: "the committed input does not open to the public input",
+            });
+        }
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        Self::prove_time(
+            r1cs,
+            ck,
+            Some(input),
+            &mut transcript,
+            &mut |_: ProofPart<E>| (),
+        )
+    }
+
+    /// Verify the SNARK proof for the matrices of `r1cs`,
+    /// and for the public input committed in `input`.
+    ///
+    /// The values of the public input `r1cs.x` are ignored (they can be, e.g., zero),
+    /// but its length must match the committed one.
+    pub fn verify_committed_input(
+        &self,
+        r1cs: &R1cs<E::ScalarField>,
+        vk: &VerifierKey<E>,
+        input: &CommittedInput<E>,
+    ) -> VerificationResult {
+        if input.len != r1cs.x.len() {
+            return Err(VerificationError::Consistency {
+                check: "committed input length",
+            });
+        }
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        let check = self.deferred_verify(r1cs, vk, Some(input), &mut transcript)?;
+        vk.check(&check.evaluate())
+            .map_err(|e| e.within("tensorcheck opening"))
+    }
+}
@@ -0,0 +1,138 @@
+//! Support for verifying proofs within a circuit over another curve.
+//!
+//! The verifier computes over two fields:
+//! the sumchecks and the tensorcheck reduce to identities over the scalar field of the curve,
+//! while the final pairing check computes multi-scalar multiplications and pairings,
+//! whose operations are over the base field of the curve.
+//! For one-layer composition, the pairing-friendly curve is chosen so that its base field is
+//! the scalar field of an outer curve:
+//! e.g., the base field of BLS12-377 is the scalar field of BW6-761,
+//! so that a circuit over BW6-761 computes the group operations of the verifier natively.
+//!
+//! [`Proof::outer_statement`] splits the verification accordingly:
+//! it performs all checks over the scalar field, and returns the [`OuterStatement`] left to check:
+//! the multi-scalar multiplications and the pairing equation of the polynomial commitment.
+//! The statement is encoded as public input of the outer circuit with [`OuterStatement::public_input`].
+//! The scalars of BLS12-377 are smaller than the modulus of the outer field, so they are encoded natively as well,
+//! e.g. for decomposing them in bits within the circuit.
+//!
+//! The checks over the scalar field are not part of the outer statement:
+//! they must be computed by whoever verifies the outer proof,
+//! or emulated in the circuit as non-native arithmetic.
+use ark_ec::pairing::Pairing;
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, Field, PrimeField, Zero};
+use ark_std::vec::Vec;
+
+use crate::circuit::R1cs;
+use crate::errors::{VerificationError, VerificationResult};
+use crate::kzg::{DeferredMsm, MsmPairingCheck, VerifierKey};
+use crate::snark::Proof;
+use crate::PROTOCOL_NAME;
+
+/// The group-side part of the verification of a [`Proof`]:
+/// the pairing equation $e(L, H) = e(\pi, Z)$, where $H$ is the generator of $\GG_2$,
+/// and $L$, $Z$ are multi-scalar multiplications (over the commitments, and the verifier key).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OuterStatement<E: Pairing> {
+    /// The pairing check, with its multi-scalar multiplications left to compute.
+    pub check: MsmPairingCheck<E>,
+    /// The generator $H$ of $\GG_2$.
+    pub g2: E::G2Affine,
+}
+
+/// Return `true` if the elements of `S` are elements of `F`,
+/// that is, if the modulus of `S` is at most the modulus of `F`.
+fn fits<S: PrimeField, F: PrimeField>() -> bool {
+    let (s, f) = (S::MODULUS.to_bytes_be(), F::MODULUS.to_bytes_be());
+    let len = usize::max(s.len(), f.len());
+    let pad = |bytes: Vec<u8>| {
+        let mut padded = vec![0u8; len - bytes.len()];
+        padded.extend(bytes);
+        padded
+    };
+    pad(s) <= pad(f)
+}
+
+/// Encode `element` as an element of `F`, assuming [`fits`].
+fn embed<S: PrimeField, F: PrimeField>(element: &S) -> F {
+    F::from_le_bytes_mod_order(&element.into_bigint().to_bytes_le())
+}
+
+/// Push the coordinates of `point` into `input`: the point at infinity is encoded as zeros.
+fn push_point<G: AffineRepr, F: PrimeField>(input: &mut Vec<F>, point: &G) {
+    match point.xy() {
+        Some((x, y)) => {
+            let coordinates = x
+                .to_base_prime_field_elements()
+                .chain(y.to_base_prime_field_elements());
+            input.extend(coordinates.map(|c| embed(&c)));
+        }
+        None => {
+            let len = 2 * G::BaseField::extension_degree() as usize;
+            input.extend((0..len).map(|_| F::zero()));
+        }
+    }
+}
+
+/// Push the bases and the scalars of `msm` into `input`, in pairs.
+fn push_msm<G: AffineRepr, F: PrimeField>(input: &mut Vec<F>, msm: &DeferredMsm<G>) {
+    for (base, scalar) in msm.bases.iter().zip(&msm.scalars) {
+        push_point(input, base);
+        input.push(embed(scalar));
+    }
+}
+
+impl<E: Pairing> OuterStatement<E> {
+    /// Check the statement natively.
+    pub fn verify(&self) -> VerificationResult {
+        let lhs = self.check.lhs.compute();
+        let zeros = self.check.zeros.compute();
+        if E::pairing(lhs, self.g2) == E::pairing(self.check.proof, zeros) {
+            Ok(())
+        } else {
+            Err(VerificationError::opening())
+        }
+    }
+
+    /// Encode the statement as elements of the field `F`, the scalar field of the outer curve:
+    /// the pairs of bases and scalars of $L$, the proof $\pi$, the pairs of $Z$, and $H$.
+    /// Points are encoded as their affine coordinates, over the prime field
+    /// (two elements for each coordinate in $\GG_2$), and the point at infinity as zeros.
+    ///
+    /// Return `None` if the coordinates or the scalars are not elements of `F`.
+    pub fn public_input<F: PrimeField>(&self) -> Option<Vec<F>> {
+        type BasePrimeField<G> = <<G as AffineRepr>::BaseField as Field>::BasePrimeField;
+        if !(fits::<E::ScalarField, F>()
+            && fits::<BasePrimeField<E::G1Affine>, F>()
+            && fits::<BasePrimeField<E::G2Affine>, F>())
+        {
+            return None;
+        }
+        let mut input = Vec::new();
+        push_msm(&mut input, &self.check.lhs);
+        push_point(&mut input, &self.check.proof);
+        push_msm(&mut input, &self.check.zeros);
+        push_point(&mut input, &self.g2);
+        Some(input)
+    }
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Perform the checks of [`Proof::verify`] over the scalar field,
+    /// and return the group-side statement left to check, e.g. within a circuit over an outer curve.
+    ///
+    /// The proof is valid if this function succeeds and the statement holds.
+    pub fn outer_statement(
+        &self,
+        r1cs: &R1cs<E::ScalarField>,
+        vk: &VerifierKey<E>,
+    ) -> Result<OuterStatement<E>, VerificationError> {
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        let check = self.deferred_verify(r1cs, vk, None, &mut transcript)?;
+        Ok(OuterStatement {
+            check,
+            g2: vk.powers_of_g2[0],
+        })
+    }
+}
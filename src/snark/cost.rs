@@ -0,0 +1,292 @@
+//! A cost model of the provers, predicting their time and memory before proving.
+//!
+//! The provers spend their time in few operations:
+//! group additions, in the multi-scalar multiplications of the commitments and of the openings;
+//! field multiplications, in the matrix products, the sumchecks, and the foldings of the tensorcheck;
+//! and, for the elastic prover, reading (and deserializing) the input streams.
+//! A [`CostModel`] holds the time of each of these operations on a given machine,
+//! as measured once by [`CostModel::calibrate`].
+//! [`CostModel::estimate`] then counts the operations of both provers over an instance of a given size,
+//! without reading the instance.
+//!
+//! The passes and the proof size are exact, and match [`Proof::dry_run_elastic`] and [`Proof::estimate_size`];
+//! the time and the memory are estimates, up to the lower-order terms of the provers.
+use ark_ec::pairing::Pairing;
+use ark_ec::AffineRepr;
+use ark_ff::{Field, PrimeField, Zero};
+use ark_serialize::{CanonicalSerialize, Compress};
+use ark_std::log2;
+use ark_std::mem::size_of;
+use ark_std::time::Duration;
+
+use crate::circuit::{padded_dim, R1cs};
+use crate::config::ProverConfig;
+use crate::kzg::pippenger::window_size;
+use crate::misc::ceil_div;
+use crate::psnark::memory::{msm_buffer, sumcheck_buffer};
+use crate::snark::dry_run::sumcheck_passes;
+use crate::snark::Proof;
+
+/// The size of an R1CS instance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IndexInfo {
+    /// The number of constraints.
+    pub num_constraints: usize,
+    /// The number of variables, i.e. the length of $\vec z$.
+    pub num_variables: usize,
+    /// The length of the statement $\vec x$.
+    pub num_public_inputs: usize,
+    /// The number of non-zero entries of each of the matrices $A, B, C$.
+    pub num_non_zero: [usize; 3],
+}
+
+impl<F: Field> From<&R1cs<F>> for IndexInfo {
+    fn from(r1cs: &R1cs<F>) -> Self {
+        let num_non_zero = [&r1cs.a, &r1cs.b, &r1cs.c].map(|m| m.iter().map(|row| row.len()).sum());
+        IndexInfo {
+            num_constraints: r1cs.a.len(),
+            num_variables: r1cs.z.len(),
+            num_public_inputs: r1cs.z.len() - r1cs.w.len(),
+            num_non_zero,
+        }
+    }
+}
+
+/// The time (in nanoseconds) of the operations of the provers on a machine.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CostModel {
+    /// The time of an addition of an affine point to a projective point of $\GG_1$, on a thread.
+    pub group_addition: f64,
+    /// The time of a multiplication in the scalar field, on a thread.
+    pub field_multiplication: f64,
+    /// The time of reading a byte of an input stream.
+    pub read_byte: f64,
+    /// The number of threads available, when the configuration does not set them.
+    pub threads: usize,
+}
+
+/// The predicted cost of a prover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ModeEstimate {
+    /// The wall-clock time of the prover.
+    pub prover_time: Duration,
+    /// The peak size (in bytes) of the instance, the key, and the buffers held in memory.
+    pub peak_memory: usize,
+    /// The number of passes over the input streams.
+    pub passes: usize,
+    /// The size (in bytes) of the proof, serialized with [`Proof::serialize_versioned`].
+    pub proof_size: usize,
+}
+
+/// The predicted cost of the time-efficient and of the elastic (space-efficient) provers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CostEstimate {
+    /// The cost of [`Proof::new_time`].
+    pub time: ModeEstimate,
+    /// The cost of [`Proof::new_elastic_with_config`].
+    pub space: ModeEstimate,
+}
+
+/// The operations of a prover.
+#[derive(Default)]
+struct Work {
+    additions: usize,
+    multiplications: usize,
+    bytes: usize,
+}
+
+impl Work {
+    /// Add the multi-scalar multiplication of `len` pairs, holding at most `max_buckets` buckets.
+    fn msm<E: Pairing>(&mut self, len: usize, max_buckets: usize) {
+        let num_bits = E::ScalarField::MODULUS_BIT_SIZE as usize;
+        let window = window_size(len, num_bits, max_buckets);
+        self.additions += ceil_div(num_bits, window) * (len + (2 << window));
+    }
+}
+
+impl CostModel {
+    /// Measure the time of the operations of the provers over the pairing `E` on this machine,
+    /// with microbenchmarks lasting few milliseconds.
+    ///
+    /// Stream reads are measured as the deserialization of field elements held in memory:
+    /// for streams read from disk, set [`CostModel::read_byte`] from the throughput of the disk.
+    #[cfg(feature = "std")]
+    pub fn calibrate<E: Pairing>() -> Self {
+        use ark_ff::One;
+        use ark_serialize::CanonicalDeserialize;
+        use ark_std::hint::black_box;
+        use ark_std::vec::Vec;
+        use ark_std::UniformRand;
+        use std::time::Instant;
+
+        const SAMPLES: usize = 1 << 12;
+        let rng = &mut ark_std::test_rng();
+
+        let points = (0..SAMPLES)
+            .map(|_| E::G1Affine::rand(rng))
+            .collect::<Vec<_>>();
+        let start = Instant::now();
+        let mut sum = E::G1::zero();
+        for point in &points {
+            sum += *point;
+        }
+        let group_addition = start.elapsed().as_nanos() as f64 / SAMPLES as f64;
+        black_box(sum);
+
+        let scalars = (0..SAMPLES)
+            .map(|_| E::ScalarField::rand(rng))
+            .collect::<Vec<_>>();
+        let start = Instant::now();
+        let product = scalars
+            .iter()
+            .fold(E::ScalarField::one(), |product, scalar| product * scalar);
+        let field_multiplication = start.elapsed().as_nanos() as f64 / SAMPLES as f64;
+        black_box(product);
+
+        let mut bytes = Vec::new();
+        scalars
+            .serialize_uncompressed(&mut bytes)
+            .expect("serialization into memory");
+        let start = Instant::now();
+        let read = Vec::<E::ScalarField>::deserialize_uncompressed_unchecked(&bytes[..])
+            .expect("deserialization from memory");
+        let read_byte = start.elapsed().as_nanos() as f64 / bytes.len() as f64;
+        black_box(read);
+
+        #[cfg(feature = "parallel")]
+        let threads = rayon::current_num_threads();
+        #[cfg(not(feature = "parallel"))]
+        let threads = 1;
+
+        CostModel {
+            group_addition,
+            field_multiplication,
+            read_byte,
+            threads,
+        }
+    }
+
+    /// Predict the cost of proving an instance of size `info` over the pairing `E`,
+    /// with the time-efficient prover and with the elastic prover configured by `config`,
+    /// and with the shortest committer key for the instance.
+    ///
+    /// Arithmetic is assumed to scale linearly with the threads of `config` (or [`CostModel::threads`]),
+    /// and stream reads not to scale.
+    pub fn estimate<E: Pairing>(&self, info: &IndexInfo, config: &ProverConfig) -> CostEstimate {
+        let field_size = E::ScalarField::zero().uncompressed_size();
+        let element_size = field_size + 8;
+        let base_size = E::G1Affine::generator().uncompressed_size();
+        let (num_constraints, num_variables) = (info.num_constraints, info.num_variables);
+        let num_non_zero = info.num_non_zero.iter().sum::<usize>();
+        let threshold = config.threshold();
+        let dim = padded_dim(num_constraints, num_variables);
+        let witness_len = dim - info.num_public_inputs;
+        let key_len = usize::max(witness_len, dim / 2);
+        let rounds = log2(dim) as usize;
+        let depth = rounds.saturating_sub(1);
+        let sumcheck_passes = sumcheck_passes(rounds, threshold);
+        let proof_size = Proof::<E>::estimate_size(num_constraints, num_variables, Compress::Yes);
+
+        // the commitments to the witness and to the foldings, and their openings.
+        let msm_lens = || {
+            let foldings = (1..depth + 1).map(move |level| ceil_div(dim, 1 << level));
+            [witness_len, witness_len.saturating_sub(3), dim / 2]
+                .into_iter()
+                .chain(foldings)
+        };
+
+        let mut time = Work::default();
+        msm_lens().for_each(|len| time.msm::<E>(len, usize::MAX));
+        // the matrix products, and the matrices tensored with the challenges;
+        // each sumcheck folds two polynomials and sums their products in each round,
+        // and the tensorcheck folds and evaluates the foldings, and evaluates and divides the witness.
+        let sumcheck = |len: usize| 8 * len;
+        time.multiplications = 2 * num_non_zero + 2 * sumcheck(dim) + 8 * dim + 7 * witness_len;
+
+        let mut space = Work::default();
+        let max_buckets = usize::max(config.max_msm_buffer, 1);
+        msm_lens().for_each(|len| space.msm::<E>(len, max_buckets));
+        // each pass of the sumchecks folds the streams from scratch,
+        // and a pass over the linear combination of the matrices multiplies each entry.
+        let lincomb = num_non_zero + dim;
+        let time_len = usize::min(dim, 1 << threshold);
+        space.multiplications = sumcheck_passes * (2 * 3 * dim + lincomb + 3 * dim)
+            + 2 * sumcheck(time_len)
+            + 5 * lincomb
+            + (depth + 3) * dim
+            + 7 * witness_len;
+
+        // the passes of the dry run, phase by phase.
+        let mut passes = 0;
+        let mut read = |elements: usize, size: usize| {
+            passes += 1;
+            space.bytes += elements * size;
+        };
+        read(num_variables, field_size);
+        read(num_variables - info.num_public_inputs, field_size);
+        read(key_len, base_size);
+        read(num_constraints, field_size);
+        for _ in 0..sumcheck_passes {
+            read(num_constraints, field_size);
+            read(num_constraints, field_size);
+        }
+        // the matrix streams have an end of line for each column.
+        let mut read_lincomb = || {
+            for non_zero in info.num_non_zero {
+                read(non_zero + num_variables, element_size);
+            }
+            read(num_variables, field_size);
+        };
+        for _ in 0..sumcheck_passes + 5 {
+            read_lincomb();
+        }
+        let threshold_level = usize::min(depth, usize::max(1, depth.saturating_sub(threshold)));
+        read(usize::min(1 << threshold, key_len), base_size);
+        for _ in 0..threshold_level {
+            read(key_len, base_size);
+        }
+        for level in 1..depth + 1 {
+            read(ceil_div(dim, 1 << level), base_size);
+        }
+        for _ in 0..4 {
+            read(num_variables - info.num_public_inputs, field_size);
+        }
+        read(witness_len.saturating_sub(3), base_size);
+
+        let time_memory = num_non_zero * (size_of::<E::ScalarField>() + size_of::<usize>())
+            + 11 * dim * size_of::<E::ScalarField>()
+            + key_len * size_of::<E::G1Affine>();
+        let tensorcheck_buffer = usize::min(1 << threshold, key_len) * size_of::<E::G1Affine>()
+            + 2 * time_len * size_of::<E::ScalarField>();
+        let space_memory = [
+            msm_buffer::<E>(usize::min(config.max_msm_buffer, dim)),
+            sumcheck_buffer::<E>(dim, threshold),
+            tensorcheck_buffer,
+        ]
+        .into_iter()
+        .max()
+        .unwrap_or(0);
+
+        let threads = config.num_threads.unwrap_or(self.threads).max(1) as f64;
+        let prover_time = |work: &Work| {
+            let arithmetic = work.additions as f64 * self.group_addition
+                + work.multiplications as f64 * self.field_multiplication;
+            let nanos = arithmetic / threads + work.bytes as f64 * self.read_byte;
+            Duration::from_nanos(nanos as u64)
+        };
+        CostEstimate {
+            time: ModeEstimate {
+                prover_time: prover_time(&time),
+                peak_memory: time_memory,
+                passes: 0,
+                proof_size,
+            },
+            space: ModeEstimate {
+                prover_time: prover_time(&space),
+                peak_memory: space_memory,
+                passes,
+                proof_size,
+            },
+        }
+    }
+}
@@ -0,0 +1,220 @@
+//! Dry runs of the elastic prover, for measuring its IO.
+//!
+//! A dry run reads the input streams as many times, and from the same positions,
+//! as [`Proof::new_elastic_with_config`], but performs no field or group operation.
+//! Comparing the time of a dry run with the time of the prover
+//! tells whether proving is IO-bound or CPU-bound on a given machine.
+use ark_ec::pairing::Pairing;
+use ark_ec::AffineRepr;
+use ark_ff::Zero;
+use ark_serialize::CanonicalSerialize;
+use ark_std::borrow::Borrow;
+use ark_std::log2;
+use ark_std::vec::Vec;
+
+use crate::circuit::{padded_dim, R1csStream};
+use crate::config::ProverConfig;
+use crate::errors::GeminiResult;
+use crate::iterable::{DoubleEndedIterable, Iterable, RandomAccessIterable};
+use crate::kzg::CommitterKeyStream;
+use crate::misc::ceil_div;
+use crate::snark::{check_key_len, Proof};
+
+/// The input read by a phase of the prover.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PhaseReport {
+    /// The name of the phase.
+    pub name: &'static str,
+    /// The number of passes over the input streams, including partial passes.
+    pub passes: usize,
+    /// The number of elements read.
+    pub elements: usize,
+    /// The size (in bytes) of the elements read, in their uncompressed serialization.
+    pub bytes: usize,
+}
+
+impl PhaseReport {
+    fn new(name: &'static str) -> Self {
+        PhaseReport {
+            name,
+            passes: 0,
+            elements: 0,
+            bytes: 0,
+        }
+    }
+
+    /// Record a pass reading `elements` elements of `element_size` bytes each.
+    fn record(&mut self, elements: usize, element_size: usize) {
+        self.passes += 1;
+        self.elements += elements;
+        self.bytes += elements * element_size;
+    }
+}
+
+/// The report of a dry run: the input read by each phase of the prover, in order.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DryRunReport {
+    /// The phases of the prover.
+    pub phases: Vec<PhaseReport>,
+}
+
+impl DryRunReport {
+    /// The total number of passes over the input streams.
+    pub fn passes(&self) -> usize {
+        self.phases.iter().map(|phase| phase.passes).sum()
+    }
+
+    /// The total size (in bytes) of the elements read.
+    pub fn bytes(&self) -> usize {
+        self.phases.iter().map(|phase| phase.bytes).sum()
+    }
+}
+
+/// Read the whole stream, and return the number of elements read.
+fn read<S: Iterable>(stream: &S) -> usize {
+    stream.iter().fold(0, |count, _| count + 1)
+}
+
+/// Read the `len` elements of `stream` starting from position `idx`,
+/// and return the number of elements read.
+fn read_from<S: RandomAccessIterable>(stream: &S, idx: usize, len: usize) -> usize {
+    stream
+        .iter_from(idx)
+        .take(len)
+        .fold(0, |count, _| count + 1)
+}
+
+/// The number of passes of the elastic sumcheck with `rounds` rounds
+/// over the streams of its polynomials.
+///
+/// The space-efficient prover performs a pass for each round,
+/// until the folded polynomials fit the budget of $2^{\text{threshold}}$ elements,
+/// and one more pass to load them in memory (or to compute the final foldings).
+pub(super) fn sumcheck_passes(rounds: usize, threshold: usize) -> usize {
+    if rounds == 0 {
+        1
+    } else if threshold == 0 {
+        rounds + 1
+    } else {
+        rounds.saturating_sub(threshold) + 2
+    }
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Perform a dry run of [`Proof::new_elastic_with_config`]
+    /// over the _streaming_ R1CS instance `r1cs` and the _streaming_ committer key `ck`,
+    /// and report the input read by each phase.
+    ///
+    /// The padding of the instance is not read, hence not reported.
+    /// Matrix elements count as a field element and a (64-bit) index.
+    ///
+    /// Fails on the same inputs as [`Proof::new_elastic_with_config`].
+    pub fn dry_run_elastic<SM, SG, SZ, SW>(
+        r1cs: R1csStream<SM, SZ, SW>,
+        ck: CommitterKeyStream<E, SG>,
+        config: &ProverConfig,
+    ) -> GeminiResult<DryRunReport>
+    where
+        SM: Iterable,
+        SZ: Iterable,
+        SW: Iterable,
+        SG: DoubleEndedIterable + RandomAccessIterable,
+        SG::Item: Borrow<E::G1Affine>,
+    {
+        r1cs.check()?;
+        let statement_len = r1cs.z.len() - r1cs.witness.len();
+        check_key_len(
+            r1cs.nonzero,
+            r1cs.z.len(),
+            statement_len,
+            ck.powers_of_g.len(),
+        )?;
+        let dry_run_time = start_timer!(|| "Dry run");
+        let field_size = CanonicalSerialize::uncompressed_size(&E::ScalarField::zero());
+        let element_size = field_size + 8;
+        let base_size = CanonicalSerialize::uncompressed_size(&E::G1Affine::generator());
+        let threshold = config.threshold();
+        let dim = padded_dim(r1cs.nonzero, r1cs.z.len());
+        let witness_len = dim - statement_len;
+        let key_len = ck.powers_of_g.len();
+        let rounds = log2(dim) as usize;
+        let sumcheck_passes = sumcheck_passes(rounds, threshold);
+        let mut phases = Vec::new();
+
+        let mut phase = PhaseReport::new("public_input");
+        phase.record(read(&r1cs.z), field_size);
+        phases.push(phase);
+
+        // the key is aligned to the witness skipping the leading bases.
+        let mut phase = PhaseReport::new("witness_commitment");
+        phase.record(read(&r1cs.witness), field_size);
+        phase.record(read(&ck.powers_of_g), base_size);
+        phases.push(phase);
+
+        let mut phase = PhaseReport::new("zc_alpha");
+        phase.record(read(&r1cs.z_c), field_size);
+        phases.push(phase);
+
+        let mut phase = PhaseReport::new("first_sumcheck");
+        for _ in 0..sumcheck_passes {
+            phase.record(read(&r1cs.z_a), field_size);
+            phase.record(read(&r1cs.z_b), field_size);
+        }
+        phases.push(phase);
+
+        // the polynomials of the second sumcheck and of the tensorcheck
+        // are linear combinations of the matrices (tensored) and of z.
+        let read_lincomb = |phase: &mut PhaseReport| {
+            phase.record(read(&r1cs.a_colmaj), element_size);
+            phase.record(read(&r1cs.b_colmaj), element_size);
+            phase.record(read(&r1cs.c_colmaj), element_size);
+            phase.record(read(&r1cs.z), field_size);
+        };
+        let mut phase = PhaseReport::new("second_sumcheck");
+        for _ in 0..sumcheck_passes {
+            read_lincomb(&mut phase);
+        }
+        phases.push(phase);
+
+        // the foldings are transcribed in memory below the threshold,
+        // committed, evaluated at two points, and opened.
+        let mut phase = PhaseReport::new("tensorcheck");
+        let depth = rounds.saturating_sub(1);
+        let threshold_level = usize::min(depth, usize::max(1, depth.saturating_sub(threshold)));
+        for _ in 0..5 {
+            read_lincomb(&mut phase);
+        }
+        let time_key_len = usize::min(1 << threshold, key_len);
+        phase.record(
+            ck.powers_of_g.rev_iter().take(time_key_len).count(),
+            base_size,
+        );
+        for _ in 0..threshold_level {
+            phase.record(read(&ck.powers_of_g), base_size);
+        }
+        for level in 1..depth + 1 {
+            let folded_len = ceil_div(dim, 1 << level);
+            phase.record(
+                read_from(&ck.powers_of_g, key_len - folded_len, folded_len),
+                base_size,
+            );
+        }
+        // the witness is evaluated at three points, and opened.
+        for _ in 0..4 {
+            phase.record(read(&r1cs.witness), field_size);
+        }
+        let quotient_len = witness_len.saturating_sub(3);
+        phase.record(
+            read_from(
+                &ck.powers_of_g,
+                (key_len + 3).saturating_sub(witness_len),
+                quotient_len,
+            ),
+            base_size,
+        );
+        phases.push(phase);
+
+        end_timer!(dry_run_time);
+        Ok(DryRunReport { phases })
+    }
+}
@@ -1,47 +1,62 @@
 //! Space-efficient algebraic prover implementation for R1CS.
 use ark_ec::pairing::Pairing;
 use ark_ff::Field;
+#[cfg(feature = "prover")]
+use ark_serialize::Write;
 use ark_std::borrow::Borrow;
 use ark_std::vec::Vec;
+#[cfg(feature = "prover")]
 use ark_std::One;
+#[cfg(feature = "prover")]
 use log::debug;
-use merlin::Transcript;
 
-use crate::circuit::R1csStream;
-use crate::iterable::Iterable;
+use crate::iterable::{DoubleEndedIterable, Iterable, RandomAccessIterable};
 use crate::kzg::CommitterKeyStream;
-use crate::misc::{evaluate_be, evaluate_le, hadamard, powers, powers2, strip_last, MatrixElement};
-use crate::snark::streams::MatrixTensor;
-use crate::snark::Proof;
-use crate::subprotocols::sumcheck::proof::Sumcheck;
+use crate::misc::{evaluate_be, evaluate_le, powers, strip_last};
 use crate::subprotocols::sumcheck::streams::FoldedPolynomialTree;
 use crate::subprotocols::tensorcheck::{evaluate_folding, partially_foldtree, TensorcheckProof};
 use crate::transcript::GeminiTranscript;
-use crate::{lincomb, PROTOCOL_NAME, SPACE_TIME_THRESHOLD};
+#[cfg(feature = "prover")]
+use crate::{
+    circuit::R1csStream,
+    config::ProverConfig,
+    errors::GeminiResult,
+    iterable::Reverse,
+    lincomb,
+    misc::{hadamard, powers2, MatrixElement},
+    snark::streams::MatrixTensor,
+    snark::{check_key_len, Proof, ProofPart, ProofSink, ProofWriter, SNARK_LABEL},
+    subprotocols::sumcheck::proof::Sumcheck,
+    transcript::{bind_context, bind_public_input},
+    PROTOCOL_NAME,
+};
 
-#[allow(dead_code)]
+/// Elastic function for producing the tensor check proof:
+/// the foldings with at most $2^{\text{threshold}}$ coefficients are committed and evaluated in memory.
 pub fn elastic_tensorcheck<F, E, SG, SB, SF1>(
-    transcript: &mut Transcript,
+    transcript: &mut impl GeminiTranscript,
     ck: CommitterKeyStream<E, SG>,
     base_polynomial: &SB,
     body_polynomials: (&SF1, &[F]),
     max_msm_buffer: usize,
-) -> TensorcheckProof<E>
+    threshold: usize,
+) -> GeminiResult<TensorcheckProof<E>>
 where
     F: Field,
     E: Pairing<ScalarField = F>,
-    SG: Iterable,
+    SG: DoubleEndedIterable + RandomAccessIterable,
     SG::Item: Borrow<E::G1Affine>,
     SB: Iterable,
     SB::Item: Borrow<E::ScalarField>,
     SF1: Iterable<Item = F>,
 {
+    trace_span!("tensorcheck", len = body_polynomials.0.len());
     let tensorcheck_challenges = strip_last(body_polynomials.1);
-    let time_ck = ck.as_committer_key(usize::min(1 << SPACE_TIME_THRESHOLD, ck.powers_of_g.len()));
+    let time_ck = ck.as_committer_key(usize::min(1 << threshold, ck.powers_of_g.len()));
     let (tensorcheck_sfoldings, tensorcheck_tfoldings) =
-        partially_foldtree(body_polynomials.0, tensorcheck_challenges);
+        partially_foldtree(body_polynomials.0, tensorcheck_challenges, threshold);
     let mut folded_polynomials_commitments =
-        ck.commit_folding(&tensorcheck_sfoldings, max_msm_buffer);
+        ck.commit_folding(&tensorcheck_sfoldings, max_msm_buffer)?;
     folded_polynomials_commitments.extend(time_ck.batch_commit(&tensorcheck_tfoldings));
 
     // add commitments to transcript
@@ -83,139 +98,268 @@ where
     let open_space_chals = &open_chals[1..];
     let tensorcheck_foldings =
         FoldedPolynomialTree::new(body_polynomials.0, tensorcheck_challenges);
-    let (_, proof_w) = ck.open_multi_points(base_polynomial, &eval_points, max_msm_buffer);
+    let (_, proof_w) = ck.open_multi_points(base_polynomial, &eval_points, max_msm_buffer)?;
     let (_, proof) = ck.open_folding(
         tensorcheck_foldings,
         &eval_points,
         open_space_chals,
         max_msm_buffer,
-    );
+    )?;
     // let time_proof = time_ck.batch_open_multi_points(tensorcheck_tfoldings, &eval_points, open_time_chals);
     let evaluation_proof = proof_w + proof;
     end_timer!(tensorcheck_open_time);
-    TensorcheckProof {
+    Ok(TensorcheckProof {
         folded_polynomials_commitments,
         folded_polynomials_evaluations,
         evaluation_proof,
         base_polynomials_evaluations: vec![evaluations_w],
-    }
-}
-
-/// Streaming function for producing the tensor check proof.
-pub fn tensorcheck<F, E, SG, SB, SF1>(
-    transcript: &mut Transcript,
-    ck: CommitterKeyStream<E, SG>,
-    base_polynomial: &SB,
-    body_polynomials: (&SF1, &[F]),
-    max_msm_buffer: usize,
-) -> TensorcheckProof<E>
-where
-    F: Field,
-    E: Pairing<ScalarField = F>,
-    SG: Iterable,
-    SG::Item: Borrow<E::G1Affine>,
-    SB: Iterable,
-    SB::Item: Borrow<E::ScalarField>,
-    SF1: Iterable<Item = F>,
-{
-    let tensorcheck_challenges = strip_last(body_polynomials.1);
-    let tensorcheck_foldings =
-        FoldedPolynomialTree::new(body_polynomials.0, tensorcheck_challenges);
-    let folded_polynomials_commitments = ck.commit_folding(&tensorcheck_foldings, max_msm_buffer);
-
-    // add commitments to transcript
-    folded_polynomials_commitments
-        .iter()
-        .for_each(|c| transcript.append_serializable(b"commitment", c));
-    let eval_chal = transcript.get_challenge::<E::ScalarField>(b"evaluation-chal");
-    let eval_points = [eval_chal.square(), eval_chal, -eval_chal];
-
-    let folded_polynomials_evaluations = evaluate_folding(&tensorcheck_foldings, eval_points[1])
-        .into_iter()
-        .zip(evaluate_folding(&tensorcheck_foldings, eval_points[2]))
-        .map(|(x, y)| [x, y])
-        .collect::<Vec<_>>();
-    let evaluations_w = [
-        evaluate_be(base_polynomial.iter(), &eval_points[0]),
-        evaluate_be(base_polynomial.iter(), &eval_points[1]),
-        evaluate_be(base_polynomial.iter(), &eval_points[2]),
-    ];
-    evaluations_w
-        .iter()
-        .for_each(|e| transcript.append_serializable(b"eval", &e));
-    folded_polynomials_evaluations
-        .iter()
-        .flatten()
-        .for_each(|e| transcript.append_serializable(b"eval", e));
-    let open_chal = transcript.get_challenge(b"open-chal");
-    let open_chal_len = body_polynomials.1.len() + 1;
-    let open_chals = powers(open_chal, open_chal_len);
-
-    let (_, proof_w) = ck.open_multi_points(base_polynomial, &eval_points, max_msm_buffer);
-    let (_, proof) = ck.open_folding(
-        tensorcheck_foldings,
-        &eval_points,
-        &open_chals[1..],
-        max_msm_buffer,
-    );
-    let evaluation_proof = proof_w + proof;
-    TensorcheckProof {
-        folded_polynomials_commitments,
-        folded_polynomials_evaluations,
-        evaluation_proof,
-        base_polynomials_evaluations: vec![evaluations_w],
-    }
+    })
 }
 
+#[cfg(feature = "prover")]
 impl<E: Pairing> Proof<E> {
     /// Given as input the _streaming_ R1CS instance `r1cs`
     /// and the _streaming_ committer key `ck`,
     /// return a new SNARK using the elastic prover.
+    ///
+    /// The column-major streams must have `r1cs.z.len()` lines.
+    /// The instance is padded on the fly (see [`R1csStream::padded`]).
+    ///
+    /// Fails if the streams $A \vec z, B \vec z, C \vec z$ do not have `r1cs.nonzero` elements,
+    /// if the witness is longer than $\vec z$,
+    /// or if the committer key is too short for the instance.
+    /// Only the declared lengths of the streams are checked beforehand:
+    /// use [`R1csStream::check_streams`] to validate their contents before a long run.
     pub fn new_elastic<SM, SG, SZ, SW>(
         r1cs: R1csStream<SM, SZ, SW>,
         ck: CommitterKeyStream<E, SG>,
         max_msm_buffer: usize,
-    ) -> Proof<E>
+    ) -> GeminiResult<Proof<E>>
+    where
+        E: Pairing,
+        SM: Iterable + Copy,
+        SZ: Iterable + Copy,
+        SW: Iterable,
+        SG: DoubleEndedIterable + RandomAccessIterable,
+        SM::Item: Borrow<MatrixElement<E::ScalarField>>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SW::Item: Borrow<E::ScalarField>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SG::Item: Borrow<E::G1Affine>,
+    {
+        let config = ProverConfig {
+            max_msm_buffer,
+            ..ProverConfig::default()
+        };
+        Self::new_elastic_with_config(r1cs, ck, &config)
+    }
+
+    /// Same as [`Proof::new_elastic`],
+    /// but with a memory budget of `memory_budget` field elements.
+    ///
+    /// Every subprotocol starts with the streaming prover,
+    /// and switches to the in-memory prover as soon as its folded state fits the budget.
+    /// The proof does not depend on the budget.
+    pub fn new_elastic_with_budget<SM, SG, SZ, SW>(
+        r1cs: R1csStream<SM, SZ, SW>,
+        ck: CommitterKeyStream<E, SG>,
+        max_msm_buffer: usize,
+        memory_budget: usize,
+    ) -> GeminiResult<Proof<E>>
+    where
+        E: Pairing,
+        SM: Iterable + Copy,
+        SZ: Iterable + Copy,
+        SW: Iterable,
+        SG: DoubleEndedIterable + RandomAccessIterable,
+        SM::Item: Borrow<MatrixElement<E::ScalarField>>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SW::Item: Borrow<E::ScalarField>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SG::Item: Borrow<E::G1Affine>,
+    {
+        let config = ProverConfig {
+            max_msm_buffer,
+            memory_budget,
+            ..ProverConfig::default()
+        };
+        Self::new_elastic_with_config(r1cs, ck, &config)
+    }
+
+    /// Same as [`Proof::new_elastic`],
+    /// with the buffer sizes, the memory budget, and the threads of `config`.
+    pub fn new_elastic_with_config<SM, SG, SZ, SW>(
+        r1cs: R1csStream<SM, SZ, SW>,
+        ck: CommitterKeyStream<E, SG>,
+        config: &ProverConfig,
+    ) -> GeminiResult<Proof<E>>
+    where
+        E: Pairing,
+        SM: Iterable + Copy,
+        SZ: Iterable + Copy,
+        SW: Iterable,
+        SG: DoubleEndedIterable + RandomAccessIterable,
+        SM::Item: Borrow<MatrixElement<E::ScalarField>>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SW::Item: Borrow<E::ScalarField>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SG::Item: Borrow<E::G1Affine>,
+    {
+        Self::new_elastic_emitting(r1cs, ck, config, &mut |_: ProofPart<E>| ())
+    }
+
+    /// Same as [`Proof::new_elastic_with_config`],
+    /// but emitting each message of the proof into `sink` as soon as it is final
+    /// (see [`ProofSink`]).
+    pub fn new_elastic_emitting<SM, SG, SZ, SW, S>(
+        r1cs: R1csStream<SM, SZ, SW>,
+        ck: CommitterKeyStream<E, SG>,
+        config: &ProverConfig,
+        sink: &mut S,
+    ) -> GeminiResult<Proof<E>>
     where
         E: Pairing,
         SM: Iterable + Copy,
         SZ: Iterable + Copy,
         SW: Iterable,
-        SG: Iterable,
+        SG: DoubleEndedIterable + RandomAccessIterable,
         SM::Item: Borrow<MatrixElement<E::ScalarField>>,
         SZ::Item: Borrow<E::ScalarField>,
         SW::Item: Borrow<E::ScalarField>,
         SZ::Item: Borrow<E::ScalarField>,
         SZ::Item: Borrow<E::ScalarField>,
         SG::Item: Borrow<E::G1Affine>,
+        S: ProofSink<E> + Send,
     {
+        let mut parts = Vec::new();
+        {
+            let collect = &mut |part: ProofPart<E>| {
+                sink.emit(part.clone());
+                parts.push(part);
+            };
+            config.install(move || Self::prove_elastic(r1cs, ck, config, collect))?;
+        }
+        Ok(Proof::from_parts(parts).expect("the prover emits all the messages in order"))
+    }
+
+    /// Same as [`Proof::new_elastic_with_config`],
+    /// but serializing each message of the proof into `writer` as soon as it is final,
+    /// and flushing it right after.
+    ///
+    /// The complete proof is never held in memory,
+    /// and the messages written survive a failure of the prover (e.g., on disk).
+    /// The bytes written are the proof serialized with [`Proof::serialize_versioned`].
+    /// Errors of the prover are returned right away,
+    /// while errors of the writer are returned by [`ProofWriter::finish`].
+    pub fn new_elastic_to_writer<SM, SG, SZ, SW, W>(
+        r1cs: R1csStream<SM, SZ, SW>,
+        ck: CommitterKeyStream<E, SG>,
+        config: &ProverConfig,
+        writer: W,
+    ) -> GeminiResult<ProofWriter<W>>
+    where
+        E: Pairing,
+        SM: Iterable + Copy,
+        SZ: Iterable + Copy,
+        SW: Iterable,
+        SG: DoubleEndedIterable + RandomAccessIterable,
+        SM::Item: Borrow<MatrixElement<E::ScalarField>>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SW::Item: Borrow<E::ScalarField>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SG::Item: Borrow<E::G1Affine>,
+        W: Write + Send,
+    {
+        let mut proof_writer = ProofWriter::new(writer);
+        {
+            let sink = &mut proof_writer;
+            config.install(move || Self::prove_elastic(r1cs, ck, config, sink))?;
+        }
+        Ok(proof_writer)
+    }
+
+    /// The elastic prover of [`Proof::new_elastic_emitting`], on the current threads.
+    ///
+    /// The messages are moved into `sink`, and not kept after they are emitted.
+    fn prove_elastic<SM, SG, SZ, SW>(
+        r1cs: R1csStream<SM, SZ, SW>,
+        ck: CommitterKeyStream<E, SG>,
+        config: &ProverConfig,
+        sink: &mut impl ProofSink<E>,
+    ) -> GeminiResult<()>
+    where
+        E: Pairing,
+        SM: Iterable + Copy,
+        SZ: Iterable + Copy,
+        SW: Iterable,
+        SG: DoubleEndedIterable + RandomAccessIterable,
+        SM::Item: Borrow<MatrixElement<E::ScalarField>>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SW::Item: Borrow<E::ScalarField>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SZ::Item: Borrow<E::ScalarField>,
+        SG::Item: Borrow<E::G1Affine>,
+    {
+        r1cs.check()?;
+        check_key_len(
+            r1cs.nonzero,
+            r1cs.z.len(),
+            r1cs.z.len() - r1cs.witness.len(),
+            ck.powers_of_g.len(),
+        )?;
         let snark_time = start_timer!(|| module_path!());
+        trace_span!(
+            "snark_elastic_prover",
+            num_constraints = r1cs.nonzero,
+            num_variables = r1cs.z.len(),
+        );
+        let max_msm_buffer = config.max_msm_buffer;
+        let threshold = config.threshold();
+        // pad the instance to square matrices of power-of-two dimension.
+        let padded = r1cs.padded::<E::ScalarField>(r1cs.z.len());
+        let r1cs = padded;
 
         debug!(
             "features:{};space-time-threshold:{};tensor-expansion:{};msm-buffer:{}",
             crate::misc::_features_enabled(),
-            crate::SPACE_TIME_THRESHOLD,
+            threshold,
             crate::misc::TENSOR_EXPANSION_LOG,
             max_msm_buffer,
         );
 
         let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        // bind the context and the public input to the transcript.
+        bind_context::<E, _>(&mut transcript, SNARK_LABEL, &[r1cs.nonzero, r1cs.z.len()]);
+        let public_input = r1cs.public_input::<E::ScalarField>();
+        bind_public_input::<E::ScalarField, _, _>(
+            &mut transcript,
+            &Reverse(public_input.as_slice()),
+        );
         // send the vector w
         let witness_commitment_time = start_timer!(|| "Commitment to w");
-        let witness_commitment = ck.commit(&r1cs.witness);
+        let witness_commitment = ck.commit_chunked(&r1cs.witness, config.chunk_size)?;
         end_timer!(witness_commitment_time);
 
         // send witness, receive challenge.
         transcript.append_serializable(b"witness", &witness_commitment);
+        sink.emit(ProofPart::WitnessCommitment(witness_commitment));
         let alpha = transcript.get_challenge(b"alpha");
 
         // send evaluation of zc(alpha)
         let zc_alpha = evaluate_be(r1cs.z_c.iter(), &alpha);
         transcript.append_serializable(b"zc(alpha)", &zc_alpha);
+        sink.emit(ProofPart::ZcAlpha(zc_alpha));
 
         // run the sumcheck for z_a and z_b with twist alpha
         let first_sumcheck_time = start_timer!(|| "First sumcheck");
-        let first_proof = Sumcheck::new_elastic(&mut transcript, r1cs.z_a, r1cs.z_b, alpha);
+        let first_proof =
+            Sumcheck::new_elastic_with_config(&mut transcript, r1cs.z_a, r1cs.z_b, alpha, config)?;
+        let first_sumcheck_msgs = first_proof.prover_messages();
+        sink.emit(ProofPart::FirstSumcheck(first_sumcheck_msgs));
         end_timer!(first_sumcheck_time);
 
         // after sumcheck, generate a new challenge
@@ -233,8 +377,15 @@ impl<E: Pairing> Proof<E> {
         let lhs = lincomb!((a_alpha, b_alpha, c_alpha), &sumcheck_batch_challenges);
 
         let second_sumcheck_time = start_timer!(|| "Second sumcheck");
-        let second_proof =
-            Sumcheck::new_elastic(&mut transcript, lhs, r1cs.z, E::ScalarField::one());
+        let second_proof = Sumcheck::new_elastic_with_config(
+            &mut transcript,
+            lhs,
+            r1cs.z,
+            E::ScalarField::one(),
+            config,
+        )?;
+        let second_sumcheck_msgs = second_proof.prover_messages();
+        sink.emit(ProofPart::SecondSumcheck(second_sumcheck_msgs));
         end_timer!(second_sumcheck_time);
 
         let batch_challenge = transcript.get_challenge::<E::ScalarField>(b"batch_challenge");
@@ -242,22 +393,18 @@ impl<E: Pairing> Proof<E> {
         let tensorcheck_time = start_timer!(|| "Tensorcheck");
         let tensorcheck_batch_challenges = powers(batch_challenge, 2);
         let tensorcheck_polynomials = lincomb!((lhs, r1cs.z), &tensorcheck_batch_challenges);
-        let tensorcheck_proof = tensorcheck(
+        let tensorcheck_proof = elastic_tensorcheck(
             &mut transcript,
             ck,
             &r1cs.witness,
             (&tensorcheck_polynomials, &second_proof.challenges),
             max_msm_buffer,
-        );
+            threshold,
+        )?;
+        sink.emit(ProofPart::Tensorcheck(tensorcheck_proof));
         end_timer!(tensorcheck_time);
 
         end_timer!(snark_time);
-        Proof {
-            witness_commitment,
-            zc_alpha,
-            first_sumcheck_msgs: first_proof.prover_messages(),
-            second_sumcheck_msgs: second_proof.prover_messages(),
-            tensorcheck_proof,
-        }
+        Ok(())
     }
 }
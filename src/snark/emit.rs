@@ -0,0 +1,146 @@
+//! Proofs emitted message by message, as soon as each message is final.
+//!
+//! The provers send their messages in the order of the protocol:
+//! the commitment to the witness, the evaluation of $C \vec z$, the messages of the two sumchecks,
+//! and the tensorcheck proof.
+//! A [`ProofSink`] receives each [`ProofPart`] as soon as it is final,
+//! while the prover is still computing the next ones:
+//! over proofs lasting minutes, the transmission of the proof overlaps with the proving.
+//! Any closure over the parts is a sink (e.g. sending them over a channel);
+//! a [`ProofWriter`] serializes them as they come,
+//! and [`Proof::new_elastic_to_writer`] proves into a writer without ever holding the complete proof.
+//!
+//! The parts serialized in order, after [`PROOF_FORMAT_VERSION`],
+//! are the proof serialized with [`Proof::serialize_versioned`].
+use ark_ec::pairing::Pairing;
+use ark_serialize::{CanonicalSerialize, Compress, SerializationError, Write};
+
+use crate::kzg::Commitment;
+use crate::snark::Proof;
+use crate::subprotocols::sumcheck::prover::ProverMsgs;
+use crate::subprotocols::tensorcheck::TensorcheckProof;
+use crate::PROOF_FORMAT_VERSION;
+
+/// A message of the SNARK proof.
+#[derive(Clone, PartialEq, Eq)]
+pub enum ProofPart<E: Pairing> {
+    /// The commitment to the witness.
+    WitnessCommitment(Commitment<E>),
+    /// The evaluation of $C \vec z$ at the challenge $\alpha$.
+    ZcAlpha(E::ScalarField),
+    /// The messages of the first sumcheck.
+    FirstSumcheck(ProverMsgs<E::ScalarField>),
+    /// The messages of the second sumcheck.
+    SecondSumcheck(ProverMsgs<E::ScalarField>),
+    /// The tensorcheck proof.
+    Tensorcheck(TensorcheckProof<E>),
+}
+
+impl<E: Pairing> CanonicalSerialize for ProofPart<E> {
+    fn serialize_with_mode<W: Write>(
+        &self,
+        writer: W,
+        compress: Compress,
+    ) -> Result<(), SerializationError> {
+        match self {
+            ProofPart::WitnessCommitment(commitment) => {
+                commitment.serialize_with_mode(writer, compress)
+            }
+            ProofPart::ZcAlpha(zc_alpha) => zc_alpha.serialize_with_mode(writer, compress),
+            ProofPart::FirstSumcheck(msgs) | ProofPart::SecondSumcheck(msgs) => {
+                msgs.serialize_with_mode(writer, compress)
+            }
+            ProofPart::Tensorcheck(proof) => proof.serialize_with_mode(writer, compress),
+        }
+    }
+
+    fn serialized_size(&self, compress: Compress) -> usize {
+        match self {
+            ProofPart::WitnessCommitment(commitment) => commitment.serialized_size(compress),
+            ProofPart::ZcAlpha(zc_alpha) => zc_alpha.serialized_size(compress),
+            ProofPart::FirstSumcheck(msgs) | ProofPart::SecondSumcheck(msgs) => {
+                msgs.serialized_size(compress)
+            }
+            ProofPart::Tensorcheck(proof) => proof.serialized_size(compress),
+        }
+    }
+}
+
+/// A receiver of the messages of a proof, in the order of the protocol.
+pub trait ProofSink<E: Pairing> {
+    /// Receive the next message of the proof.
+    fn emit(&mut self, part: ProofPart<E>);
+}
+
+impl<E: Pairing, F: FnMut(ProofPart<E>)> ProofSink<E> for F {
+    fn emit(&mut self, part: ProofPart<E>) {
+        self(part)
+    }
+}
+
+/// A sink serializing each message (with compressed points) into a writer,
+/// after [`PROOF_FORMAT_VERSION`], and flushing the writer after each message.
+///
+/// The first error stops the writes, and is returned by [`ProofWriter::finish`].
+pub struct ProofWriter<W: Write> {
+    writer: W,
+    result: Result<(), SerializationError>,
+}
+
+impl<W: Write> ProofWriter<W> {
+    /// Start writing a proof into `writer`.
+    pub fn new(mut writer: W) -> Self {
+        let result = PROOF_FORMAT_VERSION.serialize_compressed(&mut writer);
+        Self { writer, result }
+    }
+
+    /// Return the writer, or the first error raised while writing.
+    pub fn finish(self) -> Result<W, SerializationError> {
+        self.result.map(|_| self.writer)
+    }
+}
+
+impl<E: Pairing, W: Write> ProofSink<E> for ProofWriter<W> {
+    fn emit(&mut self, part: ProofPart<E>) {
+        if self.result.is_ok() {
+            self.result = part
+                .serialize_compressed(&mut self.writer)
+                .and_then(|_| self.writer.flush().map_err(SerializationError::IoError));
+        }
+    }
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Reassemble a proof from its messages, in the order of the protocol.
+    ///
+    /// Return `None` if any message is missing, out of order, or in excess.
+    pub fn from_parts<I>(parts: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = ProofPart<E>>,
+    {
+        let mut parts = parts.into_iter();
+        let proof = match (
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+            parts.next(),
+        ) {
+            (
+                Some(ProofPart::WitnessCommitment(witness_commitment)),
+                Some(ProofPart::ZcAlpha(zc_alpha)),
+                Some(ProofPart::FirstSumcheck(first_sumcheck_msgs)),
+                Some(ProofPart::SecondSumcheck(second_sumcheck_msgs)),
+                Some(ProofPart::Tensorcheck(tensorcheck_proof)),
+            ) => Proof {
+                witness_commitment,
+                zc_alpha,
+                first_sumcheck_msgs,
+                second_sumcheck_msgs,
+                tensorcheck_proof,
+            },
+            _ => return None,
+        };
+        parts.next().is_none().then_some(proof)
+    }
+}
@@ -0,0 +1,111 @@
+//! Proofs forged by a malicious prover, for testing verifiers.
+//!
+//! An honest proof only tests the verifier on its happy path.
+//! [`Proof::new_time_with_fault`] proves honestly, and then corrupts exactly one message of the proof,
+//! as a cheating prover would: each [`Fault`] targets the checks of a different subprotocol,
+//! and a sound verifier (e.g. a downstream integration of [`Proof::verify`]) must reject every one of them.
+use ark_ec::pairing::Pairing;
+use ark_ec::AffineRepr;
+use ark_ff::One;
+
+use crate::circuit::R1cs;
+use crate::errors::GeminiResult;
+use crate::kzg::CommitterKey;
+use crate::snark::Proof;
+
+/// A fault injected into a single message of a proof.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// A wrong commitment to the witness.
+    WitnessCommitment,
+    /// A wrong evaluation of $C \vec z$.
+    ZcAlpha,
+    /// A wrong round message in the first sumcheck.
+    FirstSumcheckMessage,
+    /// A wrong round message in the second sumcheck.
+    SecondSumcheckMessage,
+    /// A wrong final folding of the second sumcheck.
+    SumcheckFolding,
+    /// A wrong commitment to a folded polynomial of the tensorcheck.
+    TensorcheckFolding,
+    /// A wrong evaluation of a folded polynomial of the tensorcheck.
+    FoldedEvaluation,
+    /// A wrong evaluation of a base polynomial of the tensorcheck.
+    BaseEvaluation,
+    /// A wrong evaluation proof for the openings of the tensorcheck.
+    Opening,
+}
+
+impl Fault {
+    /// All faults, one per class of forgery.
+    pub const ALL: [Fault; 9] = [
+        Fault::WitnessCommitment,
+        Fault::ZcAlpha,
+        Fault::FirstSumcheckMessage,
+        Fault::SecondSumcheckMessage,
+        Fault::SumcheckFolding,
+        Fault::TensorcheckFolding,
+        Fault::FoldedEvaluation,
+        Fault::BaseEvaluation,
+        Fault::Opening,
+    ];
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Same as [`Proof::new_time`], but corrupt the proof with `fault`.
+    pub fn new_time_with_fault(
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+        fault: Fault,
+    ) -> GeminiResult<Self> {
+        let mut proof = Proof::new_time(r1cs, ck)?;
+        proof.inject(fault);
+        Ok(proof)
+    }
+
+    /// Corrupt the message of the proof targeted by `fault`.
+    ///
+    /// Field elements are shifted by one, and group elements by the generator.
+    /// Faults on messages absent from the proof (e.g., the foldings of a tensorcheck over constant polynomials)
+    /// leave it unchanged.
+    pub fn inject(&mut self, fault: Fault) {
+        let one = E::ScalarField::one();
+        let generator = E::G1Affine::generator();
+        let tensorcheck = &mut self.tensorcheck_proof;
+        match fault {
+            Fault::WitnessCommitment => self.witness_commitment.0 += generator,
+            Fault::ZcAlpha => self.zc_alpha += one,
+            Fault::FirstSumcheckMessage => {
+                if let Some(message) = self.first_sumcheck_msgs.0.first_mut() {
+                    message.0 += one;
+                }
+            }
+            Fault::SecondSumcheckMessage => {
+                if let Some(message) = self.second_sumcheck_msgs.0.last_mut() {
+                    message.1 += one;
+                }
+            }
+            Fault::SumcheckFolding => {
+                if let Some(folding) = self.second_sumcheck_msgs.1.first_mut() {
+                    folding[0] += one;
+                }
+            }
+            Fault::TensorcheckFolding => {
+                if let Some(commitment) = tensorcheck.folded_polynomials_commitments.first_mut() {
+                    commitment.0 += generator;
+                }
+            }
+            Fault::FoldedEvaluation => {
+                if let Some(evaluations) = tensorcheck.folded_polynomials_evaluations.last_mut() {
+                    evaluations[1] += one;
+                }
+            }
+            Fault::BaseEvaluation => {
+                if let Some(evaluations) = tensorcheck.base_polynomials_evaluations.first_mut() {
+                    evaluations[2] += one;
+                }
+            }
+            Fault::Opening => tensorcheck.evaluation_proof.0 += generator,
+        }
+    }
+}
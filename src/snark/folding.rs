@@ -0,0 +1,263 @@
+//! Accumulation of R1CS instances, in the style of Nova.
+//!
+//! A _relaxed_ R1CS instance over the matrices $(A, B, C)$
+//! is satisfied by $\vec z = (\vec x, \vec w)$ and an error vector $\vec e$ if:
+//!
+//! $$
+//! A \vec z \circ B \vec z = u \cdot C \vec z + \vec e.
+//! $$
+//!
+//! Every R1CS instance is a relaxed instance with $u = 1$ and $\vec e = \vec 0$.
+//! Two relaxed instances over the same matrices are folded into one with a random linear combination:
+//! the prover sends only a commitment to the cross term
+//!
+//! $$
+//! \vec t = A \vec z_1 \circ B \vec z_2 + A \vec z_2 \circ B \vec z_1 - u_1 C \vec z_2 - u_2 C \vec z_1,
+//! $$
+//!
+//! and the verifier folds the commitments to the witnesses and to the errors.
+//! Each folding step costs linear time in the size of the instance and one commitment.
+//!
+//! Once all instances are folded, a single SNARK proof shows that the accumulator is satisfied:
+//! the accumulated relaxed instance is proven as the R1CS instance with matrices
+//! $(A \,|\, 0,\ B \,|\, 0,\ u C \,|\, I)$ and witness $(\vec w, \vec e)$,
+//! linked to the accumulated commitments as [`WitnessSegment`]s.
+//! Therefore, the error vector is committed at offset $|\vec w|$.
+//!
+//! The folding challenges are bound to the matrices, hence instances cannot be folded
+//! over matrices other than those they are proven over.
+//!
+//! # Security
+//!
+//! The commitment scheme does not enforce degree bounds (see [`WitnessSegment`]),
+//! and the commitment to the cross term is not checked to be supported on the positions
+//! of the error vector: a cheating prover may commit to a cross term
+//! with mass on the positions of the witness.
+//! The accumulated instance then remains satisfiable,
+//! but its witness may no longer open to the accumulated commitment to the witness.
+//! Hence, the final proof shows only that _some_ witness satisfies the folded instances,
+//! and not that it is the witness committed in [`RelaxedInstance::witness_commitment`].
+use ark_ec::pairing::Pairing;
+use ark_ff::{Field, One, Zero};
+use ark_serialize::*;
+use ark_std::vec::Vec;
+
+use crate::circuit::R1cs;
+#[cfg(feature = "prover")]
+use crate::errors::GeminiResult;
+use crate::errors::VerificationResult;
+use crate::kzg::{Commitment, CommitterKey, VerifierKey};
+use crate::misc::product_matrix_vector;
+use crate::snark::{Proof, WitnessSegment};
+use crate::transcript::GeminiTranscript;
+
+/// The domain separator for the folding challenges.
+const FOLDING_PROTOCOL_NAME: &[u8] = b"GEMINI-v0-folding";
+
+/// A relaxed R1CS instance, as seen by the verifier.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct RelaxedInstance<E: Pairing> {
+    /// The public input.
+    pub x: Vec<E::ScalarField>,
+    /// The relaxation scalar.
+    pub u: E::ScalarField,
+    /// The commitment to the witness.
+    pub witness_commitment: Commitment<E>,
+    /// The commitment to the error vector, at offset the witness length.
+    pub error_commitment: Commitment<E>,
+}
+
+/// The message sent by the prover for folding two instances.
+#[derive(Clone, Debug, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct FoldingProof<E: Pairing> {
+    cross_term_commitment: Commitment<E>,
+}
+
+/// A relaxed R1CS instance, together with its witness and error vector.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Accumulator<E: Pairing> {
+    /// The accumulated instance.
+    pub instance: RelaxedInstance<E>,
+    w: Vec<E::ScalarField>,
+    e: Vec<E::ScalarField>,
+}
+
+/// Sample the folding challenge for `fst`, `snd`, and the cross term `proof`,
+/// over the matrices of `r1cs`.
+///
+/// The matrices are absorbed at each folding step, in time linear in their size.
+fn folding_challenge<E: Pairing>(
+    r1cs: &R1cs<E::ScalarField>,
+    fst: &RelaxedInstance<E>,
+    snd: &RelaxedInstance<E>,
+    proof: &FoldingProof<E>,
+) -> E::ScalarField {
+    let mut transcript = merlin::Transcript::new(FOLDING_PROTOCOL_NAME);
+    transcript.append_serializable(b"matrix-a", &r1cs.a);
+    transcript.append_serializable(b"matrix-b", &r1cs.b);
+    transcript.append_serializable(b"matrix-c", &r1cs.c);
+    transcript.append_serializable(b"instance", fst);
+    transcript.append_serializable(b"instance", snd);
+    transcript.append_serializable(b"cross-term", &proof.cross_term_commitment);
+    transcript.get_challenge(b"folding-challenge")
+}
+
+/// Return $\vec u + r \vec v$.
+fn fold_vectors<F: Field>(u: &[F], v: &[F], r: F) -> Vec<F> {
+    u.iter().zip(v).map(|(&u_i, &v_i)| u_i + r * v_i).collect()
+}
+
+impl<E: Pairing> RelaxedInstance<E> {
+    /// Fold the instance `other` into `self`, over the matrices of `r1cs`,
+    /// using the prover message `proof`.
+    ///
+    /// The commitment to the cross term in `proof` is not checked
+    /// to be supported on the positions of the error vector (see the [module](self) documentation).
+    pub fn fold(&self, r1cs: &R1cs<E::ScalarField>, other: &Self, proof: &FoldingProof<E>) -> Self {
+        let r = folding_challenge(r1cs, self, other, proof);
+        let cross_term = proof.cross_term_commitment.0;
+        RelaxedInstance {
+            x: fold_vectors(&self.x, &other.x, r),
+            u: self.u + r * other.u,
+            witness_commitment: Commitment(
+                self.witness_commitment.0 + other.witness_commitment.0 * r,
+            ),
+            error_commitment: Commitment(
+                self.error_commitment.0 + (cross_term + other.error_commitment.0 * r) * r,
+            ),
+        }
+    }
+
+    /// Verify the SNARK proof `proof` produced by [`Accumulator::prove`] for this instance.
+    ///
+    /// Only the dimensions of `r1cs` are read: the public input is taken from `self.x`.
+    pub fn verify(
+        &self,
+        r1cs: &R1cs<E::ScalarField>,
+        vk: &VerifierKey<E>,
+        proof: &Proof<E>,
+    ) -> VerificationResult {
+        let witness_len = r1cs.z.len() - r1cs.x.len();
+        let w = vec![E::ScalarField::zero(); witness_len];
+        let e = vec![E::ScalarField::zero(); r1cs.a.len()];
+        let embedded = embed(r1cs, self.u, &self.x, &w, &e);
+        proof.verify_linked(&embedded, vk, &self.segments(witness_len, e.len()))
+    }
+
+    /// The witness segments of the embedded R1CS instance.
+    fn segments(&self, witness_len: usize, error_len: usize) -> [WitnessSegment<E>; 2] {
+        [
+            WitnessSegment {
+                offset: 0,
+                len: witness_len,
+                commitment: self.witness_commitment,
+            },
+            WitnessSegment {
+                offset: witness_len,
+                len: error_len,
+                commitment: self.error_commitment,
+            },
+        ]
+    }
+}
+
+/// Embed the relaxed instance over the matrices of `r1cs` into the R1CS instance
+/// with matrices $(A \,|\, 0,\ B \,|\, 0,\ u C \,|\, I)$ and witness $(\vec w, \vec e)$.
+fn embed<F: Field>(r1cs: &R1cs<F>, u: F, x: &[F], w: &[F], e: &[F]) -> R1cs<F> {
+    let z_len = x.len() + w.len();
+    let c = r1cs
+        .c
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            let mut row = row.iter().map(|&(v, col)| (u * v, col)).collect::<Vec<_>>();
+            row.push((F::one(), z_len + i));
+            row
+        })
+        .collect();
+    R1cs {
+        a: r1cs.a.clone(),
+        b: r1cs.b.clone(),
+        c,
+        z: x.iter().chain(w).chain(e).cloned().collect(),
+        w: w.iter().chain(e).cloned().collect(),
+        x: x.to_vec(),
+    }
+}
+
+impl<E: Pairing> Accumulator<E> {
+    /// Create a new accumulator from the satisfied R1CS instance `r1cs`.
+    pub fn new(r1cs: &R1cs<E::ScalarField>, ck: &CommitterKey<E>) -> Self {
+        let instance = RelaxedInstance {
+            x: r1cs.x.clone(),
+            u: E::ScalarField::one(),
+            witness_commitment: ck.commit(&r1cs.w),
+            error_commitment: Commitment(E::G1::zero()),
+        };
+        Accumulator {
+            instance,
+            w: r1cs.w.clone(),
+            e: vec![E::ScalarField::zero(); r1cs.a.len()],
+        }
+    }
+
+    fn z(&self) -> Vec<E::ScalarField> {
+        self.instance.x.iter().chain(&self.w).cloned().collect()
+    }
+
+    /// Fold `other` into `self`, where both accumulators are over the matrices of `r1cs`.
+    ///
+    /// Return the folded accumulator and the message for the verifier,
+    /// who folds the instances with [`RelaxedInstance::fold`].
+    pub fn fold(
+        &self,
+        r1cs: &R1cs<E::ScalarField>,
+        other: &Self,
+        ck: &CommitterKey<E>,
+    ) -> (Self, FoldingProof<E>) {
+        let fold_time = start_timer!(|| "Folding");
+        trace_span!("folding", num_constraints = r1cs.a.len());
+        let (z_1, z_2) = (self.z(), other.z());
+        let (u_1, u_2) = (self.instance.u, other.instance.u);
+        let az_1 = product_matrix_vector(&r1cs.a, &z_1);
+        let bz_1 = product_matrix_vector(&r1cs.b, &z_1);
+        let cz_1 = product_matrix_vector(&r1cs.c, &z_1);
+        let az_2 = product_matrix_vector(&r1cs.a, &z_2);
+        let bz_2 = product_matrix_vector(&r1cs.b, &z_2);
+        let cz_2 = product_matrix_vector(&r1cs.c, &z_2);
+        let cross_term = (0..r1cs.a.len())
+            .map(|i| az_1[i] * bz_2[i] + az_2[i] * bz_1[i] - u_1 * cz_2[i] - u_2 * cz_1[i])
+            .collect::<Vec<_>>();
+        let proof = FoldingProof {
+            cross_term_commitment: ck.commit_at(self.w.len(), &cross_term),
+        };
+
+        let r = folding_challenge(r1cs, &self.instance, &other.instance, &proof);
+        let e = self
+            .e
+            .iter()
+            .zip(&cross_term)
+            .zip(&other.e)
+            .map(|((&e_1, &t), &e_2)| e_1 + r * (t + r * e_2))
+            .collect();
+        let accumulator = Accumulator {
+            instance: self.instance.fold(r1cs, &other.instance, &proof),
+            w: fold_vectors(&self.w, &other.w, r),
+            e,
+        };
+        end_timer!(fold_time);
+        (accumulator, proof)
+    }
+
+    /// Produce a SNARK proof that the accumulated instance over the matrices of `r1cs` is satisfied.
+    #[cfg(feature = "prover")]
+    pub fn prove(
+        &self,
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+    ) -> GeminiResult<Proof<E>> {
+        let embedded = embed(r1cs, self.instance.u, &self.instance.x, &self.w, &self.e);
+        let segments = self.instance.segments(self.w.len(), self.e.len());
+        Proof::new_time_linked(&embedded, ck, &segments)
+    }
+}
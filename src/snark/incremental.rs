@@ -0,0 +1,118 @@
+//! Incremental proving, for instances that change little from one proof to the next.
+//!
+//! The challenges of the protocol depend on the whole witness,
+//! hence the messages following the first challenge must be recomputed after any change.
+//! However, the products $A \vec z, B \vec z, C \vec z$ and the commitment to the witness, computed before,
+//! are linear in the witness:
+//! [`IncrementalProver`] caches them, and updates them in time proportional to the number of modified entries
+//! (and to the non-zero entries in their columns), instead of recomputing them from scratch.
+use ark_ec::pairing::Pairing;
+use ark_ff::Zero;
+use ark_std::vec::Vec;
+
+use crate::circuit::R1cs;
+use crate::errors::{GeminiError, GeminiResult};
+use crate::kzg::{Commitment, CommitterKey};
+use crate::misc::product_matrix_vector;
+use crate::snark::{check_key_len, Proof, ProofPart};
+use crate::PROTOCOL_NAME;
+
+/// A time-efficient prover, caching the products of the matrices with $\vec z$ and the witness commitment.
+pub struct IncrementalProver<'a, E: Pairing> {
+    /// The padded instance.
+    r1cs: R1cs<E::ScalarField>,
+    /// The length of the witness, before padding.
+    witness_len: usize,
+    ck: &'a CommitterKey<E>,
+    /// The non-zero entries `(matrix, row, value)` of each column of $A, B, C$.
+    columns: Vec<Vec<(usize, usize, E::ScalarField)>>,
+    /// The products $A \vec z, B \vec z, C \vec z$.
+    products: [Vec<E::ScalarField>; 3],
+    witness_commitment: Commitment<E>,
+}
+
+impl<'a, E: Pairing> IncrementalProver<'a, E> {
+    /// Create a new prover for the instance `r1cs`.
+    ///
+    /// Fails if the instance is malformed (see [`R1cs::check`]),
+    /// or if the committer key is too short for the instance.
+    pub fn new(r1cs: &R1cs<E::ScalarField>, ck: &'a CommitterKey<E>) -> GeminiResult<Self> {
+        r1cs.check()?;
+        check_key_len(
+            r1cs.a.len(),
+            r1cs.z.len(),
+            r1cs.x.len(),
+            ck.powers_of_g.len(),
+        )?;
+        let witness_len = r1cs.w.len();
+        let r1cs = r1cs.padded().into_owned();
+        let matrices = [&r1cs.a, &r1cs.b, &r1cs.c];
+        let mut columns = vec![Vec::new(); r1cs.z.len()];
+        for (m, matrix) in matrices.iter().enumerate() {
+            for (row, elements) in matrix.iter().enumerate() {
+                for &(val, col) in elements {
+                    columns[col].push((m, row, val));
+                }
+            }
+        }
+        let products = matrices.map(|matrix| product_matrix_vector(matrix, &r1cs.z));
+        let witness_commitment = ck.commit(&r1cs.w);
+        Ok(IncrementalProver {
+            r1cs,
+            witness_len,
+            ck,
+            columns,
+            products,
+            witness_commitment,
+        })
+    }
+
+    /// The current witness.
+    pub fn witness(&self) -> &[E::ScalarField] {
+        &self.r1cs.w[..self.witness_len]
+    }
+
+    /// Set the entries of the witness listed in `diff`, as pairs `(index, value)`.
+    ///
+    /// Fails, leaving the witness unchanged, if any index is outside of the witness.
+    pub fn update(&mut self, diff: &[(usize, E::ScalarField)]) -> GeminiResult<()> {
+        if diff.iter().any(|&(i, _)| i >= self.witness_len) {
+            return Err(GeminiError::MalformedInstance {
+                reason: "index outside of the witness",
+            });
+        }
+        let mut changes = Vec::with_capacity(diff.len());
+        for &(i, value) in diff {
+            let delta = value - self.r1cs.w[i];
+            if delta.is_zero() {
+                continue;
+            }
+            let col = self.r1cs.x.len() + i;
+            self.r1cs.w[i] = value;
+            self.r1cs.z[col] = value;
+            for &(m, row, val) in &self.columns[col] {
+                self.products[m][row] += val * delta;
+            }
+            changes.push((i, delta));
+        }
+        self.witness_commitment = self.witness_commitment.update(&changes, self.ck);
+        Ok(())
+    }
+
+    /// Produce a SNARK proof for the current witness.
+    ///
+    /// The proof is the same as [`Proof::new_time`] on the updated instance.
+    pub fn prove(&self) -> GeminiResult<Proof<E>> {
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        let [z_a, z_b, z_c] = &self.products;
+        Proof::new_time_committed(
+            &self.r1cs,
+            self.ck,
+            &mut transcript,
+            [z_a.as_slice(), z_b.as_slice(), z_c.as_slice()],
+            self.witness_commitment,
+            None,
+            &mut |_: ProofPart<E>| (),
+        )
+    }
+}
@@ -0,0 +1,142 @@
+//! JSON export of the SNARK proof.
+use ark_ec::pairing::Pairing;
+use ark_serialize::SerializationError;
+use ark_std::string::String;
+
+use crate::json::{array, array_from, point, point_from, scalar, scalar_from, scalars_from, Json};
+use crate::kzg::{Commitment, EvaluationProof};
+use crate::snark::Proof;
+use crate::subprotocols::sumcheck::prover::{ProverMsgs, RoundMsg};
+use crate::subprotocols::tensorcheck::TensorcheckProof;
+
+fn sumcheck_to_json<E: Pairing>(msgs: &ProverMsgs<E::ScalarField>) -> Json {
+    Json::object(vec![
+        (
+            "rounds",
+            array(&msgs.0, |msg| {
+                Json::Array(vec![scalar(&msg.0), scalar(&msg.1)])
+            }),
+        ),
+        (
+            "final_foldings",
+            array(&msgs.1, |foldings| array(&foldings[..], scalar)),
+        ),
+    ])
+}
+
+fn sumcheck_from_json<E: Pairing>(
+    value: &Json,
+) -> Result<ProverMsgs<E::ScalarField>, SerializationError> {
+    let rounds = array_from(value.get("rounds")?, |msg| {
+        let [a, b] = scalars_from::<E::ScalarField, 2>(msg)?;
+        Ok(RoundMsg(a, b))
+    })?;
+    let final_foldings = array_from(value.get("final_foldings")?, scalars_from)?;
+    Ok(ProverMsgs(rounds, final_foldings))
+}
+
+fn tensorcheck_to_json<E: Pairing>(proof: &TensorcheckProof<E>) -> Json {
+    Json::object(vec![
+        (
+            "folded_polynomials_commitments",
+            array(&proof.folded_polynomials_commitments, |c| point(&c.0)),
+        ),
+        (
+            "folded_polynomials_evaluations",
+            array(&proof.folded_polynomials_evaluations, |e| {
+                array(&e[..], scalar)
+            }),
+        ),
+        ("evaluation_proof", point(&proof.evaluation_proof.0)),
+        (
+            "base_polynomials_evaluations",
+            array(&proof.base_polynomials_evaluations, |e| {
+                array(&e[..], scalar)
+            }),
+        ),
+    ])
+}
+
+fn tensorcheck_from_json<E: Pairing>(
+    value: &Json,
+) -> Result<TensorcheckProof<E>, SerializationError> {
+    Ok(TensorcheckProof {
+        folded_polynomials_commitments: array_from(
+            value.get("folded_polynomials_commitments")?,
+            |c| point_from(c).map(Commitment),
+        )?,
+        folded_polynomials_evaluations: array_from(
+            value.get("folded_polynomials_evaluations")?,
+            scalars_from,
+        )?,
+        evaluation_proof: EvaluationProof(point_from(value.get("evaluation_proof")?)?),
+        base_polynomials_evaluations: array_from(
+            value.get("base_polynomials_evaluations")?,
+            scalars_from,
+        )?,
+    })
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Export the proof as a JSON object with the fields:
+    ///
+    /// ```json
+    /// {
+    ///   "protocol": "gemini",
+    ///   "version": 1,
+    ///   "type": "snark_proof",
+    ///   "witness_commitment": "0x…",
+    ///   "zc_alpha": "…",
+    ///   "first_sumcheck": {
+    ///     "rounds": [["…", "…"], …],
+    ///     "final_foldings": [["…", "…"], …]
+    ///   },
+    ///   "second_sumcheck": { … },
+    ///   "tensorcheck": {
+    ///     "folded_polynomials_commitments": ["0x…", …],
+    ///     "folded_polynomials_evaluations": [["…", "…"], …],
+    ///     "evaluation_proof": "0x…",
+    ///     "base_polynomials_evaluations": [["…", "…", "…"], …]
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// where `version` is [`PROOF_FORMAT_VERSION`](crate::PROOF_FORMAT_VERSION),
+    /// points are hex strings of their compressed serialization,
+    /// and field elements are decimal strings.
+    pub fn to_json(&self) -> String {
+        let mut fields = Json::header("snark_proof");
+        fields.push(("witness_commitment", point(&self.witness_commitment.0)));
+        fields.push(("zc_alpha", scalar(&self.zc_alpha)));
+        fields.push((
+            "first_sumcheck",
+            sumcheck_to_json::<E>(&self.first_sumcheck_msgs),
+        ));
+        fields.push((
+            "second_sumcheck",
+            sumcheck_to_json::<E>(&self.second_sumcheck_msgs),
+        ));
+        fields.push(("tensorcheck", tensorcheck_to_json(&self.tensorcheck_proof)));
+        Json::object(fields).to_pretty_string()
+    }
+
+    /// Import a proof exported with [`Proof::to_json`].
+    ///
+    /// Fails on the same inputs as [`Proof::deserialize_versioned`],
+    /// and if any field element is not in its canonical representation.
+    pub fn from_json(json: &str) -> Result<Self, SerializationError> {
+        let value = Json::parse(json)?;
+        value.check_header("snark_proof")?;
+        let proof = Proof {
+            witness_commitment: Commitment(point_from(value.get("witness_commitment")?)?),
+            zc_alpha: scalar_from(value.get("zc_alpha")?)?,
+            first_sumcheck_msgs: sumcheck_from_json::<E>(value.get("first_sumcheck")?)?,
+            second_sumcheck_msgs: sumcheck_from_json::<E>(value.get("second_sumcheck")?)?,
+            tensorcheck_proof: tensorcheck_from_json(value.get("tensorcheck")?)?,
+        };
+        if !proof.tensorcheck_proof.is_well_formed() {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(proof)
+    }
+}
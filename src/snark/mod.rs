@@ -54,25 +54,108 @@
 //! Both claims can be checked via the tensorcheck protocol.
 //! The evaluations of the base polynomials are generated internally by the verifier, using the R1CS matrices
 //! and the statement provided as input.
+//!
+//! # Composition
+//!
+//! The verification splits into checks over the scalar field and a final pairing check:
+//! see [`Proof::outer_statement`] for verifying proofs over BLS12-377 within a circuit over BW6-761.
 
-mod elastic_prover;
+#[cfg(feature = "snark-trait")]
+mod arkworks;
+mod batch;
+mod committed_input;
+mod composition;
+#[cfg(feature = "prover")]
+mod cost;
+#[cfg(feature = "prover")]
+mod dry_run;
+pub(crate) mod elastic_prover;
+mod emit;
+#[cfg(feature = "test-utils")]
+mod faults;
+mod folding;
+#[cfg(feature = "prover")]
+mod incremental;
+mod json;
+#[cfg(feature = "prover")]
+mod mode;
+mod segments;
+mod size;
+#[cfg(feature = "solidity-experimental")]
+pub mod solidity;
+#[cfg(feature = "prover")]
 mod time_prover;
 mod verifier;
 
+#[cfg(feature = "snark-trait")]
+pub use arkworks::{GeminiSnark, ProvingKey, VerifyingKey};
+pub use batch::BatchProof;
+pub use committed_input::CommittedInput;
+pub use composition::OuterStatement;
+#[cfg(feature = "prover")]
+pub use cost::{CostEstimate, CostModel, IndexInfo, ModeEstimate};
+#[cfg(feature = "prover")]
+pub use dry_run::{DryRunReport, PhaseReport};
+pub use emit::{ProofPart, ProofSink, ProofWriter};
+#[cfg(feature = "test-utils")]
+pub use faults::Fault;
+pub use folding::{Accumulator, FoldingProof, RelaxedInstance};
+#[cfg(feature = "prover")]
+pub use incremental::IncrementalProver;
+#[cfg(feature = "prover")]
+pub use mode::ProverMode;
+pub use segments::WitnessSegment;
+pub use size::SizeBreakdown;
+
 /// Utilities for producing streams in SNARK protocol.
+#[cfg(feature = "prover")]
 mod streams;
-#[cfg(test)]
+#[cfg(all(test, feature = "prover"))]
 mod tests;
 
 use ark_ec::pairing::Pairing;
+use ark_ff::Zero;
 use ark_serialize::*;
+use ark_std::log2;
 
+use crate::circuit::padded_dim;
+#[cfg(feature = "prover")]
+use crate::errors::{GeminiError, GeminiResult};
 use crate::kzg::Commitment;
 use crate::subprotocols::sumcheck::prover::ProverMsgs;
 use crate::subprotocols::tensorcheck::TensorcheckProof;
+use crate::PROOF_FORMAT_VERSION;
+
+/// The identifier of the non-preprocessing SNARK, bound to the transcript.
+pub(crate) const SNARK_LABEL: &[u8] = b"snark";
+
+/// Fail unless a committer key of `available` powers can prove an instance
+/// with `num_constraints` constraints, `num_variables` variables, and a statement of `statement_len` elements.
+///
+/// The largest polynomials committed are the padded witness, and the first folding of the tensorcheck.
+#[cfg(feature = "prover")]
+pub(crate) fn check_key_len(
+    num_constraints: usize,
+    num_variables: usize,
+    statement_len: usize,
+    available: usize,
+) -> GeminiResult<()> {
+    let dim = padded_dim(num_constraints, num_variables);
+    GeminiError::ensure_key(usize::max(dim - statement_len, dim / 2), available)
+}
 
 /// The SNARK proof, composed of all prover's messages sent throughout the protocol.
-#[derive(CanonicalSerialize, PartialEq, Eq)]
+///
+/// The provers use no randomness: the proof is a deterministic function of the instance,
+/// the committer key, and the transcript.
+/// Together with [`CommitterKey::from_seed`](crate::kzg::CommitterKey::from_seed),
+/// proofs are reproducible bit-for-bit.
+///
+/// Hence proofs cannot be rerandomized: the commitments are not hiding,
+/// and every message after the first is bound by the Fiat-Shamir challenges to the previous ones,
+/// so that a different proof for the same statement requires the witness.
+/// Relayed proofs remain linkable to their prover.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, PartialEq, Eq)]
 pub struct Proof<E: Pairing> {
     witness_commitment: Commitment<E>,
     zc_alpha: E::ScalarField,
@@ -86,3 +169,56 @@ impl<E: Pairing> ark_std::fmt::Debug for Proof<E> {
         f.debug_struct("Proof").finish()
     }
 }
+
+impl<E: Pairing> Proof<E> {
+    /// Serialize the proof, with compressed points, prefixed by [`PROOF_FORMAT_VERSION`].
+    pub fn serialize_versioned<W: Write>(&self, mut writer: W) -> Result<(), SerializationError> {
+        PROOF_FORMAT_VERSION.serialize_compressed(&mut writer)?;
+        self.serialize_compressed(writer)
+    }
+
+    /// Deserialize a proof serialized with [`Proof::serialize_versioned`].
+    ///
+    /// Fails if the format version does not match [`PROOF_FORMAT_VERSION`],
+    /// if any point is not in the prime-order subgroup,
+    /// or if the proof is malformed.
+    pub fn deserialize_versioned<R: Read>(mut reader: R) -> Result<Self, SerializationError> {
+        let version = u8::deserialize_compressed(&mut reader)?;
+        if version != PROOF_FORMAT_VERSION {
+            return Err(SerializationError::InvalidData);
+        }
+        let proof = Self::deserialize_compressed(reader)?;
+        if !(proof.tensorcheck_proof.is_well_formed()) {
+            return Err(SerializationError::InvalidData);
+        }
+        Ok(proof)
+    }
+
+    /// The size (in bytes) of the proof serialized with [`Proof::serialize_versioned`].
+    pub fn serialized_size(&self) -> usize {
+        PROOF_FORMAT_VERSION.compressed_size() + self.compressed_size()
+    }
+
+    /// Return the size (in bytes) of the proof for any instance
+    /// with `num_constraints` constraints and `num_variables` variables (the length of $\vec z$),
+    /// serialized with the version byte and in compression mode `compress`,
+    /// without running the prover.
+    ///
+    /// With [`Compress::Yes`], this is the size of [`Proof::serialize_versioned`].
+    pub fn estimate_size(
+        num_constraints: usize,
+        num_variables: usize,
+        compress: Compress,
+    ) -> usize {
+        let rounds = log2(padded_dim(num_constraints, num_variables)) as usize;
+        let proof = Proof::<E> {
+            witness_commitment: Commitment(E::G1::zero()),
+            zc_alpha: E::ScalarField::zero(),
+            first_sumcheck_msgs: ProverMsgs::zeroed(rounds, 1),
+            second_sumcheck_msgs: ProverMsgs::zeroed(rounds, 1),
+            tensorcheck_proof: TensorcheckProof::zeroed(rounds.saturating_sub(1), 1),
+        };
+        PROOF_FORMAT_VERSION.compressed_size()
+            + CanonicalSerialize::serialized_size(&proof, compress)
+    }
+}
@@ -0,0 +1,99 @@
+//! A single entry point for the provers, choosing between them from the size of the instance.
+//!
+//! The time-efficient prover holds the instance, the key, and its state in memory;
+//! the elastic prover holds buffers bounded by its [`ProverConfig`], at the cost of passes over its input streams.
+//! [`ProverMode::select`] picks the fastest prover whose memory fits [`ProverConfig::memory_cap`],
+//! as predicted by the [cost model](CostModel::estimate), and [`Proof::prove`] runs it.
+use ark_ec::pairing::Pairing;
+
+use crate::circuit::{R1cs, R1csStreams};
+use crate::config::ProverConfig;
+use crate::errors::GeminiResult;
+use crate::kzg::{CommitterKey, CommitterKeyStream};
+use crate::snark::{CostModel, IndexInfo, Proof};
+
+/// The prover to run.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProverMode {
+    /// The time-efficient prover, [`Proof::new_time_with_config`].
+    Time,
+    /// The elastic prover, [`Proof::new_elastic_with_config`], with the memory budget of the configuration.
+    Elastic,
+    /// The elastic prover with no memory budget, i.e. space-efficient in every subprotocol.
+    Space,
+}
+
+impl ProverMode {
+    /// Return the mode proving an instance of size `info` over the pairing `E` within `config.memory_cap`:
+    /// the time-efficient prover if its memory fits the cap (or there is no cap),
+    /// else the elastic prover if its buffers fit the cap,
+    /// else the space-efficient prover.
+    pub fn select<E: Pairing>(info: &IndexInfo, config: &ProverConfig) -> Self {
+        let cap = match config.memory_cap {
+            Some(cap) => cap,
+            None => return ProverMode::Time,
+        };
+        // only the memory is needed, which does not depend on the time of the operations.
+        let model = CostModel {
+            group_addition: 0.,
+            field_multiplication: 0.,
+            read_byte: 0.,
+            threads: 1,
+        };
+        let estimate = model.estimate::<E>(info, config);
+        if estimate.time.peak_memory <= cap {
+            ProverMode::Time
+        } else if estimate.space.peak_memory <= cap {
+            ProverMode::Elastic
+        } else {
+            ProverMode::Space
+        }
+    }
+
+    /// The configuration of the prover of this mode,
+    /// i.e. `config` with no memory budget for [`ProverMode::Space`].
+    pub fn config(&self, config: &ProverConfig) -> ProverConfig {
+        match self {
+            ProverMode::Space => ProverConfig {
+                memory_budget: 0,
+                ..*config
+            },
+            _ => *config,
+        }
+    }
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Prove the instance `r1cs` with the prover selected by [`ProverMode::select`] for `config`.
+    ///
+    /// All modes produce the same proof.
+    /// The elastic modes stream the instance from memory:
+    /// to stream it from elsewhere (e.g. from disk), select the mode with [`ProverMode::select`]
+    /// and call [`Proof::new_elastic_with_config`] with its [`ProverMode::config`].
+    pub fn prove(
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+        config: &ProverConfig,
+    ) -> GeminiResult<Proof<E>> {
+        let mode = ProverMode::select::<E>(&IndexInfo::from(r1cs), config);
+        Self::prove_with_mode(r1cs, ck, config, mode)
+    }
+
+    /// Same as [`Proof::prove`], with the prover of `mode`.
+    pub fn prove_with_mode(
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+        config: &ProverConfig,
+        mode: ProverMode,
+    ) -> GeminiResult<Proof<E>> {
+        let config = mode.config(config);
+        match mode {
+            ProverMode::Time => Self::new_time_with_config(r1cs, ck, &config),
+            ProverMode::Elastic | ProverMode::Space => {
+                let r1cs_streams = R1csStreams::new(r1cs);
+                let ck_stream = CommitterKeyStream::from(ck);
+                Self::new_elastic_with_config(r1cs_streams.stream(), ck_stream, &config)
+            }
+        }
+    }
+}
@@ -0,0 +1,119 @@
+//! Commit-and-prove: linking the witness to external commitments.
+//!
+//! A third party can commit to segments of the witness $\vec w$
+//! (e.g., a committed database) using the committer key of the proof system:
+//! the segment $\vec s$ starting at position $k$ is committed as the polynomial $X^k s(X)$.
+//! Hence, the commitment to the witness sent by the prover
+//! is the sum of the commitments to its segments,
+//! and the verifier can link the proof to the external commitments
+//! without ever seeing the segments, nor re-committing to them.
+//!
+//! The segments must cover the *whole* witness,
+//! and the positions not covered by any segment are asserted to be zero.
+//! Since the commitment scheme does not enforce degree bounds,
+//! a commitment to the rest of the witness chosen by the prover
+//! could alter the committed segments.
+use ark_ec::pairing::Pairing;
+use ark_std::vec::Vec;
+use ark_std::Zero;
+
+use crate::circuit::{padded_dim, R1cs};
+#[cfg(feature = "prover")]
+use crate::errors::{GeminiError, GeminiResult};
+use crate::errors::{VerificationError, VerificationResult};
+use crate::kzg::{Commitment, CommitterKey, VerifierKey};
+#[cfg(feature = "prover")]
+use crate::snark::check_key_len;
+use crate::snark::Proof;
+
+/// A commitment to the segment of the witness of length `len` starting at position `offset`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WitnessSegment<E: Pairing> {
+    /// The position of the first element of the segment within the witness.
+    pub offset: usize,
+    /// The number of elements in the segment.
+    pub len: usize,
+    /// The commitment to the segment, shifted by `offset`.
+    pub commitment: Commitment<E>,
+}
+
+impl<E: Pairing> WitnessSegment<E> {
+    /// Commit to the segment `segment`, placed at position `offset` within the witness.
+    pub fn new(ck: &CommitterKey<E>, segment: &[E::ScalarField], offset: usize) -> Self {
+        WitnessSegment {
+            offset,
+            len: segment.len(),
+            commitment: ck.commit_at(offset, segment),
+        }
+    }
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Produce a SNARK proof for `r1cs` using the time-efficient prover,
+    /// linked to the commitments to the witness segments `segments`.
+    ///
+    /// Fails if the segments do not open to the witness `r1cs.w`,
+    /// or if [`Proof::new_time`] fails.
+    #[cfg(feature = "prover")]
+    pub fn new_time_linked(
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+        segments: &[WitnessSegment<E>],
+    ) -> GeminiResult<Proof<E>> {
+        check_key_len(
+            r1cs.a.len(),
+            r1cs.z.len(),
+            r1cs.x.len(),
+            ck.powers_of_g.len(),
+        )?;
+        for segment in segments {
+            let opening = r1cs
+                .w
+                .get(segment.offset..segment.offset + segment.len)
+                .ok_or(GeminiError::MalformedInstance {
+                    reason: "the segment is outside of the witness",
+                })?;
+            if ck.commit_at(segment.offset, opening) != segment.commitment {
+                return Err(GeminiError::MalformedInstance {
+                    reason: "the segment does not open to the witness",
+                });
+            }
+        }
+        Proof::new_time(r1cs, ck)
+    }
+
+    /// Verify the SNARK proof for `r1cs`,
+    /// and that the witness is the one committed in `segments`.
+    ///
+    /// The segments must not overlap, and the witness positions not covered by any segment must be zero.
+    pub fn verify_linked(
+        &self,
+        r1cs: &R1cs<E::ScalarField>,
+        vk: &VerifierKey<E>,
+        segments: &[WitnessSegment<E>],
+    ) -> VerificationResult {
+        let witness_len = padded_dim(r1cs.a.len(), r1cs.z.len()) - r1cs.x.len();
+        let mut ranges = segments
+            .iter()
+            .map(|segment| (segment.offset, segment.offset + segment.len))
+            .collect::<Vec<_>>();
+        ranges.sort_unstable();
+        let disjoint = ranges.windows(2).all(|w| w[0].1 <= w[1].0);
+        let in_bounds = ranges.last().map_or(true, |&(_, end)| end <= witness_len);
+        if !disjoint || !in_bounds {
+            return Err(VerificationError::Consistency {
+                check: "segments are disjoint and within the witness",
+            });
+        }
+
+        let linked_commitment = segments
+            .iter()
+            .fold(E::G1::zero(), |acc, segment| acc + segment.commitment.0);
+        if linked_commitment != self.witness_commitment.0 {
+            return Err(VerificationError::Consistency {
+                check: "segments sum to the witness commitment",
+            });
+        }
+        self.verify(r1cs, vk)
+    }
+}
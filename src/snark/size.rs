@@ -0,0 +1,83 @@
+//! The size of each component of a proof.
+//!
+//! Proofs posted on chain pay for each byte of calldata.
+//! A [`SizeBreakdown`] splits the size of a serialized proof among its components,
+//! hence shows which components a change of parameters (e.g., batching more polynomials) shrinks.
+use ark_ec::pairing::Pairing;
+use ark_ff::Field;
+use ark_serialize::CanonicalSerialize;
+
+use crate::snark::Proof;
+use crate::subprotocols::sumcheck::ProverMsgs;
+use crate::subprotocols::tensorcheck::TensorcheckProof;
+use crate::PROOF_FORMAT_VERSION;
+
+/// The size (in bytes) of each component of a proof serialized with its version byte, in compressed form.
+///
+/// The sizes include the length prefixes of the vectors, and add up to the size of the serialized proof.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SizeBreakdown {
+    /// The version byte.
+    pub version: usize,
+    /// The commitments sent before the tensorcheck (e.g., to the witness).
+    pub commitments: usize,
+    /// The round messages of the sumchecks.
+    pub sumcheck_messages: usize,
+    /// The final foldings of the sumchecks.
+    pub sumcheck_finals: usize,
+    /// The commitments to the folded polynomials of the tensorcheck.
+    pub folded_commitments: usize,
+    /// The evaluations sent by the prover: the claimed sums and products,
+    /// and the evaluations of the base and folded polynomials of the tensorcheck.
+    pub evaluations: usize,
+    /// The evaluation proofs of the openings.
+    pub openings: usize,
+}
+
+impl SizeBreakdown {
+    /// The size of the serialized proof.
+    pub fn total(&self) -> usize {
+        self.version
+            + self.commitments
+            + self.sumcheck_messages
+            + self.sumcheck_finals
+            + self.folded_commitments
+            + self.evaluations
+            + self.openings
+    }
+
+    /// The breakdown of the version byte alone.
+    pub(crate) fn versioned() -> Self {
+        SizeBreakdown {
+            version: PROOF_FORMAT_VERSION.compressed_size(),
+            ..SizeBreakdown::default()
+        }
+    }
+
+    /// Add the messages of the sumcheck `msgs`.
+    pub(crate) fn add_sumcheck<F: Field>(&mut self, msgs: &ProverMsgs<F>) {
+        self.sumcheck_messages += msgs.0.compressed_size();
+        self.sumcheck_finals += msgs.1.compressed_size();
+    }
+
+    /// Add the messages of the tensorcheck `proof`.
+    pub(crate) fn add_tensorcheck<E: Pairing>(&mut self, proof: &TensorcheckProof<E>) {
+        self.folded_commitments += proof.folded_polynomials_commitments.compressed_size();
+        self.evaluations += proof.folded_polynomials_evaluations.compressed_size()
+            + proof.base_polynomials_evaluations.compressed_size();
+        self.openings += proof.evaluation_proof.compressed_size();
+    }
+}
+
+impl<E: Pairing> Proof<E> {
+    /// Return the size of each component of the proof serialized with [`Proof::serialize_versioned`].
+    pub fn size_breakdown(&self) -> SizeBreakdown {
+        let mut breakdown = SizeBreakdown::versioned();
+        breakdown.commitments += self.witness_commitment.compressed_size();
+        breakdown.evaluations += self.zc_alpha.compressed_size();
+        breakdown.add_sumcheck(&self.first_sumcheck_msgs);
+        breakdown.add_sumcheck(&self.second_sumcheck_msgs);
+        breakdown.add_tensorcheck(&self.tensorcheck_proof);
+        breakdown
+    }
+}
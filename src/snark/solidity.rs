@@ -0,0 +1,362 @@
+//! Solidity verifier for SNARK proofs over BLS12-381.
+//!
+//! [`verifier_contract`] generates a contract verifying the proofs of a fixed R1CS instance
+//! on chains providing the BLS12-381 precompiles of [EIP-2537](https://eips.ethereum.org/EIPS/eip-2537).
+//! The contract replays [`Proof::verify_with_transcript`]:
+//! the challenges are derived as by a [`Sha256Transcript`] (through the SHA-256 precompile),
+//! hence proofs must be produced with [`Proof::new_time_with_transcript`] on [`transcript()`].
+//!
+//! The matrices are embedded in the contract, 40 bytes for each non-zero entry,
+//! and the verifier is linear in their size:
+//! only small instances fit within the contract size limit of 24KiB.
+//!
+//! The contract exposes `verifyProof(bytes proof, uint256[] x) returns (bool)`,
+//! where `proof` is encoded with [`proof_bytes`] and `x` is the public input;
+//! [`calldata`] encodes a whole call.
+//!
+//! # Experimental
+//!
+//! This module is compiled only with the `solidity-experimental` feature.
+//! Only the transcript and the encodings of the contract are tested, against the Rust verifier:
+//! the contract itself has never been compiled by `solc` nor executed on an EVM,
+//! and must not be deployed before it is.
+use ark_bls12_381::{Bls12_381, Fr};
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_serialize::CanonicalSerialize;
+use ark_std::string::{String, ToString};
+use ark_std::vec::Vec;
+
+use crate::circuit::{padded_dim, Matrix, R1cs};
+use crate::errors::{GeminiError, GeminiResult};
+use crate::json::push_hex;
+use crate::kzg::VerifierKey;
+use crate::snark::{Proof, SNARK_LABEL};
+use crate::subprotocols::sumcheck::prover::{ProverMsgs, RoundMsg};
+use crate::transcript::{bind_context, GeminiTranscript, Sha256Transcript};
+use crate::PROTOCOL_NAME;
+
+/// The template of the contract.
+const TEMPLATE: &str = include_str!("verifier.sol");
+
+/// The selector of `verifyProof(bytes,uint256[])`.
+pub const VERIFY_PROOF_SELECTOR: [u8; 4] = [0x1e, 0x8e, 0x1e, 0x13];
+
+/// The transcript from which the contract derives the challenges.
+pub fn transcript() -> Sha256Transcript {
+    Sha256Transcript::new(PROTOCOL_NAME)
+}
+
+/// Records the bytes hashed by a [`Sha256Transcript`],
+/// for the messages known when generating the contract.
+struct Recorder(Vec<u8>);
+
+impl Recorder {
+    /// Record the `label`ed message `bytes`, framed as in [`Sha256Transcript`].
+    fn absorb(&mut self, label: &[u8], bytes: &[u8]) {
+        self.0.extend((label.len() as u64).to_le_bytes());
+        self.0.extend(label);
+        self.0.extend((bytes.len() as u64).to_le_bytes());
+        self.0.extend(bytes);
+    }
+}
+
+impl GeminiTranscript for Recorder {
+    fn append_serializable<S: CanonicalSerialize>(&mut self, label: &'static [u8], msg: &S) {
+        let mut message = Vec::new();
+        msg.serialize_uncompressed(&mut message).unwrap();
+        self.absorb(label, &message);
+    }
+
+    fn get_challenge<F: Field>(&mut self, _label: &'static [u8]) -> F {
+        unreachable!("the recorded messages precede any challenge")
+    }
+}
+
+/// The messages absorbed before the public input,
+/// for an instance of dimension `dim` and a public input of `statement_len` elements.
+fn transcript_prefix(dim: usize, statement_len: usize) -> Recorder {
+    let mut recorder = Recorder(Vec::new());
+    recorder.absorb(b"dom-sep", PROTOCOL_NAME);
+    bind_context::<Bls12_381, _>(&mut recorder, SNARK_LABEL, &[dim, dim]);
+    recorder.append_serializable(b"public-input-len", &(statement_len as u64));
+    recorder
+}
+
+/// Encode `point` as the precompiles of EIP-2537 do:
+/// each coordinate in 64 big-endian bytes, and the point at infinity as zeros.
+fn encode_point<G: CanonicalSerialize>(point: &G) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    point.serialize_uncompressed(&mut bytes).unwrap();
+    // the point at infinity is flagged in the first byte.
+    if bytes[0] & 0x40 != 0 {
+        return vec![0; bytes.len() / 48 * 64];
+    }
+    let mut coordinates = bytes.chunks(48).collect::<Vec<_>>();
+    // points in G2 are serialized with the imaginary part first, EIP-2537 expects it last.
+    if coordinates.len() == 4 {
+        coordinates.swap(0, 1);
+        coordinates.swap(2, 3);
+    }
+    coordinates
+        .into_iter()
+        .flat_map(|coordinate| [&[0; 16][..], coordinate].concat())
+        .collect()
+}
+
+/// Encode `scalar` in 32 big-endian bytes.
+fn encode_scalar(scalar: &Fr) -> Vec<u8> {
+    scalar.into_bigint().to_bytes_be()
+}
+
+/// Encode the non-zero entries of `matrix` as their row and column (4 big-endian bytes each),
+/// followed by their value.
+fn encode_matrix(matrix: &Matrix<Fr>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for (row, entries) in matrix.iter().enumerate() {
+        for (value, col) in entries {
+            bytes.extend((row as u32).to_be_bytes());
+            bytes.extend((*col as u32).to_be_bytes());
+            bytes.extend(encode_scalar(value));
+        }
+    }
+    bytes
+}
+
+fn hex(bytes: &[u8]) -> String {
+    let mut hex = String::new();
+    push_hex(&mut hex, bytes);
+    hex
+}
+
+/// Generate the Solidity contract `name` verifying the SNARK proofs for the instance `r1cs`
+/// (whose public input, witness, and assignment are ignored, except for the length of the public input)
+/// against the verification key `vk`.
+///
+/// `name` must be a valid Solidity identifier.
+/// Fails if the instance is malformed, if its padded dimension is smaller than 4,
+/// or if `vk` supports less than 3 evaluation points.
+pub fn verifier_contract(
+    name: &str,
+    r1cs: &R1cs<Fr>,
+    vk: &VerifierKey<Bls12_381>,
+) -> GeminiResult<String> {
+    r1cs.check()?;
+    let dim = padded_dim(r1cs.a.len(), r1cs.z.len());
+    if dim < 4 {
+        return Err(GeminiError::MalformedInstance {
+            reason: "the Solidity verifier requires a padded dimension of at least 4",
+        });
+    }
+    if vk.powers_of_g.len() < 3 || vk.powers_of_g2.len() < 4 {
+        return Err(GeminiError::MalformedInstance {
+            reason: "the verification key supports less than 3 evaluation points",
+        });
+    }
+    let vk_g1 = vk.powers_of_g[..3]
+        .iter()
+        .flat_map(encode_point)
+        .collect::<Vec<_>>();
+    let vk_g2 = vk.powers_of_g2[..4]
+        .iter()
+        .flat_map(encode_point)
+        .collect::<Vec<_>>();
+    let prefix = transcript_prefix(dim, r1cs.x.len());
+
+    Ok(TEMPLATE
+        .replace("{{NAME}}", name)
+        .replace("{{DIM}}", &dim.to_string())
+        .replace("{{LOG_DIM}}", &dim.trailing_zeros().to_string())
+        .replace("{{NUM_PUBLIC}}", &r1cs.x.len().to_string())
+        .replace("{{PREFIX}}", &hex(&prefix.0))
+        .replace("{{VK_G1}}", &hex(&vk_g1))
+        .replace("{{VK_G2}}", &hex(&vk_g2))
+        .replace("{{MATRIX_A}}", &hex(&encode_matrix(&r1cs.a)))
+        .replace("{{MATRIX_B}}", &hex(&encode_matrix(&r1cs.b)))
+        .replace("{{MATRIX_C}}", &hex(&encode_matrix(&r1cs.c))))
+}
+
+/// Encode the proof `proof` for the contract:
+///
+/// - the witness commitment, and $u_2$;
+/// - for each sumcheck, the round messages and the final foldings;
+/// - the commitments to the folded polynomials, and their evaluations at $\beta$ and $-\beta$;
+/// - the evaluation proof, and the evaluations of the witness at $\beta^2$, $\beta$, and $-\beta$,
+///
+/// where points take 128 bytes (as in EIP-2537), and scalars 32 big-endian bytes.
+///
+/// Fails if the proof is not shaped as a SNARK proof.
+pub fn proof_bytes(proof: &Proof<Bls12_381>) -> GeminiResult<Vec<u8>> {
+    let rounds = proof.first_sumcheck_msgs.0.len();
+    if rounds < 2 {
+        return Err(GeminiError::MalformedInstance {
+            reason: "the proof has less than 2 sumcheck rounds",
+        });
+    }
+    let tensorcheck = &proof.tensorcheck_proof;
+    let second_rounds = proof.second_sumcheck_msgs.0.len();
+    GeminiError::ensure_len("proof.second_sumcheck", rounds, second_rounds)?;
+    for (name, msgs) in [
+        (
+            "proof.first_sumcheck.final_foldings",
+            &proof.first_sumcheck_msgs,
+        ),
+        (
+            "proof.second_sumcheck.final_foldings",
+            &proof.second_sumcheck_msgs,
+        ),
+    ] {
+        GeminiError::ensure_len(name, 1, msgs.1.len())?;
+    }
+    let foldings = [
+        (
+            "proof.folded_polynomials_commitments",
+            tensorcheck.folded_polynomials_commitments.len(),
+        ),
+        (
+            "proof.folded_polynomials_evaluations",
+            tensorcheck.folded_polynomials_evaluations.len(),
+        ),
+    ];
+    for (name, found) in foldings {
+        GeminiError::ensure_len(name, rounds - 1, found)?;
+    }
+    GeminiError::ensure_len(
+        "proof.base_polynomials_evaluations",
+        1,
+        tensorcheck.base_polynomials_evaluations.len(),
+    )?;
+
+    let mut bytes = encode_point(&proof.witness_commitment.0);
+    bytes.extend(encode_scalar(&proof.zc_alpha));
+    for ProverMsgs(messages, final_foldings) in
+        [&proof.first_sumcheck_msgs, &proof.second_sumcheck_msgs]
+    {
+        for RoundMsg(a, b) in messages {
+            bytes.extend(encode_scalar(a));
+            bytes.extend(encode_scalar(b));
+        }
+        for e in &final_foldings[0] {
+            bytes.extend(encode_scalar(e));
+        }
+    }
+    for commitment in &tensorcheck.folded_polynomials_commitments {
+        bytes.extend(encode_point(&commitment.0));
+    }
+    for e in tensorcheck.folded_polynomials_evaluations.iter().flatten() {
+        bytes.extend(encode_scalar(e));
+    }
+    bytes.extend(encode_point(&tensorcheck.evaluation_proof.0));
+    for e in &tensorcheck.base_polynomials_evaluations[0] {
+        bytes.extend(encode_scalar(e));
+    }
+    Ok(bytes)
+}
+
+/// Encode the call `verifyProof(proof, x)`, following the contract ABI.
+pub fn calldata(proof: &Proof<Bls12_381>, x: &[Fr]) -> GeminiResult<Vec<u8>> {
+    let word = |n: usize| {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&(n as u64).to_be_bytes());
+        word
+    };
+    let proof = proof_bytes(proof)?;
+    let padding = (32 - proof.len() % 32) % 32;
+
+    let mut calldata = VERIFY_PROOF_SELECTOR.to_vec();
+    calldata.extend(word(64));
+    calldata.extend(word(64 + 32 + proof.len() + padding));
+    calldata.extend(word(proof.len()));
+    calldata.extend(&proof);
+    calldata.extend(vec![0; padding]);
+    calldata.extend(word(x.len()));
+    for e in x {
+        calldata.extend(encode_scalar(e));
+    }
+    Ok(calldata)
+}
+
+#[test]
+fn test_solidity_transcript() {
+    use ark_serialize::CanonicalDeserialize;
+    use ark_std::test_rng;
+    use ark_std::UniformRand;
+    use sha2::{Digest, Sha256};
+
+    use crate::iterable::Reverse;
+    use crate::transcript::bind_public_input;
+
+    // the challenges as derived by the contract from the absorbed bytes.
+    let challenge = |absorbed: &[u8], label: &[u8]| {
+        (0u64..)
+            .find_map(|counter| {
+                let mut hasher = Sha256::new();
+                hasher.update(absorbed);
+                hasher.update(b"challenge");
+                hasher.update(label);
+                hasher.update(counter.to_le_bytes());
+                hasher.update([0]);
+                let mut bytes: [u8; 32] = hasher.finalize().into();
+                bytes[31] &= 0x7f;
+                Fr::deserialize_compressed(&bytes[..]).ok()
+            })
+            .unwrap()
+    };
+
+    let rng = &mut test_rng();
+    let x = (0..5).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let witness = ark_bls12_381::G1Affine::rand(rng);
+
+    let mut transcript = transcript();
+    bind_context::<Bls12_381, _>(&mut transcript, SNARK_LABEL, &[16, 16]);
+    bind_public_input::<Fr, _, _>(&mut transcript, &Reverse(x.as_slice()));
+    transcript.append_serializable(b"witness", &witness);
+    let alpha = transcript.get_challenge::<Fr>(b"alpha");
+    let eta = transcript.get_challenge::<Fr>(b"eta");
+
+    let mut recorder = transcript_prefix(16, x.len());
+    let mut chunk = (x.len() as u64).to_le_bytes().to_vec();
+    x.iter()
+        .rev()
+        .for_each(|e| chunk.extend(encode_scalar(e).iter().rev()));
+    recorder.absorb(b"public-input", &chunk);
+    recorder.append_serializable(b"witness", &witness);
+    assert_eq!(challenge(&recorder.0, b"alpha"), alpha);
+    recorder.append_serializable(b"alpha", &alpha);
+    assert_eq!(challenge(&recorder.0, b"eta"), eta);
+}
+
+#[cfg(feature = "prover")]
+#[test]
+fn test_solidity_calldata() {
+    use ark_std::test_rng;
+
+    use crate::circuit::{generate_relation, random_circuit};
+    use crate::kzg::CommitterKey;
+
+    let rng = &mut test_rng();
+    let circuit = random_circuit(rng, 8, 8);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(16, 3, rng);
+    let vk = VerifierKey::from(&ck);
+
+    let proof = Proof::new_time_with_transcript(&r1cs, &ck, &mut transcript()).unwrap();
+    assert!(proof
+        .verify_with_transcript(&r1cs, &vk, &mut transcript())
+        .is_ok());
+
+    let contract = verifier_contract("GeminiVerifier", &r1cs, &vk).unwrap();
+    assert!(contract.contains("contract GeminiVerifier {"));
+    assert!(contract.contains("uint256 internal constant LOG_DIM = 3;"));
+    assert!(!contract.contains("{{"));
+
+    // 4 points, and 1 + 2 * (2 * 3 + 2) + 2 * 2 + 3 scalars.
+    let bytes = proof_bytes(&proof).unwrap();
+    assert_eq!(bytes.len(), 4 * 128 + 24 * 32);
+    let calldata = calldata(&proof, &r1cs.x).unwrap();
+    assert_eq!(calldata[..4], VERIFY_PROOF_SELECTOR);
+    assert_eq!(
+        calldata.len(),
+        4 + 3 * 32 + bytes.len() + 32 + 32 * r1cs.x.len()
+    );
+    assert_eq!(calldata[4 + 3 * 32..4 + 3 * 32 + bytes.len()], bytes[..]);
+}
@@ -26,7 +26,7 @@ fn test_snark_consistency() {
     let z_c = product_matrix_vector(&r1cs.c, &r1cs.z);
 
     let rows = r1cs.z.len();
-    let time_proof = Proof::new_time(&r1cs, &ck);
+    let time_proof = Proof::new_time(&r1cs, &ck).unwrap();
 
     let a_rowm = matrix_into_colmaj(&r1cs.a, rows);
     let b_rowm = matrix_into_colmaj(&r1cs.b, rows);
@@ -51,7 +51,7 @@ fn test_snark_consistency() {
         joint_len: num_constraints,
     };
     let ck_stream = CommitterKeyStream::from(&ck);
-    let space_proof = Proof::new_elastic(r1cs_stream, ck_stream, max_msm_buffer);
+    let space_proof = Proof::new_elastic(r1cs_stream, ck_stream, max_msm_buffer).unwrap();
 
     assert_eq!(time_proof, space_proof);
 }
@@ -67,6 +67,988 @@ fn test_snark_correctness() {
     let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
     let vk = (&ck).into();
 
-    let time_proof = Proof::new_time(&r1cs, &ck);
+    let time_proof = Proof::new_time(&r1cs, &ck).unwrap();
     assert!(time_proof.verify(&r1cs, &vk).is_ok())
 }
+
+#[test]
+fn test_snark_padding() {
+    let rng = &mut test_rng();
+    let num_constraints = 13;
+    let num_variables = 13;
+    let max_msm_buffer = 20;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    assert!(!r1cs.is_padded());
+    assert!(r1cs.padded().is_padded());
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let vk = (&ck).into();
+
+    let z_a = product_matrix_vector(&r1cs.a, &r1cs.z);
+    let z_b = product_matrix_vector(&r1cs.b, &r1cs.z);
+    let z_c = product_matrix_vector(&r1cs.c, &r1cs.z);
+    let a_colmaj = matrix_into_colmaj(&r1cs.a, r1cs.z.len());
+    let b_colmaj = matrix_into_colmaj(&r1cs.b, r1cs.z.len());
+    let c_colmaj = matrix_into_colmaj(&r1cs.c, r1cs.z.len());
+    let a_rowmaj = matrix_into_rowmaj(&r1cs.a);
+    let b_rowmaj = matrix_into_rowmaj(&r1cs.b);
+    let c_rowmaj = matrix_into_rowmaj(&r1cs.c);
+
+    let r1cs_stream = R1csStream {
+        z: Reverse(r1cs.z.as_slice()),
+        a_colmaj: a_colmaj.as_slice(),
+        b_colmaj: b_colmaj.as_slice(),
+        c_colmaj: c_colmaj.as_slice(),
+        a_rowmaj: a_rowmaj.as_slice(),
+        b_rowmaj: b_rowmaj.as_slice(),
+        c_rowmaj: c_rowmaj.as_slice(),
+        witness: Reverse(r1cs.w.as_slice()),
+        z_a: Reverse(z_a.as_slice()),
+        z_b: Reverse(z_b.as_slice()),
+        z_c: Reverse(z_c.as_slice()),
+        nonzero: r1cs.a.len(),
+        joint_len: num_constraints,
+    };
+    let ck_stream = CommitterKeyStream::from(&ck);
+
+    let time_proof = Proof::new_time(&r1cs, &ck).unwrap();
+    let padded_proof = Proof::new_time(&r1cs.padded(), &ck).unwrap();
+    let space_proof = Proof::new_elastic(r1cs_stream, ck_stream, max_msm_buffer).unwrap();
+    assert_eq!(time_proof, padded_proof);
+    assert_eq!(time_proof, space_proof);
+    assert!(time_proof.verify(&r1cs, &vk).is_ok());
+}
+
+#[test]
+fn test_snark_public_input() {
+    use ark_bls12_381::Fr;
+    use ark_std::One;
+
+    use crate::misc::MatrixElement;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let vk = (&ck).into();
+
+    // the public input is recovered from the tail of the stream z.
+    let empty: &[MatrixElement<Fr>] = &[];
+    let z = Reverse(r1cs.z.as_slice());
+    let r1cs_stream = R1csStream {
+        z,
+        a_colmaj: empty,
+        b_colmaj: empty,
+        c_colmaj: empty,
+        a_rowmaj: empty,
+        b_rowmaj: empty,
+        c_rowmaj: empty,
+        witness: Reverse(r1cs.w.as_slice()),
+        z_a: z,
+        z_b: z,
+        z_c: z,
+        nonzero: 0,
+        joint_len: 0,
+    };
+    assert_eq!(r1cs_stream.public_input::<Fr>(), r1cs.x);
+
+    let proof = Proof::new_time(&r1cs, &ck).unwrap();
+    assert!(proof.verify(&r1cs, &vk).is_ok());
+
+    let mut tampered = r1cs.clone();
+    *tampered.x.last_mut().unwrap() += Fr::one();
+    assert!(proof.verify(&tampered, &vk).is_err());
+}
+
+#[test]
+fn test_snark_prepared_verifier_key() {
+    use crate::kzg::{PreparedVerifierKey, VerifierKey};
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let vk = VerifierKey::from(&ck);
+    let pvk = PreparedVerifierKey::from(&vk);
+    for _ in 0..2 {
+        let circuit = random_circuit(rng, num_constraints, num_variables);
+        let r1cs = generate_relation(circuit);
+        let proof = Proof::new_time(&r1cs, &ck).unwrap();
+        assert!(proof.verify_prepared(&r1cs, &pvk).is_ok());
+
+        let mut tampered = r1cs.clone();
+        tampered.x[0] += ark_bls12_381::Fr::from(1u64);
+        assert!(proof.verify_prepared(&tampered, &pvk).is_err());
+    }
+}
+
+#[test]
+fn test_snark_serialization() {
+    use ark_std::vec::Vec;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let proof = Proof::new_time(&r1cs, &ck).unwrap();
+
+    let mut bytes = Vec::new();
+    proof.serialize_versioned(&mut bytes).unwrap();
+    assert_eq!(bytes.len(), proof.serialized_size());
+    assert_eq!(Proof::deserialize_versioned(&bytes[..]).unwrap(), proof);
+
+    // proofs serialized under another format version are rejected.
+    bytes[0] += 1;
+    assert!(Proof::<Bls12_381>::deserialize_versioned(&bytes[..]).is_err());
+}
+
+#[test]
+fn test_snark_emitting() {
+    use ark_std::vec::Vec;
+
+    use crate::circuit::R1csStreams;
+    use crate::config::ProverConfig;
+    use crate::snark::{ProofPart, ProofWriter};
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+
+    let mut parts = Vec::new();
+    let time_proof =
+        Proof::new_time_emitting(&r1cs, &ck, &mut |part: ProofPart<_>| parts.push(part)).unwrap();
+    assert_eq!(parts.len(), 5);
+    assert!(matches!(parts[0], ProofPart::WitnessCommitment(_)));
+    assert_eq!(Proof::from_parts(parts.clone()), Some(time_proof.clone()));
+    // missing or out-of-order messages are rejected.
+    assert_eq!(Proof::from_parts(parts[..4].to_vec()), None);
+    parts.swap(2, 3);
+    assert_eq!(Proof::from_parts(parts), None);
+
+    // the messages written as they come are the serialized proof.
+    let r1cs_streams = R1csStreams::new(&r1cs);
+    let mut writer = ProofWriter::new(Vec::new());
+    let elastic_proof = Proof::new_elastic_emitting(
+        r1cs_streams.stream(),
+        CommitterKeyStream::from(&ck),
+        &ProverConfig::default(),
+        &mut writer,
+    )
+    .unwrap();
+    assert_eq!(elastic_proof, time_proof);
+    let mut expected = Vec::new();
+    time_proof.serialize_versioned(&mut expected).unwrap();
+    assert_eq!(writer.finish().unwrap(), expected);
+
+    // the proof is written without being returned.
+    let writer = Proof::new_elastic_to_writer(
+        r1cs_streams.stream(),
+        CommitterKeyStream::from(&ck),
+        &ProverConfig::default(),
+        Vec::new(),
+    )
+    .unwrap();
+    assert_eq!(writer.finish().unwrap(), expected);
+}
+
+#[cfg(feature = "test-utils")]
+#[test]
+fn test_snark_faults() {
+    use crate::snark::Fault;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let vk = (&ck).into();
+
+    let proof = Proof::new_time(&r1cs, &ck).unwrap();
+    assert!(proof.verify(&r1cs, &vk).is_ok());
+    for fault in Fault::ALL {
+        let forged = Proof::new_time_with_fault(&r1cs, &ck, fault).unwrap();
+        assert!(forged != proof, "{fault:?} left the proof unchanged");
+        assert!(forged.verify(&r1cs, &vk).is_err(), "{fault:?} was accepted");
+    }
+}
+
+#[test]
+fn test_snark_json() {
+    use crate::kzg::VerifierKey;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let vk = VerifierKey::from(&ck);
+    let proof = Proof::new_time(&r1cs, &ck).unwrap();
+
+    let json = proof.to_json();
+    assert!(json.contains("\"protocol\": \"gemini\""));
+    let imported_proof = Proof::from_json(&json).unwrap();
+    let imported_vk = VerifierKey::<Bls12_381>::from_json(&vk.to_json()).unwrap();
+    assert_eq!(imported_proof, proof);
+    assert!(imported_proof.verify(&r1cs, &imported_vk).is_ok());
+
+    // exports of other kinds, or with missing fields, are rejected.
+    assert!(Proof::<Bls12_381>::from_json(&vk.to_json()).is_err());
+    let truncated = json.replace("\"zc_alpha\"", "\"zc_beta\"");
+    assert!(Proof::<Bls12_381>::from_json(&truncated).is_err());
+}
+
+#[test]
+fn test_snark_linked_witness() {
+    use ark_bls12_381::Fr;
+    use ark_std::One;
+
+    use crate::snark::WitnessSegment;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let vk = (&ck).into();
+
+    // the witness is committed externally in two segments.
+    let middle = r1cs.w.len() / 2;
+    let segments = [
+        WitnessSegment::new(&ck, &r1cs.w[..middle], 0),
+        WitnessSegment::new(&ck, &r1cs.w[middle..], middle),
+    ];
+    let proof = Proof::new_time_linked(&r1cs, &ck, &segments).unwrap();
+    assert!(proof.verify_linked(&r1cs, &vk, &segments).is_ok());
+
+    // the witness must be fully covered by the segments.
+    assert!(proof.verify_linked(&r1cs, &vk, &segments[..1]).is_err());
+
+    // the proof does not link to a different witness.
+    let mut other_witness = r1cs.w[middle..].to_vec();
+    other_witness[0] += Fr::one();
+    let other_segments = [segments[0], WitnessSegment::new(&ck, &other_witness, middle)];
+    assert!(proof.verify_linked(&r1cs, &vk, &other_segments).is_err());
+}
+
+#[test]
+fn test_snark_committed_input() {
+    use ark_bls12_381::Fr;
+    use ark_std::{One, Zero};
+
+    use crate::snark::CommittedInput;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let vk = (&ck).into();
+
+    let input = CommittedInput::new(&ck, &r1cs.x);
+    let proof = Proof::new_time_with_committed_input(&r1cs, &ck, &input).unwrap();
+    // the verifier does not see the public input.
+    let mut index = r1cs.clone();
+    index.x.iter_mut().for_each(|x| *x = Fr::zero());
+    assert!(proof.verify_committed_input(&index, &vk, &input).is_ok());
+    // the proof is not a proof for the public input in the clear, and vice versa.
+    assert!(proof.verify(&r1cs, &vk).is_err());
+    let public_proof = Proof::new_time(&r1cs, &ck).unwrap();
+    assert!(public_proof
+        .verify_committed_input(&index, &vk, &input)
+        .is_err());
+
+    // the proof does not verify for a different committed input.
+    let mut other_x = r1cs.x.clone();
+    other_x[0] += Fr::one();
+    let other_input = CommittedInput::new(&ck, &other_x);
+    assert!(proof
+        .verify_committed_input(&index, &vk, &other_input)
+        .is_err());
+    assert!(Proof::new_time_with_committed_input(&r1cs, &ck, &other_input).is_err());
+}
+
+#[test]
+fn test_snark_batch() {
+    use ark_bls12_381::Fr;
+    use ark_serialize::CanonicalSerialize;
+    use ark_std::vec::Vec;
+    use ark_std::One;
+
+    use crate::snark::BatchProof;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    // the same circuit, over different assignments.
+    let instances = (0..4)
+        .map(|_| generate_relation(random_circuit(rng, num_constraints, num_variables)))
+        .collect::<Vec<_>>();
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let vk = (&ck).into();
+
+    let proof = BatchProof::new_time(&instances, &ck).unwrap();
+    assert_eq!(proof.len(), instances.len());
+    assert!(proof.verify(&instances, &vk).is_ok());
+    // the batch is smaller than the proofs of each instance.
+    let single_proof = Proof::new_time(&instances[0], &ck).unwrap();
+    assert!(proof.compressed_size() < instances.len() * single_proof.compressed_size());
+
+    // the proof is bound to each statement, and to their order.
+    let mut other_instances = instances.clone();
+    other_instances.swap(0, 1);
+    assert!(proof.verify(&other_instances, &vk).is_err());
+    other_instances.swap(0, 1);
+    other_instances[3].x[1] += Fr::one();
+    assert!(proof.verify(&other_instances, &vk).is_err());
+    assert!(proof.verify(&instances[..3], &vk).is_err());
+
+    // instances over different matrices are rejected.
+    let other_circuit = random_circuit(rng, num_constraints / 2, num_variables);
+    let mut other_instances = instances.clone();
+    other_instances.push(generate_relation(other_circuit));
+    assert!(BatchProof::new_time(&other_instances, &ck).is_err());
+    assert!(BatchProof::new_time(&[], &ck).is_err());
+}
+
+#[test]
+fn test_snark_transcripts() {
+    use ark_bls12_381::Fr;
+
+    use crate::transcript::{PoseidonTranscript, Sha256Transcript};
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let vk = (&ck).into();
+
+    let transcript = PoseidonTranscript::<Fr>::new(b"test");
+    let proof = Proof::new_time_with_transcript(&r1cs, &ck, &mut transcript.clone()).unwrap();
+    assert!(proof
+        .verify_with_transcript(&r1cs, &vk, &mut transcript.clone())
+        .is_ok());
+    // the proof does not verify against a different transcript.
+    assert!(proof.verify(&r1cs, &vk).is_err());
+
+    let transcript = Sha256Transcript::new(b"test");
+    let proof = Proof::new_time_with_transcript(&r1cs, &ck, &mut transcript.clone()).unwrap();
+    assert!(proof
+        .verify_with_transcript(&r1cs, &vk, &mut transcript.clone())
+        .is_ok());
+}
+
+#[test]
+fn test_snark_folding() {
+    use ark_bls12_381::Fr;
+    use ark_std::vec::Vec;
+    use ark_std::One;
+
+    use crate::snark::Accumulator;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    // circuits of the same size share the same matrices.
+    let instances = (0..3)
+        .map(|_| generate_relation(random_circuit(rng, num_constraints, num_variables)))
+        .collect::<Vec<_>>();
+    let r1cs = &instances[0];
+    let ck = CommitterKey::<Bls12_381>::new(4 * (num_constraints + num_variables), 5, rng);
+    let vk = (&ck).into();
+
+    let mut accumulator = Accumulator::new(r1cs, &ck);
+    let mut instance = accumulator.instance.clone();
+    for other in &instances[1..] {
+        let other_accumulator = Accumulator::new(other, &ck);
+        let (folded, folding_proof) = accumulator.fold(r1cs, &other_accumulator, &ck);
+        instance = instance.fold(r1cs, &other_accumulator.instance, &folding_proof);
+        accumulator = folded;
+    }
+    assert_eq!(instance, accumulator.instance);
+
+    // the folding challenges are bound to the matrices.
+    let mut other_matrices = r1cs.clone();
+    other_matrices.a[0].push((Fr::one(), 0));
+    let other_accumulator = Accumulator::new(&instances[1], &ck);
+    let (_, folding_proof) = accumulator.fold(r1cs, &other_accumulator, &ck);
+    assert_ne!(
+        instance.fold(r1cs, &other_accumulator.instance, &folding_proof),
+        instance.fold(&other_matrices, &other_accumulator.instance, &folding_proof)
+    );
+
+    let proof = accumulator.prove(r1cs, &ck).unwrap();
+    assert!(instance.verify(r1cs, &vk, &proof).is_ok());
+
+    let mut tampered = instance.clone();
+    tampered.u += Fr::one();
+    assert!(tampered.verify(r1cs, &vk, &proof).is_err());
+}
+
+#[test]
+fn test_snark_constraint_system() {
+    use ark_relations::r1cs::{ConstraintSynthesizer, ConstraintSystem, SynthesisMode};
+
+    use crate::circuit::{R1cs, R1csStreams};
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let cs = ConstraintSystem::new_ref();
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.finalize();
+    let r1cs = R1cs::from_constraint_system(cs).unwrap();
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+
+    let r1cs_streams = R1csStreams::new(&r1cs);
+    let time_proof = Proof::new_time(&r1cs, &ck).unwrap();
+    let ck_stream = CommitterKeyStream::from(&ck);
+    let elastic_proof = Proof::new_elastic(r1cs_streams.stream(), ck_stream, 20).unwrap();
+    assert_eq!(time_proof, elastic_proof);
+
+    // matrices are not available in setup mode.
+    let cs = ConstraintSystem::new_ref();
+    cs.set_mode(SynthesisMode::Setup);
+    circuit.generate_constraints(cs.clone()).unwrap();
+    cs.finalize();
+    assert!(R1cs::<ark_bls12_381::Fr>::from_constraint_system(cs).is_none());
+}
+
+#[test]
+fn test_snark_memory_budget() {
+    use crate::circuit::R1csStreams;
+
+    let rng = &mut test_rng();
+    let num_constraints = 64;
+    let num_variables = 64;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let vk = (&ck).into();
+
+    let r1cs_streams = R1csStreams::new(&r1cs);
+    let time_proof = Proof::new_time(&r1cs, &ck).unwrap();
+    for memory_budget in [0, 1, 1 << 3, 1 << 6, 1 << 10] {
+        let ck_stream = CommitterKeyStream::from(&ck);
+        let elastic_proof =
+            Proof::new_elastic_with_budget(r1cs_streams.stream(), ck_stream, 20, memory_budget)
+                .unwrap();
+        assert_eq!(time_proof, elastic_proof);
+    }
+    assert!(time_proof.verify(&r1cs, &vk).is_ok());
+}
+
+#[test]
+fn test_snark_incremental() {
+    use ark_bls12_381::Fr;
+    use ark_std::UniformRand;
+
+    use crate::snark::IncrementalProver;
+
+    let rng = &mut test_rng();
+    let num_constraints = 32;
+    let num_variables = 32;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let mut r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let vk = (&ck).into();
+
+    let mut prover = IncrementalProver::new(&r1cs, &ck).unwrap();
+    let proof = prover.prove().unwrap();
+    assert_eq!(proof, Proof::new_time(&r1cs, &ck).unwrap());
+    assert!(proof.verify(&r1cs, &vk).is_ok());
+
+    // update a few entries of the witness.
+    let diff = [(0, Fr::rand(rng)), (3, Fr::rand(rng)), (7, r1cs.w[7])];
+    prover.update(&diff).unwrap();
+    for &(i, value) in &diff {
+        r1cs.w[i] = value;
+        r1cs.z[r1cs.x.len() + i] = value;
+    }
+    assert_eq!(prover.witness(), r1cs.w.as_slice());
+    assert_eq!(
+        prover.prove().unwrap(),
+        Proof::new_time(&r1cs, &ck).unwrap()
+    );
+}
+
+#[test]
+fn test_snark_estimate_size() {
+    use ark_serialize::Compress;
+
+    let rng = &mut test_rng();
+    for (num_constraints, num_variables) in [(16, 16), (12, 20), (33, 7)] {
+        let circuit = random_circuit(rng, num_constraints, num_variables);
+        let r1cs = generate_relation(circuit);
+        let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+        let proof = Proof::new_time(&r1cs, &ck).unwrap();
+
+        let estimate = Proof::<Bls12_381>::estimate_size(r1cs.a.len(), r1cs.z.len(), Compress::Yes);
+        assert_eq!(estimate, proof.serialized_size());
+        assert!(
+            Proof::<Bls12_381>::estimate_size(r1cs.a.len(), r1cs.z.len(), Compress::No) > estimate
+        );
+
+        // the components add up to the proof.
+        let breakdown = proof.size_breakdown();
+        assert_eq!(breakdown.total(), proof.serialized_size());
+        assert_eq!(breakdown.version, 1);
+        assert!(breakdown.sumcheck_messages > 0 && breakdown.folded_commitments > 0);
+    }
+}
+
+#[test]
+fn test_snark_verification_error() {
+    use ark_bls12_381::Fr;
+    use ark_std::One;
+
+    use crate::errors::VerificationError;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let vk = (&ck).into();
+
+    // a wrong claim is caught by the first sumcheck.
+    let mut proof = Proof::new_time(&r1cs, &ck).unwrap();
+    proof.zc_alpha += Fr::one();
+    assert!(matches!(
+        proof.verify(&r1cs, &vk),
+        Err(VerificationError::Sumcheck {
+            sumcheck: "first sumcheck",
+            ..
+        })
+    ));
+
+    // a wrong evaluation is caught by the tensorcheck.
+    let mut proof = Proof::new_time(&r1cs, &ck).unwrap();
+    proof
+        .tensorcheck_proof
+        .folded_polynomials_evaluations
+        .last_mut()
+        .unwrap()[0] += Fr::one();
+    assert!(matches!(
+        proof.verify(&r1cs, &vk),
+        Err(VerificationError::Tensorcheck { instance: 0, .. })
+    ));
+}
+
+#[test]
+fn test_snark_malformed_input() {
+    use ark_bls12_381::Fr;
+
+    use crate::errors::GeminiError;
+    use crate::snark::IncrementalProver;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+
+    // the committer key is too short for the padded witness.
+    let short_ck = CommitterKey::<Bls12_381>::new(num_variables / 4, 5, rng);
+    assert!(matches!(
+        Proof::new_time(&r1cs, &short_ck),
+        Err(GeminiError::CommitterKeyTooShort { .. })
+    ));
+
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    // the vector z is not the concatenation of x and w.
+    let mut malformed = r1cs.clone();
+    malformed.z.pop();
+    assert!(matches!(
+        Proof::new_time(&malformed, &ck),
+        Err(GeminiError::LengthMismatch { name: "r1cs.z", .. })
+    ));
+
+    // an update outside of the witness leaves the prover unchanged.
+    let mut prover = IncrementalProver::new(&r1cs, &ck).unwrap();
+    let diff = [(0, Fr::from(1u64)), (r1cs.w.len(), Fr::from(1u64))];
+    assert!(prover.update(&diff).is_err());
+    assert_eq!(prover.witness(), r1cs.w.as_slice());
+}
+
+#[test]
+fn test_snark_prover_config() {
+    use crate::circuit::R1csStreams;
+    use crate::config::ProverConfig;
+
+    let rng = &mut test_rng();
+    let num_constraints = 32;
+    let num_variables = 32;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+
+    let config = ProverConfig {
+        max_msm_buffer: 7,
+        chunk_size: 3,
+        num_threads: Some(2),
+        memory_budget: 1 << 2,
+        folding_block: 2,
+        memory_cap: None,
+    };
+    let time_proof = Proof::new_time(&r1cs, &ck).unwrap();
+    assert_eq!(
+        Proof::new_time_with_config(&r1cs, &ck, &config).unwrap(),
+        time_proof
+    );
+
+    let r1cs_streams = R1csStreams::new(&r1cs);
+    let ck_stream = CommitterKeyStream::from(&ck);
+    let elastic_proof =
+        Proof::new_elastic_with_config(r1cs_streams.stream(), ck_stream, &config).unwrap();
+    assert_eq!(elastic_proof, time_proof);
+}
+
+#[test]
+fn test_snark_prove() {
+    use crate::config::ProverConfig;
+    use crate::snark::{IndexInfo, ProverMode};
+
+    let rng = &mut test_rng();
+    let num_constraints = 32;
+    let num_variables = 32;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let vk = (&ck).into();
+    let info = IndexInfo::from(&r1cs);
+
+    // without a cap, the time-efficient prover runs; with a tiny cap, the space-efficient one.
+    let config = ProverConfig::default();
+    assert_eq!(
+        ProverMode::select::<Bls12_381>(&info, &config),
+        ProverMode::Time
+    );
+    let capped = ProverConfig {
+        memory_cap: Some(1),
+        ..config
+    };
+    assert_eq!(
+        ProverMode::select::<Bls12_381>(&info, &capped),
+        ProverMode::Space
+    );
+
+    let time_proof = Proof::new_time(&r1cs, &ck).unwrap();
+    assert_eq!(Proof::prove(&r1cs, &ck, &config).unwrap(), time_proof);
+    assert_eq!(Proof::prove(&r1cs, &ck, &capped).unwrap(), time_proof);
+    for mode in [ProverMode::Time, ProverMode::Elastic, ProverMode::Space] {
+        let proof = Proof::prove_with_mode(&r1cs, &ck, &config, mode).unwrap();
+        assert_eq!(proof, time_proof);
+    }
+    assert!(time_proof.verify(&r1cs, &vk).is_ok());
+}
+
+#[test]
+fn test_snark_deterministic() {
+    use ark_std::vec::Vec;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+
+    // two independent runs from the same seed produce the same bytes.
+    let proofs = (0..2)
+        .map(|_| {
+            let ck =
+                CommitterKey::<Bls12_381>::from_seed(num_constraints + num_variables, 5, [1; 32]);
+            let mut bytes = Vec::new();
+            Proof::new_time(&r1cs, &ck)
+                .unwrap()
+                .serialize_versioned(&mut bytes)
+                .unwrap();
+            bytes
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(proofs[0], proofs[1]);
+}
+
+#[test]
+fn test_snark_dry_run() {
+    use crate::circuit::R1csStreams;
+    use crate::config::ProverConfig;
+
+    let rng = &mut test_rng();
+    let num_constraints = 32;
+    let num_variables = 32;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let r1cs_streams = R1csStreams::new(&r1cs);
+
+    let dry_run = |memory_budget| {
+        let config = ProverConfig {
+            memory_budget,
+            ..ProverConfig::default()
+        };
+        let ck_stream = CommitterKeyStream::from(&ck);
+        Proof::dry_run_elastic(r1cs_streams.stream(), ck_stream, &config).unwrap()
+    };
+    let streaming = dry_run(0);
+    let in_memory = dry_run(1 << 10);
+
+    // the witness and the key are read once for committing.
+    let witness_commitment = &streaming.phases[1];
+    assert_eq!(witness_commitment.name, "witness_commitment");
+    assert_eq!(witness_commitment.passes, 2);
+    assert_eq!(
+        witness_commitment.elements,
+        r1cs.w.len() + ck.powers_of_g.len()
+    );
+    // within the budget, the first sumcheck loads z_a and z_b after the first round.
+    assert_eq!(in_memory.phases[3].name, "first_sumcheck");
+    assert_eq!(in_memory.phases[3].passes, 4);
+    assert!(streaming.passes() > in_memory.passes());
+    assert!(streaming.bytes() > in_memory.bytes());
+}
+
+#[test]
+fn test_snark_cost_model() {
+    use crate::circuit::R1csStreams;
+    use crate::config::ProverConfig;
+    use crate::snark::{CostModel, IndexInfo};
+
+    let rng = &mut test_rng();
+    let num_constraints = 32;
+    let num_variables = 32;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let r1cs_streams = R1csStreams::new(&r1cs);
+    let proof = Proof::new_time(&r1cs, &ck).unwrap();
+
+    let model = CostModel {
+        group_addition: 1.0,
+        field_multiplication: 1.0,
+        read_byte: 1.0,
+        threads: 1,
+    };
+    let info = IndexInfo::from(&r1cs);
+    for memory_budget in [0, 4, 1 << 10] {
+        let config = ProverConfig {
+            memory_budget,
+            ..ProverConfig::default()
+        };
+        let estimate = model.estimate::<Bls12_381>(&info, &config);
+        let ck_stream = CommitterKeyStream::from(&ck);
+        let dry_run = Proof::dry_run_elastic(r1cs_streams.stream(), ck_stream, &config).unwrap();
+        assert_eq!(estimate.space.passes, dry_run.passes());
+        assert_eq!(estimate.time.passes, 0);
+        assert_eq!(estimate.time.proof_size, proof.serialized_size());
+        assert_eq!(estimate.space.proof_size, proof.serialized_size());
+        // streams are read, and arithmetic is not cheaper.
+        assert!(estimate.space.prover_time > estimate.time.prover_time);
+    }
+
+    #[cfg(feature = "std")]
+    {
+        let model = CostModel::calibrate::<Bls12_381>();
+        assert!(model.group_addition > model.field_multiplication);
+        assert!(model.threads > 0);
+    }
+}
+
+#[test]
+fn test_snark_sharded() {
+    use crate::iterable::dummy::Mat;
+    use crate::iterable::Sharded;
+    use crate::misc::MatrixElement;
+    use ark_bls12_381::Fr;
+    use ark_std::vec::Vec;
+
+    /// Split the matrix stream `m` in two shards of whole lines.
+    fn split_lines(m: &[MatrixElement<Fr>]) -> [Mat<&[MatrixElement<Fr>]>; 2] {
+        let eols = m
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.is_eol())
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+        let head_lines = eols.len() / 2 + 1;
+        let mid = eols[head_lines - 1] + 1;
+        [
+            Mat(&m[mid..], eols.len() - head_lines),
+            Mat(&m[..mid], head_lines),
+        ]
+    }
+    /// Split the vector `v` in two shards.
+    fn split(v: &[Fr]) -> [Reverse<&[Fr]>; 2] {
+        let (low, high) = v.split_at(v.len() / 3);
+        [Reverse(low), Reverse(high)]
+    }
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let time_proof = Proof::new_time(&r1cs, &ck).unwrap();
+
+    let num_rows = r1cs.z.len();
+    let colmaj = [&r1cs.a, &r1cs.b, &r1cs.c].map(|m| matrix_into_colmaj(m, num_rows));
+    let rowmaj = [&r1cs.a, &r1cs.b, &r1cs.c].map(|m| matrix_into_rowmaj(m));
+    let colmaj_shards = colmaj.each_ref().map(|m| split_lines(m));
+    let rowmaj_shards = rowmaj.each_ref().map(|m| split_lines(m));
+    let products = [&r1cs.a, &r1cs.b, &r1cs.c].map(|m| product_matrix_vector(m, &r1cs.z));
+    let product_shards = products.each_ref().map(|v| split(v));
+    let z_shards = split(&r1cs.z);
+    let witness_shards = split(&r1cs.w);
+
+    /// Concatenate the shards, checking that they hold whole lines.
+    fn matrix<'a>(
+        shards: &'a [Mat<&'a [MatrixElement<Fr>]>],
+    ) -> Sharded<'a, Mat<&'a [MatrixElement<Fr>]>> {
+        let matrix = Sharded::new("matrix", shards);
+        assert!(matrix.check_lines::<Fr>().is_ok());
+        matrix
+    }
+    let r1cs_stream = R1csStream {
+        a_colmaj: matrix(&colmaj_shards[0]),
+        b_colmaj: matrix(&colmaj_shards[1]),
+        c_colmaj: matrix(&colmaj_shards[2]),
+        a_rowmaj: matrix(&rowmaj_shards[0]),
+        b_rowmaj: matrix(&rowmaj_shards[1]),
+        c_rowmaj: matrix(&rowmaj_shards[2]),
+        z: Sharded::new("z", &z_shards),
+        witness: Sharded::new("witness", &witness_shards),
+        z_a: Sharded::new("z_a", &product_shards[0]),
+        z_b: Sharded::new("z_b", &product_shards[1]),
+        z_c: Sharded::new("z_c", &product_shards[2]),
+        nonzero: num_constraints,
+        joint_len: num_constraints,
+    };
+    let ck_stream = CommitterKeyStream::from(&ck);
+    let elastic_proof = Proof::new_elastic(r1cs_stream, ck_stream, 20).unwrap();
+    assert_eq!(elastic_proof, time_proof);
+}
+
+#[test]
+fn test_snark_bls12_377() {
+    use ark_bls12_377::Bls12_377;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+    let max_msm_buffer = 20;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_377>::new(num_constraints + num_variables, 5, rng);
+    let vk = (&ck).into();
+
+    let z_a = product_matrix_vector(&r1cs.a, &r1cs.z);
+    let z_b = product_matrix_vector(&r1cs.b, &r1cs.z);
+    let z_c = product_matrix_vector(&r1cs.c, &r1cs.z);
+    let a_colmaj = matrix_into_colmaj(&r1cs.a, r1cs.z.len());
+    let b_colmaj = matrix_into_colmaj(&r1cs.b, r1cs.z.len());
+    let c_colmaj = matrix_into_colmaj(&r1cs.c, r1cs.z.len());
+    let a_rowmaj = matrix_into_rowmaj(&r1cs.a);
+    let b_rowmaj = matrix_into_rowmaj(&r1cs.b);
+    let c_rowmaj = matrix_into_rowmaj(&r1cs.c);
+    let r1cs_stream = R1csStream {
+        z: Reverse(r1cs.z.as_slice()),
+        a_colmaj: a_colmaj.as_slice(),
+        b_colmaj: b_colmaj.as_slice(),
+        c_colmaj: c_colmaj.as_slice(),
+        a_rowmaj: a_rowmaj.as_slice(),
+        b_rowmaj: b_rowmaj.as_slice(),
+        c_rowmaj: c_rowmaj.as_slice(),
+        witness: Reverse(r1cs.w.as_slice()),
+        z_a: Reverse(z_a.as_slice()),
+        z_b: Reverse(z_b.as_slice()),
+        z_c: Reverse(z_c.as_slice()),
+        nonzero: num_constraints,
+        joint_len: num_constraints,
+    };
+    let ck_stream = CommitterKeyStream::from(&ck);
+
+    let time_proof = Proof::new_time(&r1cs, &ck).unwrap();
+    let space_proof = Proof::new_elastic(r1cs_stream, ck_stream, max_msm_buffer).unwrap();
+    assert_eq!(time_proof, space_proof);
+    assert!(time_proof.verify(&r1cs, &vk).is_ok());
+}
+
+#[test]
+fn test_outer_statement() {
+    use ark_bls12_377::{Bls12_377, Fr};
+    use ark_ff::One;
+
+    use crate::kzg::VerifierKey;
+
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_377>::new(num_constraints + num_variables, 5, rng);
+    let vk = VerifierKey::from(&ck);
+    let proof = Proof::new_time(&r1cs, &ck).unwrap();
+
+    let statement = proof.outer_statement(&r1cs, &vk).unwrap();
+    assert!(statement.verify().is_ok());
+
+    // the scalar field of BW6-761 is the base field of BLS12-377:
+    // the coordinates of the points are encoded natively.
+    let input = statement.public_input::<ark_bw6_761::Fr>().unwrap();
+    let lhs_len = statement.check.lhs.bases.len();
+    let zeros_len = statement.check.zeros.bases.len();
+    assert_eq!(input.len(), 3 * lhs_len + 2 + 5 * zeros_len + 4);
+    assert_eq!(input[0], statement.check.lhs.bases[0].x);
+    // the base field of BLS12-377 does not fit in its scalar field.
+    assert!(statement.public_input::<Fr>().is_none());
+
+    // the statement is bound to the proof.
+    let mut wrong_statement = statement.clone();
+    wrong_statement.check.lhs.scalars[0] += Fr::one();
+    assert!(wrong_statement.verify().is_err());
+
+    // the checks over the scalar field are performed.
+    let mut wrong_r1cs = r1cs.clone();
+    wrong_r1cs.x[1] += Fr::one();
+    assert!(proof.outer_statement(&wrong_r1cs, &vk).is_err());
+}
@@ -1,26 +1,101 @@
 //! The Time prover for the algebraic proofs.
 use ark_ec::pairing::Pairing;
-use ark_ff::{Field, One, Zero};
+use ark_ff::{Field, One};
+use ark_std::vec::Vec;
 use log::debug;
 
 use crate::circuit::R1cs;
-use crate::kzg::CommitterKey;
+use crate::config::ProverConfig;
+use crate::errors::GeminiResult;
+use crate::iterable::Reverse;
+use crate::kzg::{Commitment, CommitterKey};
 use crate::misc::{evaluate_le, hadamard};
-use crate::misc::{powers, product_matrix_vector, tensor};
-use crate::snark::Proof;
+use crate::misc::{powers, product_matrix_vector, product_vectors_matrices, tensor};
+use crate::snark::{check_key_len, CommittedInput, Proof, ProofPart, ProofSink, SNARK_LABEL};
 use crate::subprotocols::sumcheck::proof::Sumcheck;
 use crate::subprotocols::tensorcheck::TensorcheckProof;
-use crate::transcript::GeminiTranscript;
+use crate::transcript::{bind_committed_input, bind_context, bind_public_input, GeminiTranscript};
 use crate::PROTOCOL_NAME;
 
 impl<E: Pairing> Proof<E> {
     /// Given as input the R1CS instance `r1cs` and the committer key `ck` for the polynomial commitment scheme,
     /// produce a new SNARK proof using the time-efficient prover.
-    pub fn new_time(r1cs: &R1cs<E::ScalarField>, ck: &CommitterKey<E>) -> Proof<E>
+    ///
+    /// The instance is padded internally (see [`R1cs::padded`]),
+    /// hence it can have any number of constraints and variables.
+    ///
+    /// Fails if the instance is malformed (see [`R1cs::check`]),
+    /// or if the committer key is too short for the instance.
+    pub fn new_time(r1cs: &R1cs<E::ScalarField>, ck: &CommitterKey<E>) -> GeminiResult<Proof<E>>
     where
         E: Pairing,
     {
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        Self::new_time_with_transcript(r1cs, ck, &mut transcript)
+    }
+
+    /// Same as [`Proof::new_time`],
+    /// running on the threads of `config`.
+    pub fn new_time_with_config(
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+        config: &ProverConfig,
+    ) -> GeminiResult<Proof<E>> {
+        config.install(|| Self::new_time(r1cs, ck))
+    }
+
+    /// Produce a new SNARK proof using the time-efficient prover,
+    /// deriving the challenges from `transcript` (e.g., a [`PoseidonTranscript`]).
+    ///
+    /// The proof must be verified with [`Proof::verify_with_transcript`],
+    /// starting from the same transcript.
+    ///
+    /// [`PoseidonTranscript`]: crate::transcript::PoseidonTranscript
+    pub fn new_time_with_transcript(
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+        transcript: &mut impl GeminiTranscript,
+    ) -> GeminiResult<Proof<E>> {
+        Self::prove_time(r1cs, ck, None, transcript, &mut |_: ProofPart<E>| ())
+    }
+
+    /// Same as [`Proof::new_time`],
+    /// but emitting each message of the proof into `sink` as soon as it is final
+    /// (see [`ProofSink`]).
+    pub fn new_time_emitting(
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+        sink: &mut impl ProofSink<E>,
+    ) -> GeminiResult<Proof<E>> {
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        Self::prove_time(r1cs, ck, None, &mut transcript, sink)
+    }
+
+    /// The time-efficient prover of [`Proof::new_time_with_transcript`], emitting into `sink`,
+    /// and binding the commitment `input` in place of the public input if any.
+    pub(crate) fn prove_time(
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+        input: Option<&CommittedInput<E>>,
+        transcript: &mut impl GeminiTranscript,
+        sink: &mut impl ProofSink<E>,
+    ) -> GeminiResult<Proof<E>> {
+        r1cs.check()?;
+        check_key_len(
+            r1cs.a.len(),
+            r1cs.z.len(),
+            r1cs.x.len(),
+            ck.powers_of_g.len(),
+        )?;
         let snark_time = start_timer!(|| module_path!());
+        trace_span!(
+            "snark_time_prover",
+            num_constraints = r1cs.a.len(),
+            num_variables = r1cs.z.len(),
+        );
+        // pad the instance to square matrices of power-of-two dimension.
+        let padded = r1cs.padded();
+        let r1cs: &R1cs<E::ScalarField> = &padded;
 
         debug!(
             "features:{};space-time-threshold:{};tensor-expansion:{}",
@@ -33,20 +108,58 @@ impl<E: Pairing> Proof<E> {
         let z_b = product_matrix_vector(&r1cs.b, &r1cs.z);
         let z_c = product_matrix_vector(&r1cs.c, &r1cs.z);
 
-        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
         let witness_commitment_time = start_timer!(|| "Commitment to w");
         let witness_commitment = ck.commit(&r1cs.w);
         end_timer!(witness_commitment_time);
 
+        let proof = Self::new_time_committed(
+            r1cs,
+            ck,
+            transcript,
+            [z_a.as_slice(), z_b.as_slice(), z_c.as_slice()],
+            witness_commitment,
+            input,
+            sink,
+        )?;
+        end_timer!(snark_time);
+        Ok(proof)
+    }
+
+    /// Produce a new SNARK proof for the padded instance `r1cs`,
+    /// given the products `[Az, Bz, Cz]` and the commitment to the witness,
+    /// emitting each message into `sink`.
+    ///
+    /// If `input` is given, it is bound in place of the public input,
+    /// and the public input is opened along with the witness.
+    pub(crate) fn new_time_committed(
+        r1cs: &R1cs<E::ScalarField>,
+        ck: &CommitterKey<E>,
+        transcript: &mut impl GeminiTranscript,
+        [z_a, z_b, z_c]: [&[E::ScalarField]; 3],
+        witness_commitment: Commitment<E>,
+        input: Option<&CommittedInput<E>>,
+        sink: &mut impl ProofSink<E>,
+    ) -> GeminiResult<Proof<E>> {
+        // bind the context and the public input (or its commitment) to the transcript.
+        bind_context::<E, _>(transcript, SNARK_LABEL, &[r1cs.a.len(), r1cs.z.len()]);
+        match input {
+            None => {
+                bind_public_input::<E::ScalarField, _, _>(transcript, &Reverse(r1cs.x.as_slice()))
+            }
+            Some(input) => bind_committed_input(transcript, input.len, &input.commitment),
+        }
         transcript.append_serializable(b"witness", &witness_commitment);
+        sink.emit(ProofPart::WitnessCommitment(witness_commitment));
         let alpha = transcript.get_challenge(b"alpha");
 
-        let zc_alpha = evaluate_le(&z_c, &alpha);
+        let zc_alpha = evaluate_le(z_c, &alpha);
         transcript.append_serializable(b"zc(alpha)", &zc_alpha);
+        sink.emit(ProofPart::ZcAlpha(zc_alpha));
 
         let first_sumcheck_time = start_timer!(|| "First sumcheck");
-        let first_proof = Sumcheck::new_time(&mut transcript, &z_a, &z_b, &alpha);
+        let first_proof = Sumcheck::new_time(transcript, z_a, z_b, &alpha)?;
         let first_sumcheck_msgs = first_proof.prover_messages();
+        sink.emit(ProofPart::FirstSumcheck(first_sumcheck_msgs.clone()));
         end_timer!(first_sumcheck_time);
 
         let b_challenges = tensor(&first_proof.challenges);
@@ -56,59 +169,46 @@ impl<E: Pairing> Proof<E> {
         let eta = transcript.get_challenge::<E::ScalarField>(b"eta");
         let eta2 = eta.square();
 
-        let mut abc_tensored = vec![E::ScalarField::zero(); r1cs.z.len()];
-
-        for (i, row_a) in r1cs.a.iter().enumerate() {
-            for &(val, col) in row_a {
-                abc_tensored[col] += a_challenges[i] * val;
-            }
-        }
-
-        for (i, row_b) in r1cs.b.iter().enumerate() {
-            for &(val, col) in row_b {
-                abc_tensored[col] += eta * b_challenges[i] * val;
-            }
-        }
-
-        for (i, row_c) in r1cs.c.iter().enumerate() {
-            for &(val, col) in row_c {
-                abc_tensored[col] += eta2 * c_challenges[i] * val;
-            }
-        }
+        let eta_b_challenges = b_challenges.iter().map(|&b| eta * b).collect::<Vec<_>>();
+        let eta2_c_challenges = c_challenges.iter().map(|&c| eta2 * c).collect::<Vec<_>>();
+        let abc_tensored = product_vectors_matrices(
+            &[
+                (r1cs.a.as_slice(), a_challenges.as_slice()),
+                (r1cs.b.as_slice(), eta_b_challenges.as_slice()),
+                (r1cs.c.as_slice(), eta2_c_challenges.as_slice()),
+            ],
+            r1cs.z.len(),
+        );
 
         let second_sumcheck_time = start_timer!(|| "Second sumcheck");
-        let second_proof = Sumcheck::new_time(
-            &mut transcript,
-            &abc_tensored,
-            &r1cs.z,
-            &E::ScalarField::one(),
-        );
+        let second_proof =
+            Sumcheck::new_time(transcript, &abc_tensored, &r1cs.z, &E::ScalarField::one())?;
         let second_sumcheck_msgs = second_proof.prover_messages();
+        sink.emit(ProofPart::SecondSumcheck(second_sumcheck_msgs.clone()));
         end_timer!(second_sumcheck_time);
 
         // derive the points needed from the challenges
-        let tc_base_polynomials = [&r1cs.w];
         let second_sumcheck_polynomials = [&abc_tensored, &r1cs.z];
         let tc_body_polynomials = [(
             &second_sumcheck_polynomials[..],
             &second_proof.challenges[..],
         )];
         let tensorcheck_time = start_timer!(|| "Tensorcheck");
-        let tensorcheck_proof = TensorcheckProof::new_time(
-            &mut transcript,
-            ck,
-            tc_base_polynomials,
-            tc_body_polynomials,
-        );
+        let tensorcheck_proof = match input {
+            None => TensorcheckProof::new_time(transcript, ck, [&r1cs.w], tc_body_polynomials),
+            Some(_) => {
+                TensorcheckProof::new_time(transcript, ck, [&r1cs.w, &r1cs.x], tc_body_polynomials)
+            }
+        };
+        sink.emit(ProofPart::Tensorcheck(tensorcheck_proof.clone()));
         end_timer!(tensorcheck_time);
 
-        end_timer!(snark_time);
-        Proof {
+        Ok(Proof {
             witness_commitment,
             zc_alpha,
             first_sumcheck_msgs,
             second_sumcheck_msgs,
             tensorcheck_proof,
-        }
+        })
     }
 }
@@ -1,23 +1,86 @@
 //! The verifier for the algebraic proofs.
 use ark_ec::pairing::Pairing;
+use ark_ff::Field;
 
 use crate::circuit::R1cs;
 use crate::errors::{VerificationError, VerificationResult};
-use crate::kzg::VerifierKey;
+use crate::iterable::Reverse;
+use crate::kzg::{MsmPairingCheck, PreparedVerifierKey, VerifierKey};
 use crate::misc::{
-    evaluate_le, hadamard_unsafe, ip, ip_unsafe, powers, product_matrix_vector, tensor,
+    evaluate_le_at, hadamard_unsafe, ip, ip_unsafe, powers, product_matrix_vector, tensor,
 };
-use crate::snark::Proof;
+use crate::snark::{CommittedInput, Proof, SNARK_LABEL};
 use crate::subprotocols::sumcheck::Subclaim;
-use crate::transcript::GeminiTranscript;
+use crate::transcript::{bind_committed_input, bind_context, bind_public_input, GeminiTranscript};
 use crate::PROTOCOL_NAME;
 
 impl<E: Pairing> Proof<E> {
     /// Verification function for SNARK proof.
     /// The input contains the R1CS instance and the verification key
     /// of polynomial commitment.
+    ///
+    /// The instance is padded exactly as the prover does (see [`R1cs::padded`]).
+    /// The public input `r1cs.x` is bound to the transcript,
+    /// and its contribution to $\vec z$ is evaluated by the verifier itself.
     pub fn verify(&self, r1cs: &R1cs<E::ScalarField>, vk: &VerifierKey<E>) -> VerificationResult {
         let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        self.verify_with_transcript(r1cs, vk, &mut transcript)
+    }
+
+    /// Verify a SNARK proof produced by [`Proof::new_time_with_transcript`],
+    /// deriving the challenges from `transcript`.
+    pub fn verify_with_transcript(
+        &self,
+        r1cs: &R1cs<E::ScalarField>,
+        vk: &VerifierKey<E>,
+        transcript: &mut impl GeminiTranscript,
+    ) -> VerificationResult {
+        let check = self.deferred_verify(r1cs, vk, None, transcript)?;
+        vk.check(&check.evaluate())
+            .map_err(|e| e.within("tensorcheck opening"))
+    }
+
+    /// Same as [`Proof::verify`], with a verifier key prepared once for many proofs.
+    pub fn verify_prepared(
+        &self,
+        r1cs: &R1cs<E::ScalarField>,
+        pvk: &PreparedVerifierKey<E>,
+    ) -> VerificationResult {
+        let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+        let check = self.deferred_verify(r1cs, pvk.verifier_key(), None, &mut transcript)?;
+        pvk.check(&check.evaluate())
+            .map_err(|e| e.within("tensorcheck opening"))
+    }
+
+    /// Same as [`Proof::verify_with_transcript`],
+    /// but return the final pairing check, with its multi-scalar multiplications left to compute.
+    ///
+    /// All other checks, over the scalar field, are performed.
+    /// If `input` is given, it stands in for the public input, whose values in `r1cs` are ignored.
+    pub(crate) fn deferred_verify(
+        &self,
+        r1cs: &R1cs<E::ScalarField>,
+        vk: &VerifierKey<E>,
+        input: Option<&CommittedInput<E>>,
+        transcript: &mut impl GeminiTranscript,
+    ) -> Result<MsmPairingCheck<E>, VerificationError> {
+        let base_polynomials = if input.is_some() { 2 } else { 1 };
+        if self.tensorcheck_proof.base_polynomials_evaluations.len() != base_polynomials {
+            return Err(VerificationError::Consistency {
+                check: "tensorcheck base polynomials",
+            });
+        }
+        // pad the instance as the prover does.
+        let padded = r1cs.padded();
+        let r1cs: &R1cs<E::ScalarField> = &padded;
+        // bind the context and the public input (or its commitment) to the transcript.
+        bind_context::<E, _>(transcript, SNARK_LABEL, &[r1cs.a.len(), r1cs.z.len()]);
+        match input {
+            None => {
+                bind_public_input::<E::ScalarField, _, _>(transcript, &Reverse(r1cs.x.as_slice()))
+            }
+            Some(input) => bind_committed_input(transcript, input.len, &input.commitment),
+        }
         let witness_commitment = self.witness_commitment;
 
         transcript.append_serializable(b"witness", &witness_commitment);
@@ -27,16 +90,12 @@ impl<E: Pairing> Proof<E> {
         // First sumcheck
         transcript.append_serializable(b"zc(alpha)", &self.zc_alpha);
 
-        let subclaim_1 = Subclaim::new(&mut transcript, first_sumcheck_msgs, self.zc_alpha)?;
+        let subclaim_1 = Subclaim::new(transcript, first_sumcheck_msgs, self.zc_alpha)
+            .map_err(|e| e.within("first sumcheck"))?;
 
         let eta = transcript.get_challenge::<E::ScalarField>(b"eta");
         let etas = powers(eta, 3);
 
-        let num_constraints = r1cs.a.len();
-        let tensor_challenges = tensor(&subclaim_1.challenges);
-        let alpha_powers = powers(alpha, num_constraints);
-        let hadamard_randomness = hadamard_unsafe(&tensor_challenges, &alpha_powers);
-
         // Second sumcheck
         let asserted_sum_2 = ip(
             &[
@@ -47,8 +106,8 @@ impl<E: Pairing> Proof<E> {
             &etas,
         );
 
-        let subclaim_2 =
-            Subclaim::new(&mut transcript, &self.second_sumcheck_msgs, asserted_sum_2)?;
+        let subclaim_2 = Subclaim::new(transcript, &self.second_sumcheck_msgs, asserted_sum_2)
+            .map_err(|e| e.within("second sumcheck"))?;
 
         // Tensorcheck
         let gamma = transcript.get_challenge::<E::ScalarField>(b"batch_challenge");
@@ -57,36 +116,21 @@ impl<E: Pairing> Proof<E> {
             .iter()
             .for_each(|c| transcript.append_serializable(b"commitment", c));
         let beta = transcript.get_challenge::<E::ScalarField>(b"evaluation-chal");
-        let beta_powers = powers(beta, num_constraints);
-        let minus_beta_powers = powers(-beta, num_constraints);
-
-        let a_beta_powers = product_matrix_vector(&r1cs.a, &beta_powers);
-        let b_beta_powers = product_matrix_vector(&r1cs.b, &beta_powers);
-        let c_beta_powers = product_matrix_vector(&r1cs.c, &beta_powers);
-        let a_minus_beta_powers = product_matrix_vector(&r1cs.a, &minus_beta_powers);
-        let b_minus_beta_powers = product_matrix_vector(&r1cs.b, &minus_beta_powers);
-        let c_minus_beta_powers = product_matrix_vector(&r1cs.c, &minus_beta_powers);
-
-        let m_pos = ip(
-            &[
-                ip(&a_beta_powers, &hadamard_randomness),
-                ip_unsafe(&b_beta_powers, &tensor_challenges),
-                ip(&c_beta_powers, &alpha_powers),
-            ],
-            &etas,
-        );
-        let m_neg = ip(
-            &[
-                ip(&a_minus_beta_powers, &hadamard_randomness),
-                ip_unsafe(&b_minus_beta_powers, &tensor_challenges),
-                ip(&c_minus_beta_powers, &alpha_powers),
-            ],
-            &etas,
-        );
-
-        let beta_power = beta_powers[r1cs.x.len()];
-        let x_beta = evaluate_le(&r1cs.x, &beta);
-        let x_minus_beta = evaluate_le(&r1cs.x, &-beta);
+        let [m_pos, m_neg] = evaluate_matrices(r1cs, alpha, &subclaim_1.challenges, eta, beta);
+
+        let beta_power = beta.pow([r1cs.x.len() as u64]);
+        // evaluate the public input at both points with a single pass,
+        // or take the evaluations of the committed input opened by the tensorcheck.
+        let (x_beta, x_minus_beta) = match input {
+            None => {
+                let x_evaluations = evaluate_le_at(&r1cs.x, &[beta, -beta]);
+                (x_evaluations[0], x_evaluations[1])
+            }
+            Some(_) => {
+                let x_evaluations = self.tensorcheck_proof.base_polynomials_evaluations[1];
+                (x_evaluations[1], x_evaluations[2])
+            }
+        };
         let z_pos = x_beta + beta_power * self.tensorcheck_proof.base_polynomials_evaluations[0][1];
 
         let beta_power = if (r1cs.x.len() & 1) == 0 {
@@ -99,18 +143,66 @@ impl<E: Pairing> Proof<E> {
 
         let direct_base_polynomials_evaluations =
             vec![[m_pos + gamma * z_pos, m_neg + gamma * z_neg]];
+        let base_commitments = match input {
+            None => vec![self.witness_commitment],
+            Some(input) => vec![self.witness_commitment, input.commitment],
+        };
 
-        self.tensorcheck_proof
-            .verify(
-                &mut transcript,
-                vk,
-                &[subclaim_2.final_foldings[0].to_vec()],
-                &[self.witness_commitment],
-                &direct_base_polynomials_evaluations,
-                &[subclaim_2.challenges],
-                beta,
-                gamma,
-            )
-            .map_err(|_| VerificationError)
+        self.tensorcheck_proof.deferred_msm_verify(
+            transcript,
+            vk,
+            &[subclaim_2.final_foldings[0].to_vec()],
+            &base_commitments,
+            &direct_base_polynomials_evaluations,
+            &[subclaim_2.challenges],
+            beta,
+            gamma,
+        )
     }
 }
+
+/// Evaluate at $\beta$ and $-\beta$ the linear combination
+/// $\vec r_A A + \eta \vec r_B B + \eta^2 \vec r_C C$ of the matrices of the padded `r1cs`,
+/// where $\vec r_B$ is the tensor of the `challenges` of the first sumcheck,
+/// $\vec r_C$ are the powers of `alpha`, and $\vec r_A \defeq \vec r_B \circ \vec r_C$.
+pub(crate) fn evaluate_matrices<F: Field>(
+    r1cs: &R1cs<F>,
+    alpha: F,
+    challenges: &[F],
+    eta: F,
+    beta: F,
+) -> [F; 2] {
+    let etas = powers(eta, 3);
+    let num_constraints = r1cs.a.len();
+    let tensor_challenges = tensor(challenges);
+    let alpha_powers = powers(alpha, num_constraints);
+    let hadamard_randomness = hadamard_unsafe(&tensor_challenges, &alpha_powers);
+
+    let beta_powers = powers(beta, num_constraints);
+    let minus_beta_powers = powers(-beta, num_constraints);
+
+    let a_beta_powers = product_matrix_vector(&r1cs.a, &beta_powers);
+    let b_beta_powers = product_matrix_vector(&r1cs.b, &beta_powers);
+    let c_beta_powers = product_matrix_vector(&r1cs.c, &beta_powers);
+    let a_minus_beta_powers = product_matrix_vector(&r1cs.a, &minus_beta_powers);
+    let b_minus_beta_powers = product_matrix_vector(&r1cs.b, &minus_beta_powers);
+    let c_minus_beta_powers = product_matrix_vector(&r1cs.c, &minus_beta_powers);
+
+    let m_pos = ip(
+        &[
+            ip(&a_beta_powers, &hadamard_randomness),
+            ip_unsafe(&b_beta_powers, &tensor_challenges),
+            ip(&c_beta_powers, &alpha_powers),
+        ],
+        &etas,
+    );
+    let m_neg = ip(
+        &[
+            ip(&a_minus_beta_powers, &hadamard_randomness),
+            ip_unsafe(&b_minus_beta_powers, &tensor_challenges),
+            ip(&c_minus_beta_powers, &alpha_powers),
+        ],
+        &etas,
+    );
+    [m_pos, m_neg]
+}
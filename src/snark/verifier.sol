@@ -0,0 +1,475 @@
+// SPDX-License-Identifier: MIT
+// Template of the Solidity verifier, filled by `snark::solidity::verifier_contract`.
+pragma solidity ^0.8.19;
+
+/// @title Verifier of the Gemini SNARK proofs for a fixed R1CS instance over BLS12-381.
+/// @notice Generated by ark-gemini, do not edit.
+/// Requires the BLS12-381 precompiles of EIP-2537.
+contract {{NAME}} {
+    /// The order of the scalar field.
+    uint256 internal constant R = 0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001;
+    /// The inverse of 2 modulo `R`.
+    uint256 internal constant TWO_INV = 0x39f6d3a994cebea4199cec0404d0ec02a9ded2017fff2dff7fffffff80000001;
+    /// The mask clearing the most significant bit of a challenge.
+    uint256 internal constant CHALLENGE_MASK = (1 << 255) - 1;
+
+    uint256 internal constant SHA256 = 0x02;
+    uint256 internal constant MODEXP = 0x05;
+    uint256 internal constant G1_MSM = 0x0c;
+    uint256 internal constant G2_MSM = 0x0e;
+    uint256 internal constant PAIRING = 0x0f;
+
+    /// The dimension of the padded matrices, and its logarithm.
+    uint256 internal constant DIM = {{DIM}};
+    uint256 internal constant LOG_DIM = {{LOG_DIM}};
+    /// The length of the public input.
+    uint256 internal constant NUM_PUBLIC = {{NUM_PUBLIC}};
+    /// The number of elements of the public input bound at once to the transcript.
+    uint256 internal constant PUBLIC_INPUT_CHUNK = 4096;
+
+    // The layout of the proof: points take 128 bytes, and scalars 32 bytes.
+    uint256 internal constant WITNESS = 0;
+    uint256 internal constant ZC = 128;
+    uint256 internal constant FIRST_SUMCHECK = 160;
+    uint256 internal constant SECOND_SUMCHECK = FIRST_SUMCHECK + 64 * LOG_DIM + 64;
+    uint256 internal constant FOLDED_COMMITMENTS = SECOND_SUMCHECK + 64 * LOG_DIM + 64;
+    uint256 internal constant FOLDED_EVALUATIONS = FOLDED_COMMITMENTS + 128 * (LOG_DIM - 1);
+    uint256 internal constant EVALUATION_PROOF = FOLDED_EVALUATIONS + 64 * (LOG_DIM - 1);
+    uint256 internal constant BASE_EVALUATIONS = EVALUATION_PROOF + 128;
+    uint256 internal constant PROOF_LEN = BASE_EVALUATIONS + 96;
+
+    /// The transcript bound before the public input: the domain separator, the context, and the public input length.
+    bytes internal constant PREFIX = hex"{{PREFIX}}";
+    /// The first three powers of the verification key in G1, and the first four in G2.
+    bytes internal constant VK_G1 = hex"{{VK_G1}}";
+    bytes internal constant VK_G2 = hex"{{VK_G2}}";
+    /// The entries of the matrices: the row (4 bytes), the column (4 bytes), and the value (32 bytes).
+    bytes internal constant MATRIX_A = hex"{{MATRIX_A}}";
+    bytes internal constant MATRIX_B = hex"{{MATRIX_B}}";
+    bytes internal constant MATRIX_C = hex"{{MATRIX_C}}";
+
+    /// The messages absorbed so far by the SHA-256 transcript.
+    struct Transcript {
+        bytes buf;
+        uint256 len;
+    }
+
+    /// The challenges and the subclaims of the verifier.
+    struct State {
+        uint256 alpha;
+        uint256 zc;
+        uint256[] rho1;
+        uint256 u0;
+        uint256 u1;
+        uint256 eta;
+        uint256[] rho2;
+        uint256 s0;
+        uint256 s1;
+        uint256 gamma;
+        uint256 beta;
+        uint256 openChallenge;
+    }
+
+    /// Return `true` if `proof` is valid for the public input `x`.
+    /// Reverts if `proof` or `x` are malformed.
+    function verifyProof(bytes calldata proof, uint256[] calldata x) external view returns (bool) {
+        require(proof.length == PROOF_LEN, "gemini: invalid proof length");
+        require(x.length == NUM_PUBLIC, "gemini: invalid public input length");
+        Transcript memory t = _transcript(x);
+        State memory s;
+        bool ok;
+
+        _absorb(t, "witness", _g1(proof, WITNESS));
+        s.alpha = _challenge(t, "alpha");
+        s.zc = _scalar(proof, ZC);
+        _absorb(t, "zc(alpha)", _le(s.zc));
+        (ok, s.rho1, s.u0, s.u1) = _sumcheck(t, proof, FIRST_SUMCHECK, s.zc);
+        if (!ok) {
+            return false;
+        }
+
+        s.eta = _challenge(t, "eta");
+        uint256 asserted = addmod(s.u0, mulmod(s.eta, addmod(s.u1, mulmod(s.eta, s.zc, R), R), R), R);
+        (ok, s.rho2, s.s0, s.s1) = _sumcheck(t, proof, SECOND_SUMCHECK, asserted);
+        if (!ok) {
+            return false;
+        }
+
+        s.gamma = _challenge(t, "batch_challenge");
+        for (uint256 i = 0; i < LOG_DIM - 1; i++) {
+            _absorb(t, "commitment", _g1(proof, FOLDED_COMMITMENTS + 128 * i));
+        }
+        s.beta = _challenge(t, "evaluation-chal");
+        (uint256 pos, uint256 neg) = _direct(s, proof, x);
+        uint256[] memory atSquare;
+        (ok, atSquare) = _tensorcheck(s, proof, pos, neg);
+        if (!ok) {
+            return false;
+        }
+
+        for (uint256 i = 0; i < 3; i++) {
+            _absorb(t, "eval", _le(_scalar(proof, BASE_EVALUATIONS + 32 * i)));
+        }
+        for (uint256 i = 0; i < 2 * (LOG_DIM - 1); i++) {
+            _absorb(t, "eval", _le(_scalar(proof, FOLDED_EVALUATIONS + 32 * i)));
+        }
+        s.openChallenge = _challenge(t, "open-chal");
+        return _opening(s, proof, atSquare);
+    }
+
+    /// Reduce the sumcheck starting at `offset` from `claim`,
+    /// returning whether the final foldings match, the challenges, and the final foldings.
+    function _sumcheck(Transcript memory t, bytes calldata proof, uint256 offset, uint256 claim)
+        private
+        view
+        returns (bool, uint256[] memory rho, uint256 f0, uint256 f1)
+    {
+        rho = new uint256[](LOG_DIM);
+        for (uint256 i = 0; i < LOG_DIM; i++) {
+            uint256 a = _scalar(proof, offset + 64 * i);
+            uint256 b = _scalar(proof, offset + 64 * i + 32);
+            _absorb(t, "evaluations", abi.encodePacked(_reverse(a), _reverse(b)));
+            uint256 r = _challenge(t, "challenge");
+            rho[i] = r;
+            // evaluate a + b X + c X^2 at r.
+            uint256 c = addmod(claim, R - a, R);
+            claim = addmod(addmod(a, mulmod(r, b, R), R), mulmod(c, mulmod(r, r, R), R), R);
+        }
+        f0 = _scalar(proof, offset + 64 * LOG_DIM);
+        f1 = _scalar(proof, offset + 64 * LOG_DIM + 32);
+        _absorb(t, "final-folding", _le(f0));
+        _absorb(t, "final-folding", _le(f1));
+        return (mulmod(f0, f1, R) == claim, rho, f0, f1);
+    }
+
+    /// The evaluations, at `beta` and `-beta`, of the base polynomial batched with `gamma`,
+    /// computed from the matrices and the public input.
+    function _direct(State memory s, bytes calldata proof, uint256[] calldata x)
+        private
+        view
+        returns (uint256 pos, uint256 neg)
+    {
+        (uint256 mPos, uint256 mNeg) = _matrices(s);
+        (uint256 zPos, uint256 zNeg) = _statement(x, s.beta, proof);
+        pos = addmod(mPos, mulmod(s.gamma, zPos, R), R);
+        neg = addmod(mNeg, mulmod(s.gamma, zNeg, R), R);
+    }
+
+    /// Evaluate at `beta` and `-beta` the polynomial of the rows of `r_A A + eta r_B B + eta^2 r_C C`.
+    function _matrices(State memory s) private pure returns (uint256 pos, uint256 neg) {
+        uint256[] memory betaPowers = _powers(s.beta, DIM);
+        uint256[] memory alphaPowers = _powers(s.alpha, DIM);
+        uint256[] memory weights = _tensor(s.rho1);
+        (pos, neg) = _matrix(MATRIX_C, alphaPowers, betaPowers);
+        (uint256 p, uint256 q) = _matrix(MATRIX_B, weights, betaPowers);
+        pos = addmod(p, mulmod(s.eta, pos, R), R);
+        neg = addmod(q, mulmod(s.eta, neg, R), R);
+        for (uint256 i = 0; i < DIM; i++) {
+            weights[i] = mulmod(weights[i], alphaPowers[i], R);
+        }
+        (p, q) = _matrix(MATRIX_A, weights, betaPowers);
+        pos = addmod(p, mulmod(s.eta, pos, R), R);
+        neg = addmod(q, mulmod(s.eta, neg, R), R);
+    }
+
+    /// Return the sums, over the entries `(i, j, v)` of `m`, of `v weights[i] beta^j` and `v weights[i] (-beta)^j`.
+    function _matrix(bytes memory m, uint256[] memory weights, uint256[] memory betaPowers)
+        private
+        pure
+        returns (uint256 pos, uint256 neg)
+    {
+        for (uint256 offset = 0; offset < m.length; offset += 40) {
+            uint256 row;
+            uint256 col;
+            uint256 value;
+            assembly {
+                let word := mload(add(add(m, 32), offset))
+                row := shr(224, word)
+                col := and(shr(192, word), 0xffffffff)
+                value := mload(add(add(m, 40), offset))
+            }
+            uint256 term = mulmod(mulmod(value, weights[row], R), betaPowers[col], R);
+            pos = addmod(pos, term, R);
+            neg = (col & 1) == 0 ? addmod(neg, term, R) : addmod(neg, R - term, R);
+        }
+    }
+
+    /// Evaluate at `beta` and `-beta` the vector `z`, from the public input and the witness evaluations.
+    function _statement(uint256[] calldata x, uint256 beta, bytes calldata proof)
+        private
+        view
+        returns (uint256 pos, uint256 neg)
+    {
+        uint256 minusBeta = R - beta;
+        for (uint256 i = x.length; i > 0; i--) {
+            pos = addmod(mulmod(pos, beta, R), x[i - 1], R);
+            neg = addmod(mulmod(neg, minusBeta, R), x[i - 1], R);
+        }
+        uint256 shift = _expmod(beta, x.length);
+        pos = addmod(pos, mulmod(shift, _scalar(proof, BASE_EVALUATIONS + 32), R), R);
+        if ((x.length & 1) == 1) {
+            shift = R - shift;
+        }
+        neg = addmod(neg, mulmod(shift, _scalar(proof, BASE_EVALUATIONS + 64), R), R);
+    }
+
+    /// Check the foldings of the tensorcheck, starting from the evaluations `pos` and `neg`,
+    /// and return the evaluations at `beta^2` of the committed polynomials.
+    function _tensorcheck(State memory s, bytes calldata proof, uint256 pos, uint256 neg)
+        private
+        view
+        returns (bool, uint256[] memory atSquare)
+    {
+        uint256 twoBetaInv = _expmod(addmod(s.beta, s.beta, R), R - 2);
+        atSquare = new uint256[](LOG_DIM);
+        atSquare[0] = _scalar(proof, BASE_EVALUATIONS);
+        for (uint256 i = 0; i < LOG_DIM - 1; i++) {
+            atSquare[i + 1] = _fold(pos, neg, s.rho2[i], twoBetaInv);
+            pos = _scalar(proof, FOLDED_EVALUATIONS + 64 * i);
+            neg = _scalar(proof, FOLDED_EVALUATIONS + 64 * i + 32);
+        }
+        uint256 expected = addmod(s.s0, mulmod(s.gamma, s.s1, R), R);
+        return (_fold(pos, neg, s.rho2[LOG_DIM - 1], twoBetaInv) == expected, atSquare);
+    }
+
+    /// The evaluation at `beta^2` of the folding with `r` of a polynomial evaluating to `pos` and `neg` at `beta` and `-beta`.
+    function _fold(uint256 pos, uint256 neg, uint256 r, uint256 twoBetaInv) private pure returns (uint256) {
+        uint256 even = mulmod(addmod(pos, neg, R), TWO_INV, R);
+        uint256 odd = mulmod(mulmod(addmod(pos, R - neg, R), r, R), twoBetaInv, R);
+        return addmod(even, odd, R);
+    }
+
+    /// Check the evaluation proof of the committed polynomials at `beta^2`, `beta`, and `-beta`.
+    function _opening(State memory s, bytes calldata proof, uint256[] memory atSquare) private view returns (bool) {
+        uint256[3] memory v;
+        bytes memory terms = new bytes(160 * (LOG_DIM + 3));
+        uint256 power = 1;
+        for (uint256 k = 0; k < LOG_DIM; k++) {
+            uint256 commitment = k == 0 ? WITNESS : FOLDED_COMMITMENTS + 128 * (k - 1);
+            uint256 evaluations = k == 0 ? BASE_EVALUATIONS + 32 : FOLDED_EVALUATIONS + 64 * (k - 1);
+            v[0] = addmod(v[0], mulmod(power, atSquare[k], R), R);
+            v[1] = addmod(v[1], mulmod(power, _scalar(proof, evaluations), R), R);
+            v[2] = addmod(v[2], mulmod(power, _scalar(proof, evaluations + 32), R), R);
+            bytes memory point = proof[commitment:commitment + 128];
+            _term(terms, k, point, 0, R - power);
+            power = mulmod(power, s.openChallenge, R);
+        }
+        uint256[3] memory c = _interpolate(s.beta, v);
+        bytes memory g1 = VK_G1;
+        for (uint256 i = 0; i < 3; i++) {
+            _term(terms, LOG_DIM + i, g1, 128 * i, c[i]);
+        }
+        // terms sum to the opposite of the left-hand side of the pairing equation.
+        bytes memory lhs = _precompile(G1_MSM, terms, 128);
+        return _pairing(lhs, s.beta, proof);
+    }
+
+    /// The coefficients of the polynomial of degree 2 evaluating to `v` at `beta^2`, `beta`, and `-beta`.
+    function _interpolate(uint256 beta, uint256[3] memory v) private view returns (uint256[3] memory c) {
+        uint256 beta2 = mulmod(beta, beta, R);
+        c[1] = mulmod(addmod(v[1], R - v[2], R), _expmod(addmod(beta, beta, R), R - 2), R);
+        uint256 even = mulmod(addmod(v[1], v[2], R), TWO_INV, R);
+        uint256 numerator = addmod(addmod(v[0], R - mulmod(c[1], beta2, R), R), R - even, R);
+        uint256 denominator = addmod(mulmod(beta2, beta2, R), R - beta2, R);
+        c[2] = mulmod(numerator, _expmod(denominator, R - 2), R);
+        c[0] = addmod(even, R - mulmod(c[2], beta2, R), R);
+    }
+
+    /// Check that e(-lhs, H) e(proof, Z) = 1, where `Z` commits to the vanishing polynomial of the evaluation points.
+    function _pairing(bytes memory lhs, uint256 beta, bytes calldata proof) private view returns (bool) {
+        uint256 beta2 = mulmod(beta, beta, R);
+        // (X - beta^2)(X - beta)(X + beta) = X^3 - beta^2 X^2 - beta^2 X + beta^4.
+        uint256[4] memory zeros = [mulmod(beta2, beta2, R), R - beta2, R - beta2, 1];
+        bytes memory g2 = VK_G2;
+        bytes memory terms = new bytes(288 * 4);
+        for (uint256 i = 0; i < 4; i++) {
+            _copy(terms, 288 * i, g2, 256 * i, 256);
+            _store(terms, 288 * i + 256, zeros[i]);
+        }
+        bytes memory z = _precompile(G2_MSM, terms, 256);
+        bytes memory pi = proof[EVALUATION_PROOF:EVALUATION_PROOF + 128];
+        bytes memory input = new bytes(768);
+        _copy(input, 0, lhs, 0, 128);
+        _copy(input, 128, g2, 0, 256);
+        _copy(input, 384, pi, 0, 128);
+        _copy(input, 512, z, 0, 256);
+        bytes memory result = _precompile(PAIRING, input, 32);
+        return uint256(bytes32(result)) == 1;
+    }
+
+    /// Create the transcript, binding the context and the public input `x`.
+    function _transcript(uint256[] calldata x) private pure returns (Transcript memory t) {
+        t.buf = new bytes(PREFIX.length + 48 * x.length + 64 + 4 * PROOF_LEN + 136 * LOG_DIM + 1024);
+        _write(t, PREFIX);
+        // the public input is bound from the last element, in chunks.
+        uint256 size;
+        for (uint256 end = x.length; end > 0; end -= size) {
+            size = end < PUBLIC_INPUT_CHUNK ? end : PUBLIC_INPUT_CHUNK;
+            _frame(t, "public-input", 8 + 32 * size);
+            _write(t, abi.encodePacked(_u64le(size)));
+            for (uint256 i = end; i > end - size; i--) {
+                require(x[i - 1] < R, "gemini: non-canonical public input");
+                _write(t, _le(x[i - 1]));
+            }
+        }
+    }
+
+    /// Absorb the `label`ed message `message`, prefixed by the lengths of both.
+    function _absorb(Transcript memory t, bytes memory label, bytes memory message) private pure {
+        _frame(t, label, message.length);
+        _write(t, message);
+    }
+
+    /// Absorb the label `label`, and the length `len` of the message following it.
+    function _frame(Transcript memory t, bytes memory label, uint256 len) private pure {
+        _write(t, abi.encodePacked(_u64le(label.length), label, _u64le(len)));
+    }
+
+    /// Derive the challenge `label` from the transcript, and absorb it.
+    function _challenge(Transcript memory t, bytes memory label) private view returns (uint256 c) {
+        uint256 len = t.len;
+        for (uint256 counter = 0; ; counter++) {
+            _write(t, abi.encodePacked("challenge", label, _u64le(counter), bytes1(0)));
+            bytes memory buf = t.buf;
+            uint256 end = t.len;
+            bytes32 h;
+            assembly {
+                if iszero(staticcall(gas(), SHA256, add(buf, 32), end, 0x00, 32)) {
+                    revert(0, 0)
+                }
+                h := mload(0x00)
+            }
+            t.len = len;
+            c = _reverse(uint256(h)) & CHALLENGE_MASK;
+            if (c < R) {
+                break;
+            }
+        }
+        _absorb(t, label, _le(c));
+    }
+
+    /// Append `data` to the transcript.
+    function _write(Transcript memory t, bytes memory data) private pure {
+        bytes memory buf = t.buf;
+        uint256 len = t.len;
+        // the last word written may exceed `data` by 31 bytes.
+        require(len + data.length + 32 <= buf.length, "gemini: transcript overflow");
+        assembly {
+            let dst := add(add(buf, 32), len)
+            let src := add(data, 32)
+            for { let i := 0 } lt(i, mload(data)) { i := add(i, 32) } {
+                mstore(add(dst, i), mload(add(src, i)))
+            }
+        }
+        t.len = len + data.length;
+    }
+
+    /// The scalar at `offset` in `proof`, encoded in 32 big-endian bytes.
+    function _scalar(bytes calldata proof, uint256 offset) private pure returns (uint256 v) {
+        v = uint256(bytes32(proof[offset:offset + 32]));
+        require(v < R, "gemini: non-canonical scalar");
+    }
+
+    /// The uncompressed serialization of the point at `offset` in `proof`,
+    /// from the encoding of EIP-2537 (whose coordinates are padded to 64 bytes).
+    function _g1(bytes calldata proof, uint256 offset) private pure returns (bytes memory) {
+        bytes calldata p = proof[offset:offset + 128];
+        require(bytes16(p[0:16]) == 0 && bytes16(p[64:80]) == 0, "gemini: invalid point encoding");
+        if (bytes32(p[16:48]) == 0 && bytes16(p[48:64]) == 0 && bytes32(p[80:112]) == 0 && bytes16(p[112:128]) == 0) {
+            // the point at infinity is flagged in the first byte.
+            bytes memory infinity = new bytes(96);
+            infinity[0] = 0x40;
+            return infinity;
+        }
+        return abi.encodePacked(p[16:64], p[80:128]);
+    }
+
+    /// The little-endian encoding of the scalar `v`.
+    function _le(uint256 v) private pure returns (bytes memory) {
+        return abi.encodePacked(_reverse(v));
+    }
+
+    /// The little-endian encoding of the 64-bit integer `v`.
+    function _u64le(uint256 v) private pure returns (bytes8) {
+        return bytes8(bytes32(_reverse(v)));
+    }
+
+    /// Reverse the order of the bytes of `v`.
+    function _reverse(uint256 v) private pure returns (uint256 r) {
+        for (uint256 i = 0; i < 32; i++) {
+            r = (r << 8) | (v & 0xff);
+            v >>= 8;
+        }
+    }
+
+    /// Return `(1, x, ..., x^{len-1})`.
+    function _powers(uint256 x, uint256 len) private pure returns (uint256[] memory p) {
+        p = new uint256[](len);
+        p[0] = 1;
+        for (uint256 i = 1; i < len; i++) {
+            p[i] = mulmod(p[i - 1], x, R);
+        }
+    }
+
+    /// Return the tensor product of `(1, rho_i)`, in little-endian order.
+    function _tensor(uint256[] memory rho) private pure returns (uint256[] memory t) {
+        t = new uint256[](1 << rho.length);
+        t[0] = 1;
+        for (uint256 i = 0; i < rho.length; i++) {
+            for (uint256 j = 0; j < (1 << i); j++) {
+                t[(1 << i) + j] = mulmod(t[j], rho[i], R);
+            }
+        }
+    }
+
+    /// Compute `base^e` modulo `R`.
+    function _expmod(uint256 base, uint256 e) private view returns (uint256 result) {
+        bytes memory input = abi.encodePacked(uint256(32), uint256(32), uint256(32), base, e, R);
+        result = uint256(bytes32(_precompile(MODEXP, input, 32)));
+    }
+
+    /// Write, as the `index`-th term of the multi-scalar multiplication `terms`,
+    /// the point at `offset` in `points` and the scalar `scalar`.
+    function _term(bytes memory terms, uint256 index, bytes memory points, uint256 offset, uint256 scalar)
+        private
+        pure
+    {
+        _copy(terms, 160 * index, points, offset, 128);
+        _store(terms, 160 * index + 128, scalar);
+    }
+
+    /// Copy `len` bytes (a multiple of 32) of `src` from `srcOffset` into `dst` at `dstOffset`.
+    function _copy(bytes memory dst, uint256 dstOffset, bytes memory src, uint256 srcOffset, uint256 len)
+        private
+        pure
+    {
+        require(len % 32 == 0 && dstOffset + len <= dst.length && srcOffset + len <= src.length);
+        for (uint256 i = 0; i < len; i += 32) {
+            _store(dst, dstOffset + i, _load(src, srcOffset + i));
+        }
+    }
+
+    function _load(bytes memory b, uint256 offset) private pure returns (uint256 v) {
+        assembly {
+            v := mload(add(add(b, 32), offset))
+        }
+    }
+
+    function _store(bytes memory b, uint256 offset, uint256 v) private pure {
+        require(offset + 32 <= b.length);
+        assembly {
+            mstore(add(add(b, 32), offset), v)
+        }
+    }
+
+    /// Call the precompile `addr` on `input`, expecting `outLen` bytes.
+    function _precompile(uint256 addr, bytes memory input, uint256 outLen) private view returns (bytes memory out) {
+        out = new bytes(outLen);
+        bool ok;
+        assembly {
+            ok := staticcall(gas(), addr, add(input, 32), mload(input), add(out, 32), outLen)
+            ok := and(ok, eq(returndatasize(), outLen))
+        }
+        require(ok, "gemini: precompile failed");
+    }
+}
@@ -5,8 +5,9 @@ use ark_std::boxed::Box;
 use ark_std::vec::Vec;
 use ark_std::One;
 
-use merlin::Transcript;
 
+use crate::config::ProverConfig;
+use crate::errors::GeminiResult;
 use crate::iterable::Iterable;
 use crate::kzg::CommitterKeyStream;
 use crate::misc::evaluate_be;
@@ -32,18 +33,19 @@ impl<'a, E: Pairing, S: Iterable<Item = E::ScalarField>>
     /// Create a new (single) entry product arugment
     /// using the commiter key `ck` and the stream `v`, whose grand product is `claimed_product`.
     pub fn new_elastic<SG>(
-        transcript: &mut Transcript,
+        transcript: &mut impl GeminiTranscript,
         ck: &CommitterKeyStream<E, SG>,
         v: &'a S,
         claimed_product: E::ScalarField,
-    ) -> Self
+    ) -> GeminiResult<Self>
     where
         SG: Iterable,
         SG::Item: Borrow<E::G1Affine>,
     {
+        trace_span!("entry_product", len = v.len());
         let (rrot_v, acc_v) = entry_product_streams(v);
 
-        let acc_v_commitments = vec![ck.commit(&acc_v)];
+        let acc_v_commitments = vec![ck.commit(&acc_v)?];
         transcript.append_serializable(b"acc_v", &acc_v_commitments[0]);
 
         let chal = transcript.get_challenge::<E::ScalarField>(b"ep-chal");
@@ -52,16 +54,16 @@ impl<'a, E: Pairing, S: Iterable<Item = E::ScalarField>>
             chal * evaluate_be(acc_v.iter(), &chal) + claimed_product
                 - chal.pow(&[acc_v.len() as u64]),
         ];
-        let provers = vec![ElasticProver::new(acc_v, rrot_v, chal)];
+        let provers = vec![ElasticProver::new(acc_v, rrot_v, chal)?];
         let msgs = ProverMsgs {
             acc_v_commitments,
             claimed_sumchecks,
         };
-        EntryProduct {
+        Ok(EntryProduct {
             provers,
             chal,
             msgs,
-        }
+        })
     }
 }
 
@@ -70,11 +72,12 @@ macro_rules! impl_elastic_batch {
         #[allow(non_snake_case)]
         #[allow(unused_assignments)]
         pub fn $name<SG, $($B),*>(
-            transcript: &mut Transcript,
+            transcript: &mut impl GeminiTranscript,
             ck: &CommitterKeyStream<E, SG>,
+            config: &ProverConfig,
             vs: ($(&'a $B,)*),
             claimed_products: &[E::ScalarField],
-        ) -> Self
+        ) -> GeminiResult<Self>
         where
             SG: Iterable,
             SG::Item: Borrow<E::G1Affine>,
@@ -83,11 +86,12 @@ macro_rules! impl_elastic_batch {
             )*
 
         {
+            trace_span!("entry_product", instances = claimed_products.len());
             let ($($B,)*) = vs;
             let mut acc_v_commitments = Vec::new();
             $(
                 let acc_v = ProductStream::new($B);
-                let acc_v_commitment = ck.commit(&acc_v);
+                let acc_v_commitment = ck.commit_chunked(&acc_v, config.chunk_size)?;
                 transcript.append_serializable(b"acc_v", &acc_v_commitment);
                 acc_v_commitments.push(acc_v_commitment);
             )*
@@ -109,7 +113,7 @@ macro_rules! impl_elastic_batch {
                 let claimed_sumcheck =  acc_v_chal * chal + claimed_product - chal_n;
 
                 claimed_sumchecks.push(claimed_sumcheck);
-                let sumcheck_prover = ElasticProver::new(acc_v, rrot_v, chal);
+                let sumcheck_prover = ElasticProver::with_config(acc_v, rrot_v, chal, config)?;
                 provers.push(Box::new(sumcheck_prover));
             )*
 
@@ -118,7 +122,7 @@ macro_rules! impl_elastic_batch {
                 // XXXX. should we send also the claimed sumchecks?
                 claimed_sumchecks,
             };
-            EntryProduct { msgs, chal, provers }
+            Ok(EntryProduct { msgs, chal, provers })
         }
     };
 }
@@ -45,12 +45,19 @@ mod tests;
 /// Sometimes the verifier already knows the entry product result.
 /// For this reason, the product $t$ is never sent or added to the transcript.
 /// It is expected that the developer takes care of it in the upper protocol layer.
-#[derive(CanonicalSerialize, Debug, PartialEq, Eq)]
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
 pub struct ProverMsgs<E: Pairing> {
     pub acc_v_commitments: Vec<Commitment<E>>,
     pub claimed_sumchecks: Vec<E::ScalarField>,
 }
 
+impl<E: Pairing> ProverMsgs<E> {
+    /// Return `true` if each accumulated product comes with its claimed sumcheck.
+    pub(crate) fn is_well_formed(&self) -> bool {
+        self.acc_v_commitments.len() == self.claimed_sumchecks.len()
+    }
+}
+
 /// The entryproduct transcript and subclaims.
 pub struct EntryProduct<E: Pairing, P: Prover<E::ScalarField>> {
     /// The messages sent by the prover.
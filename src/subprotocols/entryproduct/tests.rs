@@ -48,7 +48,8 @@ fn test_entry_product_consistency() {
     let time_transcript = &mut Transcript::new(b"test");
     let ep_time = EntryProduct::new_time(time_transcript, &ck, &v, product);
     let elastic_transcript = &mut Transcript::new(b"test");
-    let ep_space = EntryProduct::new_elastic(elastic_transcript, &stream_ck, &v_stream, product);
+    let ep_space =
+        EntryProduct::new_elastic(elastic_transcript, &stream_ck, &v_stream, product).unwrap();
     assert_eq!(ep_time.msgs, ep_space.msgs)
 }
 
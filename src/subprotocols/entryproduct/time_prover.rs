@@ -2,7 +2,6 @@ use ark_ec::pairing::Pairing;
 use ark_ff::Field;
 use ark_std::boxed::Box;
 use ark_std::vec::Vec;
-use merlin::Transcript;
 
 use super::{EntryProduct, ProverMsgs};
 use crate::kzg::CommitterKey;
@@ -59,12 +58,13 @@ impl<E: Pairing> EntryProduct<E, Box<dyn Prover<E::ScalarField>>> {
     /// # Panics
     /// If the length of the claimed products differs from the length of `vs`.
     pub fn new_time_batch(
-        transcript: &mut Transcript,
+        transcript: &mut impl GeminiTranscript,
         ck: &CommitterKey<E>,
         vs: &[Vec<E::ScalarField>],
         claimed_products: &[E::ScalarField],
     ) -> Self {
         assert_eq!(vs.len(), claimed_products.len());
+        trace_span!("entry_product", instances = vs.len());
 
         // XXX. we do not really need to store monic_vs, we can just extend every element of vs with 1.
         let monic_vs = vs.iter().map(|v| monic(v)).collect::<Vec<_>>();
@@ -115,11 +115,12 @@ impl<E: Pairing> EntryProduct<E, Box<dyn Prover<E::ScalarField>>> {
 
     /// Creates a new grand product argument using the time prover.
     pub fn new_time(
-        transcript: &mut Transcript,
+        transcript: &mut impl GeminiTranscript,
         ck: &CommitterKey<E>,
         v: &[E::ScalarField],
         claimed_product: E::ScalarField,
     ) -> Self {
+        trace_span!("entry_product", len = v.len());
         let monic_v = monic(v);
         let rrot_v = right_rotation(&monic_v);
         let acc_v = accumulated_product(&monic_v);
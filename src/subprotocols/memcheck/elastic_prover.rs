@@ -0,0 +1,124 @@
+use ark_ec::pairing::Pairing;
+use ark_ff::Field;
+use ark_std::borrow::Borrow;
+use ark_std::boxed::Box;
+use ark_std::vec::Vec;
+
+use super::{Fingerprint, MemoryCheck};
+use crate::config::ProverConfig;
+use crate::errors::GeminiResult;
+use crate::iterable::Iterable;
+use crate::kzg::{Commitment, CommitterKeyStream};
+use crate::misc::evaluate_be;
+use crate::subprotocols::entryproduct::streams::{ProductStream, RightRotationStreamer};
+use crate::subprotocols::entryproduct::{self, EntryProduct};
+use crate::subprotocols::sumcheck::{ElasticProver, Prover};
+use crate::transcript::GeminiTranscript;
+
+/// Commit to the accumulated products of the stream `v`.
+fn commit_products<E, SG, S>(
+    ck: &CommitterKeyStream<E, SG>,
+    config: &ProverConfig,
+    v: &S,
+) -> GeminiResult<Commitment<E>>
+where
+    E: Pairing,
+    SG: Iterable,
+    SG::Item: Borrow<E::G1Affine>,
+    S: Iterable<Item = E::ScalarField>,
+{
+    ck.commit_chunked(
+        &ProductStream::<E::ScalarField, _>::new(v),
+        config.chunk_size,
+    )
+}
+
+/// Return the claimed sumcheck of the entry product of the stream `v`, whose product is `product`,
+/// together with its sumcheck prover.
+fn product_prover<'a, F, S>(
+    v: &'a S,
+    product: F,
+    chal: F,
+    config: &ProverConfig,
+) -> GeminiResult<(F, Box<dyn Prover<F> + 'a>)>
+where
+    F: Field,
+    S: Iterable<Item = F>,
+{
+    let rrot_v = RightRotationStreamer::new(v, F::one());
+    let acc_v = ProductStream::<F, _>::new(v);
+    let acc_v_chal = evaluate_be(acc_v.iter(), &chal);
+    let claimed_sumcheck = acc_v_chal * chal + product - chal.pow(&[acc_v.len() as u64]);
+    let prover = ElasticProver::with_config(acc_v, rrot_v, chal, config)?;
+    Ok((claimed_sumcheck, Box::new(prover)))
+}
+
+impl<'a, E: Pairing> MemoryCheck<E, Box<dyn Prover<E::ScalarField> + 'a>> {
+    /// Prove that the reads are consistent, using the elastic prover over the streams of fingerprints `vs`
+    /// of $I, R, W, F$ (see [`memcheck_streams`](super::streams::memcheck_streams)),
+    /// computed with `fingerprint`.
+    ///
+    /// The `fingerprint` must be sampled with [`Fingerprint::new`]
+    /// after committing to the memory, the reads, and their timestamps.
+    pub fn new_elastic<SG, S0, S1, S2, S3>(
+        transcript: &mut impl GeminiTranscript,
+        ck: &CommitterKeyStream<E, SG>,
+        config: &ProverConfig,
+        fingerprint: Fingerprint<E::ScalarField>,
+        vs: (&'a S0, &'a S1, &'a S2, &'a S3),
+    ) -> GeminiResult<Self>
+    where
+        SG: Iterable,
+        SG::Item: Borrow<E::G1Affine>,
+        S0: Iterable<Item = E::ScalarField>,
+        S1: Iterable<Item = E::ScalarField>,
+        S2: Iterable<Item = E::ScalarField>,
+        S3: Iterable<Item = E::ScalarField>,
+    {
+        let (init, read, write, audit) = vs;
+        trace_span!("memory_check", memory = init.len(), reads = read.len());
+        let products: Vec<E::ScalarField> = vec![
+            init.iter().product(),
+            read.iter().product(),
+            write.iter().product(),
+            audit.iter().product(),
+        ];
+        products
+            .iter()
+            .for_each(|product| transcript.append_serializable(b"memcheck-product", product));
+
+        let acc_v_commitments = vec![
+            commit_products(ck, config, init)?,
+            commit_products(ck, config, read)?,
+            commit_products(ck, config, write)?,
+            commit_products(ck, config, audit)?,
+        ];
+        acc_v_commitments.iter().for_each(|acc_v_commitment| {
+            transcript.append_serializable(b"acc_v", acc_v_commitment)
+        });
+        let chal = transcript.get_challenge::<E::ScalarField>(b"ep-chal");
+
+        let (claimed_sumchecks, provers): (Vec<_>, Vec<_>) = [
+            product_prover(init, products[0], chal, config)?,
+            product_prover(read, products[1], chal, config)?,
+            product_prover(write, products[2], chal, config)?,
+            product_prover(audit, products[3], chal, config)?,
+        ]
+        .into_iter()
+        .unzip();
+
+        let msgs = entryproduct::ProverMsgs {
+            acc_v_commitments,
+            claimed_sumchecks,
+        };
+        Ok(MemoryCheck {
+            fingerprint,
+            products,
+            entry_product: EntryProduct {
+                msgs,
+                chal,
+                provers,
+            },
+        })
+    }
+}
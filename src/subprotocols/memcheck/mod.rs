@@ -1,5 +1,263 @@
-//!  Memory-checking lookup sub-protocol.
+//! The offline memory-checking argument.
 //!
-//! This module is temporarily disabled and in development.
+//! Offline memory checking
+//! (of [Blum et al.](https://doi.org/10.1007/BF01185212), as used in [spartan](https://eprint.iacr.org/2019/550.pdf))
+//! proves that a sequence of reads from a read-only memory $\vec m \in \FF^M$ is consistent,
+//! that is, that each value $v_k$ read at the address $a_k$ is $m_{a_k}$.
+//! Each read of an address comes with its _read timestamp_ $r_k$, the number of previous reads of $a_k$,
+//! and is followed by a write of the same value with timestamp $r_k + 1$;
+//! the _final timestamp_ $f_i$ of each address $i$ counts its reads.
+//! The reads are consistent if and only if the tuples
+//! $$
+//! \begin{aligned}
+//! I &\defeq \\{(i, m_i, 0)\\}_i &
+//! W &\defeq \\{(a_k, v_k, r_k + 1)\\}_k \\\\
+//! R &\defeq \\{(a_k, v_k, r_k)\\}_k &
+//! F &\defeq \\{(i, m_i, f_i)\\}_i
+//! \end{aligned}
+//! $$
+//! satisfy the multiset equality $I \cup W = R \cup F$.
+//! Each tuple is hashed into the [`Fingerprint`] $h(a, v, t) \defeq \tau - (a \gamma^2 + v \gamma + t)$,
+//! for challenges $\gamma, \tau$ sent by the verifier,
+//! and the multiset equality reduces to
+//! $$
+//! \prod_{x \in I} h(x) \prod_{x \in W} h(x) = \prod_{x \in R} h(x) \prod_{x \in F} h(x),
+//! $$
+//! four products proven at once with an [`EntryProduct`] over the vectors of fingerprints.
 //!
-pub mod streams;
\ No newline at end of file
+//! The memory can be any vector of the prover (e.g. a table, or the witness of a circuit)
+//! and the addresses any index into it, hence the argument serves RAM-like lookups beyond the SNARKs of this crate.
+//!
+//! # Note
+//!
+//! The memory, the reads, and their timestamps must be committed (or known to the verifier)
+//! before sampling the [`Fingerprint`].
+//! As for the [`EntryProduct`], the argument reduces to the sumcheck claims of the entry products
+//! (see [`Subclaim`]): proving them, and the evaluations of the committed vectors they require,
+//! is left to the upper protocol layer.
+//! The fingerprints are linear in the addresses, the values, and the timestamps, so their evaluations
+//! follow from the evaluations of the committed vectors and of the polynomial of the addresses $\sum_i i x^i$
+//! (see [`evaluate_index_poly`](crate::misc::evaluate_index_poly)).
+//!
+//! # Committed tables
+//!
+//! The memory can also be a table published by a third party as a [`CommittedTable`]
+//! (e.g., a registry shared by many circuits), of which the prover holds the opening.
+//! The commitment is bound to the transcript before sampling the [`Fingerprint`]
+//! (see [`Fingerprint::new_with_table`]),
+//! and the evaluations of the memory are opened against the published commitment,
+//! hence the table is never part of the index nor committed anew.
+use ark_ec::pairing::Pairing;
+use ark_ff::Field;
+use ark_serialize::*;
+use ark_std::vec::Vec;
+
+use crate::errors::{GeminiError, GeminiResult, VerificationError};
+use crate::kzg::{Commitment, CommitterKey};
+use crate::subprotocols::entryproduct::{self, EntryProduct};
+use crate::subprotocols::sumcheck::Prover;
+use crate::transcript::GeminiTranscript;
+
+mod elastic_prover;
+pub mod streams;
+pub mod time_prover;
+
+#[cfg(test)]
+mod tests;
+
+/// The challenges $\gamma, \tau$ of the verifier, hashing each tuple (address, value, timestamp) into a field element.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Fingerprint<F> {
+    /// The challenge separating the components of the tuple.
+    pub gamma: F,
+    /// The challenge shifting the fingerprints.
+    pub tau: F,
+}
+
+impl<F: Field> Fingerprint<F> {
+    /// Sample the challenges from the transcript,
+    /// once the memory, the reads, and their timestamps are committed.
+    pub fn new(transcript: &mut impl GeminiTranscript) -> Self {
+        let gamma = transcript.get_challenge(b"memcheck-gamma");
+        let tau = transcript.get_challenge(b"memcheck-tau");
+        Fingerprint { gamma, tau }
+    }
+
+    /// Same as [`Fingerprint::new`], for a memory provided as the committed `table`:
+    /// the commitment is bound to the transcript before sampling the challenges.
+    pub fn new_with_table<E: Pairing<ScalarField = F>>(
+        transcript: &mut impl GeminiTranscript,
+        table: &CommittedTable<E>,
+    ) -> Self {
+        transcript.append_serializable(b"memcheck-table", table);
+        Self::new(transcript)
+    }
+
+    /// The fingerprint $h(a, v, t)$ of the tuple (`address`, `value`, `timestamp`).
+    ///
+    /// The components can also be evaluations of the polynomials of the addresses, the values, and the timestamps,
+    /// in which case the fingerprint is the evaluation of the polynomial of the fingerprints.
+    #[inline]
+    pub fn hash(&self, address: F, value: F, timestamp: F) -> F {
+        self.tau - (address * self.gamma.square() + value * self.gamma + timestamp)
+    }
+
+    /// Same as [`Fingerprint::hash`], with integer addresses and timestamps.
+    #[inline]
+    pub(crate) fn hash_at(&self, address: usize, value: F, timestamp: usize) -> F {
+        self.hash(F::from(address as u64), value, F::from(timestamp as u64))
+    }
+}
+
+/// A table published as a commitment to its values,
+/// to be used as the memory of the argument by provers holding its opening.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommittedTable<E: Pairing> {
+    /// The length of the table.
+    pub len: usize,
+    /// The commitment to the (little-endian) values of the table.
+    pub commitment: Commitment<E>,
+}
+
+impl<E: Pairing> CommittedTable<E> {
+    /// Commit to the values of `table`.
+    pub fn new(ck: &CommitterKey<E>, table: &[E::ScalarField]) -> Self {
+        CommittedTable {
+            len: table.len(),
+            commitment: ck.commit(table),
+        }
+    }
+
+    /// Fail unless the commitment opens to `table`.
+    pub fn ensure_opening(
+        &self,
+        ck: &CommitterKey<E>,
+        table: &[E::ScalarField],
+    ) -> GeminiResult<()> {
+        GeminiError::ensure_len("table", self.len, table.len())?;
+        GeminiError::ensure_key(table.len(), ck.powers_of_g.len())?;
+        if ck.commit(table) != self.commitment {
+            return Err(GeminiError::MalformedInstance {
+                reason: "the committed table does not open to the memory",
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Given the `addresses` read from a memory of length `memory_len`,
+/// return the read timestamps (the number of previous reads of each address)
+/// and the final timestamps (the number of reads of each address of the memory).
+///
+/// The reads happen from the first address of `addresses`.
+///
+/// # Panics
+/// If any address is outside of the memory.
+pub fn timestamps(addresses: &[usize], memory_len: usize) -> (Vec<usize>, Vec<usize>) {
+    let mut final_timestamps = vec![0; memory_len];
+    let read_timestamps = addresses
+        .iter()
+        .map(|&address| {
+            let timestamp = final_timestamps[address];
+            final_timestamps[address] += 1;
+            timestamp
+        })
+        .collect();
+    (read_timestamps, final_timestamps)
+}
+
+/// The message sent by the prover during the protocol execution.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProverMsgs<E: Pairing> {
+    /// The products of the fingerprints of $I, R, W, F$.
+    pub products: Vec<E::ScalarField>,
+    /// The messages of the entry products of the fingerprints of $I, R, W, F$.
+    pub entry_product: entryproduct::ProverMsgs<E>,
+}
+
+/// The memory-checking transcript and subclaims.
+pub struct MemoryCheck<E: Pairing, P: Prover<E::ScalarField>> {
+    /// The challenges sent by the verifier for the fingerprints.
+    pub fingerprint: Fingerprint<E::ScalarField>,
+    /// The products of the fingerprints of $I, R, W, F$.
+    pub products: Vec<E::ScalarField>,
+    /// The entry products of the fingerprints of $I, R, W, F$,
+    /// with their sumcheck subclaims.
+    pub entry_product: EntryProduct<E, P>,
+}
+
+impl<E: Pairing, P: Prover<E::ScalarField>> MemoryCheck<E, P> {
+    /// Return the messages sent by the prover.
+    pub fn msgs(&self) -> ProverMsgs<E> {
+        ProverMsgs {
+            products: self.products.clone(),
+            entry_product: self.entry_product.msgs.clone(),
+        }
+    }
+}
+
+/// The subclaims of the memory-checking argument.
+pub struct Subclaim<F: Field> {
+    /// The challenges sent by the verifier for the fingerprints.
+    pub fingerprint: Fingerprint<F>,
+    /// The challenge of the entry products.
+    pub chal: F,
+    /// The sums asserted for the sumchecks of the entry products of $I, R, W, F$.
+    ///
+    /// They are sent by the prover, and must be checked against the evaluations at `chal`
+    /// of the accumulated products committed in the entry product messages.
+    pub asserted_sums: Vec<F>,
+}
+
+impl<F: Field> Subclaim<F> {
+    /// Generate a new subclaim from the messages of the prover,
+    /// checking the multiset equality $I \cup W = R \cup F$ over the products of the fingerprints.
+    pub fn new<E: Pairing<ScalarField = F>>(
+        transcript: &mut impl GeminiTranscript,
+        msgs: &ProverMsgs<E>,
+    ) -> Result<Self, VerificationError> {
+        let fingerprint = Fingerprint::new(transcript);
+        if msgs.products.len() != 4
+            || msgs.entry_product.acc_v_commitments.len() != 4
+            || !msgs.entry_product.is_well_formed()
+        {
+            return Err(VerificationError::Consistency {
+                check: "memory-checking messages",
+            });
+        }
+        msgs.products
+            .iter()
+            .for_each(|product| transcript.append_serializable(b"memcheck-product", product));
+        msgs.entry_product
+            .acc_v_commitments
+            .iter()
+            .for_each(|acc_v_commitment| {
+                transcript.append_serializable(b"acc_v", acc_v_commitment)
+            });
+        let chal = transcript.get_challenge::<F>(b"ep-chal");
+
+        let [init, read, write, audit] = [0, 1, 2, 3].map(|i| msgs.products[i]);
+        if init * write == read * audit {
+            Ok(Subclaim {
+                fingerprint,
+                chal,
+                asserted_sums: msgs.entry_product.claimed_sumchecks.clone(),
+            })
+        } else {
+            Err(VerificationError::Consistency {
+                check: "memory-checking multiset equality",
+            })
+        }
+    }
+
+    /// Same as [`Subclaim::new`], for reads from the committed `table`
+    /// (see [`MemoryCheck::new_time_with_table`]).
+    pub fn new_with_table<E: Pairing<ScalarField = F>>(
+        transcript: &mut impl GeminiTranscript,
+        table: &CommittedTable<E>,
+        msgs: &ProverMsgs<E>,
+    ) -> Result<Self, VerificationError> {
+        transcript.append_serializable(b"memcheck-table", table);
+        Self::new(transcript, msgs)
+    }
+}
@@ -0,0 +1,110 @@
+use ark_ff::Field;
+use ark_std::borrow::Borrow;
+
+use crate::iterable::Iterable;
+use crate::subprotocols::memcheck::Fingerprint;
+
+/// Given streams for the addresses \\(\vec a\\), the values \\(\vec v\\), and the read timestamps \\(\vec r\\)
+/// of a sequence of reads,
+/// stream the fingerprints \\(h(a_k, v_k, r_k + \delta)\\),
+/// where \\(\delta\\) is \\(0\\) for the reads and \\(1\\) for the writes.
+#[derive(Clone, Copy)]
+pub struct AccessStream<'a, F, SA, SV, ST> {
+    addresses: &'a SA,
+    values: &'a SV,
+    timestamps: &'a ST,
+    offset: usize,
+    fingerprint: Fingerprint<F>,
+}
+
+pub struct AccessIter<F, IA, IV, IT> {
+    fingerprint: Fingerprint<F>,
+    offset: usize,
+    addresses: IA,
+    values: IV,
+    timestamps: IT,
+}
+
+impl<'a, F, SA, SV, ST> AccessStream<'a, F, SA, SV, ST> {
+    /// Stream the fingerprints of the reads.
+    pub fn new_read(
+        addresses: &'a SA,
+        values: &'a SV,
+        timestamps: &'a ST,
+        fingerprint: Fingerprint<F>,
+    ) -> Self {
+        Self {
+            addresses,
+            values,
+            timestamps,
+            offset: 0,
+            fingerprint,
+        }
+    }
+
+    /// Stream the fingerprints of the writes following each read.
+    pub fn new_write(
+        addresses: &'a SA,
+        values: &'a SV,
+        timestamps: &'a ST,
+        fingerprint: Fingerprint<F>,
+    ) -> Self {
+        Self {
+            offset: 1,
+            ..Self::new_read(addresses, values, timestamps, fingerprint)
+        }
+    }
+}
+
+impl<'a, F, SA, SV, ST> Iterable for AccessStream<'a, F, SA, SV, ST>
+where
+    F: Field,
+    SA: Iterable,
+    SA::Item: Borrow<usize>,
+    SV: Iterable,
+    SV::Item: Borrow<F>,
+    ST: Iterable,
+    ST::Item: Borrow<usize>,
+{
+    type Item = F;
+
+    type Iter = AccessIter<F, SA::Iter, SV::Iter, ST::Iter>;
+
+    fn iter(&self) -> Self::Iter {
+        AccessIter {
+            fingerprint: self.fingerprint,
+            offset: self.offset,
+            addresses: self.addresses.iter(),
+            values: self.values.iter(),
+            timestamps: self.timestamps.iter(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.addresses.len()
+    }
+}
+
+impl<F, IA, IV, IT> Iterator for AccessIter<F, IA, IV, IT>
+where
+    F: Field,
+    IA: Iterator,
+    IA::Item: Borrow<usize>,
+    IV: Iterator,
+    IV::Item: Borrow<F>,
+    IT: Iterator,
+    IT::Item: Borrow<usize>,
+{
+    type Item = F;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let address = *self.addresses.next()?.borrow();
+        let value = *self.values.next()?.borrow();
+        let timestamp = *self.timestamps.next()?.borrow();
+        Some(
+            self.fingerprint
+                .hash_at(address, value, timestamp + self.offset),
+        )
+    }
+}
@@ -1,57 +1,52 @@
-use crate::stream::Streamer;
 use ark_ff::Field;
 use ark_std::borrow::Borrow;
 
-/// Collections of streams used withing the memory-checking protocols.
+use crate::iterable::Iterable;
+use crate::subprotocols::memcheck::Fingerprint;
 
+/// Given a stream for the memory \\(\vec m\\),
+/// stream the fingerprints \\(h(i, m_i, 0)\\) of its initial state.
 #[derive(Clone, Copy)]
-pub struct InitStream<F, S> {
-    x: F,
-    y: F,
-    stream: S,
+pub struct InitStream<'a, F, S> {
+    memory: &'a S,
+    fingerprint: Fingerprint<F>,
 }
 
 pub struct InitIter<F, I> {
-    x: F,
-    // x2: F,
-    y: F,
-    // index: F,
-    iterator: I,
+    fingerprint: Fingerprint<F>,
+    address: usize,
+    memory: I,
 }
 
-impl<F, S> InitStream<F, S> {
-    pub fn new(stream: S, x: F, y: F) -> Self {
-        Self { stream, x, y }
+impl<'a, F, S> InitStream<'a, F, S> {
+    pub fn new(memory: &'a S, fingerprint: Fingerprint<F>) -> Self {
+        Self {
+            memory,
+            fingerprint,
+        }
     }
 }
 
-impl<F, S> Streamer for InitStream<F, S>
+impl<'a, F, S> Iterable for InitStream<'a, F, S>
 where
     F: Field,
-    S: Streamer,
+    S: Iterable,
     S::Item: Borrow<F>,
 {
     type Item = F;
 
     type Iter = InitIter<F, S::Iter>;
 
-    fn stream(&self) -> Self::Iter {
-        let iterator = self.stream.stream();
-        let x = self.x;
-        let y = self.y;
-        // let x2 = self.x.square();
-        // let index = F::from(self.stream.len() as u64);
-        Self::Iter {
-            x,
-            // x2,
-            y,
-            // index,
-            iterator,
+    fn iter(&self) -> Self::Iter {
+        InitIter {
+            fingerprint: self.fingerprint,
+            address: self.memory.len(),
+            memory: self.memory.iter(),
         }
     }
 
     fn len(&self) -> usize {
-        self.stream.len()
+        self.memory.len()
     }
 }
 
@@ -64,39 +59,10 @@ where
     type Item = F;
 
     #[inline]
-    fn next(&mut self) -> Option<<Self as Iterator>::Item> {
-        let e = self.iterator.next()?;
-        // self.index -= F::one();
-        Some(self.y - (self.x * e.borrow()))
-    }
-}
-
-#[test]
-fn test_init_stream() {
-    use crate::stream::Streamer;
-    use ark_bls12_381::Fr;
-    use ark_std::UniformRand;
-
-    for _ in 0..100 {
-        let rng = &mut ark_std::test_rng();
-        let size = 1000;
-        let mut a = Vec::new();
-        for _ in 0..size {
-            a.push(Fr::rand(rng));
-        }
-
-        let x = Fr::rand(rng);
-        let y = Fr::rand(rng);
-
-        let mut ans = Vec::new();
-        for i in 0..size {
-            ans.push(y - (x * a[size - 1 - i]));
-        }
-
-        a.reverse();
-        let st = InitStream::new(a.as_slice(), x, y);
-        for (i, res) in st.stream().enumerate() {
-            assert_eq!(res, ans[i]);
-        }
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.memory.next()?;
+        // streams are big-endian: addresses go from the last one down to 0.
+        self.address -= 1;
+        Some(self.fingerprint.hash_at(self.address, *value.borrow(), 0))
     }
 }
@@ -1,57 +1,100 @@
+mod access_stream;
 mod audit_stream;
 mod init_stream;
-mod read_stream;
-mod write_stream;
 
-use std::borrow::Borrow;
-
-use ark_ff::Field;
+pub use access_stream::AccessStream;
 pub use audit_stream::AuditStream;
 pub use init_stream::InitStream;
-pub use read_stream::ReadStream;
-pub use write_stream::WriteStream;
-
-use crate::entryproduct::streams::entry_product_streams;
-use crate::stream::Streamer;
 
-use crate::entry_product::streams::entry_product_streams;
-use crate::entry_product::streams::{ProductStream, RightRotationStreamer};
+use ark_ff::Field;
+use ark_std::borrow::Borrow;
 
-type Eps<F, S> = (RightRotationStreamer<F, S>, ProductStream<F, S>);
+use crate::iterable::Iterable;
+use crate::subprotocols::memcheck::Fingerprint;
 
-pub fn memcheck_streams<SET, SUB, IND, F>(
-    subset: SUB,
-    set: SET,
-    index: IND,
-    y: F,
-    z: F,
+/// Given a memory-checking instance of the form
+/// `memory` (denoted \\(\vec m\\)),
+/// the `addresses` (denoted \\(\vec a\\)) and the `values` (denoted \\(\vec v\\)) read,
+/// the `read_timestamps` (denoted \\(\vec r\\)) and the `final_timestamps` (denoted \\(\vec f\\)),
+/// return streams for the fingerprints of the multisets:
+/// - init: \\(h(i, m_i, 0)\\)
+/// - read: \\(h(a_k, v_k, r_k)\\)
+/// - write: \\(h(a_k, v_k, r_k + 1)\\)
+/// - audit: \\(h(i, m_i, f_i)\\)
+#[allow(clippy::type_complexity)]
+pub fn memcheck_streams<'a, F, SM, SA, SV, ST, SF>(
+    fingerprint: Fingerprint<F>,
+    memory: &'a SM,
+    addresses: &'a SA,
+    values: &'a SV,
+    read_timestamps: &'a ST,
+    final_timestamps: &'a SF,
 ) -> (
-    Eps<F, InitStream<F, SET>>,
-    Eps<F, ReadStream<F, SUB, IND>>,
-    Eps<F, WriteStream<F, SUB, IND>>,
-    Eps<F, AuditStream<F, SET, IND>>,
+    InitStream<'a, F, SM>,
+    AccessStream<'a, F, SA, SV, ST>,
+    AccessStream<'a, F, SA, SV, ST>,
+    AuditStream<'a, F, SM, SF>,
 )
 where
     F: Field,
-    SET: Streamer,
-    SUB: Streamer,
-    SET::Item: Borrow<F>,
-    SUB::Item: Borrow<F>,
-    IND: Streamer<Item = usize>,
+    SM: Iterable,
+    SM::Item: Borrow<F>,
+    SA: Iterable,
+    SA::Item: Borrow<usize>,
+    SV: Iterable,
+    SV::Item: Borrow<F>,
+    ST: Iterable,
+    ST::Item: Borrow<usize>,
+    SF: Iterable,
+    SF::Item: Borrow<usize>,
 {
-    let memcheck_init = InitStream::new(set, y, z);
-    let (memcheck_init_sh, memcheck_init_acc) = entry_product_streams(memcheck_init);
-    let memcheck_read = ReadStream::new(subset, index, y, z);
-    let (memcheck_read_sh, memcheck_read_acc) = entry_product_streams(memcheck_read);
-    let memcheck_write = WriteStream::new(subset, index, y, z);
-    let (memcheck_write_sh, memcheck_write_acc) = entry_product_streams(memcheck_write);
-    let memcheck_audit = AuditStream::new(set, index, y, z);
-    let (memcheck_audit_sh, memcheck_audit_acc) = entry_product_streams(memcheck_audit);
-
-    (
-        (memcheck_init_sh, memcheck_init_acc),
-        (memcheck_read_sh, memcheck_read_acc),
-        (memcheck_write_sh, memcheck_write_acc),
-        (memcheck_audit_sh, memcheck_audit_acc),
-    )
+    let init = InitStream::new(memory, fingerprint);
+    let read = AccessStream::new_read(addresses, values, read_timestamps, fingerprint);
+    let write = AccessStream::new_write(addresses, values, read_timestamps, fingerprint);
+    let audit = AuditStream::new(memory, final_timestamps, fingerprint);
+    (init, read, write, audit)
+}
+
+#[test]
+fn test_consistency() {
+    use ark_bls12_381::Fr as F;
+    use ark_std::test_rng;
+    use ark_std::vec::Vec;
+    use ark_std::UniformRand;
+
+    use super::time_prover::fingerprints;
+    use super::timestamps;
+    use crate::iterable::Reverse;
+
+    let rng = &mut test_rng();
+    let memory = (0..10).map(|_| F::rand(rng)).collect::<Vec<_>>();
+    let addresses = (0..25).map(|k| (k * k) % 10).collect::<Vec<usize>>();
+    let values = addresses.iter().map(|&a| memory[a]).collect::<Vec<_>>();
+    let (read_timestamps, final_timestamps) = timestamps(&addresses, memory.len());
+    let fingerprint = Fingerprint {
+        gamma: F::rand(rng),
+        tau: F::rand(rng),
+    };
+
+    let (memory_stream, addresses_stream) = (Reverse(&memory[..]), Reverse(&addresses[..]));
+    let values_stream = Reverse(&values[..]);
+    let read_timestamps_stream = Reverse(&read_timestamps[..]);
+    let final_timestamps_stream = Reverse(&final_timestamps[..]);
+    let (init, read, write, audit) = memcheck_streams(
+        fingerprint,
+        &memory_stream,
+        &addresses_stream,
+        &values_stream,
+        &read_timestamps_stream,
+        &final_timestamps_stream,
+    );
+    let reversed = |mut v: Vec<F>| {
+        v.reverse();
+        v
+    };
+    let expected = fingerprints(fingerprint, &memory, &addresses);
+    assert_eq!(reversed(init.iter().collect()), expected[0]);
+    assert_eq!(reversed(read.iter().collect()), expected[1]);
+    assert_eq!(reversed(write.iter().collect()), expected[2]);
+    assert_eq!(reversed(audit.iter().collect()), expected[3]);
 }
@@ -0,0 +1,189 @@
+use ark_bls12_381::{Bls12_381, Fr as F};
+use ark_ff::One;
+use ark_std::vec::Vec;
+use ark_std::UniformRand;
+use merlin::Transcript;
+
+use super::streams::memcheck_streams;
+use super::{timestamps, Fingerprint, MemoryCheck, Subclaim};
+use crate::config::ProverConfig;
+use crate::iterable::Reverse;
+use crate::kzg::{CommitterKey, CommitterKeyStream};
+use crate::subprotocols::sumcheck::{BatchSumcheck, Subclaim as SumcheckSubclaim};
+
+#[test]
+fn test_timestamps() {
+    let (read_timestamps, final_timestamps) = timestamps(&[2, 0, 2, 2, 1], 4);
+    assert_eq!(read_timestamps, [0, 0, 1, 2, 0]);
+    assert_eq!(final_timestamps, [1, 1, 3, 0]);
+}
+
+#[test]
+fn test_memcheck_correctness() {
+    let rng = &mut ark_std::test_rng();
+    let memory = (0..16).map(|_| F::rand(rng)).collect::<Vec<_>>();
+    let addresses = (0..40)
+        .map(|k: usize| (k * k + 3 * k) % 16)
+        .collect::<Vec<_>>();
+    let ck = CommitterKey::<Bls12_381>::new(64, 1, rng);
+
+    let prover_transcript = &mut Transcript::new(b"test");
+    let fingerprint = Fingerprint::new(prover_transcript);
+    let memcheck = MemoryCheck::new_time(prover_transcript, &ck, fingerprint, &memory, &addresses);
+    let msgs = memcheck.msgs();
+    let mut batch = BatchSumcheck::new();
+    batch.extend(memcheck.entry_product.provers);
+    let sumcheck = batch.prove(prover_transcript).unwrap();
+
+    let verifier_transcript = &mut Transcript::new(b"test");
+    let subclaim = Subclaim::new(verifier_transcript, &msgs).unwrap();
+    assert_eq!(subclaim.fingerprint, fingerprint);
+    let sumcheck_subclaim = SumcheckSubclaim::new_batch(
+        verifier_transcript,
+        &sumcheck.prover_messages(),
+        &subclaim.asserted_sums,
+    );
+    assert!(sumcheck_subclaim.is_ok());
+}
+
+#[test]
+fn test_memcheck_consistency() {
+    let rng = &mut ark_std::test_rng();
+    let memory = (0..16).map(|_| F::rand(rng)).collect::<Vec<_>>();
+    let addresses = (0..40)
+        .map(|k: usize| (k * k + 3 * k) % 16)
+        .collect::<Vec<_>>();
+    let values = addresses.iter().map(|&a| memory[a]).collect::<Vec<_>>();
+    let (read_timestamps, final_timestamps) = timestamps(&addresses, memory.len());
+    let ck = CommitterKey::<Bls12_381>::new(64, 1, rng);
+    let stream_ck = CommitterKeyStream::from(&ck);
+
+    let time_transcript = &mut Transcript::new(b"test");
+    let fingerprint = Fingerprint::new(time_transcript);
+    let time_memcheck =
+        MemoryCheck::new_time(time_transcript, &ck, fingerprint, &memory, &addresses);
+
+    let memory_stream = Reverse(memory.as_slice());
+    let addresses_stream = Reverse(addresses.as_slice());
+    let values_stream = Reverse(values.as_slice());
+    let read_timestamps_stream = Reverse(read_timestamps.as_slice());
+    let final_timestamps_stream = Reverse(final_timestamps.as_slice());
+    let elastic_transcript = &mut Transcript::new(b"test");
+    let fingerprint = Fingerprint::new(elastic_transcript);
+    let (init, read, write, audit) = memcheck_streams(
+        fingerprint,
+        &memory_stream,
+        &addresses_stream,
+        &values_stream,
+        &read_timestamps_stream,
+        &final_timestamps_stream,
+    );
+    let elastic_memcheck = MemoryCheck::new_elastic(
+        elastic_transcript,
+        &stream_ck,
+        &ProverConfig::default(),
+        fingerprint,
+        (&init, &read, &write, &audit),
+    )
+    .unwrap();
+    assert_eq!(time_memcheck.msgs(), elastic_memcheck.msgs());
+}
+
+#[test]
+fn test_memcheck_inconsistent_reads() {
+    let rng = &mut ark_std::test_rng();
+    let memory = (0..16).map(|_| F::rand(rng)).collect::<Vec<_>>();
+    let addresses = (0..40)
+        .map(|k: usize| (k * k + 3 * k) % 16)
+        .collect::<Vec<_>>();
+    let mut values = addresses.iter().map(|&a| memory[a]).collect::<Vec<_>>();
+    values[7] += F::one();
+    let (read_timestamps, final_timestamps) = timestamps(&addresses, memory.len());
+    let ck = CommitterKey::<Bls12_381>::new(64, 1, rng);
+    let stream_ck = CommitterKeyStream::from(&ck);
+
+    let memory_stream = Reverse(memory.as_slice());
+    let addresses_stream = Reverse(addresses.as_slice());
+    let values_stream = Reverse(values.as_slice());
+    let read_timestamps_stream = Reverse(read_timestamps.as_slice());
+    let final_timestamps_stream = Reverse(final_timestamps.as_slice());
+    let prover_transcript = &mut Transcript::new(b"test");
+    let fingerprint = Fingerprint::new(prover_transcript);
+    let (init, read, write, audit) = memcheck_streams(
+        fingerprint,
+        &memory_stream,
+        &addresses_stream,
+        &values_stream,
+        &read_timestamps_stream,
+        &final_timestamps_stream,
+    );
+    let memcheck = MemoryCheck::new_elastic(
+        prover_transcript,
+        &stream_ck,
+        &ProverConfig::default(),
+        fingerprint,
+        (&init, &read, &write, &audit),
+    )
+    .unwrap();
+
+    let verifier_transcript = &mut Transcript::new(b"test");
+    assert!(Subclaim::new(verifier_transcript, &memcheck.msgs()).is_err());
+}
+
+#[test]
+fn test_memcheck_committed_table() {
+    use super::CommittedTable;
+
+    let rng = &mut ark_std::test_rng();
+    let table = (0..16).map(|_| F::rand(rng)).collect::<Vec<_>>();
+    let addresses = (0..40)
+        .map(|k: usize| (k * k + 3 * k) % 16)
+        .collect::<Vec<_>>();
+    let ck = CommitterKey::<Bls12_381>::new(64, 1, rng);
+    // the table is committed once, by a third party.
+    let committed_table = CommittedTable::new(&ck, &table);
+
+    let prover_transcript = &mut Transcript::new(b"test");
+    let memcheck = MemoryCheck::new_time_with_table(
+        prover_transcript,
+        &ck,
+        &committed_table,
+        &table,
+        &addresses,
+    )
+    .unwrap();
+    let msgs = memcheck.msgs();
+    let mut batch = BatchSumcheck::new();
+    batch.extend(memcheck.entry_product.provers);
+    let sumcheck = batch.prove(prover_transcript).unwrap();
+
+    let verifier_transcript = &mut Transcript::new(b"test");
+    let subclaim = Subclaim::new_with_table(verifier_transcript, &committed_table, &msgs).unwrap();
+    assert_eq!(subclaim.fingerprint, memcheck.fingerprint);
+    let sumcheck_subclaim = SumcheckSubclaim::new_batch(
+        verifier_transcript,
+        &sumcheck.prover_messages(),
+        &subclaim.asserted_sums,
+    );
+    assert!(sumcheck_subclaim.is_ok());
+
+    // the challenges depend on the table.
+    let mut other_table = table.clone();
+    other_table[0] += F::one();
+    let other_committed_table = CommittedTable::new(&ck, &other_table);
+    let other_transcript = &mut Transcript::new(b"test");
+    let other_subclaim =
+        Subclaim::new_with_table(other_transcript, &other_committed_table, &msgs).unwrap();
+    assert_ne!(other_subclaim.fingerprint, memcheck.fingerprint);
+
+    // the prover must hold the opening of the table.
+    let prover_transcript = &mut Transcript::new(b"test");
+    assert!(MemoryCheck::new_time_with_table(
+        prover_transcript,
+        &ck,
+        &committed_table,
+        &other_table,
+        &addresses,
+    )
+    .is_err());
+}
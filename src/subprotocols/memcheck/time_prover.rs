@@ -0,0 +1,108 @@
+//! The time-efficient prover for the memory-checking argument.
+use ark_ec::pairing::Pairing;
+use ark_ff::Field;
+use ark_std::boxed::Box;
+use ark_std::vec::Vec;
+
+use super::{timestamps, CommittedTable, Fingerprint, MemoryCheck};
+use crate::errors::GeminiResult;
+use crate::kzg::CommitterKey;
+use crate::subprotocols::entryproduct::EntryProduct;
+use crate::subprotocols::sumcheck::Prover;
+use crate::transcript::GeminiTranscript;
+
+/// Given the `memory` and the `addresses` read,
+/// return the vectors of fingerprints of $I, R, W, F$.
+pub fn fingerprints<F: Field>(
+    fingerprint: Fingerprint<F>,
+    memory: &[F],
+    addresses: &[usize],
+) -> Vec<Vec<F>> {
+    let (read_timestamps, final_timestamps) = timestamps(addresses, memory.len());
+    let accesses = |offset: usize| {
+        addresses
+            .iter()
+            .zip(&read_timestamps)
+            .map(|(&address, &timestamp)| {
+                fingerprint.hash_at(address, memory[address], timestamp + offset)
+            })
+            .collect::<Vec<_>>()
+    };
+    let init = memory
+        .iter()
+        .enumerate()
+        .map(|(address, &value)| fingerprint.hash_at(address, value, 0))
+        .collect();
+    let audit = memory
+        .iter()
+        .zip(&final_timestamps)
+        .enumerate()
+        .map(|(address, (&value, &timestamp))| fingerprint.hash_at(address, value, timestamp))
+        .collect();
+    vec![init, accesses(0), accesses(1), audit]
+}
+
+impl<E: Pairing> MemoryCheck<E, Box<dyn Prover<E::ScalarField>>> {
+    /// Prove that the reads at `addresses` from `memory` are consistent,
+    /// using the time-efficient prover.
+    ///
+    /// The `fingerprint` must be sampled with [`Fingerprint::new`]
+    /// after committing to the memory, the reads, and their timestamps (see [`timestamps`]).
+    ///
+    /// # Panics
+    /// If any address is outside of the memory.
+    pub fn new_time(
+        transcript: &mut impl GeminiTranscript,
+        ck: &CommitterKey<E>,
+        fingerprint: Fingerprint<E::ScalarField>,
+        memory: &[E::ScalarField],
+        addresses: &[usize],
+    ) -> Self {
+        trace_span!(
+            "memory_check",
+            memory = memory.len(),
+            reads = addresses.len()
+        );
+        let vs = fingerprints(fingerprint, memory, addresses);
+        let products = vs
+            .iter()
+            .map(|v| v.iter().product())
+            .collect::<Vec<E::ScalarField>>();
+        products
+            .iter()
+            .for_each(|product| transcript.append_serializable(b"memcheck-product", product));
+        let entry_product = EntryProduct::new_time_batch(transcript, ck, &vs, &products);
+        MemoryCheck {
+            fingerprint,
+            products,
+            entry_product,
+        }
+    }
+
+    /// Prove that the reads at `addresses` from the committed `table` are consistent,
+    /// using the time-efficient prover, where `memory` is the opening of the table.
+    ///
+    /// The reads and their timestamps must be committed beforehand:
+    /// the fingerprint is sampled with [`Fingerprint::new_with_table`].
+    /// Fails if `table` does not open to `memory`.
+    ///
+    /// # Panics
+    /// If any address is outside of the memory.
+    pub fn new_time_with_table(
+        transcript: &mut impl GeminiTranscript,
+        ck: &CommitterKey<E>,
+        table: &CommittedTable<E>,
+        memory: &[E::ScalarField],
+        addresses: &[usize],
+    ) -> GeminiResult<Self> {
+        table.ensure_opening(ck, memory)?;
+        let fingerprint = Fingerprint::new_with_table(transcript, table);
+        Ok(Self::new_time(
+            transcript,
+            ck,
+            fingerprint,
+            memory,
+            addresses,
+        ))
+    }
+}
@@ -5,15 +5,20 @@
 //!     an argument for claims of the form \\(\langle f, \otimes_j (1, \rho_j) \rangle = t\\).
 //!     This can be used for proving batches of multivariate evaluations claims using
 //!     univariate polynomial commitments.
+//! - [`tensorcheck::multilinear::MultilinearProof`],
+//!     an evaluation proof for multilinear polynomials committed with the univariate KZG.
 //! - [`sumcheck::proof::Sumcheck`],
 //!    the multivariate sumcheck implementation, implemented in 3 flavours: linear-time, log-space, and elastic.
 //! - [`entryproduct::EntryProduct`],
 //!    an argument for proving knowledge of the product of all the components in a vector \\(\vec f\\).
 //! - [`plookup`], an argument for proving lookup relations.
+//! - [`memcheck::MemoryCheck`],
+//!    an offline memory-checking argument for proving that reads from a memory are consistent.
 //!
 //!
 
 pub mod entryproduct;
+pub mod memcheck;
 pub mod plookup;
 pub mod tensorcheck;
 
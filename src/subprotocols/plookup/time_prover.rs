@@ -68,6 +68,7 @@ pub(crate) fn compute_frequency(set_len: usize, index: &[usize]) -> Vec<usize> {
     frequency
 }
 
+#[cfg(feature = "prover")]
 pub(crate) fn extend_frequency(frequency: &[usize]) -> Vec<usize> {
     let mut res = Vec::new();
     frequency
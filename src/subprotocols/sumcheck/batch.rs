@@ -0,0 +1,111 @@
+//! Batches of twisted scalar products, proven with a single sumcheck.
+//!
+//! The twisted scalar product of $\vec f, \vec g \in \FF^n$ with twist $\psi \in \FF$ is
+//! \\[
+//! \langle \vec f, \vec g \rangle_\psi = \sum_i f_i g_i \psi^i.
+//! \\]
+//! A [`BatchSumcheck`] collects claims $\langle \vec f_i, \vec g_i \rangle_{\psi_i} = t_i$,
+//! each with its own length and its own twist,
+//! and proves all of them at once, as a single claim combined by random coefficients drawn from the transcript.
+//! Each claim is proven by the prover best suited to where its vectors are:
+//! slices in memory, or streams read once per round (see [`crate::iterable`]),
+//! and the two can be mixed freely within a batch.
+//!
+//! The verifier checks the batch with [`Subclaim::new_batch`](super::Subclaim::new_batch),
+//! given the claimed sums $t_i$ in the order the claims were added.
+//! The resulting subclaim holds, for each claim, the evaluations of $\vec f_i, \vec g_i$
+//! over the tensor of the challenges (the first $\lceil \log n_i \rceil$ of them).
+use ark_ff::Field;
+use ark_std::borrow::Borrow;
+use ark_std::boxed::Box;
+use ark_std::vec::Vec;
+
+use crate::config::ProverConfig;
+use crate::errors::GeminiResult;
+use crate::iterable::Iterable;
+use crate::subprotocols::sumcheck::proof::Sumcheck;
+use crate::subprotocols::sumcheck::time_prover::Witness;
+use crate::subprotocols::sumcheck::{ElasticProver, Prover, SpaceProver, TimeProver};
+use crate::transcript::GeminiTranscript;
+
+/// A batch of twisted scalar products, to be proven with a single sumcheck.
+#[derive(Default)]
+pub struct BatchSumcheck<'a, F: Field> {
+    provers: Vec<Box<dyn Prover<F> + 'a>>,
+}
+
+impl<'a, F: Field> BatchSumcheck<'a, F> {
+    /// Start an empty batch.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add the claim $\langle \vec f, \vec g \rangle_\psi$ over vectors held in memory,
+    /// with their coefficients from the least significant (i.e., $f_0$ first).
+    pub fn add_time(&mut self, f: &[F], g: &[F], twist: F) -> &mut Self {
+        let prover = TimeProver::new(Witness::new(f, g, &twist));
+        self.add_prover(prover)
+    }
+
+    /// Add the claim $\langle \vec f, \vec g \rangle_\psi$ over streams,
+    /// with their coefficients from the most significant.
+    ///
+    /// Each round of the sumcheck is a pass over the streams.
+    /// Fails if the twist is zero.
+    pub fn add_space<SF, SG>(&mut self, f: SF, g: SG, twist: F) -> GeminiResult<&mut Self>
+    where
+        SF: Iterable + 'a,
+        SG: Iterable + 'a,
+        SF::Item: Borrow<F>,
+        SG::Item: Borrow<F>,
+    {
+        Ok(self.add_prover(SpaceProver::new(f, g, twist)?))
+    }
+
+    /// Same as [`BatchSumcheck::add_space`],
+    /// but switching to the vectors in memory once their foldings fit the memory budget of `config`.
+    pub fn add_elastic<SF, SG>(
+        &mut self,
+        f: SF,
+        g: SG,
+        twist: F,
+        config: &ProverConfig,
+    ) -> GeminiResult<&mut Self>
+    where
+        SF: Iterable + 'a,
+        SG: Iterable + 'a,
+        SF::Item: Borrow<F>,
+        SG::Item: Borrow<F>,
+    {
+        Ok(self.add_prover(ElasticProver::with_config(f, g, twist, config)?))
+    }
+
+    /// Add a claim proven by `prover`, at its first round.
+    pub fn add_prover(&mut self, prover: impl Prover<F> + 'a) -> &mut Self {
+        self.provers.push(Box::new(prover));
+        self
+    }
+
+    /// The number of claims in the batch.
+    pub fn len(&self) -> usize {
+        self.provers.len()
+    }
+
+    /// Return `true` if the batch has no claims.
+    pub fn is_empty(&self) -> bool {
+        self.provers.is_empty()
+    }
+
+    /// Prove all claims of the batch, in the order they were added.
+    ///
+    /// The proof is the same as [`Sumcheck::prove_batch`] over the provers of the claims.
+    pub fn prove(self, transcript: &mut impl GeminiTranscript) -> GeminiResult<Sumcheck<F>> {
+        Sumcheck::prove_batch(transcript, self.provers)
+    }
+}
+
+impl<'a, F: Field> Extend<Box<dyn Prover<F> + 'a>> for BatchSumcheck<'a, F> {
+    fn extend<I: IntoIterator<Item = Box<dyn Prover<F> + 'a>>>(&mut self, provers: I) {
+        self.provers.extend(provers)
+    }
+}
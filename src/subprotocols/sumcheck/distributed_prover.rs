@@ -0,0 +1,141 @@
+//! The distributed prover, splitting the instance across multiple workers.
+//!
+//! Each worker owns a contiguous chunk of $2^m$ coefficients of $f$ and $g$
+//! (the last chunk possibly shorter), and runs any [`Prover`] over its chunk, with the twist of the instance:
+//! the first $m$ rounds only fold coefficients within the same chunk,
+//! hence the round messages are the sum of the messages of the workers,
+//! each scaled by the twist raised to the offset of its chunk.
+//! After $m$ rounds each worker holds a single pair of foldings,
+//! which the coordinator collects and folds with the time-efficient prover in the remaining rounds.
+//!
+//! Workers only exchange two field elements per round with the coordinator,
+//! and can be run on different machines by implementing [`Prover`] over a connection.
+//! Likewise, commitments to the polynomials can be computed by the workers,
+//! each one against the chunk of the committer key at the same positions, and summed up.
+use ark_ff::Field;
+use ark_std::log2;
+use ark_std::vec::Vec;
+
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
+
+use crate::errors::{GeminiError, GeminiResult};
+use crate::misc::powers;
+
+use super::prover::RoundMsg;
+use super::time_prover::Witness;
+use super::{Prover, TimeProver};
+
+/// The coordinator of the distributed prover, holding the workers `P`.
+pub struct DistributedProver<F: Field, P> {
+    workers: Vec<P>,
+    /// The twist raised to the offset of each chunk.
+    offsets: Vec<F>,
+    /// The number of rounds run by the workers.
+    worker_rounds: usize,
+    /// The twist of the instance folded by the coordinator.
+    tail_twist: F,
+    /// The prover of the instance folded by the coordinator, once the workers are done.
+    tail: Option<TimeProver<F>>,
+    round: usize,
+    tot_rounds: usize,
+}
+
+impl<F: Field, P: Prover<F>> DistributedProver<F, P> {
+    /// Coordinate `workers`, the `i`-th one proving the chunk of `chunk_size` coefficients
+    /// starting from `i * chunk_size`, with twist `twist`.
+    ///
+    /// The chunk size must be a power of two, and all chunks but the last must be full.
+    /// Fails if the chunk size is not a power of two, or if a worker holds more than a chunk.
+    pub fn new(workers: Vec<P>, chunk_size: usize, twist: F) -> GeminiResult<Self> {
+        let malformed = |reason| Err(GeminiError::MalformedInstance { reason });
+        if !chunk_size.is_power_of_two() {
+            return malformed("the chunks of the distributed prover must be a power of two");
+        }
+        if workers.is_empty() {
+            return malformed("the distributed prover requires at least one worker");
+        }
+        let worker_rounds = log2(chunk_size) as usize;
+        if workers.iter().any(|w| w.rounds() > worker_rounds) {
+            return malformed("a worker of the distributed prover holds more than a chunk");
+        }
+        let tail_twist = twist.pow([chunk_size as u64]);
+        let offsets = powers(tail_twist, workers.len());
+        let tot_rounds = worker_rounds + log2(workers.len()) as usize;
+        Ok(Self {
+            workers,
+            offsets,
+            worker_rounds,
+            tail_twist,
+            tail: None,
+            round: 0,
+            tot_rounds,
+        })
+    }
+}
+
+impl<F: Field, P: Prover<F>> Prover<F> for DistributedProver<F, P> {
+    fn next_message(&mut self) -> Option<RoundMsg<F>> {
+        if self.round == self.tot_rounds {
+            return None;
+        }
+        trace_span!("distributed_sumcheck_round", round = self.round);
+        if self.round < self.worker_rounds {
+            self.round += 1;
+            let worker_messages = cfg_iter_mut!(self.workers).map(|w| {
+                // workers with a shorter chunk are done early, and keep their foldings.
+                w.next_message().unwrap_or_else(|| {
+                    let final_foldings = w.final_foldings().expect(
+                        "If next_message is None, we expect final foldings to be available",
+                    );
+                    RoundMsg(final_foldings[0] * final_foldings[1], F::zero())
+                })
+            });
+            return Some(
+                worker_messages
+                    .zip(&self.offsets)
+                    .map(|(m, offset)| m.mul(offset))
+                    .sum(),
+            );
+        }
+        if self.tail.is_none() {
+            let (f, g): (Vec<_>, Vec<_>) = self
+                .workers
+                .iter()
+                .map(|w| {
+                    let final_foldings = w.final_foldings().expect("the workers are not done");
+                    (final_foldings[0], final_foldings[1])
+                })
+                .unzip();
+            self.tail = Some(TimeProver::new(Witness::new(&f, &g, &self.tail_twist)));
+        }
+        self.round += 1;
+        self.tail.as_mut().and_then(|p| p.next_message())
+    }
+
+    fn fold(&mut self, challenge: F) {
+        match self.tail.as_mut() {
+            Some(p) => p.fold(challenge),
+            None => cfg_iter_mut!(self.workers).for_each(|w| w.fold(challenge)),
+        }
+    }
+
+    fn rounds(&self) -> usize {
+        self.tot_rounds
+    }
+
+    fn round(&self) -> usize {
+        self.round
+    }
+
+    fn final_foldings(&self) -> Option<[F; 2]> {
+        if self.round != self.tot_rounds {
+            return None;
+        }
+        match &self.tail {
+            Some(p) => p.final_foldings(),
+            // with a single worker, the coordinator folds nothing.
+            None => self.workers[0].final_foldings(),
+        }
+    }
+}
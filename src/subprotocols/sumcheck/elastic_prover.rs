@@ -1,14 +1,20 @@
 use ark_ff::Field;
 use ark_std::borrow::Borrow;
 
+use crate::config::ProverConfig;
+use crate::errors::GeminiResult;
 use crate::{iterable::Iterable, SPACE_TIME_THRESHOLD};
 
 use super::prover::RoundMsg;
 use super::{Prover, SpaceProver, TimeProver};
 
 /// Specifier of the prover type (time-efficient or space-efficient).
+///
+/// The space-efficient prover carries the logarithm of its memory budget:
+/// once the folded polynomials have fewer than $2^{\text{threshold}}$ coefficients,
+/// they are loaded in memory and the protocol continues with the time-efficient prover.
 pub enum ElasticProver<S, T> {
-    Space(S),
+    Space(S, usize),
     Time(T),
 }
 
@@ -21,8 +27,22 @@ where
     S2::Item: Borrow<F>,
 {
     /// Initialize the elastic prover.
-    pub fn new(f: S1, g: S2, twist: F) -> Self {
-        Self::Space(SpaceProver::new(f, g, twist))
+    ///
+    /// Fails if the twist is zero.
+    pub fn new(f: S1, g: S2, twist: F) -> GeminiResult<Self> {
+        Self::with_threshold(f, g, twist, SPACE_TIME_THRESHOLD)
+    }
+
+    /// Initialize the elastic prover,
+    /// switching to the time-efficient prover for the last `threshold` rounds.
+    pub fn with_threshold(f: S1, g: S2, twist: F, threshold: usize) -> GeminiResult<Self> {
+        Ok(Self::Space(SpaceProver::new(f, g, twist)?, threshold))
+    }
+
+    /// Initialize the elastic prover with the memory budget and the folding blocks of `config`.
+    pub fn with_config(f: S1, g: S2, twist: F, config: &ProverConfig) -> GeminiResult<Self> {
+        let prover = SpaceProver::with_block_size(f, g, twist, config.folding_block)?;
+        Ok(Self::Space(prover, config.threshold()))
     }
 }
 
@@ -36,15 +56,16 @@ where
 {
     fn next_message(&mut self) -> Option<RoundMsg<F>> {
         match self {
-            Self::Space(p) => p.next_message(),
+            Self::Space(p, _) => p.next_message(),
             Self::Time(p) => p.next_message(),
         }
     }
 
     fn fold(&mut self, challenge: F) {
         match self {
-            Self::Space(p) => {
-                if p.rounds() - p.round() < SPACE_TIME_THRESHOLD {
+            Self::Space(p, threshold) => {
+                if p.rounds() - p.round() < *threshold {
+                    trace_span!("sumcheck_switch", round = p.round());
                     let mut time_prover = TimeProver::from(&*p);
                     time_prover.fold(challenge);
                     *self = Self::Time(time_prover);
@@ -58,21 +79,21 @@ where
 
     fn rounds(&self) -> usize {
         match self {
-            Self::Space(p) => p.rounds(),
+            Self::Space(p, _) => p.rounds(),
             Self::Time(p) => p.rounds(),
         }
     }
 
     fn round(&self) -> usize {
         match self {
-            Self::Space(p) => p.round(),
+            Self::Space(p, _) => p.round(),
             Self::Time(p) => p.round(),
         }
     }
 
     fn final_foldings(&self) -> Option<[F; 2]> {
         match self {
-            Self::Space(p) => p.final_foldings(),
+            Self::Space(p, _) => p.final_foldings(),
             Self::Time(p) => p.final_foldings(),
         }
     }
@@ -0,0 +1,171 @@
+//! The sumcheck as an interactive public-coin protocol.
+//!
+//! [`Sumcheck::prove`] and [`Subclaim::new`] derive the challenges of the verifier from a transcript (Fiat-Shamir).
+//! An [`InteractiveProver`] and an [`InteractiveVerifier`] instead run the protocol round by round,
+//! with the challenges supplied by the caller:
+//! for experimenting with the challenges,
+//! or for embedding the rounds of the sumcheck within a larger interactive protocol.
+//!
+//! Each round, the prover sends [`InteractiveProver::next_message`] to the verifier,
+//! and both receive the challenge of the verifier for the round.
+//! Once the prover has no more messages, it sends [`InteractiveProver::final_foldings`],
+//! and the verifier reduces the claim to a [`Subclaim`] with [`InteractiveVerifier::finish`].
+use ark_ff::Field;
+use ark_std::vec::Vec;
+
+use crate::errors::VerificationError;
+use crate::subprotocols::sumcheck::proof::Sumcheck;
+use crate::subprotocols::sumcheck::prover::{ProverMsgs, RoundMsg};
+use crate::subprotocols::sumcheck::{Prover, Subclaim};
+
+/// Reduce the claim `reduced_claim` of a round with the message `message` and the challenge `challenge`.
+pub(crate) fn reduce_round<F: Field>(reduced_claim: F, message: &RoundMsg<F>, challenge: F) -> F {
+    let RoundMsg(a, b) = message;
+    let c = reduced_claim - a;
+    // evaluate (a + bx + cx2) at r
+    *a + challenge * b + c * challenge.square()
+}
+
+/// A sumcheck prover driven round by round by the caller.
+pub struct InteractiveProver<F: Field, P: Prover<F>> {
+    prover: P,
+    messages: Vec<RoundMsg<F>>,
+    challenges: Vec<F>,
+}
+
+impl<F: Field, P: Prover<F>> InteractiveProver<F, P> {
+    /// Drive the (time, space, or elastic) `prover`.
+    pub fn new(prover: P) -> Self {
+        let rounds = prover.rounds();
+        InteractiveProver {
+            prover,
+            messages: Vec::with_capacity(rounds),
+            challenges: Vec::with_capacity(rounds),
+        }
+    }
+
+    /// Return the message of the current round, or `None` once all rounds are over.
+    ///
+    /// # Panics
+    /// If the message of the previous round has not received its challenge.
+    pub fn next_message(&mut self) -> Option<RoundMsg<F>> {
+        assert_eq!(
+            self.messages.len(),
+            self.challenges.len(),
+            "The previous message has not received its challenge."
+        );
+        let message = self.prover.next_message()?;
+        self.messages.push(message);
+        Some(message)
+    }
+
+    /// Fold the instance with the `challenge` of the verifier for the current round.
+    ///
+    /// # Panics
+    /// If no message is awaiting a challenge.
+    pub fn receive_challenge(&mut self, challenge: F) {
+        assert_eq!(
+            self.messages.len(),
+            self.challenges.len() + 1,
+            "No message is awaiting a challenge."
+        );
+        self.prover.fold(challenge);
+        self.challenges.push(challenge);
+    }
+
+    /// The current round.
+    pub fn round(&self) -> usize {
+        self.prover.round()
+    }
+
+    /// The number of rounds of the protocol.
+    pub fn rounds(&self) -> usize {
+        self.prover.rounds()
+    }
+
+    /// Return the fully-folded instances once all rounds are over, otherwise `None`.
+    pub fn final_foldings(&self) -> Option<[F; 2]> {
+        self.prover.final_foldings()
+    }
+
+    /// Return the messages and the challenges of the protocol, as a [`Sumcheck`].
+    ///
+    /// # Panics
+    /// If the rounds are not over.
+    pub fn finish(self) -> Sumcheck<F> {
+        let final_foldings = self
+            .prover
+            .final_foldings()
+            .expect("The rounds of the sumcheck are not over.");
+        Sumcheck {
+            messages: self.messages,
+            challenges: self.challenges,
+            rounds: self.prover.rounds(),
+            final_foldings: vec![final_foldings],
+        }
+    }
+}
+
+/// A sumcheck verifier driven round by round by the caller.
+pub struct InteractiveVerifier<F: Field> {
+    reduced_claim: F,
+    challenges: Vec<F>,
+}
+
+impl<F: Field> InteractiveVerifier<F> {
+    /// Verify the claim that the scalar product is `asserted_sum`.
+    pub fn new(asserted_sum: F) -> Self {
+        InteractiveVerifier {
+            reduced_claim: asserted_sum,
+            challenges: Vec::new(),
+        }
+    }
+
+    /// Reduce the claim with the `message` of the prover and the `challenge` sent in reply.
+    pub fn receive_message(&mut self, message: &RoundMsg<F>, challenge: F) {
+        self.reduced_claim = reduce_round(self.reduced_claim, message, challenge);
+        self.challenges.push(challenge);
+    }
+
+    /// The claim reduced by the rounds so far.
+    pub fn reduced_claim(&self) -> F {
+        self.reduced_claim
+    }
+
+    /// Check the `final_foldings` of the prover against the reduced claim,
+    /// and return the subclaim.
+    pub fn finish(self, final_foldings: [F; 2]) -> Result<Subclaim<F>, VerificationError> {
+        let found = final_foldings[0] * final_foldings[1];
+        if found == self.reduced_claim {
+            Ok(Subclaim {
+                challenges: self.challenges,
+                final_foldings: vec![final_foldings],
+            })
+        } else {
+            Err(VerificationError::sumcheck(self.reduced_claim, found))
+        }
+    }
+}
+
+impl<F: Field> Sumcheck<F> {
+    /// Verify the messages of the sumcheck `prover_messages` for the claim `asserted_sum`,
+    /// with the `challenges` of an interactive execution (see [`InteractiveVerifier`]).
+    pub fn verify_interactive(
+        prover_messages: &ProverMsgs<F>,
+        challenges: &[F],
+        asserted_sum: F,
+    ) -> Result<Subclaim<F>, VerificationError> {
+        let ProverMsgs(messages, final_foldings) = prover_messages;
+        if messages.len() != challenges.len() || final_foldings.len() != 1 {
+            return Err(VerificationError::Consistency {
+                check: "interactive sumcheck messages",
+            });
+        }
+        let mut verifier = InteractiveVerifier::new(asserted_sum);
+        messages
+            .iter()
+            .zip(challenges)
+            .for_each(|(message, &challenge)| verifier.receive_message(message, challenge));
+        verifier.finish(final_foldings[0])
+    }
+}
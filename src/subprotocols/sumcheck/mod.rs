@@ -10,10 +10,17 @@
 //!
 //! for some random challenges $\rho_0, \dots, \rho_{n-1}$ sent by the verifier
 //! and some $t_0, t_1 \in \FF$.
+//!
+//! Many such claims are proven at once with a [`BatchSumcheck`].
+//! The protocol can also run interactively, with the challenges supplied by the caller
+//! (see [`interactive`]).
+pub mod batch;
+pub mod interactive;
 pub mod proof;
 pub mod prover;
 pub mod streams;
 
+pub mod distributed_prover;
 /// The elastic prover implementation
 pub mod elastic_prover;
 /// The logarithmic-space (quasilinear-time) prover implementation.
@@ -23,7 +30,10 @@ pub mod time_prover;
 
 mod subclaim;
 
+pub use batch::BatchSumcheck;
+pub use distributed_prover::DistributedProver;
 pub use elastic_prover::ElasticProver;
+pub use interactive::{InteractiveProver, InteractiveVerifier};
 pub use prover::{Prover, ProverMsgs};
 pub use space_prover::SpaceProver;
 pub use subclaim::Subclaim;
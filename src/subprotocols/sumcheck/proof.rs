@@ -1,13 +1,15 @@
 //! Scalar-product proof implementation.
 use ark_ff::Field;
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
 use ark_std::borrow::Borrow;
 use ark_std::boxed::Box;
 use ark_std::vec::Vec;
 
-use merlin::Transcript;
 #[cfg(feature = "parallel")]
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
 
+use crate::config::ProverConfig;
+use crate::errors::{GeminiError, GeminiResult};
 use crate::iterable::Iterable;
 use crate::subprotocols::sumcheck::{time_prover::Witness, ElasticProver, SpaceProver, TimeProver};
 use crate::transcript::GeminiTranscript;
@@ -16,16 +18,21 @@ use crate::subprotocols::sumcheck::prover::{ProverMsgs, RoundMsg};
 use crate::subprotocols::sumcheck::Prover;
 
 /// A scalar product proof, containing non-oracle messages, and oracle messages together with their queries and evaluations.
-#[derive(Debug, PartialEq, Eq)]
+/// The failure of a prover that has no final foldings once its rounds are over.
+const UNFINISHED_PROVER: GeminiError = GeminiError::MalformedInstance {
+    reason: "the sumcheck prover has no final foldings after its last round",
+};
+
+#[derive(CanonicalSerialize, CanonicalDeserialize, Debug, PartialEq, Eq)]
 pub struct Sumcheck<F: Field> {
     /// The non-oracle messages sent througout the protocol.
     pub messages: Vec<RoundMsg<F>>,
     /// The challenges sent thropughout the protocol.
     pub challenges: Vec<F>,
     /// The number of rounds in the protocol.
-    rounds: usize,
+    pub(crate) rounds: usize,
     // Folded statements
-    final_foldings: Vec<[F; 2]>,
+    pub(crate) final_foldings: Vec<[F; 2]>,
 }
 
 impl<F: Field> Sumcheck<F> {
@@ -33,8 +40,12 @@ impl<F: Field> Sumcheck<F> {
     /// The input contains a randomness generator and a prover struct.
     /// The prover struct can be either time-efficient or space-efficient
     /// depending on the configuration.
-    pub fn prove<P: Prover<F>>(transcript: &mut Transcript, mut prover: P) -> Self {
+    pub fn prove<P: Prover<F>>(
+        transcript: &mut impl GeminiTranscript,
+        mut prover: P,
+    ) -> GeminiResult<Self> {
         let rounds = prover.rounds();
+        trace_span!("sumcheck", rounds = rounds);
         let mut messages = Vec::with_capacity(rounds);
         let mut challenges = Vec::with_capacity(rounds);
 
@@ -52,25 +63,26 @@ impl<F: Field> Sumcheck<F> {
         }
 
         let rounds = prover.rounds();
-        let final_foldings = vec![prover.final_foldings().unwrap()];
+        let final_foldings = vec![prover.final_foldings().ok_or(UNFINISHED_PROVER)?];
         // Add the final foldings to the transcript
         transcript.append_serializable(b"final-folding", &final_foldings[0][0]);
         transcript.append_serializable(b"final-folding", &final_foldings[0][1]);
 
-        Sumcheck {
+        Ok(Sumcheck {
             messages,
             challenges,
             rounds,
             final_foldings,
-        }
+        })
     }
 
     /// Prove function for a batch of scalar product instances.
     pub fn prove_batch<'a>(
-        transcript: &mut Transcript,
+        transcript: &mut impl GeminiTranscript,
         mut provers: Vec<Box<dyn Prover<F> + 'a>>,
-    ) -> Sumcheck<F> {
+    ) -> GeminiResult<Sumcheck<F>> {
         let rounds = provers.iter().map(|p| p.rounds()).fold(0, usize::max);
+        trace_span!("batch_sumcheck", instances = provers.len(), rounds = rounds);
         let mut messages = Vec::with_capacity(rounds);
         let mut challenges = Vec::with_capacity(rounds);
 
@@ -104,22 +116,34 @@ impl<F: Field> Sumcheck<F> {
         let final_foldings = provers
             .iter()
             .map(|p| {
-                let final_folding = p.final_foldings().unwrap();
+                let final_folding = p.final_foldings().ok_or(UNFINISHED_PROVER)?;
                 transcript.append_serializable(b"final-folding-lhs", &final_folding[0]);
                 transcript.append_serializable(b"final-folding-rhs", &final_folding[1]);
-                final_folding
+                Ok(final_folding)
             })
-            .collect::<Vec<_>>();
-        Sumcheck {
+            .collect::<GeminiResult<Vec<_>>>()?;
+        Ok(Sumcheck {
             messages,
             challenges,
             rounds,
             final_foldings,
-        }
+        })
     }
 
     /// Create a new Proof using the Time prover.
-    pub fn new_time(transcript: &mut Transcript, f: &[F], g: &[F], twist: &F) -> Self {
+    ///
+    /// Fails if either polynomial is empty.
+    pub fn new_time(
+        transcript: &mut impl GeminiTranscript,
+        f: &[F],
+        g: &[F],
+        twist: &F,
+    ) -> GeminiResult<Self> {
+        if f.is_empty() || g.is_empty() {
+            return Err(GeminiError::MalformedInstance {
+                reason: "the polynomials of the sumcheck are empty",
+            });
+        }
         let witness = Witness::new(f, g, twist);
         let prover = TimeProver::new(witness);
 
@@ -127,26 +151,66 @@ impl<F: Field> Sumcheck<F> {
     }
 
     /// Construct a new Proof using the space prover.
-    pub fn new_space<SF1, SF2>(transcript: &mut Transcript, f: SF1, g: SF2, twist: F) -> Self
+    ///
+    /// Fails if the twist is zero.
+    pub fn new_space<SF1, SF2>(transcript: &mut impl GeminiTranscript, f: SF1, g: SF2, twist: F) -> GeminiResult<Self>
     where
         SF1: Iterable,
         SF2: Iterable,
         SF1::Item: Borrow<F>,
         SF2::Item: Borrow<F>,
     {
-        let prover = SpaceProver::new(f, g, twist);
+        let prover = SpaceProver::new(f, g, twist)?;
         Self::prove(transcript, prover)
     }
 
     /// Construct a new Proof using the Elastic prover
-    pub fn new_elastic<SF1, SF2>(transcript: &mut Transcript, f: SF1, g: SF2, twist: F) -> Self
+    pub fn new_elastic<SF1, SF2>(transcript: &mut impl GeminiTranscript, f: SF1, g: SF2, twist: F) -> GeminiResult<Self>
+    where
+        SF1: Iterable,
+        SF2: Iterable,
+        SF1::Item: Borrow<F>,
+        SF2::Item: Borrow<F>,
+    {
+        let prover = ElasticProver::new(f, g, twist)?;
+        Self::prove(transcript, prover)
+    }
+
+    /// Construct a new Proof using the Elastic prover,
+    /// switching to the time-efficient prover for the last `threshold` rounds.
+    pub fn new_elastic_with_threshold<SF1, SF2>(
+        transcript: &mut impl GeminiTranscript,
+        f: SF1,
+        g: SF2,
+        twist: F,
+        threshold: usize,
+    ) -> GeminiResult<Self>
+    where
+        SF1: Iterable,
+        SF2: Iterable,
+        SF1::Item: Borrow<F>,
+        SF2::Item: Borrow<F>,
+    {
+        let prover = ElasticProver::with_threshold(f, g, twist, threshold)?;
+        Self::prove(transcript, prover)
+    }
+
+    /// Construct a new Proof using the Elastic prover,
+    /// with the memory budget and the folding blocks of `config`.
+    pub fn new_elastic_with_config<SF1, SF2>(
+        transcript: &mut impl GeminiTranscript,
+        f: SF1,
+        g: SF2,
+        twist: F,
+        config: &ProverConfig,
+    ) -> GeminiResult<Self>
     where
         SF1: Iterable,
         SF2: Iterable,
         SF1::Item: Borrow<F>,
         SF2::Item: Borrow<F>,
     {
-        let prover = ElasticProver::new(f, g, twist);
+        let prover = ElasticProver::with_config(f, g, twist, config)?;
         Self::prove(transcript, prover)
     }
 
@@ -10,7 +10,7 @@ use ark_std::vec::Vec;
 pub struct RoundMsg<F: Field>(pub(crate) F, pub(crate) F);
 
 /// Messages sent by the prover throughout the protocol.
-#[derive(CanonicalSerialize, Clone, Debug, PartialEq, Eq)]
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
 pub struct ProverMsgs<F: Field>(pub(crate) Vec<RoundMsg<F>>, pub(crate) Vec<[F; 2]>);
 
 impl<F: Field> Sum for RoundMsg<F> {
@@ -20,6 +20,18 @@ impl<F: Field> Sum for RoundMsg<F> {
     }
 }
 
+impl<F: Field> ProverMsgs<F> {
+    /// Messages of a sumcheck with `rounds` rounds and `provers` batched instances, all set to zero.
+    ///
+    /// They have the same size as any real messages, and are used for estimating proof sizes.
+    pub(crate) fn zeroed(rounds: usize, provers: usize) -> Self {
+        ProverMsgs(
+            vec![RoundMsg(F::zero(), F::zero()); rounds],
+            vec![[F::zero(); 2]; provers],
+        )
+    }
+}
+
 impl<F: Field> RoundMsg<F> {
     pub(crate) fn mul(self, rhs: &F) -> Self {
         RoundMsg(self.0 * rhs, self.1 * rhs)
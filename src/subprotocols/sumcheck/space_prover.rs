@@ -1,20 +1,20 @@
-// #[cfg(feature = "parallel")]
-// use rayon::{
-//     iter::{IndexedParallelIterator, ParallelIterator},
-//     slice::ParallelSlice,
-// };
-
 use ark_ff::Field;
 use ark_std::borrow::Borrow;
 use ark_std::cmp::Ordering;
 use ark_std::log2;
+use ark_std::mem;
 use ark_std::vec::Vec;
 
 use super::{prover::Prover, time_prover::TimeProver};
+use crate::config::DEFAULT_FOLDING_BLOCK;
+use crate::errors::{GeminiError, GeminiResult};
 use crate::iterable::Iterable;
+use crate::misc::{ceil_div, pipeline, sequential, Producer};
 use crate::subprotocols::sumcheck::prover::RoundMsg;
 use crate::subprotocols::sumcheck::streams::FoldedPolynomialStream;
-// use crate::{misc::ceil_div, SUMCHECK_BUF_SIZE};
+
+/// The number of coefficients of the foldings read at once when computing a round message.
+const ROUND_CHUNK: usize = 1 << 12;
 
 /// This is the streaming alter-ego of `Witness`.
 /// The witness for the twisted scalar product, where the vectors are stored as streams.
@@ -55,6 +55,42 @@ where
     tot_rounds: usize,
     /// Current twist.
     twist: F,
+    /// The maximum number of elements of the foldings held in memory.
+    cache_limit: usize,
+    /// The foldings of the previous round, if they fit in the cache.
+    cache: Option<FoldingCache<F>>,
+    /// The buffers of the foldings of earlier rounds, refilled by the next round.
+    spare: FoldingCache<F>,
+    /// The number of coefficients read and folded at once from the streams.
+    block_size: usize,
+}
+
+/// The foldings of the witness computed in a previous round, held in memory.
+#[derive(Default)]
+struct FoldingCache<F> {
+    /// The number of challenges the foldings are folded with.
+    depth: usize,
+    /// The folded left-hand side, in stream order.
+    f: Vec<F>,
+    /// The folded right-hand side, in stream order.
+    g: Vec<F>,
+}
+
+impl<F: Field> FoldingCache<F> {
+    /// Collect the streams of the foldings with `depth` challenges,
+    /// reusing the buffers of `self`.
+    fn refill<SF, SG>(mut self, f: &SF, g: &SG, depth: usize) -> Self
+    where
+        SF: Iterable<Item = F>,
+        SG: Iterable<Item = F>,
+    {
+        self.depth = depth;
+        self.f.clear();
+        self.f.extend(f.iter());
+        self.g.clear();
+        self.g.extend(g.iter());
+        self
+    }
 }
 
 // A Stream that will produce the folded polynomial
@@ -89,23 +125,172 @@ where
 {
     /// Create a new space prover.
     /// This will move the witness within the instance, but never modify the initial instance.
-    pub fn new(f: SF, g: SG, twist: F) -> Self {
+    ///
+    /// Fails if the twist is zero.
+    pub fn new(f: SF, g: SG, twist: F) -> GeminiResult<Self> {
+        Self::with_cache(f, g, twist, 0)
+    }
+
+    /// Create a new space prover,
+    /// holding in memory the foldings of the witness once they have at most `cache_limit` elements.
+    ///
+    /// Without the cache, each round folds the witness from scratch with all the challenges so far.
+    /// Once the foldings are cached, each round folds the cached foldings of the previous round
+    /// with the last challenge.
+    /// The cache holds the buffers of two consecutive rounds, refilled in turn,
+    /// i.e. at most `3 * cache_limit / 2` elements.
+    pub fn with_cache(f: SF, g: SG, twist: F, cache_limit: usize) -> GeminiResult<Self> {
+        Self::with_options(f, g, twist, cache_limit, DEFAULT_FOLDING_BLOCK)
+    }
+
+    /// Create a new space prover,
+    /// reading and folding the streams in blocks of `block_size` coefficients
+    /// (see [`FoldedPolynomialStream`]).
+    pub fn with_block_size(f: SF, g: SG, twist: F, block_size: usize) -> GeminiResult<Self> {
+        Self::with_options(f, g, twist, 0, block_size)
+    }
+
+    fn with_options(
+        f: SF,
+        g: SG,
+        twist: F,
+        cache_limit: usize,
+        block_size: usize,
+    ) -> GeminiResult<Self> {
+        // the round messages are computed with the inverse of the twist.
+        if twist.is_zero() {
+            return Err(GeminiError::MalformedInstance {
+                reason: "the twist of the sumcheck is zero",
+            });
+        }
         let witness = WitnessStream::new(f, g, twist);
         let tot_rounds = witness.required_rounds();
         let challenges = Vec::with_capacity(tot_rounds);
         let twisted_challenges = Vec::with_capacity(tot_rounds);
         let round = 0;
-        SpaceProver {
+        Ok(SpaceProver {
             challenges,
             twisted_challenges,
             witness,
             round,
             tot_rounds,
             twist,
+            cache_limit,
+            cache: None,
+            spare: FoldingCache::default(),
+            block_size,
+        })
+    }
+}
+
+/// The partial sums of a round message, over the pairs of coefficients of the foldings.
+struct RoundSums<F> {
+    a: F,
+    b: F,
+    twist: F,
+    twist2inv: F,
+    twist_runner: F,
+}
+
+impl<F: Field> RoundSums<F> {
+    /// Prepare the sums over `pairs` pairs of coefficients.
+    fn new(twist: F, pairs: usize) -> Self {
+        Self {
+            a: F::zero(),
+            b: F::zero(),
+            twist,
+            twist2inv: twist.square().inverse().unwrap(),
+            twist_runner: twist.pow([(pairs * 2 - 2) as u64]),
+        }
+    }
+
+    /// Add the next coefficients of the foldings,
+    /// as an even number of (left-hand side, right-hand side) pairs,
+    /// each odd coefficient followed by the even one.
+    fn add(&mut self, coefficients: &[(F, F)]) {
+        for pair in coefficients.chunks_exact(2) {
+            let (f_odd, g_odd) = pair[0];
+            let (f_even, g_even) = pair[1];
+
+            // Add to the partial sum
+            self.a += f_even * g_even * self.twist_runner;
+            self.b += (f_even * g_odd + f_odd * g_even * self.twist) * self.twist_runner;
+            self.twist_runner *= self.twist2inv;
         }
     }
 }
 
+/// Return the message of the round, from the streams of the folded polynomials.
+///
+/// Once the foldings have more than [`ROUND_CHUNK`] coefficients,
+/// the next coefficients are read (and folded) while computing on the current ones
+/// (see [`pipeline`]).
+fn round_message<F, SF, SG>(folded_f: &SF, folded_g: &SG, twist: F) -> RoundMsg<F>
+where
+    F: Field,
+    SF: Iterable,
+    SF::Item: Borrow<F>,
+    SG: Iterable,
+    SG::Item: Borrow<F>,
+{
+    let f_coefficients = folded_f.len();
+    let g_coefficients = folded_g.len();
+
+    // Align the streams: if one stream is much larger than the other,
+    // some positions must be skipped.
+    let (f_skip, g_skip) = match f_coefficients.cmp(&g_coefficients) {
+        Ordering::Greater => (f_coefficients - g_coefficients + (g_coefficients % 2), 0),
+        Ordering::Less => (0, g_coefficients - f_coefficients + (f_coefficients % 2)),
+        Ordering::Equal => (0, 0),
+    };
+    let f_coefficients = f_coefficients - f_skip;
+    let g_coefficients = g_coefficients - g_skip;
+
+    // Compute the pairs of coefficients that will be used.
+    let f_pairs = ceil_div(f_coefficients, 2);
+    let g_pairs = ceil_div(g_coefficients, 2);
+    assert_eq!(f_pairs, g_pairs);
+
+    let produce = |producer: &mut Producer<'_, Vec<(F, F)>>| {
+        let mut f_it = folded_f.iter();
+        let mut g_it = folded_g.iter();
+        f_it.advance_by(f_skip).unwrap();
+        g_it.advance_by(g_skip).unwrap();
+
+        // Complete alignment: as we process coefficients two by two,
+        // we have to start either from an odd coefficient (and set the even to zero),
+        // or vice-versa.
+        let f_it = (f_coefficients & 1 != 0)
+            .then(F::zero)
+            .into_iter()
+            .chain(f_it.map(|f| *f.borrow()));
+        let g_it = (g_coefficients & 1 != 0)
+            .then(F::zero)
+            .into_iter()
+            .chain(g_it.map(|g| *g.borrow()));
+        let mut coefficients = f_it.zip(g_it);
+        loop {
+            let mut chunk = producer.chunk();
+            chunk.clear();
+            chunk.extend((&mut coefficients).take(ROUND_CHUNK));
+            if chunk.is_empty() || !producer.send(chunk) {
+                return;
+            }
+        }
+    };
+
+    // Compute the polynomial of the partial sum q = a + bx + c x2,
+    // For the evaluations, send only the coefficients a, b of the polynomial.
+    let mut sums = RoundSums::new(twist, f_pairs);
+    let consume = |chunk: &mut Vec<(F, F)>| sums.add(chunk);
+    if f_pairs * 2 > ROUND_CHUNK {
+        pipeline(produce, consume);
+    } else {
+        sequential(produce, consume);
+    }
+    RoundMsg(sums.a, sums.b)
+}
+
 impl<F, S1, S2> Prover<F> for SpaceProver<F, S1, S2>
 where
     F: Field,
@@ -124,119 +309,59 @@ where
         if self.round == self.tot_rounds {
             return None;
         }
+        // each round is a pass over the streams.
+        trace_span!(
+            "sumcheck_pass",
+            round = self.round,
+            len = self.witness.f.len()
+        );
 
-        let folded_f = FoldedPolynomialStream::new(&self.witness.f, &self.twisted_challenges);
-        let folded_g = FoldedPolynomialStream::new(&self.witness.g, &self.challenges);
-
-        // // The size of a step in this round.
-        // // This corresponds to the number of coefficients to read in the initial stream
-        // // in order to compute the next coefficient.
-        let mut f_coefficients = folded_f.len();
-        let mut g_coefficients = folded_g.len();
-
-        let mut f_it = folded_f.iter();
-        let mut g_it = folded_g.iter();
-
-        // Align the streams: if one stream is much larger than the other,
-        // some positions must be skipped.
-        match f_coefficients.cmp(&g_coefficients) {
-            Ordering::Greater => {
-                let delta = f_coefficients - g_coefficients + (g_coefficients % 2);
-                f_it.advance_by(delta).unwrap();
-                f_coefficients -= delta;
+        let depth = self.challenges.len();
+        let (message, cache) = match self.cache.take() {
+            // fold the foldings of the previous round with the last challenges.
+            Some(cache) => {
+                let (f, g) = (cache.f.as_slice(), cache.g.as_slice());
+                let folded_f = FoldedPolynomialStream::with_block_size(
+                    &f,
+                    &self.twisted_challenges[cache.depth..],
+                    self.block_size,
+                );
+                let folded_g = FoldedPolynomialStream::with_block_size(
+                    &g,
+                    &self.challenges[cache.depth..],
+                    self.block_size,
+                );
+                let next = mem::take(&mut self.spare).refill(&folded_f, &folded_g, depth);
+                let message = round_message(&next.f.as_slice(), &next.g.as_slice(), self.twist);
+                self.spare = cache;
+                (message, Some(next))
             }
-            Ordering::Less => {
-                let delta = g_coefficients - f_coefficients + (f_coefficients % 2);
-                g_it.advance_by(delta).unwrap();
-                g_coefficients -= delta;
+            None => {
+                let folded_f = FoldedPolynomialStream::with_block_size(
+                    &self.witness.f,
+                    &self.twisted_challenges,
+                    self.block_size,
+                );
+                let folded_g = FoldedPolynomialStream::with_block_size(
+                    &self.witness.g,
+                    &self.challenges,
+                    self.block_size,
+                );
+                if folded_f.len() + folded_g.len() <= self.cache_limit {
+                    let cache = mem::take(&mut self.spare).refill(&folded_f, &folded_g, depth);
+                    let message =
+                        round_message(&cache.f.as_slice(), &cache.g.as_slice(), self.twist);
+                    (message, Some(cache))
+                } else {
+                    (round_message(&folded_f, &folded_g, self.twist), None)
+                }
             }
-            Ordering::Equal => (),
-        }
-
-        // Complete alignment: as we process coefficients two by two,
-        // we have to start either from an odd coefficient (and set the even to zero),
-        // or vice-versa.
-        let (f_odd, f_even) = if f_coefficients & 1 != 0 {
-            (F::zero(), f_it.next().unwrap())
-        } else {
-            (f_it.next().unwrap(), f_it.next().unwrap())
         };
-
-        let (g_odd, g_even) = if g_coefficients & 1 != 0 {
-            (F::zero(), g_it.next().unwrap())
-        } else {
-            (g_it.next().unwrap(), g_it.next().unwrap())
-        };
-
-        // // Compute the pairs of coefficients that will be used.
-        let f_pairs = (f_coefficients - 2 + f_coefficients % 2) / 2;
-        let g_pairs = (g_coefficients - 2 + g_coefficients % 2) / 2;
-        assert_eq!(f_pairs, g_pairs);
-
-        // Compute the polynomial of the partial sum q = a + bx + c x2,
-        // For the evaluations, send only the coefficients a, b of the polynomial.
-        let twist2inv = self.twist.square().inverse().unwrap();
-        let mut twist_runner = self.twist.pow(&[(f_pairs * 2) as u64]);
-
-        let mut a = f_even * g_even * twist_runner;
-        let mut b = (f_even * g_odd + f_odd * g_even * self.twist) * twist_runner;
-        twist_runner *= twist2inv;
-
-        // #[cfg(not(feature = "parallel"))]
-        for _i in 0..f_pairs {
-            let f_odd = f_it.next().unwrap();
-            let g_odd = g_it.next().unwrap();
-
-            let f_even = f_it.next().unwrap();
-            let g_even = g_it.next().unwrap();
-
-            // Add to the partial sum
-            a += f_even * g_even * twist_runner;
-            b += (f_even * g_odd + f_odd * g_even * self.twist) * twist_runner;
-            twist_runner *= twist2inv;
-        }
-
-        // #[cfg(feature = "parallel")]
-        // for _i in 0..ceil_div(f_pairs, SUMCHECK_BUF_SIZE) {
-        //     let f_buf = (&mut f_it).take(SUMCHECK_BUF_SIZE).collect::<Vec<_>>();
-        //     let g_buf = (&mut g_it).take(SUMCHECK_BUF_SIZE).collect::<Vec<_>>();
-        //     let mut twist_runner_a = twist_runner;
-        //     let twist = self.twist;
-        //     a += f_buf
-        //         .par_chunks(2)
-        //         .zip(g_buf.par_chunks(2))
-        //         .map(|(f_chunk, g_chunk)| {
-        //             let _f_odd = f_chunk[0];
-        //             let f_even = f_chunk[1];
-        //             let _g_odd = g_chunk[0];
-        //             let g_even = g_chunk[1];
-
-        //             let result = f_even * g_even * twist_runner;
-        //             twist_runner_a *= twist2inv;
-        //             result
-        //         })
-        //         .sum::<F>();
-
-        //     let mut twist_runner_b = twist_runner;
-        //     b += f_buf
-        //         .par_chunks(2)
-        //         .zip(g_buf.par_chunks(2))
-        //         .map(|(f_chunk, g_chunk)| {
-        //             let f_odd = f_chunk[0];
-        //             let f_even = f_chunk[1];
-        //             let g_odd = g_chunk[0];
-        //             let g_even = g_chunk[1];
-
-        //             let result = (f_even * g_odd + f_odd * g_even * twist) * twist_runner;
-        //             twist_runner_b *= twist2inv;
-        //             result
-        //         })
-        //         .sum::<F>();
-        // }
+        self.cache = cache;
 
         // Increment the round counter.
         self.round += 1;
-        Some(RoundMsg(a, b))
+        Some(message)
     }
 
     /// Fold the current instance with the randomness r.
@@ -258,10 +383,35 @@ where
     }
 
     fn final_foldings(&self) -> Option<[F; 2]> {
-        let folded_f = FoldedPolynomialStream::new(&self.witness.f, &self.twisted_challenges);
-        let folded_g = FoldedPolynomialStream::new(&self.witness.g, &self.challenges);
-        let lhs = folded_f.iter().next()?;
-        let rhs = folded_g.iter().next()?;
+        let (lhs, rhs) = match &self.cache {
+            Some(cache) => {
+                let (f, g) = (cache.f.as_slice(), cache.g.as_slice());
+                let folded_f = FoldedPolynomialStream::with_block_size(
+                    &f,
+                    &self.twisted_challenges[cache.depth..],
+                    self.block_size,
+                );
+                let folded_g = FoldedPolynomialStream::with_block_size(
+                    &g,
+                    &self.challenges[cache.depth..],
+                    self.block_size,
+                );
+                (folded_f.iter().next()?, folded_g.iter().next()?)
+            }
+            None => {
+                let folded_f = FoldedPolynomialStream::with_block_size(
+                    &self.witness.f,
+                    &self.twisted_challenges,
+                    self.block_size,
+                );
+                let folded_g = FoldedPolynomialStream::with_block_size(
+                    &self.witness.g,
+                    &self.challenges,
+                    self.block_size,
+                );
+                (folded_f.iter().next()?, folded_g.iter().next()?)
+            }
+        };
         (self.round == self.tot_rounds).then_some([lhs, rhs])
     }
 }
@@ -275,21 +425,36 @@ where
     S2::Item: Borrow<F>,
 {
     fn from(sp: &SpaceProver<F, S1, S2>) -> Self {
-        // define the streams of folded polynomials for the current round
-        let folded_f = FoldedPolynomialStream::new(&sp.witness.f, &sp.twisted_challenges);
-        let folded_g = FoldedPolynomialStream::new(&sp.witness.g, &sp.challenges);
-
-        // fill (in reverse) with the folded polynomials
-        let mut f = vec![F::default(); folded_f.len()];
-        let mut g = vec![F::default(); folded_g.len()];
-        f.iter_mut()
-            .rev()
-            .zip(folded_f.iter())
-            .for_each(|(dst, src)| *dst = src);
-        g.iter_mut()
-            .rev()
-            .zip(folded_g.iter())
-            .for_each(|(dst, src)| *dst = src);
+        // fill (in reverse) with the folded polynomials of the current round
+        let (f, g) = match &sp.cache {
+            Some(cache) => {
+                let (f, g) = (cache.f.as_slice(), cache.g.as_slice());
+                let folded_f = FoldedPolynomialStream::with_block_size(
+                    &f,
+                    &sp.twisted_challenges[cache.depth..],
+                    sp.block_size,
+                );
+                let folded_g = FoldedPolynomialStream::with_block_size(
+                    &g,
+                    &sp.challenges[cache.depth..],
+                    sp.block_size,
+                );
+                (collect_reversed(&folded_f), collect_reversed(&folded_g))
+            }
+            None => {
+                let folded_f = FoldedPolynomialStream::with_block_size(
+                    &sp.witness.f,
+                    &sp.twisted_challenges,
+                    sp.block_size,
+                );
+                let folded_g = FoldedPolynomialStream::with_block_size(
+                    &sp.witness.g,
+                    &sp.challenges,
+                    sp.block_size,
+                );
+                (collect_reversed(&folded_f), collect_reversed(&folded_g))
+            }
+        };
 
         // copy other informations such us round(s) and twist.
         let round = sp.round;
@@ -302,6 +467,18 @@ where
             round,
             twist,
             tot_rounds,
+            scratch: Vec::new(),
         }
     }
 }
+
+/// Collect the stream `folded` in reverse order.
+fn collect_reversed<F: Field, S: Iterable<Item = F>>(folded: &S) -> Vec<F> {
+    let mut coefficients = vec![F::default(); folded.len()];
+    coefficients
+        .iter_mut()
+        .rev()
+        .zip(folded.iter())
+        .for_each(|(dst, src)| *dst = src);
+    coefficients
+}
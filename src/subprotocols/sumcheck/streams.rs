@@ -2,6 +2,7 @@ use ark_ff::Field;
 use ark_std::borrow::Borrow;
 use ark_std::vec::Vec;
 
+use crate::config::DEFAULT_FOLDING_BLOCK;
 use crate::iterable::Iterable;
 use crate::misc::ceil_div;
 
@@ -139,6 +140,13 @@ where
 }
 
 /// Stream implementation of foleded polynomial.
+///
+/// The base stream is read in blocks of `block_size` coefficients (a power of two),
+/// each folded at once before moving on to the next block:
+/// up to a depth of $\log_2$ `block_size`, the foldings are computed in place on the block,
+/// and only the deeper foldings are computed along the polynomial tree.
+/// Larger blocks make fewer and more sequential reads of the base stream,
+/// while holding `block_size` coefficients in memory.
 #[derive(Clone, Copy)]
 pub struct FoldedPolynomialStream<'a, F, S>(FoldedPolynomialTree<'a, F, S>, usize);
 /// Iterator implementation of foleded polynomial.
@@ -146,6 +154,16 @@ pub struct FoldedPolynomialStreamIter<'a, F, I> {
     challenges: &'a [F],
     iterator: I,
     stack: Vec<(usize, F)>,
+    /// The depth of the foldings computed in place on the blocks.
+    block_depth: usize,
+    /// The number of coefficients of the base stream in a block.
+    block_size: usize,
+    /// The current block, folded `block_depth` times.
+    block: Vec<F>,
+    /// The position of the next folded coefficient in the current block.
+    position: usize,
+    /// The zeros padding the base stream, still to be read into the first block.
+    padding: usize,
 }
 
 impl<'a, F, S> FoldedPolynomialStream<'a, F, S>
@@ -154,11 +172,17 @@ where
     F: Field,
     S::Item: Borrow<F>,
 {
-    /// Initialize a new folded polynomial stream.
+    /// Initialize a new folded polynomial stream,
+    /// reading blocks of the default size.
     pub fn new(coefficients: &'a S, challenges: &'a [F]) -> Self {
+        Self::with_block_size(coefficients, challenges, DEFAULT_FOLDING_BLOCK)
+    }
+
+    /// Initialize a new folded polynomial stream,
+    /// reading blocks of `block_size` coefficients (rounded up to a power of two).
+    pub fn with_block_size(coefficients: &'a S, challenges: &'a [F], block_size: usize) -> Self {
         let tree = FoldedPolynomialTree::new(coefficients, challenges);
-        let len = challenges.len();
-        Self(tree, len)
+        Self(tree, block_size.next_power_of_two())
     }
 }
 
@@ -174,11 +198,23 @@ where
     fn iter(&self) -> Self::Iter {
         let iterator = self.0.coefficients.iter();
         let challenges = self.0.challenges;
-        let stack = init_stack(self.0.coefficients.len(), challenges.len());
+        let block_depth = usize::min(challenges.len(), self.1.trailing_zeros() as usize);
+        // the padding of the deeper levels goes on the stack,
+        // the padding of the levels folded in place goes in the first block.
+        let (stack, padding): (Vec<_>, Vec<_>) =
+            init_stack::<F>(self.0.coefficients.len(), challenges.len())
+                .into_iter()
+                .partition(|&(level, _)| level >= block_depth);
+        let padding = padding.iter().map(|&(level, _)| 1 << level).sum();
         FoldedPolynomialStreamIter {
             iterator,
             challenges,
             stack,
+            block_depth,
+            block_size: self.1,
+            block: Vec::new(),
+            position: 0,
+            padding,
         }
     }
 
@@ -187,6 +223,38 @@ where
     }
 }
 
+impl<'a, F, I> FoldedPolynomialStreamIter<'a, F, I>
+where
+    F: Field,
+    I: Iterator,
+    I::Item: Borrow<F>,
+{
+    /// Read the next block from the base stream, and fold it `block_depth` times in place.
+    /// Return `None` if the base stream is over.
+    fn next_block(&mut self) -> Option<()> {
+        self.block.clear();
+        self.block.resize(self.padding, F::zero());
+        self.padding = 0;
+        let remaining = self.block_size - self.block.len();
+        self.block
+            .extend((&mut self.iterator).take(remaining).map(|x| *x.borrow()));
+        if self.block.is_empty() {
+            return None;
+        }
+
+        let mut len = self.block.len();
+        for challenge in &self.challenges[..self.block_depth] {
+            len /= 2;
+            for i in 0..len {
+                self.block[i] = self.block[2 * i] * challenge + self.block[2 * i + 1];
+            }
+        }
+        self.block.truncate(len);
+        self.position = 0;
+        Some(())
+    }
+}
+
 impl<'a, F, I> Iterator for FoldedPolynomialStreamIter<'a, F, I>
 where
     F: Field,
@@ -206,17 +274,12 @@ where
 
                 let folded_coefficient = rhs * self.challenges[level] + lhs;
                 (level + 1, folded_coefficient)
-            } else if target_level > 0 && (len == 0 || (len > 0 && self.stack[len - 1].0 != 0)) {
-                // If the target level is strictly positive, there's no need to put elements of level zero in the stream.
-                // We can immediately read 2 elements from the stream and push an element of the form (1, folded_coefficient).
-                // Nota bene: this branch is not needed, but brings in a decent speed-up for the resulting implementation.
-                let rhs = self.iterator.next()?;
-                let lhs = self.iterator.next()?;
-
-                let folded_coefficient = self.challenges[0] * rhs.borrow() + lhs.borrow();
-                (1, folded_coefficient)
             } else {
-                (0, *self.iterator.next()?.borrow())
+                if self.position == self.block.len() {
+                    self.next_block()?;
+                }
+                self.position += 1;
+                (self.block_depth, self.block[self.position - 1])
             };
 
             // do not add to the stack the coefficient of the folded polynomial, but instead return it.
@@ -227,6 +290,25 @@ where
             }
         }
     }
+
+    /// Once the (zero-padded) most significant coefficient has been produced
+    /// and the current block is over,
+    /// each folded coefficient depends on exactly `2^challenges.len()` coefficients of the base stream:
+    /// skipping folded coefficients only skips the base stream,
+    /// in constant time if the base stream supports it (e.g., for slices and memory-mapped files).
+    fn advance_by(&mut self, n: usize) -> Result<(), usize> {
+        let mut advanced = 0;
+        while advanced < n
+            && (!self.stack.is_empty() || self.padding != 0 || self.position < self.block.len())
+        {
+            self.next().ok_or(advanced)?;
+            advanced += 1;
+        }
+        let chunk_size = 1 << self.challenges.len();
+        self.iterator
+            .advance_by((n - advanced) * chunk_size)
+            .map_err(|skipped| advanced + skipped / chunk_size)
+    }
 }
 
 #[test]
@@ -240,7 +322,7 @@ fn test_folded_polynomial() {
     let challenges = vec![F::one(), two];
     let coefficients_stream = coefficients.as_slice();
     let foldstream = FoldedPolynomialTree::new(&coefficients_stream, challenges.as_slice());
-    let fold_stream = FoldedPolynomialStream(foldstream, 2);
+    let fold_stream = FoldedPolynomialStream(foldstream, 1 << 2);
     assert_eq!(fold_stream.len(), 1);
     assert_eq!(
         fold_stream.iter().next(),
@@ -252,10 +334,68 @@ fn test_folded_polynomial() {
     let challenges = vec![F::one(); 4];
     let coefficients_stream = coefficients.as_slice();
     let foldstream = FoldedPolynomialTree::new(&coefficients_stream, challenges.as_slice());
-    let fold_stream = FoldedPolynomialStream(foldstream, 4).iter();
+    let fold_stream = FoldedPolynomialStream(foldstream, 1 << 4).iter();
     assert_eq!(fold_stream.last(), Some(coefficients.iter().sum()));
 }
 
+#[test]
+fn test_folded_polynomial_advance_by() {
+    use ark_bls12_381::Fr as F;
+    use ark_std::test_rng;
+    use ark_std::UniformRand;
+
+    let rng = &mut test_rng();
+    let coefficients = (0..100).map(|_| F::rand(rng)).collect::<Vec<_>>();
+    let challenges = (0..3).map(|_| F::rand(rng)).collect::<Vec<_>>();
+    let coefficients_stream = coefficients.as_slice();
+    let expected = FoldedPolynomialStream::new(&coefficients_stream, &challenges)
+        .iter()
+        .collect::<Vec<_>>();
+    for block_size in [1, 2, 8, 1 << 10] {
+        let fold_stream =
+            FoldedPolynomialStream::with_block_size(&coefficients_stream, &challenges, block_size);
+        assert_eq!(expected.len(), fold_stream.len());
+        for n in 0..expected.len() {
+            let mut it = fold_stream.iter();
+            it.advance_by(n).unwrap();
+            assert!(it.eq(expected[n..].iter().copied()));
+        }
+        let mut it = fold_stream.iter();
+        assert_eq!(it.advance_by(expected.len() + 1), Err(expected.len()));
+    }
+}
+
+#[test]
+fn test_folded_polynomial_blocks() {
+    use ark_bls12_381::Fr as F;
+    use ark_std::test_rng;
+    use ark_std::UniformRand;
+
+    let rng = &mut test_rng();
+    let challenges = (0..6).map(|_| F::rand(rng)).collect::<Vec<_>>();
+    for len in [1, 63, 64, 100, 257] {
+        let coefficients = (0..len).map(|_| F::rand(rng)).collect::<Vec<_>>();
+        let coefficients_stream = coefficients.as_slice();
+        for depth in 0..=challenges.len() {
+            let challenges = &challenges[..depth];
+            // blocks of a single coefficient fold along the polynomial tree only.
+            let expected =
+                FoldedPolynomialStream::with_block_size(&coefficients_stream, challenges, 1)
+                    .iter()
+                    .collect::<Vec<_>>();
+            assert_eq!(expected.len(), ceil_div(len, 1 << depth));
+            for block_size in [0, 2, 5, 16, 1 << 12] {
+                let fold_stream = FoldedPolynomialStream::with_block_size(
+                    &coefficients_stream,
+                    challenges,
+                    block_size,
+                );
+                assert!(fold_stream.iter().eq(expected.iter().copied()));
+            }
+        }
+    }
+}
+
 #[test]
 fn test_folded_polynomial_tree() {
     use ark_bls12_381::Fr as F;
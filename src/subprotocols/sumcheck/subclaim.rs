@@ -1,9 +1,9 @@
 use ark_ff::Field;
 use ark_std::vec::Vec;
-use merlin::Transcript;
 
 use crate::errors::VerificationError;
 use crate::misc::ip;
+use crate::subprotocols::sumcheck::interactive::reduce_round;
 use crate::subprotocols::sumcheck::prover::ProverMsgs;
 use crate::transcript::GeminiTranscript;
 
@@ -21,7 +21,7 @@ impl<F: Field> Subclaim<F> {
     /// Generate a new subclaim
     /// from the non-oracle messages from the prover.
     pub fn new(
-        transcript: &mut Transcript,
+        transcript: &mut impl GeminiTranscript,
         prover_messages: &ProverMsgs<F>,
         asserted_sum: F,
     ) -> Result<Self, VerificationError> {
@@ -32,18 +32,24 @@ impl<F: Field> Subclaim<F> {
         transcript.append_serializable(b"final-folding", &final_foldings[0][0]);
         transcript.append_serializable(b"final-folding", &final_foldings[0][1]);
 
-        if final_foldings[0][0] * final_foldings[0][1] == reduced_claim {
+        let found = final_foldings[0][0] * final_foldings[0][1];
+        if found == reduced_claim {
             Ok(Self {
                 challenges,
                 final_foldings: final_foldings.to_vec(),
             })
         } else {
-            Err(VerificationError)
+            Err(VerificationError::sumcheck(reduced_claim, found))
         }
     }
 
+    /// Generate a new subclaim for a batch of sumchecks
+    /// (see [`BatchSumcheck`](super::BatchSumcheck)),
+    /// from the non-oracle messages from the prover and the sums asserted by each instance of the batch.
+    ///
+    /// The final foldings of the subclaim are in the order of `asserted_sums`.
     pub fn new_batch(
-        transcript: &mut Transcript,
+        transcript: &mut impl GeminiTranscript,
         prover_messages: &ProverMsgs<F>,
         asserted_sums: &[F],
     ) -> Result<Self, VerificationError> {
@@ -70,12 +76,15 @@ impl<F: Field> Subclaim<F> {
                 final_foldings: final_foldings.to_vec(),
             })
         } else {
-            Err(VerificationError)
+            Err(VerificationError::sumcheck(
+                reduced_claim,
+                expected_reduced_claim,
+            ))
         }
     }
 
     fn reduce(
-        transcript: &mut Transcript,
+        transcript: &mut impl GeminiTranscript,
         messages: &[RoundMsg<F>],
         asserted_sum: F,
     ) -> (Vec<F>, F) {
@@ -87,11 +96,7 @@ impl<F: Field> Subclaim<F> {
             transcript.append_serializable(b"evaluations", message);
             let r = transcript.get_challenge::<F>(b"challenge");
             challenges.push(r);
-
-            let RoundMsg(a, b) = message;
-            let c = reduced_claim - a;
-            // evaluate (a + bx + cx2) at r
-            reduced_claim = *a + r * b + c * r.square();
+            reduced_claim = reduce_round(reduced_claim, message, r);
         }
         (challenges, reduced_claim)
     }
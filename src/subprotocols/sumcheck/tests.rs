@@ -54,7 +54,7 @@ fn test_messages_consistency() {
     let f_stream = rev_f.as_slice();
     let g_stream = rev_g.as_slice();
 
-    let mut space_prover = SpaceProver::new(f_stream, g_stream, twist);
+    let mut space_prover = SpaceProver::new(f_stream, g_stream, twist).unwrap();
 
     // Run the next-message function on the space and the time prover,
     // Check the returned messages are equal.
@@ -84,8 +84,9 @@ fn test_messages_consistency() {
     // now check the aggregated data.
     let mut space_transcript = Transcript::new(crate::PROTOCOL_NAME);
     let mut time_transcript = Transcript::new(crate::PROTOCOL_NAME);
-    let space_proof = Sumcheck::<F>::new_space(&mut space_transcript, f_stream, g_stream, twist);
-    let time_proof = Sumcheck::<F>::new_time(&mut time_transcript, &f, &g, &twist);
+    let space_proof =
+        Sumcheck::<F>::new_space(&mut space_transcript, f_stream, g_stream, twist).unwrap();
+    let time_proof = Sumcheck::<F>::new_time(&mut time_transcript, &f, &g, &twist).unwrap();
     assert_eq!(space_proof.messages, time_proof.messages);
 }
 
@@ -108,8 +109,9 @@ fn test_consistency_elastic() {
     let g_stream = rev_g.as_slice();
 
     // now check the aggregated data.
-    let time_proof = Sumcheck::<F>::new_time(&mut time_transcript, &f, &g, &twist);
-    let elastic_proof = Sumcheck::<F>::new_elastic(&mut transcript, f_stream, g_stream, twist);
+    let time_proof = Sumcheck::<F>::new_time(&mut time_transcript, &f, &g, &twist).unwrap();
+    let elastic_proof =
+        Sumcheck::<F>::new_elastic(&mut transcript, f_stream, g_stream, twist).unwrap();
     assert_eq!(time_proof.messages, elastic_proof.messages);
 }
 
@@ -130,7 +132,7 @@ fn test_messages_consistency_with_different_lengths() {
     let f_stream = rev_f.as_slice();
     let g_stream = rev_g.as_slice();
 
-    let mut space_prover = SpaceProver::new(f_stream, g_stream, twist);
+    let mut space_prover = SpaceProver::new(f_stream, g_stream, twist).unwrap();
 
     // Run the next-message function on the space and the time prover,
     // Check the returned messages are equal.
@@ -140,6 +142,52 @@ fn test_messages_consistency_with_different_lengths() {
     );
 }
 
+#[test]
+fn test_messages_consistency_with_cache() {
+    let rng = &mut ark_std::test_rng();
+    let twist = F::rand(rng);
+
+    let f = DensePolynomial::<F>::rand(92, rng);
+    let g = DensePolynomial::<F>::rand(57, rng);
+    let f_stream = Reverse(f.coeffs());
+    let g_stream = Reverse(g.coeffs());
+    let challenges = (0..7).map(|_| F::rand(rng)).collect::<Vec<_>>();
+
+    let mut space_prover = SpaceProver::new(f_stream, g_stream, twist).unwrap();
+    let mut expected = Vec::new();
+    while let Some(message) = space_prover.next_message() {
+        expected.push(message);
+        space_prover.fold(challenges[expected.len() - 1]);
+    }
+    let expected_foldings = space_prover.final_foldings();
+
+    // the cache is filled at different rounds, or never.
+    for cache_limit in [0, 20, 80, 160, usize::MAX] {
+        let mut space_prover =
+            SpaceProver::with_cache(f_stream, g_stream, twist, cache_limit).unwrap();
+        let mut messages = Vec::new();
+        while let Some(message) = space_prover.next_message() {
+            messages.push(message);
+            space_prover.fold(challenges[messages.len() - 1]);
+            if messages.len() == 3 {
+                let time_prover = TimeProver::from(&space_prover);
+                let expected_prover = {
+                    let mut prover = SpaceProver::new(f_stream, g_stream, twist).unwrap();
+                    for challenge in &challenges[..3] {
+                        prover.next_message();
+                        prover.fold(*challenge);
+                    }
+                    TimeProver::from(&prover)
+                };
+                assert_eq!(time_prover.f, expected_prover.f);
+                assert_eq!(time_prover.g, expected_prover.g);
+            }
+        }
+        assert_eq!(messages, expected);
+        assert_eq!(space_prover.final_foldings(), expected_foldings);
+    }
+}
+
 #[test]
 fn test_folding_consistency() {
     use crate::iterable::Iterable;
@@ -220,7 +268,7 @@ fn test_sumcheck_correctness() {
     let mut prover_transcript = Transcript::new(crate::PROTOCOL_NAME);
     let mut verifier_transcript = Transcript::new(crate::PROTOCOL_NAME);
 
-    let sumcheck = Sumcheck::new_time(&mut prover_transcript, &f, &g, &twist);
+    let sumcheck = Sumcheck::new_time(&mut prover_transcript, &f, &g, &twist).unwrap();
     let prover_messages = sumcheck.prover_messages();
     let subclaim = Subclaim::new(&mut verifier_transcript, &prover_messages, asserted_sum);
     assert!(subclaim.is_ok())
@@ -261,7 +309,7 @@ fn test_batch_sumcheck_correctness() {
 
     let mut prover_transcript = Transcript::new(crate::PROTOCOL_NAME);
     let mut verifier_transcript = Transcript::new(crate::PROTOCOL_NAME);
-    let sumcheck = Sumcheck::prove_batch(&mut prover_transcript, provers);
+    let sumcheck = Sumcheck::prove_batch(&mut prover_transcript, provers).unwrap();
     let prover_messages = sumcheck.prover_messages();
     let subclaim = Subclaim::new_batch(
         &mut verifier_transcript,
@@ -270,3 +318,207 @@ fn test_batch_sumcheck_correctness() {
     );
     assert!(subclaim.is_ok());
 }
+
+#[test]
+fn test_batch_sumcheck_api() {
+    use crate::config::ProverConfig;
+    use crate::subprotocols::sumcheck::BatchSumcheck;
+
+    let rng = &mut ark_std::test_rng();
+    let twisted_ip = |f: &[F], g: &[F], twist: F| ip(&hadamard(&powers(twist, f.len()), f), g);
+    let lens = [1 << 5, 100, 1 << 8];
+    let instances = lens
+        .iter()
+        .map(|&d| {
+            let f = DensePolynomial::<F>::rand(d, rng).coeffs().to_vec();
+            let g = DensePolynomial::<F>::rand(d, rng).coeffs().to_vec();
+            (f, g, F::rand(rng))
+        })
+        .collect::<Vec<_>>();
+    let asserted_sums = instances
+        .iter()
+        .map(|(f, g, twist)| twisted_ip(f, g, *twist))
+        .collect::<Vec<_>>();
+
+    // the same claims, in memory and streamed.
+    let config = ProverConfig {
+        memory_budget: 1 << 4,
+        ..ProverConfig::default()
+    };
+    let (f0, g0, twist0) = &instances[0];
+    let (f1, g1, twist1) = &instances[1];
+    let (f2, g2, twist2) = &instances[2];
+    let mut batch = BatchSumcheck::new();
+    batch
+        .add_time(f0, g0, *twist0)
+        .add_space(Reverse(f1.as_slice()), Reverse(g1.as_slice()), *twist1)
+        .unwrap()
+        .add_elastic(
+            Reverse(f2.as_slice()),
+            Reverse(g2.as_slice()),
+            *twist2,
+            &config,
+        )
+        .unwrap();
+    assert_eq!(batch.len(), 3);
+    let mut transcript = Transcript::new(crate::PROTOCOL_NAME);
+    let sumcheck = batch.prove(&mut transcript).unwrap();
+
+    let mut time_batch = BatchSumcheck::new();
+    instances.iter().for_each(|(f, g, twist)| {
+        time_batch.add_time(f, g, *twist);
+    });
+    let mut transcript = Transcript::new(crate::PROTOCOL_NAME);
+    assert_eq!(time_batch.prove(&mut transcript).unwrap(), sumcheck);
+
+    let prover_messages = sumcheck.prover_messages();
+    let mut transcript = Transcript::new(crate::PROTOCOL_NAME);
+    let subclaim = Subclaim::new_batch(&mut transcript, &prover_messages, &asserted_sums);
+    assert!(subclaim.is_ok());
+    let mut wrong_sums = asserted_sums.clone();
+    wrong_sums[1] += F::one();
+    let mut transcript = Transcript::new(crate::PROTOCOL_NAME);
+    assert!(Subclaim::new_batch(&mut transcript, &prover_messages, &wrong_sums).is_err());
+}
+
+#[test]
+fn test_distributed_prover() {
+    use crate::subprotocols::sumcheck::{DistributedProver, ElasticProver};
+
+    let rng = &mut ark_std::test_rng();
+    // the last chunk is not full.
+    let d = 100;
+    let chunk_size = 16;
+    let f = DensePolynomial::<F>::rand(d, rng);
+    let g = DensePolynomial::<F>::rand(d, rng);
+    let twist = F::rand(rng);
+
+    let expected =
+        Sumcheck::new_time(&mut Transcript::new(crate::PROTOCOL_NAME), &f, &g, &twist).unwrap();
+    let time_workers = f
+        .chunks(chunk_size)
+        .zip(g.chunks(chunk_size))
+        .map(|(f, g)| TimeProver::new(Witness::new(f, g, &twist)))
+        .collect::<Vec<_>>();
+    let prover = DistributedProver::new(time_workers, chunk_size, twist).unwrap();
+    let sumcheck = Sumcheck::prove(&mut Transcript::new(crate::PROTOCOL_NAME), prover).unwrap();
+    assert_eq!(sumcheck, expected);
+
+    // workers can stream their chunks.
+    let elastic_workers = f
+        .chunks(chunk_size)
+        .zip(g.chunks(chunk_size))
+        .map(|(f, g)| ElasticProver::with_threshold(Reverse(f), Reverse(g), twist, 2).unwrap())
+        .collect::<Vec<_>>();
+    let prover = DistributedProver::new(elastic_workers, chunk_size, twist).unwrap();
+    let sumcheck = Sumcheck::prove(&mut Transcript::new(crate::PROTOCOL_NAME), prover).unwrap();
+    assert_eq!(sumcheck, expected);
+}
+
+#[test]
+fn test_sumcheck_serialization() {
+    use crate::subprotocols::sumcheck::prover::ProverMsgs;
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+
+    let rng = &mut ark_std::test_rng();
+    let d = 1 << 5;
+    let f = DensePolynomial::<F>::rand(d, rng).coeffs().to_vec();
+    let g = DensePolynomial::<F>::rand(d, rng).coeffs().to_vec();
+    let twist = F::rand(rng);
+    let sumcheck =
+        Sumcheck::new_time(&mut Transcript::new(crate::PROTOCOL_NAME), &f, &g, &twist).unwrap();
+
+    let mut bytes = Vec::new();
+    sumcheck.serialize_compressed(&mut bytes).unwrap();
+    assert_eq!(
+        Sumcheck::deserialize_compressed(&bytes[..]).unwrap(),
+        sumcheck
+    );
+
+    let prover_messages = sumcheck.prover_messages();
+    let mut bytes = Vec::new();
+    prover_messages.serialize_compressed(&mut bytes).unwrap();
+    assert_eq!(
+        ProverMsgs::deserialize_compressed(&bytes[..]).unwrap(),
+        prover_messages
+    );
+    // truncated messages are rejected.
+    assert!(ProverMsgs::<F>::deserialize_compressed(&bytes[..bytes.len() - 1]).is_err());
+}
+
+#[test]
+fn test_interactive_sumcheck() {
+    use crate::subprotocols::sumcheck::{InteractiveProver, InteractiveVerifier};
+
+    let rng = &mut ark_std::test_rng();
+    let d = 1 << 6;
+    let f = DensePolynomial::<F>::rand(d, rng).coeffs().to_vec();
+    let g = DensePolynomial::<F>::rand(d, rng).coeffs().to_vec();
+    let twist = F::rand(rng);
+    let asserted_sum = ip(&hadamard(&powers(twist, d + 1), &f), &g);
+
+    let mut prover =
+        InteractiveProver::new(SpaceProver::new(Reverse(&f[..]), Reverse(&g[..]), twist).unwrap());
+    let mut verifier = InteractiveVerifier::new(asserted_sum);
+    while let Some(message) = prover.next_message() {
+        let challenge = F::rand(rng);
+        prover.receive_challenge(challenge);
+        verifier.receive_message(&message, challenge);
+    }
+    let final_foldings = prover.final_foldings().unwrap();
+    let subclaim = verifier.finish(final_foldings).unwrap();
+    let sumcheck = prover.finish();
+    assert_eq!(subclaim.challenges, sumcheck.challenges);
+    assert!(Sumcheck::verify_interactive(
+        &sumcheck.prover_messages(),
+        &sumcheck.challenges,
+        asserted_sum
+    )
+    .is_ok());
+    assert!(Sumcheck::verify_interactive(
+        &sumcheck.prover_messages(),
+        &sumcheck.challenges,
+        asserted_sum + F::one()
+    )
+    .is_err());
+
+    // replaying the challenges of the transcript gives the non-interactive proof.
+    let expected =
+        Sumcheck::new_time(&mut Transcript::new(crate::PROTOCOL_NAME), &f, &g, &twist).unwrap();
+    let mut prover = InteractiveProver::new(TimeProver::new(Witness::new(&f, &g, &twist)));
+    for &challenge in &expected.challenges {
+        prover.next_message().unwrap();
+        prover.receive_challenge(challenge);
+    }
+    assert!(prover.next_message().is_none());
+    assert_eq!(prover.finish(), expected);
+}
+
+#[test]
+fn test_malformed_sumcheck() {
+    use ark_ff::Zero;
+
+    use crate::errors::GeminiError;
+    use crate::subprotocols::sumcheck::DistributedProver;
+
+    let rng = &mut ark_std::test_rng();
+    let f = DensePolynomial::<F>::rand(15, rng).coeffs().to_vec();
+    let g = DensePolynomial::<F>::rand(15, rng).coeffs().to_vec();
+    let transcript = &mut Transcript::new(crate::PROTOCOL_NAME);
+
+    // the round messages of the space prover need the inverse of the twist.
+    let zero_twist = Sumcheck::new_space(transcript, Reverse(&f[..]), Reverse(&g[..]), F::zero());
+    assert!(matches!(
+        zero_twist,
+        Err(GeminiError::MalformedInstance { .. })
+    ));
+    assert!(Sumcheck::new_time(transcript, &[], &g, &F::one()).is_err());
+
+    let workers = f
+        .chunks(4)
+        .zip(g.chunks(4))
+        .map(|(f, g)| TimeProver::new(Witness::new(f, g, &F::one())))
+        .collect::<Vec<_>>();
+    assert!(DistributedProver::new(workers, 3, F::one()).is_err());
+    assert!(DistributedProver::<F, TimeProver<F>>::new(Vec::new(), 4, F::one()).is_err());
+}
@@ -3,9 +3,15 @@
 
 use ark_ff::Field;
 use ark_std::log2;
+use ark_std::mem;
 use ark_std::vec::Vec;
 
-use crate::misc::fold_polynomial;
+#[cfg(feature = "parallel")]
+use rayon::iter::{IndexedParallelIterator, ParallelIterator};
+#[cfg(feature = "parallel")]
+use rayon::slice::ParallelSlice;
+
+use crate::misc::{fold_polynomial_into, PARALLEL_CHUNK_SIZE};
 use crate::subprotocols::sumcheck::prover::{Prover, RoundMsg};
 
 /// The witness for the Twisted Scalar product relation.
@@ -49,6 +55,8 @@ pub struct TimeProver<F: Field> {
     pub twist: F,
     /// Total number of rounds.
     pub tot_rounds: usize,
+    /// The buffer the next folding is written into, holding an earlier folding.
+    pub(crate) scratch: Vec<F>,
 }
 
 impl<F: Field> TimeProver<F> {
@@ -63,6 +71,7 @@ impl<F: Field> TimeProver<F> {
             round: 0usize,
             twist: witness.twist,
             tot_rounds: witness.required_rounds(),
+            scratch: Vec::new(),
         }
     }
 }
@@ -74,8 +83,11 @@ where
     /// Fold the sumcheck instance (inplace).
     fn fold(&mut self, r: F) {
         // Fold the polynonomials f, g in the scalar product.
-        self.f = fold_polynomial(&self.f, r * self.twist);
-        self.g = fold_polynomial(&self.g, r);
+        // fold into the scratch buffer, which then holds the previous folding.
+        fold_polynomial_into(&self.f, r * self.twist, &mut self.scratch);
+        mem::swap(&mut self.f, &mut self.scratch);
+        fold_polynomial_into(&self.g, r, &mut self.scratch);
+        mem::swap(&mut self.g, &mut self.scratch);
         self.twist.square_in_place();
     }
 
@@ -88,33 +100,41 @@ where
         if self.round == self.tot_rounds {
             return None;
         }
+        trace_span!("sumcheck_round", round = self.round, len = self.f.len());
 
         // Compute the polynomial of the partial sum q = a + bx + c x2,
         // For the evaluations, send only the coefficients a, b of the polynomial .
-        let mut a = F::zero();
-        let mut b = F::zero();
         let zero = F::zero();
         let twist2 = self.twist.square();
-
-        let mut twist_runner = F::one();
-        for (f_pair, g_pair) in self.f.chunks(2).zip(self.g.chunks(2)) {
-            // The even part of the polynomial must always be unwrapped.
-            let f_even = f_pair[0];
-            let g_even = g_pair[0];
-
-            // For the right part, we might obtain zero if the degree is not a multiple of 2.
-            let f_odd = f_pair.get(1).unwrap_or(&zero);
-            let g_odd = g_pair.get(1).unwrap_or(&zero);
-
-            // Add to the partial sum
-            a += f_even * g_even * twist_runner;
-            b += (f_even * g_odd + g_even * f_odd * self.twist) * twist_runner;
-            twist_runner *= twist2;
-        }
+        let chunk_size = 2 * PARALLEL_CHUNK_SIZE;
+        let msg = cfg_chunks!(self.f, chunk_size)
+            .zip(cfg_chunks!(self.g, chunk_size))
+            .enumerate()
+            .map(|(i, (f_chunk, g_chunk))| {
+                let mut a = F::zero();
+                let mut b = F::zero();
+                let mut twist_runner = twist2.pow([(i * PARALLEL_CHUNK_SIZE) as u64]);
+                for (f_pair, g_pair) in f_chunk.chunks(2).zip(g_chunk.chunks(2)) {
+                    // The even part of the polynomial must always be unwrapped.
+                    let f_even = f_pair[0];
+                    let g_even = g_pair[0];
+
+                    // For the right part, we might obtain zero if the degree is not a multiple of 2.
+                    let f_odd = f_pair.get(1).unwrap_or(&zero);
+                    let g_odd = g_pair.get(1).unwrap_or(&zero);
+
+                    // Add to the partial sum
+                    a += f_even * g_even * twist_runner;
+                    b += (f_even * g_odd + g_even * f_odd * self.twist) * twist_runner;
+                    twist_runner *= twist2;
+                }
+                RoundMsg(a, b)
+            })
+            .sum::<RoundMsg<F>>();
         // Increment the round counter
         self.round += 1;
 
-        Some(RoundMsg(a, b))
+        Some(msg)
     }
 
     /// The number of rounds this prover is supposed to run on.
@@ -42,24 +42,26 @@ use ark_ec::pairing::Pairing;
 use ark_ff::Field;
 use ark_serialize::*;
 use ark_std::borrow::Borrow;
+use ark_std::string::ToString;
 use ark_std::vec::Vec;
-use ark_std::One;
-
-use merlin::Transcript;
+use ark_std::{One, Zero};
+#[cfg(feature = "parallel")]
+use rayon::iter::{IntoParallelIterator, ParallelIterator};
 
+use crate::errors::{VerificationError, VerificationResult};
 use crate::iterable::Iterable;
 use crate::kzg::Commitment;
 use crate::kzg::CommitterKey;
 use crate::kzg::EvaluationProof;
-use crate::kzg::VerificationError;
-use crate::kzg::VerificationResult;
+use crate::kzg::MsmPairingCheck;
+use crate::kzg::PairingCheck;
 use crate::kzg::VerifierKey;
 use crate::misc::strip_last;
 use crate::misc::{evaluate_le, fold_polynomial, ip, linear_combination, powers};
 use crate::subprotocols::sumcheck::streams::FoldedPolynomialTree;
 use crate::transcript::GeminiTranscript;
-use crate::SPACE_TIME_THRESHOLD;
 
+pub mod multilinear;
 pub mod streams;
 
 #[cfg(test)]
@@ -89,6 +91,32 @@ where
     result
 }
 
+/// Evaluate a folded polynomial tree at the points `points`.
+///
+/// Same as [`evaluate_folding`], but with a single pass for all points:
+/// the vector returned stores at the $j-1$-th position the evaluations of $f^{(j)}$.
+pub fn evaluate_folding_points<F, S, const N: usize>(
+    polynomials: &FoldedPolynomialTree<'_, F, S>,
+    points: &[F; N],
+) -> Vec<[F; N]>
+where
+    F: Field,
+    S: Iterable,
+    S::Item: Borrow<F>,
+{
+    let mut result = vec![[F::zero(); N]; polynomials.depth()];
+    for (i, c) in polynomials.iter() {
+        // foldings are in the interval [[1, n-1]].
+        let evaluations = &mut result[i - 1];
+        evaluations
+            .iter_mut()
+            .zip(points)
+            .for_each(|(dst, x)| *dst = *dst * x + c.borrow());
+    }
+
+    result
+}
+
 /// Compute the evaluation of folded polynomials in the next round.
 ///
 /// Let $ f(x) $ denote the polynomial in the current round,
@@ -109,7 +137,7 @@ pub fn evaluate_sq_fp<F: Field>(
 }
 
 /// The struct for the tensor check proof.
-#[derive(CanonicalSerialize, PartialEq, Eq)]
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, PartialEq, Eq)]
 pub struct TensorcheckProof<E: Pairing> {
     /// The commitments for all the folded polynomials in the tensor check.
     pub folded_polynomials_commitments: Vec<Commitment<E>>,
@@ -121,6 +149,27 @@ pub struct TensorcheckProof<E: Pairing> {
     pub base_polynomials_evaluations: Vec<[E::ScalarField; 3]>,
 }
 
+impl<E: Pairing> TensorcheckProof<E> {
+    /// Return `true` if each folded polynomial comes with its evaluations.
+    pub(crate) fn is_well_formed(&self) -> bool {
+        self.folded_polynomials_commitments.len() == self.folded_polynomials_evaluations.len()
+            && !self.base_polynomials_evaluations.is_empty()
+    }
+
+    /// A proof with `foldings` folded polynomials and `base_polynomials` base polynomials, all set to zero.
+    ///
+    /// It has the same size as any real proof, and is used for estimating proof sizes.
+    pub(crate) fn zeroed(foldings: usize, base_polynomials: usize) -> Self {
+        let zero = E::ScalarField::zero();
+        TensorcheckProof {
+            folded_polynomials_commitments: vec![Commitment(E::G1::zero()); foldings],
+            folded_polynomials_evaluations: vec![[zero; 2]; foldings],
+            evaluation_proof: EvaluationProof(E::G1::zero()),
+            base_polynomials_evaluations: vec![[zero; 3]; base_polynomials],
+        }
+    }
+}
+
 /// The function for folding polynomials using given challenges for each round.
 /// It skips the last challenge since the result can be obtained from asserted results.
 pub fn foldings_polynomial<F: Field>(polynomial: &[F], challenges: &[F]) -> Vec<Vec<F>> {
@@ -159,9 +208,15 @@ where
     transcribed_foldings
 }
 
+/// Split the foldings of `stream` into a polynomial tree, streamed,
+/// and the last foldings, stored in memory.
+///
+/// The foldings stored in memory have at most $2^{\text{threshold}}$ coefficients;
+/// the first folding is always streamed, even if the whole tree fits the threshold.
 pub(crate) fn partially_foldtree<'a, F, S>(
     stream: &'a S,
     challenges: &'a [F],
+    threshold: usize,
 ) -> (FoldedPolynomialTree<'a, F, S>, Vec<Vec<F>>)
 where
     F: Field,
@@ -169,11 +224,10 @@ where
     S::Item: Borrow<F>,
 {
     let full_foldings = FoldedPolynomialTree::new(stream, challenges);
-    let threshold_level = if full_foldings.depth() > SPACE_TIME_THRESHOLD {
-        full_foldings.depth() - SPACE_TIME_THRESHOLD
-    } else {
-        full_foldings.depth()
-    };
+    let threshold_level = usize::min(
+        full_foldings.depth(),
+        usize::max(1, full_foldings.depth().saturating_sub(threshold)),
+    );
     let transcribed_foldings = transcribe_foldings(full_foldings, threshold_level);
     let partial_foldings = FoldedPolynomialTree::new(stream, &challenges[..threshold_level]);
     (partial_foldings, transcribed_foldings)
@@ -190,7 +244,7 @@ impl<E: Pairing> TensorcheckProof<E> {
     /// The folded polynomials `body_polynomials` consist of multiple tensor check intance.
     /// Each instance contains a set of folded polynomials and folding randomnesses.
     pub fn new_time<const N: usize, const M: usize>(
-        transcript: &mut Transcript,
+        transcript: &mut impl GeminiTranscript,
         ck: &CommitterKey<E>,
         base_polynomials: [&Vec<E::ScalarField>; N],
         body_polynomials: [(&[&Vec<E::ScalarField>], &[E::ScalarField]); M],
@@ -199,6 +253,7 @@ impl<E: Pairing> TensorcheckProof<E> {
             .iter()
             .map(|x| x.0.len())
             .fold(0, usize::max);
+        trace_span!("tensorcheck", len = max_len);
 
         let batch_challenge = transcript.get_challenge::<E::ScalarField>(b"batch_challenge");
         let batch_challenges = powers(batch_challenge, max_len);
@@ -283,7 +338,7 @@ impl<E: Pairing> TensorcheckProof<E> {
     /// and the random challenge `batch_challenge` for batching tensor check instances with the same folding randomnesses.
     pub fn verify(
         &self,
-        transcript: &mut Transcript,
+        transcript: &mut impl GeminiTranscript,
         vk: &VerifierKey<E>,
         asserted_res_vec: &[Vec<E::ScalarField>],
         base_polynomials_commitments: &[Commitment<E>],
@@ -292,14 +347,74 @@ impl<E: Pairing> TensorcheckProof<E> {
         eval_chal: E::ScalarField,
         batch_challenge: E::ScalarField,
     ) -> VerificationResult
+    where
+        E: Pairing,
+    {
+        let check = self.deferred_verify(
+            transcript,
+            vk,
+            asserted_res_vec,
+            base_polynomials_commitments,
+            direct_base_polynomials_evaluations,
+            fold_randomness,
+            eval_chal,
+            batch_challenge,
+        )?;
+        vk.check(&check)
+            .map_err(|e| e.within("tensorcheck opening"))
+    }
+
+    /// Same as [`TensorcheckProof::verify`],
+    /// but return the final pairing check instead of computing it.
+    pub(crate) fn deferred_verify(
+        &self,
+        transcript: &mut impl GeminiTranscript,
+        vk: &VerifierKey<E>,
+        asserted_res_vec: &[Vec<E::ScalarField>],
+        base_polynomials_commitments: &[Commitment<E>],
+        direct_base_polynomials_evaluations: &[[E::ScalarField; 2]],
+        fold_randomness: &[Vec<E::ScalarField>],
+        eval_chal: E::ScalarField,
+        batch_challenge: E::ScalarField,
+    ) -> Result<PairingCheck<E>, VerificationError>
+    where
+        E: Pairing,
+    {
+        self.deferred_msm_verify(
+            transcript,
+            vk,
+            asserted_res_vec,
+            base_polynomials_commitments,
+            direct_base_polynomials_evaluations,
+            fold_randomness,
+            eval_chal,
+            batch_challenge,
+        )
+        .map(|check| check.evaluate())
+    }
+
+    /// Same as [`TensorcheckProof::deferred_verify`],
+    /// but leave the multi-scalar multiplications of the pairing check to compute.
+    pub(crate) fn deferred_msm_verify(
+        &self,
+        transcript: &mut impl GeminiTranscript,
+        vk: &VerifierKey<E>,
+        asserted_res_vec: &[Vec<E::ScalarField>],
+        base_polynomials_commitments: &[Commitment<E>],
+        direct_base_polynomials_evaluations: &[[E::ScalarField; 2]],
+        fold_randomness: &[Vec<E::ScalarField>],
+        eval_chal: E::ScalarField,
+        batch_challenge: E::ScalarField,
+    ) -> Result<MsmPairingCheck<E>, VerificationError>
     where
         E: Pairing,
     {
         let minus_eval_chal = -eval_chal;
         let eval_chal2 = eval_chal.square();
 
-        let two_inv = E::ScalarField::one().double().inverse().unwrap();
-        let two_beta_inv = eval_chal.double().inverse().unwrap();
+        let mut inverses = [E::ScalarField::one().double(), eval_chal.double()];
+        ark_ff::batch_inversion(&mut inverses);
+        let [two_inv, two_beta_inv] = inverses;
 
         let mut evaluations = Vec::new();
         evaluations.extend(
@@ -318,45 +433,40 @@ impl<E: Pairing> TensorcheckProof<E> {
             let asserted_res = &asserted_res_vec[instance];
             offset += rounds;
 
-            evaluations.push(vec![
-                evaluate_sq_fp(
-                    &base_evals[0],
-                    &base_evals[1],
-                    &randomness[0],
-                    &two_inv,
-                    &two_beta_inv,
-                ),
-                folded_polynomials_evaluations[0][0],
-                folded_polynomials_evaluations[0][1],
-            ]);
-
-            for i in 1..rounds {
-                evaluations.push(vec![
+            // the evaluation of each folding at $\beta^2$ follows from its parent's at $\pm \beta$,
+            // independently of the other levels.
+            let squared_evaluations = cfg_into_iter!(0..rounds + 1)
+                .map(|i| {
+                    let parent = match i {
+                        0 => base_evals,
+                        i => &folded_polynomials_evaluations[i - 1],
+                    };
                     evaluate_sq_fp(
-                        &folded_polynomials_evaluations[i - 1][0],
-                        &folded_polynomials_evaluations[i - 1][1],
+                        &parent[0],
+                        &parent[1],
                         &randomness[i],
                         &two_inv,
                         &two_beta_inv,
-                    ),
-                    folded_polynomials_evaluations[i][0],
-                    folded_polynomials_evaluations[i][1],
-                ]);
-            }
-
-            let subclaim = evaluate_sq_fp(
-                &folded_polynomials_evaluations[rounds - 1][0],
-                &folded_polynomials_evaluations[rounds - 1][1],
-                &randomness[rounds],
-                &two_inv,
-                &two_beta_inv,
+                    )
+                })
+                .collect::<Vec<_>>();
+            evaluations.extend(
+                folded_polynomials_evaluations
+                    .iter()
+                    .zip(&squared_evaluations)
+                    .map(|(&[pos, neg], &sq)| vec![sq, pos, neg]),
             );
+            let subclaim = squared_evaluations[rounds];
 
             let batch_challenges = powers(batch_challenge, asserted_res.len());
             let lc_asserted_res = ip(asserted_res, &batch_challenges);
 
             if subclaim != lc_asserted_res {
-                return Err(VerificationError);
+                return Err(VerificationError::Tensorcheck {
+                    instance,
+                    expected: lc_asserted_res.to_string(),
+                    found: subclaim.to_string(),
+                });
             }
         }
 
@@ -373,13 +483,13 @@ impl<E: Pairing> TensorcheckProof<E> {
             .for_each(|e| transcript.append_serializable(b"eval", e));
         let open_chal = transcript.get_challenge(b"open-chal");
 
-        vk.verify_multi_points(
+        Ok(vk.multi_points_msm_check(
             &all_commitments,
             &[eval_chal2, eval_chal, minus_eval_chal],
             &evaluations,
             &self.evaluation_proof,
             &open_chal,
-        )
+        ))
     }
 }
 #[test]
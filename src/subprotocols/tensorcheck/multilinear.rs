@@ -0,0 +1,160 @@
+//! Evaluation proofs for multilinear polynomials, committed with the univariate KZG.
+//!
+//! The multilinear polynomial $f(x_0, \dots, x_{n-1})$ with coefficients $f_0, \dots, f_{2^n-1}$
+//! (the $i$-th coefficient being the one of the monomial $\prod_{j} x_j^{i_j}$,
+//! where $i_j$ is the $j$-th bit of $i$)
+//! is committed as the univariate polynomial $f(x) = \sum_i f_i x^i$.
+//! Its evaluation at the point $(\rho_0, \dots, \rho_{n-1})$ is the scalar product
+//! $\langle f, \otimes_j (1, \rho_j) \rangle$,
+//! proved with a single tensorcheck instance.
+use ark_ec::pairing::Pairing;
+use ark_ff::Zero;
+use ark_serialize::*;
+use ark_std::borrow::Borrow;
+use ark_std::vec::Vec;
+
+use crate::config::ProverConfig;
+use crate::errors::{GeminiError, GeminiResult, VerificationError, VerificationResult};
+use crate::iterable::{DoubleEndedIterable, Iterable, RandomAccessIterable, ZeroPadded};
+use crate::kzg::{Commitment, CommitterKey, CommitterKeyStream, VerifierKey};
+use crate::misc::{ip, tensor};
+use crate::snark::elastic_prover::elastic_tensorcheck;
+use crate::subprotocols::sumcheck::streams::FoldedPolynomialStream;
+use crate::subprotocols::tensorcheck::TensorcheckProof;
+use crate::transcript::GeminiTranscript;
+
+/// A proof of the evaluation of a multilinear polynomial at a point.
+///
+/// The point must have at least two coordinates,
+/// and the polynomial at most $2^n$ coefficients, where $n$ is the number of coordinates.
+/// The commitment to the polynomial is expected to be already in the transcript.
+#[derive(CanonicalSerialize, CanonicalDeserialize, PartialEq, Eq)]
+pub struct MultilinearProof<E: Pairing> {
+    /// The tensorcheck proof for the evaluation.
+    pub tensorcheck_proof: TensorcheckProof<E>,
+}
+
+/// Add the statement of the evaluation proof to the transcript.
+fn bind_statement<F: CanonicalSerialize>(
+    transcript: &mut impl GeminiTranscript,
+    point: &[F],
+    evaluation: &F,
+) {
+    point
+        .iter()
+        .for_each(|x| transcript.append_serializable(b"multilinear-point", x));
+    transcript.append_serializable(b"multilinear-evaluation", evaluation);
+}
+
+impl<E: Pairing> MultilinearProof<E> {
+    /// Evaluate the multilinear polynomial with (little-endian) coefficients `polynomial`
+    /// at `point`, and prove the evaluation with the committer key `ck`.
+    ///
+    /// Return the evaluation along with its proof.
+    pub fn new_time(
+        transcript: &mut impl GeminiTranscript,
+        ck: &CommitterKey<E>,
+        polynomial: &Vec<E::ScalarField>,
+        point: &[E::ScalarField],
+    ) -> (E::ScalarField, Self) {
+        assert!(point.len() > 1, "expecting at least two coordinates");
+        assert!(polynomial.len() <= 1 << point.len());
+        let evaluation = ip(polynomial, &tensor(point)[..polynomial.len()]);
+        bind_statement(transcript, point, &evaluation);
+
+        let tensorcheck_proof =
+            TensorcheckProof::new_time(transcript, ck, [polynomial], [(&[polynomial][..], point)]);
+        (evaluation, MultilinearProof { tensorcheck_proof })
+    }
+
+    /// Same as [`MultilinearProof::new_time`],
+    /// but with the (big-endian) coefficients streamed by `polynomial`,
+    /// and the streaming committer key `ck`.
+    ///
+    /// The proof is the same as the one produced by [`MultilinearProof::new_time`].
+    /// Fails if the point has fewer than two coordinates, if the polynomial is too long for the point,
+    /// or if the committer key is too short for the polynomial.
+    pub fn new_elastic<SG, SF>(
+        transcript: &mut impl GeminiTranscript,
+        ck: CommitterKeyStream<E, SG>,
+        polynomial: &SF,
+        point: &[E::ScalarField],
+        config: &ProverConfig,
+    ) -> GeminiResult<(E::ScalarField, Self)>
+    where
+        SG: DoubleEndedIterable + RandomAccessIterable,
+        SG::Item: Borrow<E::G1Affine>,
+        SF: Iterable,
+        SF::Item: Borrow<E::ScalarField>,
+    {
+        if point.len() < 2 {
+            return Err(GeminiError::MalformedInstance {
+                reason: "the point of a multilinear evaluation has fewer than two coordinates",
+            });
+        }
+        if polynomial.len() > 1 << point.len() {
+            return Err(GeminiError::MalformedInstance {
+                reason: "the multilinear polynomial has more coefficients than the point allows",
+            });
+        }
+        let evaluation = FoldedPolynomialStream::new(polynomial, point)
+            .iter()
+            .next()
+            .unwrap_or_else(E::ScalarField::zero);
+        bind_statement(transcript, point, &evaluation);
+        // drawn by `TensorcheckProof::new_time`, irrelevant for a single polynomial.
+        let _batch_challenge = transcript.get_challenge::<E::ScalarField>(b"batch_challenge");
+
+        // the folded polynomials are streamed by value.
+        let body_polynomial = ZeroPadded::new(polynomial, polynomial.len());
+        let tensorcheck_proof = elastic_tensorcheck(
+            transcript,
+            ck,
+            polynomial,
+            (&body_polynomial, point),
+            config.max_msm_buffer,
+            config.threshold(),
+        )?;
+        Ok((evaluation, MultilinearProof { tensorcheck_proof }))
+    }
+
+    /// Verify that the polynomial committed in `commitment` evaluates to `evaluation` at `point`.
+    pub fn verify(
+        &self,
+        transcript: &mut impl GeminiTranscript,
+        vk: &VerifierKey<E>,
+        commitment: &Commitment<E>,
+        point: &[E::ScalarField],
+        evaluation: E::ScalarField,
+    ) -> VerificationResult {
+        let proof = &self.tensorcheck_proof;
+        if point.len() < 2
+            || !proof.is_well_formed()
+            || proof.base_polynomials_evaluations.len() != 1
+            || proof.folded_polynomials_commitments.len() != point.len() - 1
+        {
+            return Err(VerificationError::Consistency {
+                check: "multilinear proof shape",
+            });
+        }
+        bind_statement(transcript, point, &evaluation);
+        let batch_challenge = transcript.get_challenge::<E::ScalarField>(b"batch_challenge");
+        proof
+            .folded_polynomials_commitments
+            .iter()
+            .for_each(|c| transcript.append_serializable(b"commitment", c));
+        let eval_chal = transcript.get_challenge::<E::ScalarField>(b"evaluation-chal");
+
+        let base_evaluations = proof.base_polynomials_evaluations[0];
+        proof.verify(
+            transcript,
+            vk,
+            &[vec![evaluation]],
+            &[*commitment],
+            &[[base_evaluations[1], base_evaluations[2]]],
+            &[point.to_vec()],
+            eval_chal,
+            batch_challenge,
+        )
+    }
+}
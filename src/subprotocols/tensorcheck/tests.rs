@@ -85,3 +85,106 @@ fn test_tensor_check() {
         )
         .is_ok());
 }
+
+#[test]
+fn test_multilinear_opening() {
+    use crate::config::ProverConfig;
+    use crate::iterable::Reverse;
+    use crate::kzg::CommitterKeyStream;
+    use crate::subprotocols::tensorcheck::multilinear::MultilinearProof;
+
+    let rng = &mut test_rng();
+    let d = 1 << 4;
+    let ck = CommitterKey::<Bls12_381>::new(d, 5, rng);
+    let vk = (&ck).into();
+
+    let polynomial = DensePolynomial::rand(d - 1, rng).coeffs;
+    let point = (0..4).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let commitment = ck.commit(&polynomial);
+
+    let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+    let (evaluation, proof) = MultilinearProof::new_time(&mut transcript, &ck, &polynomial, &point);
+    assert_eq!(evaluation, ip(&polynomial, &tensor(&point)));
+
+    let ck_stream = CommitterKeyStream::from(&ck);
+    let polynomial_stream = Reverse(polynomial.as_slice());
+    let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+    let (elastic_evaluation, elastic_proof) = MultilinearProof::new_elastic(
+        &mut transcript,
+        ck_stream,
+        &polynomial_stream,
+        &point,
+        &ProverConfig::default(),
+    )
+    .unwrap();
+    assert_eq!(elastic_evaluation, evaluation);
+    assert!(elastic_proof == proof);
+
+    let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+    assert!(proof
+        .verify(&mut transcript, &vk, &commitment, &point, evaluation)
+        .is_ok());
+    let mut transcript = merlin::Transcript::new(PROTOCOL_NAME);
+    assert!(proof
+        .verify(
+            &mut transcript,
+            &vk,
+            &commitment,
+            &point,
+            evaluation + Fr::one()
+        )
+        .is_err());
+}
+
+#[test]
+fn test_evaluate_folding_points() {
+    use crate::iterable::Reverse;
+    use crate::subprotocols::sumcheck::streams::FoldedPolynomialTree;
+    use crate::subprotocols::tensorcheck::{evaluate_folding, evaluate_folding_points};
+
+    let rng = &mut test_rng();
+    let d = 1 << 5;
+    let polynomial = DensePolynomial::<Fr>::rand(d - 1, rng);
+    let stream = Reverse(polynomial.coeffs());
+    let challenges = (0..log2(d) - 1).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let foldings = FoldedPolynomialTree::new(&stream, &challenges);
+
+    let x = Fr::rand(rng);
+    let points = [x, -x];
+    let evaluations = evaluate_folding_points(&foldings, &points);
+    let expected = evaluate_folding(&foldings, x)
+        .into_iter()
+        .zip(evaluate_folding(&foldings, -x))
+        .map(|(x, y)| [x, y])
+        .collect::<Vec<_>>();
+    assert_eq!(evaluations.len(), challenges.len());
+    assert_eq!(evaluations, expected);
+}
+
+#[test]
+fn test_partially_foldtree() {
+    use crate::iterable::Reverse;
+    use crate::misc::evaluate_le;
+    use crate::subprotocols::sumcheck::streams::FoldedPolynomialTree;
+    use crate::subprotocols::tensorcheck::{evaluate_folding, partially_foldtree};
+
+    let rng = &mut test_rng();
+    let d = 1 << 5;
+    let polynomial = DensePolynomial::<Fr>::rand(d - 1, rng);
+    let stream = Reverse(polynomial.coeffs());
+    let challenges = (0..log2(d) - 1).map(|_| Fr::rand(rng)).collect::<Vec<_>>();
+    let x = Fr::rand(rng);
+    let expected = evaluate_folding(&FoldedPolynomialTree::new(&stream, &challenges), x);
+
+    // the foldings with at most 2^threshold coefficients are held in memory,
+    // but the first folding is streamed even when the whole tree fits.
+    for (threshold, streamed) in [(0, 4), (2, 2), (3, 1), (4, 1), (10, 1)] {
+        let (sfoldings, tfoldings) = partially_foldtree(&stream, &challenges, threshold);
+        assert_eq!(sfoldings.depth(), streamed);
+        assert_eq!(tfoldings.len(), challenges.len() - streamed);
+        let evaluations = evaluate_folding(&sfoldings, x)
+            .into_iter()
+            .chain(tfoldings.iter().map(|p| evaluate_le(p, &x)));
+        assert!(evaluations.eq(expected.iter().copied()));
+    }
+}
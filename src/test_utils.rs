@@ -0,0 +1,342 @@
+//! Synthetic R1CS instances, for benchmarks.
+//!
+//! A [`SyntheticR1cs`] describes a satisfiable instance by its size, the density of its matrices,
+//! and the distribution of its witness, and generates it from a seed:
+//! the same description yields the same instance, hence benchmarks over it are comparable.
+//! The instance is generated in memory with [`SyntheticR1cs::r1cs`] (and [`SyntheticR1cs::streams`]),
+//! or written to disk with [`SyntheticR1cs::write_files`], for the elastic prover to stream it back.
+//!
+//! Each constraint has `row_density` random entries in each of the matrices $A, B, C$.
+//! The first variable is the constant one (as the first public input),
+//! and the entry of $C$ in its column is chosen so that the constraint is satisfied.
+use ark_ff::PrimeField;
+use ark_std::collections::BTreeSet;
+use ark_std::iter;
+use ark_std::rand::rngs::StdRng;
+use ark_std::rand::{Rng, RngCore, SeedableRng};
+use ark_std::vec::Vec;
+use ark_std::UniformRand;
+use std::io;
+use std::path::Path;
+
+use crate::circuit::{R1cs, R1csStream, R1csStreams};
+use crate::iterable::transpose::{transpose, TransposedMatrix};
+use crate::iterable::{Generator, Iterable, MatrixPadded, MmapIterable, Reverse, ZeroPadded};
+use crate::misc::MatrixElement;
+use crate::psnark::R1csStats;
+
+/// The distribution of the entries of $\vec z$ (but the leading one).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WitnessDistribution {
+    /// Uniformly random field elements.
+    Uniform,
+    /// Uniformly random integers of the given number of bits (at most 64), e.g. for range-checked values.
+    Bits(u32),
+    /// Uniformly random bits, e.g. for boolean circuits.
+    Binary,
+}
+
+/// The description of a synthetic, satisfiable R1CS instance.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SyntheticR1cs {
+    /// The number of constraints.
+    pub num_constraints: usize,
+    /// The number of variables, i.e. the length of $\vec z$.
+    pub num_variables: usize,
+    /// The length of the statement $\vec x$ (including the leading one).
+    pub num_public_inputs: usize,
+    /// The number of non-zero entries in each row of each matrix.
+    pub row_density: usize,
+    /// The distribution of the entries of $\vec z$.
+    pub witness: WitnessDistribution,
+    /// The seed of the randomness generating the instance.
+    pub seed: u64,
+}
+
+/// The rows of the constraint `i` in $A, B, C$, and the entries of $A \vec z, B \vec z, C \vec z$.
+type Constraint<F> = ([Vec<(F, usize)>; 3], [F; 3]);
+
+impl SyntheticR1cs {
+    /// Describe an instance with `num_constraints` constraints and `num_variables` variables,
+    /// with a single public input, three entries per row, and a uniformly random witness.
+    pub fn new(num_constraints: usize, num_variables: usize) -> Self {
+        SyntheticR1cs {
+            num_constraints,
+            num_variables,
+            num_public_inputs: 1,
+            row_density: 3,
+            witness: WitnessDistribution::Uniform,
+            seed: 0,
+        }
+    }
+
+    /// Panic unless the description is of a valid instance.
+    fn check(&self) {
+        assert!(
+            (1..=self.num_variables).contains(&self.num_public_inputs),
+            "the statement must hold the leading one, and fit into z"
+        );
+        assert!(
+            (1..=self.num_variables).contains(&self.row_density),
+            "the rows must have between one and `num_variables` entries"
+        );
+        if let WitnessDistribution::Bits(bits) = self.witness {
+            assert!(bits <= 64, "at most 64 bits per entry");
+        }
+    }
+
+    /// The randomness of the stream `index`, independent of the other streams.
+    fn rng(&self, index: u64) -> StdRng {
+        let mut seed = [0u8; 32];
+        seed[..8].copy_from_slice(&self.seed.to_le_bytes());
+        seed[8..16].copy_from_slice(&index.to_le_bytes());
+        StdRng::from_seed(seed)
+    }
+
+    /// Return $\vec z$, in little-endian order.
+    fn z<F: PrimeField>(&self) -> Vec<F> {
+        let rng = &mut self.rng(u64::MAX);
+        let entries = (1..self.num_variables).map(|_| match self.witness {
+            WitnessDistribution::Uniform => F::rand(rng),
+            WitnessDistribution::Bits(bits) => {
+                F::from(rng.next_u64() & u64::MAX.checked_shr(64 - bits).unwrap_or(0))
+            }
+            WitnessDistribution::Binary => F::from(rng.next_u64() & 1),
+        });
+        iter::once(F::one()).chain(entries).collect()
+    }
+
+    /// Return the constraint `i`, over $\vec z$ `z`.
+    fn constraint<F: PrimeField>(&self, i: usize, z: &[F]) -> Constraint<F> {
+        let rng = &mut self.rng(i as u64);
+        let mut row = |columns: BTreeSet<usize>| {
+            let mut columns = columns;
+            while columns.len() < self.row_density {
+                columns.insert(rng.gen_range(0..self.num_variables));
+            }
+            columns
+                .into_iter()
+                .map(|col| (F::rand(rng), col))
+                .collect::<Vec<_>>()
+        };
+        let product = |row: &[(F, usize)]| row.iter().map(|&(val, col)| val * z[col]).sum::<F>();
+
+        let a = row(BTreeSet::new());
+        let b = row(BTreeSet::new());
+        // the column of the constant one is the first, hence it comes first in the row.
+        let mut c = row(iter::once(0).collect());
+        let (z_a, z_b) = (product(&a), product(&b));
+        c[0].0 = z_a * z_b - product(&c[1..]);
+        ([a, b, c], [z_a, z_b, z_a * z_b])
+    }
+
+    /// Generate the instance in memory.
+    ///
+    /// # Panics
+    /// If the statement is empty or longer than $\vec z$,
+    /// if the rows are empty or longer than $\vec z$,
+    /// or if the witness has more than 64 bits per entry.
+    pub fn r1cs<F: PrimeField>(&self) -> R1cs<F> {
+        self.check();
+        let z = self.z::<F>();
+        let (mut a, mut b, mut c) = (Vec::new(), Vec::new(), Vec::new());
+        for i in 0..self.num_constraints {
+            let ([a_row, b_row, c_row], _) = self.constraint(i, &z);
+            a.push(a_row);
+            b.push(b_row);
+            c.push(c_row);
+        }
+        R1cs {
+            a,
+            b,
+            c,
+            x: z[..self.num_public_inputs].to_vec(),
+            w: z[self.num_public_inputs..].to_vec(),
+            z,
+        }
+    }
+
+    /// Generate the instance in memory, flattened into the streams of the elastic provers.
+    ///
+    /// # Panics
+    /// As [`SyntheticR1cs::r1cs`].
+    pub fn streams<F: PrimeField>(&self) -> R1csStreams<F> {
+        R1csStreams::new(&self.r1cs())
+    }
+
+    /// Generate the instance on disk, for the elastic provers.
+    ///
+    /// The vectors are written to the directory `dir`, and the matrices are transposed in external memory,
+    /// holding at most `buffer_size` entries of each matrix in memory at any given time.
+    /// The instance is generated one constraint at a time, holding only the vectors in memory.
+    ///
+    /// # Panics
+    /// As [`SyntheticR1cs::r1cs`], or if `buffer_size` is zero.
+    pub fn write_files<F, P>(&self, dir: P, buffer_size: usize) -> io::Result<SyntheticFiles<F>>
+    where
+        F: PrimeField,
+        P: AsRef<Path>,
+    {
+        self.check();
+        let generation_time = start_timer!(|| "Synthetic R1CS on disk");
+        let (num_constraints, num_variables) = (self.num_constraints, self.num_variables);
+        let z = self.z::<F>();
+        let constraints = (0..num_constraints).map(|i| self.constraint(i, &z));
+        let products = constraints
+            .map(|(_, products)| products)
+            .collect::<Vec<_>>();
+
+        // the row-major streams list the rows from the last, and each row from the last entry.
+        let rowmaj = |matrix: usize| {
+            let z = &z;
+            Generator::new(num_constraints * (self.row_density + 1), move || {
+                (0..num_constraints).rev().flat_map(move |i| {
+                    let (mut rows, _) = self.constraint(i, z);
+                    let row = ark_std::mem::take(&mut rows[matrix]);
+                    row.into_iter()
+                        .rev()
+                        .map(MatrixElement::Element)
+                        .chain(iter::once(MatrixElement::EOL))
+                })
+            })
+        };
+        let (a_rowmaj, b_rowmaj, c_rowmaj) = (rowmaj(0), rowmaj(1), rowmaj(2));
+        let stats = R1csStats::new(
+            &a_rowmaj,
+            &b_rowmaj,
+            &c_rowmaj,
+            num_constraints,
+            num_variables,
+        );
+        let colmaj = |m| transpose(m, num_constraints, num_variables, buffer_size);
+        let (a_colmaj, b_colmaj, c_colmaj) =
+            (colmaj(&a_rowmaj)?, colmaj(&b_rowmaj)?, colmaj(&c_rowmaj)?);
+        let rowmaj = |m| transpose(m, num_variables, num_constraints, buffer_size);
+        let (a_rowmaj, b_rowmaj, c_rowmaj) =
+            (rowmaj(&a_colmaj)?, rowmaj(&b_colmaj)?, rowmaj(&c_colmaj)?);
+
+        let dir = dir.as_ref();
+        let product = |k: usize| products.iter().map(move |p| p[k]).collect::<Vec<_>>();
+        let write = |name: &str, v: &[F]| MmapIterable::create(dir.join(name), &Reverse(v));
+        let files = SyntheticFiles {
+            a_colmaj,
+            b_colmaj,
+            c_colmaj,
+            a_rowmaj,
+            b_rowmaj,
+            c_rowmaj,
+            z: write("z", &z)?,
+            witness: write("witness", &z[self.num_public_inputs..])?,
+            z_a: write("z_a", &product(0))?,
+            z_b: write("z_b", &product(1))?,
+            z_c: write("z_c", &product(2))?,
+            num_constraints,
+            joint_len: stats.joint_non_zero,
+        };
+        end_timer!(generation_time);
+        Ok(files)
+    }
+}
+
+/// A synthetic R1CS instance on disk, written by [`SyntheticR1cs::write_files`].
+///
+/// The transposed matrices are removed when dropped; the vectors are left in their directory.
+pub struct SyntheticFiles<F: PrimeField> {
+    a_colmaj: TransposedMatrix<F>,
+    b_colmaj: TransposedMatrix<F>,
+    c_colmaj: TransposedMatrix<F>,
+    a_rowmaj: TransposedMatrix<F>,
+    b_rowmaj: TransposedMatrix<F>,
+    c_rowmaj: TransposedMatrix<F>,
+    z: MmapIterable<F>,
+    witness: MmapIterable<F>,
+    z_a: MmapIterable<F>,
+    z_b: MmapIterable<F>,
+    z_c: MmapIterable<F>,
+    num_constraints: usize,
+    joint_len: usize,
+}
+
+/// A matrix stream of [`SyntheticFiles`].
+pub type SyntheticMatrix<'a, F> = MatrixPadded<'a, TransposedMatrix<F>, F>;
+/// A vector stream of [`SyntheticFiles`].
+pub type SyntheticVector<'a, F> = ZeroPadded<'a, MmapIterable<F>, F>;
+
+/// Stream `v` as is.
+fn vector<F: PrimeField>(v: &MmapIterable<F>) -> SyntheticVector<'_, F> {
+    ZeroPadded::new(v, v.len())
+}
+
+impl<F: PrimeField> SyntheticFiles<F> {
+    /// Return the streams of the instance, read from disk.
+    ///
+    /// Column-major streams have one line per variable, and row-major streams one line per constraint.
+    pub fn stream(
+        &self,
+    ) -> R1csStream<SyntheticMatrix<'_, F>, SyntheticVector<'_, F>, SyntheticVector<'_, F>> {
+        let matrix = |m| MatrixPadded::new(m, 0);
+        R1csStream {
+            a_colmaj: matrix(&self.a_colmaj),
+            b_colmaj: matrix(&self.b_colmaj),
+            c_colmaj: matrix(&self.c_colmaj),
+            a_rowmaj: matrix(&self.a_rowmaj),
+            b_rowmaj: matrix(&self.b_rowmaj),
+            c_rowmaj: matrix(&self.c_rowmaj),
+            z: vector(&self.z),
+            witness: vector(&self.witness),
+            z_a: vector(&self.z_a),
+            z_b: vector(&self.z_b),
+            z_c: vector(&self.z_c),
+            nonzero: self.num_constraints,
+            joint_len: self.joint_len,
+        }
+    }
+}
+
+#[test]
+fn test_synthetic_r1cs() {
+    use ark_bls12_381::{Bls12_381, Fr};
+
+    use crate::kzg::{CommitterKey, CommitterKeyStream};
+    use crate::misc::{hadamard, product_matrix_vector};
+    use crate::snark::Proof;
+
+    let rng = &mut ark_std::test_rng();
+    let synthetic = SyntheticR1cs {
+        num_public_inputs: 3,
+        row_density: 4,
+        witness: WitnessDistribution::Bits(8),
+        ..SyntheticR1cs::new(20, 24)
+    };
+    let r1cs = synthetic.r1cs::<Fr>();
+    assert!(r1cs.check().is_ok());
+    assert!(r1cs.a.iter().all(|row| row.len() == 4));
+    let z_a = product_matrix_vector(&r1cs.a, &r1cs.z);
+    let z_b = product_matrix_vector(&r1cs.b, &r1cs.z);
+    assert_eq!(
+        hadamard(&z_a, &z_b),
+        product_matrix_vector(&r1cs.c, &r1cs.z)
+    );
+    // the instance depends only on its description.
+    assert_eq!(synthetic.r1cs::<Fr>().z, r1cs.z);
+    let reseeded = SyntheticR1cs {
+        seed: 1,
+        ..synthetic
+    };
+    assert_ne!(reseeded.r1cs::<Fr>().z, r1cs.z);
+
+    let ck = CommitterKey::<Bls12_381>::new(64, 5, rng);
+    let vk = (&ck).into();
+    let time_proof = Proof::new_time(&r1cs, &ck).unwrap();
+    assert!(time_proof.verify(&r1cs, &vk).is_ok());
+
+    // the instance on disk is the same.
+    let dir = std::env::temp_dir().join(std::format!("gemini-synthetic-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let files = synthetic.write_files::<Fr, _>(&dir, 7).unwrap();
+    let elastic_proof =
+        Proof::new_elastic(files.stream(), CommitterKeyStream::from(&ck), 20).unwrap();
+    assert_eq!(elastic_proof, time_proof);
+    drop(files);
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -1,11 +1,36 @@
 //! Transcript utilities for the scalar product sub-protocol.
+//!
+//! All provers and verifiers derive their challenges from a [`GeminiTranscript`].
+//! By default, this is a [`merlin::Transcript`];
+//! the [`PoseidonTranscript`] is an algebraic alternative,
+//! friendly to recursive verification,
+//! and the [`Sha256Transcript`] is a hash-based alternative,
+//! for interoperability with other proof systems.
+//! Other transcripts can be plugged in by implementing [`GeminiTranscript`].
+//! Any of them can be wrapped in a [`DebugTranscript`],
+//! which records the messages and the challenges for later inspection.
+use ark_ec::pairing::Pairing;
+use ark_ec::AffineRepr;
 use ark_ff::Field;
 use ark_serialize::CanonicalSerialize;
+use ark_std::borrow::Borrow;
 use ark_std::vec::Vec;
 use merlin::Transcript;
 
+use crate::iterable::Iterable;
+use crate::PROTOCOL_NAME;
+
+mod debug;
+mod poseidon;
+mod sha256;
+
+pub use debug::{DebugTranscript, TranscriptEvent, TranscriptOp};
+pub use poseidon::PoseidonTranscript;
+pub use sha256::Sha256Transcript;
+
 /// A Transcript with some shorthands for feeding scalars, group elements, and obtaining challenges as field elements.
 pub trait GeminiTranscript {
+    /// Append the `label`ed message `msg` to the transcript.
     fn append_serializable<S: CanonicalSerialize>(&mut self, label: &'static [u8], msg: &S);
 
     /// Compute a `label`ed challenge scalar from the given commitments and the choice bit.
@@ -33,3 +58,90 @@ impl GeminiTranscript for Transcript {
         }
     }
 }
+
+/// The version of the crate, bound to the transcript of every proof.
+const CRATE_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+/// Bind to `transcript` the context of a proof for the argument `protocol`:
+/// the protocol identifier, the version of the crate,
+/// the curve of `E` (through the generators of $\GG_1$ and $\GG_2$),
+/// and the dimensions `index` of the padded index.
+///
+/// Provers and verifiers bind the context before any other message,
+/// hence a proof verifies only against the same protocol, version, curve, and index dimensions.
+pub(crate) fn bind_context<E, T>(transcript: &mut T, protocol: &'static [u8], index: &[usize])
+where
+    E: Pairing,
+    T: GeminiTranscript,
+{
+    transcript.append_serializable(b"protocol", &PROTOCOL_NAME.to_vec());
+    transcript.append_serializable(b"argument", &protocol.to_vec());
+    transcript.append_serializable(b"version", &CRATE_VERSION.as_bytes().to_vec());
+    transcript.append_serializable(b"curve-g1", &E::G1Affine::generator());
+    transcript.append_serializable(b"curve-g2", &E::G2Affine::generator());
+    let index = index.iter().map(|&d| d as u64).collect::<Vec<_>>();
+    transcript.append_serializable(b"index", &index);
+}
+
+/// Bind to `transcript` the `digest` of the verifier index,
+/// committing to all index commitments and dimensions at once.
+///
+/// Provers and verifiers bind the digest before the context (see [`bind_context`]),
+/// hence before any challenge is derived.
+pub(crate) fn bind_index_digest<T: GeminiTranscript>(transcript: &mut T, digest: &[u8; 32]) {
+    transcript.append_serializable(b"index-digest", digest);
+}
+
+/// The number of elements of the public input bound at once to the transcript.
+const PUBLIC_INPUT_CHUNK: usize = 1 << 12;
+
+/// Bind to `transcript` the public input `x`, given as a (big-endian) stream.
+///
+/// The public input is absorbed in chunks of [`PUBLIC_INPUT_CHUNK`] elements,
+/// hence it is never held entirely in memory.
+pub(crate) fn bind_public_input<F, S, T>(transcript: &mut T, x: &S)
+where
+    F: Field,
+    S: Iterable,
+    S::Item: Borrow<F>,
+    T: GeminiTranscript,
+{
+    transcript.append_serializable(b"public-input-len", &(x.len() as u64));
+    let mut elements = x.iter();
+    loop {
+        let chunk = (&mut elements)
+            .take(PUBLIC_INPUT_CHUNK)
+            .map(|e| *e.borrow())
+            .collect::<Vec<F>>();
+        if chunk.is_empty() {
+            break;
+        }
+        transcript.append_serializable(b"public-input", &chunk);
+    }
+}
+
+/// Bind to `transcript` the `commitment` to a public input of length `len`,
+/// in place of the public input itself (see [`bind_public_input`]).
+pub(crate) fn bind_committed_input<S, T>(transcript: &mut T, len: usize, commitment: &S)
+where
+    S: CanonicalSerialize,
+    T: GeminiTranscript,
+{
+    transcript.append_serializable(b"committed-input-len", &(len as u64));
+    transcript.append_serializable(b"committed-input", commitment);
+}
+
+#[test]
+fn test_bind_context() {
+    use ark_bls12_381::{Bls12_381, Fr};
+
+    let challenge = |protocol, index: &[usize]| {
+        let mut transcript = Transcript::new(PROTOCOL_NAME);
+        bind_context::<Bls12_381, _>(&mut transcript, protocol, index);
+        transcript.get_challenge::<Fr>(b"challenge")
+    };
+    let expected = challenge(b"snark", &[16, 16]);
+    assert_eq!(challenge(b"snark", &[16, 16]), expected);
+    assert_ne!(challenge(b"psnark", &[16, 16]), expected);
+    assert_ne!(challenge(b"snark", &[32, 32]), expected);
+}
@@ -0,0 +1,250 @@
+//! A transcript that records every message, for debugging.
+//!
+//! [`DebugTranscript`] wraps any other [`GeminiTranscript`],
+//! and logs each absorbed message and each squeezed challenge, along with its label,
+//! in their canonical (uncompressed) serialization.
+//! Passing it to the `_with_transcript` variants of the provers and the verifiers,
+//! and exporting the two logs in JSON or CBOR,
+//! pinpoints the first message where two implementations diverge.
+use ark_ff::Field;
+use ark_serialize::CanonicalSerialize;
+use ark_std::string::String;
+use ark_std::vec::Vec;
+
+use crate::transcript::GeminiTranscript;
+
+/// An operation on the transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TranscriptOp {
+    /// A message absorbed by the transcript.
+    Absorb,
+    /// A challenge squeezed from the transcript.
+    Squeeze,
+}
+
+impl TranscriptOp {
+    fn name(&self) -> &'static str {
+        match self {
+            TranscriptOp::Absorb => "absorb",
+            TranscriptOp::Squeeze => "squeeze",
+        }
+    }
+}
+
+/// An entry of the log of a [`DebugTranscript`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptEvent {
+    /// The operation performed.
+    pub op: TranscriptOp,
+    /// The label of the message or of the challenge.
+    pub label: &'static [u8],
+    /// The uncompressed serialization of the message or of the challenge.
+    pub value: Vec<u8>,
+}
+
+/// A transcript logging all operations performed on the transcript `T`.
+#[derive(Clone, Debug)]
+pub struct DebugTranscript<T> {
+    inner: T,
+    events: Vec<TranscriptEvent>,
+}
+
+impl<T: GeminiTranscript> DebugTranscript<T> {
+    /// Log the operations on the transcript `inner`.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            events: Vec::new(),
+        }
+    }
+
+    /// The operations performed so far, in order.
+    pub fn events(&self) -> &[TranscriptEvent] {
+        &self.events
+    }
+
+    /// Return the wrapped transcript.
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    /// Export the log as a JSON array of objects
+    /// `{"op": "absorb" | "squeeze", "label": <string>, "value": <hex string>}`.
+    pub fn to_json(&self) -> String {
+        let mut json = String::from("[");
+        for (i, event) in self.events.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            json.push_str("\n  {\"op\": \"");
+            json.push_str(event.op.name());
+            json.push_str("\", \"label\": ");
+            push_json_string(&mut json, event.label);
+            json.push_str(", \"value\": \"");
+            push_hex(&mut json, &event.value);
+            json.push_str("\"}");
+        }
+        json.push_str("\n]\n");
+        json
+    }
+
+    /// Export the log in CBOR ([RFC 8949](https://www.rfc-editor.org/rfc/rfc8949)),
+    /// as an array of maps with the same keys of [`DebugTranscript::to_json`],
+    /// where labels are text strings and values are byte strings.
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut cbor = Vec::new();
+        push_cbor_header(&mut cbor, CBOR_ARRAY, self.events.len());
+        for event in &self.events {
+            push_cbor_header(&mut cbor, CBOR_MAP, 3);
+            push_cbor_text(&mut cbor, b"op");
+            push_cbor_text(&mut cbor, event.op.name().as_bytes());
+            push_cbor_text(&mut cbor, b"label");
+            push_cbor_text(&mut cbor, event.label);
+            push_cbor_text(&mut cbor, b"value");
+            push_cbor_header(&mut cbor, CBOR_BYTES, event.value.len());
+            cbor.extend_from_slice(&event.value);
+        }
+        cbor
+    }
+
+    /// Write the JSON export of the log to the file at `path`.
+    #[cfg(feature = "std")]
+    pub fn write_json<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_json())
+    }
+
+    /// Write the CBOR export of the log to the file at `path`.
+    #[cfg(feature = "std")]
+    pub fn write_cbor<P: AsRef<std::path::Path>>(&self, path: P) -> std::io::Result<()> {
+        std::fs::write(path, self.to_cbor())
+    }
+}
+
+impl<T: GeminiTranscript> GeminiTranscript for DebugTranscript<T> {
+    fn append_serializable<S: CanonicalSerialize>(&mut self, label: &'static [u8], msg: &S) {
+        let mut value = Vec::new();
+        msg.serialize_uncompressed(&mut value).unwrap();
+        self.events.push(TranscriptEvent {
+            op: TranscriptOp::Absorb,
+            label,
+            value,
+        });
+        self.inner.append_serializable(label, msg)
+    }
+
+    fn get_challenge<F: Field>(&mut self, label: &'static [u8]) -> F {
+        let challenge = self.inner.get_challenge::<F>(label);
+        let mut value = Vec::new();
+        challenge.serialize_uncompressed(&mut value).unwrap();
+        self.events.push(TranscriptEvent {
+            op: TranscriptOp::Squeeze,
+            label,
+            value,
+        });
+        challenge
+    }
+}
+
+/// Append to `json` the (quoted and escaped) string `bytes`,
+/// replacing invalid UTF-8 sequences.
+fn push_json_string(json: &mut String, bytes: &[u8]) {
+    json.push('"');
+    for c in String::from_utf8_lossy(bytes).chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if (c as u32) < 0x20 => {
+                json.push_str("\\u00");
+                push_hex(json, &[c as u8]);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+/// Append to `out` the lowercase hexadecimal encoding of `bytes`.
+fn push_hex(out: &mut String, bytes: &[u8]) {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    for byte in bytes {
+        out.push(DIGITS[(byte >> 4) as usize] as char);
+        out.push(DIGITS[(byte & 0x0f) as usize] as char);
+    }
+}
+
+/// The CBOR major type of byte strings.
+const CBOR_BYTES: u8 = 2;
+/// The CBOR major type of text strings.
+const CBOR_TEXT: u8 = 3;
+/// The CBOR major type of arrays.
+const CBOR_ARRAY: u8 = 4;
+/// The CBOR major type of maps.
+const CBOR_MAP: u8 = 5;
+
+/// Append to `cbor` the header of an item of major type `major` and length `len`.
+fn push_cbor_header(cbor: &mut Vec<u8>, major: u8, len: usize) {
+    let major = major << 5;
+    let len = len as u64;
+    if len < 24 {
+        cbor.push(major | len as u8);
+    } else if len <= u8::MAX as u64 {
+        cbor.push(major | 24);
+        cbor.push(len as u8);
+    } else if len <= u16::MAX as u64 {
+        cbor.push(major | 25);
+        cbor.extend_from_slice(&(len as u16).to_be_bytes());
+    } else if len <= u32::MAX as u64 {
+        cbor.push(major | 26);
+        cbor.extend_from_slice(&(len as u32).to_be_bytes());
+    } else {
+        cbor.push(major | 27);
+        cbor.extend_from_slice(&len.to_be_bytes());
+    }
+}
+
+/// Append to `cbor` the text string `text`, replacing invalid UTF-8 sequences.
+fn push_cbor_text(cbor: &mut Vec<u8>, text: &[u8]) {
+    let text = String::from_utf8_lossy(text);
+    push_cbor_header(cbor, CBOR_TEXT, text.len());
+    cbor.extend_from_slice(text.as_bytes());
+}
+
+#[test]
+fn test_debug_transcript() {
+    use ark_bls12_381::Fr;
+    use merlin::Transcript;
+
+    let mut transcript = Transcript::new(b"test");
+    let mut debug_transcript = DebugTranscript::new(transcript.clone());
+
+    transcript.append_serializable(b"message", &Fr::from(1u64));
+    debug_transcript.append_serializable(b"message", &Fr::from(1u64));
+    let challenge = transcript.get_challenge::<Fr>(b"challenge");
+    // the wrapped transcript derives the same challenges.
+    assert_eq!(
+        debug_transcript.get_challenge::<Fr>(b"challenge"),
+        challenge
+    );
+
+    let events = debug_transcript.events();
+    assert_eq!(events.len(), 2);
+    assert_eq!(events[0].op, TranscriptOp::Absorb);
+    assert_eq!(events[1].op, TranscriptOp::Squeeze);
+    assert_eq!(events[1].label, b"challenge");
+    let mut value = Vec::new();
+    challenge.serialize_uncompressed(&mut value).unwrap();
+    assert_eq!(events[1].value, value);
+
+    let json = debug_transcript.to_json();
+    assert!(json.contains("{\"op\": \"absorb\", \"label\": \"message\", \"value\": \"01"));
+    assert!(json.contains("\"label\": \"challenge\""));
+
+    let cbor = debug_transcript.to_cbor();
+    // an array of two maps of three entries, starting with the text "op".
+    assert_eq!(&cbor[..5], &[0x82, 0xa3, 0x62, b'o', b'p']);
+    // the 32-byte challenge closes the export.
+    assert_eq!(
+        &cbor[cbor.len() - 34..],
+        &[&[0x58, 32][..], &value[..]].concat()[..]
+    );
+}
@@ -0,0 +1,274 @@
+//! An algebraic transcript, based on the Poseidon sponge.
+//!
+//! The Keccak-based sponge behind [`merlin::Transcript`] is expensive to express as constraints.
+//! [`PoseidonTranscript`] instead operates natively over a prime field $\FF$,
+//! with a permutation of width 3 and S-box $x^\alpha$,
+//! and thus it is cheap to verify inside other SNARKs defined over $\FF$.
+//! The exponent $\alpha$ is the smallest integer greater than 2 coprime with $|\FF| - 1$,
+//! so that the S-box is a permutation of $\FF$
+//! (e.g., 5 for the scalar field of BLS12-381, and 11 for the scalar field of BLS12-377).
+//! The number of rounds is derived from the size of the field and from $\alpha$,
+//! following the round numbers script of the reference implementation
+//! (for a security level of 128 bits, and with its security margin).
+//!
+//! Messages are serialized and packed into field elements,
+//! each holding `(MODULUS_BIT_SIZE - 1) / 8` bytes.
+//! The round constants are derived deterministically from a domain separator
+//! (and not with the Grain LFSR of the reference specification),
+//! hence this transcript is not interoperable with other implementations of Poseidon.
+use ark_ff::{BigInteger, Field, PrimeField};
+use ark_serialize::CanonicalSerialize;
+use ark_std::vec::Vec;
+
+use crate::misc::{batch_inverse, ceil_div};
+use crate::transcript::GeminiTranscript;
+
+/// The size of the state of the permutation.
+const WIDTH: usize = 3;
+/// The number of field elements absorbed per permutation.
+const RATE: usize = 2;
+/// The security level of the permutation, in bits.
+const SECURITY_LEVEL: usize = 128;
+/// The domain separator for deriving the round constants.
+const CONSTANTS_LABEL: &[u8] = b"GEMINI-poseidon-constants";
+
+/// The parameters of the Poseidon permutation.
+#[derive(Clone, Debug)]
+struct PoseidonParameters<F: PrimeField> {
+    /// The exponent of the S-box.
+    alpha: u64,
+    /// The number of full rounds, half of which are performed before the partial rounds.
+    full_rounds: usize,
+    /// The number of partial rounds, applying the S-box to the first element of the state only.
+    partial_rounds: usize,
+    round_constants: Vec<[F; WIDTH]>,
+    mds: [[F; WIDTH]; WIDTH],
+}
+
+/// Return the remainder of the division of the integer with little-endian limbs `limbs` by `m`.
+fn limbs_rem(limbs: &[u64], m: u64) -> u64 {
+    limbs.iter().rev().fold(0, |rem, &limb| {
+        ((((rem as u128) << 64) | limb as u128) % m as u128) as u64
+    })
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    match b {
+        0 => a,
+        b => gcd(b, a % b),
+    }
+}
+
+/// Return the smallest `k` such that `alpha^k >= 2^bits`, for `alpha` odd.
+fn log_ceil(alpha: u64, bits: usize) -> usize {
+    // alpha^k is odd: it is at least 2^bits if and only if it has more than `bits` bits.
+    let bit_len =
+        |limbs: &[u64]| limbs.len() * 64 - limbs[limbs.len() - 1].leading_zeros() as usize;
+    let mut power = ark_std::vec![1u64];
+    let mut k = 0;
+    while bit_len(&power) <= bits {
+        let carry = power.iter_mut().fold(0, |carry, limb| {
+            let product = *limb as u128 * alpha as u128 + carry;
+            *limb = product as u64;
+            product >> 64
+        });
+        if carry != 0 {
+            power.push(carry as u64);
+        }
+        k += 1;
+    }
+    k
+}
+
+/// Return the number of full and partial rounds for the S-box $x^\alpha$ over `F`.
+///
+/// The full rounds resist statistical attacks, and both kinds of rounds
+/// resist interpolation and Gröbner basis attacks,
+/// whose bounds are dominated by the interpolation one for a width of 3.
+/// Adding full rounds is more expensive than adding partial rounds,
+/// hence the full rounds are kept to the minimum.
+/// The rounds are then increased by the security margin of the reference implementation:
+/// 2 more full rounds, and 7.5% more partial rounds.
+fn round_numbers<F: PrimeField>(alpha: u64) -> (usize, usize) {
+    let field_bits = F::MODULUS_BIT_SIZE as usize;
+    // 6 full rounds resist statistical attacks
+    // if the security level is at most (log |F| - (alpha - 1) / 2) * (WIDTH + 1) bits.
+    let statistical = (field_bits - 1 - (alpha as usize - 1) / 2) * (WIDTH + 1);
+    let full_rounds = if SECURITY_LEVEL <= statistical { 6 } else { 10 };
+    let width_log = (0..).find(|&k| alpha.pow(k) >= WIDTH as u64).unwrap() as usize;
+    let interpolation = 1 + log_ceil(alpha, usize::min(SECURITY_LEVEL, field_bits)) + width_log;
+    let partial_rounds = interpolation.saturating_sub(full_rounds);
+    (full_rounds + 2, ceil_div(partial_rounds * 1075, 1000))
+}
+
+impl<F: PrimeField> PoseidonParameters<F> {
+    fn new() -> Self {
+        // the smallest exponent for which the S-box is a permutation:
+        // x^alpha is a bijection if and only if alpha is coprime with |F| - 1.
+        let modulus_minus_one = |m| (limbs_rem(F::MODULUS.as_ref(), m) + m - 1) % m;
+        let alpha = (3..)
+            .step_by(2)
+            .find(|&alpha| gcd(alpha, modulus_minus_one(alpha)) == 1)
+            .unwrap();
+        let (full_rounds, partial_rounds) = round_numbers::<F>(alpha);
+        let mut transcript = merlin::Transcript::new(CONSTANTS_LABEL);
+        let round_constants = (0..full_rounds + partial_rounds)
+            .map(|_| [(); WIDTH].map(|_| transcript.get_challenge(b"round-constant")))
+            .collect();
+        // The Cauchy matrix 1/(x_i + y_j), with x_i = i and y_j = WIDTH + j, is MDS.
+        let mut mds = [[F::zero(); WIDTH]; WIDTH];
+        mds.iter_mut().enumerate().for_each(|(i, row)| {
+            let denominators = (0..WIDTH).map(|j| F::from((i + j + WIDTH) as u64));
+            row.iter_mut()
+                .zip(batch_inverse(denominators, WIDTH))
+                .for_each(|(m, inverse)| *m = inverse)
+        });
+        PoseidonParameters {
+            alpha,
+            full_rounds,
+            partial_rounds,
+            round_constants,
+            mds,
+        }
+    }
+
+    fn permute(&self, state: &mut [F; WIDTH]) {
+        let half_full_rounds = self.full_rounds / 2;
+        for (round, constants) in self.round_constants.iter().enumerate() {
+            state.iter_mut().zip(constants).for_each(|(s, c)| *s += c);
+            if round < half_full_rounds || round >= half_full_rounds + self.partial_rounds {
+                state.iter_mut().for_each(|s| *s = s.pow([self.alpha]));
+            } else {
+                state[0] = state[0].pow([self.alpha]);
+            }
+            *state = self
+                .mds
+                .map(|row| row.iter().zip(state.iter()).map(|(m, s)| *m * s).sum());
+        }
+    }
+}
+
+/// A transcript over the prime field `F`, based on the Poseidon sponge in duplex mode.
+#[derive(Clone, Debug)]
+pub struct PoseidonTranscript<F: PrimeField> {
+    parameters: PoseidonParameters<F>,
+    state: [F; WIDTH],
+    absorbed: usize,
+}
+
+impl<F: PrimeField> PoseidonTranscript<F> {
+    /// Create a new transcript, with domain separator `label`.
+    pub fn new(label: &'static [u8]) -> Self {
+        let mut transcript = PoseidonTranscript {
+            parameters: PoseidonParameters::new(),
+            state: [F::zero(); WIDTH],
+            absorbed: 0,
+        };
+        transcript.absorb_bytes(label);
+        transcript
+    }
+
+    fn absorb(&mut self, element: F) {
+        if self.absorbed == RATE {
+            self.parameters.permute(&mut self.state);
+            self.absorbed = 0;
+        }
+        self.state[self.absorbed] += element;
+        self.absorbed += 1;
+    }
+
+    /// Absorb the length of `bytes`, followed by `bytes` packed into field elements.
+    fn absorb_bytes(&mut self, bytes: &[u8]) {
+        let chunk_size = (F::MODULUS_BIT_SIZE as usize - 1) / 8;
+        self.absorb(F::from(bytes.len() as u64));
+        bytes
+            .chunks(chunk_size)
+            .for_each(|chunk| self.absorb(F::from_le_bytes_mod_order(chunk)));
+    }
+
+    fn squeeze(&mut self) -> F {
+        self.parameters.permute(&mut self.state);
+        self.absorbed = 0;
+        self.state[0]
+    }
+}
+
+impl<F: PrimeField> GeminiTranscript for PoseidonTranscript<F> {
+    fn append_serializable<S: CanonicalSerialize>(&mut self, label: &'static [u8], msg: &S) {
+        let mut message = Vec::new();
+        msg.serialize_uncompressed(&mut message).unwrap();
+        self.absorb_bytes(label);
+        self.absorb_bytes(&message);
+    }
+
+    /// Squeeze a challenge from the sponge.
+    ///
+    /// When the challenge lives in the field of the transcript,
+    /// it is the squeezed element itself.
+    fn get_challenge<G: Field>(&mut self, label: &'static [u8]) -> G {
+        self.absorb_bytes(label);
+        loop {
+            let bytes = self.squeeze().into_bigint().to_bytes_le();
+            if let Some(e) = G::from_random_bytes(&bytes) {
+                return e;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_poseidon_transcript() {
+    use ark_bls12_381::Fr;
+
+    let mut transcript = PoseidonTranscript::<Fr>::new(b"test");
+    let mut same_transcript = transcript.clone();
+    let mut other_transcript = transcript.clone();
+
+    transcript.append_serializable(b"message", &Fr::from(1u64));
+    same_transcript.append_serializable(b"message", &Fr::from(1u64));
+    other_transcript.append_serializable(b"message", &Fr::from(2u64));
+    let challenge = transcript.get_challenge::<Fr>(b"challenge");
+    assert_eq!(challenge, same_transcript.get_challenge::<Fr>(b"challenge"));
+    assert_ne!(
+        challenge,
+        other_transcript.get_challenge::<Fr>(b"challenge")
+    );
+    assert_ne!(challenge, transcript.get_challenge::<Fr>(b"challenge"));
+}
+
+#[test]
+fn test_poseidon_parameters() {
+    // 5 does not divide the order of the multiplicative group of the BLS12-381 scalar field.
+    let parameters = PoseidonParameters::<ark_bls12_381::Fr>::new();
+    assert_eq!(parameters.alpha, 5);
+    assert_eq!((parameters.full_rounds, parameters.partial_rounds), (8, 56));
+    assert_eq!(parameters.round_constants.len(), 8 + 56);
+    // 3, 5, 7 divide the order of the multiplicative group of the BLS12-377 scalar field.
+    let parameters = PoseidonParameters::<ark_bls12_377::Fr>::new();
+    assert_eq!(parameters.alpha, 11);
+    assert_eq!((parameters.full_rounds, parameters.partial_rounds), (8, 37));
+
+    assert_eq!(log_ceil(3, 0), 0);
+    assert_eq!(log_ceil(3, 1), 1);
+    assert_eq!(log_ceil(5, 128), 56);
+    assert_eq!(log_ceil(11, 128), 38);
+}
+
+#[test]
+fn test_poseidon_transcript_bls12_377() {
+    use ark_bls12_377::Fr;
+
+    let mut transcript = PoseidonTranscript::<Fr>::new(b"test");
+    let mut same_transcript = transcript.clone();
+    let mut other_transcript = transcript.clone();
+
+    transcript.append_serializable(b"message", &Fr::from(1u64));
+    same_transcript.append_serializable(b"message", &Fr::from(1u64));
+    other_transcript.append_serializable(b"message", &Fr::from(2u64));
+    let challenge = transcript.get_challenge::<Fr>(b"challenge");
+    assert_eq!(challenge, same_transcript.get_challenge::<Fr>(b"challenge"));
+    assert_ne!(
+        challenge,
+        other_transcript.get_challenge::<Fr>(b"challenge")
+    );
+}
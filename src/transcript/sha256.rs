@@ -0,0 +1,95 @@
+//! A hash-based transcript, built on SHA-256.
+//!
+//! Many proof systems (and smart-contract verifiers) derive their challenges
+//! with a plain hash function rather than with a sponge.
+//! [`Sha256Transcript`] hashes every message, framed with its label and length,
+//! into a running SHA-256 state, and derives each challenge from a copy of the state,
+//! so that the challenges depend on all the messages absorbed so far.
+use ark_ff::Field;
+use ark_serialize::CanonicalSerialize;
+use ark_std::vec::Vec;
+use sha2::{Digest, Sha256};
+
+use crate::transcript::GeminiTranscript;
+
+/// A transcript based on the SHA-256 hash function.
+#[derive(Clone, Debug)]
+pub struct Sha256Transcript {
+    hasher: Sha256,
+}
+
+impl Sha256Transcript {
+    /// Create a new transcript, with domain separator `label`.
+    pub fn new(label: &'static [u8]) -> Self {
+        let mut transcript = Sha256Transcript {
+            hasher: Sha256::new(),
+        };
+        transcript.absorb(b"dom-sep", label);
+        transcript
+    }
+
+    /// Absorb the `label`ed message `bytes`, prefixed by the lengths of both.
+    fn absorb(&mut self, label: &[u8], bytes: &[u8]) {
+        self.hasher.update((label.len() as u64).to_le_bytes());
+        self.hasher.update(label);
+        self.hasher.update((bytes.len() as u64).to_le_bytes());
+        self.hasher.update(bytes);
+    }
+
+    /// Return 64 pseudorandom bytes for the `counter`-th attempt of the challenge `label`.
+    fn squeeze(&self, label: &[u8], counter: u64) -> [u8; 64] {
+        let mut bytes = [0; 64];
+        for (i, chunk) in bytes.chunks_mut(32).enumerate() {
+            let mut hasher = self.hasher.clone();
+            hasher.update(b"challenge");
+            hasher.update(label);
+            hasher.update(counter.to_le_bytes());
+            hasher.update([i as u8]);
+            chunk.copy_from_slice(&hasher.finalize());
+        }
+        bytes
+    }
+}
+
+impl GeminiTranscript for Sha256Transcript {
+    fn append_serializable<S: CanonicalSerialize>(&mut self, label: &'static [u8], msg: &S) {
+        let mut message = Vec::new();
+        msg.serialize_uncompressed(&mut message).unwrap();
+        self.absorb(label, &message);
+    }
+
+    /// Derive a challenge from the current state,
+    /// and bind the challenge to the transcript.
+    fn get_challenge<F: Field>(&mut self, label: &'static [u8]) -> F {
+        let mut counter = 0u64;
+        let challenge = loop {
+            let bytes = self.squeeze(label, counter);
+            if let Some(e) = F::from_random_bytes(&bytes) {
+                break e;
+            }
+            counter += 1;
+        };
+        self.append_serializable(label, &challenge);
+        challenge
+    }
+}
+
+#[test]
+fn test_sha256_transcript() {
+    use ark_bls12_381::Fr;
+
+    let mut transcript = Sha256Transcript::new(b"test");
+    let mut same_transcript = transcript.clone();
+    let mut other_transcript = transcript.clone();
+
+    transcript.append_serializable(b"message", &Fr::from(1u64));
+    same_transcript.append_serializable(b"message", &Fr::from(1u64));
+    other_transcript.append_serializable(b"message", &Fr::from(2u64));
+    let challenge = transcript.get_challenge::<Fr>(b"challenge");
+    assert_eq!(challenge, same_transcript.get_challenge::<Fr>(b"challenge"));
+    assert_ne!(
+        challenge,
+        other_transcript.get_challenge::<Fr>(b"challenge")
+    );
+    assert_ne!(challenge, transcript.get_challenge::<Fr>(b"challenge"));
+}
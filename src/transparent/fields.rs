@@ -0,0 +1,52 @@
+//! The Goldilocks field and its quadratic extension.
+//!
+//! The Goldilocks prime \\(p = 2^{64} - 2^{32} + 1\\) has two-adicity 32,
+//! hence it supports Reed–Solomon encodings of length up to \\(2^{32}\\);
+//! challenges are drawn from the quadratic extension \\(\FF_p[X]/(X^2 - 7)\\),
+//! of about 128 bits.
+use ark_ff::fields::{Fp2, Fp2Config, Fp64, MontBackend, MontConfig};
+use ark_ff::MontFp;
+
+/// The Montgomery configuration of the Goldilocks field.
+#[derive(MontConfig)]
+#[modulus = "18446744069414584321"]
+#[generator = "7"]
+pub struct GoldilocksConfig;
+
+/// The Goldilocks field.
+pub type Goldilocks = Fp64<MontBackend<GoldilocksConfig, 1>>;
+
+/// The configuration of the quadratic extension of the Goldilocks field.
+pub struct GoldilocksExt2Config;
+
+impl Fp2Config for GoldilocksExt2Config {
+    type Fp = Goldilocks;
+
+    // 7 generates the multiplicative group, hence it is not a square.
+    const NONRESIDUE: Goldilocks = MontFp!("7");
+
+    // the non-residue raised to the (p^i - 1) / 2, for i = 0, 1.
+    const FROBENIUS_COEFF_FP2_C1: &'static [Goldilocks] =
+        &[MontFp!("1"), MontFp!("18446744069414584320")];
+}
+
+/// The quadratic extension of the Goldilocks field.
+pub type GoldilocksExt2 = Fp2<GoldilocksExt2Config>;
+
+#[test]
+fn test_goldilocks() {
+    use ark_ff::{FftField, Field, One, UniformRand};
+
+    let rng = &mut ark_std::test_rng();
+    assert_eq!(Goldilocks::TWO_ADICITY, 32);
+    let root = Goldilocks::TWO_ADIC_ROOT_OF_UNITY;
+    assert_eq!(root.pow([1u64 << 32]), Goldilocks::one());
+    assert_ne!(root.pow([1u64 << 31]), Goldilocks::one());
+
+    // the extension is a field, and the Frobenius map is its automorphism.
+    let a = GoldilocksExt2::rand(rng);
+    assert_eq!(a * a.inverse().unwrap(), GoldilocksExt2::one());
+    let mut frobenius = a;
+    frobenius.frobenius_map_in_place(1);
+    assert_eq!(frobenius, a.pow(Goldilocks::characteristic()));
+}
@@ -0,0 +1,426 @@
+//! A transparent polynomial commitment over small prime fields.
+//!
+//! Over small fields (e.g., [Goldilocks](fields::Goldilocks)) there are no pairing-friendly curves,
+//! and the streaming provers cannot rely on [KZG](crate::kzg) commitments.
+//! This module provides a transparent alternative, in the style of Ligero:
+//! the coefficients of a polynomial of degree less than $N$, read as a stream,
+//! are laid out in a matrix $M$ of $N / m$ rows and $m$ columns, one row per chunk of the stream.
+//! Each row is encoded with a Reed–Solomon code of rate $1/\rho$,
+//! and the commitment is the Merkle root of the (hashed) columns of the encoded matrix.
+//!
+//! Since the evaluation $f(\alpha)$ is a tensor product $\vec a^\intercal M \vec b$,
+//! an evaluation proof holds the combination $\vec a^\intercal M$ of the rows (from which the verifier computes the evaluation),
+//! a random combination of the rows (for the proximity test),
+//! and a number of columns of the encoded matrix, with their authentication paths,
+//! against which the verifier checks both combinations.
+//! Evaluation points and challenges live in an extension field `K` of the base field `F`,
+//! so that soundness does not depend on the size of `F`.
+//!
+//! The committer reads the polynomial once, keeping only one row and the hashes of the columns in memory;
+//! an opening reads the polynomial twice.
+//! With rows of $m \approx \sqrt N$ elements, both run in space $O(\sqrt N)$.
+//! The commitment and the evaluation proofs rely only on SHA-256 and the transcript.
+//!
+//! This module is only the commitment scheme:
+//! the [`snark`](crate::snark) and [`psnark`](crate::psnark) provers and verifiers
+//! are generic over a pairing and commit with KZG, and there is no small-field proving mode yet.
+//! Such a mode needs the arguments to be generic over the commitment scheme,
+//! with their challenges drawn from the extension field `K`.
+use ark_ff::{FftField, Field, PrimeField, Zero};
+use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use ark_std::borrow::Borrow;
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+use sha2::{Digest, Sha256};
+
+use crate::errors::{VerificationError, VerificationResult};
+use crate::iterable::Iterable;
+use crate::transcript::GeminiTranscript;
+
+pub mod fields;
+
+/// A SHA-256 digest.
+pub type Digest32 = [u8; 32];
+
+/// The parameters of the transparent commitment.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransparentParams {
+    /// The number of columns $m$ of the matrix, a power of two.
+    pub num_cols: usize,
+    /// The inverse rate $\rho$ of the code, a power of two.
+    pub blowup: usize,
+    /// The number of columns opened by each evaluation proof.
+    pub num_queries: usize,
+}
+
+impl TransparentParams {
+    /// The parameters for polynomials of degree less than `max_degree`,
+    /// with rows of about $\sqrt{\text{max_degree}}$ elements,
+    /// rate $1/4$, and 256 queries (that is, about 100 bits of security in the unique-decoding regime).
+    pub fn new(max_degree: usize) -> Self {
+        let log_degree = ark_std::log2(max_degree.max(1));
+        Self {
+            num_cols: 1 << ((log_degree + 1) / 2),
+            blowup: 4,
+            num_queries: 256,
+        }
+    }
+
+    /// The number of rows of the matrix of a polynomial with `len` coefficients.
+    fn num_rows(&self, len: usize) -> usize {
+        ark_std::cmp::max(1, (len + self.num_cols - 1) / self.num_cols)
+    }
+
+    /// The length of the encoded rows.
+    fn encoded_len(&self) -> usize {
+        self.num_cols * self.blowup
+    }
+}
+
+/// A commitment to a polynomial: the Merkle root of the columns of the encoded matrix,
+/// and the number of coefficients.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TransparentCommitment {
+    root: Digest32,
+    len: usize,
+}
+
+/// The state kept by the committer for later openings: the Merkle tree of the encoded columns.
+#[derive(Clone, Debug)]
+pub struct CommitterState {
+    /// The layers of the tree, from the hashes of the columns to the root.
+    layers: Vec<Vec<Digest32>>,
+}
+
+impl CommitterState {
+    /// Return the authentication path of the leaf `index`.
+    fn path(&self, mut index: usize) -> Vec<Digest32> {
+        let mut path = Vec::with_capacity(self.layers.len() - 1);
+        for layer in &self.layers[..self.layers.len() - 1] {
+            path.push(layer[index ^ 1]);
+            index >>= 1;
+        }
+        path
+    }
+}
+
+/// An evaluation proof of the transparent commitment.
+#[derive(CanonicalSerialize, CanonicalDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TransparentProof<F: Field, K: Field> {
+    /// The random combination of the rows, for the proximity test.
+    proximity_row: Vec<K>,
+    /// The combination of the rows weighted by the powers of the evaluation point.
+    evaluation_row: Vec<K>,
+    /// The opened columns of the encoded matrix.
+    columns: Vec<Vec<F>>,
+    /// The authentication paths of the opened columns.
+    paths: Vec<Vec<Digest32>>,
+}
+
+fn hash_nodes(left: &Digest32, right: &Digest32) -> Digest32 {
+    let mut hasher = Sha256::new();
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn hash_element<F: CanonicalSerialize>(hasher: &mut Sha256, element: &F) {
+    let mut bytes = Vec::new();
+    element
+        .serialize_uncompressed(&mut bytes)
+        .expect("serialization into a vector cannot fail");
+    hasher.update(&bytes);
+}
+
+/// Return the Reed–Solomon encoding of `row` over `domain`.
+fn encode<F: FftField>(domain: &Radix2EvaluationDomain<F>, row: &[F]) -> Vec<F> {
+    domain.fft(row)
+}
+
+/// Return the encoding of the vector `row` over the extension,
+/// encoding each of its coordinates over the base field.
+fn encode_extension<F, K>(domain: &Radix2EvaluationDomain<F>, row: &[K]) -> Vec<K>
+where
+    F: FftField + PrimeField,
+    K: Field<BasePrimeField = F>,
+{
+    let degree = K::extension_degree() as usize;
+    let coordinates = (0..degree)
+        .map(|c| {
+            let coordinate = row
+                .iter()
+                .map(|e| e.to_base_prime_field_elements().nth(c).unwrap())
+                .collect::<Vec<_>>();
+            encode(domain, &coordinate)
+        })
+        .collect::<Vec<_>>();
+    (0..domain.size())
+        .map(|i| {
+            let elements = coordinates.iter().map(|c| c[i]).collect::<Vec<_>>();
+            K::from_base_prime_field_elems(&elements).unwrap()
+        })
+        .collect()
+}
+
+/// Iterate over the rows of the matrix of `polynomial` (in stream order),
+/// padding the stream with leading zeros.
+fn rows<F, S>(params: &TransparentParams, polynomial: &S) -> impl Iterator<Item = Vec<F>>
+where
+    F: Field,
+    S: Iterable,
+    S::Item: Borrow<F>,
+{
+    let num_cols = params.num_cols;
+    let padding = params.num_rows(polynomial.len()) * num_cols - polynomial.len();
+    let mut elements = ark_std::iter::repeat(F::zero())
+        .take(padding)
+        .chain(polynomial.iter().map(|e| *e.borrow()))
+        .peekable();
+    ark_std::iter::from_fn(move || {
+        elements.peek()?;
+        Some((&mut elements).take(num_cols).collect())
+    })
+}
+
+/// The tensor vectors $\vec a, \vec b$ such that $f(\alpha) = \vec a^\intercal M \vec b$,
+/// for a matrix of `num_rows` rows (in stream order, that is, from the highest degree).
+fn tensor<K: Field>(params: &TransparentParams, num_rows: usize, point: &K) -> (Vec<K>, Vec<K>) {
+    let mut b = crate::misc::powers(*point, params.num_cols);
+    b.reverse();
+    let mut a = crate::misc::powers(point.pow([params.num_cols as u64]), num_rows);
+    a.reverse();
+    (a, b)
+}
+
+/// Derive the combination coefficients of the proximity test.
+fn proximity_challenges<K: Field>(
+    transcript: &mut impl GeminiTranscript,
+    commitment: &TransparentCommitment,
+    point: &K,
+    num_rows: usize,
+) -> Vec<K> {
+    transcript.append_serializable(b"transparent-commitment", commitment);
+    transcript.append_serializable(b"transparent-point", point);
+    (0..num_rows)
+        .map(|_| transcript.get_challenge::<K>(b"transparent-proximity"))
+        .collect()
+}
+
+/// Derive the columns to open.
+fn query_indices<F: PrimeField, K: Field, T: GeminiTranscript>(
+    params: &TransparentParams,
+    transcript: &mut T,
+    proximity_row: &[K],
+    evaluation_row: &[K],
+) -> Vec<usize> {
+    transcript.append_serializable(b"transparent-proximity-row", &proximity_row.to_vec());
+    transcript.append_serializable(b"transparent-evaluation-row", &evaluation_row.to_vec());
+    (0..params.num_queries)
+        .map(|_| {
+            let challenge = transcript.get_challenge::<F>(b"transparent-query");
+            (challenge.into_bigint().as_ref()[0] as usize) % params.encoded_len()
+        })
+        .collect()
+}
+
+/// The transparent commitment scheme over the base field `F`, with points in the extension `K`.
+pub struct TransparentPcs<F, K> {
+    params: TransparentParams,
+    domain: Radix2EvaluationDomain<F>,
+    _extension: PhantomData<K>,
+}
+
+impl<F, K> TransparentPcs<F, K>
+where
+    F: FftField + PrimeField,
+    K: Field<BasePrimeField = F>,
+{
+    /// Instantiate the scheme with `params`.
+    ///
+    /// Fails if the number of columns or the blowup are not powers of two,
+    /// or if the field does not support encodings of the required length.
+    pub fn new(params: TransparentParams) -> Option<Self> {
+        if !params.num_cols.is_power_of_two() || !params.blowup.is_power_of_two() {
+            return None;
+        }
+        let domain = Radix2EvaluationDomain::new(params.encoded_len())?;
+        Some(Self {
+            params,
+            domain,
+            _extension: PhantomData,
+        })
+    }
+
+    /// Commit to the coefficients of `polynomial`, streamed from the highest degree,
+    /// and return the commitment with the state needed to open it.
+    pub fn commit<S>(&self, polynomial: &S) -> (TransparentCommitment, CommitterState)
+    where
+        S: Iterable,
+        S::Item: Borrow<F>,
+    {
+        trace_span!("transparent_commit", len = polynomial.len());
+        let mut column_hashers = vec![Sha256::new(); self.params.encoded_len()];
+        for row in rows(&self.params, polynomial) {
+            for (hasher, element) in column_hashers.iter_mut().zip(encode(&self.domain, &row)) {
+                hash_element(hasher, &element);
+            }
+        }
+        let mut layers = vec![column_hashers
+            .into_iter()
+            .map(|hasher| hasher.finalize().into())
+            .collect::<Vec<Digest32>>()];
+        while layers.last().unwrap().len() > 1 {
+            let next = layers
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| hash_nodes(&pair[0], &pair[1]))
+                .collect();
+            layers.push(next);
+        }
+        let commitment = TransparentCommitment {
+            root: layers.last().unwrap()[0],
+            len: polynomial.len(),
+        };
+        (commitment, CommitterState { layers })
+    }
+
+    /// Evaluate `polynomial` (committed in `commitment`, with state `state`) at `point`,
+    /// and prove the evaluation.
+    pub fn open<S>(
+        &self,
+        transcript: &mut impl GeminiTranscript,
+        commitment: &TransparentCommitment,
+        state: &CommitterState,
+        polynomial: &S,
+        point: &K,
+    ) -> (K, TransparentProof<F, K>)
+    where
+        S: Iterable,
+        S::Item: Borrow<F>,
+    {
+        trace_span!("transparent_open", len = polynomial.len());
+        let num_rows = self.params.num_rows(polynomial.len());
+        let gammas = proximity_challenges(transcript, commitment, point, num_rows);
+        let (a, b) = tensor(&self.params, num_rows, point);
+
+        // first pass: combine the rows.
+        let mut proximity_row = vec![K::zero(); self.params.num_cols];
+        let mut evaluation_row = vec![K::zero(); self.params.num_cols];
+        for ((row, gamma), a_i) in rows(&self.params, polynomial).zip(&gammas).zip(&a) {
+            for ((p, e), m) in proximity_row.iter_mut().zip(&mut evaluation_row).zip(&row) {
+                let m = K::from_base_prime_field(*m);
+                *p += m * gamma;
+                *e += m * a_i;
+            }
+        }
+        let evaluation = evaluation_row.iter().zip(&b).map(|(u, b)| *u * b).sum();
+
+        // second pass: open the queried columns.
+        let queries =
+            query_indices::<F, K, _>(&self.params, transcript, &proximity_row, &evaluation_row);
+        let mut columns = vec![Vec::with_capacity(num_rows); queries.len()];
+        for row in rows(&self.params, polynomial) {
+            let encoded = encode(&self.domain, &row);
+            for (column, &q) in columns.iter_mut().zip(&queries) {
+                column.push(encoded[q]);
+            }
+        }
+        let paths = queries.iter().map(|&q| state.path(q)).collect();
+        let proof = TransparentProof {
+            proximity_row,
+            evaluation_row,
+            columns,
+            paths,
+        };
+        (evaluation, proof)
+    }
+
+    /// Verify that `proof` proves the evaluation `evaluation` at `point`
+    /// of the polynomial committed in `commitment`.
+    pub fn verify(
+        &self,
+        transcript: &mut impl GeminiTranscript,
+        commitment: &TransparentCommitment,
+        point: &K,
+        evaluation: &K,
+        proof: &TransparentProof<F, K>,
+    ) -> VerificationResult {
+        let num_rows = self.params.num_rows(commitment.len);
+        let depth = ark_std::log2(self.params.encoded_len()) as usize;
+        let well_formed = proof.proximity_row.len() == self.params.num_cols
+            && proof.evaluation_row.len() == self.params.num_cols
+            && proof.columns.len() == self.params.num_queries
+            && proof.paths.len() == self.params.num_queries
+            && proof.columns.iter().all(|c| c.len() == num_rows)
+            && proof.paths.iter().all(|p| p.len() == depth);
+        if !well_formed {
+            return Err(VerificationError::Consistency {
+                check: "shape of the transparent evaluation proof",
+            });
+        }
+        let gammas = proximity_challenges(transcript, commitment, point, num_rows);
+        let (a, b) = tensor(&self.params, num_rows, point);
+        let found: K = proof
+            .evaluation_row
+            .iter()
+            .zip(&b)
+            .map(|(u, b)| *u * b)
+            .sum();
+        if found != *evaluation {
+            return Err(VerificationError::Consistency {
+                check: "transparent evaluation",
+            });
+        }
+
+        let queries = query_indices::<F, K, _>(
+            &self.params,
+            transcript,
+            &proof.proximity_row,
+            &proof.evaluation_row,
+        );
+        let encoded_proximity = encode_extension(&self.domain, &proof.proximity_row);
+        let encoded_evaluation = encode_extension(&self.domain, &proof.evaluation_row);
+        for ((&q, column), path) in queries.iter().zip(&proof.columns).zip(&proof.paths) {
+            let mut hasher = Sha256::new();
+            column.iter().for_each(|e| hash_element(&mut hasher, e));
+            let mut node: Digest32 = hasher.finalize().into();
+            let mut index = q;
+            for sibling in path {
+                node = if index & 1 == 0 {
+                    hash_nodes(&node, sibling)
+                } else {
+                    hash_nodes(sibling, &node)
+                };
+                index >>= 1;
+            }
+            if node != commitment.root {
+                return Err(VerificationError::Opening {
+                    opening: "transparent column",
+                });
+            }
+            let combine = |coefficients: &[K]| -> K {
+                column
+                    .iter()
+                    .zip(coefficients)
+                    .map(|(m, c)| K::from_base_prime_field(*m) * c)
+                    .sum()
+            };
+            if combine(&gammas) != encoded_proximity[q] {
+                return Err(VerificationError::Consistency {
+                    check: "transparent proximity test",
+                });
+            }
+            if combine(&a) != encoded_evaluation[q] {
+                return Err(VerificationError::Consistency {
+                    check: "transparent evaluation row",
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,80 @@
+use ark_ff::{Field, Zero};
+use ark_std::vec::Vec;
+use ark_std::UniformRand;
+use merlin::Transcript;
+
+use crate::iterable::Reverse;
+use crate::transparent::fields::{Goldilocks, GoldilocksExt2};
+use crate::transparent::{TransparentParams, TransparentPcs};
+
+fn evaluate(coefficients: &[Goldilocks], point: &GoldilocksExt2) -> GoldilocksExt2 {
+    coefficients
+        .iter()
+        .rev()
+        .fold(GoldilocksExt2::zero(), |acc, c| {
+            acc * point + GoldilocksExt2::from_base_prime_field(*c)
+        })
+}
+
+#[test]
+fn test_transparent_pcs() {
+    let rng = &mut ark_std::test_rng();
+    // not a multiple of the number of columns, so that the matrix is padded.
+    let d = 1000;
+    let coefficients = (0..d).map(|_| Goldilocks::rand(rng)).collect::<Vec<_>>();
+    let polynomial = Reverse(coefficients.as_slice());
+    let params = TransparentParams {
+        num_cols: 32,
+        blowup: 4,
+        num_queries: 30,
+    };
+    let pcs = TransparentPcs::<Goldilocks, GoldilocksExt2>::new(params).unwrap();
+    let (commitment, state) = pcs.commit(&polynomial);
+
+    let point = GoldilocksExt2::rand(rng);
+    let mut prover_transcript = Transcript::new(crate::PROTOCOL_NAME);
+    let (evaluation, proof) = pcs.open(
+        &mut prover_transcript,
+        &commitment,
+        &state,
+        &polynomial,
+        &point,
+    );
+    assert_eq!(evaluation, evaluate(&coefficients, &point));
+
+    let mut verifier_transcript = Transcript::new(crate::PROTOCOL_NAME);
+    assert!(pcs
+        .verify(
+            &mut verifier_transcript,
+            &commitment,
+            &point,
+            &evaluation,
+            &proof
+        )
+        .is_ok());
+
+    // a wrong evaluation is rejected.
+    let mut verifier_transcript = Transcript::new(crate::PROTOCOL_NAME);
+    let wrong_evaluation = evaluation + GoldilocksExt2::from(1u64);
+    assert!(pcs
+        .verify(
+            &mut verifier_transcript,
+            &commitment,
+            &point,
+            &wrong_evaluation,
+            &proof
+        )
+        .is_err());
+}
+
+#[test]
+fn test_transparent_params() {
+    let params = TransparentParams::new(1 << 20);
+    assert_eq!(params.num_cols, 1 << 10);
+    assert!(TransparentPcs::<Goldilocks, GoldilocksExt2>::new(params).is_some());
+    let params = TransparentParams {
+        num_cols: 3,
+        ..params
+    };
+    assert!(TransparentPcs::<Goldilocks, GoldilocksExt2>::new(params).is_none());
+}
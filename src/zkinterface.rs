@@ -0,0 +1,835 @@
+//! Streaming import of circuits in the [zkInterface](https://github.com/QED-it/zkinterface) format.
+//!
+//! A zkInterface file is a sequence of size-prefixed FlatBuffers messages (version 1.1 onwards):
+//! a `CircuitHeader`, declaring the instance variables and the number of variables,
+//! followed by `ConstraintSystem` messages, holding the constraints,
+//! or by `Witness` messages, holding the assignment to the witness variables.
+//! Constraints and witness are usually written in distinct files, each with its own header.
+//!
+//! Variable 0 is the constant one.
+//! The instance variables must be numbered $1, \dots, n$, and the witness variables from $n+1$ onwards,
+//! so that the variables are laid out exactly as the vector $\vec z = (\vec x, \vec w)$,
+//! as do the frontends built on bellman and libsnark.
+//!
+//! Messages are read lazily, one at a time, from the last one:
+//! only the offsets of the messages are kept in memory.
+//! Matrix streams are row-major, and can be fed directly to the psnark indexer;
+//! column-major streams can be obtained with [`transpose`](crate::iterable::transpose::transpose).
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::marker::PhantomData;
+use ark_std::vec::Vec;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::iterable::Iterable;
+use crate::misc::MatrixElement;
+
+/// The largest supported field element, in bytes.
+const MAX_ELEMENT_SIZE: usize = 64;
+/// The FlatBuffers identifier of zkInterface messages.
+const FILE_IDENTIFIER: &[u8; 4] = b"zkif";
+/// The type of `CircuitHeader` messages.
+const CIRCUIT_HEADER: u8 = 1;
+/// The type of `ConstraintSystem` messages.
+const CONSTRAINT_SYSTEM: u8 = 2;
+/// The type of `Witness` messages.
+const WITNESS: u8 = 3;
+
+fn invalid_data(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Read `N` bytes of `buf` from position `pos`.
+fn read_array<const N: usize>(buf: &[u8], pos: usize) -> io::Result<[u8; N]> {
+    buf.get(pos..pos.saturating_add(N))
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| invalid_data("truncated message"))
+}
+
+/// Follow the (unsigned, forward) FlatBuffers offset at position `pos`.
+fn follow(buf: &[u8], pos: usize) -> io::Result<usize> {
+    let offset = u32::from_le_bytes(read_array(buf, pos)?) as usize;
+    Ok(pos.saturating_add(offset))
+}
+
+/// A table of a FlatBuffers message.
+#[derive(Clone, Copy)]
+struct Table<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Table<'a> {
+    /// The position of the field of index `field`, if present.
+    fn field(&self, field: usize) -> io::Result<Option<usize>> {
+        let soffset = i32::from_le_bytes(read_array(self.buf, self.pos)?);
+        let vtable = (self.pos as i64)
+            .checked_sub(soffset as i64)
+            .filter(|&vtable| vtable >= 0)
+            .ok_or_else(|| invalid_data("malformed table"))? as usize;
+        let vtable_size = u16::from_le_bytes(read_array(self.buf, vtable)?) as usize;
+        let entry = 4 + 2 * field;
+        if entry + 2 > vtable_size {
+            return Ok(None);
+        }
+        let offset = u16::from_le_bytes(read_array(self.buf, vtable + entry)?) as usize;
+        Ok((offset != 0).then_some(self.pos + offset))
+    }
+
+    fn u8(&self, field: usize) -> io::Result<u8> {
+        match self.field(field)? {
+            Some(pos) => Ok(read_array::<1>(self.buf, pos)?[0]),
+            None => Ok(0),
+        }
+    }
+
+    fn u64(&self, field: usize) -> io::Result<u64> {
+        match self.field(field)? {
+            Some(pos) => Ok(u64::from_le_bytes(read_array(self.buf, pos)?)),
+            None => Ok(0),
+        }
+    }
+
+    fn table(&self, field: usize) -> io::Result<Option<Table<'a>>> {
+        match self.field(field)? {
+            Some(pos) => Ok(Some(Table {
+                buf: self.buf,
+                pos: follow(self.buf, pos)?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    /// The position of the first element and the length of the vector in field `field`.
+    fn vector(&self, field: usize, element_size: usize) -> io::Result<(usize, usize)> {
+        let pos = match self.field(field)? {
+            Some(pos) => follow(self.buf, pos)?,
+            None => return Ok((0, 0)),
+        };
+        let len = u32::from_le_bytes(read_array(self.buf, pos)?) as usize;
+        let start = pos + 4;
+        if start.saturating_add(len.saturating_mul(element_size)) > self.buf.len() {
+            return Err(invalid_data("truncated message"));
+        }
+        Ok((start, len))
+    }
+
+    fn bytes(&self, field: usize) -> io::Result<&'a [u8]> {
+        let (start, len) = self.vector(field, 1)?;
+        Ok(&self.buf[start..start + len])
+    }
+
+    fn u64s(&self, field: usize) -> io::Result<Vec<u64>> {
+        let (start, len) = self.vector(field, 8)?;
+        (0..len)
+            .map(|i| read_array(self.buf, start + 8 * i).map(u64::from_le_bytes))
+            .collect()
+    }
+
+    fn tables(&self, field: usize) -> io::Result<Vec<Table<'a>>> {
+        let (start, len) = self.vector(field, 4)?;
+        (0..len)
+            .map(|i| {
+                let pos = follow(self.buf, start + 4 * i)?;
+                Ok(Table { buf: self.buf, pos })
+            })
+            .collect()
+    }
+}
+
+/// Return the type and the table of the message `message`.
+fn read_root(message: &[u8]) -> io::Result<(u8, Table<'_>)> {
+    if message.get(4..8) != Some(&FILE_IDENTIFIER[..]) {
+        return Err(invalid_data("unexpected file type"));
+    }
+    let root = Table {
+        buf: message,
+        pos: follow(message, 0)?,
+    };
+    // the union `message` is stored as its type (field 0) and its value (field 1).
+    let message_type = root.u8(0)?;
+    let table = root
+        .table(1)?
+        .ok_or_else(|| invalid_data("empty message"))?;
+    Ok((message_type, table))
+}
+
+/// Read the `Variables` table `variables`:
+/// the identifiers of the variables, and the values assigned to them (if any).
+fn read_variables<F: PrimeField>(variables: Option<Table>) -> io::Result<(Vec<u64>, Vec<F>)> {
+    let variables = match variables {
+        Some(variables) => variables,
+        None => return Ok((Vec::new(), Vec::new())),
+    };
+    let ids = variables.u64s(0)?;
+    let values = variables.bytes(1)?;
+    if values.is_empty() {
+        return Ok((ids, Vec::new()));
+    }
+    // all values have the same size, possibly shorter than the field elements.
+    if ids.is_empty() || values.len() % ids.len() != 0 {
+        return Err(invalid_data("values do not match the variables"));
+    }
+    let n8 = values.len() / ids.len();
+    if n8 > MAX_ELEMENT_SIZE {
+        return Err(invalid_data("field elements are too large"));
+    }
+    let values = values.chunks(n8).map(F::from_le_bytes_mod_order).collect();
+    Ok((ids, values))
+}
+
+/// Read a linear combination, as a sparse row sorted by column.
+fn read_linear_combination<F: PrimeField>(variables: Option<Table>) -> io::Result<Vec<(F, usize)>> {
+    let (ids, coefficients) = read_variables::<F>(variables)?;
+    if coefficients.len() != ids.len() {
+        return Err(invalid_data("missing coefficients"));
+    }
+    let mut row = coefficients
+        .into_iter()
+        .zip(ids.into_iter().map(|id| id as usize))
+        .collect::<Vec<_>>();
+    row.sort_unstable_by_key(|&(_, col)| col);
+    Ok(row)
+}
+
+/// Read the constraints of a `ConstraintSystem` message,
+/// each as the rows of the matrices $A$, $B$, $C$.
+fn read_constraints<F: PrimeField>(message: &[u8]) -> io::Result<Vec<[Vec<(F, usize)>; 3]>> {
+    let (_, constraint_system) = read_root(message)?;
+    constraint_system
+        .tables(0)?
+        .iter()
+        .map(|constraint| {
+            Ok([
+                read_linear_combination(constraint.table(0)?)?,
+                read_linear_combination(constraint.table(1)?)?,
+                read_linear_combination(constraint.table(2)?)?,
+            ])
+        })
+        .collect()
+}
+
+/// Read the assignment of a `Witness` message.
+fn read_assignment<F: PrimeField>(message: &[u8]) -> io::Result<(Vec<u64>, Vec<F>)> {
+    let (_, witness) = read_root(message)?;
+    let (ids, values) = read_variables(witness.table(0)?)?;
+    if values.len() != ids.len() {
+        return Err(invalid_data("missing witness values"));
+    }
+    Ok((ids, values))
+}
+
+/// The header of a circuit: the number of variables, and the instance variables.
+struct Header<F> {
+    num_variables: usize,
+    num_instance: usize,
+    /// The values of the instance variables, if assigned.
+    instance: Vec<F>,
+}
+
+/// Read a `CircuitHeader` message, checking that the field matches `F`.
+fn read_header<F: PrimeField>(header: Table) -> io::Result<Header<F>> {
+    let (ids, instance) = read_variables(header.table(0)?)?;
+    if ids.iter().enumerate().any(|(i, &id)| id != i as u64 + 1) {
+        return Err(invalid_data("instance variables must be numbered from 1"));
+    }
+    let num_variables = header.u64(1)? as usize;
+    if num_variables <= ids.len() {
+        return Err(invalid_data("too few variables"));
+    }
+    // the field is specified by its largest element (if at all).
+    let field_maximum = header.bytes(2)?;
+    if !field_maximum.is_empty() {
+        let maximum = (-F::one()).into_bigint().to_bytes_le();
+        let trim = |bytes: &[u8]| bytes.len() - bytes.iter().rev().take_while(|&&b| b == 0).count();
+        if field_maximum[..trim(field_maximum)] != maximum[..trim(&maximum)] {
+            return Err(invalid_data("the prime does not match the scalar field"));
+        }
+    }
+    Ok(Header {
+        num_variables,
+        num_instance: ids.len(),
+        instance,
+    })
+}
+
+/// A message within a file.
+struct Chunk {
+    offset: u64,
+    len: usize,
+    /// The first variable assigned by the message (for `Witness` messages).
+    first: usize,
+}
+
+/// Read the messages of the file at `path`,
+/// calling `f` on the type, the location, and the contents of each one.
+fn for_each_message<G>(path: &Path, mut f: G) -> io::Result<()>
+where
+    G: FnMut(u8, Chunk, &[u8]) -> io::Result<()>,
+{
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut offset = 0;
+    while !reader.fill_buf()?.is_empty() {
+        let mut size = [0u8; 4];
+        reader.read_exact(&mut size)?;
+        let len = u32::from_le_bytes(size) as usize;
+        let mut message = vec![0u8; len];
+        reader.read_exact(&mut message)?;
+        offset += 4;
+        let (message_type, _) = read_root(&message)?;
+        let chunk = Chunk {
+            offset,
+            len,
+            first: 0,
+        };
+        f(message_type, chunk, &message)?;
+        offset += len as u64;
+    }
+    Ok(())
+}
+
+/// Read the message at `chunk`.
+fn read_message(reader: &mut BufReader<File>, chunk: &Chunk) -> io::Result<Vec<u8>> {
+    reader.seek(SeekFrom::Start(chunk.offset))?;
+    let mut message = vec![0u8; chunk.len];
+    reader.read_exact(&mut message)?;
+    Ok(message)
+}
+
+/// The constraints of a zkInterface file.
+pub struct ZkifR1cs<F> {
+    path: PathBuf,
+    num_variables: usize,
+    num_statement: usize,
+    num_constraints: usize,
+    /// The `ConstraintSystem` messages.
+    chunks: Vec<Chunk>,
+    _field: PhantomData<F>,
+}
+
+impl<F: PrimeField> ZkifR1cs<F> {
+    /// Open the zkInterface file at `path`, and index its constraints.
+    ///
+    /// Fails if the file has no header, if the field does not match `F`,
+    /// or if any constraint refers to an undeclared variable.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut header = None;
+        let mut chunks = Vec::new();
+        let mut num_constraints = 0;
+        let mut max_variable = 0;
+        for_each_message(&path, |message_type, chunk, message| {
+            match message_type {
+                CIRCUIT_HEADER => header = Some(read_header::<F>(read_root(message)?.1)?),
+                CONSTRAINT_SYSTEM => {
+                    let constraints = read_constraints::<F>(message)?;
+                    num_constraints += constraints.len();
+                    max_variable = constraints
+                        .iter()
+                        .flatten()
+                        .flatten()
+                        .fold(max_variable, |max, &(_, col)| usize::max(max, col));
+                    chunks.push(chunk);
+                }
+                _ => (),
+            }
+            Ok(())
+        })?;
+        let header = header.ok_or_else(|| invalid_data("missing circuit header"))?;
+        if num_constraints > 0 && max_variable >= header.num_variables {
+            return Err(invalid_data("undeclared variable"));
+        }
+        Ok(ZkifR1cs {
+            path,
+            num_variables: header.num_variables,
+            num_statement: 1 + header.num_instance,
+            num_constraints,
+            chunks,
+            _field: PhantomData,
+        })
+    }
+
+    /// The number of variables, that is, the length of $\vec z$.
+    pub fn num_variables(&self) -> usize {
+        self.num_variables
+    }
+
+    /// The length of the public input $\vec x$, including the constant one.
+    pub fn num_statement(&self) -> usize {
+        self.num_statement
+    }
+
+    /// The number of constraints.
+    pub fn num_constraints(&self) -> usize {
+        self.num_constraints
+    }
+
+    /// The row-major stream of the matrix $A$.
+    pub fn a(&self) -> ZkifMatrix<'_, F> {
+        ZkifMatrix {
+            r1cs: self,
+            index: 0,
+        }
+    }
+
+    /// The row-major stream of the matrix $B$.
+    pub fn b(&self) -> ZkifMatrix<'_, F> {
+        ZkifMatrix {
+            r1cs: self,
+            index: 1,
+        }
+    }
+
+    /// The row-major stream of the matrix $C$.
+    pub fn c(&self) -> ZkifMatrix<'_, F> {
+        ZkifMatrix {
+            r1cs: self,
+            index: 2,
+        }
+    }
+}
+
+/// The row-major stream of one of the matrices of a [`ZkifR1cs`],
+/// with one line per constraint.
+#[derive(Clone, Copy)]
+pub struct ZkifMatrix<'a, F> {
+    r1cs: &'a ZkifR1cs<F>,
+    index: usize,
+}
+
+/// The iterator returned by [`ZkifMatrix`].
+pub struct ZkifMatrixIter<'a, F> {
+    r1cs: &'a ZkifR1cs<F>,
+    reader: BufReader<File>,
+    index: usize,
+    /// The number of messages left to read.
+    chunks: usize,
+    /// The elements of the current message, in reversed order.
+    buffer: Vec<MatrixElement<F>>,
+}
+
+impl<'a, F: PrimeField> ZkifMatrixIter<'a, F> {
+    fn read_chunk(&mut self, chunk: usize) -> io::Result<()> {
+        let message = read_message(&mut self.reader, &self.r1cs.chunks[chunk])?;
+        for rows in read_constraints::<F>(&message)? {
+            let row = rows.into_iter().nth(self.index).unwrap();
+            self.buffer.push(MatrixElement::EOL);
+            self.buffer
+                .extend(row.into_iter().map(MatrixElement::Element));
+        }
+        Ok(())
+    }
+}
+
+impl<'a, F: PrimeField> Iterator for ZkifMatrixIter<'a, F> {
+    type Item = MatrixElement<F>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(e) = self.buffer.pop() {
+                return Some(e);
+            }
+            if self.chunks == 0 {
+                return None;
+            }
+            self.chunks -= 1;
+            self.read_chunk(self.chunks)
+                .expect("failed to read the zkInterface constraints");
+        }
+    }
+}
+
+impl<'a, F: PrimeField> Iterable for ZkifMatrix<'a, F> {
+    type Item = MatrixElement<F>;
+
+    type Iter = ZkifMatrixIter<'a, F>;
+
+    fn iter(&self) -> Self::Iter {
+        let file = File::open(&self.r1cs.path).expect("failed to open the zkInterface file");
+        ZkifMatrixIter {
+            r1cs: self.r1cs,
+            reader: BufReader::new(file),
+            index: self.index,
+            chunks: self.r1cs.chunks.len(),
+            buffer: Vec::new(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.r1cs.num_constraints
+    }
+}
+
+/// The assignment of a zkInterface file.
+pub struct ZkifWitness<F> {
+    path: PathBuf,
+    num_variables: usize,
+    /// The public input $\vec x$, including the constant one.
+    public_input: Vec<F>,
+    /// The `Witness` messages, sorted by their first variable.
+    chunks: Vec<Chunk>,
+}
+
+impl<F: PrimeField> ZkifWitness<F> {
+    /// Open the zkInterface file at `path`, and index its assignment.
+    ///
+    /// Fails if the file has no header assigning the instance variables,
+    /// if the field does not match `F`,
+    /// or unless the witness variables are assigned exactly once, in order within each message.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut header = None;
+        let mut chunks = Vec::new();
+        for_each_message(&path, |message_type, mut chunk, message| {
+            match message_type {
+                CIRCUIT_HEADER => header = Some(read_header::<F>(read_root(message)?.1)?),
+                WITNESS => {
+                    let (ids, _) = read_assignment::<F>(message)?;
+                    if let Some(&first) = ids.first() {
+                        if ids
+                            .iter()
+                            .zip(first..)
+                            .any(|(&id, expected)| id != expected)
+                        {
+                            return Err(invalid_data("witness variables are not contiguous"));
+                        }
+                        chunk.first = first as usize;
+                        chunks.push((chunk, ids.len()));
+                    }
+                }
+                _ => (),
+            }
+            Ok(())
+        })?;
+        let header = header.ok_or_else(|| invalid_data("missing circuit header"))?;
+        if header.instance.len() != header.num_instance {
+            return Err(invalid_data("missing instance values"));
+        }
+        chunks.sort_unstable_by_key(|(chunk, _)| chunk.first);
+        let mut next = 1 + header.num_instance;
+        for (chunk, count) in &chunks {
+            if chunk.first != next {
+                return Err(invalid_data(
+                    "witness variables are not assigned exactly once",
+                ));
+            }
+            next += count;
+        }
+        if next != header.num_variables {
+            return Err(invalid_data(
+                "witness variables are not assigned exactly once",
+            ));
+        }
+        let mut public_input = vec![F::one()];
+        public_input.extend(header.instance);
+        Ok(ZkifWitness {
+            path,
+            num_variables: header.num_variables,
+            public_input,
+            chunks: chunks.into_iter().map(|(chunk, _)| chunk).collect(),
+        })
+    }
+
+    /// The number of variables.
+    pub fn len(&self) -> usize {
+        self.num_variables
+    }
+
+    /// Return `true` if there are no variables.
+    pub fn is_empty(&self) -> bool {
+        self.num_variables == 0
+    }
+
+    /// The (big-endian) stream of the vector $\vec z$.
+    pub fn z(&self) -> ZkifAssignment<'_, F> {
+        ZkifAssignment {
+            witness: self,
+            start: 0,
+        }
+    }
+
+    /// The (big-endian) stream of the witness $\vec w$.
+    pub fn witness(&self) -> ZkifAssignment<'_, F> {
+        ZkifAssignment {
+            witness: self,
+            start: self.public_input.len(),
+        }
+    }
+
+    /// The public input $\vec x$, including the constant one.
+    pub fn public_input(&self) -> &[F] {
+        &self.public_input
+    }
+}
+
+/// The (big-endian) stream of the variables of a [`ZkifWitness`] from `start` onwards.
+#[derive(Clone, Copy)]
+pub struct ZkifAssignment<'a, F> {
+    witness: &'a ZkifWitness<F>,
+    start: usize,
+}
+
+/// The iterator returned by [`ZkifAssignment`].
+pub struct ZkifAssignmentIter<'a, F> {
+    witness: &'a ZkifWitness<F>,
+    reader: BufReader<File>,
+    start: usize,
+    /// The first variable that has been read.
+    next: usize,
+    /// The variables of the current message, in reversed order.
+    buffer: Vec<F>,
+}
+
+impl<'a, F: PrimeField> ZkifAssignmentIter<'a, F> {
+    fn read_chunk(&mut self) -> io::Result<()> {
+        let public_input = &self.witness.public_input;
+        if self.next <= public_input.len() {
+            self.buffer
+                .extend_from_slice(&public_input[self.start..self.next]);
+            self.next = self.start;
+            return Ok(());
+        }
+        // the last message assigning variables before `next`.
+        let chunks = &self.witness.chunks;
+        let chunk = &chunks[chunks.partition_point(|chunk| chunk.first < self.next) - 1];
+        let (_, values) = read_assignment::<F>(&read_message(&mut self.reader, chunk)?)?;
+        let first = usize::max(chunk.first, self.start);
+        self.buffer
+            .extend_from_slice(&values[first - chunk.first..self.next - chunk.first]);
+        self.next = first;
+        Ok(())
+    }
+}
+
+impl<'a, F: PrimeField> Iterator for ZkifAssignmentIter<'a, F> {
+    type Item = F;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(e) = self.buffer.pop() {
+                return Some(e);
+            }
+            if self.next == self.start {
+                return None;
+            }
+            self.read_chunk()
+                .expect("failed to read the zkInterface witness");
+        }
+    }
+}
+
+impl<'a, F: PrimeField> Iterable for ZkifAssignment<'a, F> {
+    type Item = F;
+
+    type Iter = ZkifAssignmentIter<'a, F>;
+
+    fn iter(&self) -> Self::Iter {
+        let file = File::open(&self.witness.path).expect("failed to open the zkInterface file");
+        ZkifAssignmentIter {
+            witness: self.witness,
+            reader: BufReader::new(file),
+            start: self.start,
+            next: self.witness.num_variables,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.witness.num_variables - self.start
+    }
+}
+
+#[test]
+fn test_zkinterface_import() {
+    use ark_bls12_381::Fr;
+    use ark_serialize::CanonicalSerialize;
+    use ark_std::test_rng;
+    use std::io::Write;
+
+    use crate::circuit::{generate_relation, matrix_into_rowmaj, random_circuit};
+
+    /// A field of a FlatBuffers table.
+    enum Value {
+        U8(u8),
+        U64(u64),
+        Bytes(Vec<u8>),
+        U64s(Vec<u64>),
+        Table(Vec<Value>),
+        Tables(Vec<Vec<Value>>),
+    }
+
+    fn patch(buf: &mut [u8], slot: usize, target: usize) {
+        buf[slot..slot + 4].copy_from_slice(&((target - slot) as u32).to_le_bytes());
+    }
+
+    /// Write the vtable, the table, and then the children of the table, returning its position.
+    fn table(buf: &mut Vec<u8>, fields: &[Value]) -> usize {
+        let vtable = buf.len();
+        let sizes = fields
+            .iter()
+            .map(|field| match field {
+                Value::U8(_) => 1,
+                Value::U64(_) => 8,
+                _ => 4,
+            })
+            .collect::<Vec<u16>>();
+        buf.extend(((4 + 2 * fields.len()) as u16).to_le_bytes());
+        buf.extend((4 + sizes.iter().sum::<u16>()).to_le_bytes());
+        let mut offset = 4u16;
+        for size in sizes {
+            buf.extend(offset.to_le_bytes());
+            offset += size;
+        }
+        let pos = buf.len();
+        buf.extend(((pos - vtable) as i32).to_le_bytes());
+        let mut slots = Vec::new();
+        for field in fields {
+            match field {
+                Value::U8(value) => buf.push(*value),
+                Value::U64(value) => buf.extend(value.to_le_bytes()),
+                _ => {
+                    slots.push(buf.len());
+                    buf.extend([0u8; 4]);
+                }
+            }
+        }
+        let children = fields
+            .iter()
+            .filter(|field| !matches!(field, Value::U8(_) | Value::U64(_)));
+        for (slot, field) in slots.into_iter().zip(children) {
+            let target = buf.len();
+            match field {
+                Value::Bytes(bytes) => {
+                    buf.extend((bytes.len() as u32).to_le_bytes());
+                    buf.extend(bytes);
+                }
+                Value::U64s(values) => {
+                    buf.extend((values.len() as u32).to_le_bytes());
+                    values
+                        .iter()
+                        .for_each(|value| buf.extend(value.to_le_bytes()));
+                }
+                Value::Table(fields) => {
+                    let child = table(buf, fields);
+                    patch(buf, slot, child);
+                    continue;
+                }
+                Value::Tables(tables) => {
+                    buf.extend((tables.len() as u32).to_le_bytes());
+                    buf.extend(vec![0u8; 4 * tables.len()]);
+                    for (i, fields) in tables.iter().enumerate() {
+                        let child = table(buf, fields);
+                        patch(buf, target + 4 + 4 * i, child);
+                    }
+                }
+                _ => unreachable!(),
+            }
+            patch(buf, slot, target);
+        }
+        pos
+    }
+
+    fn message(message_type: u8, fields: Vec<Value>) -> Vec<u8> {
+        let mut buf = [0u8; 4].to_vec();
+        buf.extend(b"zkif");
+        let root = table(&mut buf, &[Value::U8(message_type), Value::Table(fields)]);
+        patch(&mut buf, 0, root);
+        let mut bytes = (buf.len() as u32).to_le_bytes().to_vec();
+        bytes.extend(buf);
+        bytes
+    }
+
+    let variables = |ids: Vec<usize>, values: &[Fr]| {
+        let mut bytes = Vec::new();
+        values
+            .iter()
+            .for_each(|value| value.serialize_uncompressed(&mut bytes).unwrap());
+        Value::Table(vec![
+            Value::U64s(ids.into_iter().map(|id| id as u64).collect()),
+            Value::Bytes(bytes),
+        ])
+    };
+    let row = |row: &Vec<(Fr, usize)>| {
+        let (values, ids): (Vec<Fr>, Vec<usize>) = row.iter().cloned().unzip();
+        variables(ids, &values)
+    };
+
+    let rng = &mut test_rng();
+    let r1cs = generate_relation::<Fr, _>(random_circuit(rng, 1 << 6, 1 << 6));
+    let num_instance = r1cs.x.len() - 1;
+    let header = |values: &[Fr]| {
+        message(
+            CIRCUIT_HEADER,
+            vec![
+                variables((1..=num_instance).collect(), values),
+                Value::U64(r1cs.z.len() as u64),
+                Value::Bytes((-Fr::from(1u64)).into_bigint().to_bytes_le()),
+            ],
+        )
+    };
+    let constraints = |range: ark_std::ops::Range<usize>| {
+        let constraints = range
+            .map(|i| vec![row(&r1cs.a[i]), row(&r1cs.b[i]), row(&r1cs.c[i])])
+            .collect();
+        message(CONSTRAINT_SYSTEM, vec![Value::Tables(constraints)])
+    };
+    let assignment = |range: ark_std::ops::Range<usize>| {
+        let values = &r1cs.z[range.clone()];
+        message(WITNESS, vec![variables(range.collect(), values)])
+    };
+
+    // the constraints are split across two messages,
+    // and the witness is split across two messages, out of order.
+    let num_constraints = r1cs.a.len();
+    let mid = r1cs.x.len() + r1cs.w.len() / 2;
+    let circuit_path = std::env::temp_dir().join("gemini-test-zkinterface-circuit.zkif");
+    let mut circuit_file = File::create(&circuit_path).unwrap();
+    circuit_file.write_all(&header(&[])).unwrap();
+    circuit_file.write_all(&constraints(0..10)).unwrap();
+    circuit_file
+        .write_all(&constraints(10..num_constraints))
+        .unwrap();
+    let witness_path = std::env::temp_dir().join("gemini-test-zkinterface-witness.zkif");
+    let mut witness_file = File::create(&witness_path).unwrap();
+    witness_file.write_all(&header(&r1cs.x[1..])).unwrap();
+    witness_file
+        .write_all(&assignment(mid..r1cs.z.len()))
+        .unwrap();
+    witness_file
+        .write_all(&assignment(r1cs.x.len()..mid))
+        .unwrap();
+
+    let zkif_r1cs = ZkifR1cs::<Fr>::open(&circuit_path).unwrap();
+    assert_eq!(zkif_r1cs.num_constraints(), num_constraints);
+    assert_eq!(zkif_r1cs.num_variables(), r1cs.z.len());
+    assert_eq!(zkif_r1cs.num_statement(), r1cs.x.len());
+    assert_eq!(
+        zkif_r1cs.a().iter().collect::<Vec<_>>(),
+        matrix_into_rowmaj(&r1cs.a)
+    );
+    assert_eq!(
+        zkif_r1cs.c().iter().collect::<Vec<_>>(),
+        matrix_into_rowmaj(&r1cs.c)
+    );
+
+    let zkif_witness = ZkifWitness::<Fr>::open(&witness_path).unwrap();
+    let z = r1cs.z.iter().rev().cloned().collect::<Vec<_>>();
+    assert_eq!(zkif_witness.z().iter().collect::<Vec<_>>(), z);
+    assert_eq!(
+        zkif_witness.witness().iter().collect::<Vec<_>>(),
+        z[..r1cs.w.len()]
+    );
+    assert_eq!(zkif_witness.public_input(), &r1cs.x[..]);
+
+    // a witness missing some variables is rejected.
+    let mut witness_file = File::create(&witness_path).unwrap();
+    witness_file.write_all(&header(&r1cs.x[1..])).unwrap();
+    witness_file
+        .write_all(&assignment(mid..r1cs.z.len()))
+        .unwrap();
+    assert!(ZkifWitness::<Fr>::open(&witness_path).is_err());
+
+    std::fs::remove_file(circuit_path).unwrap();
+    std::fs::remove_file(witness_path).unwrap();
+}
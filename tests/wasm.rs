@@ -0,0 +1,63 @@
+//! Proving and verification of small instances on `wasm32`.
+//!
+//! Run with:
+//!
+//! ```bash
+//! wasm-pack test --node -- --no-default-features --features std,prover
+//! ```
+#![cfg(target_arch = "wasm32")]
+
+use ark_bls12_381::Bls12_381;
+use ark_gemini::circuit::{generate_relation, random_circuit};
+use ark_gemini::iterable::dummy::{dummy_r1cs_stream, DummyStreamer};
+use ark_gemini::kzg::{CommitterKey, CommitterKeyStream, VerifierKey};
+use ark_gemini::snark::Proof;
+use ark_std::test_rng;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+#[wasm_bindgen_test]
+fn test_wasm_snark_correctness() {
+    let rng = &mut test_rng();
+    let num_constraints = 16;
+    let num_variables = 16;
+
+    let circuit = random_circuit(rng, num_constraints, num_variables);
+    let r1cs = generate_relation(circuit);
+    let ck = CommitterKey::<Bls12_381>::new(num_constraints + num_variables, 5, rng);
+    let vk = VerifierKey::from(&ck);
+
+    let proof = Proof::new_time(&r1cs, &ck).unwrap();
+    assert!(proof.verify(&r1cs, &vk).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_wasm_elastic_snark() {
+    use ark_ec::AffineRepr;
+
+    let rng = &mut test_rng();
+    let instance_size = 1 << 6;
+    let g1 = <Bls12_381 as ark_ec::pairing::Pairing>::G1Affine::generator();
+    let g2 = <Bls12_381 as ark_ec::pairing::Pairing>::G2Affine::generator();
+
+    let r1cs_stream = dummy_r1cs_stream(rng, instance_size);
+    let ck = CommitterKeyStream::<Bls12_381, _> {
+        powers_of_g: DummyStreamer::new(g1, instance_size + 1),
+        powers_of_g2: vec![g2; 4],
+    };
+    // the streaming commitments read the committer key through a pipeline.
+    assert!(Proof::new_elastic(r1cs_stream, ck, 1 << 10).is_ok());
+}
+
+#[wasm_bindgen_test]
+fn test_wasm_space_sumcheck() {
+    use ark_bls12_381::Fr;
+    use ark_gemini::subprotocols::sumcheck::proof::Sumcheck;
+    use merlin::Transcript;
+
+    // long enough for the rounds to read the foldings through a pipeline.
+    let len = 1 << 13;
+    let f = DummyStreamer::new(Fr::from(2u64), len);
+    let g = DummyStreamer::new(Fr::from(3u64), len);
+    let mut transcript = Transcript::new(b"wasm");
+    assert!(Sumcheck::new_space(&mut transcript, f, g, Fr::from(1u64)).is_ok());
+}